@@ -0,0 +1,12 @@
+//! Compiles the control-plane protobufs when the `grpc` feature is on
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/control.proto");
+        // A vendored protoc keeps the build free of system packages
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is available for this platform");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::compile_protos("proto/control.proto").expect("control.proto compiles");
+    }
+}
@@ -0,0 +1,491 @@
+//! Bandwidth Accounting Module
+//!
+//! Counts the bytes each channel reads and writes, in total and over
+//! fixed-length windows, and enforces optional caps on the transport's
+//! throughput. Caps are token buckets in bytes per second, one for the whole
+//! node and one per peer, kept separately for each direction. A transfer
+//! over a cap is not refused: it waits until the buckets have repaid it.
+//! Waiting before the next write slows senders; waiting before the next read
+//! leaves data in the socket, so TCP pushes back on the remote peer.
+//!
+//! Counted bytes are the frames on the wire, Noise overhead included, but not
+//! the handshake that opens a channel.
+//!
+//! Nodes on metered links can also set a `BandwidthBudget`: kilobits per
+//! second up and down for everything the node moves, gossip, discovery and
+//! artifact transfers alike. The budget is shaped by message priority
+//! rather than repaid afterwards. A transfer waits until the budget's bucket
+//! holds its bytes plus the headroom its priority must leave for more urgent
+//! traffic, so low-priority chatter backs off first and never pushes
+//! transaction messages past the cap. `BandwidthStats` reports how much of
+//! the budget is in use and which transfers had to wait for it.
+//!
+//! A transfer held back by the budget can be cancelled while it waits.
+//! Nothing is counted for it then; once a transfer's bytes are counted it
+//! always goes ahead, so frames are never cut short.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::messaging::{MessagePriority, PriorityCounts};
+use crate::ratelimit::TokenBucket;
+
+/// Caps and accounting window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BandwidthConfig {
+    pub window: Duration,                 // Rates are reported over the last full window
+    pub global_limit: Option<f64>,        // Bytes per second per direction for the node; None: unlimited
+    pub peer_limit: Option<f64>,          // Bytes per second per direction for each peer; None: unlimited
+    pub burst: Duration,                  // Traffic at the capped rate a bucket holds
+    pub budget: Option<BandwidthBudget>,  // Node-wide, priority-shaped
+}
+
+impl Default for BandwidthConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(10),
+            global_limit: None,
+            peer_limit: None,
+            burst: Duration::from_secs(1),
+            budget: None,
+        }
+    }
+}
+
+/// Node-wide bandwidth budget for metered links
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandwidthBudget {
+    pub upload_kbps: Option<u32>,         // None: unlimited
+    pub download_kbps: Option<u32>,
+    pub low_share: f64,                   // Share of the budget `Low` traffic may use
+    pub normal_share: f64,                // Share `Normal` traffic may use; higher priorities use all of it
+}
+
+impl BandwidthBudget {
+    pub fn new(upload_kbps: u32, download_kbps: u32) -> Self {
+        Self {
+            upload_kbps: Some(upload_kbps),
+            download_kbps: Some(download_kbps),
+            low_share: 0.5,
+            normal_share: 0.8,
+        }
+    }
+
+    /// Budget for one direction, in bytes per second
+    fn bytes_per_sec(&self, flow: Flow) -> Option<f64> {
+        let kbps = match flow {
+            Flow::Read => self.download_kbps,
+            Flow::Write => self.upload_kbps,
+        };
+        kbps.map(|kbps| kbps as f64 * 1000.0 / 8.0)
+    }
+
+    /// Share of a full bucket that traffic at `priority` must leave untouched
+    fn headroom(&self, priority: MessagePriority) -> f64 {
+        match priority {
+            MessagePriority::Low => 1.0 - self.low_share.clamp(0.0, 1.0),
+            MessagePriority::Normal => 1.0 - self.normal_share.clamp(0.0, 1.0),
+            MessagePriority::High | MessagePriority::Critical => 0.0,
+        }
+    }
+}
+
+/// Direction of a transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    Read,
+    Write,
+}
+
+/// Bytes moved in one direction
+#[derive(Debug, Clone, Copy, Default)]
+struct Counter {
+    total: u64,
+    current: u64,                         // In the window under way
+    previous: u64,                        // In the last full window
+}
+
+impl Counter {
+    fn add(&mut self, bytes: u64) {
+        self.total += bytes;
+        self.current += bytes;
+    }
+
+    /// Close `windows` windows; only the latest one's bytes are kept
+    fn roll(&mut self, windows: u32) {
+        self.previous = if windows == 1 { self.current } else { 0 };
+        self.current = 0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Traffic {
+    read: Counter,
+    written: Counter,
+}
+
+impl Traffic {
+    fn counter(&mut self, flow: Flow) -> &mut Counter {
+        match flow {
+            Flow::Read => &mut self.read,
+            Flow::Write => &mut self.written,
+        }
+    }
+
+    fn roll(&mut self, windows: u32) {
+        self.read.roll(windows);
+        self.written.roll(windows);
+    }
+}
+
+/// One bucket per direction
+#[derive(Debug, Clone)]
+struct Buckets {
+    read: TokenBucket,
+    write: TokenBucket,
+}
+
+impl Buckets {
+    fn new(bytes_per_sec: f64, burst: Duration, now: Instant) -> Self {
+        let capacity = (bytes_per_sec * burst.as_secs_f64()).max(1.0);
+        Self {
+            read: TokenBucket::new(capacity, bytes_per_sec, now),
+            write: TokenBucket::new(capacity, bytes_per_sec, now),
+        }
+    }
+
+    fn bucket(&mut self, flow: Flow) -> &mut TokenBucket {
+        match flow {
+            Flow::Read => &mut self.read,
+            Flow::Write => &mut self.write,
+        }
+    }
+}
+
+/// Bytes one peer moved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerBandwidth {
+    pub node_id: String,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    pub read_rate: f64,                   // Bytes per second over the last full window
+    pub write_rate: f64,
+}
+
+/// Snapshot of the node's traffic
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BandwidthStats {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    pub read_rate: f64,                   // Bytes per second over the last full window
+    pub write_rate: f64,
+    pub throttled: u64,                   // Transfers that waited for a cap
+    pub upload_utilization: Option<f64>,  // Write rate over the upload budget, if there is one
+    pub download_utilization: Option<f64>,
+    pub deferred: PriorityCounts,         // Transfers that waited for the budget, by priority
+    pub peers: Vec<PeerBandwidth>,        // Busiest first
+}
+
+#[derive(Debug)]
+struct State {
+    window_start: Instant,
+    node: Traffic,
+    peers: HashMap<String, Traffic>,
+    global_buckets: Option<Buckets>,
+    peer_buckets: HashMap<String, Buckets>,
+    budget_read: Option<TokenBucket>,
+    budget_write: Option<TokenBucket>,
+    throttled: u64,
+    deferred: PriorityCounts,
+}
+
+/// Byte counters and caps shared by every channel of a node
+#[derive(Debug)]
+pub struct BandwidthMeter {
+    config: BandwidthConfig,
+    state: Mutex<State>,
+}
+
+impl BandwidthMeter {
+    pub fn new(config: BandwidthConfig) -> Self {
+        let now = Instant::now();
+        let budget_bucket = |flow| {
+            let rate = config.budget.as_ref()?.bytes_per_sec(flow)?;
+            Some(TokenBucket::new((rate * config.burst.as_secs_f64()).max(1.0), rate, now))
+        };
+        let (budget_read, budget_write) = (budget_bucket(Flow::Read), budget_bucket(Flow::Write));
+        Self {
+            config,
+            state: Mutex::new(State {
+                window_start: now,
+                node: Traffic::default(),
+                peers: HashMap::new(),
+                global_buckets: None,
+                peer_buckets: HashMap::new(),
+                budget_read,
+                budget_write,
+                throttled: 0,
+                deferred: PriorityCounts::default(),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &BandwidthConfig {
+        &self.config
+    }
+
+    /// Count `bytes` read from `node_id`, waiting first if that goes over a
+    /// cap or the budget allows `priority`
+    pub async fn inbound(&self, node_id: &str, bytes: usize, priority: MessagePriority) {
+        self.transfer(node_id, bytes, Flow::Read, priority, None).await;
+    }
+
+    /// Count `bytes` about to be written to `node_id`, waiting first if that
+    /// goes over a cap or the budget allows `priority`
+    pub async fn outbound(&self, node_id: &str, bytes: usize, priority: MessagePriority) {
+        self.transfer(node_id, bytes, Flow::Write, priority, None).await;
+    }
+
+    /// `outbound` that gives up if `cancel` fires while the budget holds the
+    /// transfer back; false if it did, and nothing was counted
+    pub async fn outbound_cancellable(
+        &self,
+        node_id: &str,
+        bytes: usize,
+        priority: MessagePriority,
+        cancel: &CancellationToken,
+    ) -> bool {
+        self.transfer(node_id, bytes, Flow::Write, priority, Some(cancel)).await
+    }
+
+    /// Drop a disconnected peer's bucket; its byte counts are kept
+    pub fn forget(&self, node_id: &str) {
+        self.state.lock().peer_buckets.remove(node_id);
+    }
+
+    pub fn stats(&self) -> BandwidthStats {
+        let mut state = self.state.lock();
+        self.roll(&mut state, Instant::now());
+        let window = self.config.window.as_secs_f64().max(f64::EPSILON);
+        let mut peers: Vec<PeerBandwidth> = state
+            .peers
+            .iter()
+            .map(|(node_id, traffic)| PeerBandwidth {
+                node_id: node_id.clone(),
+                bytes_read: traffic.read.total,
+                bytes_written: traffic.written.total,
+                read_rate: traffic.read.previous as f64 / window,
+                write_rate: traffic.written.previous as f64 / window,
+            })
+            .collect();
+        peers.sort_by(|a, b| {
+            (b.bytes_read + b.bytes_written)
+                .cmp(&(a.bytes_read + a.bytes_written))
+                .then_with(|| a.node_id.cmp(&b.node_id))
+        });
+        let read_rate = state.node.read.previous as f64 / window;
+        let write_rate = state.node.written.previous as f64 / window;
+        let budget = self.config.budget.as_ref();
+        BandwidthStats {
+            bytes_read: state.node.read.total,
+            bytes_written: state.node.written.total,
+            read_rate,
+            write_rate,
+            throttled: state.throttled,
+            upload_utilization: budget.and_then(|budget| budget.bytes_per_sec(Flow::Write)).map(|limit| write_rate / limit),
+            download_utilization: budget.and_then(|budget| budget.bytes_per_sec(Flow::Read)).map(|limit| read_rate / limit),
+            deferred: state.deferred,
+            peers,
+        }
+    }
+
+    /// Wait for the budget and caps, then count the transfer; false if
+    /// `cancel` fired first
+    async fn transfer(
+        &self,
+        node_id: &str,
+        bytes: usize,
+        flow: Flow,
+        priority: MessagePriority,
+        cancel: Option<&CancellationToken>,
+    ) -> bool {
+        if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+            return false;
+        }
+        let mut wait = self.take_budget(bytes, flow, priority, Instant::now());
+        if !wait.is_zero() {
+            self.state.lock().deferred.record(priority);
+        }
+        // Retake rather than reserve, so urgent traffic arriving meanwhile goes first
+        while !wait.is_zero() {
+            match cancel {
+                Some(cancel) => tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = cancel.cancelled() => return false,
+                },
+                None => tokio::time::sleep(wait).await,
+            }
+            wait = self.take_budget(bytes, flow, priority, Instant::now());
+        }
+        let delay = self.charge(node_id, bytes, flow, Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        true
+    }
+
+    /// Count a transfer and return how long it must wait to stay under the caps
+    fn charge(&self, node_id: &str, bytes: usize, flow: Flow, now: Instant) -> Duration {
+        let mut state = self.state.lock();
+        self.roll(&mut state, now);
+        state.node.counter(flow).add(bytes as u64);
+        state.peers.entry(node_id.to_string()).or_default().counter(flow).add(bytes as u64);
+
+        let burst = self.config.burst;
+        let mut delay = Duration::ZERO;
+        if let Some(limit) = self.config.global_limit {
+            let buckets = state.global_buckets.get_or_insert_with(|| Buckets::new(limit, burst, now));
+            delay = delay.max(buckets.bucket(flow).reserve(bytes as f64, now));
+        }
+        if let Some(limit) = self.config.peer_limit {
+            let buckets = state
+                .peer_buckets
+                .entry(node_id.to_string())
+                .or_insert_with(|| Buckets::new(limit, burst, now));
+            delay = delay.max(buckets.bucket(flow).reserve(bytes as f64, now));
+        }
+        if !delay.is_zero() {
+            state.throttled += 1;
+        }
+        delay
+    }
+
+    /// Take a transfer from the budget, or return how long until `priority`
+    /// may
+    fn take_budget(&self, bytes: usize, flow: Flow, priority: MessagePriority, now: Instant) -> Duration {
+        let Some(budget) = &self.config.budget else {
+            return Duration::ZERO;
+        };
+        let mut state = self.state.lock();
+        let bucket = match flow {
+            Flow::Read => state.budget_read.as_mut(),
+            Flow::Write => state.budget_write.as_mut(),
+        };
+        let Some(bucket) = bucket else {
+            return Duration::ZERO;
+        };
+        let reserve = bucket.capacity() * budget.headroom(priority);
+        bucket.take_above(bytes as f64, reserve, now)
+    }
+
+    /// Close every window that ended by `now`
+    fn roll(&self, state: &mut State, now: Instant) {
+        let window = self.config.window.max(Duration::from_millis(1));
+        let elapsed = now.saturating_duration_since(state.window_start);
+        if elapsed < window {
+            return;
+        }
+        let windows = (elapsed.as_nanos() / window.as_nanos()).min(u32::MAX as u128) as u32;
+        state.window_start += window * windows;
+        state.node.roll(windows);
+        for traffic in state.peers.values_mut() {
+            traffic.roll(windows);
+        }
+    }
+}
+
+impl Default for BandwidthMeter {
+    fn default() -> Self {
+        Self::new(BandwidthConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traffic_is_counted_per_window_and_capped() {
+        let config = BandwidthConfig {
+            window: Duration::from_secs(1),
+            global_limit: Some(10_000.0),
+            peer_limit: Some(1_000.0),
+            burst: Duration::from_secs(1),
+            budget: None,
+        };
+        let meter = BandwidthMeter::new(config);
+        let start = meter.state.lock().window_start;
+
+        // Within each peer's burst, nothing waits
+        assert_eq!(meter.charge("a", 1_000, Flow::Write, start), Duration::ZERO);
+        assert_eq!(meter.charge("b", 400, Flow::Read, start), Duration::ZERO);
+        // Over it, the transfer waits until the peer's bucket has refilled
+        assert_eq!(meter.charge("a", 500, Flow::Write, start), Duration::from_millis(500));
+        // Directions are capped separately
+        assert_eq!(meter.charge("a", 500, Flow::Read, start), Duration::ZERO);
+
+        meter.roll(&mut meter.state.lock(), start + Duration::from_millis(1_500));
+        let stats = meter.stats();
+        assert_eq!((stats.bytes_read, stats.bytes_written, stats.throttled), (900, 1_500, 1));
+        assert_eq!(stats.peers[0].node_id, "a");
+        assert_eq!(stats.peers[0].write_rate, 1_500.0);
+        assert_eq!(stats.peers[1].read_rate, 400.0);
+    }
+
+    #[test]
+    fn test_budget_leaves_headroom_for_urgent_traffic() {
+        let config = BandwidthConfig {
+            budget: Some(BandwidthBudget::new(80, 8)),
+            ..BandwidthConfig::default()
+        };
+        let meter = BandwidthMeter::new(config);
+        let start = meter.state.lock().window_start;
+
+        // 80 kbps up is 10,000 bytes per second. Low traffic may drain half
+        // of the bucket, and then waits for it to refill past that mark.
+        assert_eq!(meter.take_budget(5_000, Flow::Write, MessagePriority::Low, start), Duration::ZERO);
+        assert_eq!(meter.take_budget(1_000, Flow::Write, MessagePriority::Low, start), Duration::from_millis(100));
+        // Normal traffic may go down to a fifth, transaction traffic to empty
+        assert_eq!(meter.take_budget(3_000, Flow::Write, MessagePriority::Normal, start), Duration::ZERO);
+        assert_eq!(meter.take_budget(2_000, Flow::Write, MessagePriority::High, start), Duration::ZERO);
+        assert_eq!(meter.take_budget(1_000, Flow::Write, MessagePriority::High, start), Duration::from_millis(100));
+        // Downloads have their own, smaller budget
+        assert_eq!(meter.take_budget(1_000, Flow::Read, MessagePriority::High, start), Duration::ZERO);
+        assert!(meter.take_budget(1, Flow::Read, MessagePriority::High, start) > Duration::ZERO);
+
+        meter.charge("a", 10_000, Flow::Write, start);
+        meter.roll(&mut meter.state.lock(), start + Duration::from_secs(10));
+        let stats = meter.stats();
+        assert_eq!(stats.upload_utilization, Some(0.1));
+        assert_eq!(stats.download_utilization, Some(0.0));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_transfer_is_not_counted() {
+        let config = BandwidthConfig {
+            budget: Some(BandwidthBudget::new(8, 8)),
+            ..BandwidthConfig::default()
+        };
+        let meter = BandwidthMeter::new(config);
+        let cancel = CancellationToken::new();
+
+        // 8 kbps is 1,000 bytes per second: the first transfer drains the
+        // bucket and the second waits for it
+        assert!(meter.outbound_cancellable("a", 1_000, MessagePriority::High, &cancel).await);
+        let cancel_soon = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        };
+        let (sent, ()) = tokio::join!(meter.outbound_cancellable("a", 1_000, MessagePriority::High, &cancel), cancel_soon);
+        assert!(!sent);
+        assert!(!meter.outbound_cancellable("a", 1, MessagePriority::High, &cancel).await);
+
+        let stats = meter.stats();
+        assert_eq!(stats.bytes_written, 1_000);
+        assert_eq!(stats.deferred.high, 1);
+    }
+}
@@ -0,0 +1,187 @@
+//! Bootstrap Sources
+//!
+//! Where a node finds its first peers. Besides the addresses configured in
+//! `DiscoveryConfig::bootstrap_nodes`, operators can publish seeds in DNS,
+//! as TXT records holding `host:port` entries or as SRV records naming seed
+//! hosts and ports, and ship a peer list file signed with an operator key.
+//!
+//! Sources are tried in the configured order, and a later source is only
+//! used when the earlier ones produced no peers, so a signed list baked into
+//! a release can back up DNS seeds that are unreachable or tampered with.
+//! Every source keeps its own statistics under its `label`.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hickory_resolver::TokioAsyncResolver;
+use serde::{Deserialize, Serialize};
+
+/// A place to find bootstrap nodes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BootstrapSource {
+    Static(Vec<SocketAddr>),
+    DnsTxt(String),                       // Name whose TXT records list `host:port` seeds
+    DnsSrv(String),                       // Name whose SRV records point at seeds
+    PeerList(PathBuf),                    // JSON `SignedPeerList` from a trusted signer
+}
+
+impl BootstrapSource {
+    /// Key for this source's statistics
+    pub fn label(&self) -> String {
+        match self {
+            BootstrapSource::Static(_) => "static".to_string(),
+            BootstrapSource::DnsTxt(name) => format!("dns-txt:{}", name),
+            BootstrapSource::DnsSrv(name) => format!("dns-srv:{}", name),
+            BootstrapSource::PeerList(path) => format!("peer-list:{}", path.display()),
+        }
+    }
+
+    pub fn is_dns(&self) -> bool {
+        matches!(self, BootstrapSource::DnsTxt(_) | BootstrapSource::DnsSrv(_))
+    }
+
+    /// Bootstrap addresses this source currently lists
+    pub async fn resolve(&self, trusted_signers: &[[u8; 32]]) -> Result<Vec<SocketAddr>> {
+        match self {
+            BootstrapSource::Static(addrs) => Ok(addrs.clone()),
+            BootstrapSource::DnsTxt(name) => {
+                let resolver = system_resolver()?;
+                let records = resolver.txt_lookup(name.as_str()).await.with_context(|| format!("TXT lookup of {} failed", name))?;
+                let mut addrs = Vec::new();
+                for entry in records.iter().flat_map(|txt| txt.iter().flat_map(|data| txt_entries(&String::from_utf8_lossy(data)))) {
+                    match resolve_host(&resolver, &entry).await {
+                        Ok(resolved) => addrs.extend(resolved),
+                        Err(e) => tracing::debug!("Skipping seed {} from {}: {}", entry, name, e),
+                    }
+                }
+                Ok(addrs)
+            }
+            BootstrapSource::DnsSrv(name) => {
+                let resolver = system_resolver()?;
+                let records = resolver.srv_lookup(name.as_str()).await.with_context(|| format!("SRV lookup of {} failed", name))?;
+                let mut srvs: Vec<_> = records.iter().collect();
+                srvs.sort_by_key(|srv| (srv.priority(), std::cmp::Reverse(srv.weight())));
+                let mut addrs = Vec::new();
+                for srv in srvs {
+                    let target = srv.target().to_utf8();
+                    match resolver.lookup_ip(target.as_str()).await {
+                        Ok(ips) => addrs.extend(ips.iter().map(|ip| SocketAddr::new(ip, srv.port()))),
+                        Err(e) => tracing::debug!("Skipping seed {} from {}: {}", target, name, e),
+                    }
+                }
+                Ok(addrs)
+            }
+            BootstrapSource::PeerList(path) => {
+                let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+                let list: SignedPeerList = serde_json::from_str(&json).with_context(|| format!("{} is not a signed peer list", path.display()))?;
+                Ok(list.verify(trusted_signers, Utc::now())?.to_vec())
+            }
+        }
+    }
+}
+
+/// Bootstrap addresses published by an operator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerList {
+    pub peers: Vec<SocketAddr>,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Peer list with the operator's signature over it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedPeerList {
+    pub list: PeerList,
+    pub signer: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+impl SignedPeerList {
+    pub fn sign(list: PeerList, key: &SigningKey) -> Result<Self> {
+        let signature = key.sign(&signing_bytes(&list)?).to_bytes().to_vec();
+        Ok(Self { list, signer: key.verifying_key().to_bytes(), signature })
+    }
+
+    /// The listed peers, if a trusted signer signed the list and it has not expired
+    pub fn verify(&self, trusted_signers: &[[u8; 32]], now: DateTime<Utc>) -> Result<&[SocketAddr]> {
+        if !trusted_signers.contains(&self.signer) {
+            bail!("Peer list is signed by an untrusted key");
+        }
+        let key = VerifyingKey::from_bytes(&self.signer).map_err(|_| anyhow!("Invalid key on peer list"))?;
+        let signature = Signature::from_slice(&self.signature).map_err(|_| anyhow!("Malformed signature on peer list"))?;
+        key.verify(&signing_bytes(&self.list)?, &signature).map_err(|_| anyhow!("Bad signature on peer list"))?;
+        if self.list.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            bail!("Peer list expired");
+        }
+        Ok(&self.list.peers)
+    }
+}
+
+fn signing_bytes(list: &PeerList) -> Result<Vec<u8>> {
+    bincode::serialize(list).map_err(|e| anyhow!("Peer list encoding failed: {}", e))
+}
+
+/// How one bootstrap source has fared
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BootstrapSourceStats {
+    pub resolutions: u64,
+    pub failed_resolutions: u64,
+    pub addresses_resolved: u64,
+    pub bootstrap_attempts: u64,
+    pub successful_bootstraps: u64,
+    pub peers_discovered: u64,
+    pub last_error: Option<String>,
+}
+
+fn system_resolver() -> Result<TokioAsyncResolver> {
+    TokioAsyncResolver::tokio_from_system_conf().context("Failed to load the system DNS configuration")
+}
+
+/// Seeds in a TXT record: `host:port` entries separated by commas or spaces
+fn txt_entries(record: &str) -> Vec<String> {
+    record
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+async fn resolve_host(resolver: &TokioAsyncResolver, entry: &str) -> Result<Vec<SocketAddr>> {
+    if let Ok(addr) = entry.parse() {
+        return Ok(vec![addr]);
+    }
+    let (host, port) = entry.rsplit_once(':').ok_or_else(|| anyhow!("missing port"))?;
+    let port: u16 = port.parse().context("invalid port")?;
+    let ips = resolver.lookup_ip(host).await?;
+    Ok(ips.iter().map(|ip| SocketAddr::new(ip, port)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_list_needs_trusted_unexpired_signature() {
+        let operator = SigningKey::from_bytes(&[7; 32]);
+        let list = PeerList {
+            peers: vec!["10.0.0.1:7000".parse().unwrap(), "10.0.0.2:7000".parse().unwrap()],
+            issued_at: Utc::now(),
+            expires_at: Some(Utc::now() + chrono::Duration::days(30)),
+        };
+        let signed = SignedPeerList::sign(list, &operator).unwrap();
+        let trusted = [operator.verifying_key().to_bytes()];
+
+        assert_eq!(signed.verify(&trusted, Utc::now()).unwrap().len(), 2);
+        assert!(signed.verify(&[[9; 32]], Utc::now()).is_err());
+        assert!(signed.verify(&trusted, Utc::now() + chrono::Duration::days(31)).is_err());
+
+        let mut tampered = signed.clone();
+        tampered.list.peers.push("10.6.6.6:7000".parse().unwrap());
+        assert!(tampered.verify(&trusted, Utc::now()).is_err());
+
+        assert_eq!(txt_entries("10.0.0.1:7000, seed.example.com:7000  10.0.0.3:7000"), vec!["10.0.0.1:7000", "seed.example.com:7000", "10.0.0.3:7000"]);
+    }
+}
@@ -0,0 +1,277 @@
+//! Gossip Wire Codec Module
+//!
+//! Encodes gossip messages for the wire. Messages are serialized as JSON or
+//! CBOR and optionally compressed with LZ4 or zstd. Each frame starts with a
+//! two byte header naming its encoding and compression, so a receiver can
+//! decode any frame regardless of what it negotiated:
+//!
+//! ```text
+//! [encoding: u8][compression: u8][body]
+//! ```
+//!
+//! Peers advertise the codecs they support in `CodecCapabilities` and each
+//! side sends with the best codec both understand. Peers that never
+//! advertised get plain JSON, which every node reads.
+
+use std::io::Read;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, anyhow};
+
+use crate::constants::MAX_MESSAGE_SIZE;
+use crate::gossip::GossipMessage;
+
+/// Size of the frame header
+const FRAME_HEADER_SIZE: usize = 2;
+
+/// Default zstd compression level
+const ZSTD_LEVEL: i32 = 3;
+
+/// Serialization format of a frame body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PayloadEncoding {
+    Json,
+    Cbor,
+}
+
+impl PayloadEncoding {
+    fn tag(self) -> u8 {
+        match self {
+            PayloadEncoding::Json => 0,
+            PayloadEncoding::Cbor => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(PayloadEncoding::Json),
+            1 => Ok(PayloadEncoding::Cbor),
+            other => Err(anyhow!("Unknown payload encoding: {}", other)),
+        }
+    }
+}
+
+/// Compression applied to an encoded frame body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Compression {
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl Compression {
+    fn tag(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Lz4 => 1,
+            Compression::Zstd => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Lz4),
+            2 => Ok(Compression::Zstd),
+            other => Err(anyhow!("Unknown compression: {}", other)),
+        }
+    }
+
+    fn compress(self, body: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(body.to_vec()),
+            Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(body)),
+            Compression::Zstd => Ok(zstd::encode_all(body, ZSTD_LEVEL)?),
+        }
+    }
+
+    /// Decompress, refusing output larger than the maximum message size
+    fn decompress(self, body: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(body.to_vec()),
+            Compression::Lz4 => {
+                let size = body.get(..4).map(|len| u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize);
+                if size.is_none_or(|size| size > MAX_MESSAGE_SIZE) {
+                    return Err(anyhow!("LZ4 frame exceeds maximum message size"));
+                }
+                lz4_flex::decompress_size_prepended(body).map_err(|e| anyhow!("Invalid LZ4 frame: {}", e))
+            }
+            Compression::Zstd => {
+                let mut decoded = Vec::new();
+                zstd::stream::read::Decoder::new(body)?
+                    .take(MAX_MESSAGE_SIZE as u64 + 1)
+                    .read_to_end(&mut decoded)?;
+                if decoded.len() > MAX_MESSAGE_SIZE {
+                    return Err(anyhow!("zstd frame exceeds maximum message size"));
+                }
+                Ok(decoded)
+            }
+        }
+    }
+}
+
+/// Codecs a node can read and write, in order of preference
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodecCapabilities {
+    pub encodings: Vec<PayloadEncoding>,
+    pub compressions: Vec<Compression>,
+}
+
+impl CodecCapabilities {
+    /// Capabilities of a node that only speaks plain JSON
+    pub fn json_only() -> Self {
+        Self {
+            encodings: vec![PayloadEncoding::Json],
+            compressions: vec![Compression::None],
+        }
+    }
+
+    /// Best codec both sides support, by our order of preference
+    pub fn negotiate(&self, remote: &CodecCapabilities, compression_threshold: usize) -> WireCodec {
+        let encoding = self
+            .encodings
+            .iter()
+            .copied()
+            .find(|encoding| remote.encodings.contains(encoding))
+            .unwrap_or(PayloadEncoding::Json);
+        let compression = self
+            .compressions
+            .iter()
+            .copied()
+            .find(|compression| remote.compressions.contains(compression))
+            .unwrap_or(Compression::None);
+
+        WireCodec { encoding, compression, compression_threshold }
+    }
+}
+
+impl Default for CodecCapabilities {
+    fn default() -> Self {
+        Self {
+            encodings: vec![PayloadEncoding::Cbor, PayloadEncoding::Json],
+            compressions: vec![Compression::Zstd, Compression::Lz4, Compression::None],
+        }
+    }
+}
+
+/// Encoded frame with its size before compression
+#[derive(Debug, Clone)]
+pub struct EncodedFrame {
+    pub bytes: Vec<u8>,
+    pub uncompressed_len: usize,          // Header plus encoded body before compression
+}
+
+/// Codec used to send to one peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireCodec {
+    pub encoding: PayloadEncoding,
+    pub compression: Compression,
+    pub compression_threshold: usize,     // Bodies smaller than this are sent uncompressed
+}
+
+impl Default for WireCodec {
+    fn default() -> Self {
+        Self {
+            encoding: PayloadEncoding::Json,
+            compression: Compression::None,
+            compression_threshold: 0,
+        }
+    }
+}
+
+impl WireCodec {
+    /// Encode a message into a frame
+    pub fn encode(&self, message: &GossipMessage) -> Result<EncodedFrame> {
+        let body = match self.encoding {
+            PayloadEncoding::Json => serde_json::to_vec(message)?,
+            PayloadEncoding::Cbor => {
+                let mut body = Vec::new();
+                ciborium::ser::into_writer(message, &mut body).map_err(|e| anyhow!("CBOR encoding failed: {}", e))?;
+                body
+            }
+        };
+
+        // Small bodies rarely shrink enough to pay for the work
+        let compression = if body.len() < self.compression_threshold { Compression::None } else { self.compression };
+        let compressed = compression.compress(&body)?;
+
+        let mut bytes = Vec::with_capacity(FRAME_HEADER_SIZE + compressed.len());
+        bytes.push(self.encoding.tag());
+        bytes.push(compression.tag());
+        bytes.extend_from_slice(&compressed);
+
+        Ok(EncodedFrame { bytes, uncompressed_len: FRAME_HEADER_SIZE + body.len() })
+    }
+
+    /// Decode a frame written with any codec
+    pub fn decode(frame: &[u8]) -> Result<(GossipMessage, usize)> {
+        if frame.len() < FRAME_HEADER_SIZE {
+            return Err(anyhow!("Truncated gossip frame"));
+        }
+        let encoding = PayloadEncoding::from_tag(frame[0])?;
+        let compression = Compression::from_tag(frame[1])?;
+        let body = compression.decompress(&frame[FRAME_HEADER_SIZE..])?;
+
+        let message = match encoding {
+            PayloadEncoding::Json => serde_json::from_slice(&body)?,
+            PayloadEncoding::Cbor => {
+                ciborium::de::from_reader(body.as_slice()).map_err(|e| anyhow!("Invalid CBOR frame: {}", e))?
+            }
+        };
+        Ok((message, FRAME_HEADER_SIZE + body.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gossip::GossipMessageType;
+
+    fn message() -> GossipMessage {
+        let entries: Vec<_> = (0..50).map(|i| serde_json::json!({"peer": format!("peer{}", i), "score": 0.5})).collect();
+        GossipMessage::new(
+            GossipMessageType::ReputationUpdate,
+            "node".to_string(),
+            serde_json::json!({"entries": entries, "epoch": 7}),
+            10,
+        )
+    }
+
+    #[test]
+    fn test_frames_round_trip_with_every_codec() {
+        let message = message();
+
+        for encoding in [PayloadEncoding::Json, PayloadEncoding::Cbor] {
+            for compression in [Compression::None, Compression::Lz4, Compression::Zstd] {
+                let codec = WireCodec { encoding, compression, compression_threshold: 0 };
+                let frame = codec.encode(&message).unwrap();
+                let (decoded, uncompressed_len) = WireCodec::decode(&frame.bytes).unwrap();
+                assert_eq!(decoded.id, message.id);
+                assert_eq!(decoded.payload, message.payload);
+                assert_eq!(uncompressed_len, frame.uncompressed_len);
+                if compression != Compression::None {
+                    assert!(frame.bytes.len() < frame.uncompressed_len, "{:?}/{:?}", encoding, compression);
+                }
+            }
+        }
+
+        assert!(WireCodec::decode(&[9, 0, 1]).is_err());
+        assert!(WireCodec::decode(&[0, 2, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_negotiation_picks_the_best_shared_codec() {
+        let local = CodecCapabilities::default();
+        let lz4_peer = CodecCapabilities {
+            encodings: vec![PayloadEncoding::Json, PayloadEncoding::Cbor],
+            compressions: vec![Compression::Lz4, Compression::None],
+        };
+
+        let codec = local.negotiate(&lz4_peer, 256);
+        assert_eq!((codec.encoding, codec.compression), (PayloadEncoding::Cbor, Compression::Lz4));
+        assert_eq!(local.negotiate(&CodecCapabilities::json_only(), 256), WireCodec { compression_threshold: 256, ..WireCodec::default() });
+
+        // Below the threshold the frame goes out uncompressed
+        let small = GossipMessage::new(GossipMessageType::HeartBeat, "node".to_string(), serde_json::json!({}), 5);
+        assert_eq!(codec.encode(&small).unwrap().bytes[1], Compression::None.tag());
+    }
+}
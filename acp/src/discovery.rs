@@ -2,17 +2,63 @@
 //!
 //! Handles automatic discovery and management of peers in the Solace Protocol network.
 //! Implements various discovery mechanisms including DHT, gossip, and bootstrap nodes.
+//!
+//! The DHT is Kademlia. Node ids are hashed with SHA-256 onto a 256-bit
+//! keyspace, and the routing table keeps up to `k` peers per bucket of XOR
+//! distance. Lookups are iterative: the `alpha` closest peers not yet asked
+//! are sent `FIND_NODE` in parallel over the P2P transport, their answers
+//! join the shortlist, and the lookup ends once the `k` closest peers have
+//! all answered or failed. Full buckets keep the newest peer they turned
+//! away as a replacement, which takes the place of any entry that fails to
+//! answer; buckets left idle longer than `bucket_refresh` are refreshed with
+//! a lookup of a random key in their range.
+//!
+//! With the `mdns` feature, agents on the same LAN find each other without
+//! any bootstrap node: each advertises itself as a DNS-SD service of type
+//! `_solace-acp._tcp.local.`, carrying its id, key, capabilities and node
+//! type in TXT records, and browses for everyone else's advertisements.
+//!
+//! Every peer seen keeps a scored `PeerRecord`. Records restored from a
+//! `PeerStore` with `with_stored_peers` are dialed best score first when the
+//! service starts, ahead of the bootstrap sources.
+//!
+//! With `with_peer_scorer`, peers the `PeerScorer` bans are blacklisted at
+//! each discovery round, and blacklisting a peer by hand bans it in the
+//! scorer, so the blacklist persists with the scorer's state.
+//!
+//! A peer's advertised reputation is only a starting point. With
+//! `with_reputation_source`, the reputations of connected peers are
+//! re-queried every `reputation_refresh.interval` from the reputation system
+//! or an on-chain oracle. A peer is disconnected only after its refreshed
+//! reputation stays below the threshold, less a hysteresis margin, for
+//! several refreshes in a row, so a score hovering near the threshold does
+//! not flap; refreshed scores also override the stale ones peers advertise
+//! when they are rediscovered.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
+use futures::future::{join_all, BoxFuture};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use tokio::sync::{broadcast, oneshot};
 use tokio::time::interval;
 use tracing::{info, warn, debug, error};
 
+use crate::bootstrap::{BootstrapSource, BootstrapSourceStats};
+use crate::events::{ACPEvent, EventBus};
+use crate::messaging::{ACPMessage, MessageType};
+use crate::misbehavior::Violation;
+use crate::p2p::{InboundMessage, P2PNetwork};
+use crate::rng::NodeRng;
+use crate::peer_store::{PeerRecord, PeerScoreWeights};
+use crate::security::PeerScorer;
+
 /// Peer information structure
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PeerInfo {
     pub id: String,
     pub address: SocketAddr,
@@ -22,6 +68,8 @@ pub struct PeerInfo {
     pub last_seen: chrono::DateTime<chrono::Utc>,
     pub protocol_version: String,
     pub node_type: NodeType,
+    #[serde(default)]
+    pub rtt_ms: Option<f64>,              // Smoothed heartbeat round trip, once measured
 }
 
 /// Types of nodes in the network
@@ -35,7 +83,7 @@ pub enum NodeType {
 }
 
 /// Discovery method enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DiscoveryMethod {
     Bootstrap,
     DHT,
@@ -43,6 +91,7 @@ pub enum DiscoveryMethod {
     Manual,
     MDNS,
     DNS,
+    PeerStore,
 }
 
 /// Discovery configuration
@@ -56,15 +105,57 @@ pub struct DiscoveryConfig {
     pub enable_gossip: bool,
     pub enable_mdns: bool,
     pub reputation_threshold: f64,
+    pub kademlia: KademliaConfig,
+    pub bootstrap_sources: Vec<BootstrapSource>,  // Fallbacks, in order, after `bootstrap_nodes`
+    pub trusted_list_signers: Vec<[u8; 32]>,      // Keys accepted on signed peer lists
+    pub stored_peer_dials: usize,                 // Stored peers dialed on startup, best first
+    pub peer_scoring: PeerScoreWeights,
+    pub reputation_refresh: ReputationRefreshConfig,
+}
+
+/// How connected peers' reputations are refreshed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReputationRefreshConfig {
+    pub interval: Duration,               // Checked each discovery round
+    pub hysteresis: f64,                  // Margin below `reputation_threshold` that counts as low
+    pub strikes: u32,                     // Consecutive low refreshes before disconnecting
+}
+
+impl Default for ReputationRefreshConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            hysteresis: 0.05,
+            strikes: 2,
+        }
+    }
+}
+
+/// Current reputations of peers, from the reputation system or an on-chain oracle
+pub trait ReputationSource: Send + Sync {
+    /// Reputations of those of `peer_ids` the source has a score for
+    fn reputations<'a>(&'a self, peer_ids: &'a [String]) -> BoxFuture<'a, Result<HashMap<String, f64>>>;
+}
+
+/// Outcome of one reputation refresh
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReputationRefresh {
+    pub refreshed: usize,                 // Connected peers the source had a score for
+    pub disconnected: Vec<String>,
+}
+
+/// Last refreshed reputation of a peer
+#[derive(Debug, Clone, Copy)]
+struct RefreshedReputation {
+    reputation: f64,
+    strikes: u32,                         // Consecutive refreshes below the low mark
 }
 
 impl Default for DiscoveryConfig {
     fn default() -> Self {
         Self {
-            bootstrap_nodes: vec![
-                "bootstrap1.solace.network:8080".parse().unwrap(),
-                "bootstrap2.solace.network:8080".parse().unwrap(),
-            ],
+            // Host names do not parse as socket addresses; bootstrap nodes are configured explicitly
+            bootstrap_nodes: Vec::new(),
             max_peers: 50,
             discovery_interval: Duration::from_secs(30),
             peer_timeout: Duration::from_secs(300),
@@ -72,6 +163,12 @@ impl Default for DiscoveryConfig {
             enable_gossip: true,
             enable_mdns: false,
             reputation_threshold: 0.3,
+            kademlia: KademliaConfig::default(),
+            bootstrap_sources: Vec::new(),
+            trusted_list_signers: Vec::new(),
+            stored_peer_dials: 8,
+            peer_scoring: PeerScoreWeights::default(),
+            reputation_refresh: ReputationRefreshConfig::default(),
         }
     }
 }
@@ -86,17 +183,450 @@ pub struct DiscoveryStats {
     pub gossip_messages: u64,
     pub failed_connections: u64,
     pub peer_disconnections: u64,
+    pub bootstrap_sources: BTreeMap<String, BootstrapSourceStats>,  // By source label
+    pub stored_peer_dials: u64,
+    pub stored_peer_connections: u64,
+    pub reputation_refreshes: u64,
+    pub reputation_disconnections: u64,  // Peers dropped for a refreshed reputation below the threshold
+}
+
+/// Kademlia settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KademliaConfig {
+    pub k: usize,                         // Bucket size and number of peers a lookup returns
+    pub alpha: usize,                     // Parallel FIND_NODE requests per lookup round
+    pub request_timeout: Duration,
+    pub bucket_refresh: Duration,         // Idle time after which a bucket is refreshed
+    pub rng_seed: Option<u64>,            // Base seed for refresh keys (None = SOLACE_SEED or random)
+}
+
+impl Default for KademliaConfig {
+    fn default() -> Self {
+        Self {
+            k: 20,
+            alpha: 3,
+            request_timeout: Duration::from_secs(5),
+            bucket_refresh: Duration::from_secs(3600),
+            rng_seed: None,
+        }
+    }
+}
+
+/// Position in the Kademlia keyspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeKey(pub [u8; 32]);
+
+impl NodeKey {
+    /// Key of a node: the SHA-256 of its id
+    pub fn for_node(node_id: &str) -> Self {
+        Self(Sha256::digest(node_id.as_bytes()).into())
+    }
+
+    /// XOR distance to another key
+    pub fn distance(&self, other: &NodeKey) -> NodeKey {
+        let mut distance = [0u8; 32];
+        for (i, byte) in distance.iter_mut().enumerate() {
+            *byte = self.0[i] ^ other.0[i];
+        }
+        NodeKey(distance)
+    }
+
+    /// Bucket `other` falls in as seen from this key: the index of the
+    /// highest differing bit, or `None` if the keys are equal
+    pub fn bucket_index(&self, other: &NodeKey) -> Option<usize> {
+        let distance = self.distance(other);
+        let (i, byte) = distance.0.iter().enumerate().find(|(_, byte)| **byte != 0)?;
+        Some(255 - (i * 8 + byte.leading_zeros() as usize))
+    }
+
+    /// Random key in bucket `index` as seen from this key, drawn from `rng`
+    pub fn random_in_bucket(&self, index: usize, rng: &NodeRng) -> NodeKey {
+        let mut key: [u8; 32] = rng.with(|rng| rng.gen());
+        // Bits above the bucket's match this key and the bucket's own bit differs
+        let differing = 255 - index.min(255);
+        for position in 0..=differing {
+            let (byte, mask) = (position / 8, 0x80u8 >> (position % 8));
+            let bit = if position == differing { !self.0[byte] & mask } else { self.0[byte] & mask };
+            key[byte] = (key[byte] & !mask) | bit;
+        }
+        NodeKey(key)
+    }
+}
+
+/// Peers at one range of distance from the local key
+#[derive(Debug, Clone)]
+struct KBucket {
+    peers: VecDeque<PeerInfo>,            // Least recently seen first
+    replacement: Option<PeerInfo>,        // Newest peer turned away while full
+    last_touched: Instant,
+}
+
+/// Kademlia routing table: 256 buckets of up to `k` peers by XOR distance
+pub struct RoutingTable {
+    local: NodeKey,
+    k: usize,
+    buckets: Vec<KBucket>,
 }
 
-/// Discovery event types
+impl RoutingTable {
+    pub fn new(local: NodeKey, k: usize, now: Instant) -> Self {
+        let bucket = KBucket {
+            peers: VecDeque::new(),
+            replacement: None,
+            last_touched: now,
+        };
+        Self {
+            local,
+            k: k.max(1),
+            buckets: vec![bucket; 256],
+        }
+    }
+
+    pub fn local_key(&self) -> NodeKey {
+        self.local
+    }
+
+    /// Record that a peer was seen, moving it to the back of its bucket.
+    /// A full bucket keeps its peers and holds the newcomer as replacement.
+    pub fn update(&mut self, peer: PeerInfo, now: Instant) -> bool {
+        let Some(index) = self.local.bucket_index(&NodeKey::for_node(&peer.id)) else {
+            return false;
+        };
+        let bucket = &mut self.buckets[index];
+        bucket.last_touched = now;
+        if let Some(position) = bucket.peers.iter().position(|known| known.id == peer.id) {
+            bucket.peers.remove(position);
+        } else if bucket.peers.len() >= self.k {
+            bucket.replacement = Some(peer);
+            return false;
+        }
+        bucket.peers.push_back(peer);
+        true
+    }
+
+    /// Drop a peer, letting its bucket's replacement take its place
+    pub fn remove(&mut self, peer_id: &str) -> bool {
+        let Some(index) = self.local.bucket_index(&NodeKey::for_node(peer_id)) else {
+            return false;
+        };
+        let bucket = &mut self.buckets[index];
+        let Some(position) = bucket.peers.iter().position(|known| known.id == peer_id) else {
+            return false;
+        };
+        bucket.peers.remove(position);
+        if let Some(replacement) = bucket.replacement.take() {
+            bucket.peers.push_back(replacement);
+        }
+        true
+    }
+
+    pub fn get(&self, peer_id: &str) -> Option<&PeerInfo> {
+        let index = self.local.bucket_index(&NodeKey::for_node(peer_id))?;
+        self.buckets[index].peers.iter().find(|peer| peer.id == peer_id)
+    }
+
+    /// Up to `count` known peers closest to `target`, nearest first
+    pub fn closest(&self, target: &NodeKey, count: usize) -> Vec<PeerInfo> {
+        let mut peers: Vec<(NodeKey, &PeerInfo)> = self
+            .buckets
+            .iter()
+            .flat_map(|bucket| bucket.peers.iter())
+            .map(|peer| (NodeKey::for_node(&peer.id).distance(target), peer))
+            .collect();
+        peers.sort_by_key(|(distance, _)| *distance);
+        peers.into_iter().take(count).map(|(_, peer)| peer.clone()).collect()
+    }
+
+    /// Mark the bucket covering `target` as recently used
+    pub fn touch(&mut self, target: &NodeKey, now: Instant) {
+        if let Some(index) = self.local.bucket_index(target) {
+            self.buckets[index].last_touched = now;
+        }
+    }
+
+    /// Non-empty buckets untouched for at least `refresh`
+    pub fn stale_buckets(&self, refresh: Duration, now: Instant) -> Vec<usize> {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, bucket)| !bucket.peers.is_empty() && now.saturating_duration_since(bucket.last_touched) >= refresh)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn peers(&self) -> Vec<PeerInfo> {
+        self.buckets.iter().flat_map(|bucket| bucket.peers.iter().cloned()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.peers.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Kademlia RPCs, carried as the payload of `MessageType::PeerDiscovery` messages
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum DiscoveryEvent {
-    PeerDiscovered(PeerInfo),
-    PeerConnected(String),
-    PeerDisconnected(String),
-    PeerTimeout(String),
-    BootstrapCompleted,
-    DiscoveryFailed(String),
+enum DhtMessage {
+    FindNode {
+        request_id: String,
+        sender: PeerInfo,
+        target: NodeKey,
+    },
+    Nodes {
+        request_id: String,
+        sender: PeerInfo,
+        peers: Vec<PeerInfo>,
+    },
+}
+
+impl DhtMessage {
+    fn sender(&self) -> &PeerInfo {
+        match self {
+            DhtMessage::FindNode { sender, .. } | DhtMessage::Nodes { sender, .. } => sender,
+        }
+    }
+}
+
+/// FIND_NODE awaiting its answer
+struct PendingRequest {
+    peer_id: Option<String>,              // Expected responder; unknown when bootstrapping by address
+    reply: oneshot::Sender<(PeerInfo, Vec<PeerInfo>)>,
+}
+
+/// Kademlia DHT over the P2P transport
+///
+/// Messages from `P2PNetwork::incoming()` must be passed to `handle_message`
+/// for this node to answer lookups and receive answers to its own.
+pub struct KademliaDht {
+    local: PeerInfo,
+    config: KademliaConfig,
+    network: Arc<P2PNetwork>,
+    table: parking_lot::Mutex<RoutingTable>,
+    pending: parking_lot::Mutex<HashMap<String, PendingRequest>>,
+    rng: NodeRng,
+}
+
+impl KademliaDht {
+    /// DHT node for `local`, whose id must be the network's node id
+    pub fn new(local: PeerInfo, network: Arc<P2PNetwork>, config: KademliaConfig) -> Self {
+        let table = RoutingTable::new(NodeKey::for_node(&local.id), config.k, Instant::now());
+        let rng = NodeRng::for_node(config.rng_seed, &local.id);
+        Self {
+            local,
+            config,
+            network,
+            table: parking_lot::Mutex::new(table),
+            pending: parking_lot::Mutex::new(HashMap::new()),
+            rng,
+        }
+    }
+
+    pub fn local(&self) -> &PeerInfo {
+        &self.local
+    }
+
+    pub fn local_key(&self) -> NodeKey {
+        self.table.lock().local_key()
+    }
+
+    /// Known peers in the routing table
+    pub fn peers(&self) -> Vec<PeerInfo> {
+        self.table.lock().peers()
+    }
+
+    /// Add a peer to the routing table and make it dialable
+    pub fn add_peer(&self, peer: PeerInfo) {
+        if peer.id == self.local.id {
+            return;
+        }
+        self.network.add_peer(peer.id.clone(), peer.address);
+        self.table.lock().update(peer, Instant::now());
+    }
+
+    /// Join through the node at `addr` and look up our own key, returning
+    /// the closest peers found
+    pub async fn bootstrap(&self, addr: SocketAddr) -> Result<Vec<PeerInfo>> {
+        let (sender, _) = self.request(&addr.to_string(), None, &self.local_key()).await?;
+        self.add_peer(sender);
+        self.lookup(&self.local_key()).await
+    }
+
+    /// Iterative lookup of the `k` peers closest to `target`
+    pub async fn lookup(&self, target: &NodeKey) -> Result<Vec<PeerInfo>> {
+        let mut shortlist: BTreeMap<NodeKey, PeerInfo> = {
+            let mut table = self.table.lock();
+            table.touch(target, Instant::now());
+            table
+                .closest(target, self.config.k)
+                .into_iter()
+                .map(|peer| (NodeKey::for_node(&peer.id).distance(target), peer))
+                .collect()
+        };
+        if shortlist.is_empty() {
+            return Err(anyhow!("Routing table is empty"));
+        }
+
+        let mut queried = HashSet::new();
+        loop {
+            let round: Vec<PeerInfo> = shortlist
+                .values()
+                .take(self.config.k)
+                .filter(|peer| !queried.contains(&peer.id))
+                .take(self.config.alpha.max(1))
+                .cloned()
+                .collect();
+            if round.is_empty() {
+                break;
+            }
+
+            let answers = join_all(round.iter().map(|peer| self.find_node(peer, target))).await;
+            for (peer, answer) in round.into_iter().zip(answers) {
+                queried.insert(peer.id.clone());
+                match answer {
+                    Ok(found) => {
+                        for candidate in found.into_iter().filter(|candidate| candidate.id != self.local.id) {
+                            let distance = NodeKey::for_node(&candidate.id).distance(target);
+                            shortlist.entry(distance).or_insert(candidate);
+                        }
+                    }
+                    Err(e) => {
+                        debug!("FIND_NODE to {} failed: {}", peer.id, e);
+                        shortlist.remove(&NodeKey::for_node(&peer.id).distance(target));
+                    }
+                }
+            }
+        }
+
+        Ok(shortlist.into_values().take(self.config.k).collect())
+    }
+
+    /// Look up a random key in each bucket idle for longer than
+    /// `bucket_refresh`, returning how many buckets were refreshed
+    pub async fn refresh(&self) -> usize {
+        let stale = self.table.lock().stale_buckets(self.config.bucket_refresh, Instant::now());
+        let local = self.local_key();
+        for index in &stale {
+            if let Err(e) = self.lookup(&local.random_in_bucket(*index, &self.rng)).await {
+                debug!("Refresh of bucket {} failed: {}", index, e);
+            }
+        }
+        stale.len()
+    }
+
+    /// Answer or complete a DHT request, returning false for messages that
+    /// are not DHT traffic
+    pub async fn handle_message(&self, inbound: &InboundMessage) -> bool {
+        if inbound.message.message_type != MessageType::PeerDiscovery {
+            return false;
+        }
+        let message: DhtMessage = match serde_json::from_slice(&inbound.message.payload) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Malformed DHT message from {}: {}", inbound.peer.node_id, e);
+                self.network.misbehavior().record(&inbound.peer.node_id, Violation::MalformedMessage, Instant::now());
+                return true;
+            }
+        };
+        // Peers may only describe themselves under the identity they authenticated as
+        if message.sender().id != inbound.peer.node_id {
+            warn!("{} sent a DHT record claiming to be {}", inbound.peer.node_id, message.sender().id);
+            self.network.misbehavior().record(&inbound.peer.node_id, Violation::FakePeerRecord, Instant::now());
+            return true;
+        }
+
+        match message {
+            DhtMessage::FindNode { request_id, sender, target } => {
+                let requester = sender.id.clone();
+                self.add_peer(sender);
+                let peers = self
+                    .table
+                    .lock()
+                    .closest(&target, self.config.k + 1)
+                    .into_iter()
+                    .filter(|peer| peer.id != requester)
+                    .take(self.config.k)
+                    .collect();
+                let reply = DhtMessage::Nodes {
+                    request_id,
+                    sender: self.local.clone(),
+                    peers,
+                };
+                if let Err(e) = self.send(&requester, &reply).await {
+                    debug!("Could not answer FIND_NODE from {}: {}", requester, e);
+                }
+            }
+            DhtMessage::Nodes { request_id, sender, peers } => {
+                let mut pending = self.pending.lock();
+                let expected = pending.get(&request_id).map(|request| request.peer_id.as_deref());
+                match expected {
+                    Some(Some(peer_id)) if peer_id != sender.id => {
+                        warn!("Ignoring answer to a request for {} from {}", peer_id, sender.id);
+                    }
+                    Some(_) => {
+                        if let Some(request) = pending.remove(&request_id) {
+                            let _ = request.reply.send((sender, peers));
+                        }
+                    }
+                    None => debug!("Ignoring unsolicited DHT answer from {}", sender.id),
+                }
+            }
+        }
+        true
+    }
+
+    /// FIND_NODE to a known peer, keeping the routing table in step with
+    /// whether it answered
+    async fn find_node(&self, peer: &PeerInfo, target: &NodeKey) -> Result<Vec<PeerInfo>> {
+        self.network.add_peer(peer.id.clone(), peer.address);
+        match self.request(&peer.id, Some(&peer.id), target).await {
+            Ok((sender, peers)) => {
+                self.table.lock().update(sender, Instant::now());
+                Ok(peers)
+            }
+            Err(e) => {
+                self.table.lock().remove(&peer.id);
+                Err(e)
+            }
+        }
+    }
+
+    async fn request(&self, peer_id: &str, expected: Option<&str>, target: &NodeKey) -> Result<(PeerInfo, Vec<PeerInfo>)> {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let (reply, answer) = oneshot::channel();
+        self.pending.lock().insert(
+            request_id.clone(),
+            PendingRequest {
+                peer_id: expected.map(str::to_string),
+                reply,
+            },
+        );
+
+        let message = DhtMessage::FindNode {
+            request_id: request_id.clone(),
+            sender: self.local.clone(),
+            target: *target,
+        };
+        let result = async {
+            self.send(peer_id, &message).await?;
+            tokio::time::timeout(self.config.request_timeout, answer)
+                .await
+                .map_err(|_| anyhow!("FIND_NODE to {} timed out", peer_id))?
+                .map_err(|_| anyhow!("FIND_NODE to {} was abandoned", peer_id))
+        }
+        .await;
+        self.pending.lock().remove(&request_id);
+        result
+    }
+
+    async fn send(&self, peer_id: &str, message: &DhtMessage) -> Result<()> {
+        let payload = serde_json::to_vec(message)?;
+        let message = ACPMessage::new(MessageType::PeerDiscovery, self.local.id.clone(), Some(peer_id.to_string()), payload);
+        self.network.send_message(peer_id, &message).await?;
+        Ok(())
+    }
 }
 
 /// Peer discovery service
@@ -107,7 +637,15 @@ pub struct PeerDiscovery {
     blacklisted_peers: HashSet<String>,
     stats: DiscoveryStats,
     last_discovery: Instant,
-    event_callbacks: Vec<Box<dyn Fn(DiscoveryEvent) + Send + Sync>>,
+    events: EventBus,
+    dht: Option<Arc<KademliaDht>>,
+    peer_records: HashMap<String, PeerRecord>,
+    scorer: Option<Arc<PeerScorer>>,
+    reputation_source: Option<Arc<dyn ReputationSource>>,
+    refreshed: HashMap<String, RefreshedReputation>,
+    last_reputation_refresh: Instant,
+    #[cfg(feature = "mdns")]
+    mdns: Option<mdns::MdnsService>,
 }
 
 impl PeerDiscovery {
@@ -120,15 +658,76 @@ impl PeerDiscovery {
             blacklisted_peers: HashSet::new(),
             stats: DiscoveryStats::default(),
             last_discovery: Instant::now(),
-            event_callbacks: Vec::new(),
+            events: EventBus::default(),
+            dht: None,
+            peer_records: HashMap::new(),
+            scorer: None,
+            reputation_source: None,
+            refreshed: HashMap::new(),
+            last_reputation_refresh: Instant::now(),
+            #[cfg(feature = "mdns")]
+            mdns: None,
         }
     }
 
+    /// Publish discovery events on the node's bus
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Discover peers through a Kademlia DHT
+    pub fn with_dht(mut self, dht: Arc<KademliaDht>) -> Self {
+        self.dht = Some(dht);
+        self
+    }
+
+    /// Resume from records saved by a `PeerStore`
+    pub fn with_stored_peers(mut self, records: Vec<PeerRecord>) -> Self {
+        self.peer_records.extend(records.into_iter().map(|record| (record.info.id.clone(), record)));
+        self
+    }
+
+    /// Blacklist the peers `scorer` bans, starting with those already banned
+    pub fn with_peer_scorer(mut self, scorer: Arc<PeerScorer>) -> Self {
+        for peer_id in scorer.banned() {
+            self.blacklist(&peer_id);
+        }
+        scorer.take_new_bans();
+        self.scorer = Some(scorer);
+        self
+    }
+
+    /// Refresh connected peers' reputations from `source`
+    pub fn with_reputation_source(mut self, source: Arc<dyn ReputationSource>) -> Self {
+        self.reputation_source = Some(source);
+        self
+    }
+
+    /// Advertise `local` on the LAN over mDNS and browse for other agents
+    #[cfg(feature = "mdns")]
+    pub fn with_mdns(mut self, local: &PeerInfo) -> Result<Self> {
+        self.mdns = Some(mdns::MdnsService::start(local)?);
+        Ok(self)
+    }
+
+    /// The `k` peers closest to `node_id`, found by an iterative DHT lookup
+    pub async fn lookup(&mut self, node_id: &str) -> Result<Vec<PeerInfo>> {
+        let dht = self.dht.clone().ok_or_else(|| anyhow!("DHT discovery is not enabled"))?;
+        self.stats.dht_queries += 1;
+        let peers = dht.lookup(&NodeKey::for_node(node_id)).await?;
+        for peer in &peers {
+            self.add_peer(peer.clone(), DiscoveryMethod::DHT).await;
+        }
+        Ok(peers)
+    }
+
     /// Start the discovery service
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting peer discovery service");
         
-        // Bootstrap from known nodes
+        // Reconnect to the best peers we already know, then bootstrap
+        self.dial_stored_peers().await;
         self.bootstrap().await?;
         
         // Start periodic discovery
@@ -137,37 +736,118 @@ impl PeerDiscovery {
         Ok(())
     }
 
-    /// Bootstrap from configured bootstrap nodes
-    async fn bootstrap(&mut self) -> Result<()> {
-        info!("Bootstrapping from {} nodes", self.config.bootstrap_nodes.len());
+    /// Dial up to `stored_peer_dials` stored peers, highest score first
+    async fn dial_stored_peers(&mut self) -> usize {
+        let mut candidates: Vec<PeerRecord> = self.peer_records
+            .values()
+            .filter(|record| !self.blacklisted_peers.contains(&record.info.id))
+            .cloned()
+            .collect();
+        for record in &mut candidates {
+            record.rescore(&self.config.peer_scoring);
+        }
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
         
-        for bootstrap_addr in &self.config.bootstrap_nodes {
-            self.stats.bootstrap_attempts += 1;
-            
-            match self.connect_to_bootstrap(*bootstrap_addr).await {
-                Ok(peers) => {
-                    info!("Successfully bootstrapped from {}, discovered {} peers", 
-                        bootstrap_addr, peers.len());
-                    
-                    for peer in peers {
-                        self.add_peer(peer, DiscoveryMethod::Bootstrap).await;
+        let mut connected = 0;
+        for record in candidates.into_iter().take(self.config.stored_peer_dials) {
+            let peer_id = record.info.id.clone();
+            self.add_peer(record.info, DiscoveryMethod::PeerStore).await;
+            if !self.known_peers.contains_key(&peer_id) {
+                continue;
+            }
+            self.stats.stored_peer_dials += 1;
+            match self.connect_peer(&peer_id).await {
+                Ok(()) => connected += 1,
+                Err(e) => {
+                    debug!("Stored peer {} unreachable: {}", peer_id, e);
+                    if let Some(record) = self.peer_records.get_mut(&peer_id) {
+                        record.record_dial(None);
                     }
                 },
+            }
+        }
+        
+        self.stats.stored_peer_connections += connected as u64;
+        info!("Reconnected to {} stored peers", connected);
+        connected
+    }
+
+    /// Bootstrap sources in the order they are tried
+    fn bootstrap_sources(&self) -> Vec<BootstrapSource> {
+        let configured = (!self.config.bootstrap_nodes.is_empty()).then(|| BootstrapSource::Static(self.config.bootstrap_nodes.clone()));
+        configured.into_iter().chain(self.config.bootstrap_sources.iter().cloned()).collect()
+    }
+
+    /// Bootstrap from each source in turn until one yields peers
+    async fn bootstrap(&mut self) -> Result<()> {
+        let sources = self.bootstrap_sources();
+        info!("Bootstrapping from {} sources", sources.len());
+        
+        for source in sources {
+            let label = source.label();
+            let method = if source.is_dns() { DiscoveryMethod::DNS } else { DiscoveryMethod::Bootstrap };
+            let resolved = source.resolve(&self.config.trusted_list_signers).await;
+            let stats = self.stats.bootstrap_sources.entry(label.clone()).or_default();
+            stats.resolutions += 1;
+            let addrs = match resolved {
+                Ok(addrs) => {
+                    stats.addresses_resolved += addrs.len() as u64;
+                    addrs
+                },
                 Err(e) => {
-                    warn!("Failed to bootstrap from {}: {}", bootstrap_addr, e);
-                    self.stats.failed_connections += 1;
+                    warn!("Failed to resolve bootstrap source {}: {:#}", label, e);
+                    stats.failed_resolutions += 1;
+                    stats.last_error = Some(format!("{:#}", e));
+                    continue;
                 }
+            };
+            
+            let mut discovered = 0;
+            for bootstrap_addr in addrs {
+                self.stats.bootstrap_attempts += 1;
+                let result = self.connect_to_bootstrap(bootstrap_addr).await;
+                let stats = self.stats.bootstrap_sources.entry(label.clone()).or_default();
+                stats.bootstrap_attempts += 1;
+                
+                match result {
+                    Ok(peers) => {
+                        info!("Successfully bootstrapped from {}, discovered {} peers", 
+                            bootstrap_addr, peers.len());
+                        stats.successful_bootstraps += 1;
+                        stats.peers_discovered += peers.len() as u64;
+                        discovered += peers.len();
+                        
+                        for peer in peers {
+                            self.add_peer(peer, method.clone()).await;
+                        }
+                    },
+                    Err(e) => {
+                        warn!("Failed to bootstrap from {}: {}", bootstrap_addr, e);
+                        stats.last_error = Some(e.to_string());
+                        self.stats.failed_connections += 1;
+                    }
+                }
+            }
+            
+            if discovered > 0 {
+                break;
             }
+            warn!("Bootstrap source {} yielded no peers, falling back to the next", label);
+            self.emit_event(ACPEvent::DiscoveryFailed { reason: format!("Bootstrap source {} yielded no peers", label) });
         }
         
-        self.emit_event(DiscoveryEvent::BootstrapCompleted);
+        self.emit_event(ACPEvent::BootstrapCompleted);
         Ok(())
     }
 
     /// Connect to a bootstrap node and get peer list
     async fn connect_to_bootstrap(&self, addr: SocketAddr) -> Result<Vec<PeerInfo>> {
-        // Simulate bootstrap connection and peer list retrieval
         debug!("Connecting to bootstrap node: {}", addr);
+        if let Some(dht) = &self.dht {
+            return dht.bootstrap(addr).await;
+        }
+        
+        // Simulate bootstrap connection and peer list retrieval
         
         // In a real implementation, this would make an actual network request
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -182,6 +862,7 @@ impl PeerDiscovery {
                 last_seen: chrono::Utc::now(),
                 protocol_version: "1.0.0".to_string(),
                 node_type: NodeType::Validator,
+                rtt_ms: None,
             },
             PeerInfo {
                 id: format!("peer-{}", uuid::Uuid::new_v4()),
@@ -192,6 +873,7 @@ impl PeerDiscovery {
                 last_seen: chrono::Utc::now(),
                 protocol_version: "1.0.0".to_string(),
                 node_type: NodeType::Agent,
+                rtt_ms: None,
             },
         ];
         
@@ -210,40 +892,95 @@ impl PeerDiscovery {
             }
             
             self.cleanup_inactive_peers().await;
+            
+            if self.reputation_source.is_some() && self.last_reputation_refresh.elapsed() >= self.config.reputation_refresh.interval {
+                if let Err(e) = self.refresh_reputations().await {
+                    error!("Reputation refresh failed: {}", e);
+                }
+            }
         }
     }
 
+    /// Re-query the reputations of connected peers and disconnect those
+    /// that have stayed below the threshold for `strikes` refreshes
+    pub async fn refresh_reputations(&mut self) -> Result<ReputationRefresh> {
+        let source = self.reputation_source.clone().ok_or_else(|| anyhow!("No reputation source configured"))?;
+        self.last_reputation_refresh = Instant::now();
+        self.stats.reputation_refreshes += 1;
+        
+        let mut peer_ids: Vec<String> = self.connected_peers.iter().cloned().collect();
+        peer_ids.sort();
+        let reputations = source.reputations(&peer_ids).await?;
+        
+        let settings = &self.config.reputation_refresh;
+        let low_mark = self.config.reputation_threshold - settings.hysteresis;
+        let mut outcome = ReputationRefresh::default();
+        for peer_id in &peer_ids {
+            let Some(&reputation) = reputations.get(peer_id) else {
+                continue;
+            };
+            outcome.refreshed += 1;
+            if let Some(peer) = self.known_peers.get_mut(peer_id) {
+                peer.reputation = reputation;
+            }
+            if let Some(record) = self.peer_records.get_mut(peer_id) {
+                record.info.reputation = reputation;
+            }
+            
+            let state = self.refreshed.entry(peer_id.clone()).or_insert(RefreshedReputation { reputation, strikes: 0 });
+            state.reputation = reputation;
+            if reputation < low_mark {
+                state.strikes += 1;
+            } else if reputation >= self.config.reputation_threshold {
+                state.strikes = 0;
+            }
+            if state.strikes >= settings.strikes.max(1) {
+                state.strikes = 0;
+                outcome.disconnected.push(peer_id.clone());
+            }
+        }
+        
+        for peer_id in &outcome.disconnected {
+            info!("Disconnecting {}: refreshed reputation {:.3} is below {:.3}", 
+                peer_id, self.refreshed[peer_id].reputation, self.config.reputation_threshold);
+            self.stats.reputation_disconnections += 1;
+            self.disconnect_peer(peer_id).await?;
+        }
+        Ok(outcome)
+    }
+
     /// Discover new peers using various methods
     async fn discover_peers(&mut self) -> Result<()> {
         debug!("Starting peer discovery round");
         self.last_discovery = Instant::now();
+        self.apply_bans();
         
         let mut new_peers = Vec::new();
         
         // DHT-based discovery
         if self.config.enable_dht {
             if let Ok(dht_peers) = self.dht_discovery().await {
-                new_peers.extend(dht_peers);
+                new_peers.extend(dht_peers.into_iter().map(|peer| (peer, DiscoveryMethod::DHT)));
             }
         }
         
         // Gossip-based discovery
         if self.config.enable_gossip {
             if let Ok(gossip_peers) = self.gossip_discovery().await {
-                new_peers.extend(gossip_peers);
+                new_peers.extend(gossip_peers.into_iter().map(|peer| (peer, DiscoveryMethod::Gossip)));
             }
         }
         
         // mDNS discovery (local network)
         if self.config.enable_mdns {
             if let Ok(mdns_peers) = self.mdns_discovery().await {
-                new_peers.extend(mdns_peers);
+                new_peers.extend(mdns_peers.into_iter().map(|peer| (peer, DiscoveryMethod::MDNS)));
             }
         }
         
         // Add discovered peers
-        for peer in new_peers {
-            self.add_peer(peer, DiscoveryMethod::DHT).await;
+        for (peer, method) in new_peers {
+            self.add_peer(peer, method).await;
         }
         
         info!("Discovery round completed. Known peers: {}, Connected: {}", 
@@ -254,26 +991,16 @@ impl PeerDiscovery {
 
     /// DHT-based peer discovery
     async fn dht_discovery(&mut self) -> Result<Vec<PeerInfo>> {
+        let Some(dht) = self.dht.clone() else {
+            return Ok(Vec::new());
+        };
         self.stats.dht_queries += 1;
         
-        // Simulate DHT query
+        // Refresh idle buckets, then look for our own neighbors
         debug!("Performing DHT peer discovery");
-        tokio::time::sleep(Duration::from_millis(50)).await;
-        
-        let peers = vec![
-            PeerInfo {
-                id: format!("dht-peer-{}", uuid::Uuid::new_v4()),
-                address: "10.0.1.50:8080".parse()?,
-                public_key: "dht_peer_key".to_string(),
-                capabilities: vec!["agent".to_string(), "relay".to_string()],
-                reputation: 0.7,
-                last_seen: chrono::Utc::now(),
-                protocol_version: "1.0.0".to_string(),
-                node_type: NodeType::Agent,
-            },
-        ];
-        
-        Ok(peers)
+        let refreshed = dht.refresh().await;
+        debug!("Refreshed {} idle buckets", refreshed);
+        dht.lookup(&dht.local_key()).await
     }
 
     /// Gossip-based peer discovery
@@ -294,29 +1021,38 @@ impl PeerDiscovery {
                 last_seen: chrono::Utc::now(),
                 protocol_version: "1.0.0".to_string(),
                 node_type: NodeType::Validator,
+                rtt_ms: None,
             },
         ];
         
         Ok(peers)
     }
 
-    /// mDNS local network discovery
+    /// mDNS local network discovery: agents resolved since the last round
     async fn mdns_discovery(&self) -> Result<Vec<PeerInfo>> {
-        debug!("Performing mDNS peer discovery");
-        tokio::time::sleep(Duration::from_millis(20)).await;
+        #[cfg(feature = "mdns")]
+        if let Some(mdns) = &self.mdns {
+            debug!("Performing mDNS peer discovery");
+            return Ok(mdns.resolved());
+        }
         
-        // Simulate local network discovery
+        debug!("mDNS discovery is enabled but not running; it needs the `mdns` feature and `with_mdns`");
         Ok(Vec::new())
     }
 
     /// Add a newly discovered peer
-    async fn add_peer(&mut self, peer: PeerInfo, method: DiscoveryMethod) {
+    async fn add_peer(&mut self, mut peer: PeerInfo, method: DiscoveryMethod) {
         // Check if peer is blacklisted
         if self.blacklisted_peers.contains(&peer.id) {
             debug!("Ignoring blacklisted peer: {}", peer.id);
             return;
         }
         
+        // A refreshed reputation overrides the one the peer advertises
+        if let Some(refreshed) = self.refreshed.get(&peer.id) {
+            peer.reputation = refreshed.reputation;
+        }
+        
         // Check reputation threshold
         if peer.reputation < self.config.reputation_threshold {
             debug!("Ignoring peer with low reputation: {} ({})", peer.id, peer.reputation);
@@ -334,9 +1070,13 @@ impl PeerDiscovery {
         if is_new {
             self.stats.total_discovered += 1;
             info!("Discovered new peer: {} via {:?}", peer.id, method);
-            self.emit_event(DiscoveryEvent::PeerDiscovered(peer.clone()));
+            self.emit_event(ACPEvent::PeerDiscovered { peer: peer.clone(), method });
         }
         
+        self.peer_records
+            .entry(peer.id.clone())
+            .and_modify(|record| record.info = peer.clone())
+            .or_insert_with(|| PeerRecord::new(peer.clone()));
         self.known_peers.insert(peer.id.clone(), peer);
     }
 
@@ -352,23 +1092,47 @@ impl PeerDiscovery {
             .collect();
         
         for peer_id in inactive_peers {
-            self.remove_peer(&peer_id).await;
+            self.remove_peer(&peer_id);
         }
     }
 
     /// Remove a peer
-    async fn remove_peer(&mut self, peer_id: &str) {
+    fn remove_peer(&mut self, peer_id: &str) {
         if self.known_peers.remove(peer_id).is_some() {
             self.connected_peers.remove(peer_id);
             self.stats.peer_disconnections += 1;
             debug!("Removed inactive peer: {}", peer_id);
-            self.emit_event(DiscoveryEvent::PeerTimeout(peer_id.to_string()));
+            self.emit_event(ACPEvent::PeerTimeout { peer_id: peer_id.to_string() });
         }
     }
 
-    /// Blacklist a peer
+    /// Blacklist a peer, banning it in the peer scorer if there is one
     pub fn blacklist_peer(&mut self, peer_id: &str) {
+        if let Some(scorer) = self.scorer.clone() {
+            scorer.ban(peer_id, chrono::Utc::now());
+            self.apply_bans();
+        }
+        if !self.blacklisted_peers.contains(peer_id) {
+            self.blacklist(peer_id);
+        }
+    }
+
+    /// Blacklist the peers banned by the peer scorer since the last call,
+    /// returning how many
+    pub fn apply_bans(&mut self) -> usize {
+        let Some(scorer) = self.scorer.clone() else {
+            return 0;
+        };
+        let banned = scorer.take_new_bans();
+        for peer_id in &banned {
+            self.blacklist(peer_id);
+        }
+        banned.len()
+    }
+
+    fn blacklist(&mut self, peer_id: &str) {
         self.blacklisted_peers.insert(peer_id.to_string());
+        self.peer_records.remove(peer_id);
         self.remove_peer(peer_id);
         warn!("Blacklisted peer: {}", peer_id);
     }
@@ -394,35 +1158,63 @@ impl PeerDiscovery {
             .collect()
     }
 
+    /// Feed in a peer's current misbehavior score
+    pub fn record_misbehavior(&mut self, peer_id: &str, score: f64) {
+        if let Some(record) = self.peer_records.get_mut(peer_id) {
+            record.misbehavior = score;
+        }
+    }
+
+    /// Scored records of every peer seen, for saving to a `PeerStore`
+    pub fn peer_records(&self) -> Vec<PeerRecord> {
+        let mut records: Vec<PeerRecord> = self.peer_records.values().cloned().collect();
+        for record in &mut records {
+            record.rescore(&self.config.peer_scoring);
+        }
+        records.sort_by(|a, b| b.score.total_cmp(&a.score));
+        records
+    }
+
     /// Get discovery statistics
     pub fn get_stats(&self) -> &DiscoveryStats {
         &self.stats
     }
 
-    /// Register event callback
-    pub fn on_event<F>(&mut self, callback: F)
-    where
-        F: Fn(DiscoveryEvent) + Send + Sync + 'static,
-    {
-        self.event_callbacks.push(Box::new(callback));
+    /// Receiver of the discovery events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ACPEvent> {
+        self.events.subscribe()
     }
 
-    /// Emit discovery event
-    fn emit_event(&self, event: DiscoveryEvent) {
-        for callback in &self.event_callbacks {
-            callback(event.clone());
+    /// Apply an event published by another component; heartbeat round
+    /// trips measured by gossip become the peer's `rtt_ms`
+    pub fn observe(&mut self, event: &ACPEvent) {
+        if let ACPEvent::PeerRtt { peer_id, rtt_ms } = event {
+            if let Some(peer) = self.known_peers.get_mut(peer_id) {
+                peer.rtt_ms = Some(*rtt_ms);
+            }
+            if let Some(record) = self.peer_records.get_mut(peer_id) {
+                record.info.rtt_ms = Some(*rtt_ms);
+            }
         }
     }
 
+    /// Emit discovery event
+    fn emit_event(&self, event: ACPEvent) {
+        self.events.publish(event);
+    }
+
     /// Connect to a specific peer
     pub async fn connect_peer(&mut self, peer_id: &str) -> Result<()> {
         if let Some(peer) = self.known_peers.get(peer_id) {
             // Simulate connection
             debug!("Connecting to peer: {}", peer_id);
+            let started = Instant::now();
             tokio::time::sleep(Duration::from_millis(100)).await;
             
+            let record = self.peer_records.entry(peer_id.to_string()).or_insert_with(|| PeerRecord::new(peer.clone()));
+            record.record_dial(Some(started.elapsed()));
             self.connected_peers.insert(peer_id.to_string());
-            self.emit_event(DiscoveryEvent::PeerConnected(peer_id.to_string()));
+            self.emit_event(ACPEvent::PeerConnected { peer_id: peer_id.to_string() });
             
             Ok(())
         } else {
@@ -433,7 +1225,7 @@ impl PeerDiscovery {
     /// Disconnect from a peer
     pub async fn disconnect_peer(&mut self, peer_id: &str) -> Result<()> {
         if self.connected_peers.remove(peer_id) {
-            self.emit_event(DiscoveryEvent::PeerDisconnected(peer_id.to_string()));
+            self.emit_event(ACPEvent::PeerDisconnected { peer_id: peer_id.to_string() });
             debug!("Disconnected from peer: {}", peer_id);
             Ok(())
         } else {
@@ -442,6 +1234,111 @@ impl PeerDiscovery {
     }
 }
 
+#[cfg(feature = "mdns")]
+mod mdns {
+    //! mDNS advertisement and browsing over mdns-sd
+
+    use std::collections::HashMap;
+    use std::net::{IpAddr, SocketAddr};
+
+    use anyhow::{anyhow, Result};
+    use mdns_sd::{Receiver, ServiceDaemon, ServiceEvent, ServiceInfo};
+    use tracing::debug;
+
+    use super::{NodeKey, NodeType, PeerInfo};
+
+    const SERVICE_TYPE: &str = "_solace-acp._tcp.local.";
+    const UNRATED_REPUTATION: f64 = 0.5;  // LAN peers have no reputation yet
+
+    /// Our advertisement and the browse for everyone else's
+    pub struct MdnsService {
+        daemon: ServiceDaemon,
+        fullname: String,
+        local_id: String,
+        events: Receiver<ServiceEvent>,
+    }
+
+    impl MdnsService {
+        pub fn start(local: &PeerInfo) -> Result<Self> {
+            let daemon = ServiceDaemon::new().map_err(|e| anyhow!("Failed to start mDNS daemon: {}", e))?;
+            let service = advertisement(local)?;
+            let fullname = service.get_fullname().to_string();
+            daemon.register(service).map_err(|e| anyhow!("Failed to advertise over mDNS: {}", e))?;
+            let events = daemon.browse(SERVICE_TYPE).map_err(|e| anyhow!("Failed to browse mDNS: {}", e))?;
+            Ok(Self { daemon, fullname, local_id: local.id.clone(), events })
+        }
+
+        /// Other agents resolved since the last call
+        pub fn resolved(&self) -> Vec<PeerInfo> {
+            self.events
+                .try_iter()
+                .filter_map(|event| match event {
+                    ServiceEvent::ServiceResolved(service) => peer_from_service(&service),
+                    _ => None,
+                })
+                .filter(|peer| peer.id != self.local_id)
+                .collect()
+        }
+    }
+
+    impl Drop for MdnsService {
+        fn drop(&mut self) {
+            if let Err(e) = self.daemon.unregister(&self.fullname) {
+                debug!("Failed to withdraw mDNS advertisement: {}", e);
+            }
+            let _ = self.daemon.shutdown();
+        }
+    }
+
+    /// DNS-SD service advertising `local`. Node ids can exceed the 63-byte
+    /// DNS label limit, so the instance is named after the node's key and
+    /// the id travels in a TXT record.
+    pub fn advertisement(local: &PeerInfo) -> Result<ServiceInfo> {
+        let instance: String = NodeKey::for_node(&local.id).0[..8].iter().map(|byte| format!("{:02x}", byte)).collect();
+        let node_type = serde_json::to_value(&local.node_type)?;
+        let properties = HashMap::from([
+            ("id".to_string(), local.id.clone()),
+            ("key".to_string(), local.public_key.clone()),
+            ("caps".to_string(), local.capabilities.join(",")),
+            ("type".to_string(), node_type.as_str().unwrap_or_default().to_string()),
+            ("ver".to_string(), local.protocol_version.clone()),
+        ]);
+        let host = format!("solace-{}.local.", instance);
+        let address = local.address;
+        let service = if address.ip().is_unspecified() {
+            // Listening on every interface: advertise every interface's address
+            ServiceInfo::new(SERVICE_TYPE, &instance, &host, (), address.port(), properties).map(ServiceInfo::enable_addr_auto)
+        } else {
+            ServiceInfo::new(SERVICE_TYPE, &instance, &host, address.ip(), address.port(), properties)
+        };
+        service.map_err(|e| anyhow!("Invalid mDNS advertisement: {}", e))
+    }
+
+    /// Peer described by a resolved service, preferring an IPv4 address
+    pub fn peer_from_service(service: &ServiceInfo) -> Option<PeerInfo> {
+        let addresses = service.get_addresses();
+        let ip: IpAddr = addresses.iter().find(|ip| ip.is_ipv4()).or_else(|| addresses.iter().next()).copied()?;
+        let node_type: NodeType = serde_json::from_value(service.get_property_val_str("type")?.into()).ok()?;
+        Some(PeerInfo {
+            id: service.get_property_val_str("id")?.to_string(),
+            address: SocketAddr::new(ip, service.get_port()),
+            public_key: service.get_property_val_str("key").unwrap_or_default().to_string(),
+            capabilities: service
+                .get_property_val_str("caps")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|capability| !capability.is_empty())
+                .map(str::to_string)
+                .collect(),
+            reputation: UNRATED_REPUTATION,
+            last_seen: chrono::Utc::now(),
+            protocol_version: service.get_property_val_str("ver").unwrap_or_default().to_string(),
+            node_type,
+            rtt_ms: None,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -469,6 +1366,7 @@ mod tests {
             last_seen: chrono::Utc::now(),
             protocol_version: "1.0.0".to_string(),
             node_type: NodeType::Agent,
+            rtt_ms: None,
         };
         
         discovery.add_peer(peer, DiscoveryMethod::Manual).await;
@@ -477,6 +1375,25 @@ mod tests {
         assert!(discovery.known_peers.contains_key("test_peer"));
     }
 
+    #[tokio::test]
+    async fn test_scorer_bans_are_blacklisted() {
+        use crate::misbehavior::Violation;
+        use crate::security::PeerScorerConfig;
+
+        let scorer = Arc::new(PeerScorer::new(PeerScorerConfig { ban_threshold: 50.0, ..Default::default() }));
+        scorer.ban("known_bad", chrono::Utc::now());
+        let mut discovery = PeerDiscovery::new(DiscoveryConfig::default()).with_peer_scorer(scorer.clone());
+        assert!(discovery.blacklisted_peers.contains("known_bad"));
+
+        scorer.record("forger", Violation::FakePeerRecord, chrono::Utc::now());
+        assert_eq!(discovery.apply_bans(), 1);
+        assert!(discovery.blacklisted_peers.contains("forger"));
+
+        discovery.blacklist_peer("manual");
+        assert!(scorer.is_banned("manual"));
+        assert_eq!(discovery.apply_bans(), 0);
+    }
+
     #[tokio::test]
     async fn test_blacklist_peer() {
         let config = DiscoveryConfig::default();
@@ -486,4 +1403,228 @@ mod tests {
         
         assert!(discovery.blacklisted_peers.contains("bad_peer"));
     }
-} 
\ No newline at end of file
+
+    fn peer_info(id: &str, address: SocketAddr) -> PeerInfo {
+        PeerInfo {
+            id: id.to_string(),
+            address,
+            public_key: String::new(),
+            capabilities: Vec::new(),
+            reputation: 1.0,
+            last_seen: chrono::Utc::now(),
+            protocol_version: crate::ACP_VERSION.to_string(),
+            node_type: NodeType::Agent,
+            rtt_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_routing_table_orders_by_distance_and_keeps_replacements() {
+        let local = NodeKey::for_node("local");
+        assert_eq!(local.bucket_index(&local), None);
+        let rng = NodeRng::from_seed(5);
+        for index in [0, 7, 100, 255] {
+            assert_eq!(local.bucket_index(&local.random_in_bucket(index, &rng)), Some(index));
+        }
+        // The same seed replays the same refresh keys
+        let (a, b) = (NodeRng::from_seed(9), NodeRng::from_seed(9));
+        assert_eq!(local.random_in_bucket(100, &a), local.random_in_bucket(100, &b));
+
+        let now = Instant::now();
+        let addr: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let mut table = RoutingTable::new(local, 2, now);
+        let ids: Vec<String> = (0..40).map(|i| format!("peer-{}", i)).collect();
+        for id in &ids {
+            table.update(peer_info(id, addr), now);
+        }
+        assert!(table.len() < ids.len());
+        assert!(table.buckets.iter().all(|bucket| bucket.peers.len() <= 2));
+
+        let target = NodeKey::for_node("peer-3");
+        let closest = table.closest(&target, 5);
+        let distances: Vec<NodeKey> = closest.iter().map(|peer| NodeKey::for_node(&peer.id).distance(&target)).collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        // A failed entry in a full bucket gives way to the replacement
+        let full = table.buckets.iter().position(|bucket| bucket.replacement.is_some()).unwrap();
+        let failed = table.buckets[full].peers[0].id.clone();
+        let replacement = table.buckets[full].replacement.clone().unwrap();
+        assert!(table.remove(&failed));
+        assert!(table.get(&replacement.id).is_some());
+        assert!(table.stale_buckets(Duration::from_secs(60), now).is_empty());
+        assert!(table.stale_buckets(Duration::from_secs(60), now + Duration::from_secs(60)).contains(&full));
+    }
+
+    async fn dht_node(node_id: &str) -> Arc<KademliaDht> {
+        let config = crate::ACPConfig {
+            node_id: node_id.to_string(),
+            listen_address: "127.0.0.1:0".to_string(),
+            ..Default::default()
+        };
+        let network = Arc::new(P2PNetwork::new(&config).await.unwrap());
+        network.start().await.unwrap();
+        let local = peer_info(node_id, network.local_addr().unwrap());
+        let dht = Arc::new(KademliaDht::new(local, network.clone(), KademliaConfig::default()));
+
+        let mut incoming = network.incoming().unwrap();
+        let serving = dht.clone();
+        tokio::spawn(async move {
+            while let Some(inbound) = incoming.recv().await {
+                serving.handle_message(&inbound).await;
+            }
+        });
+        dht
+    }
+
+    #[tokio::test]
+    async fn test_iterative_lookup_finds_peers_beyond_the_routing_table() {
+        let nodes = [dht_node("a").await, dht_node("b").await, dht_node("c").await, dht_node("d").await];
+        // a knows only b, b knows c, c knows d
+        for pair in nodes.windows(2) {
+            pair[0].add_peer(pair[1].local().clone());
+        }
+        assert!(nodes[0].peers().iter().all(|peer| peer.id == "b"));
+
+        let mut discovery = PeerDiscovery::new(DiscoveryConfig::default()).with_dht(nodes[0].clone());
+        let found = discovery.lookup("d").await.unwrap();
+        assert_eq!(found[0].id, "d");
+        assert_eq!(found.len(), 3);
+        assert!(nodes[0].peers().iter().any(|peer| peer.id == "d"));
+        // Asking a node teaches it about the asker
+        assert!(nodes[3].peers().iter().any(|peer| peer.id == "a"));
+
+        // A newcomer joins through a bootstrap address alone
+        let newcomer = dht_node("e").await;
+        let neighbors = newcomer.bootstrap(nodes[1].local().address).await.unwrap();
+        assert_eq!(neighbors.len(), 4);
+    }
+
+    #[cfg(feature = "mdns")]
+    #[test]
+    fn test_mdns_advertisement_describes_the_peer() {
+        let mut local = peer_info("agent-with-an-id-longer-than-a-dns-label-allows-0123456789abcdef", "192.168.1.20:7000".parse().unwrap());
+        local.capabilities = vec!["relay".to_string(), "pricing".to_string()];
+        local.node_type = NodeType::Validator;
+
+        let service = mdns::advertisement(&local).unwrap();
+        let peer = mdns::peer_from_service(&service).unwrap();
+        assert_eq!(peer.id, local.id);
+        assert_eq!(peer.address, local.address);
+        assert_eq!(peer.capabilities, local.capabilities);
+        assert_eq!(peer.node_type, NodeType::Validator);
+        assert!(peer.reputation >= DiscoveryConfig::default().reputation_threshold);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_falls_back_through_sources() {
+        use crate::bootstrap::{PeerList, SignedPeerList};
+
+        let operator = ed25519_dalek::SigningKey::from_bytes(&[3; 32]);
+        let list = PeerList { peers: vec!["10.0.0.1:7000".parse().unwrap()], issued_at: chrono::Utc::now(), expires_at: None };
+        let dir = tempfile::tempdir().unwrap();
+        let (forged, signed) = (dir.path().join("forged.json"), dir.path().join("peers.json"));
+        let forger = ed25519_dalek::SigningKey::from_bytes(&[4; 32]);
+        std::fs::write(&forged, serde_json::to_string(&SignedPeerList::sign(list.clone(), &forger).unwrap()).unwrap()).unwrap();
+        std::fs::write(&signed, serde_json::to_string(&SignedPeerList::sign(list, &operator).unwrap()).unwrap()).unwrap();
+
+        let config = DiscoveryConfig {
+            bootstrap_sources: vec![
+                BootstrapSource::PeerList(dir.path().join("missing.json")),
+                BootstrapSource::PeerList(forged.clone()),
+                BootstrapSource::PeerList(signed.clone()),
+                BootstrapSource::Static(vec!["10.0.0.9:7000".parse().unwrap()]),
+            ],
+            trusted_list_signers: vec![operator.verifying_key().to_bytes()],
+            ..Default::default()
+        };
+        let mut discovery = PeerDiscovery::new(config);
+        discovery.bootstrap().await.unwrap();
+
+        let stats = &discovery.get_stats().bootstrap_sources;
+        assert_eq!(stats[&BootstrapSource::PeerList(dir.path().join("missing.json")).label()].failed_resolutions, 1);
+        assert!(stats[&BootstrapSource::PeerList(forged).label()].last_error.as_ref().unwrap().contains("untrusted"));
+        let used = &stats[&BootstrapSource::PeerList(signed).label()];
+        assert_eq!((used.successful_bootstraps, used.peers_discovered), (1, 2));
+        assert!(!stats.contains_key("static"));
+        assert_eq!(discovery.known_peers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stored_peers_dialed_best_first() {
+        let stored = |id: &str, round_trip_ms: u64, failed_dials: u64| {
+            let mut record = PeerRecord::new(PeerInfo {
+                id: id.to_string(),
+                address: "127.0.0.1:8080".parse().unwrap(),
+                public_key: format!("{}_key", id),
+                capabilities: vec!["agent".to_string()],
+                reputation: 0.8,
+                last_seen: chrono::Utc::now(),
+                protocol_version: "1.0.0".to_string(),
+                node_type: NodeType::Agent,
+                rtt_ms: None,
+            });
+            record.record_dial(Some(Duration::from_millis(round_trip_ms)));
+            for _ in 0..failed_dials {
+                record.record_dial(None);
+            }
+            record
+        };
+        let config = DiscoveryConfig { stored_peer_dials: 2, ..Default::default() };
+        let mut discovery = PeerDiscovery::new(config)
+            .with_stored_peers(vec![stored("slow", 900, 0), stored("flaky", 50, 3), stored("solid", 50, 0)]);
+        let mut events = discovery.subscribe();
+
+        assert_eq!(discovery.dial_stored_peers().await, 2);
+        let mut dialed = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let ACPEvent::PeerConnected { peer_id } = event {
+                dialed.push(peer_id);
+            }
+        }
+        assert_eq!(dialed, vec!["solid".to_string(), "slow".to_string()]);
+        assert_eq!(discovery.get_stats().stored_peer_connections, 2);
+
+        discovery.record_misbehavior("solid", 10.0);
+        let records = discovery.peer_records();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records.iter().find(|record| record.info.id == "solid").unwrap().dials, 2);
+        assert_ne!(records[0].info.id, "solid");
+    }
+
+    struct FixedReputations(parking_lot::Mutex<HashMap<String, f64>>);
+
+    impl ReputationSource for FixedReputations {
+        fn reputations<'a>(&'a self, peer_ids: &'a [String]) -> BoxFuture<'a, Result<HashMap<String, f64>>> {
+            let known = self.0.lock();
+            let found = peer_ids.iter().filter_map(|id| known.get(id).map(|score| (id.clone(), *score))).collect();
+            Box::pin(async move { Ok(found) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refreshed_reputation_disconnects_after_strikes() {
+        let source = Arc::new(FixedReputations(parking_lot::Mutex::new(HashMap::new())));
+        let mut discovery = PeerDiscovery::new(DiscoveryConfig::default()).with_reputation_source(source.clone());
+        for id in ["a", "b", "c"] {
+            discovery.add_peer(peer_info(id, "127.0.0.1:8080".parse().unwrap()), DiscoveryMethod::Manual).await;
+            discovery.connect_peer(id).await.unwrap();
+        }
+
+        // b sits inside the hysteresis margin, c well below the threshold
+        source.0.lock().extend([("a".to_string(), 0.9), ("b".to_string(), 0.27), ("c".to_string(), 0.1)]);
+        let first = discovery.refresh_reputations().await.unwrap();
+        assert_eq!((first.refreshed, first.disconnected.len()), (3, 0));
+        assert_eq!(discovery.known_peers["c"].reputation, 0.1);
+
+        let second = discovery.refresh_reputations().await.unwrap();
+        assert_eq!(second.disconnected, vec!["c".to_string()]);
+        assert!(discovery.connected_peers.contains("b"));
+        assert!(!discovery.connected_peers.contains("c"));
+        assert_eq!(discovery.get_stats().reputation_disconnections, 1);
+
+        // Readvertising a stale reputation does not bring c back
+        discovery.known_peers.remove("c");
+        discovery.add_peer(peer_info("c", "127.0.0.1:8080".parse().unwrap()), DiscoveryMethod::Manual).await;
+        assert!(!discovery.known_peers.contains_key("c"));
+    }
+}
@@ -0,0 +1,157 @@
+//! Event Bus
+//!
+//! Lifecycle events from every layer of a node, as one `ACPEvent` stream:
+//! peers found, connected, and lost by discovery, heartbeat round trips
+//! and timeouts from gossip, failed transport handshakes, messages
+//! delivered to this node, gossip from other nodes, route changes, and
+//! reliable-delivery receipts. The transport creates the node's `EventBus`
+//! and every other component publishes to a clone of it; consumers call
+//! `subscribe` for their own receiver. Components take no callbacks:
+//! received messages reach their consumers only as events.
+//!
+//! The bus is a broadcast channel. Publishing never blocks, and a receiver
+//! that falls more than the bus capacity behind is told how many events it
+//! missed (`RecvError::Lagged`) and continues from the oldest one kept.
+//! Consumers that must see every received message and delivery receipt
+//! call `subscribe_deliveries` instead: its unbounded channel gets each of
+//! them no matter how far the consumer is behind.
+
+use std::net::SocketAddr;
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::discovery::{DiscoveryMethod, PeerInfo};
+use crate::gossip::GossipMessage;
+use crate::messaging::ACPMessage;
+use crate::outbox::DeliveryReceipt;
+
+/// Events kept per receiver before the oldest are dropped
+pub const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+/// Something that happened to the node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ACPEvent {
+    PeerDiscovered { peer: PeerInfo, method: DiscoveryMethod },
+    PeerConnected { peer_id: String },
+    PeerDisconnected { peer_id: String },
+    PeerTimeout { peer_id: String },
+    PeerRtt { peer_id: String, rtt_ms: f64 },  // Smoothed heartbeat round trip
+    BootstrapCompleted,
+    DiscoveryFailed { reason: String },
+    HandshakeFailed { remote: SocketAddr, reason: String },
+    MessageReceived { message: ACPMessage },  // Addressed to this node, before its handlers run
+    TopicMessage { topic: String, message: GossipMessage },
+    GossipReceived { message: GossipMessage },  // Gossip from another node outside any topic
+    RouteChanged { destination: String, next_hop: Option<String>, hops: usize },  // No next hop: unreachable
+    DeliveryReceipt(DeliveryReceipt),
+}
+
+impl ACPEvent {
+    /// Whether the event goes to `subscribe_deliveries` receivers, which
+    /// never miss one
+    pub fn must_deliver(&self) -> bool {
+        matches!(
+            self,
+            ACPEvent::MessageReceived { .. } | ACPEvent::TopicMessage { .. } | ACPEvent::GossipReceived { .. } | ACPEvent::DeliveryReceipt(_)
+        )
+    }
+}
+
+/// Broadcast channel of `ACPEvent`s shared by a node's components
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ACPEvent>,
+    deliveries: Arc<Mutex<Vec<mpsc::UnboundedSender<ACPEvent>>>>,  // Receivers of every `must_deliver` event
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender, deliveries: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    /// Receiver of every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ACPEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Receiver of every `must_deliver` event published from now on. It is
+    /// unbounded, so it never misses one; drop it to unsubscribe.
+    pub fn subscribe_deliveries(&self) -> mpsc::UnboundedReceiver<ACPEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.deliveries.lock().push(sender);
+        receiver
+    }
+
+    /// Publish to every receiver; dropped if there are none
+    pub fn publish(&self, event: ACPEvent) {
+        if event.must_deliver() {
+            self.deliveries.lock().retain(|sender| sender.send(event.clone()).is_ok());
+        }
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_see_events_published_after_subscribing() {
+        let bus = EventBus::new(2);
+        bus.publish(ACPEvent::BootstrapCompleted);
+
+        let mut events = bus.subscribe();
+        bus.clone().publish(ACPEvent::PeerConnected { peer_id: "a".to_string() });
+        assert!(matches!(events.recv().await.unwrap(), ACPEvent::PeerConnected { peer_id } if peer_id == "a"));
+
+        // A receiver that falls behind learns how much it missed
+        for peer in ["b", "c", "d"] {
+            bus.publish(ACPEvent::PeerDisconnected { peer_id: peer.to_string() });
+        }
+        assert!(matches!(events.recv().await, Err(broadcast::error::RecvError::Lagged(1))));
+        assert!(matches!(events.recv().await.unwrap(), ACPEvent::PeerDisconnected { peer_id } if peer_id == "c"));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_receivers_never_lag() {
+        let bus = EventBus::new(1);
+        let mut events = bus.subscribe();
+        let mut deliveries = bus.subscribe_deliveries();
+        let dropped = bus.subscribe_deliveries();
+        drop(dropped);
+
+        let receipts: Vec<DeliveryReceipt> = (1..=3).map(|attempts| DeliveryReceipt {
+            message_id: uuid::Uuid::new_v4(),
+            destination: Some("b".to_string()),
+            attempts,
+            status: crate::outbox::DeliveryStatus::Delivered,
+        }).collect();
+        for receipt in &receipts {
+            bus.publish(ACPEvent::DeliveryReceipt(receipt.clone()));
+            bus.publish(ACPEvent::PeerConnected { peer_id: "b".to_string() });
+        }
+
+        // The broadcast receiver lagged, the delivery receiver got every receipt and nothing else
+        assert!(matches!(events.recv().await, Err(broadcast::error::RecvError::Lagged(5))));
+        for receipt in receipts {
+            assert!(matches!(deliveries.recv().await.unwrap(), ACPEvent::DeliveryReceipt(got) if got == receipt));
+        }
+        assert!(deliveries.try_recv().is_err());
+        assert_eq!(bus.deliveries.lock().len(), 1);
+    }
+}
@@ -0,0 +1,412 @@
+//! WebSocket Gateway
+//!
+//! Browser dashboards and JS SDK agents cannot speak the binary, Noise
+//! encrypted P2P transport. The gateway lets them take part over a plain
+//! WebSocket instead: every frame is a JSON object tagged by `op`. Clients
+//! subscribe to gossip topics and publish to them, and send direct messages
+//! to nodes, all on behalf of the gateway's node, which signs what they send.
+//!
+//! ```text
+//! → {"op":"subscribe","topic":"rfq.data_analysis"}
+//! ← {"op":"subscribed","topic":"rfq.data_analysis"}
+//! ← {"op":"message","topic":"rfq.data_analysis","sender":"node-7","payload":{...}}
+//! → {"op":"publish","topic":"rfq.data_analysis","payload":{...}}
+//! → {"op":"send","to":"node-7","message_type":"TransactionRequest","payload":{...}}
+//! ```
+//!
+//! The gateway does not touch the network itself. What clients ask of the
+//! node arrives as `GatewayRequest`s for `ACP::handle_gateway_request`, and
+//! the node subscribes to a topic only while some client wants it. Topic
+//! messages and messages addressed to the node reach clients from the
+//! node's event bus, which `ACP::attach_gateway` has the gateway follow; a
+//! client too slow to keep up loses frames rather than holding up the node.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::events::ACPEvent;
+use crate::gossip::GossipMessage;
+use crate::messaging::{ACPMessage, MessageType};
+use crate::{ACPError, Result};
+
+/// Topic clients subscribe to for direct messages addressed to the node
+pub const DIRECT_TOPIC: &str = "direct";
+
+/// Gateway settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayConfig {
+    pub listen_address: String,
+    pub max_clients: usize,
+    pub max_frame_bytes: usize,
+    pub client_buffer: usize,             // Frames queued per client before it loses them
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        Self {
+            listen_address: "127.0.0.1:8090".to_string(),
+            max_clients: 256,
+            max_frame_bytes: 64 * 1024,
+            client_buffer: 256,
+        }
+    }
+}
+
+/// Connected client, numbered in order of connection
+pub type ClientId = u64;
+
+/// Frame sent by a client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ClientFrame {
+    Subscribe { topic: String },
+    Unsubscribe { topic: String },
+    Publish { topic: String, payload: serde_json::Value },
+    Send { to: String, message_type: MessageType, payload: serde_json::Value },
+    Ping,
+}
+
+/// Frame sent to a client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ServerFrame {
+    Subscribed { topic: String },
+    Unsubscribed { topic: String },
+    Message { topic: String, sender: String, payload: serde_json::Value },
+    Direct { from: String, message_type: MessageType, payload: serde_json::Value },
+    Error { reason: String },
+    Pong,
+}
+
+/// What clients ask the node to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum GatewayRequest {
+    Subscribe { topic: String },          // First client joined the topic
+    Unsubscribe { topic: String },        // Last client left it
+    Publish { client: ClientId, topic: String, payload: serde_json::Value },
+    Send { client: ClientId, to: String, message_type: MessageType, payload: serde_json::Value },
+}
+
+/// Gateway counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GatewayStats {
+    pub clients: usize,
+    pub frames_in: u64,
+    pub frames_out: u64,
+    pub dropped: u64,                     // Frames lost to full client buffers
+}
+
+struct Client {
+    frames: mpsc::Sender<ServerFrame>,
+    topics: HashSet<String>,
+}
+
+/// WebSocket front end to one node
+pub struct Gateway {
+    config: GatewayConfig,
+    clients: RwLock<HashMap<ClientId, Client>>,
+    next_client: AtomicU64,
+    requests: mpsc::Sender<GatewayRequest>,
+    stats: RwLock<GatewayStats>,
+}
+
+impl Gateway {
+    /// Gateway and the requests its clients make of the node
+    pub fn new(config: GatewayConfig) -> (Arc<Self>, mpsc::Receiver<GatewayRequest>) {
+        let (requests, received) = mpsc::channel(config.client_buffer.max(1));
+        let gateway = Arc::new(Self {
+            config,
+            clients: RwLock::new(HashMap::new()),
+            next_client: AtomicU64::new(1),
+            requests,
+            stats: RwLock::new(GatewayStats::default()),
+        });
+        (gateway, received)
+    }
+
+    pub fn config(&self) -> &GatewayConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> GatewayStats {
+        GatewayStats { clients: self.clients.read().len(), ..self.stats.read().clone() }
+    }
+
+    /// Listen on the configured address until the task is dropped
+    pub async fn serve(self: Arc<Self>) -> Result<()> {
+        let listener = TcpListener::bind(&self.config.listen_address)
+            .await
+            .map_err(|e| ACPError::Network(format!("Failed to bind gateway on {}: {}", self.config.listen_address, e)))?;
+        self.run(listener).await
+    }
+
+    /// Accept clients on a bound listener
+    pub async fn run(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        loop {
+            let (stream, addr) = listener.accept().await.map_err(|e| ACPError::Network(format!("Gateway accept failed: {}", e)))?;
+            if self.clients.read().len() >= self.config.max_clients {
+                tracing::warn!("Gateway full, refusing client {}", addr);
+                continue;
+            }
+            tokio::spawn(self.clone().connection(stream, addr));
+        }
+    }
+
+    async fn connection(self: Arc<Self>, stream: TcpStream, addr: SocketAddr) {
+        let ws_config = WebSocketConfig {
+            max_message_size: Some(self.config.max_frame_bytes),
+            max_frame_size: Some(self.config.max_frame_bytes),
+            ..WebSocketConfig::default()
+        };
+        let socket = match tokio_tungstenite::accept_async_with_config(stream, Some(ws_config)).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::debug!("WebSocket handshake with {} failed: {}", addr, e);
+                return;
+            }
+        };
+        let (mut sink, mut source) = socket.split();
+
+        let client = self.next_client.fetch_add(1, Ordering::Relaxed);
+        let (frames, mut outgoing) = mpsc::channel(self.config.client_buffer.max(1));
+        self.clients.write().insert(client, Client { frames, topics: HashSet::new() });
+        tracing::info!("Gateway client {} connected from {}", client, addr);
+
+        let writer = tokio::spawn(async move {
+            while let Some(frame) = outgoing.recv().await {
+                let Ok(text) = serde_json::to_string(&frame) else { continue };
+                if sink.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(Ok(message)) = source.next().await {
+            match message {
+                Message::Text(text) => self.receive(client, &text).await,
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+
+        writer.abort();
+        self.disconnect(client).await;
+        tracing::info!("Gateway client {} disconnected", client);
+    }
+
+    /// Handle one text frame from a client
+    async fn receive(&self, client: ClientId, text: &str) {
+        self.stats.write().frames_in += 1;
+        let frame = match serde_json::from_str::<ClientFrame>(text) {
+            Ok(frame) => frame,
+            Err(e) => {
+                self.notify(client, ServerFrame::Error { reason: format!("Malformed frame: {}", e) });
+                return;
+            }
+        };
+
+        let request = match frame {
+            ClientFrame::Ping => {
+                self.notify(client, ServerFrame::Pong);
+                return;
+            }
+            ClientFrame::Subscribe { topic } => {
+                let first = self.join(client, &topic);
+                self.notify(client, ServerFrame::Subscribed { topic: topic.clone() });
+                match first && topic != DIRECT_TOPIC {
+                    true => GatewayRequest::Subscribe { topic },
+                    false => return,
+                }
+            }
+            ClientFrame::Unsubscribe { topic } => {
+                let last = self.leave(client, &topic);
+                self.notify(client, ServerFrame::Unsubscribed { topic: topic.clone() });
+                match last && topic != DIRECT_TOPIC {
+                    true => GatewayRequest::Unsubscribe { topic },
+                    false => return,
+                }
+            }
+            ClientFrame::Publish { topic, payload } => GatewayRequest::Publish { client, topic, payload },
+            ClientFrame::Send { to, message_type, payload } => GatewayRequest::Send { client, to, message_type, payload },
+        };
+        if self.requests.send(request).await.is_err() {
+            self.notify(client, ServerFrame::Error { reason: "Node is not accepting gateway requests".to_string() });
+        }
+    }
+
+    /// Add a client to a topic, returning whether it is the first there
+    fn join(&self, client: ClientId, topic: &str) -> bool {
+        let mut clients = self.clients.write();
+        let first = !clients.values().any(|c| c.topics.contains(topic));
+        if let Some(entry) = clients.get_mut(&client) {
+            entry.topics.insert(topic.to_string());
+        }
+        first
+    }
+
+    /// Remove a client from a topic, returning whether it was the last there
+    fn leave(&self, client: ClientId, topic: &str) -> bool {
+        let mut clients = self.clients.write();
+        let removed = clients.get_mut(&client).is_some_and(|entry| entry.topics.remove(topic));
+        removed && !clients.values().any(|c| c.topics.contains(topic))
+    }
+
+    async fn disconnect(&self, client: ClientId) {
+        let Some(entry) = self.clients.write().remove(&client) else { return };
+        let orphaned: Vec<String> = {
+            let clients = self.clients.read();
+            entry
+                .topics
+                .into_iter()
+                .filter(|topic| topic != DIRECT_TOPIC && !clients.values().any(|c| c.topics.contains(topic)))
+                .collect()
+        };
+        for topic in orphaned {
+            let _ = self.requests.send(GatewayRequest::Unsubscribe { topic }).await;
+        }
+    }
+
+    /// Queue a frame for one client, dropping it if the client is behind
+    pub fn notify(&self, client: ClientId, frame: ServerFrame) -> bool {
+        let sent = self.clients.read().get(&client).is_some_and(|entry| entry.frames.try_send(frame).is_ok());
+        let mut stats = self.stats.write();
+        match sent {
+            true => stats.frames_out += 1,
+            false => stats.dropped += 1,
+        }
+        sent
+    }
+
+    /// Queue a frame for every client on `topic`, returning how many got it
+    fn fan_out(&self, topic: &str, frame: ServerFrame) -> usize {
+        let subscribers: Vec<ClientId> = self
+            .clients
+            .read()
+            .iter()
+            .filter(|(_, entry)| entry.topics.contains(topic))
+            .map(|(id, _)| *id)
+            .collect();
+        subscribers.into_iter().filter(|client| self.notify(*client, frame.clone())).count()
+    }
+
+    /// Deliver a gossip topic message to the topic's clients
+    pub fn deliver_topic(&self, message: &GossipMessage) -> usize {
+        let Some(topic) = &message.topic else { return 0 };
+        let frame = ServerFrame::Message {
+            topic: topic.clone(),
+            sender: message.sender_id.clone(),
+            payload: message.payload.clone(),
+        };
+        self.fan_out(topic, frame)
+    }
+
+    /// Deliver a message addressed to the node to clients on `DIRECT_TOPIC`
+    pub fn deliver_direct(&self, message: &ACPMessage) -> usize {
+        let frame = ServerFrame::Direct {
+            from: message.from.clone(),
+            message_type: message.message_type.clone(),
+            payload: json_payload(&message.payload),
+        };
+        self.fan_out(DIRECT_TOPIC, frame)
+    }
+
+    /// Pass topic and direct messages from the node's events on to clients
+    /// until the bus closes
+    pub async fn follow(self: Arc<Self>, mut events: broadcast::Receiver<ACPEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(ACPEvent::TopicMessage { message, .. }) => {
+                    self.deliver_topic(&message);
+                }
+                Ok(ACPEvent::MessageReceived { message }) => {
+                    self.deliver_direct(&message);
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!("Gateway fell behind the node and missed {} events", missed);
+                    self.stats.write().dropped += missed;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+}
+
+/// Payload bytes as JSON for clients: parsed if they are JSON, otherwise
+/// an array of byte values
+pub fn json_payload(bytes: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(bytes).unwrap_or_else(|_| serde_json::Value::from(bytes.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+
+    async fn connect(addr: SocketAddr) -> tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>> {
+        tokio_tungstenite::connect_async(format!("ws://{}", addr)).await.unwrap().0
+    }
+
+    async fn next_frame(socket: &mut tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>>) -> ServerFrame {
+        loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clients_subscribe_publish_and_receive() {
+        let (gateway, mut requests) = Gateway::new(GatewayConfig::default());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(gateway.clone().run(listener));
+
+        let mut alice = connect(addr).await;
+        let mut bob = connect(addr).await;
+        for socket in [&mut alice, &mut bob] {
+            socket.send(Message::Text(r#"{"op":"subscribe","topic":"rfq.data_analysis"}"#.to_string())).await.unwrap();
+            assert_eq!(next_frame(socket).await, ServerFrame::Subscribed { topic: "rfq.data_analysis".to_string() });
+        }
+        // Only the first subscriber has the node join the topic
+        assert_eq!(requests.recv().await.unwrap(), GatewayRequest::Subscribe { topic: "rfq.data_analysis".to_string() });
+
+        alice.send(Message::Text(r#"{"op":"publish","topic":"rfq.data_analysis","payload":{"budget":5}}"#.to_string())).await.unwrap();
+        match requests.recv().await.unwrap() {
+            GatewayRequest::Publish { topic, payload, .. } => {
+                assert_eq!(topic, "rfq.data_analysis");
+                assert_eq!(payload["budget"], 5);
+            }
+            other => panic!("unexpected request {:?}", other),
+        }
+
+        let bus = EventBus::default();
+        tokio::spawn(gateway.clone().follow(bus.subscribe()));
+        let message = GossipMessage::new(crate::gossip::GossipMessageType::Publish, "node-7".to_string(), serde_json::json!({"quote": 4}), 3)
+            .on_topic("rfq.data_analysis");
+        bus.publish(ACPEvent::TopicMessage { topic: "rfq.data_analysis".to_string(), message });
+        for socket in [&mut alice, &mut bob] {
+            match next_frame(socket).await {
+                ServerFrame::Message { sender, payload, .. } => assert_eq!((sender.as_str(), payload["quote"].as_i64()), ("node-7", Some(4))),
+                other => panic!("unexpected frame {:?}", other),
+            }
+        }
+
+        bob.send(Message::Text("not json".to_string())).await.unwrap();
+        assert!(matches!(next_frame(&mut bob).await, ServerFrame::Error { .. }));
+
+        drop(alice);
+        bob.send(Message::Text(r#"{"op":"unsubscribe","topic":"rfq.data_analysis"}"#.to_string())).await.unwrap();
+        assert_eq!(requests.recv().await.unwrap(), GatewayRequest::Unsubscribe { topic: "rfq.data_analysis".to_string() });
+    }
+}
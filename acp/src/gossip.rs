@@ -2,15 +2,75 @@
 //!
 //! Implements efficient information dissemination across the Solace Protocol network
 //! using epidemiological gossip algorithms for scalable peer-to-peer communication.
+//!
+//! Messages go out in the codec negotiated with each peer (see `codec`), and
+//! the statistics count both the bytes on the wire and their size before
+//! compression.
+//!
+//! `GossipMode` chooses how messages spread. Push sends each message on to
+//! `fanout` peers as it arrives. Pull leaves messages in the cache and
+//! periodically asks random neighbors for the IDs of their recent messages,
+//! fetching only the ones it has not seen, which avoids the redundant copies
+//! push floods a dense network with. PushPull pushes to half the fanout and
+//! lets pull rounds fill the gaps.
+//!
+//! Messages published to a topic (see `topics`) bypass the modes above and
+//! travel only along that topic's mesh of subscribed peers.
+//!
+//! `broadcast_cancellable`, `publish_cancellable`, and
+//! `gossip_message_cancellable` stop queuing a message for its targets
+//! when their token fires, for instance while a full outbound queue holds
+//! them back. A message no target was given is forgotten, as if it had
+//! never been gossiped; one some targets already have spreads from them.
+//!
+//! Messages from other nodes reach consumers as events on the node's bus:
+//! `TopicMessage` for topic messages and `GossipReceived` for the rest.
+//!
+//! Incoming messages are charged to the peer that relayed them against the
+//! per-peer quotas in `ratelimit`; over-quota messages are dropped and
+//! persistent offenders muted.
+//!
+//! With a `MisbehaviorDetector` attached, over-quota gossip is also charged
+//! to the relaying peer as a flood, quarantined peers are ignored outright,
+//! and the detector's signed `MisbehaviorReport`s are gossiped. Received
+//! reports are verified and counted; a report that fails verification is
+//! charged to the peer that relayed it and goes no further, and misbehavior
+//! confirmed by enough reporters is broadcast as a reputation update.
+//!
+//! Privacy tiers (see `privacy`) limit where messages may go. Peers are
+//! tagged with the domains they belong to, and every way a message leaves
+//! this node (push, forwarding, topic meshes, and pull digests and fetches)
+//! skips peers its tiers do not permit.
+//!
+//! Every `heartbeat_interval` each peer is sent a heartbeat carrying a nonce,
+//! which it echoes straight back. The round trip updates the peer's
+//! `latency`, smoothed the way TCP smooths its RTT, and is published on the
+//! event bus. A peer that leaves `max_missed_heartbeats` heartbeats in a row
+//! unanswered, without sending anything else either, is marked inactive and
+//! reported timed out until it is heard from again.
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::RwLock;
 use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn, debug, error};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::codec::{CodecCapabilities, Compression, WireCodec};
+use crate::events::{ACPEvent, EventBus};
+use crate::journal::{JournalConfig, JournalWriter, MessageJournal};
+use crate::messaging::MessagePriority;
+use crate::misbehavior::{MisbehaviorDetector, MisbehaviorReport, Violation};
+use crate::privacy::{PrivacyPolicy, PrivacyTier};
+use crate::queue::{self, QueueConfig, QueueMetrics, QueueReceiver, QueueSender};
+use crate::ratelimit::{RateDecision, RateLimitConfig, RateLimiter};
+use crate::rng::NodeRng;
+use crate::stats::ShardedCounter;
+use crate::topics::TopicMesh;
 
 /// Gossip message types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -19,11 +79,33 @@ pub enum GossipMessageType {
     TransactionBroadcast,
     StateUpdate,
     HeartBeat,
+    HeartBeatAck,                         // Echoes a heartbeat's nonce back to its sender
     RoutingUpdate,
     ReputationUpdate,
+    QuoteRequest,                         // RFQ intent on a capability topic
+    Quote,                                // Binding quote answering an RFQ
+    MarketObservations,                   // Signed observations on a trust domain topic
+    PullRequest,                          // Asks a neighbor for its recent message IDs
+    PullDigest,                           // Recent message IDs, answering a pull request
+    PullFetch,                            // Message IDs wanted from a digest
+    Subscribe,                            // Peer joined a topic
+    Unsubscribe,                          // Peer left a topic
+    Publish,                              // Message on a topic
+    MisbehaviorReport,                    // Signed report of a quarantined peer
     Custom(String),
 }
 
+impl GossipMessageType {
+    /// Priority in the outbound queue; a full queue sheds the lowest first
+    pub fn priority(&self) -> MessagePriority {
+        match self {
+            Self::TransactionBroadcast | Self::Quote | Self::MisbehaviorReport | Self::ReputationUpdate => MessagePriority::High,
+            Self::HeartBeat | Self::HeartBeatAck | Self::PullRequest | Self::PullDigest | Self::PullFetch => MessagePriority::Low,
+            _ => MessagePriority::Normal,
+        }
+    }
+}
+
 /// Gossip message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GossipMessage {
@@ -36,6 +118,10 @@ pub struct GossipMessage {
     pub payload: serde_json::Value,
     pub signature: Option<String>,
     pub routing_path: Vec<String>,
+    #[serde(default)]
+    pub topic: Option<String>,            // Set on messages published to a topic
+    #[serde(default)]
+    pub privacy: Vec<PrivacyTier>,        // Stamped by the origin; every tier must permit a peer
 }
 
 impl GossipMessage {
@@ -56,9 +142,17 @@ impl GossipMessage {
             payload,
             signature: None,
             routing_path: Vec::new(),
+            topic: None,
+            privacy: Vec::new(),
         }
     }
 
+    /// Place the message on a topic
+    pub fn on_topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = Some(topic.into());
+        self
+    }
+
     /// Check if message has expired
     pub fn is_expired(&self) -> bool {
         self.ttl == 0 || self.hop_count > 10 // Max hop limit
@@ -78,6 +172,14 @@ impl GossipMessage {
     }
 }
 
+/// How messages spread through the network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GossipMode {
+    Push,                                 // Forward each message to `fanout` peers
+    Pull,                                 // Only fetch unseen messages from neighbors' digests
+    PushPull,                             // Push to half the fanout, pull the rest
+}
+
 /// Gossip configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GossipConfig {
@@ -87,8 +189,19 @@ pub struct GossipConfig {
     pub max_message_cache: usize,         // Max messages to cache
     pub duplicate_window: Duration,       // Window for duplicate detection
     pub heartbeat_interval: Duration,     // Heartbeat frequency
+    pub max_missed_heartbeats: u32,       // Unanswered heartbeats in a row before a peer is dead
     pub enable_anti_entropy: bool,        // Enable anti-entropy protocol
-    pub compression: bool,                // Enable message compression
+    pub compression: bool,                // Offer LZ4/zstd compression to peers
+    pub compression_threshold: usize,     // Smallest encoded message worth compressing
+    pub journal: Option<JournalConfig>,   // Durable journal for critical messages
+    pub rng_seed: Option<u64>,            // Base seed for peer selection (None = SOLACE_SEED or random)
+    pub mode: GossipMode,
+    pub pull_interval: Duration,          // How often to pull in Pull and PushPull modes
+    pub pull_digest_size: usize,          // Most recent message IDs offered per digest
+    pub topic_mesh_degree: usize,         // Target peers per topic mesh
+    pub rate_limit: Option<RateLimitConfig>,  // Per-peer incoming quotas (None = unlimited)
+    pub privacy: PrivacyPolicy,           // Propagation limits by topic and message type
+    pub outbound_queue: QueueConfig,      // Bound and backpressure policy for messages awaiting send
 }
 
 impl Default for GossipConfig {
@@ -100,8 +213,19 @@ impl Default for GossipConfig {
             max_message_cache: 1000,
             duplicate_window: Duration::from_secs(60),
             heartbeat_interval: Duration::from_secs(30),
+            max_missed_heartbeats: 3,
             enable_anti_entropy: true,
             compression: false,
+            compression_threshold: 512,
+            journal: None,
+            rng_seed: None,
+            mode: GossipMode::Push,
+            pull_interval: Duration::from_secs(2),
+            pull_digest_size: 256,
+            topic_mesh_degree: 6,
+            rate_limit: Some(RateLimitConfig::default()),
+            privacy: PrivacyPolicy::default(),
+            outbound_queue: QueueConfig::default(),
         }
     }
 }
@@ -114,11 +238,78 @@ pub struct GossipStats {
     pub messages_forwarded: u64,
     pub duplicates_filtered: u64,
     pub expired_messages: u64,
-    pub bytes_sent: u64,
+    pub bytes_sent: u64,                  // On the wire, after compression
     pub bytes_received: u64,
+    pub uncompressed_bytes_sent: u64,     // Encoded size before compression
+    pub uncompressed_bytes_received: u64,
+    pub pull_requests_sent: u64,
+    pub messages_fetched: u64,            // Sent to peers that pulled them
+    pub rate_limited_drops: u64,          // Dropped for exceeding a peer's quota
+    pub muted_drops: u64,                 // Dropped because the peer was muted
+    pub quarantined_drops: u64,           // Dropped because the peer is quarantined
     pub active_peers: usize,
+    pub outbound_queue: QueueMetrics,     // Messages awaiting send and what backpressure cost
 }
 
+impl GossipStats {
+    /// Wire bytes per uncompressed byte, sent and received together
+    pub fn compression_ratio(&self) -> f64 {
+        let uncompressed = self.uncompressed_bytes_sent + self.uncompressed_bytes_received;
+        if uncompressed == 0 {
+            1.0
+        } else {
+            (self.bytes_sent + self.bytes_received) as f64 / uncompressed as f64
+        }
+    }
+}
+
+/// Live gossip counters, aggregated into `GossipStats` on read
+#[derive(Debug, Default)]
+struct GossipCounters {
+    messages_sent: ShardedCounter,
+    messages_received: ShardedCounter,
+    messages_forwarded: ShardedCounter,
+    duplicates_filtered: ShardedCounter,
+    expired_messages: ShardedCounter,
+    bytes_sent: ShardedCounter,
+    bytes_received: ShardedCounter,
+    uncompressed_bytes_sent: ShardedCounter,
+    uncompressed_bytes_received: ShardedCounter,
+    pull_requests_sent: ShardedCounter,
+    messages_fetched: ShardedCounter,
+    rate_limited_drops: ShardedCounter,
+    muted_drops: ShardedCounter,
+    quarantined_drops: ShardedCounter,
+    active_peers: AtomicUsize,
+}
+
+impl GossipCounters {
+    /// Take a snapshot of the current counter values
+    fn snapshot(&self) -> GossipStats {
+        GossipStats {
+            messages_sent: self.messages_sent.get(),
+            messages_received: self.messages_received.get(),
+            messages_forwarded: self.messages_forwarded.get(),
+            duplicates_filtered: self.duplicates_filtered.get(),
+            expired_messages: self.expired_messages.get(),
+            bytes_sent: self.bytes_sent.get(),
+            bytes_received: self.bytes_received.get(),
+            uncompressed_bytes_sent: self.uncompressed_bytes_sent.get(),
+            uncompressed_bytes_received: self.uncompressed_bytes_received.get(),
+            pull_requests_sent: self.pull_requests_sent.get(),
+            messages_fetched: self.messages_fetched.get(),
+            rate_limited_drops: self.rate_limited_drops.get(),
+            muted_drops: self.muted_drops.get(),
+            quarantined_drops: self.quarantined_drops.get(),
+            active_peers: self.active_peers.load(Ordering::Relaxed),
+            ..GossipStats::default()
+        }
+    }
+}
+
+/// Weight of each new round-trip sample in a peer's smoothed latency
+const RTT_SMOOTHING: f64 = 0.125;
+
 /// Peer information for gossip
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GossipPeer {
@@ -126,7 +317,12 @@ pub struct GossipPeer {
     pub last_seen: Instant,
     pub message_count: u64,
     pub is_active: bool,
-    pub latency: Duration,
+    pub latency: Duration,                // Smoothed heartbeat round trip
+    pub rtt_samples: u64,                 // Heartbeat round trips measured
+    pub pending_heartbeat: Option<(u64, Instant)>,  // Nonce and send time of the unanswered heartbeat
+    pub missed_heartbeats: u32,           // Heartbeats in a row left unanswered
+    pub codec: WireCodec,                 // Negotiated from the peer's advertised capabilities
+    pub domains: HashSet<String>,         // Jurisdictions and trust domains, for privacy tiers
 }
 
 /// Message cache entry
@@ -137,47 +333,99 @@ struct CacheEntry {
     forwarded_to: HashSet<String>,
 }
 
+/// Copies of a journaled message queued for peers, tracked so its journal
+/// entry is acknowledged only once they are on the wire
+#[derive(Debug, Default)]
+struct JournaledSend {
+    in_flight: usize,                     // Copies queued but not yet sent
+    copies: usize,                        // Copies queued in total
+    sent: bool,                           // At least one copy reached a peer
+    queued: bool,                         // The sender has queued every copy
+}
+
+/// Journaled messages with copies still in the outbound queue, by id
+type JournaledSends = Arc<parking_lot::Mutex<HashMap<String, JournaledSend>>>;
+
 /// Gossip protocol implementation
 pub struct GossipProtocol {
     node_id: String,
     config: GossipConfig,
     peers: Arc<RwLock<HashMap<String, GossipPeer>>>,
     message_cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
-    stats: Arc<RwLock<GossipStats>>,
-    message_handlers: HashMap<GossipMessageType, Box<dyn Fn(&GossipMessage) -> Result<()> + Send + Sync>>,
-    outbound_tx: mpsc::UnboundedSender<(String, GossipMessage)>,
-    outbound_rx: Option<mpsc::UnboundedReceiver<(String, GossipMessage)>>,
+    stats: Arc<GossipCounters>,
+    events: EventBus,
+    topics: Arc<RwLock<TopicMesh>>,
+    outbound_tx: QueueSender<(String, GossipMessage)>,
+    outbound_rx: Option<QueueReceiver<(String, GossipMessage)>>,
+    journal: Option<JournalWriter>,
+    journaled_sends: JournaledSends,
+    rate_limiter: Option<parking_lot::Mutex<RateLimiter>>,
+    misbehavior: Option<Arc<MisbehaviorDetector>>,
+    rng: Arc<NodeRng>,
+    heartbeat_nonce: Arc<AtomicU64>,
 }
 
 impl GossipProtocol {
     /// Create a new gossip protocol instance
     pub fn new(node_id: String, config: GossipConfig) -> Self {
-        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
+        let (outbound_tx, outbound_rx) = queue::bounded(config.outbound_queue.clone());
+        let rng = Arc::new(NodeRng::for_node(config.rng_seed, &node_id));
+        let topics = Arc::new(RwLock::new(TopicMesh::new(config.topic_mesh_degree)));
+        
+        let rate_limiter = config.rate_limit.clone().map(|limits| parking_lot::Mutex::new(RateLimiter::new(limits)));
         
         Self {
             node_id,
             config,
             peers: Arc::new(RwLock::new(HashMap::new())),
             message_cache: Arc::new(RwLock::new(HashMap::new())),
-            stats: Arc::new(RwLock::new(GossipStats::default())),
-            message_handlers: HashMap::new(),
+            stats: Arc::new(GossipCounters::default()),
+            events: EventBus::default(),
+            topics,
             outbound_tx,
             outbound_rx: Some(outbound_rx),
+            journal: None,
+            journaled_sends: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            rate_limiter,
+            misbehavior: None,
+            rng,
+            heartbeat_nonce: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Publish every message from other nodes on the node's bus
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Charge violations to, and gossip reports from, `detector`
+    pub fn with_misbehavior(mut self, detector: Arc<MisbehaviorDetector>) -> Self {
+        self.misbehavior = Some(detector);
+        self
+    }
+
     /// Start the gossip protocol
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting gossip protocol for node: {}", self.node_id);
         
-        // Start periodic gossip
-        self.start_periodic_gossip().await;
+        // Start heartbeats
+        self.start_heartbeats().await;
+        if self.config.mode != GossipMode::Push {
+            self.start_pull_rounds().await;
+        }
+        
+        // Open the journal first, so the processor acknowledges what it sends
+        let unsent = self.open_journal().await?;
         
         // Start message processing
         if let Some(rx) = self.outbound_rx.take() {
             self.start_message_processor(rx).await;
         }
         
+        // Recover messages that were not sent before the last shutdown
+        self.replay_journal(unsent).await?;
+        
         // Start maintenance tasks
         self.start_maintenance_tasks().await;
         
@@ -191,14 +439,23 @@ impl GossipProtocol {
             last_seen: Instant::now(),
             message_count: 0,
             is_active: true,
-            latency: Duration::from_millis(50), // Default latency
+            latency: Duration::from_millis(50), // Default latency until a heartbeat is answered
+            rtt_samples: 0,
+            pending_heartbeat: None,
+            missed_heartbeats: 0,
+            codec: WireCodec { compression_threshold: self.config.compression_threshold, ..WireCodec::default() },
+            domains: HashSet::new(),
         };
         
         let mut peers = self.peers.write().await;
         peers.insert(peer_id.clone(), peer);
+        self.stats.active_peers.store(peers.len(), Ordering::Relaxed);
+        drop(peers);
         
-        let mut stats = self.stats.write().await;
-        stats.active_peers = peers.len();
+        // Tell the new peer what we subscribe to
+        for topic in self.topics.read().await.subscriptions() {
+            self.send_subscription(&peer_id, GossipMessageType::Subscribe, &topic).await;
+        }
         
         debug!("Added gossip peer: {}", peer_id);
     }
@@ -207,12 +464,139 @@ impl GossipProtocol {
     pub async fn remove_peer(&self, peer_id: &str) {
         let mut peers = self.peers.write().await;
         if peers.remove(peer_id).is_some() {
-            let mut stats = self.stats.write().await;
-            stats.active_peers = peers.len();
+            self.stats.active_peers.store(peers.len(), Ordering::Relaxed);
+            self.topics.write().await.remove_peer(peer_id, &self.rng);
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.lock().forget(peer_id);
+            }
             debug!("Removed gossip peer: {}", peer_id);
         }
     }
 
+    /// Subscribe to a topic and announce it to every peer
+    pub async fn subscribe(&self, topic: &str) {
+        if !self.topics.write().await.subscribe(topic, &self.rng) {
+            return;
+        }
+        let peer_ids: Vec<String> = self.peers.read().await.keys().cloned().collect();
+        for peer_id in &peer_ids {
+            self.send_subscription(peer_id, GossipMessageType::Subscribe, topic).await;
+        }
+        info!("Subscribed to topic {}", topic);
+    }
+
+    /// Leave a topic and tell every peer
+    pub async fn unsubscribe(&self, topic: &str) {
+        if !self.topics.write().await.unsubscribe(topic) {
+            return;
+        }
+        let peer_ids: Vec<String> = self.peers.read().await.keys().cloned().collect();
+        for peer_id in &peer_ids {
+            self.send_subscription(peer_id, GossipMessageType::Unsubscribe, topic).await;
+        }
+        info!("Unsubscribed from topic {}", topic);
+    }
+
+    /// Topics this node subscribes to
+    pub async fn subscriptions(&self) -> Vec<String> {
+        self.topics.read().await.subscriptions()
+    }
+
+    /// Peers in our mesh for a topic
+    pub async fn topic_mesh(&self, topic: &str) -> Vec<String> {
+        self.topics.read().await.mesh_peers(topic)
+    }
+
+    /// Publish a message to a topic's subscribers
+    pub async fn publish(&self, topic: &str, payload: serde_json::Value) -> Result<()> {
+        let message = self.topic_message(topic, payload);
+        self.gossip_with(message, None).await
+    }
+
+    /// `publish` that stops queuing the message when `cancel` fires
+    pub async fn publish_cancellable(&self, topic: &str, payload: serde_json::Value, cancel: &CancellationToken) -> Result<()> {
+        let message = self.topic_message(topic, payload);
+        self.gossip_with(message, Some(cancel)).await
+    }
+
+    fn topic_message(&self, topic: &str, payload: serde_json::Value) -> GossipMessage {
+        GossipMessage::new(
+            GossipMessageType::Publish,
+            self.node_id.clone(),
+            payload,
+            self.config.message_ttl,
+        ).on_topic(topic)
+    }
+
+    async fn send_subscription(&self, peer_id: &str, message_type: GossipMessageType, topic: &str) {
+        let message = GossipMessage::new(message_type, self.node_id.clone(), serde_json::json!({}), 1).on_topic(topic);
+        if let Err(e) = self.queue_outbound(peer_id, message).await {
+            error!("Failed to queue subscription for peer {}: {}", peer_id, e);
+        }
+    }
+
+    /// Record a peer joining or leaving a topic
+    async fn handle_subscription(&self, message: GossipMessage) -> Result<()> {
+        let topic = message.topic.as_deref().ok_or_else(|| anyhow!("Subscription without a topic"))?;
+        let mut topics = self.topics.write().await;
+        if message.message_type == GossipMessageType::Subscribe {
+            topics.peer_subscribed(&message.sender_id, topic, &self.rng);
+        } else {
+            topics.peer_unsubscribed(&message.sender_id, topic, &self.rng);
+        }
+        drop(topics);
+        
+        self.update_peer_info(&message.sender_id).await;
+        Ok(())
+    }
+
+    /// Codecs this node offers to peers
+    pub fn codec_capabilities(&self) -> CodecCapabilities {
+        let mut capabilities = CodecCapabilities::default();
+        if !self.config.compression {
+            capabilities.compressions = vec![Compression::None];
+        }
+        capabilities
+    }
+
+    /// Negotiate the codec for a peer from the capabilities it advertised
+    pub async fn set_peer_capabilities(&self, peer_id: &str, remote: &CodecCapabilities) {
+        let codec = self.codec_capabilities().negotiate(remote, self.config.compression_threshold);
+        if let Some(peer) = self.peers.write().await.get_mut(peer_id) {
+            debug!("Using {:?}/{:?} for gossip peer {}", codec.encoding, codec.compression, peer_id);
+            peer.codec = codec;
+        }
+    }
+
+    /// Tag a peer with the domains it belongs to, replacing earlier tags
+    pub async fn set_peer_domains<I, S>(&self, peer_id: &str, domains: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        if let Some(peer) = self.peers.write().await.get_mut(peer_id) {
+            peer.domains = domains.into_iter().map(Into::into).collect();
+        }
+    }
+
+    /// Domains a peer is tagged with; none if it is unknown
+    async fn peer_domains(&self, peer_id: &str) -> HashSet<String> {
+        self.peers.read().await.get(peer_id).map(|peer| peer.domains.clone()).unwrap_or_default()
+    }
+
+    /// Encode a message in a peer's codec, counting it as sent
+    pub async fn encode_for_peer(&self, peer_id: &str, message: &GossipMessage) -> Result<Vec<u8>> {
+        let codec = self.peers.read().await.get(peer_id).map(|peer| peer.codec).unwrap_or_default();
+        Self::encode_counted(&self.stats, codec, message)
+    }
+
+    fn encode_counted(stats: &GossipCounters, codec: WireCodec, message: &GossipMessage) -> Result<Vec<u8>> {
+        let frame = codec.encode(message)?;
+        stats.bytes_sent.add(frame.bytes.len() as u64);
+        stats.uncompressed_bytes_sent.add(frame.uncompressed_len as u64);
+        Ok(frame.bytes)
+    }
+
     /// Broadcast a message to the network
     pub async fn broadcast(&self, message_type: GossipMessageType, payload: serde_json::Value) -> Result<()> {
         let message = GossipMessage::new(
@@ -222,76 +606,590 @@ impl GossipProtocol {
             self.config.message_ttl,
         );
         
-        self.gossip_message(message).await
+        self.gossip_with(message, None).await
+    }
+
+    /// `broadcast` that stops queuing the message when `cancel` fires
+    pub async fn broadcast_cancellable(
+        &self,
+        message_type: GossipMessageType,
+        payload: serde_json::Value,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        let message = GossipMessage::new(message_type, self.node_id.clone(), payload, self.config.message_ttl);
+        self.gossip_with(message, Some(cancel)).await
     }
 
     /// Gossip a specific message
     pub async fn gossip_message(&self, message: GossipMessage) -> Result<()> {
+        self.gossip_with(message, None).await
+    }
+
+    /// `gossip_message` that stops queuing the message for its targets when
+    /// `cancel` fires. Either way a cancelled message is not replayed after
+    /// a crash.
+    pub async fn gossip_message_cancellable(&self, message: GossipMessage, cancel: &CancellationToken) -> Result<()> {
+        self.gossip_with(message, Some(cancel)).await
+    }
+
+    async fn gossip_with(&self, mut message: GossipMessage, cancel: Option<&CancellationToken>) -> Result<()> {
+        if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(anyhow!("Gossip of message {} cancelled", message.id));
+        }
+        // Later hops enforce the origin's tiers even if their own policy is looser
+        let tiers: Vec<PrivacyTier> = self.config.privacy.tiers_for(&message).cloned().collect();
+        for tier in tiers {
+            if !message.privacy.contains(&tier) {
+                message.privacy.push(tier);
+            }
+        }
+        self.journal_append(&message).await?;
+        let message_id = message.id.clone();
+        
+        let completed = self.send_to_gossip_targets(message, cancel).await;
+        
+        if !completed {
+            self.journal_ack(&message_id);
+            return Err(anyhow!("Gossip of message {} cancelled", message_id));
+        }
+        self.journal_queued(&message_id);
+        Ok(())
+    }
+
+    /// Cache a message and queue it for the selected gossip targets; false
+    /// if `cancel` fired first, dropping the message from the cache when no
+    /// target was given it
+    async fn send_to_gossip_targets(&self, message: GossipMessage, cancel: Option<&CancellationToken>) -> bool {
         // Cache the message
         self.cache_message(message.clone()).await;
         
         // Select peers to gossip to
-        let target_peers = self.select_gossip_targets().await;
+        let target_peers = match &message.topic {
+            Some(topic) => self.select_topic_targets(&message, topic).await,
+            None => self.select_gossip_targets(&message).await,
+        };
         
         // Send to selected peers
+        let mut queued = 0;
         for peer_id in target_peers {
-            if let Err(e) = self.outbound_tx.send((peer_id.clone(), message.clone())) {
-                error!("Failed to queue message for peer {}: {}", peer_id, e);
+            let result = match cancel {
+                Some(cancel) => tokio::select! {
+                    result = self.queue_outbound(&peer_id, message.clone()) => result,
+                    _ = cancel.cancelled() => {
+                        if queued == 0 {
+                            self.message_cache.write().await.remove(&message.id);
+                        } else {
+                            self.stats.messages_sent.increment();
+                        }
+                        return false;
+                    }
+                },
+                None => self.queue_outbound(&peer_id, message.clone()).await,
+            };
+            match result {
+                Ok(()) => queued += 1,
+                Err(e) => error!("Failed to queue message for peer {}: {}", peer_id, e),
             }
         }
         
-        let mut stats = self.stats.write().await;
-        stats.messages_sent += 1;
-        
-        Ok(())
+        self.stats.messages_sent.increment();
+        true
     }
 
     /// Process incoming gossip message
     pub async fn handle_incoming_message(&self, message: GossipMessage) -> Result<()> {
-        let mut stats = self.stats.write().await;
-        stats.messages_received += 1;
-        stats.bytes_received += serde_json::to_vec(&message)?.len() as u64;
-        drop(stats);
+        let size = serde_json::to_vec(&message)?.len() as u64;
+        self.stats.bytes_received.add(size);
+        self.stats.uncompressed_bytes_received.add(size);
+        let admitted = self.admit(&message, size as usize);
+        self.publish_misbehavior_reports().await?;
+        if !admitted {
+            return Ok(());
+        }
+        self.receive(message).await
+    }
+
+    /// Decode and process an incoming wire frame in any codec
+    pub async fn handle_incoming_frame(&self, frame: &[u8]) -> Result<()> {
+        let (message, uncompressed_len) = WireCodec::decode(frame)?;
+        self.stats.bytes_received.add(frame.len() as u64);
+        self.stats.uncompressed_bytes_received.add(uncompressed_len as u64);
+        let admitted = self.admit(&message, frame.len());
+        self.publish_misbehavior_reports().await?;
+        if !admitted {
+            return Ok(());
+        }
+        self.receive(message).await
+    }
+
+    /// Gossip the reports the misbehavior detector produced since the last
+    /// call, returning how many went out
+    pub async fn publish_misbehavior_reports(&self) -> Result<usize> {
+        let Some(detector) = &self.misbehavior else {
+            return Ok(0);
+        };
+        let reports = detector.take_reports();
+        for report in &reports {
+            self.broadcast(GossipMessageType::MisbehaviorReport, serde_json::to_value(report)?).await?;
+        }
+        Ok(reports.len())
+    }
+
+    /// Count a received misbehavior report, returning whether to pass it on
+    async fn handle_misbehavior_report(&self, message: &GossipMessage) -> Result<bool> {
+        let Some(detector) = &self.misbehavior else {
+            return Ok(true);
+        };
+        let relayed_by = message.routing_path.last().unwrap_or(&message.sender_id);
+        let report = match serde_json::from_value::<MisbehaviorReport>(message.payload.clone()) {
+            Ok(report) => report,
+            Err(e) => {
+                warn!("Dropping malformed misbehavior report relayed by {}: {}", relayed_by, e);
+                detector.record(relayed_by, Violation::MalformedMessage, Instant::now());
+                return Ok(false);
+            }
+        };
+        match detector.ingest_report(report, Instant::now()) {
+            Ok(Some(confirmed)) => {
+                let update = serde_json::to_value(confirmed.reputation_update())?;
+                self.broadcast(GossipMessageType::ReputationUpdate, update).await?;
+                Ok(true)
+            }
+            Ok(None) => Ok(true),
+            Err(e) => {
+                warn!("Dropping misbehavior report relayed by {}: {}", relayed_by, e);
+                detector.record(relayed_by, Violation::InvalidSignature, Instant::now());
+                Ok(false)
+            }
+        }
+    }
+
+    /// Charge a message to the quota of the peer that relayed it, counting
+    /// it as dropped if over quota
+    fn admit(&self, message: &GossipMessage, bytes: usize) -> bool {
+        let peer_id = message.routing_path.last().unwrap_or(&message.sender_id);
+        if let Some(detector) = &self.misbehavior {
+            if detector.is_quarantined(peer_id, Instant::now()) {
+                self.stats.quarantined_drops.increment();
+                return false;
+            }
+        }
+        let Some(limiter) = &self.rate_limiter else {
+            return true;
+        };
+        match limiter.lock().check(peer_id, bytes, Instant::now()) {
+            RateDecision::Allowed => true,
+            RateDecision::Limited => {
+                debug!("Rate limited gossip from peer {}", peer_id);
+                self.stats.rate_limited_drops.increment();
+                if let Some(detector) = &self.misbehavior {
+                    detector.record(peer_id, Violation::GossipFlood, Instant::now());
+                }
+                false
+            }
+            RateDecision::Muted => {
+                self.stats.muted_drops.increment();
+                false
+            }
+        }
+    }
+
+    /// Lift the mute on a peer that exceeded its quotas
+    pub fn unmute_peer(&self, peer_id: &str) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.lock().unmute(peer_id);
+        }
+    }
+
+    async fn receive(&self, message: GossipMessage) -> Result<()> {
+        self.stats.messages_received.increment();
+        
+        // Pull exchanges are answered directly, never cached or forwarded
+        if matches!(
+            message.message_type,
+            GossipMessageType::PullRequest | GossipMessageType::PullDigest | GossipMessageType::PullFetch
+        ) {
+            return self.handle_pull(message).await;
+        }
+        if matches!(message.message_type, GossipMessageType::Subscribe | GossipMessageType::Unsubscribe) {
+            return self.handle_subscription(message).await;
+        }
+        if matches!(message.message_type, GossipMessageType::HeartBeat | GossipMessageType::HeartBeatAck) {
+            return self.handle_heartbeat(message).await;
+        }
         
         // Check for duplicates
         if self.is_duplicate(&message).await {
-            let mut stats = self.stats.write().await;
-            stats.duplicates_filtered += 1;
+            self.stats.duplicates_filtered.increment();
             return Ok(());
         }
         
         // Check if expired
         if message.is_expired() {
-            let mut stats = self.stats.write().await;
-            stats.expired_messages += 1;
+            self.stats.expired_messages.increment();
+            return Ok(());
+        }
+        
+        if message.message_type == GossipMessageType::MisbehaviorReport && !self.handle_misbehavior_report(&message).await? {
             return Ok(());
         }
         
-        // Process the message
-        self.process_message(&message).await?;
+        // Journal critical messages before acting on them
+        self.journal_append(&message).await?;
+        
+        self.process_and_forward(message).await
+    }
+
+    /// Publish a message and forward it; its journal entry is settled once
+    /// the forwarded copies are sent
+    async fn process_and_forward(&self, message: GossipMessage) -> Result<()> {
+        let message_id = message.id.clone();
+        
+        // Remember it, so copies pushed or pulled later are recognized
+        self.cache_message(message.clone()).await;
+        
+        // Process the message; on failure its journal entry stays pending
+        if let Err(e) = self.process_message(&message).await {
+            self.journaled_sends.lock().remove(&message_id);
+            return Err(e);
+        }
         
         // Forward the message if appropriate
         if self.should_forward(&message).await {
             self.forward_message(message).await?;
         }
         
+        self.journal_queued(&message_id);
         Ok(())
     }
 
-    /// Register a message handler
-    pub fn register_handler<F>(&mut self, message_type: GossipMessageType, handler: F)
-    where
-        F: Fn(&GossipMessage) -> Result<()> + Send + Sync + 'static,
-    {
-        self.message_handlers.insert(message_type, Box::new(handler));
+    /// Re-send journaled messages that were never acknowledged
+    ///
+    /// Replayed messages from other peers are published again, so delivery
+    /// to consumers is at-least-once across a crash.
+    async fn replay_journal(&self, messages: Vec<GossipMessage>) -> Result<()> {
+        for message in messages {
+            self.journaled_sends.lock().insert(message.id.clone(), JournaledSend::default());
+            if message.sender_id == self.node_id {
+                let message_id = message.id.clone();
+                self.send_to_gossip_targets(message, None).await;
+                self.journal_queued(&message_id);
+            } else {
+                self.process_and_forward(message).await?;
+            }
+        }
+        
+        Ok(())
     }
 
-    /// Process a message using registered handlers
-    async fn process_message(&self, message: &GossipMessage) -> Result<()> {
-        if let Some(handler) = self.message_handlers.get(&message.message_type) {
-            handler(message)?;
+    /// Ask random neighbors for their recent message IDs, returning how many
+    /// were asked
+    pub async fn pull_round(&self) -> usize {
+        Self::request_digests(&self.node_id, &self.peers, &self.rng, &self.outbound_tx, &self.stats, self.config.fanout).await
+    }
+
+    async fn request_digests(
+        node_id: &str,
+        peers: &RwLock<HashMap<String, GossipPeer>>,
+        rng: &NodeRng,
+        outbound_tx: &QueueSender<(String, GossipMessage)>,
+        stats: &GossipCounters,
+        fanout: usize,
+    ) -> usize {
+        let targets = {
+            let peers = peers.read().await;
+            let mut active_peers: Vec<_> = peers.values().filter(|peer| peer.is_active).collect();
+            Self::choose_from(rng, &mut active_peers, fanout)
+        };
+        
+        for peer_id in &targets {
+            let request = GossipMessage::new(GossipMessageType::PullRequest, node_id.to_string(), serde_json::json!({}), 1);
+            if let Err(e) = outbound_tx.send((peer_id.clone(), request), MessagePriority::Low).await {
+                error!("Failed to queue pull request for peer {}: {}", peer_id, e);
+            }
+            stats.pull_requests_sent.increment();
+        }
+        targets.len()
+    }
+
+    /// Send every peer a heartbeat, marking dead those that left
+    /// `max_missed_heartbeats` in a row unanswered, and return how many were sent
+    pub async fn heartbeat_round(&self) -> usize {
+        Self::send_heartbeats(
+            &self.node_id,
+            &self.peers,
+            &self.outbound_tx,
+            &self.events,
+            &self.heartbeat_nonce,
+            self.config.max_missed_heartbeats,
+        ).await
+    }
+
+    async fn send_heartbeats(
+        node_id: &str,
+        peers: &RwLock<HashMap<String, GossipPeer>>,
+        outbound_tx: &QueueSender<(String, GossipMessage)>,
+        events: &EventBus,
+        nonces: &AtomicU64,
+        max_missed: u32,
+    ) -> usize {
+        let now = Instant::now();
+        let mut heartbeats = Vec::new();
+        let mut dead = Vec::new();
+        {
+            let mut peers = peers.write().await;
+            for peer in peers.values_mut() {
+                if peer.pending_heartbeat.is_some() {
+                    peer.missed_heartbeats += 1;
+                    if peer.is_active && peer.missed_heartbeats >= max_missed.max(1) {
+                        peer.is_active = false;
+                        dead.push(peer.id.clone());
+                    }
+                }
+                let nonce = nonces.fetch_add(1, Ordering::Relaxed);
+                peer.pending_heartbeat = Some((nonce, now));
+                heartbeats.push((peer.id.clone(), nonce));
+            }
+        }
+        
+        for peer_id in dead {
+            warn!("Gossip peer {} missed {} heartbeats, marking it dead", peer_id, max_missed);
+            events.publish(ACPEvent::PeerTimeout { peer_id });
+        }
+        for (peer_id, nonce) in &heartbeats {
+            let heartbeat = GossipMessage::new(GossipMessageType::HeartBeat, node_id.to_string(), serde_json::json!({ "nonce": nonce }), 1);
+            if let Err(e) = outbound_tx.send((peer_id.clone(), heartbeat), MessagePriority::Low).await {
+                error!("Failed to queue heartbeat for peer {}: {}", peer_id, e);
+            }
+        }
+        heartbeats.len()
+    }
+
+    /// Echo a heartbeat, or fold an echo of ours into the peer's latency
+    async fn handle_heartbeat(&self, message: GossipMessage) -> Result<()> {
+        let peer_id = message.sender_id.clone();
+        let nonce = message.payload["nonce"].as_u64().ok_or_else(|| anyhow!("Heartbeat without a nonce"))?;
+        
+        if message.message_type == GossipMessageType::HeartBeat {
+            let ack = GossipMessage::new(GossipMessageType::HeartBeatAck, self.node_id.clone(), serde_json::json!({ "nonce": nonce }), 1);
+            self.queue_outbound(&peer_id, ack).await?;
+            self.update_peer_info(&peer_id).await;
+            return Ok(());
+        }
+        
+        let mut peers = self.peers.write().await;
+        let Some(peer) = peers.get_mut(&peer_id) else {
+            return Ok(());
+        };
+        let sent_at = match peer.pending_heartbeat {
+            Some((pending, sent_at)) if pending == nonce => sent_at,
+            _ => {
+                debug!("Ignoring stale heartbeat ack from peer {}", peer_id);
+                return Ok(());
+            }
+        };
+        let sample = sent_at.elapsed();
+        peer.latency = if peer.rtt_samples == 0 {
+            sample
         } else {
-            debug!("No handler registered for message type: {:?}", message.message_type);
+            peer.latency.mul_f64(1.0 - RTT_SMOOTHING) + sample.mul_f64(RTT_SMOOTHING)
+        };
+        peer.rtt_samples += 1;
+        peer.pending_heartbeat = None;
+        peer.missed_heartbeats = 0;
+        peer.is_active = true;
+        peer.last_seen = Instant::now();
+        let rtt_ms = peer.latency.as_secs_f64() * 1000.0;
+        drop(peers);
+        
+        self.events.publish(ACPEvent::PeerRtt { peer_id, rtt_ms });
+        Ok(())
+    }
+
+    /// Smoothed heartbeat round trip to a peer, once one has been measured
+    pub async fn peer_rtt(&self, peer_id: &str) -> Option<Duration> {
+        self.peers.read().await.get(peer_id).filter(|peer| peer.rtt_samples > 0).map(|peer| peer.latency)
+    }
+
+    /// Answer one step of a pull exchange
+    async fn handle_pull(&self, message: GossipMessage) -> Result<()> {
+        let peer_id = message.sender_id.clone();
+        let ids = |message: &GossipMessage| -> Result<Vec<String>> {
+            Ok(serde_json::from_value(message.payload["ids"].clone())?)
+        };
+        
+        match message.message_type {
+            GossipMessageType::PullRequest => {
+                let domains = self.peer_domains(&peer_id).await;
+                let ids = {
+                    // Topic messages only go to the topic's subscribers, private ones only where permitted
+                    let topics = self.topics.read().await;
+                    let cache = self.message_cache.read().await;
+                    let mut recent: Vec<_> = cache
+                        .values()
+                        .filter(|entry| !entry.message.is_expired())
+                        .filter(|entry| entry.message.topic.as_ref().is_none_or(|topic| topics.is_peer_subscribed(&peer_id, topic)))
+                        .filter(|entry| self.config.privacy.permits(&entry.message, &domains))
+                        .collect();
+                    recent.sort_by_key(|entry| std::cmp::Reverse(entry.received_at));
+                    recent.iter().take(self.config.pull_digest_size).map(|entry| entry.message.id.clone()).collect::<Vec<_>>()
+                };
+                self.send_pull(&peer_id, GossipMessageType::PullDigest, ids).await;
+            }
+            GossipMessageType::PullDigest => {
+                let unknown: Vec<String> = {
+                    let cache = self.message_cache.read().await;
+                    ids(&message)?.into_iter().filter(|id| !cache.contains_key(id)).collect()
+                };
+                if !unknown.is_empty() {
+                    debug!("Fetching {} unseen messages from peer {}", unknown.len(), peer_id);
+                    self.send_pull(&peer_id, GossipMessageType::PullFetch, unknown).await;
+                }
+            }
+            GossipMessageType::PullFetch => {
+                let wanted = ids(&message)?;
+                let domains = self.peer_domains(&peer_id).await;
+                let cache = self.message_cache.read().await;
+                let permitted = wanted
+                    .iter()
+                    .filter_map(|id| cache.get(id))
+                    .filter(|cached| self.config.privacy.permits(&cached.message, &domains));
+                for cached in permitted {
+                    if let Err(e) = self.queue_outbound(&peer_id, cached.message.clone()).await {
+                        error!("Failed to queue fetched message for peer {}: {}", peer_id, e);
+                    }
+                    self.stats.messages_fetched.increment();
+                }
+            }
+            _ => return Err(anyhow!("Not a pull message: {:?}", message.message_type)),
+        }
+        
+        self.update_peer_info(&peer_id).await;
+        Ok(())
+    }
+
+    async fn send_pull(&self, peer_id: &str, message_type: GossipMessageType, ids: Vec<String>) {
+        let message = GossipMessage::new(message_type, self.node_id.clone(), serde_json::json!({ "ids": ids }), 1);
+        if let Err(e) = self.queue_outbound(peer_id, message).await {
+            error!("Failed to queue pull message for peer {}: {}", peer_id, e);
+        }
+    }
+
+    /// Queue a message for `peer_id` at its type's priority, waiting or
+    /// shedding load as the outbound queue's policy says when it is full
+    async fn queue_outbound(&self, peer_id: &str, message: GossipMessage) -> Result<()> {
+        let priority = message.message_type.priority();
+        let message_id = message.id.clone();
+        let journaled = match self.journaled_sends.lock().get_mut(&message_id) {
+            Some(send) => {
+                send.in_flight += 1;
+                send.copies += 1;
+                true
+            }
+            None => false,
+        };
+        let result = self.outbound_tx.send((peer_id.to_string(), message), priority).await;
+        if result.is_err() && journaled {
+            if let Some(send) = self.journaled_sends.lock().get_mut(&message_id) {
+                send.in_flight -= 1;
+                send.copies -= 1;
+            }
+        }
+        Ok(result?)
+    }
+
+    /// Peers each message is pushed to in the configured mode
+    fn push_fanout(&self) -> usize {
+        match self.config.mode {
+            GossipMode::Push => self.config.fanout,
+            GossipMode::PushPull => self.config.fanout.div_ceil(2),
+            GossipMode::Pull => 0,
+        }
+    }
+
+    /// Open the configured journal on its writer thread, returning the
+    /// messages that were never sent before the last shutdown
+    async fn open_journal(&mut self) -> Result<Vec<GossipMessage>> {
+        let Some(journal_config) = self.config.journal.clone() else {
+            return Ok(Vec::new());
+        };
+        let (journal, unsent) = tokio::task::spawn_blocking(move || {
+            let mut journal = MessageJournal::open(journal_config)?;
+            let unsent = journal.replay()?;
+            Ok::<_, anyhow::Error>((journal, unsent))
+        })
+        .await??;
+        self.journal = Some(JournalWriter::spawn(journal));
+        Ok(unsent)
+    }
+
+    /// Append a message to the journal if its type is journaled, tracking
+    /// the copies queued for it from now on
+    async fn journal_append(&self, message: &GossipMessage) -> Result<()> {
+        if let Some(journal) = &self.journal {
+            if journal.is_journaled(&message.message_type) {
+                journal.append(message).await?;
+                self.journaled_sends.lock().insert(message.id.clone(), JournaledSend::default());
+            }
+        }
+        Ok(())
+    }
+
+    /// Acknowledge a cancelled message at once, so it is not replayed
+    fn journal_ack(&self, message_id: &str) {
+        if self.journaled_sends.lock().remove(message_id).is_some() {
+            if let Some(journal) = &self.journal {
+                journal.ack(message_id);
+            }
+        }
+    }
+
+    /// Note that every copy of a journaled message has been queued
+    fn journal_queued(&self, message_id: &str) {
+        let mut sends = self.journaled_sends.lock();
+        if let Some(send) = sends.get_mut(message_id) {
+            send.queued = true;
+        }
+        Self::settle_journaled(&mut sends, self.journal.as_ref(), message_id);
+    }
+
+    /// Record the outcome of sending one copy of a message to a peer
+    fn journal_sent(sends: &JournaledSends, journal: Option<&JournalWriter>, message_id: &str, sent: bool) {
+        let mut sends = sends.lock();
+        let Some(send) = sends.get_mut(message_id) else {
+            return;
+        };
+        send.in_flight = send.in_flight.saturating_sub(1);
+        send.sent |= sent;
+        Self::settle_journaled(&mut sends, journal, message_id);
+    }
+
+    /// Acknowledge a journaled message once all of its copies are settled and
+    /// one reached a peer, or nothing had to be sent; if every copy failed
+    /// the entry stays pending and is sent again after a restart
+    fn settle_journaled(sends: &mut HashMap<String, JournaledSend>, journal: Option<&JournalWriter>, message_id: &str) {
+        let Some(send) = sends.get(message_id) else {
+            return;
+        };
+        if !send.queued || send.in_flight > 0 {
+            return;
+        }
+        let delivered = send.sent || send.copies == 0;
+        sends.remove(message_id);
+        if !delivered {
+            warn!("No copy of journaled message {} was sent; it stays in the journal", message_id);
+        } else if let Some(journal) = journal {
+            journal.ack(message_id);
+        }
+    }
+
+    /// Publish a message from another node on the node's bus
+    async fn process_message(&self, message: &GossipMessage) -> Result<()> {
+        if message.sender_id != self.node_id {
+            match &message.topic {
+                Some(topic) => self.events.publish(ACPEvent::TopicMessage { topic: topic.clone(), message: message.clone() }),
+                None => self.events.publish(ACPEvent::GossipReceived { message: message.clone() }),
+            }
         }
         
         // Update peer information
@@ -349,15 +1247,24 @@ impl GossipProtocol {
             return false;
         }
         
-        // Don't forward expired messages
         if message.is_expired() {
             return false;
         }
         
+        // Topic messages go to the whole mesh, whatever the mode
+        if let Some(topic) = &message.topic {
+            return self.topics.read().await.is_subscribed(topic);
+        }
+        
+        // Nothing is pushed on in pull mode
+        if self.push_fanout() == 0 {
+            return false;
+        }
+        
         // Check if we've already forwarded to enough peers
         let cache = self.message_cache.read().await;
         if let Some(entry) = cache.get(&message.id) {
-            return entry.forwarded_to.len() < self.config.fanout;
+            return entry.forwarded_to.len() < self.push_fanout();
         }
         
         true
@@ -371,11 +1278,14 @@ impl GossipProtocol {
         }
         
         // Select peers to forward to (excluding sender and previous forwarders)
-        let target_peers = self.select_forward_targets(&message).await;
+        let target_peers = match &message.topic {
+            Some(topic) => self.select_topic_targets(&message, topic).await,
+            None => self.select_forward_targets(&message).await,
+        };
         
         // Send to selected peers
         for peer_id in &target_peers {
-            if let Err(e) = self.outbound_tx.send((peer_id.clone(), message.clone())) {
+            if let Err(e) = self.queue_outbound(peer_id, message.clone()).await {
                 error!("Failed to queue forwarded message for peer {}: {}", peer_id, e);
             }
         }
@@ -388,34 +1298,52 @@ impl GossipProtocol {
             }
         }
         
-        let mut stats = self.stats.write().await;
-        stats.messages_forwarded += 1;
+        self.stats.messages_forwarded.increment();
         
         Ok(())
     }
 
+    /// Mesh peers for a topic we subscribe to, or a fanout of its known
+    /// subscribers for one we only publish to
+    async fn select_topic_targets(&self, message: &GossipMessage, topic: &str) -> Vec<String> {
+        let topics = self.topics.read().await;
+        let peers = self.peers.read().await;
+        let excluded: HashSet<_> = message.routing_path.iter().collect();
+        if topics.is_subscribed(topic) {
+            return topics
+                .mesh_peers(topic)
+                .into_iter()
+                .filter(|peer| *peer != message.sender_id && !excluded.contains(peer))
+                .filter(|peer| peers.get(peer).is_some_and(|peer| self.permitted(message, peer)))
+                .collect();
+        }
+        
+        let subscribers: HashSet<String> = topics.subscribers(topic).into_iter().collect();
+        let mut candidates: Vec<_> = peers
+            .values()
+            .filter(|peer| peer.is_active && subscribers.contains(&peer.id) && self.permitted(message, peer))
+            .collect();
+        let count = self.config.fanout.min(candidates.len());
+        self.choose_peers(&mut candidates, count)
+    }
+
     /// Select peers for gossiping
-    async fn select_gossip_targets(&self) -> Vec<String> {
+    async fn select_gossip_targets(&self, message: &GossipMessage) -> Vec<String> {
         let peers = self.peers.read().await;
-        let active_peers: Vec<_> = peers
+        let mut active_peers: Vec<_> = peers
             .values()
-            .filter(|peer| peer.is_active)
+            .filter(|peer| peer.is_active && self.permitted(message, peer))
             .collect();
         
         if active_peers.is_empty() {
             return Vec::new();
         }
         
-        let target_count = std::cmp::min(self.config.fanout, active_peers.len());
+        let target_count = std::cmp::min(self.push_fanout(), active_peers.len());
         
         // Simple random selection for now
         // In production, this could use more sophisticated selection algorithms
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        active_peers
-            .choose_multiple(&mut rng, target_count)
-            .map(|peer| peer.id.clone())
-            .collect()
+        self.choose_peers(&mut active_peers, target_count)
     }
 
     /// Select peers for forwarding (excluding sender and routing path)
@@ -423,12 +1351,13 @@ impl GossipProtocol {
         let peers = self.peers.read().await;
         let excluded: HashSet<_> = message.routing_path.iter().cloned().collect();
         
-        let available_peers: Vec<_> = peers
+        let mut available_peers: Vec<_> = peers
             .values()
             .filter(|peer| {
                 peer.is_active && 
                 peer.id != message.sender_id && 
-                !excluded.contains(&peer.id)
+                !excluded.contains(&peer.id) &&
+                self.permitted(message, peer)
             })
             .collect();
         
@@ -436,14 +1365,32 @@ impl GossipProtocol {
             return Vec::new();
         }
         
-        let target_count = std::cmp::min(self.config.fanout, available_peers.len());
+        let target_count = std::cmp::min(self.push_fanout(), available_peers.len());
         
+        self.choose_peers(&mut available_peers, target_count)
+    }
+
+    /// Randomly choose peers using the node's seeded generator
+    /// Whether the message's privacy tiers let it go to `peer`
+    fn permitted(&self, message: &GossipMessage, peer: &GossipPeer) -> bool {
+        self.config.privacy.permits(message, &peer.domains)
+    }
+
+    fn choose_peers(&self, candidates: &mut [&GossipPeer], count: usize) -> Vec<String> {
+        Self::choose_from(&self.rng, candidates, count)
+    }
+
+    fn choose_from(rng: &NodeRng, candidates: &mut [&GossipPeer], count: usize) -> Vec<String> {
         use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        available_peers
-            .choose_multiple(&mut rng, target_count)
-            .map(|peer| peer.id.clone())
-            .collect()
+        
+        // Map iteration order varies between runs, so sort before sampling
+        candidates.sort_by(|a, b| a.id.cmp(&b.id));
+        rng.with(|rng| {
+            candidates
+                .choose_multiple(rng, count)
+                .map(|peer| peer.id.clone())
+                .collect()
+        })
     }
 
     /// Update peer information
@@ -453,49 +1400,71 @@ impl GossipProtocol {
             peer.last_seen = Instant::now();
             peer.message_count += 1;
             peer.is_active = true;
+            peer.missed_heartbeats = 0;       // Any message proves the peer alive
         }
     }
 
-    /// Start periodic gossip task
-    async fn start_periodic_gossip(&self) {
+    /// Start periodic heartbeats
+    async fn start_heartbeats(&self) {
+        let node_id = self.node_id.clone();
+        let peers = self.peers.clone();
+        let outbound_tx = self.outbound_tx.clone();
+        let events = self.events.clone();
+        let nonces = self.heartbeat_nonce.clone();
         let config = self.config.clone();
+        
+        tokio::spawn(async move {
+            let mut interval = interval(config.heartbeat_interval);
+            
+            loop {
+                interval.tick().await;
+                Self::send_heartbeats(&node_id, &peers, &outbound_tx, &events, &nonces, config.max_missed_heartbeats).await;
+            }
+        });
+    }
+
+    /// Start periodic pull rounds
+    async fn start_pull_rounds(&self) {
+        let node_id = self.node_id.clone();
         let peers = self.peers.clone();
+        let rng = self.rng.clone();
+        let outbound_tx = self.outbound_tx.clone();
+        let stats = self.stats.clone();
+        let config = self.config.clone();
         
         tokio::spawn(async move {
-            let mut interval = interval(config.gossip_interval);
+            let mut interval = interval(config.pull_interval);
             
             loop {
                 interval.tick().await;
-                
-                // Send heartbeat messages
-                let heartbeat = GossipMessage::new(
-                    GossipMessageType::HeartBeat,
-                    "node_id".to_string(), // Would use actual node ID
-                    serde_json::json!({"timestamp": chrono::Utc::now()}),
-                    5, // Short TTL for heartbeats
-                );
-                
-                // This would trigger gossip of heartbeat message
-                debug!("Periodic heartbeat gossip");
+                Self::request_digests(&node_id, &peers, &rng, &outbound_tx, &stats, config.fanout).await;
             }
         });
     }
 
     /// Start message processor task
-    async fn start_message_processor(&self, mut rx: mpsc::UnboundedReceiver<(String, GossipMessage)>) {
+    async fn start_message_processor(&self, mut rx: QueueReceiver<(String, GossipMessage)>) {
         let stats = self.stats.clone();
+        let peers = self.peers.clone();
+        let journaled_sends = self.journaled_sends.clone();
+        let journal = self.journal.clone();
         
         tokio::spawn(async move {
             while let Some((peer_id, message)) = rx.recv().await {
                 // Simulate sending message to peer
                 debug!("Sending message {} to peer {}", message.id, peer_id);
                 
-                // Update stats
-                let mut stats = stats.write().await;
-                stats.bytes_sent += serde_json::to_vec(&message).unwrap_or_default().len() as u64;
+                // Encode in the peer's codec, updating stats
+                let codec = peers.read().await.get(&peer_id).map(|peer| peer.codec).unwrap_or_default();
+                if let Err(e) = Self::encode_counted(&stats, codec, &message) {
+                    error!("Failed to encode message {} for peer {}: {}", message.id, peer_id, e);
+                    Self::journal_sent(&journaled_sends, journal.as_ref(), &message.id, false);
+                    continue;
+                }
                 
                 // In a real implementation, this would send over the network
                 tokio::time::sleep(Duration::from_millis(10)).await;
+                Self::journal_sent(&journaled_sends, journal.as_ref(), &message.id, true);
             }
         });
     }
@@ -535,7 +1504,7 @@ impl GossipProtocol {
 
     /// Get gossip statistics
     pub async fn get_stats(&self) -> GossipStats {
-        self.stats.read().await.clone()
+        GossipStats { outbound_queue: self.outbound_tx.metrics(), ..self.stats.snapshot() }
     }
 
     /// Get active peer count
@@ -543,6 +1512,11 @@ impl GossipProtocol {
         let peers = self.peers.read().await;
         peers.values().filter(|peer| peer.is_active).count()
     }
+
+    /// Get the number of messages held in the duplicate-detection cache
+    pub async fn get_cache_size(&self) -> usize {
+        self.message_cache.read().await.len()
+    }
 }
 
 #[cfg(test)]
@@ -596,4 +1570,347 @@ mod tests {
         let stats = protocol.get_stats().await;
         assert_eq!(stats.active_peers, 2);
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_compressed_gossip_is_counted_both_ways() {
+        let config = GossipConfig {
+            compression: true,
+            compression_threshold: 64,
+            ..Default::default()
+        };
+        let sender = GossipProtocol::new("sender".to_string(), config.clone());
+        let receiver = GossipProtocol::new("receiver".to_string(), config);
+        sender.add_peer("receiver".to_string()).await;
+        sender.set_peer_capabilities("receiver", &receiver.codec_capabilities()).await;
+
+        let rows: Vec<_> = (0..100).map(|i| serde_json::json!({"agent": format!("agent{}", i), "score": 0.9})).collect();
+        let message = GossipMessage::new(GossipMessageType::ReputationUpdate, "sender".to_string(), serde_json::json!(rows), 10);
+        let frame = sender.encode_for_peer("receiver", &message).await.unwrap();
+        receiver.handle_incoming_frame(&frame).await.unwrap();
+
+        let sent = sender.get_stats().await;
+        let received = receiver.get_stats().await;
+        assert_eq!(sent.bytes_sent, received.bytes_received);
+        assert_eq!(sent.uncompressed_bytes_sent, received.uncompressed_bytes_received);
+        assert!(received.compression_ratio() < 0.5);
+        assert_eq!(received.messages_received, 1);
+
+        // A peer that never advertised gets plain JSON
+        sender.add_peer("legacy".to_string()).await;
+        let frame = sender.encode_for_peer("legacy", &message).await.unwrap();
+        assert_eq!(&frame[..2], &[0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_target_selection() {
+        let config = GossipConfig {
+            rng_seed: Some(7),
+            ..Default::default()
+        };
+
+        let mut selections = Vec::new();
+        for _ in 0..2 {
+            let protocol = GossipProtocol::new("test_node".to_string(), config.clone());
+            for i in 0..20 {
+                protocol.add_peer(format!("peer{}", i)).await;
+            }
+            let message = GossipMessage::new(GossipMessageType::StateUpdate, "test_node".to_string(), serde_json::json!({}), 5);
+            selections.push(protocol.select_gossip_targets(&message).await);
+        }
+
+        assert_eq!(selections[0].len(), config.fanout);
+        assert_eq!(selections[0], selections[1]);
+    }
+
+    #[tokio::test]
+    async fn test_pull_fetches_only_unseen_messages() {
+        let config = GossipConfig { mode: GossipMode::Pull, ..Default::default() };
+        let mut a = GossipProtocol::new("a".to_string(), config.clone());
+        let mut b = GossipProtocol::new("b".to_string(), config);
+        let mut a_out = a.outbound_rx.take().unwrap();
+        let mut b_out = b.outbound_rx.take().unwrap();
+        a.add_peer("b".to_string()).await;
+        b.add_peer("a".to_string()).await;
+
+        // Pull mode keeps new messages in the cache instead of pushing them
+        a.broadcast(GossipMessageType::StateUpdate, serde_json::json!({"epoch": 1})).await.unwrap();
+        assert!(a_out.try_recv().is_none());
+
+        // b asks for a digest, a answers, b fetches the unseen message
+        assert_eq!(b.pull_round().await, 1);
+        for step in [GossipMessageType::PullRequest, GossipMessageType::PullFetch] {
+            let (to, message) = b_out.try_recv().unwrap();
+            assert_eq!((to.as_str(), &message.message_type), ("a", &step));
+            a.handle_incoming_message(message).await.unwrap();
+            let (to, reply) = a_out.try_recv().unwrap();
+            assert_eq!(to, "b");
+            b.handle_incoming_message(reply).await.unwrap();
+        }
+        assert_eq!(b.get_cache_size().await, 1);
+        assert_eq!(a.get_stats().await.messages_fetched, 1);
+
+        // Once b has everything, a digest leads to no fetch
+        b.pull_round().await;
+        let (_, request) = b_out.try_recv().unwrap();
+        a.handle_incoming_message(request).await.unwrap();
+        b.handle_incoming_message(a_out.try_recv().unwrap().1).await.unwrap();
+        assert!(b_out.try_recv().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_measure_rtt_and_detect_dead_peers() {
+        let config = GossipConfig { max_missed_heartbeats: 2, ..Default::default() };
+        let mut a = GossipProtocol::new("a".to_string(), config.clone());
+        let mut b = GossipProtocol::new("b".to_string(), config);
+        let mut a_out = a.outbound_rx.take().unwrap();
+        let mut b_out = b.outbound_rx.take().unwrap();
+        let mut events = a.events.subscribe();
+        a.add_peer("b".to_string()).await;
+        a.add_peer("c".to_string()).await;
+        b.add_peer("a".to_string()).await;
+
+        // b echoes a's heartbeat, and a times the round trip
+        assert_eq!(a.heartbeat_round().await, 2);
+        let mut heartbeats = [a_out.try_recv().unwrap(), a_out.try_recv().unwrap()];
+        heartbeats.sort_by(|x, y| x.0.cmp(&y.0));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        b.handle_incoming_message(heartbeats[0].1.clone()).await.unwrap();
+        let (to, ack) = b_out.try_recv().unwrap();
+        assert_eq!((to.as_str(), &ack.message_type), ("a", &GossipMessageType::HeartBeatAck));
+        a.handle_incoming_message(ack.clone()).await.unwrap();
+        let rtt = a.peer_rtt("b").await.unwrap();
+        assert!(rtt >= Duration::from_millis(20));
+        assert!(matches!(events.try_recv().unwrap(), ACPEvent::PeerRtt { peer_id, rtt_ms } if peer_id == "b" && rtt_ms >= 20.0));
+        assert_eq!(a.peer_rtt("c").await, None);
+
+        // A repeated echo is not measured twice
+        a.handle_incoming_message(ack).await.unwrap();
+        assert!(events.try_recv().is_err());
+
+        // c never answers and is dead after its second miss
+        a.heartbeat_round().await;
+        assert!(events.try_recv().is_err());
+        a.heartbeat_round().await;
+        assert!(matches!(events.try_recv().unwrap(), ACPEvent::PeerTimeout { peer_id } if peer_id == "c"));
+        let peers = a.peers.read().await;
+        assert!(!peers["c"].is_active);
+        assert!(peers["b"].is_active);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_broadcast_waiting_on_a_full_queue() {
+        let config = GossipConfig {
+            outbound_queue: QueueConfig { capacity: 1, policy: crate::queue::BackpressurePolicy::Block },
+            ..GossipConfig::default()
+        };
+        let node = GossipProtocol::new("a".to_string(), config);
+        for peer in ["b", "c"] {
+            node.add_peer(peer.to_string()).await;
+        }
+        let cancel = CancellationToken::new();
+        let cancel_soon = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        };
+
+        // One target took the message before the queue filled; it stays cached to spread from there
+        let (sent, ()) = tokio::join!(
+            node.broadcast_cancellable(GossipMessageType::StateUpdate, serde_json::json!({"epoch": 1}), &cancel),
+            cancel_soon,
+        );
+        assert!(sent.is_err());
+        assert_eq!(node.get_cache_size().await, 1);
+        assert_eq!(node.get_stats().await.messages_sent, 1);
+
+        // No target took this one, so it is forgotten
+        let cancel = CancellationToken::new();
+        let cancel_soon = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        };
+        let (sent, ()) = tokio::join!(
+            node.broadcast_cancellable(GossipMessageType::StateUpdate, serde_json::json!({"epoch": 2}), &cancel),
+            cancel_soon,
+        );
+        assert!(sent.is_err());
+        assert_eq!(node.get_cache_size().await, 1);
+        assert_eq!(node.get_stats().await.messages_sent, 1);
+    }
+
+    #[tokio::test]
+    async fn test_journal_entry_settles_once_copies_are_sent() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = JournalConfig { dir: dir.path().to_path_buf(), sync_writes: false, ..JournalConfig::default() };
+        let unsent = || MessageJournal::open(journal.clone()).unwrap().replay().unwrap();
+        let mut node = GossipProtocol::new("a".to_string(), GossipConfig { journal: Some(journal.clone()), ..GossipConfig::default() });
+        node.open_journal().await.unwrap();
+        node.add_peer("b".to_string()).await;
+        let outbox = node.outbound_rx.take().unwrap();
+
+        // Queued but not on the wire yet, so a crash now would send it again
+        node.broadcast(GossipMessageType::StateUpdate, serde_json::json!({"epoch": 1})).await.unwrap();
+        assert_eq!(unsent().len(), 1);
+
+        node.start_message_processor(outbox).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(unsent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_topic_messages_reach_only_subscribers() {
+        let mut nodes: Vec<GossipProtocol> = ["a", "b", "c"]
+            .iter()
+            .map(|id| GossipProtocol::new(id.to_string(), GossipConfig::default()))
+            .collect();
+        let mut outboxes: Vec<_> = nodes.iter_mut().map(|node| node.outbound_rx.take().unwrap()).collect();
+        for node in &nodes {
+            for peer in ["a", "b", "c"].iter().filter(|peer| **peer != node.node_id) {
+                node.add_peer(peer.to_string()).await;
+            }
+        }
+        let mut received = nodes[1].events.subscribe_deliveries();
+        let mut prices = move || std::iter::from_fn(|| received.try_recv().ok())
+            .filter(|event| matches!(event, ACPEvent::TopicMessage { topic, .. } if topic == "market.prices"))
+            .count();
+
+        // Deliver queued messages until the network is quiet, counting per recipient
+        async fn settle(nodes: &[GossipProtocol], outboxes: &mut [QueueReceiver<(String, GossipMessage)>]) -> HashMap<String, usize> {
+            let mut delivered = HashMap::new();
+            loop {
+                let queued: Vec<_> = outboxes.iter_mut().flat_map(|outbox| std::iter::from_fn(|| outbox.try_recv())).collect();
+                if queued.is_empty() {
+                    return delivered;
+                }
+                for (to, message) in queued {
+                    *delivered.entry(to.clone()).or_insert(0) += 1;
+                    let node = nodes.iter().find(|node| node.node_id == to).unwrap();
+                    node.handle_incoming_message(message).await.unwrap();
+                }
+            }
+        }
+
+        nodes[0].subscribe("market.prices").await;
+        nodes[1].subscribe("market.prices").await;
+        settle(&nodes, &mut outboxes).await;
+        assert_eq!(nodes[0].topic_mesh("market.prices").await, vec!["b".to_string()]);
+        assert!(nodes[2].topic_mesh("market.prices").await.is_empty());
+
+        nodes[0].publish("market.prices", serde_json::json!({"price": 42})).await.unwrap();
+        let delivered = settle(&nodes, &mut outboxes).await;
+        assert_eq!(delivered.get("b"), Some(&1));
+        assert_eq!(delivered.get("c"), None);
+        assert_eq!(prices(), 1);
+
+        // Publishing without subscribing reaches the known subscribers
+        nodes[2].publish("market.prices", serde_json::json!({"price": 43})).await.unwrap();
+        let delivered = settle(&nodes, &mut outboxes).await;
+        assert!(delivered.contains_key("a") && delivered.contains_key("b"));
+        assert_eq!(prices(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_privacy_tiers_limit_propagation() {
+        let config = GossipConfig {
+            privacy: PrivacyPolicy::default()
+                .with_message_type(GossipMessageType::ReputationUpdate, PrivacyTier::DomainOnly("eu".to_string()))
+                .with_message_type(GossipMessageType::StateUpdate, PrivacyTier::LocalOnly),
+            ..Default::default()
+        };
+        let mut protocol = GossipProtocol::new("node".to_string(), config);
+        let mut outbox = protocol.outbound_rx.take().unwrap();
+        for peer in ["paris", "berlin", "ohio"] {
+            protocol.add_peer(peer.to_string()).await;
+        }
+        protocol.set_peer_domains("paris", ["eu"]).await;
+        protocol.set_peer_domains("berlin", ["eu", "de"]).await;
+        protocol.set_peer_domains("ohio", ["us"]).await;
+
+        let message = GossipMessage::new(GossipMessageType::ReputationUpdate, "node".to_string(), serde_json::json!({}), 5);
+        protocol.gossip_message(message).await.unwrap();
+        let mut sent: Vec<_> = std::iter::from_fn(|| outbox.try_recv()).collect();
+        sent.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(sent.iter().map(|(to, _)| to.as_str()).collect::<Vec<_>>(), vec!["berlin", "paris"]);
+        assert_eq!(sent[0].1.privacy, vec![PrivacyTier::DomainOnly("eu".to_string())]);
+
+        let message = GossipMessage::new(GossipMessageType::StateUpdate, "node".to_string(), serde_json::json!({}), 5);
+        protocol.gossip_message(message).await.unwrap();
+        assert!(outbox.try_recv().is_none());
+
+        // A relay honours the origin's stamp even though its own policy is open
+        let mut relay = GossipProtocol::new("relay".to_string(), GossipConfig::default());
+        let mut relay_outbox = relay.outbound_rx.take().unwrap();
+        for peer in ["paris", "ohio"] {
+            relay.add_peer(peer.to_string()).await;
+        }
+        relay.set_peer_domains("paris", ["eu"]).await;
+        let (_, stamped) = sent.remove(0);
+        relay.handle_incoming_message(stamped).await.unwrap();
+        let forwarded: Vec<_> = std::iter::from_fn(|| relay_outbox.try_recv()).map(|(to, _)| to).collect();
+        assert_eq!(forwarded, vec!["paris".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_abusive_peers_are_limited_and_muted() {
+        let config = GossipConfig {
+            rate_limit: Some(RateLimitConfig {
+                messages_per_sec: 0.001,
+                message_burst: 2.0,
+                violations_before_mute: 2,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let protocol = GossipProtocol::new("node".to_string(), config);
+        for i in 0..5 {
+            let message = GossipMessage::new(GossipMessageType::StateUpdate, "spammer".to_string(), serde_json::json!({ "n": i }), 5);
+            protocol.handle_incoming_message(message).await.unwrap();
+        }
+
+        let stats = protocol.get_stats().await;
+        assert_eq!(stats.messages_received, 2);
+        assert_eq!((stats.rate_limited_drops, stats.muted_drops), (2, 1));
+
+        protocol.unmute_peer("spammer");
+        let message = GossipMessage::new(GossipMessageType::StateUpdate, "honest".to_string(), serde_json::json!({}), 5);
+        protocol.handle_incoming_message(message).await.unwrap();
+        assert_eq!(protocol.get_stats().await.messages_received, 3);
+    }
+
+    #[tokio::test]
+    async fn test_misbehavior_reports_are_verified_and_confirmed() {
+        use crate::misbehavior::MisbehaviorConfig;
+        use crate::security::SecurityManager;
+
+        let witness = |node_id: &str| {
+            let config = MisbehaviorConfig { confirmations: 2, ..Default::default() };
+            Arc::new(MisbehaviorDetector::new(config, Arc::new(SecurityManager::for_node(node_id))))
+        };
+        let detector = witness("node");
+        let protocol = GossipProtocol::new("node".to_string(), GossipConfig::default()).with_misbehavior(detector.clone());
+        let report_from = |reporter: &str| {
+            let reporter = witness(reporter);
+            for _ in 0..3 {
+                reporter.record("byzantine", Violation::InvalidSignature, Instant::now());
+            }
+            let report = reporter.take_reports().remove(0);
+            let mut message = GossipMessage::new(GossipMessageType::MisbehaviorReport, report.reporter.clone(), serde_json::to_value(report).unwrap(), 5);
+            message.routing_path.push("relay".to_string());
+            message
+        };
+
+        // A forged report is charged to the peer that relayed it
+        let mut forged = report_from("alice");
+        forged.payload["reporter"] = serde_json::json!("bob");
+        protocol.handle_incoming_message(forged).await.unwrap();
+        assert!(detector.score("relay", Instant::now()) > 0.0);
+
+        protocol.handle_incoming_message(report_from("alice")).await.unwrap();
+        assert!(!detector.is_quarantined("byzantine", Instant::now()));
+        protocol.handle_incoming_message(report_from("bob")).await.unwrap();
+        assert!(detector.is_quarantined("byzantine", Instant::now()));
+
+        let message = GossipMessage::new(GossipMessageType::StateUpdate, "byzantine".to_string(), serde_json::json!({}), 5);
+        protocol.handle_incoming_message(message).await.unwrap();
+        assert_eq!(protocol.get_stats().await.quarantined_drops, 1);
+    }
+}
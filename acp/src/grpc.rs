@@ -0,0 +1,235 @@
+//! gRPC Control Plane
+//!
+//! Serves the `AcpControl` service from `proto/control.proto`, so services
+//! written in any language can drive a running node without linking this
+//! crate: send and broadcast messages that the node signs, read its
+//! statistics and peers, and stream the events it sees.
+//!
+//! Event streams follow the node's event bus, passing on gossip topic
+//! messages and delivery receipts. Each subscriber has its own receiver; one
+//! that falls more than the bus capacity behind skips the events it missed
+//! rather than slowing the node.
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::Stream;
+use tokio::sync::broadcast;
+use tonic::{Request, Response, Status};
+
+use crate::events::ACPEvent;
+use crate::messaging::{ACPMessage, MessageType};
+use crate::outbox::DeliveryStatus;
+use crate::{ACPError, Result, ACP};
+
+/// Generated protobuf types and service stubs
+pub mod proto {
+    tonic::include_proto!("solace.acp.v1");
+}
+
+use proto::acp_control_server::{AcpControl, AcpControlServer};
+use proto::node_event::Event;
+
+/// Control-plane form of a node event, for the kinds the service streams
+pub fn node_event(event: &ACPEvent) -> Option<proto::NodeEvent> {
+    let event = match event {
+        ACPEvent::TopicMessage { topic, message } => Event::TopicMessage(proto::TopicMessage {
+            topic: topic.clone(),
+            sender: message.sender_id.clone(),
+            payload_json: message.payload.to_string(),
+        }),
+        ACPEvent::DeliveryReceipt(receipt) => Event::DeliveryReceipt(proto::DeliveryReceipt {
+            message_id: receipt.message_id.to_string(),
+            destination: receipt.destination.clone().unwrap_or_default(),
+            attempts: receipt.attempts,
+            delivered: receipt.status == DeliveryStatus::Delivered,
+        }),
+        _ => return None,
+    };
+    Some(proto::NodeEvent { event: Some(event) })
+}
+
+/// Which events a subscriber asked for
+fn wanted(request: &proto::SubscribeEventsRequest, event: &proto::NodeEvent) -> bool {
+    match &event.event {
+        Some(Event::TopicMessage(message)) => request.topics.is_empty() || request.topics.contains(&message.topic),
+        Some(Event::DeliveryReceipt(_)) => request.delivery_receipts,
+        None => false,
+    }
+}
+
+/// ACP message type named by a client: a built-in type, or a custom one
+pub fn parse_message_type(name: &str) -> MessageType {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .unwrap_or_else(|_| MessageType::Custom(name.to_string()))
+}
+
+fn status(error: ACPError) -> Status {
+    match error {
+        ACPError::Security(reason) => Status::permission_denied(reason),
+        ACPError::Message(reason) | ACPError::Protocol(reason) | ACPError::Config(reason) => Status::invalid_argument(reason),
+        ACPError::Network(reason) | ACPError::Connection(reason) => Status::unavailable(reason),
+        ACPError::Cancelled(reason) => Status::cancelled(reason),
+        other => Status::internal(other.to_string()),
+    }
+}
+
+/// `AcpControl` over a running node
+pub struct ControlService {
+    acp: Arc<ACP>,
+}
+
+impl ControlService {
+    pub fn new(acp: Arc<ACP>) -> Self {
+        Self { acp }
+    }
+
+    pub fn into_server(self) -> AcpControlServer<Self> {
+        AcpControlServer::new(self)
+    }
+
+    /// Serve the control plane on `addr` until the task is dropped
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        tracing::info!("Serving ACP control plane on {}", addr);
+        tonic::transport::Server::builder()
+            .add_service(self.into_server())
+            .serve(addr)
+            .await
+            .map_err(|e| ACPError::Network(format!("Control plane on {} failed: {}", addr, e)))
+    }
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = std::result::Result<proto::NodeEvent, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl AcpControl for ControlService {
+    async fn send_message(&self, request: Request<proto::SendMessageRequest>) -> std::result::Result<Response<proto::SendMessageResponse>, Status> {
+        let request = request.into_inner();
+        let mut message = ACPMessage::new(
+            parse_message_type(&request.message_type),
+            self.acp.node_id().to_string(),
+            Some(request.to.clone()),
+            request.payload,
+        );
+        message.headers.extend(request.headers);
+        let message_id = message.id.to_string();
+
+        let sent = match request.routed {
+            true => self.acp.route_message(message).await,
+            false => self.acp.send_message(&request.to, message).await,
+        };
+        sent.map_err(status)?;
+        Ok(Response::new(proto::SendMessageResponse { message_id }))
+    }
+
+    async fn broadcast(&self, request: Request<proto::BroadcastRequest>) -> std::result::Result<Response<proto::BroadcastResponse>, Status> {
+        let request = request.into_inner();
+        let message = ACPMessage::new(parse_message_type(&request.message_type), self.acp.node_id().to_string(), None, request.payload);
+        let message_id = message.id.to_string();
+        self.acp.broadcast_message(message).await.map_err(status)?;
+        Ok(Response::new(proto::BroadcastResponse { message_id }))
+    }
+
+    async fn get_stats(&self, _request: Request<proto::GetStatsRequest>) -> std::result::Result<Response<proto::Stats>, Status> {
+        let stats = self.acp.get_stats();
+        Ok(Response::new(proto::Stats {
+            peer_count: stats.peer_count as u64,
+            messages_sent: stats.messages_sent,
+            messages_received: stats.messages_received,
+            uptime_secs: stats.uptime.as_secs_f64(),
+            bytes_read: stats.bandwidth.bytes_read,
+            bytes_written: stats.bandwidth.bytes_written,
+            read_rate: stats.bandwidth.read_rate,
+            write_rate: stats.bandwidth.write_rate,
+        }))
+    }
+
+    async fn list_peers(&self, _request: Request<proto::ListPeersRequest>) -> std::result::Result<Response<proto::ListPeersResponse>, Status> {
+        let mut peers: Vec<proto::Peer> = self
+            .acp
+            .peers()
+            .into_iter()
+            .map(|(node_id, addr)| proto::Peer {
+                connected: self.acp.is_connected(&node_id),
+                node_id,
+                address: addr.to_string(),
+            })
+            .collect();
+        peers.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        Ok(Response::new(proto::ListPeersResponse { peers }))
+    }
+
+    type SubscribeEventsStream = EventStream;
+
+    async fn subscribe_events(&self, request: Request<proto::SubscribeEventsRequest>) -> std::result::Result<Response<EventStream>, Status> {
+        let filter = request.into_inner();
+        let events = self.acp.subscribe_events();
+        let stream = futures::stream::unfold((events, filter), |(mut events, filter)| async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if let Some(event) = node_event(&event).filter(|event| wanted(&filter, event)) {
+                            return Some((Ok(event), (events, filter)));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::debug!("Control-plane subscriber skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use crate::gossip::{GossipMessage, GossipMessageType};
+    use crate::outbox::DeliveryReceipt;
+
+    #[test]
+    fn test_message_types_parse_by_name() {
+        assert_eq!(parse_message_type("QuoteRequest"), MessageType::QuoteRequest);
+        assert_eq!(parse_message_type("invoice"), MessageType::Custom("invoice".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_event_streams_follow_their_filters() {
+        let bus = EventBus::new(8);
+        let request = proto::SubscribeEventsRequest { topics: vec!["rfq.data_analysis".to_string()], delivery_receipts: false };
+        let mut events = bus.subscribe();
+
+        let other = GossipMessage::new(GossipMessageType::Publish, "node-2".to_string(), serde_json::json!({}), 3).on_topic("rfq.trading");
+        let quote = GossipMessage::new(GossipMessageType::Publish, "node-7".to_string(), serde_json::json!({"quote": 4}), 3)
+            .on_topic("rfq.data_analysis");
+        bus.publish(ACPEvent::TopicMessage { topic: "rfq.trading".to_string(), message: other });
+        bus.publish(ACPEvent::PeerConnected { peer_id: "node-7".to_string() });
+        bus.publish(ACPEvent::DeliveryReceipt(DeliveryReceipt {
+            message_id: uuid::Uuid::new_v4(),
+            destination: Some("node-7".to_string()),
+            attempts: 1,
+            status: DeliveryStatus::Delivered,
+        }));
+        bus.publish(ACPEvent::TopicMessage { topic: "rfq.data_analysis".to_string(), message: quote });
+        drop(bus);
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.recv().await {
+            received.extend(node_event(&event));
+        }
+        let kept: Vec<&proto::NodeEvent> = received.iter().filter(|event| wanted(&request, event)).collect();
+        assert_eq!(received.len(), 3);
+        assert_eq!(kept.len(), 1);
+        match &kept[0].event {
+            Some(Event::TopicMessage(message)) => {
+                assert_eq!(message.sender, "node-7");
+                assert_eq!(message.payload_json, r#"{"quote":4}"#);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
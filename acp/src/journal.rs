@@ -0,0 +1,498 @@
+//! Gossip Message Journal Module
+//!
+//! Durable write-ahead journal for gossip messages that must survive a crash.
+//! Messages are appended to sequentially written segment files before they are
+//! processed, and acknowledged once they have been sent to peers. On restart
+//! the journal is replayed and any message without an acknowledgment is
+//! returned so it can be forwarded again instead of being silently dropped.
+//!
+//! Writes and fsyncs block, so at runtime the journal is owned by a
+//! `JournalWriter` thread; async callers hand it records over a channel and
+//! only wait for appends, which must be durable before a message is acted on.
+//!
+//! Each record is framed as `[len: u32][crc32: u32][kind: u8][body]`. A torn
+//! or corrupt record at the tail of the last segment marks the end of the
+//! journal; everything written before it is still recovered. Reopening the
+//! journal keeps appending to that segment, after cutting off the torn tail
+//! so the new records stay readable.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, anyhow};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn, debug, error};
+
+use crate::gossip::{GossipMessage, GossipMessageType};
+
+/// Record kind for an appended message
+const RECORD_APPEND: u8 = 1;
+
+/// Record kind for a send acknowledgment
+const RECORD_ACK: u8 = 2;
+
+/// Size of the record header (length + CRC)
+const HEADER_SIZE: usize = 8;
+
+/// Segment file extension
+const SEGMENT_EXTENSION: &str = "journal";
+
+/// Journal configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalConfig {
+    pub dir: PathBuf,                              // Directory holding segment files
+    pub segment_size: u64,                         // Rotate segments above this size
+    pub journaled_types: Vec<GossipMessageType>,   // Message types written to the journal
+    pub sync_writes: bool,                         // fsync after every record
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("./solace_data/gossip_journal"),
+            segment_size: 16 * 1024 * 1024,
+            journaled_types: vec![
+                GossipMessageType::StateUpdate,
+                GossipMessageType::ReputationUpdate,
+            ],
+            sync_writes: true,
+        }
+    }
+}
+
+/// Durable gossip message journal
+pub struct MessageJournal {
+    config: JournalConfig,
+    segment: File,
+    segment_seq: u64,
+    segment_len: u64,
+    pending: HashMap<String, u64>,
+}
+
+impl MessageJournal {
+    /// Open the journal, creating the directory if needed
+    pub fn open(config: JournalConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+
+        let segments = list_segments(&config.dir)?;
+        let (segment_seq, segment, segment_len) = match segments.last() {
+            Some((seq, path)) => {
+                let intact: u64 = read_records(path)?
+                    .iter()
+                    .map(|(_, body)| (HEADER_SIZE + 1 + body.len()) as u64)
+                    .sum();
+                let segment = open_segment(&config.dir, *seq)?;
+                segment.set_len(intact)?;
+                (*seq, segment, intact)
+            }
+            None => (0, open_segment(&config.dir, 0)?, 0),
+        };
+
+        Ok(Self {
+            config,
+            segment,
+            segment_seq,
+            segment_len,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Check whether a message type is journaled
+    pub fn is_journaled(&self, message_type: &GossipMessageType) -> bool {
+        self.config.journaled_types.contains(message_type)
+    }
+
+    /// Append a message before it is processed
+    pub fn append(&mut self, message: &GossipMessage) -> Result<()> {
+        let body = serde_json::to_vec(message)?;
+        self.write_record(RECORD_APPEND, &body)?;
+        self.pending.insert(message.id.clone(), self.segment_seq);
+        Ok(())
+    }
+
+    /// Acknowledge that a message has been sent to peers
+    pub fn ack(&mut self, message_id: &str) -> Result<()> {
+        if self.pending.remove(message_id).is_none() {
+            return Ok(());
+        }
+
+        self.write_record(RECORD_ACK, message_id.as_bytes())?;
+        self.remove_settled_segments()
+    }
+
+    /// Replay all segments and return messages that were never acknowledged
+    ///
+    /// Replayed messages are re-registered as pending so they can be
+    /// acknowledged once forwarded again. The active segment is read too, so
+    /// replay right after `open`, before appending.
+    pub fn replay(&mut self) -> Result<Vec<GossipMessage>> {
+        let mut unacked: Vec<(u64, GossipMessage)> = Vec::new();
+
+        for (seq, path) in list_segments(&self.config.dir)? {
+            for (kind, body) in read_records(&path)? {
+                match kind {
+                    RECORD_APPEND => {
+                        let message: GossipMessage = serde_json::from_slice(&body)?;
+                        unacked.push((seq, message));
+                    }
+                    RECORD_ACK => {
+                        let id = String::from_utf8_lossy(&body);
+                        unacked.retain(|(_, message)| message.id != id);
+                    }
+                    other => warn!("Skipping unknown journal record kind {}", other),
+                }
+            }
+        }
+
+        for (seq, message) in &unacked {
+            self.pending.insert(message.id.clone(), *seq);
+        }
+
+        info!("Replayed gossip journal: {} unforwarded messages", unacked.len());
+        Ok(unacked.into_iter().map(|(_, message)| message).collect())
+    }
+
+    /// Number of messages waiting for acknowledgment
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Write a framed record to the active segment
+    fn write_record(&mut self, kind: u8, body: &[u8]) -> Result<()> {
+        if self.segment_len >= self.config.segment_size {
+            self.rotate()?;
+        }
+
+        let record = frame_record(kind, body);
+        self.segment.write_all(&record)?;
+        if self.config.sync_writes {
+            self.segment.sync_data()?;
+        }
+
+        self.segment_len += record.len() as u64;
+        Ok(())
+    }
+
+    /// Start a new segment file
+    fn rotate(&mut self) -> Result<()> {
+        self.segment_seq += 1;
+        self.segment = open_segment(&self.config.dir, self.segment_seq)?;
+        self.segment_len = 0;
+        debug!("Rotated gossip journal to segment {}", self.segment_seq);
+        Ok(())
+    }
+
+    /// Delete closed segments that no longer hold pending messages
+    fn remove_settled_segments(&mut self) -> Result<()> {
+        let oldest_pending = self.pending.values().min().copied().unwrap_or(self.segment_seq);
+
+        for (seq, path) in list_segments(&self.config.dir)? {
+            if seq < oldest_pending && seq < self.segment_seq {
+                fs::remove_file(&path)?;
+                debug!("Removed settled journal segment {}", seq);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Request handled by a journal writer thread
+enum JournalOp {
+    Append(Box<GossipMessage>, oneshot::Sender<Result<()>>),
+    Ack(String),
+}
+
+/// Handle to a journal owned by a writer thread
+#[derive(Debug, Clone)]
+pub struct JournalWriter {
+    tx: mpsc::UnboundedSender<JournalOp>,
+    journaled_types: Vec<GossipMessageType>,
+}
+
+impl JournalWriter {
+    /// Move `journal` onto its own thread that applies records in order
+    ///
+    /// The thread lives as long as any handle does, so it is a plain thread
+    /// rather than one borrowed from the runtime's blocking pool.
+    pub fn spawn(mut journal: MessageJournal) -> Self {
+        let journaled_types = journal.config.journaled_types.clone();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        std::thread::spawn(move || {
+            while let Some(op) = rx.blocking_recv() {
+                match op {
+                    JournalOp::Append(message, reply) => {
+                        let _ = reply.send(journal.append(&message));
+                    }
+                    JournalOp::Ack(message_id) => {
+                        if let Err(e) = journal.ack(&message_id) {
+                            error!("Failed to acknowledge journaled message {}: {}", message_id, e);
+                        }
+                    }
+                }
+            }
+            debug!("Gossip journal writer stopped");
+        });
+
+        Self { tx, journaled_types }
+    }
+
+    /// Check whether a message type is journaled
+    pub fn is_journaled(&self, message_type: &GossipMessageType) -> bool {
+        self.journaled_types.contains(message_type)
+    }
+
+    /// Append a message, returning once the record is written
+    pub async fn append(&self, message: &GossipMessage) -> Result<()> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(JournalOp::Append(Box::new(message.clone()), reply_tx))
+            .map_err(|_| anyhow!("Gossip journal writer has stopped"))?;
+        reply_rx.await.map_err(|_| anyhow!("Gossip journal writer has stopped"))?
+    }
+
+    /// Acknowledge that a message has been sent, without waiting for the
+    /// record; a lost acknowledgment only means the message is sent again
+    pub fn ack(&self, message_id: &str) {
+        if self.tx.send(JournalOp::Ack(message_id.to_string())).is_err() {
+            warn!("Gossip journal writer has stopped; message {} stays pending", message_id);
+        }
+    }
+}
+
+/// Open a segment file for appending, creating it if needed
+fn open_segment(dir: &Path, seq: u64) -> Result<File> {
+    let path = dir.join(format!("{:020}.{}", seq, SEGMENT_EXTENSION));
+    Ok(OpenOptions::new().create(true).append(true).open(path)?)
+}
+
+/// List segment files ordered by sequence number
+fn list_segments(dir: &Path) -> Result<Vec<(u64, PathBuf)>> {
+    let mut segments = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+
+        if let Some(seq) = path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse().ok()) {
+            segments.push((seq, path));
+        }
+    }
+
+    segments.sort_by_key(|(seq, _)| *seq);
+    Ok(segments)
+}
+
+/// Frame a record as `[len][crc32][kind][body]`
+pub(crate) fn frame_record(kind: u8, body: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(body.len() + 1);
+    payload.push(kind);
+    payload.extend_from_slice(body);
+
+    let mut record = Vec::with_capacity(HEADER_SIZE + payload.len());
+    record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    record.extend_from_slice(&crc32(&payload).to_le_bytes());
+    record.extend_from_slice(&payload);
+    record
+}
+
+/// Read all intact records from a segment
+///
+/// Reading stops at the first truncated or corrupt record, which is how a
+/// write interrupted by a crash shows up at the tail of a segment.
+pub(crate) fn read_records(path: &Path) -> Result<Vec<(u8, Vec<u8>)>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+
+    loop {
+        let mut header = [0u8; HEADER_SIZE];
+        if reader.read_exact(&mut header).is_err() {
+            break;
+        }
+
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let expected_crc = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+        if len == 0 || len > crate::constants::MAX_MESSAGE_SIZE + 1 {
+            warn!("Invalid record length {} in {}, truncating replay", len, path.display());
+            break;
+        }
+
+        let mut payload = vec![0u8; len];
+        if reader.read_exact(&mut payload).is_err() {
+            warn!("Torn record at end of {}, truncating replay", path.display());
+            break;
+        }
+
+        if crc32(&payload) != expected_crc {
+            warn!("CRC mismatch in {}, truncating replay", path.display());
+            break;
+        }
+
+        let body = payload.split_off(1);
+        records.push((payload[0], body));
+    }
+
+    Ok(records)
+}
+
+/// CRC-32 (IEEE 802.3) checksum
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+impl std::fmt::Debug for MessageJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageJournal")
+            .field("dir", &self.config.dir)
+            .field("segment_seq", &self.segment_seq)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(dir: &Path) -> JournalConfig {
+        JournalConfig {
+            dir: dir.to_path_buf(),
+            segment_size: 1024,
+            sync_writes: false,
+            ..Default::default()
+        }
+    }
+
+    fn state_update(seq: u64) -> GossipMessage {
+        GossipMessage::new(
+            GossipMessageType::StateUpdate,
+            "sender".to_string(),
+            serde_json::json!({"seq": seq}),
+            10,
+        )
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_replay_returns_unacked_messages() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = state_update(1);
+        let second = state_update(2);
+        {
+            let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+            journal.append(&first).unwrap();
+            journal.append(&second).unwrap();
+            journal.ack(&first.id).unwrap();
+        }
+
+        let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+        let replayed = journal.replay().unwrap();
+
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].id, second.id);
+        assert_eq!(journal.pending_count(), 1);
+    }
+
+    #[test]
+    fn test_torn_tail_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let message = state_update(1);
+        {
+            let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+            journal.append(&message).unwrap();
+        }
+
+        // Simulate a crash in the middle of writing the next record
+        let (_, path) = list_segments(dir.path()).unwrap().pop().unwrap();
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&[200, 0, 0, 0, 1, 2]).unwrap();
+
+        let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+        let replayed = journal.replay().unwrap();
+
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].id, message.id);
+    }
+
+    #[test]
+    fn test_settled_segments_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+
+        let messages: Vec<_> = (0..20).map(state_update).collect();
+        for message in &messages {
+            journal.append(message).unwrap();
+        }
+        assert!(list_segments(dir.path()).unwrap().len() > 1);
+
+        for message in &messages {
+            journal.ack(&message.id).unwrap();
+        }
+        assert_eq!(list_segments(dir.path()).unwrap().len(), 1);
+        assert_eq!(journal.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_reopen_appends_after_torn_tail() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = state_update(1);
+        {
+            let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+            journal.append(&first).unwrap();
+        }
+
+        let (_, path) = list_segments(dir.path()).unwrap().pop().unwrap();
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&[200, 0, 0, 0, 1, 2]).unwrap();
+
+        let second = state_update(2);
+        {
+            let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+            journal.append(&second).unwrap();
+        }
+
+        // Both runs share one segment, and the torn bytes no longer hide the second record
+        assert_eq!(list_segments(dir.path()).unwrap().len(), 1);
+        let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+        let replayed: Vec<_> = journal.replay().unwrap().into_iter().map(|message| message.id).collect();
+        assert_eq!(replayed, vec![first.id, second.id]);
+    }
+
+    #[tokio::test]
+    async fn test_writer_applies_records_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = JournalWriter::spawn(MessageJournal::open(test_config(dir.path())).unwrap());
+
+        let messages: Vec<_> = (0..3).map(state_update).collect();
+        writer.append(&messages[0]).await.unwrap();
+        writer.append(&messages[1]).await.unwrap();
+        writer.ack(&messages[0].id);
+        // Queued behind the ack, so the ack is written once this returns
+        writer.append(&messages[2]).await.unwrap();
+
+        let mut journal = MessageJournal::open(test_config(dir.path())).unwrap();
+        let replayed: Vec<_> = journal.replay().unwrap().into_iter().map(|message| message.id).collect();
+        assert_eq!(replayed, vec![messages[1].id.clone(), messages[2].id.clone()]);
+    }
+}
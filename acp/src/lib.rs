@@ -5,24 +5,60 @@
 //! mechanisms for autonomous agent interactions.
 
 pub mod messaging;
+pub mod bandwidth;
+pub mod bootstrap;
+pub mod codec;
 pub mod discovery;
+pub mod events;
 pub mod gossip;
+pub mod misbehavior;
+pub mod nat;
+pub mod outbox;
 pub mod p2p;
+pub mod peer_store;
+pub mod privacy;
 pub mod protocol;
+pub mod proxy;
+pub mod queue;
 pub mod routing;
 pub mod security;
-
-pub use messaging::{ACPMessage, MessageType, MessageHandler};
-pub use discovery::{PeerDiscovery, NodeInfo};
+pub mod journal;
+pub mod ratelimit;
+pub mod topics;
+pub use solace_common::{rng, stats};
+#[cfg(feature = "gateway")]
+pub mod gateway;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
+pub use messaging::{ACPMessage, MessageType, MessageHandler, MessagePriority, PriorityCounts, TRAFFIC_PRIORITY_HEADER};
+pub use bandwidth::{BandwidthBudget, BandwidthConfig, BandwidthMeter, BandwidthStats, PeerBandwidth};
+pub use bootstrap::{BootstrapSource, BootstrapSourceStats, SignedPeerList};
+pub use discovery::{PeerDiscovery, NodeInfo, KademliaDht, NodeKey, ReputationSource, ReputationRefresh, ReputationRefreshConfig};
+pub use events::{ACPEvent, EventBus};
 pub use gossip::{GossipProtocol, GossipMessage};
-pub use p2p::{P2PNetwork, ConnectionManager};
+pub use topics::TopicMesh;
+pub use misbehavior::{MisbehaviorDetector, MisbehaviorReport, Violation};
+pub use nat::{Reachability, RelayConfig, RelayService};
+pub use outbox::{DeliveryReceipt, DeliveryStatus, Outbox, OutboxConfig};
+pub use p2p::{P2PNetwork, ConnectionManager, ConnectionPolicy, ConnectionState, Direction, Transport, TransportConfig};
+pub use peer_store::{FilePeerBackend, PeerBackend, PeerRecord, PeerScoreWeights, PeerStore};
+pub use privacy::{PrivacyPolicy, PrivacyTier};
 pub use protocol::{ProtocolVersion, HandshakeManager};
-pub use routing::{MessageRouter, RoutingTable};
-pub use security::{SecurityManager, MessageAuthentication};
+pub use proxy::{PeerAddress, ProxyConfig, ProxyCredentials, ProxyKind};
+pub use queue::{BackpressurePolicy, QueueConfig, QueueMetrics};
+pub use routing::{MessageRouter, RoutingTable, RoutingConfig, Route};
+#[cfg(feature = "gateway")]
+pub use gateway::{ClientFrame, Gateway, GatewayConfig, GatewayRequest, GatewayStats, ServerFrame};
+#[cfg(feature = "grpc")]
+pub use grpc::ControlService;
+pub use security::{SecurityManager, MessageAuthentication, PeerIdentity, PeerScorer, PeerScorerConfig, PeerScorerState, ReplayConfig};
 
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 /// ACP Protocol version
 pub const ACP_VERSION: &str = "1.0.0";
@@ -67,6 +103,19 @@ pub struct ACPConfig {
     pub enable_discovery: bool,
     /// Message timeout duration
     pub message_timeout: Duration,
+    /// Read-only node: listens to gossip but never signs or sends
+    pub observer: bool,
+    /// Role in the network; `Relay` nodes forward traffic for unreachable peers
+    pub node_type: discovery::NodeType,
+    /// Outbox for reliable messages; without one they cannot be sent
+    #[serde(default)]
+    pub outbox: Option<OutboxConfig>,
+    /// SOCKS5 or HTTP CONNECT proxy for outbound peer connections
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    /// Node-wide kbps budget for metered links, shaped by message priority
+    #[serde(default)]
+    pub bandwidth_budget: Option<BandwidthBudget>,
 }
 
 impl Default for ACPConfig {
@@ -79,7 +128,120 @@ impl Default for ACPConfig {
             enable_gossip: true,
             enable_discovery: true,
             message_timeout: constants::MESSAGE_TIMEOUT,
+            observer: false,
+            node_type: discovery::NodeType::Agent,
+            outbox: None,
+            proxy: None,
+            bandwidth_budget: None,
+        }
+    }
+}
+
+impl ACPConfig {
+    /// Start building a configuration from the defaults
+    pub fn builder() -> ACPConfigBuilder {
+        ACPConfigBuilder::default()
+    }
+
+    /// Check the configuration before a node is started from it
+    pub fn validate(&self) -> Result<()> {
+        if self.node_id.trim().is_empty() {
+            return Err(ACPError::Config("node id cannot be empty".to_string()));
+        }
+        let listenable = self
+            .listen_address
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        if !listenable {
+            return Err(ACPError::Config(format!("listen address {} is not host:port", self.listen_address)));
+        }
+        if let Some(peer) = self.bootstrap_peers.iter().find(|peer| peer.parse::<PeerAddress>().is_err()) {
+            return Err(ACPError::Config(format!("invalid bootstrap peer address: {}", peer)));
+        }
+        if self.max_peers == 0 {
+            return Err(ACPError::Config("max peers must be at least 1".to_string()));
+        }
+        if self.message_timeout.is_zero() {
+            return Err(ACPError::Config("message timeout must be positive".to_string()));
         }
+        if self.observer && self.outbox.is_some() {
+            return Err(ACPError::Config("observer nodes never send, so take no outbox".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Builds an `ACPConfig`, validating it at `build`
+#[derive(Debug, Clone, Default)]
+pub struct ACPConfigBuilder {
+    config: ACPConfig,
+}
+
+impl ACPConfigBuilder {
+    pub fn with_node_id(mut self, node_id: impl Into<String>) -> Self {
+        self.config.node_id = node_id.into();
+        self
+    }
+
+    pub fn with_listen_address(mut self, address: impl Into<String>) -> Self {
+        self.config.listen_address = address.into();
+        self
+    }
+
+    pub fn with_bootstrap_peer(mut self, address: impl Into<String>) -> Self {
+        self.config.bootstrap_peers.push(address.into());
+        self
+    }
+
+    pub fn with_max_peers(mut self, max_peers: usize) -> Self {
+        self.config.max_peers = max_peers;
+        self
+    }
+
+    pub fn with_gossip(mut self, enabled: bool) -> Self {
+        self.config.enable_gossip = enabled;
+        self
+    }
+
+    pub fn with_discovery(mut self, enabled: bool) -> Self {
+        self.config.enable_discovery = enabled;
+        self
+    }
+
+    pub fn with_message_timeout(mut self, timeout: Duration) -> Self {
+        self.config.message_timeout = timeout;
+        self
+    }
+
+    /// Listen to gossip without ever signing or sending
+    pub fn as_observer(mut self) -> Self {
+        self.config.observer = true;
+        self
+    }
+
+    pub fn with_node_type(mut self, node_type: discovery::NodeType) -> Self {
+        self.config.node_type = node_type;
+        self
+    }
+
+    pub fn with_outbox(mut self, outbox: OutboxConfig) -> Self {
+        self.config.outbox = Some(outbox);
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    pub fn with_bandwidth_budget(mut self, budget: BandwidthBudget) -> Self {
+        self.config.bandwidth_budget = Some(budget);
+        self
+    }
+
+    pub fn build(self) -> Result<ACPConfig> {
+        self.config.validate()?;
+        Ok(self.config)
     }
 }
 
@@ -101,11 +263,17 @@ pub enum ACPError {
     #[error("Timeout error")]
     Timeout,
 
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
     #[error("Connection error: {0}")]
     Connection(String),
 
     #[error("Discovery error: {0}")]
     Discovery(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
 }
 
 /// ACP Result type
@@ -118,17 +286,36 @@ pub struct ACP {
     discovery: PeerDiscovery,
     gossip: GossipProtocol,
     router: MessageRouter,
-    security: SecurityManager,
+    security: Arc<SecurityManager>,
 }
 
 impl ACP {
     /// Create a new ACP instance
     pub async fn new(config: ACPConfig) -> Result<Self> {
-        let network = P2PNetwork::new(&config).await?;
-        let discovery = PeerDiscovery::new(&config);
-        let gossip = GossipProtocol::new(&config);
-        let router = MessageRouter::new();
-        let security = SecurityManager::new();
+        // The same node keys sign messages and authenticate peer channels
+        let security = Arc::new(SecurityManager::for_node(config.node_id.clone()));
+        let transport = TransportConfig {
+            policy: ConnectionPolicy { max_peers: config.max_peers, ..ConnectionPolicy::default() },
+            proxy: config.proxy.clone(),
+            bandwidth: BandwidthConfig { budget: config.bandwidth_budget.clone(), ..BandwidthConfig::default() },
+            ..TransportConfig::default()
+        };
+        let network = P2PNetwork::with_security(&config, transport, security.clone()).await?;
+        // Every component publishes to the transport's event bus
+        let events = network.events().clone();
+        // Peers the transport's scorer bans are blacklisted by discovery
+        let discovery = PeerDiscovery::new(&config)
+            .with_peer_scorer(network.misbehavior().scorer().clone())
+            .with_event_bus(events.clone());
+        // Violations seen by the transport and by gossip count toward the same quarantines
+        let gossip = GossipProtocol::new(&config)
+            .with_misbehavior(network.misbehavior().clone())
+            .with_event_bus(events.clone());
+        let router = MessageRouter::with_config(
+            config.node_id.clone(),
+            RoutingConfig { outbox: config.outbox.clone(), ..RoutingConfig::default() },
+        )
+        .with_event_bus(events);
 
         Ok(Self {
             config,
@@ -178,25 +365,118 @@ impl ACP {
 
     /// Send a message to a specific peer
     pub async fn send_message(&self, peer_id: &str, message: ACPMessage) -> Result<()> {
+        self.ensure_can_sign()?;
+
         // Authenticate and sign the message
         let signed_message = self.security.sign_message(message)?;
         
-        // Route the message
-        self.router.route_message(peer_id, signed_message).await
+        // Deliver it over the transport
+        self.network.send_message(peer_id, &signed_message).await
+    }
+
+    /// `send_message` that gives up if `cancel` fires before the message is
+    /// written to the peer's connection
+    pub async fn send_message_cancellable(&self, peer_id: &str, message: ACPMessage, cancel: &CancellationToken) -> Result<()> {
+        self.ensure_can_sign()?;
+
+        let signed_message = self.security.sign_message(message)?;
+        self.network.send_message_cancellable(peer_id, &signed_message, cancel).await
+    }
+
+    /// Send a message to its `to` node, relaying it over multiple hops if
+    /// that node is not a direct peer. Reliable messages are retransmitted
+    /// until acknowledged.
+    pub async fn route_message(&self, message: ACPMessage) -> Result<()> {
+        self.ensure_can_sign()?;
+
+        let signed_message = self.security.sign_message(message)?;
+        self.router.send(&self.network, signed_message).await
+    }
+
+    /// `route_message` that gives up if `cancel` fires while a route is
+    /// being discovered or before the first hop takes the message, as for
+    /// `MessageRouter::send_cancellable`. Large artifact transfers use it,
+    /// or `send_message_cancellable` between direct peers.
+    pub async fn route_message_cancellable(&self, message: ACPMessage, cancel: &CancellationToken) -> Result<()> {
+        self.ensure_can_sign()?;
+
+        let signed_message = self.security.sign_message(message)?;
+        self.router.send_cancellable(&self.network, signed_message, cancel).await
     }
 
     /// Broadcast a message to all peers
     pub async fn broadcast_message(&self, message: ACPMessage) -> Result<()> {
+        self.ensure_can_sign()?;
+
         // Use gossip protocol for efficient broadcasting
         self.gossip.broadcast(message).await
     }
 
-    /// Register a message handler
-    pub fn register_handler<F>(&mut self, message_type: MessageType, handler: F)
-    where
-        F: Fn(ACPMessage) -> Result<()> + Send + Sync + 'static,
-    {
-        self.router.register_handler(message_type, Box::new(handler));
+    /// Refuse to originate messages on an observer node
+    fn ensure_can_sign(&self) -> Result<()> {
+        if self.config.observer {
+            return Err(ACPError::Security("Observer nodes cannot sign or send messages".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Pass topic messages and messages addressed to this node on to the
+    /// gateway's clients until the node is dropped
+    #[cfg(feature = "gateway")]
+    pub fn attach_gateway(&self, gateway: &Arc<Gateway>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(gateway.clone().follow(self.subscribe_events()))
+    }
+
+    /// Carry out a request from a gateway client on this node's behalf
+    #[cfg(feature = "gateway")]
+    pub async fn handle_gateway_request(&self, request: GatewayRequest) -> Result<()> {
+        match request {
+            GatewayRequest::Subscribe { topic } => self.gossip.subscribe(&topic).await,
+            GatewayRequest::Unsubscribe { topic } => self.gossip.unsubscribe(&topic).await,
+            GatewayRequest::Publish { topic, payload, .. } => {
+                self.ensure_can_sign()?;
+                self.gossip.publish(&topic, payload).await.map_err(|e| ACPError::Message(e.to_string()))?;
+            }
+            GatewayRequest::Send { to, message_type, payload, .. } => {
+                let payload = serde_json::to_vec(&payload).map_err(|e| ACPError::Message(e.to_string()))?;
+                let message = ACPMessage::new(message_type, self.config.node_id.clone(), Some(to.clone()), payload);
+                self.send_message(&to, message).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Receiver of the node's lifecycle events published from now on:
+    /// peers, handshakes, routes, received messages, topic messages, and
+    /// delivery receipts
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<ACPEvent> {
+        self.network.events().subscribe()
+    }
+
+    /// Receiver of every message delivered to this node, gossip message,
+    /// and delivery receipt published from now on; unlike
+    /// `subscribe_events`, it never misses one
+    pub fn subscribe_deliveries(&self) -> tokio::sync::mpsc::UnboundedReceiver<ACPEvent> {
+        self.network.events().subscribe_deliveries()
+    }
+
+    /// Resend reliable messages that have not been acknowledged
+    pub async fn retransmit_unacknowledged(&self) -> Result<usize> {
+        self.router.retransmit(&self.network).await
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.config.node_id
+    }
+
+    /// Known peers and their dialable addresses
+    pub fn peers(&self) -> Vec<(String, PeerAddress)> {
+        self.network.peers()
+    }
+
+    /// Whether a peer holds an open channel to this node
+    pub fn is_connected(&self, peer_id: &str) -> bool {
+        self.network.connections().has_channel(peer_id)
     }
 
     /// Get current peer count
@@ -210,6 +490,8 @@ impl ACP {
             peer_count: self.peer_count(),
             messages_sent: self.router.messages_sent(),
             messages_received: self.router.messages_received(),
+            dispatched_by_priority: self.router.dispatched_by_priority(),
+            bandwidth: self.network.connections().bandwidth().stats(),
             uptime: self.network.uptime(),
         }
     }
@@ -221,6 +503,8 @@ pub struct ACPStats {
     pub peer_count: usize,
     pub messages_sent: u64,
     pub messages_received: u64,
+    pub dispatched_by_priority: PriorityCounts,  // Received messages handled at each priority
+    pub bandwidth: BandwidthStats,
     pub uptime: Duration,
 }
 
@@ -243,6 +527,23 @@ mod tests {
         assert!(config.enable_discovery);
     }
 
+    #[test]
+    fn test_config_builder_validates_at_build() {
+        assert!(ACPConfig::builder().with_max_peers(0).build().is_err());
+        assert!(ACPConfig::builder().with_bootstrap_peer("not an address").build().is_err());
+        assert!(ACPConfig::builder().as_observer().with_outbox(OutboxConfig::default()).build().is_err());
+
+        let config = ACPConfig::builder()
+            .with_node_id("node-1")
+            .with_listen_address("127.0.0.1:9000")
+            .with_bootstrap_peer("127.0.0.1:9001")
+            .with_max_peers(8)
+            .build()
+            .unwrap();
+        assert_eq!(config.node_id, "node-1");
+        assert_eq!(config.bootstrap_peers.len(), 1);
+    }
+
     #[test]
     fn test_constants() {
         assert_eq!(constants::MAX_MESSAGE_SIZE, 1024 * 1024);
@@ -30,10 +30,40 @@ pub enum MessageType {
     Gossip,
     /// Protocol handshake message
     Handshake,
+    /// Request for quotes on a capability topic
+    QuoteRequest,
+    /// Binding quote answering a request for quotes
+    Quote,
+    /// Ask a relay to forward traffic for an unreachable node
+    RelayRequest,
+    /// Message forwarded through a relay
+    RelayData,
+    /// Multi-hop route request or reply
+    RouteDiscovery,
+    /// Message relayed hop by hop to a node that is not a direct peer
+    RoutedData,
+    /// Acknowledgment of a reliable message
+    Ack,
     /// Custom message type
     Custom(String),
 }
 
+/// Header carrying the owning transaction's deadline (RFC 3339)
+pub const TRANSACTION_DEADLINE_HEADER: &str = "transaction_deadline";
+
+/// Header carrying the owning transaction's phase
+pub const TRANSACTION_PHASE_HEADER: &str = "transaction_phase";
+
+/// Phase value for messages that carry out an agreed transaction
+pub const EXECUTION_PHASE: &str = "execution";
+
+/// Header flagging a message for at-least-once delivery
+pub const RELIABLE_HEADER: &str = "reliable";
+
+/// Header overriding the priority a message is shaped at under a bandwidth
+/// budget, as the `MessagePriority` level (1 to 4)
+pub const TRAFFIC_PRIORITY_HEADER: &str = "traffic_priority";
+
 /// Core ACP message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ACPMessage {
@@ -98,6 +128,46 @@ impl ACPMessage {
         self.signature.is_some()
     }
 
+    /// Flag the message for at-least-once delivery: it is retransmitted
+    /// until the recipient acknowledges it
+    pub fn set_reliable(&mut self) {
+        self.add_header(RELIABLE_HEADER, "true");
+    }
+
+    /// Check if the message asks for at-least-once delivery
+    pub fn is_reliable(&self) -> bool {
+        self.get_header(RELIABLE_HEADER).is_some_and(|value| value == "true")
+    }
+
+    /// Shape the message at `priority` under a bandwidth budget, e.g. `Low`
+    /// for bulk artifact transfers
+    pub fn set_traffic_priority(&mut self, priority: MessagePriority) {
+        self.add_header(TRAFFIC_PRIORITY_HEADER, (priority as u8).to_string());
+    }
+
+    /// Priority the message is shaped at under a bandwidth budget: the one
+    /// set on it, or else its type's
+    pub fn traffic_priority(&self) -> MessagePriority {
+        if let Some(priority) = self
+            .get_header(TRAFFIC_PRIORITY_HEADER)
+            .and_then(|level| level.parse().ok())
+            .and_then(MessagePriority::from_level)
+        {
+            return priority;
+        }
+        match self.message_type {
+            MessageType::TransactionRequest
+            | MessageType::TransactionProposal
+            | MessageType::TransactionResponse
+            | MessageType::TransactionComplete
+            | MessageType::Handshake
+            | MessageType::RelayRequest
+            | MessageType::Ack => MessagePriority::High,
+            MessageType::Heartbeat | MessageType::PeerDiscovery | MessageType::RouteDiscovery => MessagePriority::Low,
+            _ => MessagePriority::Normal,
+        }
+    }
+
     /// Serialize the message for transmission
     pub fn serialize(&self) -> Result<Vec<u8>> {
         bincode::serialize(self).map_err(|e| ACPError::Message(format!("Serialization failed: {}", e)))
@@ -124,6 +194,26 @@ impl ACPMessage {
         false
     }
 
+    /// Tag the message with its transaction's deadline and phase, so queues
+    /// can escalate it as the deadline approaches
+    pub fn set_transaction_deadline(&mut self, deadline: chrono::DateTime<chrono::Utc>, phase: &str) {
+        self.add_header(TRANSACTION_DEADLINE_HEADER, deadline.to_rfc3339());
+        self.add_header(TRANSACTION_PHASE_HEADER, phase);
+    }
+
+    /// Deadline of the transaction this message belongs to
+    pub fn transaction_deadline(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.get_header(TRANSACTION_DEADLINE_HEADER)
+            .and_then(|deadline| chrono::DateTime::parse_from_rfc3339(deadline).ok())
+            .map(|deadline| deadline.with_timezone(&chrono::Utc))
+    }
+
+    /// Whether the message belongs to a transaction in its execution phase
+    pub fn is_execution_phase(&self) -> bool {
+        self.get_header(TRANSACTION_PHASE_HEADER)
+            .is_some_and(|phase| phase.eq_ignore_ascii_case(EXECUTION_PHASE))
+    }
+
     /// Create a response message
     pub fn create_response(&self, response_type: MessageType, payload: Vec<u8>) -> ACPMessage {
         let mut response = ACPMessage::new(
@@ -171,6 +261,9 @@ pub struct PriorityMessage {
     pub priority: MessagePriority,
     pub retry_count: u32,
     pub max_retries: u32,
+    /// Direct peer the message arrived from, for queues of received messages
+    #[serde(default)]
+    pub source: Option<String>,
 }
 
 impl PriorityMessage {
@@ -181,9 +274,16 @@ impl PriorityMessage {
             priority,
             retry_count: 0,
             max_retries: 3,
+            source: None,
         }
     }
 
+    /// Record the direct peer the message arrived from
+    pub fn from_peer(mut self, peer_id: impl Into<String>) -> Self {
+        self.source = Some(peer_id.into());
+        self
+    }
+
     /// Check if message can be retried
     pub fn can_retry(&self) -> bool {
         self.retry_count < self.max_retries
@@ -195,30 +295,190 @@ impl PriorityMessage {
     }
 }
 
+/// Raises the priority of messages whose transaction deadline is close.
+///
+/// A message inherits the urgency of its transaction: inside `urgent_within`
+/// of the deadline it is treated as at least `High`, inside
+/// `critical_within` (or past the deadline) as `Critical`. Execution-phase
+/// messages are raised one level further, since work on an agreed deal is
+/// worth more than a negotiation that can still be abandoned. Messages
+/// without deadline metadata keep their own priority.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadlineEscalation {
+    pub urgent_within: std::time::Duration,
+    pub critical_within: std::time::Duration,
+}
+
+impl Default for DeadlineEscalation {
+    fn default() -> Self {
+        Self {
+            urgent_within: std::time::Duration::from_secs(60),
+            critical_within: std::time::Duration::from_secs(10),
+        }
+    }
+}
+
+impl DeadlineEscalation {
+    /// Priority a message is processed at, as of `now`
+    pub fn effective_priority(&self, message: &PriorityMessage, now: chrono::DateTime<chrono::Utc>) -> MessagePriority {
+        self.escalate(&message.message, message.priority, now)
+    }
+
+    /// Priority of `message` queued at `priority`, raised by its transaction
+    /// deadline as of `now`
+    pub fn escalate(&self, message: &ACPMessage, priority: MessagePriority, now: chrono::DateTime<chrono::Utc>) -> MessagePriority {
+        let Some(deadline) = message.transaction_deadline() else {
+            return priority;
+        };
+        let remaining = (deadline - now).to_std().unwrap_or_default();
+        let inherited = if remaining <= self.critical_within {
+            MessagePriority::Critical
+        } else if remaining <= self.urgent_within {
+            MessagePriority::High
+        } else {
+            return priority;
+        };
+        let inherited = if message.is_execution_phase() { inherited.raised() } else { inherited };
+        inherited.max(priority)
+    }
+}
+
+impl MessagePriority {
+    /// Priority with the given numeric level
+    pub fn from_level(level: u8) -> Option<Self> {
+        match level {
+            1 => Some(MessagePriority::Low),
+            2 => Some(MessagePriority::Normal),
+            3 => Some(MessagePriority::High),
+            4 => Some(MessagePriority::Critical),
+            _ => None,
+        }
+    }
+
+    /// One level up, saturating at `Critical`
+    pub fn raised(self) -> Self {
+        match self {
+            MessagePriority::Low => MessagePriority::Normal,
+            MessagePriority::Normal => MessagePriority::High,
+            MessagePriority::High | MessagePriority::Critical => MessagePriority::Critical,
+        }
+    }
+}
+
+/// Messages dispatched at each priority
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriorityCounts {
+    pub low: u64,
+    pub normal: u64,
+    pub high: u64,
+    pub critical: u64,
+}
+
+impl PriorityCounts {
+    pub fn record(&mut self, priority: MessagePriority) {
+        match priority {
+            MessagePriority::Low => self.low += 1,
+            MessagePriority::Normal => self.normal += 1,
+            MessagePriority::High => self.high += 1,
+            MessagePriority::Critical => self.critical += 1,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.low + self.normal + self.high + self.critical
+    }
+}
+
 /// Message queue for handling prioritized messages
+///
+/// Priorities are evaluated when a message is popped, so a message queued
+/// early for a transaction that is now due overtakes fresh traffic. Ties go
+/// to the earlier deadline, then to the message queued first.
+///
+/// With aging enabled, a message is raised one level for every `aging`
+/// interval it has waited, so a steady stream of urgent traffic delays
+/// low-priority messages but cannot starve them.
 pub struct MessageQueue {
-    messages: std::sync::RwLock<std::collections::BinaryHeap<PriorityMessage>>,
+    messages: std::sync::RwLock<Vec<(u64, chrono::DateTime<chrono::Utc>, PriorityMessage)>>,
+    next_seq: std::sync::atomic::AtomicU64,
+    escalation: DeadlineEscalation,
+    aging: Option<std::time::Duration>,
+    dispatched: std::sync::Mutex<PriorityCounts>,
 }
 
 impl MessageQueue {
     /// Create a new message queue
     pub fn new() -> Self {
+        Self::with_escalation(DeadlineEscalation::default())
+    }
+
+    /// Create a queue with a custom deadline escalation policy
+    pub fn with_escalation(escalation: DeadlineEscalation) -> Self {
         Self {
-            messages: std::sync::RwLock::new(std::collections::BinaryHeap::new()),
+            messages: std::sync::RwLock::new(Vec::new()),
+            next_seq: std::sync::atomic::AtomicU64::new(0),
+            escalation,
+            aging: None,
+            dispatched: std::sync::Mutex::new(PriorityCounts::default()),
         }
     }
 
+    /// Raise waiting messages one priority level per `interval` waited
+    pub fn with_aging(mut self, interval: std::time::Duration) -> Self {
+        self.aging = Some(interval).filter(|interval| !interval.is_zero());
+        self
+    }
+
     /// Add a message to the queue
     pub fn push(&self, message: PriorityMessage) -> Result<()> {
+        self.push_at(message, chrono::Utc::now())
+    }
+
+    /// Add a message queued at `now`
+    pub fn push_at(&self, message: PriorityMessage, now: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        let seq = self.next_seq.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         let mut queue = self.messages.write().unwrap();
-        queue.push(message);
+        queue.push((seq, now, message));
         Ok(())
     }
 
     /// Get the next highest priority message
     pub fn pop(&self) -> Option<PriorityMessage> {
+        self.pop_at(chrono::Utc::now())
+    }
+
+    /// Get the message that should be processed next as of `now`
+    pub fn pop_at(&self, now: chrono::DateTime<chrono::Utc>) -> Option<PriorityMessage> {
+        use std::cmp::Reverse;
+
         let mut queue = self.messages.write().unwrap();
-        queue.pop()
+        let (next, priority) = queue
+            .iter()
+            .enumerate()
+            .map(|(index, (seq, queued_at, message))| {
+                let deadline = message.message.transaction_deadline().unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC);
+                let priority = self.aged(self.escalation.effective_priority(message, now), *queued_at, now);
+                (index, (priority, Reverse(deadline), Reverse(*seq)))
+            })
+            .max_by_key(|(_, key)| *key)
+            .map(|(index, (priority, _, _))| (index, priority))?;
+        self.dispatched.lock().unwrap().record(priority);
+        Some(queue.swap_remove(next).2)
+    }
+
+    /// `priority` raised for the time waited since `queued_at`
+    fn aged(&self, priority: MessagePriority, queued_at: chrono::DateTime<chrono::Utc>, now: chrono::DateTime<chrono::Utc>) -> MessagePriority {
+        let Some(interval) = self.aging else {
+            return priority;
+        };
+        let waited = (now - queued_at).to_std().unwrap_or_default();
+        let levels = (waited.as_nanos() / interval.as_nanos()).min(3);
+        (0..levels).fold(priority, |priority, _| priority.raised())
+    }
+
+    /// Messages popped so far, by the priority they were dispatched at
+    pub fn dispatched(&self) -> PriorityCounts {
+        *self.dispatched.lock().unwrap()
     }
 
     /// Get queue size
@@ -350,6 +610,39 @@ pub mod messages {
                 Vec::new(),
             )
         }
+
+        /// Ask `relay` to forward traffic for `from`
+        pub fn relay_request(from: String, relay: String) -> Self {
+            ACPMessage::new(MessageType::RelayRequest, from, Some(relay), Vec::new())
+        }
+
+        /// Wrap `message` for a relay to forward to `target`
+        pub fn relay_data(from: String, target: String, message: &ACPMessage) -> Result<Self> {
+            let mut envelope = ACPMessage::new(MessageType::RelayData, from, Some(target), message.serialize()?);
+            envelope.set_traffic_priority(message.traffic_priority());
+            Ok(envelope)
+        }
+
+        /// Acknowledge receipt of `message`
+        pub fn ack(from: String, message: &ACPMessage) -> Self {
+            ACPMessage::new(MessageType::Ack, from, Some(message.from.clone()), message.id.as_bytes().to_vec())
+        }
+
+        /// Id of the message an `Ack` acknowledges
+        pub fn acked_id(&self) -> Result<Uuid> {
+            if self.message_type != MessageType::Ack {
+                return Err(ACPError::Message(format!("{:?} is not an acknowledgment", self.message_type)));
+            }
+            Uuid::from_slice(&self.payload).map_err(|e| ACPError::Message(format!("Malformed acknowledgment: {}", e)))
+        }
+
+        /// The message a `RelayData` envelope carries
+        pub fn relayed_message(&self) -> Result<ACPMessage> {
+            if self.message_type != MessageType::RelayData {
+                return Err(ACPError::Message(format!("{:?} is not a relay envelope", self.message_type)));
+            }
+            ACPMessage::deserialize(&self.payload)
+        }
     }
 }
 
@@ -431,6 +724,53 @@ mod tests {
         assert_eq!(queue.len(), 1);
     }
 
+    #[test]
+    fn test_aging_prevents_starvation() {
+        let queue = MessageQueue::new().with_aging(std::time::Duration::from_secs(1));
+        let start = chrono::Utc::now();
+        let message = |from: &str| ACPMessage::new(MessageType::TransactionResponse, from.to_string(), None, Vec::new());
+
+        queue.push_at(PriorityMessage::new(message("waiting"), MessagePriority::Low), start).unwrap();
+        queue.push_at(PriorityMessage::new(message("urgent"), MessagePriority::High), start).unwrap();
+        assert_eq!(queue.pop_at(start).unwrap().message.from, "urgent");
+
+        // Fresh High traffic overtakes the Low message until it has waited two intervals
+        let later = start + chrono::Duration::seconds(2);
+        queue.push_at(PriorityMessage::new(message("fresh"), MessagePriority::High), later).unwrap();
+        assert_eq!(queue.pop_at(later).unwrap().message.from, "waiting");
+        assert_eq!(queue.dispatched(), PriorityCounts { high: 2, ..Default::default() });
+    }
+
+    #[test]
+    fn test_due_soon_execution_overtakes_fresh_negotiation() {
+        let queue = MessageQueue::new();
+        let now = chrono::Utc::now();
+
+        let fresh = PriorityMessage::new(
+            ACPMessage::new(MessageType::TransactionRequest, "node1".to_string(), None, Vec::new()),
+            MessagePriority::High,
+        );
+        let mut later = ACPMessage::new(MessageType::TransactionComplete, "node2".to_string(), None, Vec::new());
+        later.set_transaction_deadline(now + chrono::Duration::minutes(30), EXECUTION_PHASE);
+        let mut due_soon = ACPMessage::new(MessageType::TransactionComplete, "node3".to_string(), None, Vec::new());
+        due_soon.set_transaction_deadline(now + chrono::Duration::seconds(45), EXECUTION_PHASE);
+        let mut negotiating = ACPMessage::new(MessageType::TransactionProposal, "node4".to_string(), None, Vec::new());
+        negotiating.set_transaction_deadline(now + chrono::Duration::seconds(45), "negotiation");
+
+        queue.push(fresh).unwrap();
+        queue.push(PriorityMessage::new(later, MessagePriority::Normal)).unwrap();
+        queue.push(PriorityMessage::new(negotiating, MessagePriority::Normal)).unwrap();
+        queue.push(PriorityMessage::new(due_soon, MessagePriority::Normal)).unwrap();
+
+        // Execution within a minute of its deadline is raised to Critical, a
+        // negotiation only to High, where it ties and yields to its deadline
+        assert_eq!(queue.pop_at(now).unwrap().message.from, "node3");
+        assert_eq!(queue.pop_at(now).unwrap().message.from, "node4");
+        assert_eq!(queue.pop_at(now).unwrap().message.from, "node1");
+        assert_eq!(queue.pop_at(now).unwrap().message.from, "node2");
+        assert!(queue.is_empty());
+    }
+
     #[test]
     fn test_message_expiry() {
         let mut message = ACPMessage::new(
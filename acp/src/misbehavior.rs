@@ -0,0 +1,412 @@
+//! Misbehavior Detection Module
+//!
+//! Tracks protocol violations per peer and quarantines Byzantine ones. Each
+//! violation adds a penalty to the peer's score, which decays with a
+//! configurable half-life so the occasional fault of an honest peer fades
+//! while sustained misbehavior accumulates. A peer whose score reaches the
+//! threshold is quarantined: its channels are refused and everything it
+//! sends is dropped until the quarantine expires.
+//!
+//! Quarantining a peer produces a `MisbehaviorReport` signed with this node's
+//! identity key, queued for gossip. Reports from other nodes are checked
+//! against the key they carry (and the reporter's pinned key, if any) and
+//! counted per offender; once enough distinct reporters agree, the offender
+//! is quarantined here too and the confirmation can be fed into reputation.
+//!
+//! Every violation is also charged to the detector's `PeerScorer`, whose
+//! slower-decaying score bans persistent offenders outright; banned peers
+//! count as quarantined for good.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::messaging::messages::ReputationUpdatePayload;
+use crate::security::{PeerScorer, PeerScorerConfig, SecurityManager};
+use crate::{ACPError, Result};
+
+/// Protocol violation attributed to a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Violation {
+    InvalidSignature,                     // Message or report whose signature does not verify
+    MalformedMessage,                     // Frame or envelope that does not decode
+    GossipFlood,                          // Gossip dropped for exceeding the peer's quota
+    FakePeerRecord,                       // Peer record whose identity does not check out
+    ReplayedMessage,                      // Signed message that is stale or was already received
+}
+
+impl Violation {
+    /// Score added per occurrence
+    pub fn penalty(&self) -> f64 {
+        match self {
+            Violation::InvalidSignature => 40.0,
+            Violation::MalformedMessage => 10.0,
+            Violation::GossipFlood => 5.0,
+            Violation::FakePeerRecord => 50.0,
+            Violation::ReplayedMessage => 10.0,
+        }
+    }
+}
+
+/// Scoring and quarantine settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MisbehaviorConfig {
+    pub quarantine_threshold: f64,        // Score that gets a peer quarantined
+    pub score_half_life: Duration,
+    pub quarantine_duration: Duration,
+    pub confirmations: usize,             // Distinct reporters, this node included, that confirm misbehavior
+    #[serde(default)]
+    pub banning: PeerScorerConfig,
+}
+
+impl Default for MisbehaviorConfig {
+    fn default() -> Self {
+        Self {
+            quarantine_threshold: 100.0,
+            score_half_life: Duration::from_secs(600),
+            quarantine_duration: Duration::from_secs(3600),
+            confirmations: 3,
+            banning: PeerScorerConfig::default(),
+        }
+    }
+}
+
+/// Signed claim that a node quarantined a peer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MisbehaviorReport {
+    pub offender: String,
+    pub reporter: String,
+    pub violations: BTreeMap<Violation, u32>,  // Occurrences behind the quarantine
+    pub score: f64,
+    pub issued_at: DateTime<Utc>,
+    pub reporter_key: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+impl MisbehaviorReport {
+    /// Check the signature against the key the report carries
+    pub fn verify(&self) -> Result<VerifyingKey> {
+        let key = VerifyingKey::from_bytes(&self.reporter_key)
+            .map_err(|_| ACPError::Security(format!("Invalid key on report from {}", self.reporter)))?;
+        let signature = Signature::from_slice(&self.signature)
+            .map_err(|_| ACPError::Security(format!("Malformed signature on report from {}", self.reporter)))?;
+        key.verify(&self.signing_bytes()?, &signature)
+            .map_err(|_| ACPError::Security(format!("Bad signature on report from {}", self.reporter)))?;
+        Ok(key)
+    }
+
+    fn signing_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(&(
+            &self.offender,
+            &self.reporter,
+            &self.violations,
+            self.score,
+            &self.issued_at,
+            &self.reporter_key,
+        ))
+        .map_err(|e| ACPError::Security(format!("Report encoding failed: {}", e)))
+    }
+}
+
+/// Misbehavior agreed on by enough distinct reporters
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedMisbehavior {
+    pub offender: String,
+    pub reporters: Vec<String>,
+    pub violations: BTreeMap<Violation, u32>,  // Summed over all reports
+}
+
+impl ConfirmedMisbehavior {
+    /// Reputation update rating the offender at zero
+    pub fn reputation_update(&self) -> ReputationUpdatePayload {
+        ReputationUpdatePayload {
+            agent_id: self.offender.clone(),
+            transaction_id: uuid::Uuid::nil(),  // Not tied to a transaction
+            rating: 0.0,
+            feedback: format!("Misbehavior confirmed by {} peers", self.reporters.len()),
+            metrics: self
+                .violations
+                .iter()
+                .map(|(violation, count)| (format!("{:?}", violation), *count as f64))
+                .collect(),
+        }
+    }
+}
+
+/// Violation history of one peer
+#[derive(Debug, Clone)]
+struct PeerRecord {
+    score: f64,
+    updated_at: Instant,
+    violations: BTreeMap<Violation, u32>,
+    quarantined_until: Option<Instant>,
+}
+
+impl PeerRecord {
+    fn decay(&mut self, half_life: Duration, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.score *= 0.5f64.powf(elapsed / half_life.as_secs_f64().max(f64::EPSILON));
+        self.updated_at = now;
+    }
+}
+
+/// Per-peer violation scoring, quarantine, and report confirmation
+pub struct MisbehaviorDetector {
+    config: MisbehaviorConfig,
+    security: Arc<SecurityManager>,
+    peers: Mutex<HashMap<String, PeerRecord>>,
+    reports: Mutex<HashMap<String, HashMap<String, MisbehaviorReport>>>,  // Offender -> reporter -> report
+    confirmed: Mutex<HashSet<String>>,
+    outbox: Mutex<Vec<MisbehaviorReport>>,  // Own reports waiting to be gossiped
+    scorer: Arc<PeerScorer>,
+}
+
+impl MisbehaviorDetector {
+    pub fn new(config: MisbehaviorConfig, security: Arc<SecurityManager>) -> Self {
+        let scorer = Arc::new(PeerScorer::new(config.banning.clone()));
+        Self {
+            config,
+            security,
+            peers: Mutex::new(HashMap::new()),
+            reports: Mutex::new(HashMap::new()),
+            confirmed: Mutex::new(HashSet::new()),
+            outbox: Mutex::new(Vec::new()),
+            scorer,
+        }
+    }
+
+    pub fn config(&self) -> &MisbehaviorConfig {
+        &self.config
+    }
+
+    /// Long-term scores and bans, for discovery and persistence
+    pub fn scorer(&self) -> &Arc<PeerScorer> {
+        &self.scorer
+    }
+
+    /// Charge a violation to a peer, returning whether it was just quarantined
+    pub fn record(&self, peer_id: &str, violation: Violation, now: Instant) -> bool {
+        self.scorer.record(peer_id, violation, Utc::now());
+        let mut peers = self.peers.lock();
+        let record = peers.entry(peer_id.to_string()).or_insert_with(|| PeerRecord {
+            score: 0.0,
+            updated_at: now,
+            violations: BTreeMap::new(),
+            quarantined_until: None,
+        });
+        record.decay(self.config.score_half_life, now);
+        record.score += violation.penalty();
+        *record.violations.entry(violation).or_default() += 1;
+        tracing::debug!("{:?} from {}, score now {:.1}", violation, peer_id, record.score);
+
+        if matches!(record.quarantined_until, Some(until) if now < until) || record.score < self.config.quarantine_threshold {
+            return false;
+        }
+        record.quarantined_until = Some(now + self.config.quarantine_duration);
+        tracing::warn!("Quarantining {} (score {:.1})", peer_id, record.score);
+
+        let report = self.sign_report(peer_id, record);
+        drop(peers);
+        match report {
+            Ok(report) => {
+                self.reports
+                    .lock()
+                    .entry(peer_id.to_string())
+                    .or_default()
+                    .insert(report.reporter.clone(), report.clone());
+                self.outbox.lock().push(report);
+            }
+            Err(e) => tracing::warn!("Could not sign misbehavior report on {}: {}", peer_id, e),
+        }
+        true
+    }
+
+    /// Current score of a peer, decayed to `now`
+    pub fn score(&self, peer_id: &str, now: Instant) -> f64 {
+        self.peers.lock().get_mut(peer_id).map_or(0.0, |record| {
+            record.decay(self.config.score_half_life, now);
+            record.score
+        })
+    }
+
+    /// Whether a peer is in quarantine at `now`, or banned
+    pub fn is_quarantined(&self, peer_id: &str, now: Instant) -> bool {
+        if self.scorer.is_banned(peer_id) {
+            return true;
+        }
+        self.peers
+            .lock()
+            .get(peer_id)
+            .and_then(|record| record.quarantined_until)
+            .is_some_and(|until| now < until)
+    }
+
+    /// Peers in quarantine at `now`
+    pub fn quarantined(&self, now: Instant) -> Vec<String> {
+        self.peers
+            .lock()
+            .iter()
+            .filter(|(_, record)| record.quarantined_until.is_some_and(|until| now < until))
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect()
+    }
+
+    /// Forget a peer's violations, reports, and quarantine
+    pub fn release(&self, peer_id: &str) {
+        self.peers.lock().remove(peer_id);
+        self.reports.lock().remove(peer_id);
+        self.confirmed.lock().remove(peer_id);
+    }
+
+    /// Own reports produced since the last call, for gossip
+    pub fn take_reports(&self) -> Vec<MisbehaviorReport> {
+        std::mem::take(&mut *self.outbox.lock())
+    }
+
+    /// Count a report from another node, returning the misbehavior once
+    /// enough distinct reporters confirm it
+    ///
+    /// Fails if the report's signature or key does not check out; the caller
+    /// should charge that to whoever delivered it.
+    pub fn ingest_report(&self, report: MisbehaviorReport, now: Instant) -> Result<Option<ConfirmedMisbehavior>> {
+        if report.reporter == self.security.node_id() {
+            return Ok(None);
+        }
+        let key = report.verify()?;
+        if self.security.pinned_key(&report.reporter).is_some_and(|pinned| pinned != key) {
+            return Err(ACPError::Security(format!("Report key mismatch for {}", report.reporter)));
+        }
+        // Self-reports prove nothing and quarantined nodes are not trusted as witnesses
+        if report.reporter == report.offender || self.is_quarantined(&report.reporter, now) {
+            return Ok(None);
+        }
+
+        let offender = report.offender.clone();
+        let mut reports = self.reports.lock();
+        let by_reporter = reports.entry(offender.clone()).or_default();
+        by_reporter.insert(report.reporter.clone(), report);
+        if by_reporter.len() < self.config.confirmations || !self.confirmed.lock().insert(offender.clone()) {
+            return Ok(None);
+        }
+
+        let mut violations = BTreeMap::new();
+        for report in by_reporter.values() {
+            for (violation, count) in &report.violations {
+                *violations.entry(*violation).or_default() += count;
+            }
+        }
+        let mut reporters: Vec<String> = by_reporter.keys().cloned().collect();
+        reporters.sort();
+        drop(reports);
+
+        self.peers
+            .lock()
+            .entry(offender.clone())
+            .or_insert_with(|| PeerRecord {
+                score: 0.0,
+                updated_at: now,
+                violations: BTreeMap::new(),
+                quarantined_until: None,
+            })
+            .quarantined_until = Some(now + self.config.quarantine_duration);
+        tracing::warn!("Misbehavior of {} confirmed by {} reporters", offender, reporters.len());
+
+        Ok(Some(ConfirmedMisbehavior {
+            offender,
+            reporters,
+            violations,
+        }))
+    }
+
+    fn sign_report(&self, offender: &str, record: &PeerRecord) -> Result<MisbehaviorReport> {
+        let mut report = MisbehaviorReport {
+            offender: offender.to_string(),
+            reporter: self.security.node_id().to_string(),
+            violations: record.violations.clone(),
+            score: record.score,
+            issued_at: Utc::now(),
+            reporter_key: self.security.verifying_key().to_bytes(),
+            signature: Vec::new(),
+        };
+        report.signature = self.security.sign(&report.signing_bytes()?).to_bytes().to_vec();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector(node_id: &str) -> MisbehaviorDetector {
+        MisbehaviorDetector::new(MisbehaviorConfig::default(), Arc::new(SecurityManager::for_node(node_id)))
+    }
+
+    #[test]
+    fn test_violations_accumulate_decay_and_quarantine() {
+        let detector = detector("alice");
+        let start = Instant::now();
+
+        for _ in 0..9 {
+            assert!(!detector.record("mallory", Violation::MalformedMessage, start));
+        }
+        // Ten minutes is one half-life
+        let later = start + Duration::from_secs(600);
+        assert!((detector.score("mallory", later) - 45.0).abs() < 1e-6);
+        assert!(!detector.is_quarantined("mallory", later));
+
+        assert!(!detector.record("mallory", Violation::InvalidSignature, later));
+        assert!(detector.record("mallory", Violation::FakePeerRecord, later));
+        assert!(detector.is_quarantined("mallory", later));
+        assert!(!detector.record("mallory", Violation::FakePeerRecord, later));
+        assert!(!detector.is_quarantined("mallory", later + Duration::from_secs(3601)));
+
+        let reports = detector.take_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].violations[&Violation::MalformedMessage], 9);
+        assert!(reports[0].verify().is_ok());
+        assert!(detector.take_reports().is_empty());
+    }
+
+    #[test]
+    fn test_reports_are_confirmed_by_distinct_valid_reporters() {
+        let now = Instant::now();
+        let observer = detector("observer");
+        let witnesses: Vec<_> = ["alice", "bob", "carol"].iter().map(|id| detector(id)).collect();
+        let reports: Vec<_> = witnesses
+            .iter()
+            .map(|witness| {
+                for _ in 0..3 {
+                    witness.record("mallory", Violation::InvalidSignature, now);
+                }
+                witness.take_reports().remove(0)
+            })
+            .collect();
+
+        // Tampered and re-sent reports do not count
+        let mut forged = reports[0].clone();
+        forged.reporter = "dave".to_string();
+        assert!(observer.ingest_report(forged, now).is_err());
+        assert_eq!(observer.ingest_report(reports[0].clone(), now).unwrap(), None);
+        assert_eq!(observer.ingest_report(reports[0].clone(), now).unwrap(), None);
+        assert_eq!(observer.ingest_report(reports[1].clone(), now).unwrap(), None);
+        assert!(!observer.is_quarantined("mallory", now));
+
+        let confirmed = observer.ingest_report(reports[2].clone(), now).unwrap().unwrap();
+        assert_eq!(confirmed.reporters, vec!["alice", "bob", "carol"]);
+        assert_eq!(confirmed.violations[&Violation::InvalidSignature], 9);
+        assert!(observer.is_quarantined("mallory", now));
+        assert_eq!(confirmed.reputation_update().rating, 0.0);
+
+        // Confirmed once until released
+        let fourth = detector("erin");
+        for _ in 0..3 {
+            fourth.record("mallory", Violation::InvalidSignature, now);
+        }
+        assert_eq!(observer.ingest_report(fourth.take_reports().remove(0), now).unwrap(), None);
+    }
+}
@@ -0,0 +1,376 @@
+//! NAT Traversal Module
+//!
+//! Helps agents behind NAT take part in the network. A node learns its
+//! public (server-reflexive) address with a STUN binding request (RFC 5389),
+//! answered by any STUN server or by a relay running `serve_stun`. Two nodes
+//! that know each other's public addresses can then punch a UDP hole by
+//! probing each other at the same time; the punched socket can carry QUIC
+//! directly.
+//!
+//! Nodes that stay unreachable fall back to a relay: a `NodeType::Relay`
+//! peer that accepts a `RelayRequest` keeps the requester's connection open
+//! and forwards `RelayData` envelopes addressed to it over that connection.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+
+use crate::{ACPError, Result};
+
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_RESPONSE: u16 = 0x0101;
+const STUN_MAGIC_COOKIE: u32 = 0x2112_A442;
+const STUN_HEADER_LEN: usize = 20;
+const ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+/// Payload of a hole-punching probe
+const PUNCH_PROBE: &[u8] = b"acp-punch";
+
+/// How a node can be reached from the internet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Reachability {
+    /// Listening on its public address
+    Public,
+    /// Behind NAT; seen from outside as `external`
+    Nat { external: SocketAddr },
+}
+
+impl Reachability {
+    /// Compare a socket's local address with its address as seen by a STUN server
+    pub fn classify(local: SocketAddr, external: SocketAddr) -> Self {
+        if local == external {
+            Reachability::Public
+        } else {
+            Reachability::Nat { external }
+        }
+    }
+}
+
+/// Learn `socket`'s public address from the STUN server at `server`
+pub async fn stun_binding_request(socket: &UdpSocket, server: SocketAddr, timeout: Duration) -> Result<SocketAddr> {
+    let transaction_id: [u8; 12] = rand::random();
+    socket
+        .send_to(&encode_stun(STUN_BINDING_REQUEST, &transaction_id, &[]), server)
+        .await
+        .map_err(|e| ACPError::Network(format!("STUN request to {} failed: {}", server, e)))?;
+
+    let mut buf = [0u8; 512];
+    tokio::time::timeout(timeout, async {
+        loop {
+            let (len, from) = socket
+                .recv_from(&mut buf)
+                .await
+                .map_err(|e| ACPError::Network(format!("STUN response from {} failed: {}", server, e)))?;
+            // Stray datagrams and responses to other requests are skipped
+            if from == server {
+                if let Some(mapped) = decode_binding_response(&buf[..len], &transaction_id) {
+                    return Ok(mapped);
+                }
+            }
+        }
+    })
+    .await
+    .map_err(|_| ACPError::Timeout)?
+}
+
+/// Answer STUN binding requests on `socket` until it fails
+pub async fn serve_stun(socket: UdpSocket) {
+    let mut buf = [0u8; 512];
+    loop {
+        let (len, from) = match socket.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(e) => {
+                tracing::warn!("STUN responder stopped: {}", e);
+                return;
+            }
+        };
+        let Some(transaction_id) = decode_binding_request(&buf[..len]) else {
+            continue;
+        };
+        let response = encode_stun(STUN_BINDING_RESPONSE, &transaction_id, &xor_mapped_address(from, &transaction_id));
+        if let Err(e) = socket.send_to(&response, from).await {
+            tracing::debug!("STUN response to {} failed: {}", from, e);
+        }
+    }
+}
+
+fn encode_stun(message_type: u16, transaction_id: &[u8; 12], attributes: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(STUN_HEADER_LEN + attributes.len());
+    message.extend_from_slice(&message_type.to_be_bytes());
+    message.extend_from_slice(&(attributes.len() as u16).to_be_bytes());
+    message.extend_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    message.extend_from_slice(transaction_id);
+    message.extend_from_slice(attributes);
+    message
+}
+
+/// Split a STUN message into its type, transaction id, and attributes
+fn decode_stun(message: &[u8]) -> Option<(u16, [u8; 12], &[u8])> {
+    if message.len() < STUN_HEADER_LEN {
+        return None;
+    }
+    let message_type = u16::from_be_bytes([message[0], message[1]]);
+    let length = u16::from_be_bytes([message[2], message[3]]) as usize;
+    let cookie = u32::from_be_bytes([message[4], message[5], message[6], message[7]]);
+    if cookie != STUN_MAGIC_COOKIE || message.len() < STUN_HEADER_LEN + length {
+        return None;
+    }
+    let transaction_id = message[8..20].try_into().ok()?;
+    Some((message_type, transaction_id, &message[STUN_HEADER_LEN..STUN_HEADER_LEN + length]))
+}
+
+fn decode_binding_request(message: &[u8]) -> Option<[u8; 12]> {
+    match decode_stun(message)? {
+        (STUN_BINDING_REQUEST, transaction_id, _) => Some(transaction_id),
+        _ => None,
+    }
+}
+
+/// Mapped address from a binding response to `transaction_id`
+fn decode_binding_response(message: &[u8], transaction_id: &[u8; 12]) -> Option<SocketAddr> {
+    let (message_type, id, mut attributes) = decode_stun(message)?;
+    if message_type != STUN_BINDING_RESPONSE || id != *transaction_id {
+        return None;
+    }
+
+    let mut mapped = None;
+    while attributes.len() >= 4 {
+        let kind = u16::from_be_bytes([attributes[0], attributes[1]]);
+        let length = u16::from_be_bytes([attributes[2], attributes[3]]) as usize;
+        let value = attributes.get(4..4 + length)?;
+        match kind {
+            ATTR_XOR_MAPPED_ADDRESS => return decode_address(value, Some(transaction_id)),
+            ATTR_MAPPED_ADDRESS => mapped = decode_address(value, None),
+            _ => {}
+        }
+        // Attributes are padded to a multiple of four bytes
+        let padded = (4 + length).div_ceil(4) * 4;
+        attributes = attributes.get(padded..).unwrap_or_default();
+    }
+    mapped
+}
+
+/// Decode an address attribute, un-XORing it when `transaction_id` is given
+fn decode_address(value: &[u8], transaction_id: Option<&[u8; 12]>) -> Option<SocketAddr> {
+    let mut port = u16::from_be_bytes([*value.get(2)?, *value.get(3)?]);
+    let len = match value.get(1)? {
+        0x01 => 4,
+        0x02 => 16,
+        _ => return None,
+    };
+    let mut octets = value.get(4..4 + len)?.to_vec();
+    if let Some(id) = transaction_id {
+        port ^= (STUN_MAGIC_COOKIE >> 16) as u16;
+        xor_in_place(&mut octets, &xor_mask(id));
+    }
+    let ip = match <[u8; 4]>::try_from(octets.as_slice()) {
+        Ok(v4) => IpAddr::V4(Ipv4Addr::from(v4)),
+        Err(_) => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(octets.as_slice()).ok()?)),
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// XOR-MAPPED-ADDRESS attribute for `addr`
+fn xor_mapped_address(addr: SocketAddr, transaction_id: &[u8; 12]) -> Vec<u8> {
+    let (family, mut octets) = match addr.ip() {
+        IpAddr::V4(ip) => (0x01, ip.octets().to_vec()),
+        IpAddr::V6(ip) => (0x02, ip.octets().to_vec()),
+    };
+    xor_in_place(&mut octets, &xor_mask(transaction_id));
+
+    let mut attribute = Vec::with_capacity(8 + octets.len());
+    attribute.extend_from_slice(&ATTR_XOR_MAPPED_ADDRESS.to_be_bytes());
+    attribute.extend_from_slice(&(4 + octets.len() as u16).to_be_bytes());
+    attribute.extend_from_slice(&[0, family]);
+    attribute.extend_from_slice(&(addr.port() ^ (STUN_MAGIC_COOKIE >> 16) as u16).to_be_bytes());
+    attribute.extend_from_slice(&octets);
+    attribute
+}
+
+/// Magic cookie followed by the transaction id
+fn xor_mask(transaction_id: &[u8; 12]) -> [u8; 16] {
+    let mut mask = [0u8; 16];
+    mask[..4].copy_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    mask[4..].copy_from_slice(transaction_id);
+    mask
+}
+
+fn xor_in_place(octets: &mut [u8], mask: &[u8; 16]) {
+    octets.iter_mut().zip(mask).for_each(|(octet, m)| *octet ^= m);
+}
+
+/// Hole punching schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PunchConfig {
+    pub attempts: u32,
+    pub interval: Duration,
+}
+
+impl Default for PunchConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 20,
+            interval: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Probe `peer`'s public address from `socket` while it probes back, opening
+/// a path through both NATs. Both sides must punch at about the same time.
+///
+/// Returns the address the peer's probes came from, which differs from
+/// `peer` in port when the peer's NAT remaps per destination.
+pub async fn punch_hole(socket: &UdpSocket, peer: SocketAddr, config: &PunchConfig) -> Result<SocketAddr> {
+    let mut ticker = tokio::time::interval(config.interval);
+    let mut buf = [0u8; 64];
+    let mut sent = 0;
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if sent == config.attempts {
+                    return Err(ACPError::Connection(format!("No reply from {} after {} probes", peer, sent)));
+                }
+                // Unreachable errors are expected until the peer's NAT opens
+                let _ = socket.send_to(PUNCH_PROBE, peer).await;
+                sent += 1;
+            }
+            received = socket.recv_from(&mut buf) => {
+                let Ok((len, from)) = received else { continue };
+                if from.ip() == peer.ip() && &buf[..len] == PUNCH_PROBE {
+                    // Make sure the peer sees a probe arrive too
+                    let _ = socket.send_to(PUNCH_PROBE, from).await;
+                    return Ok(from);
+                }
+            }
+        }
+    }
+}
+
+/// Relay settings for `NodeType::Relay` nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayConfig {
+    pub max_reservations: usize,
+    pub stun_address: Option<String>,     // Also answer STUN binding requests here
+}
+
+impl Default for RelayConfig {
+    fn default() -> Self {
+        Self {
+            max_reservations: 128,
+            stun_address: None,
+        }
+    }
+}
+
+/// Relay activity counters
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayStats {
+    pub reservations: usize,
+    pub forwarded: u64,
+    pub dropped: u64,                     // Envelopes for nodes without a live reservation
+}
+
+/// Reservations held by a relay for unreachable nodes
+pub struct RelayService {
+    config: RelayConfig,
+    reservations: Mutex<HashSet<String>>,
+    forwarded: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl RelayService {
+    pub fn new(config: RelayConfig) -> Self {
+        Self {
+            config,
+            reservations: Mutex::new(HashSet::new()),
+            forwarded: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &RelayConfig {
+        &self.config
+    }
+
+    /// Reserve a slot for `node_id`; false when the relay is full
+    pub fn reserve(&self, node_id: &str) -> bool {
+        let mut reservations = self.reservations.lock();
+        if reservations.contains(node_id) {
+            return true;
+        }
+        if reservations.len() >= self.config.max_reservations {
+            return false;
+        }
+        reservations.insert(node_id.to_string());
+        true
+    }
+
+    pub fn release(&self, node_id: &str) {
+        self.reservations.lock().remove(node_id);
+    }
+
+    pub fn is_reserved(&self, node_id: &str) -> bool {
+        self.reservations.lock().contains(node_id)
+    }
+
+    /// Count an envelope as forwarded or dropped
+    pub fn record(&self, forwarded: bool) {
+        let counter = if forwarded { &self.forwarded } else { &self.dropped };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> RelayStats {
+        RelayStats {
+            reservations: self.reservations.lock().len(),
+            forwarded: self.forwarded.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stun_discovers_mapped_address_and_hole_punch_connects() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = server.local_addr().unwrap();
+        tokio::spawn(serve_stun(server));
+
+        let a = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let b = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let external = stun_binding_request(&a, server_addr, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(external, a.local_addr().unwrap());
+        assert_eq!(Reachability::classify(a.local_addr().unwrap(), external), Reachability::Public);
+
+        let (a_addr, b_addr) = (a.local_addr().unwrap(), b.local_addr().unwrap());
+        let config = PunchConfig::default();
+        let (from_b, from_a) = tokio::join!(punch_hole(&a, b_addr, &config), punch_hole(&b, a_addr, &config));
+        assert_eq!(from_b.unwrap(), b_addr);
+        assert_eq!(from_a.unwrap(), a_addr);
+
+        let v6 = SocketAddr::from((Ipv6Addr::LOCALHOST, 4242));
+        let id = [7u8; 12];
+        let response = encode_stun(STUN_BINDING_RESPONSE, &id, &xor_mapped_address(v6, &id));
+        assert_eq!(decode_binding_response(&response, &id), Some(v6));
+        assert_eq!(decode_binding_response(&response, &[0u8; 12]), None);
+    }
+
+    #[test]
+    fn test_relay_reservations_are_capped() {
+        let relay = RelayService::new(RelayConfig { max_reservations: 1, ..RelayConfig::default() });
+        assert!(relay.reserve("a"));
+        assert!(relay.reserve("a"));
+        assert!(!relay.reserve("b"));
+        relay.release("a");
+        assert!(relay.reserve("b"));
+        assert_eq!(relay.stats().reservations, 1);
+    }
+}
@@ -0,0 +1,288 @@
+//! Reliable Delivery Outbox
+//!
+//! Messages flagged `reliable` are written to the outbox before they are
+//! first sent and stay there until the recipient acknowledges them with an
+//! `Ack` message. Unacknowledged messages are retransmitted with doubling
+//! intervals and given up after `max_attempts`. Either outcome settles the
+//! message and produces a `DeliveryReceipt`.
+//!
+//! The outbox is a single log file framed like the gossip journal: a
+//! `queued` record holding the message and a `settled` record holding its
+//! id. On open the log is replayed, every message never settled is queued
+//! for immediate retransmission, and the log is compacted down to them.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::journal::{frame_record, read_records};
+use crate::messaging::ACPMessage;
+
+/// Record kind for a message entering the outbox
+const RECORD_QUEUED: u8 = 1;
+
+/// Record kind for a message acknowledged or given up
+const RECORD_SETTLED: u8 = 2;
+
+/// Outbox configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxConfig {
+    pub path: PathBuf,
+    pub max_attempts: u32,                // Sends before a message is given up
+    pub retry_interval: Duration,         // Wait before the first retransmission, doubled each time
+    pub max_retry_interval: Duration,
+    pub sync_writes: bool,                // fsync after every record
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("./solace_data/outbox.log"),
+            max_attempts: 8,
+            retry_interval: Duration::from_secs(2),
+            max_retry_interval: Duration::from_secs(60),
+            sync_writes: true,
+        }
+    }
+}
+
+/// How a reliable message's delivery ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Delivered,
+    Abandoned,                            // No acknowledgment after `max_attempts` sends
+}
+
+/// Outcome of a reliable message, published on the node's event bus
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryReceipt {
+    pub message_id: Uuid,
+    pub destination: Option<String>,
+    pub attempts: u32,
+    pub status: DeliveryStatus,
+}
+
+/// A message waiting for acknowledgment
+#[derive(Debug, Clone)]
+struct PendingDelivery {
+    message: ACPMessage,
+    attempts: u32,
+    next_attempt: Instant,
+}
+
+/// Durable store of reliable messages awaiting acknowledgment
+pub struct Outbox {
+    config: OutboxConfig,
+    log: File,
+    pending: HashMap<Uuid, PendingDelivery>,
+    records: usize,                       // Records in the log, to decide when to compact
+}
+
+impl Outbox {
+    /// Open the outbox, queueing every message it still holds for retransmission
+    pub fn open(config: OutboxConfig) -> Result<Self> {
+        if let Some(dir) = config.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut queued: Vec<ACPMessage> = Vec::new();
+        if config.path.exists() {
+            for (kind, body) in read_records(&config.path)? {
+                match kind {
+                    RECORD_QUEUED => queued.push(serde_json::from_slice(&body)?),
+                    RECORD_SETTLED => {
+                        let id = String::from_utf8_lossy(&body);
+                        queued.retain(|message| message.id.to_string() != id);
+                    }
+                    other => warn!("Skipping unknown outbox record kind {}", other),
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let pending = queued
+            .into_iter()
+            .map(|message| (message.id, PendingDelivery { message, attempts: 0, next_attempt: now }))
+            .collect();
+        let mut outbox = Self {
+            log: OpenOptions::new().create(true).append(true).open(&config.path)?,
+            config,
+            pending,
+            records: 0,
+        };
+        outbox.compact()?;
+
+        if !outbox.pending.is_empty() {
+            info!("Recovered {} unacknowledged messages from the outbox", outbox.pending.len());
+        }
+        Ok(outbox)
+    }
+
+    /// Queue a message about to be sent for the first time
+    pub fn push(&mut self, message: &ACPMessage) -> Result<()> {
+        self.write_record(RECORD_QUEUED, &serde_json::to_vec(message)?)?;
+        self.pending.insert(message.id, PendingDelivery {
+            message: message.clone(),
+            attempts: 1,
+            next_attempt: Instant::now() + self.config.retry_interval,
+        });
+        Ok(())
+    }
+
+    /// Settle an acknowledged message, returning its receipt if it was pending
+    pub fn ack(&mut self, message_id: &Uuid) -> Result<Option<DeliveryReceipt>> {
+        let Some(delivery) = self.pending.remove(message_id) else {
+            return Ok(None);
+        };
+        self.settle(message_id)?;
+        Ok(Some(receipt(&delivery, DeliveryStatus::Delivered)))
+    }
+
+    /// Messages due for retransmission at `now`, and receipts for those given up
+    pub fn due(&mut self, now: Instant) -> Result<(Vec<ACPMessage>, Vec<DeliveryReceipt>)> {
+        let mut resend = Vec::new();
+        let mut abandoned = Vec::new();
+        let due: Vec<Uuid> = self.pending.iter().filter(|(_, delivery)| delivery.next_attempt <= now).map(|(id, _)| *id).collect();
+
+        for id in due {
+            let Some(delivery) = self.pending.get_mut(&id) else { continue };
+            if delivery.attempts >= self.config.max_attempts {
+                let delivery = self.pending.remove(&id).expect("due delivery is pending");
+                self.settle(&id)?;
+                abandoned.push(receipt(&delivery, DeliveryStatus::Abandoned));
+                continue;
+            }
+            let backoff = self.config.retry_interval.saturating_mul(1 << delivery.attempts.min(16));
+            delivery.attempts += 1;
+            delivery.next_attempt = now + backoff.min(self.config.max_retry_interval);
+            resend.push(delivery.message.clone());
+        }
+        Ok((resend, abandoned))
+    }
+
+    /// Number of messages waiting for acknowledgment
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn settle(&mut self, message_id: &Uuid) -> Result<()> {
+        self.write_record(RECORD_SETTLED, message_id.to_string().as_bytes())?;
+        // Settled records outnumbering pending ones are dead weight
+        if self.records > 64 && self.records > 4 * self.pending.len() {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    fn write_record(&mut self, kind: u8, body: &[u8]) -> Result<()> {
+        self.log.write_all(&frame_record(kind, body))?;
+        if self.config.sync_writes {
+            self.log.sync_data()?;
+        }
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Rewrite the log with only the pending messages
+    fn compact(&mut self) -> Result<()> {
+        let staging = self.config.path.with_extension("compact");
+        let mut file = File::create(&staging)?;
+        for delivery in self.pending.values() {
+            file.write_all(&frame_record(RECORD_QUEUED, &serde_json::to_vec(&delivery.message)?))?;
+        }
+        file.sync_all()?;
+        fs::rename(&staging, &self.config.path)?;
+
+        self.log = OpenOptions::new().append(true).open(&self.config.path)?;
+        self.records = self.pending.len();
+        Ok(())
+    }
+}
+
+fn receipt(delivery: &PendingDelivery, status: DeliveryStatus) -> DeliveryReceipt {
+    DeliveryReceipt {
+        message_id: delivery.message.id,
+        destination: delivery.message.to.clone(),
+        attempts: delivery.attempts,
+        status,
+    }
+}
+
+impl std::fmt::Debug for Outbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Outbox")
+            .field("path", &self.config.path)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging::MessageType;
+
+    fn test_config(dir: &std::path::Path) -> OutboxConfig {
+        OutboxConfig {
+            path: dir.join("outbox.log"),
+            max_attempts: 3,
+            retry_interval: Duration::from_secs(1),
+            sync_writes: false,
+            ..Default::default()
+        }
+    }
+
+    fn reliable(to: &str) -> ACPMessage {
+        let mut message = ACPMessage::new(MessageType::TransactionRequest, "a".to_string(), Some(to.to_string()), vec![1, 2, 3]);
+        message.set_reliable();
+        message
+    }
+
+    #[test]
+    fn test_unacked_messages_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let (acked, unacked) = (reliable("b"), reliable("c"));
+        {
+            let mut outbox = Outbox::open(test_config(dir.path())).unwrap();
+            outbox.push(&acked).unwrap();
+            outbox.push(&unacked).unwrap();
+            let receipt = outbox.ack(&acked.id).unwrap().unwrap();
+            assert_eq!((receipt.status, receipt.attempts), (DeliveryStatus::Delivered, 1));
+            assert!(outbox.ack(&acked.id).unwrap().is_none());
+        }
+
+        let mut outbox = Outbox::open(test_config(dir.path())).unwrap();
+        assert_eq!(outbox.pending_count(), 1);
+        let (resend, _) = outbox.due(Instant::now()).unwrap();
+        assert_eq!(resend.len(), 1);
+        assert_eq!(resend[0].id, unacked.id);
+    }
+
+    #[test]
+    fn test_retransmits_back_off_then_give_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut outbox = Outbox::open(test_config(dir.path())).unwrap();
+        let message = reliable("b");
+        outbox.push(&message).unwrap();
+
+        let start = Instant::now();
+        assert!(outbox.due(start).unwrap().0.is_empty());
+        assert_eq!(outbox.due(start + Duration::from_secs(1)).unwrap().0.len(), 1);
+        // The next retransmission waits twice as long
+        assert!(outbox.due(start + Duration::from_secs(2)).unwrap().0.is_empty());
+        assert_eq!(outbox.due(start + Duration::from_secs(3)).unwrap().0.len(), 1);
+
+        let (resend, abandoned) = outbox.due(start + Duration::from_secs(60)).unwrap();
+        assert!(resend.is_empty());
+        assert_eq!(abandoned[0].status, DeliveryStatus::Abandoned);
+        assert_eq!(abandoned[0].attempts, 3);
+        assert_eq!(outbox.pending_count(), 0);
+    }
+}
@@ -0,0 +1,1601 @@
+//! Peer-to-Peer Transport Module
+//!
+//! Moves serialized `ACPMessage`s between nodes. The default transport is
+//! plain TCP: every message travels as one frame, a 4-byte big-endian length
+//! followed by the bincode-encoded message, and frames larger than the
+//! configured maximum close the connection. With the `quic` feature, QUIC
+//! can be selected instead; messages then travel over one bidirectional
+//! stream per connection.
+//!
+//! Every connection, on either transport, starts with a Noise XX handshake
+//! in which both sides prove their node identity (see `security`). After it,
+//! each message is encrypted as one or more Noise transport messages, each
+//! sent as its own frame. QUIC's TLS layer uses a throwaway self-signed
+//! certificate; peers are authenticated by the Noise handshake inside it.
+//!
+//! Channels are bidirectional. Messages arriving over a connection this
+//! node dialed are delivered like those on accepted connections, and a peer
+//! that dialed in can be sent to over its own connection by node id. That
+//! is what lets a relay reach nodes behind NAT (see `nat`): a `NodeType::Relay`
+//! node forwards `RelayData` envelopes to nodes that reserved a slot with a
+//! `RelayRequest`, over the connection they keep open to it.
+//!
+//! Outbound connections are pooled per remote address and reused across
+//! sends. A send over a pooled connection that turns out to be dead is
+//! retried once on a fresh connection. An address that refuses connections
+//! is backed off exponentially: until its backoff expires, sends fail fast
+//! instead of waiting on another connect timeout.
+//!
+//! A `ConnectionPolicy` bounds the connections kept in both directions.
+//! Accepted connections may take only a share of `max_peers`, and only a few
+//! may come from one IP address, so inbound peers cannot crowd out the ones
+//! this node chose. Beyond `max_peers` the least recently used connections
+//! are shed, accepted ones first while they exceed their share. Shedding is
+//! graceful: a connection leaves the pool, and it closes once sends already
+//! holding it finish. Preferred peers are pinned: exempt from the limits and
+//! never shed. `ConnectionManager::connection_states` shows what is open.
+//!
+//! Every channel's reads and writes are counted and capped by the node's
+//! `BandwidthMeter` (see `bandwidth`). Under a bandwidth budget, each frame
+//! is shaped at its message's `traffic_priority`; a frame read is charged
+//! once decoded, so an undecodable one counts as `Normal`.
+//!
+//! Peers are dialed at a `PeerAddress`. With `TransportConfig::proxy` set,
+//! TCP connections are tunneled through a SOCKS5 or HTTP CONNECT proxy (see
+//! `proxy`); with the `tor` feature, peers may then also be onion services.
+//! A proxied connection's remote address is the proxy's.
+//!
+//! Protocol violations seen on a channel (undecodable frames, messages whose
+//! signature does not match the channel's peer, malformed relay envelopes)
+//! are charged to that peer in `misbehavior`. Quarantined peers have their
+//! channels closed and refused until the quarantine expires.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::bandwidth::{BandwidthConfig, BandwidthMeter};
+use crate::discovery::NodeType;
+use crate::events::{ACPEvent, EventBus};
+use crate::messaging::{ACPMessage, MessagePriority, MessageType};
+use crate::misbehavior::{MisbehaviorConfig, MisbehaviorDetector, Violation};
+use crate::nat::{serve_stun, RelayConfig, RelayService};
+use crate::proxy::{PeerAddress, ProxyConfig};
+use crate::security::{MessageAuthentication, PeerIdentity, SecurityManager};
+use crate::{constants, ACPConfig, ACPError, Result};
+
+/// Wire transport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Transport {
+    Tcp,
+    #[cfg(feature = "quic")]
+    Quic,
+}
+
+/// Exponential reconnect backoff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconnectBackoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(250),
+            max: Duration::from_secs(30),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl ReconnectBackoff {
+    /// Delay before the next attempt after `failures` consecutive failures
+    pub fn delay(&self, failures: u32) -> Duration {
+        let factor = self.multiplier.powi(failures.saturating_sub(1) as i32);
+        self.initial.mul_f64(factor).min(self.max)
+    }
+}
+
+/// Transport configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransportConfig {
+    pub transport: Transport,
+    pub connect_timeout: Duration,
+    pub max_frame_size: usize,
+    pub max_pooled_connections: usize,    // Least recently used is evicted beyond this
+    pub inbound_buffer: usize,            // Received messages awaiting `incoming()`
+    pub backoff: ReconnectBackoff,
+    pub relay: RelayConfig,               // Used when the node type is `Relay`
+    pub misbehavior: MisbehaviorConfig,
+    pub policy: ConnectionPolicy,
+    pub bandwidth: BandwidthConfig,
+    pub proxy: Option<ProxyConfig>,       // Outbound TCP connections tunnel through it
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            transport: Transport::Tcp,
+            connect_timeout: Duration::from_secs(5),
+            max_frame_size: constants::MAX_MESSAGE_SIZE,
+            max_pooled_connections: constants::MAX_PEERS,
+            inbound_buffer: 1024,
+            backoff: ReconnectBackoff::default(),
+            relay: RelayConfig::default(),
+            misbehavior: MisbehaviorConfig::default(),
+            policy: ConnectionPolicy::default(),
+            bandwidth: BandwidthConfig::default(),
+            proxy: None,
+        }
+    }
+}
+
+/// Limits on the connections a node keeps, and whom they exempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionPolicy {
+    pub max_peers: usize,                 // Open connections in both directions before shedding
+    pub max_inbound_ratio: f64,           // Share of `max_peers` accepted connections may take
+    pub max_per_ip: usize,                // Accepted connections from one IP address
+    pub preferred_peers: HashSet<String>, // Node ids pinned from the start
+}
+
+impl Default for ConnectionPolicy {
+    fn default() -> Self {
+        Self {
+            max_peers: constants::MAX_PEERS,
+            max_inbound_ratio: 0.5,
+            max_per_ip: 8,
+            preferred_peers: HashSet::new(),
+        }
+    }
+}
+
+impl ConnectionPolicy {
+    /// Accepted connections allowed at once
+    pub fn max_inbound(&self) -> usize {
+        (self.max_peers as f64 * self.max_inbound_ratio.clamp(0.0, 1.0)).ceil() as usize
+    }
+}
+
+/// Which side opened a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+/// An open connection, as reported by `ConnectionManager::connection_states`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionState {
+    pub node_id: String,
+    pub remote: SocketAddr,
+    pub direction: Direction,
+    pub pinned: bool,
+    pub connected_for: Duration,
+    pub idle_for: Duration,
+}
+
+/// Message received from a remote node
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    pub remote: SocketAddr,
+    pub peer: PeerIdentity,               // Authenticated sender of the channel; the relay for relayed messages
+    pub message: ACPMessage,
+}
+
+/// Largest Noise message, authentication tag included
+const NOISE_MAX_MESSAGE: usize = 65535;
+
+/// Plaintext carried by one Noise transport message
+const NOISE_MAX_PLAINTEXT: usize = NOISE_MAX_MESSAGE - 16;
+
+/// Bytes on the wire for a message of `len` bytes: its length header and
+/// body, split into Noise messages that each carry a tag and a frame header
+fn wire_size(len: usize) -> usize {
+    let plaintext = 4 + len;
+    plaintext + plaintext.div_ceil(NOISE_MAX_PLAINTEXT) * (16 + 4)
+}
+
+fn unsupported(reason: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Unsupported, reason)
+}
+
+/// Write one length-prefixed frame
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, payload: &[u8]) -> std::io::Result<()> {
+    let len = u32::try_from(payload.len()).map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "frame too large"))?;
+    writer.write_all(&len.to_be_bytes()).await?;
+    writer.write_all(payload).await?;
+    writer.flush().await
+}
+
+/// Read one length-prefixed frame, rejecting frames over `max_len`
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R, max_len: usize) -> std::io::Result<Vec<u8>> {
+    let len = reader.read_u32().await? as usize;
+    if len > max_len {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("frame of {} bytes exceeds limit of {}", len, max_len),
+        ));
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).await?;
+    Ok(payload)
+}
+
+fn noise_error<E: std::fmt::Display>(error: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// Run the Noise XX handshake on a fresh stream and authenticate the peer
+async fn handshake<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    security: &SecurityManager,
+    initiator: bool,
+) -> std::io::Result<(snow::TransportState, PeerIdentity)>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let identity = security.identity_payload().map_err(noise_error)?;
+    let mut buf = vec![0u8; NOISE_MAX_MESSAGE];
+    let (state, peer) = if initiator {
+        let mut state = security.initiator().map_err(noise_error)?;
+        // -> e
+        let len = state.write_message(&[], &mut buf).map_err(noise_error)?;
+        write_frame(writer, &buf[..len]).await?;
+        // <- e, ee, s, es, responder identity
+        let message = read_frame(reader, NOISE_MAX_MESSAGE).await?;
+        let len = state.read_message(&message, &mut buf).map_err(noise_error)?;
+        let peer = authenticate(security, &state, &buf[..len])?;
+        // -> s, se, our identity
+        let len = state.write_message(&identity, &mut buf).map_err(noise_error)?;
+        write_frame(writer, &buf[..len]).await?;
+        (state, peer)
+    } else {
+        let mut state = security.responder().map_err(noise_error)?;
+        let message = read_frame(reader, NOISE_MAX_MESSAGE).await?;
+        state.read_message(&message, &mut buf).map_err(noise_error)?;
+        let len = state.write_message(&identity, &mut buf).map_err(noise_error)?;
+        write_frame(writer, &buf[..len]).await?;
+        let message = read_frame(reader, NOISE_MAX_MESSAGE).await?;
+        let len = state.read_message(&message, &mut buf).map_err(noise_error)?;
+        let peer = authenticate(security, &state, &buf[..len])?;
+        (state, peer)
+    };
+    Ok((state.into_transport_mode().map_err(noise_error)?, peer))
+}
+
+/// Verify the identity proof a peer sent with its static key
+fn authenticate(security: &SecurityManager, state: &snow::HandshakeState, payload: &[u8]) -> std::io::Result<PeerIdentity> {
+    let remote_static = state
+        .get_remote_static()
+        .ok_or_else(|| noise_error("peer sent no static key"))?;
+    security.verify_peer(payload, remote_static).map_err(noise_error)
+}
+
+/// Noise session shared by a channel's reader and writer
+type SharedTransport = Arc<Mutex<snow::TransportState>>;
+
+/// Encrypt and send one message, split across as many Noise messages as it needs
+async fn write_secure<W: AsyncWrite + Unpin>(writer: &mut W, transport: &Mutex<snow::TransportState>, payload: &[u8]) -> std::io::Result<()> {
+    let len = u32::try_from(payload.len()).map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "message too large"))?;
+    let mut plaintext = Vec::with_capacity(4 + payload.len());
+    plaintext.extend_from_slice(&len.to_be_bytes());
+    plaintext.extend_from_slice(payload);
+
+    let mut buf = vec![0u8; NOISE_MAX_MESSAGE];
+    for chunk in plaintext.chunks(NOISE_MAX_PLAINTEXT) {
+        let len = transport.lock().write_message(chunk, &mut buf).map_err(noise_error)?;
+        write_frame(writer, &buf[..len]).await?;
+    }
+    Ok(())
+}
+
+/// Receive and decrypt one message, rejecting messages over `max_len`
+async fn read_secure<R: AsyncRead + Unpin>(reader: &mut R, transport: &Mutex<snow::TransportState>, max_len: usize) -> std::io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; NOISE_MAX_MESSAGE];
+    let mut plaintext = Vec::new();
+    read_noise_message(reader, transport, &mut buf, &mut plaintext).await?;
+    if plaintext.len() < 4 {
+        return Err(noise_error("truncated message header"));
+    }
+    let len = u32::from_be_bytes([plaintext[0], plaintext[1], plaintext[2], plaintext[3]]) as usize;
+    if len > max_len {
+        return Err(noise_error(format!("message of {} bytes exceeds limit of {}", len, max_len)));
+    }
+
+    while plaintext.len() < 4 + len {
+        read_noise_message(reader, transport, &mut buf, &mut plaintext).await?;
+    }
+    if plaintext.len() > 4 + len {
+        return Err(noise_error("message overran its length"));
+    }
+    plaintext.drain(..4);
+    Ok(plaintext)
+}
+
+/// Read one Noise transport message and append its plaintext to `plaintext`
+async fn read_noise_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    transport: &Mutex<snow::TransportState>,
+    buf: &mut [u8],
+    plaintext: &mut Vec<u8>,
+) -> std::io::Result<()> {
+    let frame = read_frame(reader, NOISE_MAX_MESSAGE).await?;
+    let len = transport.lock().read_message(&frame, buf).map_err(noise_error)?;
+    plaintext.extend_from_slice(&buf[..len]);
+    Ok(())
+}
+
+/// Where a channel's reader delivers messages and reports what it saw
+struct ChannelSinks {
+    max_frame_size: usize,
+    inbound: mpsc::Sender<InboundMessage>,
+    misbehavior: Arc<MisbehaviorDetector>,
+    bandwidth: Arc<BandwidthMeter>,
+}
+
+/// Read messages from an authenticated channel until it closes
+async fn read_channel<R: AsyncRead + Unpin>(mut reader: R, transport: SharedTransport, remote: SocketAddr, peer: PeerIdentity, sinks: ChannelSinks) {
+    let ChannelSinks { max_frame_size, inbound, misbehavior, bandwidth } = sinks;
+    loop {
+        let payload = match read_secure(&mut reader, &transport, max_frame_size).await {
+            Ok(payload) => payload,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return,
+            Err(e) => {
+                tracing::warn!("Closing connection with {}: {}", remote, e);
+                return;
+            }
+        };
+        let decoded = ACPMessage::deserialize(&payload);
+        let priority = decoded.as_ref().map_or(MessagePriority::Normal, ACPMessage::traffic_priority);
+        bandwidth.inbound(&peer.node_id, wire_size(payload.len()), priority).await;
+        let message = match decoded {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!("Dropping undecodable message from {}: {}", remote, e);
+                misbehavior.record(&peer.node_id, Violation::MalformedMessage, Instant::now());
+                continue;
+            }
+        };
+        if misbehavior.is_quarantined(&peer.node_id, Instant::now()) {
+            tracing::debug!("Closing connection with quarantined peer {}", peer.node_id);
+            return;
+        }
+        let received = InboundMessage {
+            remote,
+            peer: peer.clone(),
+            message,
+        };
+        if inbound.send(received).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Sending side of an open, authenticated channel
+struct Connection {
+    // Held across encryption and the write, so frames go out in nonce order
+    writer: tokio::sync::Mutex<Box<dyn AsyncWrite + Send + Unpin>>,
+    transport: SharedTransport,
+    peer: PeerIdentity,
+    remote: SocketAddr,
+    opened_at: Instant,
+}
+
+impl Connection {
+    fn new<W>(writer: W, transport: SharedTransport, peer: PeerIdentity, remote: SocketAddr) -> Self
+    where
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Self {
+            writer: tokio::sync::Mutex::new(Box::new(writer)),
+            transport,
+            peer,
+            remote,
+            opened_at: Instant::now(),
+        }
+    }
+
+    async fn send(&self, payload: &[u8]) -> std::io::Result<()> {
+        let mut writer = self.writer.lock().await;
+        write_secure(&mut *writer, &self.transport, payload).await
+    }
+}
+
+/// Open connection with its last use
+struct PooledConnection {
+    connection: Arc<Connection>,
+    last_used: Instant,
+}
+
+/// Reconnect state for an address that failed to connect
+#[derive(Debug, Clone, Copy)]
+struct BackoffState {
+    failures: u32,
+    retry_at: Instant,
+}
+
+/// Connection pool with reconnect backoff
+///
+/// Messages read from any channel, dialed or accepted, are passed to the
+/// `inbound` sender it was created with.
+pub struct ConnectionManager {
+    config: TransportConfig,
+    security: Arc<SecurityManager>,
+    inbound: mpsc::Sender<InboundMessage>,
+    pool: Mutex<HashMap<PeerAddress, PooledConnection>>,
+    channels: Mutex<HashMap<String, PooledConnection>>,  // Accepted channels by peer node id
+    backoff: Mutex<HashMap<PeerAddress, BackoffState>>,
+    pinned: Mutex<HashSet<String>>,
+    misbehavior: Arc<MisbehaviorDetector>,
+    bandwidth: Arc<BandwidthMeter>,
+    events: EventBus,
+    #[cfg(feature = "quic")]
+    endpoint: Mutex<Option<quinn::Endpoint>>,
+}
+
+impl ConnectionManager {
+    pub fn new(config: TransportConfig, security: Arc<SecurityManager>, inbound: mpsc::Sender<InboundMessage>) -> Self {
+        let misbehavior = Arc::new(MisbehaviorDetector::new(config.misbehavior.clone(), security.clone()));
+        let pinned = config.policy.preferred_peers.clone();
+        let bandwidth = Arc::new(BandwidthMeter::new(config.bandwidth.clone()));
+        Self {
+            config,
+            security,
+            inbound,
+            pool: Mutex::new(HashMap::new()),
+            channels: Mutex::new(HashMap::new()),
+            backoff: Mutex::new(HashMap::new()),
+            pinned: Mutex::new(pinned),
+            misbehavior,
+            bandwidth,
+            events: EventBus::default(),
+            #[cfg(feature = "quic")]
+            endpoint: Mutex::new(None),
+        }
+    }
+
+    /// The node's event bus, shared with its other components
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    fn handshake_failed(&self, remote: SocketAddr, reason: impl std::fmt::Display) {
+        tracing::warn!("Handshake with {} failed: {}", remote, reason);
+        self.events.publish(ACPEvent::HandshakeFailed { remote, reason: reason.to_string() });
+    }
+
+    /// Deliver one frame to `addr`, connecting or reconnecting as needed
+    pub async fn send(&self, addr: impl Into<PeerAddress>, payload: &[u8], priority: MessagePriority) -> Result<()> {
+        self.deliver(addr.into(), payload, priority, None).await
+    }
+
+    /// `send` that gives up if `cancel` fires before the frame is written.
+    /// A cancelled send writes nothing and leaves the connection pooled.
+    pub async fn send_cancellable(
+        &self,
+        addr: impl Into<PeerAddress>,
+        payload: &[u8],
+        priority: MessagePriority,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        self.deliver(addr.into(), payload, priority, Some(cancel)).await
+    }
+
+    async fn deliver(&self, addr: PeerAddress, payload: &[u8], priority: MessagePriority, cancel: Option<&CancellationToken>) -> Result<()> {
+        if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(ACPError::Cancelled(format!("send to {}", addr)));
+        }
+        let (connection, pooled) = self.connection(&addr).await?;
+        let Err(error) = self.transmit(&connection, payload, priority, cancel).await? else {
+            return Ok(());
+        };
+        self.evict(addr.clone());
+        if !pooled {
+            return Err(ACPError::Network(format!("Send to {} failed: {}", addr, error)));
+        }
+
+        // The pooled connection may have died while idle; try a fresh one
+        tracing::debug!("Pooled connection to {} failed ({}), reconnecting", addr, error);
+        let (connection, _) = self.connection(&addr).await?;
+        self.transmit(&connection, payload, priority, cancel).await?.map_err(|e| {
+            self.evict(addr.clone());
+            ACPError::Network(format!("Send to {} failed: {}", addr, e))
+        })
+    }
+
+    /// Deliver one frame over the channel `node_id` opened to this node
+    pub async fn send_to_node(&self, node_id: &str, payload: &[u8], priority: MessagePriority) -> Result<()> {
+        self.deliver_to_node(node_id, payload, priority, None).await
+    }
+
+    /// `send_to_node` that gives up if `cancel` fires before the frame is written
+    pub async fn send_to_node_cancellable(
+        &self,
+        node_id: &str,
+        payload: &[u8],
+        priority: MessagePriority,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        self.deliver_to_node(node_id, payload, priority, Some(cancel)).await
+    }
+
+    async fn deliver_to_node(&self, node_id: &str, payload: &[u8], priority: MessagePriority, cancel: Option<&CancellationToken>) -> Result<()> {
+        let channel = self
+            .channels
+            .lock()
+            .get_mut(node_id)
+            .map(|channel| {
+                channel.last_used = Instant::now();
+                channel.connection.clone()
+            })
+            .ok_or_else(|| ACPError::Connection(format!("No open channel from {}", node_id)))?;
+        self.transmit(&channel, payload, priority, cancel).await?.map_err(|e| {
+            self.close_channel(node_id, &channel);
+            ACPError::Network(format!("Send to {} failed: {}", node_id, e))
+        })
+    }
+
+    fn sinks(&self) -> ChannelSinks {
+        ChannelSinks {
+            max_frame_size: self.config.max_frame_size,
+            inbound: self.inbound.clone(),
+            misbehavior: self.misbehavior.clone(),
+            bandwidth: self.bandwidth.clone(),
+        }
+    }
+
+    /// Write `payload` once the bandwidth caps and budget allow it. Fails
+    /// only if cancelled while waiting; the inner result is the write's.
+    async fn transmit(
+        &self,
+        connection: &Connection,
+        payload: &[u8],
+        priority: MessagePriority,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<std::io::Result<()>> {
+        let node_id = &connection.peer.node_id;
+        let bytes = wire_size(payload.len());
+        match cancel {
+            Some(cancel) => {
+                if !self.bandwidth.outbound_cancellable(node_id, bytes, priority, cancel).await {
+                    return Err(ACPError::Cancelled(format!("send to {}", node_id)));
+                }
+            }
+            None => self.bandwidth.outbound(node_id, bytes, priority).await,
+        }
+        Ok(connection.send(payload).await)
+    }
+
+    /// Whether `node_id` has a channel open to this node
+    pub fn has_channel(&self, node_id: &str) -> bool {
+        self.channels.lock().contains_key(node_id)
+    }
+
+    /// Number of pooled connections
+    pub fn pooled(&self) -> usize {
+        self.pool.lock().len()
+    }
+
+    /// Number of channels other nodes opened to this one
+    pub fn accepted(&self) -> usize {
+        self.channels.lock().len()
+    }
+
+    /// Exempt `node_id` from the connection policy's limits and from shedding
+    pub fn pin_peer(&self, node_id: &str) {
+        self.pinned.lock().insert(node_id.to_string());
+    }
+
+    pub fn unpin_peer(&self, node_id: &str) {
+        self.pinned.lock().remove(node_id);
+    }
+
+    pub fn is_pinned(&self, node_id: &str) -> bool {
+        self.pinned.lock().contains(node_id)
+    }
+
+    /// Every open connection, dialed and accepted, most recently used first
+    pub fn connection_states(&self) -> Vec<ConnectionState> {
+        let pinned = self.pinned.lock().clone();
+        let now = Instant::now();
+        let state = |pooled: &PooledConnection, direction| ConnectionState {
+            node_id: pooled.connection.peer.node_id.clone(),
+            remote: pooled.connection.remote,
+            direction,
+            pinned: pinned.contains(&pooled.connection.peer.node_id),
+            connected_for: now.saturating_duration_since(pooled.connection.opened_at),
+            idle_for: now.saturating_duration_since(pooled.last_used),
+        };
+        let mut states: Vec<ConnectionState> = self.pool.lock().values().map(|pooled| state(pooled, Direction::Outbound)).collect();
+        states.extend(self.channels.lock().values().map(|channel| state(channel, Direction::Inbound)));
+        states.sort_by_key(|state| state.idle_for);
+        states
+    }
+
+    /// Authenticated identity behind the pooled connection to `addr`
+    pub fn peer(&self, addr: impl Into<PeerAddress>) -> Option<PeerIdentity> {
+        self.pool.lock().get(&addr.into()).map(|pooled| pooled.connection.peer.clone())
+    }
+
+    pub fn security(&self) -> &SecurityManager {
+        &self.security
+    }
+
+    /// Bytes read and written over these channels, and their caps
+    pub fn bandwidth(&self) -> &BandwidthMeter {
+        &self.bandwidth
+    }
+
+    /// Violation scores and quarantines of the peers behind these channels
+    pub fn misbehavior(&self) -> &Arc<MisbehaviorDetector> {
+        &self.misbehavior
+    }
+
+    /// Time left before `addr` may be dialed again, if it is backed off
+    pub fn backoff_remaining(&self, addr: impl Into<PeerAddress>) -> Option<Duration> {
+        self.backoff
+            .lock()
+            .get(&addr.into())
+            .map(|state| state.retry_at.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Drop the pooled connection to `addr`
+    pub fn evict(&self, addr: impl Into<PeerAddress>) {
+        self.pool.lock().remove(&addr.into());
+    }
+
+    /// Close every pooled connection and accepted channel
+    pub fn clear(&self) {
+        self.pool.lock().clear();
+        self.channels.lock().clear();
+    }
+
+    /// Forget an accepted channel, unless it was already replaced
+    fn close_channel(&self, node_id: &str, channel: &Arc<Connection>) {
+        let mut channels = self.channels.lock();
+        if channels.get(node_id).is_some_and(|current| Arc::ptr_eq(&current.connection, channel)) {
+            channels.remove(node_id);
+        }
+    }
+
+    /// Pooled connection to `addr`, or a new one; the flag says whether it was pooled
+    async fn connection(&self, addr: &PeerAddress) -> Result<(Arc<Connection>, bool)> {
+        if let Some(pooled) = self.pool.lock().get_mut(addr) {
+            pooled.last_used = Instant::now();
+            return Ok((pooled.connection.clone(), true));
+        }
+        if let Some(remaining) = self.backoff_remaining(addr.clone()) {
+            return Err(ACPError::Connection(format!("Backing off from {} for {:?}", addr, remaining)));
+        }
+
+        let connection = match tokio::time::timeout(self.config.connect_timeout, self.connect(addr)).await {
+            Ok(Ok(connection)) => Arc::new(connection),
+            Ok(Err(error)) => return Err(self.connect_failed(addr, error.to_string())),
+            Err(_) => return Err(self.connect_failed(addr, "timed out".to_string())),
+        };
+        self.backoff.lock().remove(addr);
+        if self.misbehavior.is_quarantined(&connection.peer.node_id, Instant::now()) {
+            return Err(ACPError::Security(format!("{} at {} is quarantined", connection.peer.node_id, addr)));
+        }
+
+        {
+            let pinned = self.pinned.lock();
+            let mut pool = self.pool.lock();
+            if pool.len() >= self.config.max_pooled_connections {
+                let idlest = pool
+                    .iter()
+                    .filter(|(_, pooled)| !pinned.contains(&pooled.connection.peer.node_id))
+                    .min_by_key(|(_, pooled)| pooled.last_used)
+                    .map(|(addr, _)| addr.clone());
+                if let Some(idlest) = idlest {
+                    pool.remove(&idlest);
+                }
+            }
+            pool.insert(
+                addr.clone(),
+                PooledConnection {
+                    connection: connection.clone(),
+                    last_used: Instant::now(),
+                },
+            );
+        }
+        self.shed();
+        Ok((connection, false))
+    }
+
+    /// Keep an accepted channel if the policy has room for it. A peer that
+    /// reconnects replaces its earlier channel rather than adding to it.
+    fn accept_channel(&self, channel: Arc<Connection>) -> std::result::Result<(), String> {
+        let policy = &self.config.policy;
+        let node_id = channel.peer.node_id.clone();
+        let pinned = self.is_pinned(&node_id);
+        let mut channels = self.channels.lock();
+        if !pinned {
+            let others: Vec<&PooledConnection> = channels.iter().filter(|(id, _)| **id != node_id).map(|(_, other)| other).collect();
+            let ip = channel.remote.ip();
+            let from_ip = others.iter().filter(|other| other.connection.remote.ip() == ip).count();
+            if from_ip >= policy.max_per_ip {
+                return Err(format!("{} connections from {} already", from_ip, ip));
+            }
+            if others.len() >= policy.max_inbound() {
+                return Err(format!("inbound limit of {} reached", policy.max_inbound()));
+            }
+        }
+        channels.insert(
+            node_id,
+            PooledConnection {
+                connection: channel,
+                last_used: Instant::now(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Drop the least recently used unpinned connections while more than
+    /// `max_peers` are open, accepted ones first while they exceed their share
+    fn shed(&self) {
+        let policy = &self.config.policy;
+        let pinned = self.pinned.lock().clone();
+        let mut pool = self.pool.lock();
+        let mut channels = self.channels.lock();
+        while pool.len() + channels.len() > policy.max_peers {
+            let inbound = channels
+                .iter()
+                .filter(|(node_id, _)| !pinned.contains(*node_id))
+                .min_by_key(|(_, channel)| channel.last_used)
+                .map(|(node_id, channel)| (node_id.clone(), channel.last_used));
+            let outbound = pool
+                .iter()
+                .filter(|(_, pooled)| !pinned.contains(&pooled.connection.peer.node_id))
+                .min_by_key(|(_, pooled)| pooled.last_used)
+                .map(|(addr, pooled)| (addr.clone(), pooled.last_used));
+            let shed_inbound = match (&inbound, &outbound) {
+                (Some(_), _) if channels.len() > policy.max_inbound() => true,
+                (Some((_, inbound_used)), Some((_, outbound_used))) => inbound_used <= outbound_used,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => return,  // Only pinned peers left
+            };
+            match (shed_inbound, inbound, outbound) {
+                (true, Some((node_id, _)), _) => {
+                    tracing::debug!("Shedding channel from {}", node_id);
+                    channels.remove(&node_id);
+                }
+                (_, _, Some((addr, _))) => {
+                    tracing::debug!("Shedding connection to {}", addr);
+                    pool.remove(&addr);
+                }
+                _ => return,
+            }
+        }
+    }
+
+    async fn connect(&self, addr: &PeerAddress) -> std::io::Result<Connection> {
+        match self.config.transport {
+            Transport::Tcp => {
+                let stream = match (&self.config.proxy, addr.socket_addr()) {
+                    (Some(proxy), _) => proxy.connect(addr).await?,
+                    (None, Some(addr)) => TcpStream::connect(addr).await?,
+                    (None, None) => return Err(unsupported(format!("{} is only reachable through a proxy", addr))),
+                };
+                stream.set_nodelay(true)?;
+                let remote = stream.peer_addr()?;
+                let (mut reader, mut writer) = stream.into_split();
+                let (transport, peer) = handshake(&mut reader, &mut writer, &self.security, true)
+                    .await
+                    .inspect_err(|e| self.handshake_failed(remote, e))?;
+                Ok(self.open(tokio::io::BufReader::new(reader), writer, remote, transport, peer))
+            }
+            #[cfg(feature = "quic")]
+            Transport::Quic => {
+                let addr = match (&self.config.proxy, addr.socket_addr()) {
+                    (None, Some(addr)) => addr,
+                    (Some(_), _) => return Err(unsupported("QUIC connections cannot be proxied".to_string())),
+                    (None, None) => return Err(unsupported(format!("{} is only reachable through a proxy", addr))),
+                };
+                let endpoint = self.quic_endpoint()?;
+                // The stream keeps the QUIC connection open
+                let (mut writer, mut reader) = quic::connect(&endpoint, addr).await?;
+                let (transport, peer) = handshake(&mut reader, &mut writer, &self.security, true)
+                    .await
+                    .inspect_err(|e| self.handshake_failed(addr, e))?;
+                Ok(self.open(reader, writer, addr, transport, peer))
+            }
+        }
+    }
+
+    /// Start reading a dialed channel and return its sending side
+    fn open<R, W>(&self, reader: R, writer: W, addr: SocketAddr, transport: snow::TransportState, peer: PeerIdentity) -> Connection
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let transport = Arc::new(Mutex::new(transport));
+        tokio::spawn(read_channel(reader, transport.clone(), addr, peer.clone(), self.sinks()));
+        Connection::new(writer, transport, peer, addr)
+    }
+
+    /// Record a failed connect and start or extend the address's backoff
+    fn connect_failed(&self, addr: &PeerAddress, reason: String) -> ACPError {
+        let mut backoff = self.backoff.lock();
+        let state = backoff.entry(addr.clone()).or_insert(BackoffState {
+            failures: 0,
+            retry_at: Instant::now(),
+        });
+        state.failures += 1;
+        let delay = self.config.backoff.delay(state.failures);
+        state.retry_at = Instant::now() + delay;
+        tracing::debug!("Connect to {} failed ({}), retrying in {:?}", addr, reason, delay);
+        ACPError::Connection(format!("Failed to connect to {}: {}", addr, reason))
+    }
+
+    /// The listening endpoint once started, otherwise a client-only one
+    #[cfg(feature = "quic")]
+    fn quic_endpoint(&self) -> std::io::Result<quinn::Endpoint> {
+        let mut endpoint = self.endpoint.lock();
+        if let Some(endpoint) = endpoint.as_ref() {
+            return Ok(endpoint.clone());
+        }
+        let client = quic::client_endpoint()?;
+        *endpoint = Some(client.clone());
+        Ok(client)
+    }
+}
+
+/// P2P network node: listens for messages and delivers outgoing ones
+pub struct P2PNetwork {
+    node_id: String,
+    node_type: NodeType,
+    listen_address: String,
+    config: TransportConfig,
+    connections: Arc<ConnectionManager>,
+    peers: Mutex<HashMap<String, PeerAddress>>,  // Peer id -> dialable address
+    relayed: Mutex<HashMap<String, String>>,    // Peer id -> relay that reaches it
+    relay: Option<Arc<RelayService>>,
+    inbound_rx: Mutex<Option<mpsc::Receiver<InboundMessage>>>,
+    router: JoinHandle<()>,
+    local_addr: Mutex<Option<SocketAddr>>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    started_at: Instant,
+}
+
+impl P2PNetwork {
+    /// Create a network over TCP
+    pub async fn new(config: &ACPConfig) -> Result<Self> {
+        Self::with_transport(config, TransportConfig::default()).await
+    }
+
+    /// Create a network with a custom transport configuration and fresh node keys
+    pub async fn with_transport(config: &ACPConfig, transport: TransportConfig) -> Result<Self> {
+        let security = Arc::new(SecurityManager::for_node(config.node_id.clone()));
+        Self::with_security(config, transport, security).await
+    }
+
+    /// Create a network whose channels authenticate with `security`'s keys
+    pub async fn with_security(config: &ACPConfig, transport: TransportConfig, security: Arc<SecurityManager>) -> Result<Self> {
+        let buffer = transport.inbound_buffer.max(1);
+        let (received_tx, received_rx) = mpsc::channel(buffer);
+        let (inbound_tx, inbound_rx) = mpsc::channel(buffer);
+        let connections = Arc::new(ConnectionManager::new(transport.clone(), security, received_tx));
+        let relay = (config.node_type == NodeType::Relay).then(|| Arc::new(RelayService::new(transport.relay.clone())));
+        let router = tokio::spawn(route_inbound(received_rx, inbound_tx, connections.clone(), relay.clone()));
+
+        let network = Self {
+            node_id: config.node_id.clone(),
+            node_type: config.node_type.clone(),
+            listen_address: config.listen_address.clone(),
+            connections,
+            config: transport,
+            peers: Mutex::new(HashMap::new()),
+            relayed: Mutex::new(HashMap::new()),
+            relay,
+            inbound_rx: Mutex::new(Some(inbound_rx)),
+            router,
+            local_addr: Mutex::new(None),
+            tasks: Mutex::new(Vec::new()),
+            started_at: Instant::now(),
+        };
+
+        // Bootstrap peers are known by address until they introduce themselves
+        for peer in &config.bootstrap_peers {
+            match peer.parse::<PeerAddress>() {
+                Ok(addr) => network.add_peer(peer.clone(), addr),
+                Err(_) => tracing::warn!("Ignoring bootstrap peer with invalid address: {}", peer),
+            }
+        }
+        Ok(network)
+    }
+
+    /// Start listening for incoming messages
+    pub async fn start(&self) -> Result<()> {
+        let addr = match self.config.transport {
+            Transport::Tcp => self.listen_tcp().await?,
+            #[cfg(feature = "quic")]
+            Transport::Quic => self.listen_quic()?,
+        };
+        *self.local_addr.lock() = Some(addr);
+        tracing::info!("Node {} listening on {} ({:?})", self.node_id, addr, self.config.transport);
+
+        if let Some(stun_address) = self.relay.as_ref().and_then(|relay| relay.config().stun_address.clone()) {
+            let socket = tokio::net::UdpSocket::bind(&stun_address)
+                .await
+                .map_err(|e| ACPError::Network(format!("Failed to bind STUN responder on {}: {}", stun_address, e)))?;
+            self.tasks.lock().push(tokio::spawn(serve_stun(socket)));
+        }
+        Ok(())
+    }
+
+    /// Stop listening and close every connection
+    pub async fn stop(&self) -> Result<()> {
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+        self.connections.clear();
+        #[cfg(feature = "quic")]
+        if let Some(endpoint) = self.connections.endpoint.lock().take() {
+            endpoint.close(0u32.into(), b"shutdown");
+        }
+        *self.local_addr.lock() = None;
+        Ok(())
+    }
+
+    /// Send a message to a peer, known by id or given as a socket address.
+    /// Peers reached through a relay get it wrapped in a `RelayData` envelope.
+    pub async fn send_message(&self, peer_id: &str, message: &ACPMessage) -> Result<()> {
+        self.send_routed(peer_id, message, None).await
+    }
+
+    /// `send_message` that gives up if `cancel` fires before the message is
+    /// written, as for `ConnectionManager::send_cancellable`
+    pub async fn send_message_cancellable(&self, peer_id: &str, message: &ACPMessage, cancel: &CancellationToken) -> Result<()> {
+        self.send_routed(peer_id, message, Some(cancel)).await
+    }
+
+    async fn send_routed(&self, peer_id: &str, message: &ACPMessage, cancel: Option<&CancellationToken>) -> Result<()> {
+        let relay = self.relayed.lock().get(peer_id).cloned();
+        match relay {
+            Some(relay) => {
+                let from = self.connections.security.node_id().to_string();
+                let envelope = ACPMessage::relay_data(from, peer_id.to_string(), message)?;
+                self.send_direct(&relay, &envelope, cancel).await
+            }
+            None => self.send_direct(peer_id, message, cancel).await,
+        }
+    }
+
+    /// Ask `relay` to forward traffic for this node. The connection to the
+    /// relay stays pooled, and relayed messages arrive over it.
+    pub async fn reserve_relay(&self, relay: &str) -> Result<()> {
+        let request = ACPMessage::relay_request(self.connections.security.node_id().to_string(), relay.to_string());
+        self.send_direct(relay, &request, None).await
+    }
+
+    /// Reach `peer_id`, whose node id it must be, through `relay`
+    pub fn add_relayed_peer(&self, peer_id: String, relay: String) {
+        self.relayed.lock().insert(peer_id, relay);
+    }
+
+    /// Take the stream of received messages; only the first caller gets it
+    pub fn incoming(&self) -> Option<mpsc::Receiver<InboundMessage>> {
+        self.inbound_rx.lock().take()
+    }
+
+    /// Learn or update a peer's dialable address
+    pub fn add_peer(&self, peer_id: String, addr: impl Into<PeerAddress>) {
+        self.peers.lock().insert(peer_id, addr.into());
+    }
+
+    /// Forget a peer and close its connection
+    pub fn remove_peer(&self, peer_id: &str) {
+        self.relayed.lock().remove(peer_id);
+        self.connections.bandwidth.forget(peer_id);
+        if let Some(addr) = self.peers.lock().remove(peer_id) {
+            self.connections.evict(addr);
+        }
+    }
+
+    /// Address bound by `start`
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.lock()
+    }
+
+    pub fn node_type(&self) -> &NodeType {
+        &self.node_type
+    }
+
+    /// Reservations this node holds as a relay
+    pub fn relay(&self) -> Option<&RelayService> {
+        self.relay.as_deref()
+    }
+
+    /// Violation scores and quarantines of connected peers
+    pub fn misbehavior(&self) -> &Arc<MisbehaviorDetector> {
+        self.connections.misbehavior()
+    }
+
+    pub fn connections(&self) -> &ConnectionManager {
+        &self.connections
+    }
+
+    /// Known peers and their dialable addresses
+    pub fn peers(&self) -> Vec<(String, PeerAddress)> {
+        self.peers.lock().iter().map(|(id, addr)| (id.clone(), addr.clone())).collect()
+    }
+
+    /// The node's event bus
+    pub fn events(&self) -> &EventBus {
+        self.connections.events()
+    }
+
+    /// Number of known peers
+    pub fn peer_count(&self) -> usize {
+        self.peers.lock().len()
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Send without relaying: to a known address, over a channel the peer
+    /// opened to us, or to `peer_id` parsed as an address
+    async fn send_direct(&self, peer_id: &str, message: &ACPMessage, cancel: Option<&CancellationToken>) -> Result<()> {
+        let payload = message.serialize()?;
+        if payload.len() > self.config.max_frame_size {
+            return Err(ACPError::Message(format!(
+                "Message of {} bytes exceeds limit of {}",
+                payload.len(),
+                self.config.max_frame_size
+            )));
+        }
+
+        let priority = message.traffic_priority();
+        let known = self.peers.lock().get(peer_id).cloned();
+        if let Some(addr) = known {
+            return self.connections.deliver(addr, &payload, priority, cancel).await;
+        }
+        if self.connections.has_channel(peer_id) {
+            return self.connections.deliver_to_node(peer_id, &payload, priority, cancel).await;
+        }
+        let addr: PeerAddress = peer_id
+            .parse()
+            .map_err(|_| ACPError::Network(format!("No address known for peer {}", peer_id)))?;
+        self.connections.deliver(addr, &payload, priority, cancel).await
+    }
+
+    async fn listen_tcp(&self) -> Result<SocketAddr> {
+        let listener = TcpListener::bind(&self.listen_address)
+            .await
+            .map_err(|e| ACPError::Network(format!("Failed to bind {}: {}", self.listen_address, e)))?;
+        let addr = listener.local_addr().map_err(|e| ACPError::Network(e.to_string()))?;
+
+        let connections = self.connections.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, remote) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::warn!("Failed to accept connection: {}", e);
+                        continue;
+                    }
+                };
+                let (reader, writer) = stream.into_split();
+                tokio::spawn(serve_inbound(tokio::io::BufReader::new(reader), writer, remote, connections.clone()));
+            }
+        });
+        self.tasks.lock().push(task);
+        Ok(addr)
+    }
+
+    #[cfg(feature = "quic")]
+    fn listen_quic(&self) -> Result<SocketAddr> {
+        let addr: SocketAddr = self
+            .listen_address
+            .parse()
+            .map_err(|_| ACPError::Network(format!("Invalid listen address: {}", self.listen_address)))?;
+        let endpoint = quic::server_endpoint(addr).map_err(|e| ACPError::Network(format!("Failed to bind {}: {}", addr, e)))?;
+        let local = endpoint.local_addr().map_err(|e| ACPError::Network(e.to_string()))?;
+
+        // The listening endpoint also dials out, so peers see one address
+        *self.connections.endpoint.lock() = Some(endpoint.clone());
+        let task = tokio::spawn(quic::accept(endpoint, self.connections.clone()));
+        self.tasks.lock().push(task);
+        Ok(local)
+    }
+}
+
+impl Drop for P2PNetwork {
+    fn drop(&mut self) {
+        self.router.abort();
+        for task in self.tasks.get_mut().drain(..) {
+            task.abort();
+        }
+    }
+}
+
+/// Authenticate an accepted connection, offer it as a channel back to the
+/// peer, and read messages until it closes
+async fn serve_inbound<R, W>(mut reader: R, mut writer: W, remote: SocketAddr, connections: Arc<ConnectionManager>)
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Send + Unpin + 'static,
+{
+    let established = tokio::time::timeout(
+        connections.config.connect_timeout,
+        handshake(&mut reader, &mut writer, &connections.security, false),
+    );
+    let (transport, peer) = match established.await {
+        Ok(Ok(established)) => established,
+        Ok(Err(e)) => {
+            connections.handshake_failed(remote, e);
+            return;
+        }
+        Err(_) => {
+            connections.handshake_failed(remote, "timed out");
+            return;
+        }
+    };
+    tracing::debug!("Authenticated {} as {}", remote, peer.node_id);
+    if connections.misbehavior.is_quarantined(&peer.node_id, Instant::now()) {
+        tracing::debug!("Refusing quarantined peer {} at {}", peer.node_id, remote);
+        return;
+    }
+
+    let transport = Arc::new(Mutex::new(transport));
+    let channel = Arc::new(Connection::new(writer, transport.clone(), peer.clone(), remote));
+    if let Err(reason) = connections.accept_channel(channel.clone()) {
+        tracing::debug!("Refusing {} at {}: {}", peer.node_id, remote, reason);
+        return;
+    }
+    connections.shed();
+
+    let node_id = peer.node_id.clone();
+    read_channel(reader, transport, remote, peer, connections.sinks()).await;
+    connections.close_channel(&node_id, &channel);
+}
+
+/// Handle relay traffic and pass everything else on to `incoming()`
+async fn route_inbound(
+    mut received: mpsc::Receiver<InboundMessage>,
+    delivered: mpsc::Sender<InboundMessage>,
+    connections: Arc<ConnectionManager>,
+    relay: Option<Arc<RelayService>>,
+) {
+    while let Some(inbound) = received.recv().await {
+        if !signed_by_channel_peer(&inbound, &connections) {
+            continue;
+        }
+        let inbound = match inbound.message.message_type {
+            MessageType::RelayRequest => {
+                reserve(&inbound.peer, relay.as_deref());
+                continue;
+            }
+            MessageType::RelayData if inbound.message.to.as_deref() != Some(connections.security.node_id()) => {
+                forward(inbound, &connections, relay.as_deref()).await;
+                continue;
+            }
+            MessageType::RelayData => match inbound.message.relayed_message() {
+                Ok(message) => InboundMessage { message, ..inbound },
+                Err(e) => {
+                    tracing::warn!("Dropping malformed relay envelope from {}: {}", inbound.peer.node_id, e);
+                    connections.misbehavior.record(&inbound.peer.node_id, Violation::MalformedMessage, Instant::now());
+                    continue;
+                }
+            },
+            _ => inbound,
+        };
+        if delivered.send(inbound).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Check the signature and freshness of a message the channel's peer signed
+/// as itself, charging the peer if it does not verify
+fn signed_by_channel_peer(inbound: &InboundMessage, connections: &ConnectionManager) -> bool {
+    if inbound.message.signature.is_none() || inbound.message.from != inbound.peer.node_id {
+        return true;
+    }
+    match connections.security.verify_message(&inbound.message, &inbound.peer.verifying_key) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("Dropping message from {}: {}", inbound.peer.node_id, e);
+            // Stale or replayed, but genuinely signed, messages may be clock skew
+            let violation = match connections.security.verify_signature(&inbound.message, &inbound.peer.verifying_key) {
+                Ok(()) => Violation::ReplayedMessage,
+                Err(_) => Violation::InvalidSignature,
+            };
+            connections.misbehavior.record(&inbound.peer.node_id, violation, Instant::now());
+            false
+        }
+    }
+}
+
+/// Reserve a relay slot for the authenticated peer that asked for one
+fn reserve(peer: &PeerIdentity, relay: Option<&RelayService>) {
+    match relay {
+        Some(relay) if relay.reserve(&peer.node_id) => tracing::debug!("Relaying for {}", peer.node_id),
+        Some(_) => tracing::warn!("Relay full, refusing reservation for {}", peer.node_id),
+        None => tracing::debug!("Ignoring relay request from {}: not a relay", peer.node_id),
+    }
+}
+
+/// Forward an envelope to the reserved node it is addressed to
+async fn forward(inbound: InboundMessage, connections: &ConnectionManager, relay: Option<&RelayService>) {
+    let Some(relay) = relay else {
+        tracing::debug!("Ignoring relay envelope from {}: not a relay", inbound.peer.node_id);
+        return;
+    };
+    let envelope = inbound.message;
+    let target = envelope.to.clone().unwrap_or_default();
+    // Envelopes may only name their authenticated sender as origin
+    if envelope.from != inbound.peer.node_id || !relay.is_reserved(&target) {
+        relay.record(false);
+        return;
+    }
+
+    let forwarded = match envelope.serialize() {
+        Ok(payload) => connections.send_to_node(&target, &payload, envelope.traffic_priority()).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = &forwarded {
+        tracing::debug!("Releasing relay slot for {}: {}", target, e);
+        relay.release(&target);
+    }
+    relay.record(forwarded.is_ok());
+}
+
+#[cfg(feature = "quic")]
+mod quic {
+    //! QUIC transport over quinn; one bidirectional stream per connection
+
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+    use rustls::{DigitallySignedStruct, SignatureScheme};
+    use super::{serve_inbound, ConnectionManager};
+
+    const SERVER_NAME: &str = "solace-acp";
+    const ALPN: &[u8] = b"acp/1";
+
+    fn io_error<E: std::fmt::Display>(error: E) -> std::io::Error {
+        std::io::Error::other(error.to_string())
+    }
+
+    /// Accepts any certificate; messages are authenticated by their signatures
+    #[derive(Debug)]
+    struct AnyCertificate(Arc<rustls::crypto::CryptoProvider>);
+
+    impl ServerCertVerifier for AnyCertificate {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+
+    fn client_config() -> std::io::Result<quinn::ClientConfig> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let mut crypto = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(io_error)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AnyCertificate(provider)))
+            .with_no_client_auth();
+        crypto.alpn_protocols = vec![ALPN.to_vec()];
+        let crypto = quinn::crypto::rustls::QuicClientConfig::try_from(crypto).map_err(io_error)?;
+        Ok(quinn::ClientConfig::new(Arc::new(crypto)))
+    }
+
+    fn server_config() -> std::io::Result<quinn::ServerConfig> {
+        let certified = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()]).map_err(io_error)?;
+        let cert = CertificateDer::from(certified.cert);
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(certified.key_pair.serialize_der()));
+        let mut crypto = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(io_error)?
+            .with_no_client_auth()
+            .with_single_cert(vec![cert], key)
+            .map_err(io_error)?;
+        crypto.alpn_protocols = vec![ALPN.to_vec()];
+        let crypto = quinn::crypto::rustls::QuicServerConfig::try_from(crypto).map_err(io_error)?;
+        Ok(quinn::ServerConfig::with_crypto(Arc::new(crypto)))
+    }
+
+    pub(super) fn server_endpoint(addr: SocketAddr) -> std::io::Result<quinn::Endpoint> {
+        let mut endpoint = quinn::Endpoint::server(server_config()?, addr)?;
+        endpoint.set_default_client_config(client_config()?);
+        Ok(endpoint)
+    }
+
+    pub(super) fn client_endpoint() -> std::io::Result<quinn::Endpoint> {
+        let mut endpoint = quinn::Endpoint::client(SocketAddr::from(([0, 0, 0, 0], 0)))?;
+        endpoint.set_default_client_config(client_config()?);
+        Ok(endpoint)
+    }
+
+    /// Connect and open the connection's message stream
+    pub(super) async fn connect(endpoint: &quinn::Endpoint, addr: SocketAddr) -> std::io::Result<(quinn::SendStream, quinn::RecvStream)> {
+        let connection = endpoint.connect(addr, SERVER_NAME).map_err(io_error)?.await.map_err(io_error)?;
+        connection.open_bi().await.map_err(io_error)
+    }
+
+    /// Accept connections and serve each one's message stream
+    pub(super) async fn accept(endpoint: quinn::Endpoint, connections: Arc<ConnectionManager>) {
+        while let Some(incoming) = endpoint.accept().await {
+            let connections = connections.clone();
+            tokio::spawn(async move {
+                let connection = match incoming.await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        tracing::warn!("QUIC handshake failed: {}", e);
+                        return;
+                    }
+                };
+                let remote = connection.remote_address();
+                match connection.accept_bi().await {
+                    Ok((writer, reader)) => serve_inbound(reader, writer, remote, connections).await,
+                    Err(e) => tracing::warn!("QUIC connection from {} opened no stream: {}", remote, e),
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging::MessageType;
+
+    fn local_config() -> ACPConfig {
+        ACPConfig {
+            listen_address: "127.0.0.1:0".to_string(),
+            ..ACPConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_messages_reach_remote_listener_over_pooled_connection() {
+        let receiver = P2PNetwork::new(&local_config()).await.unwrap();
+        receiver.start().await.unwrap();
+        let mut incoming = receiver.incoming().unwrap();
+        assert!(receiver.incoming().is_none());
+
+        let sender = P2PNetwork::new(&local_config()).await.unwrap();
+        sender.add_peer("receiver".to_string(), receiver.local_addr().unwrap());
+        let mut bytes = 0;
+        for i in 0..3u8 {
+            let message = ACPMessage::new(MessageType::Heartbeat, "sender".to_string(), Some("receiver".to_string()), vec![i; 4]);
+            sender.send_message("receiver", &message).await.unwrap();
+            bytes += wire_size(message.serialize().unwrap().len()) as u64;
+        }
+
+        for i in 0..3u8 {
+            let received = tokio::time::timeout(Duration::from_secs(5), incoming.recv()).await.unwrap().unwrap();
+            assert_eq!(received.message.from, "sender");
+            assert_eq!(received.message.payload, vec![i; 4]);
+            assert_eq!(received.peer.verifying_key, sender.connections().security().verifying_key());
+        }
+        assert_eq!(sender.connections().pooled(), 1);
+        assert_eq!(sender.connections().bandwidth().stats().bytes_written, bytes);
+        assert_eq!(receiver.connections().bandwidth().stats().peers[0].bytes_read, bytes);
+
+        receiver.stop().await.unwrap();
+        sender.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dials_tunnel_through_socks5_proxy() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // A SOCKS5 proxy that relays each CONNECT to its IPv4 target
+        let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_addr = proxy.local_addr().unwrap();
+        let tunnels = Arc::new(AtomicUsize::new(0));
+        let opened = tunnels.clone();
+        tokio::spawn(async move {
+            while let Ok((mut client, _)) = proxy.accept().await {
+                let mut request = [0u8; 13];
+                client.read_exact(&mut request[..3]).await.unwrap();
+                client.write_all(&[5, 0]).await.unwrap();
+                client.read_exact(&mut request[3..]).await.unwrap();
+                let target = SocketAddr::from(([request[7], request[8], request[9], request[10]], u16::from_be_bytes([request[11], request[12]])));
+                let mut upstream = TcpStream::connect(target).await.unwrap();
+                client.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0]).await.unwrap();
+                opened.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move { tokio::io::copy_bidirectional(&mut client, &mut upstream).await });
+            }
+        });
+
+        let receiver = P2PNetwork::new(&local_config()).await.unwrap();
+        receiver.start().await.unwrap();
+        let mut incoming = receiver.incoming().unwrap();
+        let transport = TransportConfig { proxy: Some(ProxyConfig::socks5(proxy_addr.to_string())), ..TransportConfig::default() };
+        let sender = P2PNetwork::with_transport(&local_config(), transport).await.unwrap();
+        sender.add_peer("receiver".to_string(), receiver.local_addr().unwrap());
+        for _ in 0..2 {
+            sender.send_message("receiver", &ACPMessage::heartbeat("sender".to_string())).await.unwrap();
+            tokio::time::timeout(Duration::from_secs(5), incoming.recv()).await.unwrap().unwrap();
+        }
+
+        // One tunnel, pooled, and the connection's remote end is the proxy
+        assert_eq!(tunnels.load(Ordering::SeqCst), 1);
+        assert_eq!(sender.connections().connection_states()[0].remote, proxy_addr);
+
+        receiver.stop().await.unwrap();
+        sender.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_handshake_rejects_peer_without_pinned_key() {
+        let receiver = P2PNetwork::new(&local_config()).await.unwrap();
+        receiver.start().await.unwrap();
+        let mut incoming = receiver.incoming().unwrap();
+        let addr = receiver.local_addr().unwrap();
+
+        let strict = Arc::new(SecurityManager::for_node("sender").require_pinned_peers());
+        let sender = P2PNetwork::with_security(&local_config(), TransportConfig::default(), strict).await.unwrap();
+        let mut events = sender.events().subscribe();
+        let message = ACPMessage::heartbeat("sender".to_string());
+        let refused = sender.send_message(&addr.to_string(), &message).await;
+        assert!(matches!(refused, Err(ACPError::Connection(reason)) if reason.contains("Unknown peer")));
+        assert!(matches!(events.try_recv(), Ok(ACPEvent::HandshakeFailed { remote, .. }) if remote == addr));
+
+        let receiver_security = receiver.connections().security();
+        sender.connections().security().pin_peer(receiver_security.node_id(), receiver_security.verifying_key());
+        tokio::time::sleep(ReconnectBackoff::default().initial).await;
+        sender.send_message(&addr.to_string(), &message).await.unwrap();
+        assert_eq!(sender.connections().peer(addr).unwrap().verifying_key, receiver_security.verifying_key());
+
+        let received = tokio::time::timeout(Duration::from_secs(5), incoming.recv()).await.unwrap().unwrap();
+        assert_eq!(received.peer.node_id, "sender");
+        assert_eq!(received.message.id, message.id);
+
+        receiver.stop().await.unwrap();
+        sender.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_relay_forwards_to_node_behind_nat() {
+        let relay = P2PNetwork::new(&ACPConfig { node_type: NodeType::Relay, ..local_config() }).await.unwrap();
+        relay.start().await.unwrap();
+        let relay_addr = relay.local_addr().unwrap().to_string();
+
+        // Never listens, so it can only be reached over its own connection to the relay
+        let hidden = P2PNetwork::new(&ACPConfig { node_id: "hidden".to_string(), ..local_config() }).await.unwrap();
+        let mut incoming = hidden.incoming().unwrap();
+        hidden.reserve_relay(&relay_addr).await.unwrap();
+        let reservations = relay.relay().unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !reservations.is_reserved("hidden") {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let sender = P2PNetwork::new(&ACPConfig { node_id: "sender".to_string(), ..local_config() }).await.unwrap();
+        sender.add_relayed_peer("hidden".to_string(), relay_addr);
+        let message = ACPMessage::new(MessageType::Heartbeat, "sender".to_string(), Some("hidden".to_string()), vec![7]);
+        sender.send_message("hidden", &message).await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), incoming.recv()).await.unwrap().unwrap();
+        assert_eq!(received.message.id, message.id);
+        assert_eq!(received.peer.verifying_key, relay.connections().security().verifying_key());
+        assert_eq!(reservations.stats().forwarded, 1);
+    }
+
+    /// Wait until `connections` holds `accepted` channels
+    async fn settle(connections: &ConnectionManager, accepted: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while connections.accepted() != accepted {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_policy_limits_pins_and_sheds_connections() {
+        let policy = ConnectionPolicy { max_peers: 2, max_per_ip: 1, ..ConnectionPolicy::default() };
+        let transport = TransportConfig { policy, ..TransportConfig::default() };
+        let named = |node_id: &str| ACPConfig { node_id: node_id.to_string(), ..local_config() };
+        let a = P2PNetwork::new(&named("a")).await.unwrap();
+        let b = P2PNetwork::new(&named("b")).await.unwrap();
+        let c = P2PNetwork::new(&named("c")).await.unwrap();
+        let node = P2PNetwork::with_transport(&named("node"), transport).await.unwrap();
+        node.start().await.unwrap();
+        let addr = node.local_addr().unwrap().to_string();
+
+        a.send_message(&addr, &ACPMessage::heartbeat("a".to_string())).await.unwrap();
+        settle(node.connections(), 1).await;
+
+        // A second peer on the same IP is refused until it is pinned
+        b.send_message(&addr, &ACPMessage::heartbeat("b".to_string())).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(node.connections().accepted(), 1);
+        node.connections().pin_peer("b");
+        b.connections().evict(node.local_addr().unwrap());
+        b.send_message(&addr, &ACPMessage::heartbeat("b".to_string())).await.unwrap();
+        settle(node.connections(), 2).await;
+
+        // Dialing out goes over `max_peers`; the idlest unpinned channel is shed
+        c.start().await.unwrap();
+        node.send_message(&c.local_addr().unwrap().to_string(), &ACPMessage::heartbeat("node".to_string())).await.unwrap();
+        let states: Vec<(String, Direction, bool)> = node
+            .connections()
+            .connection_states()
+            .into_iter()
+            .map(|state| (state.node_id, state.direction, state.pinned))
+            .collect();
+        assert_eq!(
+            states,
+            vec![("c".to_string(), Direction::Outbound, false), ("b".to_string(), Direction::Inbound, true)],
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_peer_is_backed_off() {
+        // Bind then drop a listener to find a port nobody is listening on
+        let addr = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let network = P2PNetwork::new(&local_config()).await.unwrap();
+        let message = ACPMessage::heartbeat("sender".to_string());
+
+        let first = network.send_message(&addr.to_string(), &message).await;
+        assert!(matches!(first, Err(ACPError::Connection(reason)) if reason.starts_with("Failed")));
+        let remaining = network.connections().backoff_remaining(addr).unwrap();
+        assert!(remaining <= ReconnectBackoff::default().initial);
+
+        // Fails fast while backed off, without dialing
+        let second = network.send_message(&addr.to_string(), &message).await;
+        assert!(matches!(second, Err(ACPError::Connection(reason)) if reason.starts_with("Backing off")));
+
+        let backoff = ReconnectBackoff::default();
+        assert_eq!(backoff.delay(3), Duration::from_secs(1));
+        assert_eq!(backoff.delay(20), backoff.max);
+    }
+}
@@ -0,0 +1,317 @@
+//! Peer Store
+//!
+//! Keeps what a node learned about its peers across restarts. Each known
+//! peer has a `PeerRecord`: its last `PeerInfo` plus dial history, and a
+//! score combining uptime (the share of dials that reached it), latency (a
+//! moving average of dial round trips) and conduct (its misbehavior score).
+//! `PeerDiscovery` keeps records current, and on startup dials the
+//! best-scoring stored peers before falling back to bootstrap sources.
+//!
+//! `PeerStore` persists records under `peer:` keys, and the `PeerScorer`'s
+//! scores and bans under a state key of their own, through a `PeerBackend`.
+//! `FilePeerBackend` keeps records in a directory; a node with a key-value
+//! store of its own can implement `PeerBackend` over it instead.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::discovery::PeerInfo;
+use crate::security::PeerScorerState;
+
+const PEER_PREFIX: &str = "peer:";
+const SCORER_KEY: &str = "state:peer_scorer";
+
+/// Weight of each new latency sample in the moving average
+const LATENCY_SMOOTHING: f64 = 0.3;
+/// Component score for a peer never measured
+const UNMEASURED: f64 = 0.5;
+
+/// How uptime, latency and conduct combine into a peer's score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerScoreWeights {
+    pub uptime: f64,
+    pub latency: f64,
+    pub conduct: f64,
+    pub latency_ceiling: Duration,        // Round trip that earns no latency credit
+}
+
+impl Default for PeerScoreWeights {
+    fn default() -> Self {
+        Self {
+            uptime: 0.4,
+            latency: 0.3,
+            conduct: 0.3,
+            latency_ceiling: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A known peer and how it has behaved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerRecord {
+    pub info: PeerInfo,
+    pub first_seen: DateTime<Utc>,
+    pub dials: u64,
+    pub failed_dials: u64,
+    pub latency_ms: Option<f64>,          // Moving average of successful dial round trips
+    pub misbehavior: f64,                 // Latest misbehavior score reported for the peer
+    pub score: f64,                       // In [0, 1], as of the last `rescore`
+}
+
+impl PeerRecord {
+    pub fn new(info: PeerInfo) -> Self {
+        Self {
+            first_seen: info.last_seen,
+            info,
+            dials: 0,
+            failed_dials: 0,
+            latency_ms: None,
+            misbehavior: 0.0,
+            score: 0.0,
+        }
+    }
+
+    /// Share of dials that reached the peer
+    pub fn uptime(&self) -> Option<f64> {
+        (self.dials > 0).then(|| (self.dials - self.failed_dials) as f64 / self.dials as f64)
+    }
+
+    /// Record a dial: its round trip if it connected, `None` if it failed
+    pub fn record_dial(&mut self, round_trip: Option<Duration>) {
+        self.dials += 1;
+        match round_trip {
+            Some(rtt) => {
+                let sample = rtt.as_secs_f64() * 1000.0;
+                self.latency_ms = Some(match self.latency_ms {
+                    Some(average) => average + LATENCY_SMOOTHING * (sample - average),
+                    None => sample,
+                });
+                self.info.last_seen = Utc::now();
+            }
+            None => self.failed_dials += 1,
+        }
+    }
+
+    /// Recompute `score` under `weights`
+    pub fn rescore(&mut self, weights: &PeerScoreWeights) -> f64 {
+        let ceiling_ms = weights.latency_ceiling.as_secs_f64() * 1000.0;
+        let uptime = self.uptime().unwrap_or(UNMEASURED);
+        let latency = self.latency_ms.map(|ms| 1.0 - (ms / ceiling_ms).min(1.0)).unwrap_or(UNMEASURED);
+        let conduct = 1.0 / (1.0 + self.misbehavior.max(0.0));
+        let total = weights.uptime + weights.latency + weights.conduct;
+        self.score = if total > 0.0 {
+            (weights.uptime * uptime + weights.latency * latency + weights.conduct * conduct) / total
+        } else {
+            0.0
+        };
+        self.score
+    }
+}
+
+/// Key-value storage a `PeerStore` persists through
+pub trait PeerBackend: Send + Sync {
+    fn put<'a>(&'a self, key: &'a str, value: Vec<u8>) -> BoxFuture<'a, Result<()>>;
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>>>;
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>>;
+    /// Keys starting with `prefix`
+    fn list_keys<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, Result<Vec<String>>>;
+}
+
+/// Backend keeping one file per key in a directory
+pub struct FilePeerBackend {
+    dir: PathBuf,
+}
+
+impl FilePeerBackend {
+    /// Open `dir`, creating it if needed
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Keys are hex-encoded into file names, so any peer id is a valid one
+    fn path(&self, key: &str) -> PathBuf {
+        let name: String = key.bytes().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(name)
+    }
+
+    fn key(name: &str) -> Option<String> {
+        let bytes = (0..name.len())
+            .step_by(2)
+            .map(|i| name.get(i..i + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+            .collect::<Option<Vec<u8>>>()?;
+        String::from_utf8(bytes).ok()
+    }
+}
+
+impl PeerBackend for FilePeerBackend {
+    fn put<'a>(&'a self, key: &'a str, value: Vec<u8>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            // Write then rename, so a crash never leaves a torn record
+            let path = self.path(key);
+            let staged = path.with_extension("tmp");
+            tokio::fs::write(&staged, value).await?;
+            tokio::fs::rename(&staged, &path).await?;
+            Ok(())
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            match tokio::fs::read(self.path(key)).await {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            match tokio::fs::remove_file(self.path(key)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    fn list_keys<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        Box::pin(async move {
+            let mut keys = Vec::new();
+            let mut entries = tokio::fs::read_dir(&self.dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name();
+                if let Some(key) = name.to_str().and_then(Self::key) {
+                    if key.starts_with(prefix) {
+                        keys.push(key);
+                    }
+                }
+            }
+            Ok(keys)
+        })
+    }
+}
+
+/// Peer records persisted through a `PeerBackend`
+pub struct PeerStore {
+    backend: Arc<dyn PeerBackend>,
+}
+
+impl PeerStore {
+    pub fn new(backend: Arc<dyn PeerBackend>) -> Self {
+        Self { backend }
+    }
+
+    pub async fn save(&self, record: &PeerRecord) -> Result<()> {
+        self.backend.put(&peer_key(&record.info.id), serde_json::to_vec(record)?).await
+    }
+
+    /// Save a snapshot such as `PeerDiscovery::peer_records`
+    pub async fn save_all(&self, records: &[PeerRecord]) -> Result<()> {
+        for record in records {
+            self.save(record).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn remove(&self, peer_id: &str) -> Result<()> {
+        self.backend.delete(&peer_key(peer_id)).await
+    }
+
+    /// Every stored record, highest score first
+    pub async fn load(&self) -> Result<Vec<PeerRecord>> {
+        let mut records = Vec::new();
+        for key in self.backend.list_keys(PEER_PREFIX).await? {
+            if let Some(bytes) = self.backend.get(&key).await? {
+                records.push(serde_json::from_slice::<PeerRecord>(&bytes)?);
+            }
+        }
+        records.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(records)
+    }
+
+    /// Save a `PeerScorer::snapshot`
+    pub async fn save_scorer(&self, state: &PeerScorerState) -> Result<()> {
+        self.backend.put(SCORER_KEY, serde_json::to_vec(state)?).await
+    }
+
+    /// Scores and bans for `PeerScorer::restore`, empty if none were saved
+    pub async fn load_scorer(&self) -> Result<PeerScorerState> {
+        match self.backend.get(SCORER_KEY).await? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(PeerScorerState::default()),
+        }
+    }
+}
+
+fn peer_key(peer_id: &str) -> String {
+    format!("{}{}", PEER_PREFIX, peer_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::NodeType;
+
+    fn record(id: &str) -> PeerRecord {
+        PeerRecord::new(PeerInfo {
+            id: id.to_string(),
+            address: "127.0.0.1:8080".parse().unwrap(),
+            public_key: format!("{}_key", id),
+            capabilities: vec!["agent".to_string()],
+            reputation: 0.8,
+            last_seen: Utc::now(),
+            protocol_version: "1.0.0".to_string(),
+            node_type: NodeType::Agent,
+            rtt_ms: None,
+        })
+    }
+
+    #[test]
+    fn test_score_rewards_uptime_latency_and_conduct() {
+        let weights = PeerScoreWeights::default();
+        let mut fast = record("fast");
+        fast.record_dial(Some(Duration::from_millis(20)));
+        fast.record_dial(Some(Duration::from_millis(40)));
+
+        let mut flaky = record("flaky");
+        flaky.record_dial(Some(Duration::from_millis(20)));
+        flaky.record_dial(None);
+
+        let mut abusive = fast.clone();
+        abusive.misbehavior = 5.0;
+
+        let unknown = record("unknown").rescore(&weights);
+        assert!(fast.rescore(&weights) > flaky.rescore(&weights));
+        assert!(fast.score > abusive.rescore(&weights));
+        assert!(fast.score > unknown && unknown > 0.0);
+        assert_eq!(flaky.uptime(), Some(0.5));
+        assert!((fast.latency_ms.unwrap() - 26.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_peer_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PeerStore::new(Arc::new(FilePeerBackend::open(dir.path()).unwrap()));
+        let mut good = record("good");
+        good.score = 0.9;
+        let mut poor = record("poor");
+        poor.score = 0.2;
+        store.save_all(&[poor.clone(), good.clone()]).await.unwrap();
+        store.save_scorer(&PeerScorerState::default()).await.unwrap();
+
+        // A node reopening the directory finds the same records
+        let reopened = PeerStore::new(Arc::new(FilePeerBackend::open(dir.path()).unwrap()));
+        assert_eq!(reopened.load().await.unwrap(), vec![good, poor]);
+        reopened.remove("good").await.unwrap();
+        reopened.remove("good").await.unwrap();
+        assert_eq!(reopened.load().await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,104 @@
+//! Gossip Privacy Module
+//!
+//! Data residency for gossip. A `PrivacyPolicy` assigns privacy tiers to
+//! topics and message types: `LocalOnly` messages never leave the node,
+//! `DomainOnly` messages go only to peers tagged with the named domain, and
+//! `Global` messages go anywhere. A domain is any label peers are tagged
+//! with, such as a jurisdiction (`eu`) or a trust domain's topic.
+//!
+//! The originating node stamps a message with the tiers its policy assigns,
+//! and every node enforces both the stamped tiers and its own policy each
+//! time it picks peers to send the message to, so a relay with a looser
+//! configuration cannot widen a message's reach.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::gossip::{GossipMessage, GossipMessageType};
+
+/// How far a message may propagate
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PrivacyTier {
+    LocalOnly,                            // Never sent to a peer
+    DomainOnly(String),                   // Only to peers tagged with this domain
+    Global,
+}
+
+impl PrivacyTier {
+    /// Whether a peer tagged with `domains` may receive a message under this tier
+    pub fn permits(&self, domains: &HashSet<String>) -> bool {
+        match self {
+            PrivacyTier::LocalOnly => false,
+            PrivacyTier::DomainOnly(domain) => domains.contains(domain),
+            PrivacyTier::Global => true,
+        }
+    }
+}
+
+/// Privacy tiers by topic and message type; anything unlisted is global
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivacyPolicy {
+    pub topics: HashMap<String, PrivacyTier>,
+    pub message_types: HashMap<GossipMessageType, PrivacyTier>,
+}
+
+impl PrivacyPolicy {
+    pub fn with_topic(mut self, topic: impl Into<String>, tier: PrivacyTier) -> Self {
+        self.topics.insert(topic.into(), tier);
+        self
+    }
+
+    pub fn with_message_type(mut self, message_type: GossipMessageType, tier: PrivacyTier) -> Self {
+        self.message_types.insert(message_type, tier);
+        self
+    }
+
+    /// Tiers this policy assigns to a message, by its topic and by its type
+    pub fn tiers_for<'a>(&'a self, message: &'a GossipMessage) -> impl Iterator<Item = &'a PrivacyTier> {
+        message
+            .topic
+            .as_ref()
+            .and_then(|topic| self.topics.get(topic))
+            .into_iter()
+            .chain(self.message_types.get(&message.message_type))
+    }
+
+    /// Whether a peer tagged with `domains` may receive a message: the tiers
+    /// stamped on it and those this policy assigns must all permit it
+    pub fn permits(&self, message: &GossipMessage, domains: &HashSet<String>) -> bool {
+        message
+            .privacy
+            .iter()
+            .chain(self.tiers_for(message))
+            .all(|tier| tier.permits(domains))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stamped_and_local_tiers_must_all_permit() {
+        let policy = PrivacyPolicy::default()
+            .with_topic("disputes", PrivacyTier::DomainOnly("eu".to_string()))
+            .with_message_type(GossipMessageType::StateUpdate, PrivacyTier::LocalOnly);
+        let eu: HashSet<String> = ["eu".to_string()].into_iter().collect();
+        let us: HashSet<String> = ["us".to_string()].into_iter().collect();
+
+        let dispute = GossipMessage::new(GossipMessageType::Publish, "a".to_string(), serde_json::json!({}), 5).on_topic("disputes");
+        assert!(policy.permits(&dispute, &eu));
+        assert!(!policy.permits(&dispute, &us));
+
+        let state = GossipMessage::new(GossipMessageType::StateUpdate, "a".to_string(), serde_json::json!({}), 5);
+        assert!(!policy.permits(&state, &eu));
+
+        // A stamp from the origin holds even where the local policy is open
+        let mut evaluation = GossipMessage::new(GossipMessageType::Publish, "a".to_string(), serde_json::json!({}), 5).on_topic("evaluations");
+        assert!(PrivacyPolicy::default().permits(&evaluation, &us));
+        evaluation.privacy.push(PrivacyTier::DomainOnly("eu".to_string()));
+        assert!(!PrivacyPolicy::default().permits(&evaluation, &us));
+        assert!(PrivacyPolicy::default().permits(&evaluation, &eu));
+    }
+}
@@ -0,0 +1,339 @@
+//! Outbound Proxy Module
+//!
+//! Routes outbound peer connections through a SOCKS5 or HTTP CONNECT proxy,
+//! for operators whose networks only let traffic out that way. The proxy is
+//! set per network in `TransportConfig::proxy`; every TCP connection the
+//! node dials then opens a tunnel through it, while listening is unaffected.
+//! SOCKS5 may ask for a username and password (RFC 1929), and HTTP proxies
+//! for Basic credentials. QUIC runs over UDP and cannot be proxied.
+//!
+//! With the `tor` feature, peers may also be addressed by Tor onion service
+//! (`<name>.onion:<port>`). Onion addresses are never resolved locally: the
+//! hostname is handed to a SOCKS5 proxy, normally the Tor client's, which
+//! must be configured to dial them.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Longest HTTP CONNECT response header we read
+const MAX_HTTP_RESPONSE: usize = 8 * 1024;
+
+/// Proxy protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProxyKind {
+    Socks5,
+    HttpConnect,
+}
+
+/// Username and password the proxy asks for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Proxy that outbound connections tunnel through
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub kind: ProxyKind,
+    pub address: String,                  // host:port of the proxy itself
+    pub credentials: Option<ProxyCredentials>,
+}
+
+impl ProxyConfig {
+    pub fn socks5(address: impl Into<String>) -> Self {
+        Self { kind: ProxyKind::Socks5, address: address.into(), credentials: None }
+    }
+
+    pub fn http_connect(address: impl Into<String>) -> Self {
+        Self { kind: ProxyKind::HttpConnect, address: address.into(), credentials: None }
+    }
+
+    /// The local Tor client's SOCKS port
+    #[cfg(feature = "tor")]
+    pub fn tor() -> Self {
+        Self::socks5("127.0.0.1:9050")
+    }
+
+    pub fn with_credentials(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credentials = Some(ProxyCredentials { username: username.into(), password: password.into() });
+        self
+    }
+
+    /// Connect to the proxy and open a tunnel to `target`
+    pub async fn connect(&self, target: &PeerAddress) -> std::io::Result<TcpStream> {
+        let mut stream = TcpStream::connect(&self.address).await?;
+        stream.set_nodelay(true)?;
+        self.handshake(&mut stream, target).await?;
+        Ok(stream)
+    }
+
+    /// Ask the proxy, over `stream`, for a tunnel to `target`
+    pub async fn handshake<S>(&self, stream: &mut S, target: &PeerAddress) -> std::io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        match self.kind {
+            ProxyKind::Socks5 => socks5_connect(stream, target, self.credentials.as_ref()).await,
+            ProxyKind::HttpConnect => {
+                #[cfg(feature = "tor")]
+                if let PeerAddress::Onion { .. } = target {
+                    return Err(refused("onion addresses need a SOCKS5 proxy"));
+                }
+                http_connect(stream, target, self.credentials.as_ref()).await
+            }
+        }
+    }
+}
+
+/// Where a peer can be dialed
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PeerAddress {
+    Socket(SocketAddr),
+    #[cfg(feature = "tor")]
+    Onion { host: String, port: u16 },    // Tor onion service, reached only through a SOCKS5 proxy
+}
+
+impl PeerAddress {
+    /// The socket address, unless only a proxy can resolve it
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Socket(addr) => Some(*addr),
+            #[cfg(feature = "tor")]
+            Self::Onion { .. } => None,
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerAddress {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Socket(addr)
+    }
+}
+
+impl FromStr for PeerAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(addr) = s.parse::<SocketAddr>() {
+            return Ok(Self::Socket(addr));
+        }
+        #[cfg(feature = "tor")]
+        if let Some((host, port)) = s.rsplit_once(':') {
+            let name = host.strip_suffix(".onion").unwrap_or_default();
+            if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()) {
+                let port = port.parse().map_err(|_| format!("Invalid port in {}", s))?;
+                return Ok(Self::Onion { host: host.to_ascii_lowercase(), port });
+            }
+        }
+        Err(format!("Not a peer address: {}", s))
+    }
+}
+
+impl fmt::Display for PeerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Socket(addr) => write!(f, "{}", addr),
+            #[cfg(feature = "tor")]
+            Self::Onion { host, port } => write!(f, "{}:{}", host, port),
+        }
+    }
+}
+
+fn refused(reason: impl Into<String>) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::ConnectionRefused, format!("Proxy: {}", reason.into()))
+}
+
+/// SOCKS5 CONNECT (RFC 1928), with username/password authentication (RFC 1929)
+async fn socks5_connect<S>(stream: &mut S, target: &PeerAddress, credentials: Option<&ProxyCredentials>) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // Offer no authentication, and username/password when we have them
+    let greeting: &[u8] = if credentials.is_some() { &[5, 2, 0, 2] } else { &[5, 1, 0] };
+    stream.write_all(greeting).await?;
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await?;
+    match (choice, credentials) {
+        ([5, 0], _) => {}
+        ([5, 2], Some(credentials)) => {
+            let (username, password) = (credentials.username.as_bytes(), credentials.password.as_bytes());
+            if username.len() > 255 || password.len() > 255 {
+                return Err(refused("credentials longer than 255 bytes"));
+            }
+            let mut request = vec![1, username.len() as u8];
+            request.extend_from_slice(username);
+            request.push(password.len() as u8);
+            request.extend_from_slice(password);
+            stream.write_all(&request).await?;
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await?;
+            if status[1] != 0 {
+                return Err(refused("SOCKS5 authentication failed"));
+            }
+        }
+        ([5, _], _) => return Err(refused("no acceptable SOCKS5 authentication method")),
+        _ => return Err(refused("not a SOCKS5 proxy")),
+    }
+
+    let mut request = vec![5, 1, 0];
+    match target {
+        PeerAddress::Socket(SocketAddr::V4(addr)) => {
+            request.push(1);
+            request.extend_from_slice(&addr.ip().octets());
+        }
+        PeerAddress::Socket(SocketAddr::V6(addr)) => {
+            request.push(4);
+            request.extend_from_slice(&addr.ip().octets());
+        }
+        #[cfg(feature = "tor")]
+        PeerAddress::Onion { host, .. } => {
+            request.extend_from_slice(&[3, host.len() as u8]);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    let port = match target {
+        PeerAddress::Socket(addr) => addr.port(),
+        #[cfg(feature = "tor")]
+        PeerAddress::Onion { port, .. } => *port,
+    };
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        let reason = match reply[1] {
+            1 => "general failure",
+            2 => "connection not allowed by ruleset",
+            3 => "network unreachable",
+            4 => "host unreachable",
+            5 => "connection refused",
+            6 => "TTL expired",
+            7 => "command not supported",
+            8 => "address type not supported",
+            _ => "unknown error",
+        };
+        return Err(refused(format!("SOCKS5 connect to {} failed: {}", target, reason)));
+    }
+    // Skip the address the proxy bound for us
+    let bound = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => stream.read_u8().await? as usize,
+        other => return Err(refused(format!("unknown SOCKS5 address type {}", other))),
+    };
+    let mut skipped = vec![0u8; bound + 2];
+    stream.read_exact(&mut skipped).await?;
+    Ok(())
+}
+
+/// HTTP CONNECT tunnel, with Basic proxy authentication
+async fn http_connect<S>(stream: &mut S, target: &PeerAddress, credentials: Option<&ProxyCredentials>) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some(credentials) = credentials {
+        let token = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", credentials.username, credentials.password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // Read byte by byte so nothing past the header is consumed
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_HTTP_RESPONSE {
+            return Err(refused("HTTP CONNECT response too long"));
+        }
+        response.push(stream.read_u8().await?);
+    }
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(refused(format!("HTTP CONNECT to {} failed: {}", target, status_line))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answer one SOCKS5 CONNECT as a proxy requiring `user`/`pass`, returning the target
+    async fn fake_socks5<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S) -> (u8, Vec<u8>, u16) {
+        let mut greeting = [0u8; 4];
+        stream.read_exact(&mut greeting).await.unwrap();
+        assert_eq!(greeting, [5, 2, 0, 2]);
+        stream.write_all(&[5, 2]).await.unwrap();
+        let mut auth = [0u8; 11];
+        stream.read_exact(&mut auth).await.unwrap();
+        assert_eq!(&auth, b"\x01\x04user\x04pass");
+        stream.write_all(&[1, 0]).await.unwrap();
+
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header).await.unwrap();
+        let len = match header[3] {
+            1 => 4,
+            3 => stream.read_u8().await.unwrap() as usize,
+            _ => 16,
+        };
+        let mut host = vec![0u8; len];
+        stream.read_exact(&mut host).await.unwrap();
+        let port = stream.read_u16().await.unwrap();
+        stream.write_all(&[5, 0, 0, 1, 10, 0, 0, 1, 0, 80]).await.unwrap();
+        (header[3], host, port)
+    }
+
+    #[tokio::test]
+    async fn test_socks5_and_http_connect_handshakes() {
+        let proxy = ProxyConfig::socks5("proxy:1080").with_credentials("user", "pass");
+        let target: PeerAddress = "10.1.2.3:7000".parse().unwrap();
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let (result, (kind, host, port)) = tokio::join!(proxy.handshake(&mut client, &target), fake_socks5(&mut server));
+        result.unwrap();
+        assert_eq!((kind, host, port), (1, vec![10, 1, 2, 3], 7000));
+
+        let proxy = ProxyConfig::http_connect("proxy:3128").with_credentials("user", "pass");
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        server.write_all(b"HTTP/1.1 200 Connection established\r\n\r\nafter").await.unwrap();
+        proxy.handshake(&mut client, &target).await.unwrap();
+        let mut tunneled = [0u8; 5];
+        client.read_exact(&mut tunneled).await.unwrap();
+        assert_eq!(&tunneled, b"after");
+        let mut request = vec![0u8; 256];
+        let read = server.read(&mut request).await.unwrap();
+        let request = String::from_utf8_lossy(&request[..read]);
+        assert!(request.starts_with("CONNECT 10.1.2.3:7000 HTTP/1.1\r\n"));
+        assert!(request.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        server.write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n").await.unwrap();
+        let error = proxy.handshake(&mut client, &target).await.unwrap_err();
+        assert!(error.to_string().contains("407"));
+    }
+
+    #[cfg(feature = "tor")]
+    #[tokio::test]
+    async fn test_onion_addresses_are_resolved_by_the_proxy() {
+        let target: PeerAddress = "ExampleOnionService.onion:9735".parse().unwrap();
+        assert_eq!(target.socket_addr(), None);
+        assert!("not-an-onion.example:80".parse::<PeerAddress>().is_err());
+
+        let proxy = ProxyConfig::tor().with_credentials("user", "pass");
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let (result, (kind, host, port)) = tokio::join!(proxy.handshake(&mut client, &target), fake_socks5(&mut server));
+        result.unwrap();
+        assert_eq!((kind, host.as_slice(), port), (3, b"exampleonionservice.onion".as_slice(), 9735));
+
+        let error = ProxyConfig::http_connect("proxy:3128").handshake(&mut client, &target).await.unwrap_err();
+        assert!(error.to_string().contains("SOCKS5"));
+    }
+}
@@ -0,0 +1,267 @@
+//! Bounded Message Queues
+//!
+//! Queues between protocol stages hold at most `capacity` items, so a burst
+//! cannot grow memory without limit. What happens when a queue is full is
+//! its `BackpressurePolicy`: `Block` makes senders wait for room,
+//! `DropOldest` evicts the item queued first, and `DropLowestPriority`
+//! evicts the oldest item of the lowest priority, or drops the new item if
+//! everything queued outranks it. Depth, high-water mark, drops and blocked
+//! sends are exposed as `QueueMetrics`.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::Notify;
+
+use crate::messaging::MessagePriority;
+
+/// What a full queue does with one more item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackpressurePolicy {
+    Block,
+    DropOldest,
+    DropLowestPriority,
+}
+
+/// Queue settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueConfig {
+    pub capacity: usize,
+    pub policy: BackpressurePolicy,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 4096,
+            policy: BackpressurePolicy::DropLowestPriority,
+        }
+    }
+}
+
+/// Snapshot of a queue's load
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueMetrics {
+    pub depth: usize,
+    pub capacity: usize,
+    pub high_water: usize,                // Deepest the queue has been
+    pub dropped: u64,                     // Items discarded by the policy
+    pub blocked: u64,                     // Sends that had to wait for room
+}
+
+#[derive(Debug, Error)]
+pub enum QueueError {
+    #[error("Queue receiver is closed")]
+    Closed,
+}
+
+struct Shared<T> {
+    items: Mutex<VecDeque<(MessagePriority, T)>>,
+    config: QueueConfig,
+    not_empty: Notify,
+    not_full: Notify,
+    senders: AtomicUsize,
+    receiver_open: AtomicBool,
+    high_water: AtomicUsize,
+    dropped: AtomicU64,
+    blocked: AtomicU64,
+}
+
+impl<T> Shared<T> {
+    fn metrics(&self) -> QueueMetrics {
+        QueueMetrics {
+            depth: self.items.lock().len(),
+            capacity: self.config.capacity,
+            high_water: self.high_water.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            blocked: self.blocked.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Create a bounded queue
+pub fn bounded<T>(config: QueueConfig) -> (QueueSender<T>, QueueReceiver<T>) {
+    let shared = Arc::new(Shared {
+        items: Mutex::new(VecDeque::with_capacity(config.capacity.min(1024))),
+        config: QueueConfig { capacity: config.capacity.max(1), ..config },
+        not_empty: Notify::new(),
+        not_full: Notify::new(),
+        senders: AtomicUsize::new(1),
+        receiver_open: AtomicBool::new(true),
+        high_water: AtomicUsize::new(0),
+        dropped: AtomicU64::new(0),
+        blocked: AtomicU64::new(0),
+    });
+    (QueueSender { shared: shared.clone() }, QueueReceiver { shared })
+}
+
+/// Sending half of a bounded queue
+pub struct QueueSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> QueueSender<T> {
+    /// Queue `item`, applying the backpressure policy if the queue is full
+    pub async fn send(&self, item: T, priority: MessagePriority) -> Result<(), QueueError> {
+        let mut item = Some(item);
+        let mut waited = false;
+        loop {
+            if !self.shared.receiver_open.load(Ordering::Acquire) {
+                return Err(QueueError::Closed);
+            }
+            {
+                let mut items = self.shared.items.lock();
+                if items.len() < self.shared.config.capacity {
+                    items.push_back((priority, item.take().expect("item is queued once")));
+                    self.shared.high_water.fetch_max(items.len(), Ordering::Relaxed);
+                    drop(items);
+                    self.shared.not_empty.notify_one();
+                    return Ok(());
+                }
+                match self.shared.config.policy {
+                    BackpressurePolicy::Block => {
+                        if !waited {
+                            self.shared.blocked.fetch_add(1, Ordering::Relaxed);
+                            waited = true;
+                        }
+                    }
+                    BackpressurePolicy::DropOldest => {
+                        items.pop_front();
+                        items.push_back((priority, item.take().expect("item is queued once")));
+                        self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        return Ok(());
+                    }
+                    BackpressurePolicy::DropLowestPriority => {
+                        self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        let lowest = items.iter().enumerate().min_by_key(|(index, (queued, _))| (*queued, *index)).map(|(index, (queued, _))| (index, *queued));
+                        if let Some((index, queued)) = lowest.filter(|(_, queued)| *queued <= priority) {
+                            tracing::trace!("Queue full, evicting a {:?} item", queued);
+                            items.remove(index);
+                            items.push_back((priority, item.take().expect("item is queued once")));
+                        }
+                        return Ok(());
+                    }
+                }
+            }
+            self.shared.not_full.notified().await;
+        }
+    }
+
+    pub fn metrics(&self) -> QueueMetrics {
+        self.shared.metrics()
+    }
+}
+
+impl<T> Clone for QueueSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for QueueSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.not_empty.notify_one();
+        }
+    }
+}
+
+/// Receiving half of a bounded queue
+pub struct QueueReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> QueueReceiver<T> {
+    /// Next item in queue order; `None` once every sender is gone and the
+    /// queue is drained
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            if let Some(item) = self.try_recv() {
+                return Some(item);
+            }
+            if self.shared.senders.load(Ordering::Acquire) == 0 {
+                return self.try_recv();
+            }
+            self.shared.not_empty.notified().await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<T> {
+        let item = self.shared.items.lock().pop_front().map(|(_, item)| item);
+        if item.is_some() {
+            self.shared.not_full.notify_one();
+        }
+        item
+    }
+
+    pub fn metrics(&self) -> QueueMetrics {
+        self.shared.metrics()
+    }
+}
+
+impl<T> Drop for QueueReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_open.store(false, Ordering::Release);
+        self.shared.not_full.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn queue(policy: BackpressurePolicy) -> (QueueSender<u32>, QueueReceiver<u32>) {
+        bounded(QueueConfig { capacity: 3, policy })
+    }
+
+    async fn drain(rx: &mut QueueReceiver<u32>) -> Vec<u32> {
+        std::iter::from_fn(|| rx.try_recv()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_drop_policies() {
+        let (tx, mut rx) = queue(BackpressurePolicy::DropOldest);
+        for item in 0..5 {
+            tx.send(item, MessagePriority::Normal).await.unwrap();
+        }
+        assert_eq!(drain(&mut rx).await, vec![2, 3, 4]);
+        assert_eq!((tx.metrics().dropped, tx.metrics().high_water), (2, 3));
+
+        let (tx, mut rx) = queue(BackpressurePolicy::DropLowestPriority);
+        tx.send(1, MessagePriority::High).await.unwrap();
+        tx.send(2, MessagePriority::Low).await.unwrap();
+        tx.send(3, MessagePriority::Normal).await.unwrap();
+        // Evicts the low-priority item, then refuses one outranked by everything queued
+        tx.send(4, MessagePriority::Normal).await.unwrap();
+        tx.send(5, MessagePriority::Low).await.unwrap();
+        assert_eq!(drain(&mut rx).await, vec![1, 3, 4]);
+        assert_eq!(rx.metrics().dropped, 2);
+    }
+
+    #[tokio::test]
+    async fn test_block_waits_for_room() {
+        let (tx, mut rx) = queue(BackpressurePolicy::Block);
+        for item in 0..3 {
+            tx.send(item, MessagePriority::Normal).await.unwrap();
+        }
+        let blocked = tokio::spawn(async move {
+            tx.send(3, MessagePriority::Normal).await.unwrap();
+            tx.metrics()
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+
+        assert_eq!(rx.recv().await, Some(0));
+        let metrics = blocked.await.unwrap();
+        assert_eq!((metrics.blocked, metrics.dropped, metrics.depth), (1, 0, 3));
+        assert_eq!(drain(&mut rx).await, vec![1, 2, 3]);
+        // Every sender is gone
+        assert_eq!(rx.recv().await, None);
+    }
+}
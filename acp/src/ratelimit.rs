@@ -0,0 +1,230 @@
+//! Gossip Rate Limiting Module
+//!
+//! Per-peer quotas on incoming gossip. Each peer gets two token buckets, one
+//! counting messages and one counting bytes; a message is admitted only if
+//! both have tokens left. Buckets refill continuously up to a burst size, so
+//! well-behaved peers can send short bursts while sustained floods are cut
+//! to the configured rate. A peer that keeps hitting its limits is muted:
+//! everything it sends is dropped until the mute expires.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Per-peer quotas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub messages_per_sec: f64,
+    pub message_burst: f64,
+    pub bytes_per_sec: f64,
+    pub byte_burst: f64,
+    pub violations_before_mute: u32,      // Drops that get a peer muted
+    pub mute_duration: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            messages_per_sec: 50.0,
+            message_burst: 100.0,
+            bytes_per_sec: 1024.0 * 1024.0,
+            byte_burst: 4.0 * 1024.0 * 1024.0,
+            violations_before_mute: 20,
+            mute_duration: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Verdict on an incoming message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDecision {
+    Allowed,
+    Limited,                              // Over quota, dropped
+    Muted,                                // Peer is muted, dropped
+}
+
+/// Continuously refilling token bucket
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Full bucket
+    pub fn new(capacity: f64, refill_per_sec: f64, now: Instant) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    /// Take tokens if enough are available
+    pub fn try_take(&mut self, amount: f64, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens >= amount {
+            self.tokens -= amount;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Take tokens even if that leaves the bucket in debt, and return how
+    /// long until the debt is repaid
+    pub fn reserve(&mut self, amount: f64, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= amount;
+        if self.tokens >= 0.0 || self.refill_per_sec <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(-self.tokens / self.refill_per_sec)
+    }
+
+    /// Take tokens if that leaves at least `reserve` in the bucket, or return
+    /// how long until it would. A take too large to fit beside the reserve
+    /// waits for a full bucket instead, and may leave it in debt.
+    pub fn take_above(&mut self, amount: f64, reserve: f64, now: Instant) -> Duration {
+        self.refill(now);
+        let needed = (amount + reserve).min(self.capacity);
+        if self.tokens >= needed || self.refill_per_sec <= 0.0 {
+            self.tokens -= amount;
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64((needed - self.tokens) / self.refill_per_sec)
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Whether a single take of this size could ever succeed
+    fn fits(&self, amount: f64) -> bool {
+        amount <= self.capacity
+    }
+}
+
+/// Quota state for one peer
+#[derive(Debug, Clone)]
+struct PeerQuota {
+    messages: TokenBucket,
+    bytes: TokenBucket,
+    violations: u32,
+    muted_until: Option<Instant>,
+}
+
+/// Token-bucket limiter over all peers
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    pub config: RateLimitConfig,
+    peers: HashMap<String, PeerQuota>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Charge a message of `bytes` to a peer's quota
+    pub fn check(&mut self, peer_id: &str, bytes: usize, now: Instant) -> RateDecision {
+        let config = &self.config;
+        let quota = self.peers.entry(peer_id.to_string()).or_insert_with(|| PeerQuota {
+            messages: TokenBucket::new(config.message_burst, config.messages_per_sec, now),
+            bytes: TokenBucket::new(config.byte_burst, config.bytes_per_sec, now),
+            violations: 0,
+            muted_until: None,
+        });
+
+        match quota.muted_until {
+            Some(until) if now < until => return RateDecision::Muted,
+            Some(_) => quota.muted_until = None,
+            None => {}
+        }
+
+        // Oversized messages never fit the byte bucket; count them as a
+        // violation rather than letting them drain it forever
+        let bytes = bytes as f64;
+        let allowed = quota.bytes.fits(bytes) && quota.messages.try_take(1.0, now) && quota.bytes.try_take(bytes, now);
+        if allowed {
+            quota.violations = quota.violations.saturating_sub(1);
+            return RateDecision::Allowed;
+        }
+
+        quota.violations += 1;
+        if quota.violations >= config.violations_before_mute {
+            quota.violations = 0;
+            quota.muted_until = Some(now + config.mute_duration);
+        }
+        RateDecision::Limited
+    }
+
+    /// Whether a peer is muted
+    pub fn is_muted(&self, peer_id: &str, now: Instant) -> bool {
+        self.peers
+            .get(peer_id)
+            .and_then(|quota| quota.muted_until)
+            .is_some_and(|until| now < until)
+    }
+
+    /// Lift a peer's mute early
+    pub fn unmute(&mut self, peer_id: &str) {
+        if let Some(quota) = self.peers.get_mut(peer_id) {
+            quota.muted_until = None;
+            quota.violations = 0;
+        }
+    }
+
+    /// Drop a disconnected peer's quota state
+    pub fn forget(&mut self, peer_id: &str) {
+        self.peers.remove(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_floods_are_limited_then_muted() {
+        let config = RateLimitConfig {
+            messages_per_sec: 10.0,
+            message_burst: 5.0,
+            byte_burst: 1_000.0,
+            violations_before_mute: 3,
+            ..RateLimitConfig::default()
+        };
+        let mut limiter = RateLimiter::new(config);
+        let start = Instant::now();
+
+        for _ in 0..5 {
+            assert_eq!(limiter.check("flood", 10, start), RateDecision::Allowed);
+        }
+        assert_eq!(limiter.check("flood", 10, start), RateDecision::Limited);
+        assert_eq!(limiter.check("quiet", 2_000, start), RateDecision::Limited);
+        assert_eq!(limiter.check("quiet", 10, start), RateDecision::Allowed);
+
+        // Tokens come back at the configured rate
+        let later = start + Duration::from_millis(100);
+        assert_eq!(limiter.check("flood", 10, later), RateDecision::Allowed);
+
+        for _ in 0..3 {
+            assert_eq!(limiter.check("flood", 10, later), RateDecision::Limited);
+        }
+        assert!(limiter.is_muted("flood", later));
+        assert_eq!(limiter.check("flood", 10, later + Duration::from_secs(30)), RateDecision::Muted);
+        assert_eq!(limiter.check("flood", 10, later + Duration::from_secs(61)), RateDecision::Allowed);
+    }
+}
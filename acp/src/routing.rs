@@ -0,0 +1,917 @@
+//! Message Routing
+//!
+//! Delivers messages to nodes that are not direct peers. Routes are found
+//! on demand: a node that has none floods a `RouteDiscovery::Request` to its
+//! peers, each hop appending itself to the request's path, and the target
+//! answers with a `RouteDiscovery::Reply` carrying the full path back the
+//! way it came. Every node a request or reply crosses learns routes to the
+//! nodes on either side of it.
+//!
+//! Messages then travel hop by hop inside a `RoutedData` envelope. Each hop
+//! appends itself to the envelope's `routing_path` and forwards it to the
+//! next hop of its own best route, skipping any next hop already on the
+//! path. Envelopes that come back to a node on their path, or exceed
+//! `max_hops`, are dropped, and each request is flooded at most once per
+//! node, so neither data nor discovery can loop.
+//!
+//! `RoutingTable::best_route` caches the best route per destination. Losing
+//! a peer drops every route through it and invalidates the cache entries
+//! that depended on it.
+//!
+//! Messages flagged reliable are kept in an `Outbox` until the recipient
+//! answers with an `Ack`, retransmitted by `retransmit`, and reported on
+//! the event bus as `DeliveryReceipt`s once acknowledged or given up. The
+//! bus hands receipts to `EventBus::subscribe_deliveries` receivers without
+//! ever dropping one.
+//!
+//! `send_cancellable` and `discover_route_cancellable` stop waiting for a
+//! route, or for the first hop to take the message, when their token fires.
+//! A reliable message is committed once it is in the outbox: cancelling
+//! after that leaves it to retransmission rather than unsending it.
+//!
+//! Received messages can be queued with `enqueue` and processed by `run`.
+//! The inbound queue is bounded; when it fills, `RoutingConfig::inbound_queue`
+//! decides whether receivers wait or heartbeats and plain traffic are shed
+//! before route control and messages whose transaction deadline is near.
+//! `run` dispatches what has arrived by priority through a `MessageQueue`:
+//! transaction responses and route control preempt heartbeats, and
+//! messages waiting longer than `starvation_age` are raised a level at a
+//! time so low-priority traffic still gets through.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, oneshot};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::events::{ACPEvent, EventBus};
+use crate::messaging::{ACPMessage, DeadlineEscalation, MessagePriority, MessageQueue, MessageType, PriorityCounts, PriorityMessage};
+use crate::outbox::{DeliveryReceipt, Outbox, OutboxConfig};
+use crate::p2p::{InboundMessage, P2PNetwork};
+use crate::queue::{self, QueueConfig, QueueMetrics, QueueReceiver, QueueSender};
+use crate::{ACPError, Result};
+
+/// Route requests remembered to suppress re-flooding
+const SEEN_REQUESTS: usize = 4096;
+
+/// Messages to send, each with the direct peer it goes to
+pub type Outgoing = Vec<(String, ACPMessage)>;
+
+/// Routing settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    pub max_hops: usize,
+    pub route_ttl: Duration,              // Learned routes older than this are not used
+    pub discovery_timeout: Duration,
+    #[serde(default)]
+    pub outbox: Option<OutboxConfig>,     // Needed to send reliable messages
+    #[serde(default)]
+    pub inbound_queue: QueueConfig,       // Bound and backpressure policy for received messages
+    pub starvation_age: Duration,         // Wait that raises a queued message one priority level
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            max_hops: 8,
+            route_ttl: Duration::from_secs(300),
+            discovery_timeout: Duration::from_secs(5),
+            outbox: None,
+            inbound_queue: QueueConfig::default(),
+            starvation_age: Duration::from_secs(5),
+        }
+    }
+}
+
+/// A way to reach a destination
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub destination: String,
+    pub next_hop: String,
+    pub path: Vec<String>,                // Hops after this node, ending at the destination
+    pub learned_at: Instant,
+}
+
+impl Route {
+    pub fn hops(&self) -> usize {
+        self.path.len()
+    }
+
+    fn passes_through(&self, node_id: &str) -> bool {
+        self.path.iter().any(|hop| hop == node_id)
+    }
+}
+
+/// Direct peers and learned multi-hop routes, with the best route per
+/// destination cached
+pub struct RoutingTable {
+    local_id: String,
+    route_ttl: Duration,
+    neighbors: HashSet<String>,
+    routes: HashMap<String, Vec<Route>>,  // Destination -> learned routes
+    cache: HashMap<String, Route>,        // Destination -> best route
+}
+
+impl RoutingTable {
+    pub fn new(local_id: impl Into<String>, route_ttl: Duration) -> Self {
+        Self {
+            local_id: local_id.into(),
+            route_ttl,
+            neighbors: HashSet::new(),
+            routes: HashMap::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn add_neighbor(&mut self, peer_id: &str) {
+        if peer_id != self.local_id && self.neighbors.insert(peer_id.to_string()) {
+            // A direct link beats any cached multi-hop route
+            self.cache.remove(peer_id);
+        }
+    }
+
+    /// Forget a disconnected peer and every route through it. Returns the
+    /// destinations left without a route, the peer itself included.
+    pub fn remove_neighbor(&mut self, peer_id: &str) -> Vec<String> {
+        self.neighbors.remove(peer_id);
+        let mut unreachable = vec![peer_id.to_string()];
+        for (destination, routes) in self.routes.iter_mut() {
+            routes.retain(|route| !route.passes_through(peer_id));
+            if routes.is_empty() && destination != peer_id {
+                unreachable.push(destination.clone());
+            }
+        }
+        self.routes.retain(|_, routes| !routes.is_empty());
+        self.cache.retain(|_, route| !route.passes_through(peer_id));
+        unreachable.retain(|destination| !self.routes.contains_key(destination));
+        unreachable.sort();
+        unreachable
+    }
+
+    pub fn is_neighbor(&self, peer_id: &str) -> bool {
+        self.neighbors.contains(peer_id)
+    }
+
+    pub fn neighbors(&self) -> impl Iterator<Item = &String> {
+        self.neighbors.iter()
+    }
+
+    /// Learn the routes along `path`, which starts at a direct peer: one to
+    /// every node on it. Returns whether the path was usable.
+    pub fn learn_path(&mut self, path: &[String], now: Instant) -> bool {
+        let Some(next_hop) = path.first() else {
+            return false;
+        };
+        let mut seen = HashSet::new();
+        let loop_free = path.iter().all(|hop| hop != &self.local_id && seen.insert(hop));
+        if !self.neighbors.contains(next_hop) || !loop_free {
+            return false;
+        }
+
+        for end in 1..=path.len() {
+            let route = Route {
+                destination: path[end - 1].clone(),
+                next_hop: next_hop.clone(),
+                path: path[..end].to_vec(),
+                learned_at: now,
+            };
+            let routes = self.routes.entry(route.destination.clone()).or_default();
+            routes.retain(|known| known.path != route.path);
+            routes.push(route.clone());
+            if self.cache.get(&route.destination).is_some_and(|cached| cached.hops() > route.hops()) {
+                self.cache.remove(&route.destination);
+            }
+        }
+        true
+    }
+
+    /// Best route to `destination`: direct if it is a peer, otherwise the
+    /// fewest hops, newest first
+    pub fn best_route(&mut self, destination: &str) -> Option<Route> {
+        self.best_route_at(destination, Instant::now())
+    }
+
+    pub fn best_route_at(&mut self, destination: &str, now: Instant) -> Option<Route> {
+        if let Some(cached) = self.cache.get(destination) {
+            if self.is_fresh(cached, now) {
+                return Some(cached.clone());
+            }
+            self.cache.remove(destination);
+        }
+        let best = self.route_avoiding(destination, &[], now)?;
+        self.cache.insert(destination.to_string(), best.clone());
+        Some(best)
+    }
+
+    /// Best route whose next hop is not in `avoid`
+    fn route_avoiding(&self, destination: &str, avoid: &[String], now: Instant) -> Option<Route> {
+        if self.neighbors.contains(destination) && !avoid.iter().any(|hop| hop == destination) {
+            return Some(Route {
+                destination: destination.to_string(),
+                next_hop: destination.to_string(),
+                path: vec![destination.to_string()],
+                learned_at: now,
+            });
+        }
+        self.routes
+            .get(destination)?
+            .iter()
+            .filter(|route| self.is_fresh(route, now) && !avoid.contains(&route.next_hop))
+            .min_by_key(|route| (route.hops(), std::cmp::Reverse(route.learned_at)))
+            .cloned()
+    }
+
+    fn is_fresh(&self, route: &Route, now: Instant) -> bool {
+        self.neighbors.contains(&route.next_hop) && now.saturating_duration_since(route.learned_at) <= self.route_ttl
+    }
+
+    /// Destinations with a cached best route
+    pub fn cached(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+/// Path discovery, carried as the payload of `MessageType::RouteDiscovery` messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RouteDiscovery {
+    Request {
+        id: Uuid,
+        target: String,
+        path: Vec<String>,                // Nodes the request crossed, origin first
+    },
+    Reply {
+        id: Uuid,
+        path: Vec<String>,                // Origin to target
+    },
+}
+
+/// A message on its way to a node that is not a direct peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutedEnvelope {
+    pub destination: String,
+    pub routing_path: Vec<String>,        // Nodes that handled it, origin first
+    pub message: ACPMessage,
+}
+
+/// Routing counters
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RoutingStats {
+    pub forwarded: u64,
+    pub loops_dropped: u64,
+    pub hop_limit_dropped: u64,
+    pub unroutable: u64,
+    pub discoveries: u64,
+}
+
+/// Routes messages to direct peers and across multiple hops, and publishes
+/// messages addressed to this node on the event bus
+pub struct MessageRouter {
+    local_id: String,
+    config: RoutingConfig,
+    table: Mutex<RoutingTable>,
+    pending: Mutex<HashMap<String, Vec<oneshot::Sender<Route>>>>,  // Target -> waiting discoveries
+    seen_requests: Mutex<(HashSet<Uuid>, VecDeque<Uuid>)>,
+    stats: Mutex<RoutingStats>,
+    outbox: Mutex<Option<Outbox>>,
+    events: EventBus,
+    inbound_tx: QueueSender<InboundMessage>,
+    inbound_rx: Mutex<Option<QueueReceiver<InboundMessage>>>,  // Taken by `run`
+    escalation: DeadlineEscalation,
+    dispatch: MessageQueue,
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+}
+
+impl MessageRouter {
+    pub fn new(local_id: impl Into<String>) -> Self {
+        Self::with_config(local_id, RoutingConfig::default())
+    }
+
+    pub fn with_config(local_id: impl Into<String>, config: RoutingConfig) -> Self {
+        let local_id = local_id.into();
+        let (inbound_tx, inbound_rx) = queue::bounded(config.inbound_queue.clone());
+        let dispatch = MessageQueue::new().with_aging(config.starvation_age);
+        Self {
+            table: Mutex::new(RoutingTable::new(local_id.clone(), config.route_ttl)),
+            local_id,
+            config,
+            pending: Mutex::new(HashMap::new()),
+            seen_requests: Mutex::new((HashSet::new(), VecDeque::new())),
+            stats: Mutex::new(RoutingStats::default()),
+            outbox: Mutex::new(None),
+            events: EventBus::default(),
+            inbound_tx,
+            inbound_rx: Mutex::new(Some(inbound_rx)),
+            escalation: DeadlineEscalation::default(),
+            dispatch,
+            messages_sent: AtomicU64::new(0),
+            messages_received: AtomicU64::new(0),
+        }
+    }
+
+    /// Publish receipts, route changes, and delivered messages on the node's bus
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        if let Some(outbox_config) = self.config.outbox.clone() {
+            let outbox = Outbox::open(outbox_config).map_err(|e| ACPError::Message(format!("Failed to open outbox: {}", e)))?;
+            *self.outbox.lock() = Some(outbox);
+        }
+        tracing::info!("Message router for {} ready (max {} hops)", self.local_id, self.config.max_hops);
+        Ok(())
+    }
+
+    /// Receiver of the router's events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ACPEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn peer_connected(&self, peer_id: &str) {
+        self.table.lock().add_neighbor(peer_id);
+    }
+
+    pub fn peer_disconnected(&self, peer_id: &str) {
+        let unreachable = self.table.lock().remove_neighbor(peer_id);
+        for destination in unreachable {
+            self.events.publish(ACPEvent::RouteChanged { destination, next_hop: None, hops: 0 });
+        }
+    }
+
+    /// Learn the routes along `path`, announcing the route to its end
+    fn learn_route(&self, path: &[String]) -> bool {
+        if !self.table.lock().learn_path(path, Instant::now()) {
+            return false;
+        }
+        if let (Some(next_hop), Some(destination)) = (path.first(), path.last()) {
+            self.events.publish(ACPEvent::RouteChanged {
+                destination: destination.clone(),
+                next_hop: Some(next_hop.clone()),
+                hops: path.len(),
+            });
+        }
+        true
+    }
+
+    pub fn best_route(&self, destination: &str) -> Option<Route> {
+        self.table.lock().best_route(destination)
+    }
+
+    /// Send `message` to its `to` node, discovering a route if none is known
+    pub async fn send(&self, network: &P2PNetwork, message: ACPMessage) -> Result<()> {
+        self.send_with(network, message, None).await
+    }
+
+    /// `send` that gives up if `cancel` fires before the first hop takes
+    /// the message. A reliable message is not cancelled once it is in the
+    /// outbox.
+    pub async fn send_cancellable(&self, network: &P2PNetwork, message: ACPMessage, cancel: &CancellationToken) -> Result<()> {
+        self.send_with(network, message, Some(cancel)).await
+    }
+
+    async fn send_with(&self, network: &P2PNetwork, message: ACPMessage, cancel: Option<&CancellationToken>) -> Result<()> {
+        let destination = message.to.clone().ok_or_else(|| ACPError::Message("Routed messages need a destination".to_string()))?;
+        if self.best_route(&destination).is_none() {
+            self.discover(network, &destination, cancel).await?;
+        }
+        if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(ACPError::Cancelled(format!("send to {}", destination)));
+        }
+        if message.is_reliable() {
+            self.with_outbox(|outbox| outbox.push(&message))?;
+            let (next_hop, outgoing) = self.originate(message)?;
+            return network.send_message(&next_hop, &outgoing).await;
+        }
+        let (next_hop, outgoing) = self.originate(message)?;
+        Self::send_to(network, &next_hop, &outgoing, cancel).await
+    }
+
+    async fn send_to(network: &P2PNetwork, peer_id: &str, message: &ACPMessage, cancel: Option<&CancellationToken>) -> Result<()> {
+        match cancel {
+            Some(cancel) => network.send_message_cancellable(peer_id, message, cancel).await,
+            None => network.send_message(peer_id, message).await,
+        }
+    }
+
+    /// Resend reliable messages still waiting for acknowledgment
+    pub async fn retransmit(&self, network: &P2PNetwork) -> Result<usize> {
+        let outgoing = self.due_retransmissions()?;
+        let count = outgoing.len();
+        for (peer_id, message) in outgoing {
+            if let Err(e) = network.send_message(&peer_id, &message).await {
+                tracing::debug!("Retransmission to {} failed: {}", peer_id, e);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Reliable messages due for retransmission, wrapped for their first
+    /// hop. Messages given up on are reported as delivery receipts.
+    pub fn due_retransmissions(&self) -> Result<Outgoing> {
+        if self.outbox.lock().is_none() {
+            return Ok(Vec::new());
+        }
+        let (resend, abandoned) = self.with_outbox(|outbox| outbox.due(Instant::now()))?;
+        for receipt in abandoned {
+            tracing::warn!("Gave up on message {} after {} attempts", receipt.message_id, receipt.attempts);
+            self.report(receipt);
+        }
+        Ok(resend.into_iter().filter_map(|message| self.originate(message).ok()).collect())
+    }
+
+    fn with_outbox<T>(&self, f: impl FnOnce(&mut Outbox) -> anyhow::Result<T>) -> Result<T> {
+        let mut outbox = self.outbox.lock();
+        let outbox = outbox.as_mut().ok_or_else(|| ACPError::Message("Reliable delivery needs an outbox".to_string()))?;
+        f(outbox).map_err(|e| ACPError::Message(format!("Outbox error: {}", e)))
+    }
+
+    fn report(&self, receipt: DeliveryReceipt) {
+        self.events.publish(ACPEvent::DeliveryReceipt(receipt));
+    }
+
+    /// Flood a route request for `target` and wait for the first reply
+    pub async fn discover_route(&self, network: &P2PNetwork, target: &str) -> Result<Route> {
+        self.discover(network, target, None).await
+    }
+
+    /// `discover_route` that stops waiting for a reply when `cancel` fires.
+    /// A reply arriving later still teaches the routes it carries.
+    pub async fn discover_route_cancellable(&self, network: &P2PNetwork, target: &str, cancel: &CancellationToken) -> Result<Route> {
+        self.discover(network, target, Some(cancel)).await
+    }
+
+    async fn discover(&self, network: &P2PNetwork, target: &str, cancel: Option<&CancellationToken>) -> Result<Route> {
+        let cancelled = || ACPError::Cancelled(format!("route discovery for {}", target));
+        if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(cancelled());
+        }
+        let (answer, outgoing) = self.begin_discovery(target)?;
+        for (peer_id, message) in outgoing {
+            match Self::send_to(network, &peer_id, &message, cancel).await {
+                Err(ACPError::Cancelled(_)) => return Err(cancelled()),
+                Err(e) => tracing::debug!("Route request to {} failed: {}", peer_id, e),
+                Ok(()) => {}
+            }
+        }
+        let answer = tokio::time::timeout(self.config.discovery_timeout, answer);
+        let answer = match cancel {
+            Some(cancel) => tokio::select! {
+                answer = answer => answer,
+                _ = cancel.cancelled() => return Err(cancelled()),
+            },
+            None => answer.await,
+        };
+        match answer {
+            Ok(Ok(route)) => Ok(route),
+            _ => {
+                self.stats.lock().unroutable += 1;
+                Err(ACPError::Network(format!("No route to {}", target)))
+            }
+        }
+    }
+
+    /// Process a received message, sending on whatever it calls for
+    pub async fn handle(&self, network: &P2PNetwork, inbound: InboundMessage) -> Result<()> {
+        for (peer_id, message) in self.process(&inbound.peer.node_id, inbound.message) {
+            network.send_message(&peer_id, &message).await?;
+        }
+        Ok(())
+    }
+
+    /// Queue a received message for `run`, applying the inbound queue's
+    /// backpressure policy if it is full
+    pub async fn enqueue(&self, inbound: InboundMessage) -> Result<()> {
+        let priority = self.inbound_priority(&inbound.message);
+        self.inbound_tx
+            .send(inbound, priority)
+            .await
+            .map_err(|e| ACPError::Message(format!("Inbound queue error: {}", e)))
+    }
+
+    /// Process queued messages, highest priority first, until the router
+    /// is dropped
+    pub async fn run(&self, network: &P2PNetwork) -> Result<()> {
+        let mut inbound_rx = self.inbound_rx.lock().take().ok_or_else(|| ACPError::Protocol("Router is already running".to_string()))?;
+        loop {
+            // Wait only when nothing is scheduled, then take in everything that has arrived
+            if self.dispatch.is_empty() {
+                match inbound_rx.recv().await {
+                    Some(inbound) => self.schedule(&inbound.peer.node_id, inbound.message)?,
+                    None => return Ok(()),
+                }
+            }
+            while let Some(inbound) = inbound_rx.try_recv() {
+                self.schedule(&inbound.peer.node_id, inbound.message)?;
+            }
+
+            for (peer_id, message) in self.dispatch_next() {
+                if let Err(e) = network.send_message(&peer_id, &message).await {
+                    tracing::debug!("Failed to send routed message to {}: {}", peer_id, e);
+                }
+            }
+        }
+    }
+
+    /// Schedule a message from direct peer `from` for dispatch
+    fn schedule(&self, from: &str, message: ACPMessage) -> Result<()> {
+        let priority = Self::base_priority(&message.message_type);
+        self.dispatch.push(PriorityMessage::new(message, priority).from_peer(from))
+    }
+
+    /// Process the scheduled message that should go next
+    fn dispatch_next(&self) -> Outgoing {
+        let Some(next) = self.dispatch.pop() else {
+            return Vec::new();
+        };
+        self.process(next.source.as_deref().unwrap_or_default(), next.message)
+    }
+
+    /// Depth and backpressure counters of the inbound queue
+    pub fn queue_metrics(&self) -> QueueMetrics {
+        self.inbound_tx.metrics()
+    }
+
+    /// Messages dispatched by `run`, by the priority they went at
+    pub fn dispatched_by_priority(&self) -> PriorityCounts {
+        self.dispatch.dispatched()
+    }
+
+    /// Priority of a received message before deadline escalation and aging
+    fn base_priority(message_type: &MessageType) -> MessagePriority {
+        match message_type {
+            MessageType::TransactionResponse | MessageType::TransactionComplete | MessageType::RouteDiscovery | MessageType::Ack => MessagePriority::High,
+            MessageType::Heartbeat | MessageType::PeerDiscovery => MessagePriority::Low,
+            _ => MessagePriority::Normal,
+        }
+    }
+
+    /// Priority in the inbound queue, escalated for transactions near their deadline
+    fn inbound_priority(&self, message: &ACPMessage) -> MessagePriority {
+        self.escalation.escalate(message, Self::base_priority(&message.message_type), chrono::Utc::now())
+    }
+
+    /// Wrap `message` for its first hop
+    pub fn originate(&self, message: ACPMessage) -> Result<(String, ACPMessage)> {
+        let destination = message.to.clone().ok_or_else(|| ACPError::Message("Routed messages need a destination".to_string()))?;
+        let route = self.best_route(&destination).ok_or_else(|| ACPError::Network(format!("No route to {}", destination)))?;
+        let envelope = RoutedEnvelope {
+            destination,
+            routing_path: vec![self.local_id.clone()],
+            message,
+        };
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        Ok((route.next_hop.clone(), self.envelope_message(&route.next_hop, &envelope)?))
+    }
+
+    /// Start a route discovery: the receiver resolves when a reply arrives,
+    /// and the messages go to every direct peer
+    pub fn begin_discovery(&self, target: &str) -> Result<(oneshot::Receiver<Route>, Outgoing)> {
+        let (reply, answer) = oneshot::channel();
+        self.pending.lock().entry(target.to_string()).or_default().push(reply);
+        self.stats.lock().discoveries += 1;
+
+        let id = Uuid::new_v4();
+        self.mark_seen(id);
+        let request = RouteDiscovery::Request { id, target: target.to_string(), path: vec![self.local_id.clone()] };
+        let neighbors: Vec<String> = self.table.lock().neighbors().cloned().collect();
+        let outgoing = neighbors
+            .into_iter()
+            .map(|peer_id| Ok((peer_id.clone(), self.discovery_message(&peer_id, &request)?)))
+            .collect::<Result<_>>()?;
+        Ok((answer, outgoing))
+    }
+
+    /// Handle a message from direct peer `from`, returning the messages to
+    /// send on. Messages for this node are published on the bus.
+    pub fn process(&self, from: &str, message: ACPMessage) -> Outgoing {
+        let result = match message.message_type {
+            MessageType::RouteDiscovery => serde_json::from_slice(&message.payload)
+                .map_err(|e| ACPError::Message(format!("Malformed route discovery: {}", e)))
+                .and_then(|discovery| self.on_discovery(from, discovery)),
+            MessageType::RoutedData => serde_json::from_slice(&message.payload)
+                .map_err(|e| ACPError::Message(format!("Malformed routed envelope: {}", e)))
+                .and_then(|envelope| self.on_routed(envelope)),
+            _ => Ok(self.receive(message).map(|ack| (from.to_string(), ack)).into_iter().collect()),
+        };
+        result.unwrap_or_else(|e| {
+            tracing::warn!("Dropping message from {}: {}", from, e);
+            Vec::new()
+        })
+    }
+
+    fn on_discovery(&self, from: &str, discovery: RouteDiscovery) -> Result<Outgoing> {
+        match discovery {
+            RouteDiscovery::Request { id, target, mut path } => {
+                if path.iter().any(|hop| hop == &self.local_id) || !self.mark_seen(id) {
+                    return Ok(Vec::new());
+                }
+                // The way back to every node the request crossed
+                let back: Vec<String> = path.iter().rev().cloned().collect();
+                if back.first().map(String::as_str) == Some(from) {
+                    self.learn_route(&back);
+                }
+
+                path.push(self.local_id.clone());
+                if target == self.local_id {
+                    let reply = RouteDiscovery::Reply { id, path };
+                    return Ok(vec![(from.to_string(), self.discovery_message(from, &reply)?)]);
+                }
+                if path.len() > self.config.max_hops {
+                    self.stats.lock().hop_limit_dropped += 1;
+                    return Ok(Vec::new());
+                }
+
+                let request = RouteDiscovery::Request { id, target, path: path.clone() };
+                let neighbors: Vec<String> = self.table.lock().neighbors().filter(|peer| !path.contains(peer)).cloned().collect();
+                neighbors
+                    .into_iter()
+                    .map(|peer_id| Ok((peer_id.clone(), self.discovery_message(&peer_id, &request)?)))
+                    .collect()
+            }
+            RouteDiscovery::Reply { id, path } => {
+                let Some(position) = path.iter().position(|hop| hop == &self.local_id) else {
+                    return Ok(Vec::new());
+                };
+                let ahead = &path[position + 1..];
+                if !self.learn_route(ahead) {
+                    return Ok(Vec::new());
+                }
+                if position == 0 {
+                    let target = path.last().cloned().unwrap_or_default();
+                    if let Some(route) = self.best_route(&target) {
+                        for waiter in self.pending.lock().remove(&target).unwrap_or_default() {
+                            let _ = waiter.send(route.clone());
+                        }
+                    }
+                    return Ok(Vec::new());
+                }
+                let previous = &path[position - 1];
+                let reply = RouteDiscovery::Reply { id, path: path.clone() };
+                Ok(vec![(previous.clone(), self.discovery_message(previous, &reply)?)])
+            }
+        }
+    }
+
+    fn on_routed(&self, mut envelope: RoutedEnvelope) -> Result<Outgoing> {
+        if envelope.destination == self.local_id {
+            let previous_hop = envelope.routing_path.last().cloned().unwrap_or_default();
+            return match self.receive(envelope.message) {
+                Some(ack) => self.route_back(ack, &previous_hop),
+                None => Ok(Vec::new()),
+            };
+        }
+        if envelope.routing_path.contains(&self.local_id) {
+            tracing::debug!("Dropping looped message {} for {}", envelope.message.id, envelope.destination);
+            self.stats.lock().loops_dropped += 1;
+            return Ok(Vec::new());
+        }
+        if envelope.routing_path.len() >= self.config.max_hops {
+            self.stats.lock().hop_limit_dropped += 1;
+            return Ok(Vec::new());
+        }
+
+        envelope.routing_path.push(self.local_id.clone());
+        let route = self.table.lock().route_avoiding(&envelope.destination, &envelope.routing_path, Instant::now());
+        let Some(route) = route else {
+            tracing::debug!("No route onward to {}", envelope.destination);
+            self.stats.lock().unroutable += 1;
+            return Ok(Vec::new());
+        };
+        self.stats.lock().forwarded += 1;
+        Ok(vec![(route.next_hop.clone(), self.envelope_message(&route.next_hop, &envelope)?)])
+    }
+
+    /// Take a message addressed to this node, returning the
+    /// acknowledgment to send if it asked for one
+    fn receive(&self, message: ACPMessage) -> Option<ACPMessage> {
+        if message.message_type == MessageType::Ack {
+            match message.acked_id() {
+                Ok(id) => match self.with_outbox(|outbox| outbox.ack(&id)) {
+                    Ok(Some(receipt)) => self.report(receipt),
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to settle message {}: {}", id, e),
+                },
+                Err(e) => tracing::debug!("Ignoring acknowledgment from {}: {}", message.from, e),
+            }
+            return None;
+        }
+        let ack = message.is_reliable().then(|| ACPMessage::ack(self.local_id.clone(), &message));
+        self.deliver(message);
+        ack
+    }
+
+    /// Send `message` back toward its destination, through `fallback` when
+    /// no route to it is known
+    fn route_back(&self, message: ACPMessage, fallback: &str) -> Result<Outgoing> {
+        let destination = message.to.clone().unwrap_or_default();
+        let next_hop = self.best_route(&destination).map(|route| route.next_hop).unwrap_or_else(|| fallback.to_string());
+        let envelope = RoutedEnvelope { destination, routing_path: vec![self.local_id.clone()], message };
+        Ok(vec![(next_hop.clone(), self.envelope_message(&next_hop, &envelope)?)])
+    }
+
+    /// Publish a message addressed to this node on the node's bus
+    fn deliver(&self, message: ACPMessage) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.events.publish(ACPEvent::MessageReceived { message });
+    }
+
+    /// Remember a request id, returning false if it was already seen
+    fn mark_seen(&self, id: Uuid) -> bool {
+        let mut seen = self.seen_requests.lock();
+        let (ids, order) = &mut *seen;
+        if !ids.insert(id) {
+            return false;
+        }
+        order.push_back(id);
+        if order.len() > SEEN_REQUESTS {
+            if let Some(oldest) = order.pop_front() {
+                ids.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn discovery_message(&self, peer_id: &str, discovery: &RouteDiscovery) -> Result<ACPMessage> {
+        let payload = serde_json::to_vec(discovery).map_err(|e| ACPError::Message(e.to_string()))?;
+        Ok(ACPMessage::new(MessageType::RouteDiscovery, self.local_id.clone(), Some(peer_id.to_string()), payload))
+    }
+
+    fn envelope_message(&self, peer_id: &str, envelope: &RoutedEnvelope) -> Result<ACPMessage> {
+        let payload = serde_json::to_vec(envelope).map_err(|e| ACPError::Message(e.to_string()))?;
+        Ok(ACPMessage::new(MessageType::RoutedData, self.local_id.clone(), Some(peer_id.to_string()), payload))
+    }
+
+    pub fn stats(&self) -> RoutingStats {
+        self.stats.lock().clone()
+    }
+
+    pub fn messages_sent(&self) -> u64 {
+        self.messages_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn messages_received(&self) -> u64 {
+        self.messages_received.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outbox::DeliveryStatus;
+
+    /// Routers linked as `links`, exchanging messages in memory
+    fn network(nodes: &[&str], links: &[(&str, &str)]) -> HashMap<String, MessageRouter> {
+        let routers: HashMap<String, MessageRouter> = nodes.iter().map(|id| (id.to_string(), MessageRouter::new(*id))).collect();
+        for (a, b) in links {
+            routers[*a].peer_connected(b);
+            routers[*b].peer_connected(a);
+        }
+        routers
+    }
+
+    /// Deliver messages in send order until the network is quiet
+    fn pump(routers: &HashMap<String, MessageRouter>, from: &str, outgoing: Vec<(String, ACPMessage)>) {
+        let mut queue: VecDeque<(String, String, ACPMessage)> = outgoing.into_iter().map(|(to, message)| (from.to_string(), to, message)).collect();
+        while let Some((from, to, message)) = queue.pop_front() {
+            for (next, message) in routers[&to].process(&from, message) {
+                queue.push_back((to.clone(), next, message));
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_hop_discovery_and_delivery() {
+        // a - b - c - d, with a shortcut b - d
+        let routers = network(&["a", "b", "c", "d"], &[("a", "b"), ("b", "c"), ("c", "d"), ("b", "d")]);
+        let mut delivered = routers["d"].events.subscribe_deliveries();
+
+        let (mut answer, outgoing) = routers["a"].begin_discovery("d").unwrap();
+        pump(&routers, "a", outgoing);
+        let route = answer.try_recv().unwrap();
+        assert_eq!(route.next_hop, "b");
+        assert_eq!(routers["a"].best_route("d").unwrap().hops(), 2);
+        // The target learned its way back to the origin
+        assert_eq!(routers["d"].best_route("a").unwrap().next_hop, "b");
+
+        let (next_hop, outgoing) = routers["a"].originate(ACPMessage::new(MessageType::Heartbeat, "a".to_string(), Some("d".to_string()), Vec::new())).unwrap();
+        pump(&routers, "a", vec![(next_hop, outgoing)]);
+        assert!(matches!(delivered.try_recv().unwrap(), ACPEvent::MessageReceived { message } if message.from == "a"));
+        assert!(delivered.try_recv().is_err());
+        assert_eq!(routers["b"].stats().forwarded, 1);
+    }
+
+    #[test]
+    fn test_reliable_message_is_acknowledged_across_hops() {
+        let routers = network(&["a", "b", "c"], &[("a", "b"), ("b", "c")]);
+        let dir = tempfile::tempdir().unwrap();
+        let config = OutboxConfig { path: dir.path().join("outbox.log"), sync_writes: false, ..Default::default() };
+        *routers["a"].outbox.lock() = Some(Outbox::open(config).unwrap());
+        let mut deliveries = routers["a"].events.subscribe_deliveries();
+
+        let (_, outgoing) = routers["a"].begin_discovery("c").unwrap();
+        pump(&routers, "a", outgoing);
+        let mut message = ACPMessage::new(MessageType::TransactionRequest, "a".to_string(), Some("c".to_string()), vec![7]);
+        message.set_reliable();
+        routers["a"].with_outbox(|outbox| outbox.push(&message)).unwrap();
+        let first_hop = routers["a"].originate(message.clone()).unwrap();
+        pump(&routers, "a", vec![first_hop]);
+
+        let mut receipts = Vec::new();
+        while let Ok(event) = deliveries.try_recv() {
+            if let ACPEvent::DeliveryReceipt(receipt) = event {
+                receipts.push(receipt);
+            }
+        }
+        assert_eq!(receipts.len(), 1);
+        assert_eq!((receipts[0].message_id, receipts[0].status), (message.id, DeliveryStatus::Delivered));
+        assert!(routers["a"].due_retransmissions().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_send_leaves_nothing_behind() {
+        let config = crate::ACPConfig { listen_address: "127.0.0.1:0".to_string(), ..crate::ACPConfig::default() };
+        let network = P2PNetwork::new(&config).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = OutboxConfig { path: dir.path().join("outbox.log"), sync_writes: false, ..Default::default() };
+        let router = MessageRouter::with_config("a", RoutingConfig { outbox: Some(outbox), ..RoutingConfig::default() });
+        router.start().await.unwrap();
+        router.peer_connected("b");
+
+        // Nobody answers the route request; cancelling ends the wait early
+        let cancel = CancellationToken::new();
+        let cancel_soon = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        };
+        let started = Instant::now();
+        let (discovered, ()) = tokio::join!(router.discover_route_cancellable(&network, "c", &cancel), cancel_soon);
+        assert!(matches!(discovered, Err(ACPError::Cancelled(_))));
+        assert!(started.elapsed() < router.config.discovery_timeout);
+        assert_eq!(router.stats().unroutable, 0);
+
+        // A reliable message cancelled before it reached the outbox is never retransmitted
+        let mut message = ACPMessage::new(MessageType::TransactionRequest, "a".to_string(), Some("b".to_string()), vec![7]);
+        message.set_reliable();
+        assert!(matches!(router.send_cancellable(&network, message, &cancel).await, Err(ACPError::Cancelled(_))));
+        assert_eq!(router.with_outbox(|outbox| Ok(outbox.pending_count())).unwrap(), 0);
+        assert_eq!(router.messages_sent(), 0);
+    }
+
+    #[test]
+    fn test_looping_envelope_is_dropped() {
+        let routers = network(&["a", "b"], &[("a", "b")]);
+        let envelope = RoutedEnvelope {
+            destination: "z".to_string(),
+            routing_path: vec!["x".to_string(), "b".to_string(), "a".to_string()],
+            message: ACPMessage::heartbeat("x".to_string()),
+        };
+        let message = routers["a"].envelope_message("b", &envelope).unwrap();
+        assert!(routers["b"].process("a", message).is_empty());
+        assert_eq!(routers["b"].stats().loops_dropped, 1);
+    }
+
+    #[test]
+    fn test_dispatch_prefers_transaction_responses_over_heartbeats() {
+        let router = MessageRouter::new("a");
+        let mut delivered = router.events.subscribe_deliveries();
+
+        for message_type in [MessageType::Heartbeat, MessageType::TransactionResponse] {
+            router.schedule("b", ACPMessage::new(message_type, "b".to_string(), Some("a".to_string()), Vec::new())).unwrap();
+        }
+        for _ in 0..2 {
+            assert!(router.dispatch_next().is_empty());
+        }
+
+        let mut order = Vec::new();
+        while let Ok(ACPEvent::MessageReceived { message }) = delivered.try_recv() {
+            order.push(message.message_type);
+        }
+        assert_eq!(order, vec![MessageType::TransactionResponse, MessageType::Heartbeat]);
+        assert_eq!(router.dispatched_by_priority(), PriorityCounts { low: 1, high: 1, ..Default::default() });
+    }
+
+    #[test]
+    fn test_disconnect_invalidates_cached_routes() {
+        let mut table = RoutingTable::new("a", Duration::from_secs(60));
+        table.add_neighbor("b");
+        table.add_neighbor("c");
+        let now = Instant::now();
+        assert!(table.learn_path(&["b".to_string(), "x".to_string(), "d".to_string()], now));
+        assert!(table.learn_path(&["c".to_string(), "y".to_string(), "z".to_string(), "d".to_string()], now));
+        assert!(!table.learn_path(&["b".to_string(), "a".to_string()], now));
+
+        assert_eq!(table.best_route_at("d", now).unwrap().next_hop, "b");
+        assert_eq!(table.cached(), 1);
+
+        assert_eq!(table.remove_neighbor("b"), vec!["b".to_string(), "x".to_string()]);
+        assert_eq!(table.cached(), 0);
+        assert_eq!(table.best_route_at("d", now).unwrap().next_hop, "c");
+        assert!(table.best_route_at("x", now).is_none());
+    }
+}
@@ -0,0 +1,577 @@
+//! Security Module
+//!
+//! Node identity and peer authentication. Every node holds two keys: an
+//! Ed25519 signing key, which is its long-term identity and signs the
+//! messages it originates, and an X25519 static keypair for Noise
+//! handshakes. Peer channels run `Noise_XX_25519_ChaChaPoly_BLAKE2s`; inside
+//! the handshake each side sends its node id, its Ed25519 verifying key, and
+//! a signature by that key over its Noise static key. A peer is accepted only
+//! if the signature holds and, when its node id has a pinned key, the key
+//! matches, so both ends of every channel are authenticated by node keys
+//! rather than by Noise keys alone.
+//!
+//! Signed messages carry a random nonce header, covered by the signature.
+//! `verify_message` rejects messages whose timestamp is outside the replay
+//! window and any (sender, nonce) pair it has already accepted within that
+//! window, so a captured message cannot be delivered twice. Pairs are only
+//! forgotten once they fall out of the window; while the cache is full of
+//! live pairs, new messages are refused rather than reopening old ones.
+//!
+//! A `PeerScorer` keeps the long-term record behind bans. It is charged with
+//! the same violations as the misbehavior detector, but decays over hours
+//! rather than minutes, so only peers that misbehave persistently cross its
+//! threshold; those are banned for good and blacklisted by discovery. Its
+//! state serializes, so scores and bans survive restarts.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use crate::messaging::ACPMessage;
+use crate::misbehavior::Violation;
+use crate::{ACPError, Result};
+
+/// Noise handshake pattern used for every peer channel
+pub const NOISE_PATTERN: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+
+/// Authenticated identity of the node at the other end of a channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerIdentity {
+    pub node_id: String,
+    pub verifying_key: VerifyingKey,
+    pub static_key: Vec<u8>,              // Peer's Noise static public key
+}
+
+/// Header carrying the nonce a signed message is deduplicated by
+pub const NONCE_HEADER: &str = "nonce";
+
+/// Identity proof carried inside the Noise handshake
+#[derive(Serialize, Deserialize)]
+struct HandshakeIdentity {
+    node_id: String,
+    verifying_key: [u8; 32],
+    signature: Vec<u8>,                   // Over the sender's Noise static key
+}
+
+/// Signs outgoing messages and checks signatures on received ones
+pub trait MessageAuthentication {
+    /// Sign a message as this node
+    fn sign_message(&self, message: ACPMessage) -> Result<ACPMessage>;
+
+    /// Check a message's signature against its sender's key, and that it
+    /// is fresh and not a replay
+    fn verify_message(&self, message: &ACPMessage, key: &VerifyingKey) -> Result<()>;
+}
+
+/// How long signed messages stay acceptable and how many are remembered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    pub window: Duration,                 // Largest accepted clock difference either way
+    pub max_entries: usize,               // Live nonces remembered; new messages are refused past this
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(5 * 60),
+            max_entries: 100_000,
+        }
+    }
+}
+
+/// Why a (sender, nonce) pair was not accepted
+#[derive(Debug, PartialEq, Eq)]
+enum ReplayRejection {
+    Seen,
+    Full,
+}
+
+/// (sender, nonce) pairs accepted within the replay window
+#[derive(Default)]
+struct ReplayCache {
+    seen: HashMap<(String, String), DateTime<Utc>>,
+    order: VecDeque<(DateTime<Utc>, (String, String))>,  // By time accepted
+}
+
+impl ReplayCache {
+    /// Remember a pair, refusing it if it was already seen or if the cache
+    /// is full of pairs still inside the window
+    fn insert(&mut self, key: (String, String), now: DateTime<Utc>, config: &ReplayConfig) -> std::result::Result<(), ReplayRejection> {
+        // Entries older than twice the window cover every timestamp still accepted
+        let horizon = now - chrono::Duration::from_std(config.window * 2).unwrap_or_else(|_| chrono::Duration::zero());
+        while let Some((accepted, _)) = self.order.front() {
+            if *accepted >= horizon {
+                break;
+            }
+            if let Some((_, expired)) = self.order.pop_front() {
+                self.seen.remove(&expired);
+            }
+        }
+        if self.seen.contains_key(&key) {
+            return Err(ReplayRejection::Seen);
+        }
+        if self.order.len() >= config.max_entries {
+            return Err(ReplayRejection::Full);
+        }
+        self.seen.insert(key.clone(), now);
+        self.order.push_back((now, key));
+        Ok(())
+    }
+}
+
+/// Node keys and peer verification
+pub struct SecurityManager {
+    node_id: String,
+    signing_key: SigningKey,
+    static_keypair: snow::Keypair,
+    pinned: RwLock<HashMap<String, VerifyingKey>>,  // Node id -> expected identity key
+    pinned_only: bool,                    // Refuse peers without a pinned key
+    replay: ReplayConfig,
+    seen_nonces: Mutex<ReplayCache>,
+}
+
+impl Default for SecurityManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecurityManager {
+    /// Fresh identity whose node id is its hex-encoded verifying key
+    pub fn new() -> Self {
+        let signing_key = SigningKey::from_bytes(&rand::random());
+        let node_id = hex(signing_key.verifying_key().as_bytes());
+        Self::with_identity(node_id, signing_key)
+    }
+
+    /// Fresh keys for a node with a configured id
+    pub fn for_node(node_id: impl Into<String>) -> Self {
+        Self::with_identity(node_id.into(), SigningKey::from_bytes(&rand::random()))
+    }
+
+    /// Existing identity key with a fresh Noise static keypair
+    pub fn with_identity(node_id: String, signing_key: SigningKey) -> Self {
+        let static_keypair = snow::Builder::new(noise_params())
+            .generate_keypair()
+            .expect("the default resolver supports X25519");
+        Self {
+            node_id,
+            signing_key,
+            static_keypair,
+            pinned: RwLock::new(HashMap::new()),
+            pinned_only: false,
+            replay: ReplayConfig::default(),
+            seen_nonces: Mutex::new(ReplayCache::default()),
+        }
+    }
+
+    /// Replay window and dedup cache size for received messages
+    pub fn with_replay_config(mut self, replay: ReplayConfig) -> Self {
+        self.replay = replay;
+        self
+    }
+
+    /// Only accept peers whose node id has a pinned key
+    pub fn require_pinned_peers(mut self) -> Self {
+        self.pinned_only = true;
+        self
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    /// Noise static keypair used in handshakes
+    pub fn static_keypair(&self) -> &snow::Keypair {
+        &self.static_keypair
+    }
+
+    pub fn static_public_key(&self) -> &[u8] {
+        &self.static_keypair.public
+    }
+
+    /// Expect `node_id` to authenticate with `key` from now on
+    pub fn pin_peer(&self, node_id: impl Into<String>, key: VerifyingKey) {
+        self.pinned.write().insert(node_id.into(), key);
+    }
+
+    /// Key pinned for `node_id`, if any
+    pub fn pinned_key(&self, node_id: &str) -> Option<VerifyingKey> {
+        self.pinned.read().get(node_id).copied()
+    }
+
+    /// Check a message's signature alone, without the replay checks
+    pub fn verify_signature(&self, message: &ACPMessage, key: &VerifyingKey) -> Result<()> {
+        let signature = message
+            .signature
+            .as_deref()
+            .ok_or_else(|| ACPError::Security(format!("Message {} is unsigned", message.id)))?;
+        let signature = Signature::from_slice(signature)
+            .map_err(|_| ACPError::Security(format!("Malformed signature on message {}", message.id)))?;
+        key.verify(&signing_bytes(message)?, &signature)
+            .map_err(|_| ACPError::Security(format!("Bad signature on message {}", message.id)))
+    }
+
+    /// Accept a message's nonce once, and only while its timestamp is
+    /// within the replay window of `now`
+    pub fn check_replay(&self, message: &ACPMessage, now: DateTime<Utc>) -> Result<()> {
+        let nonce = message
+            .get_header(NONCE_HEADER)
+            .ok_or_else(|| ACPError::Security(format!("Message {} has no nonce", message.id)))?;
+        let skew = (now - message.timestamp).abs().to_std().unwrap_or(Duration::MAX);
+        if skew > self.replay.window {
+            return Err(ACPError::Security(format!(
+                "Message {} is {}s outside the replay window",
+                message.id,
+                (skew - self.replay.window).as_secs()
+            )));
+        }
+        match self.seen_nonces.lock().insert((message.from.clone(), nonce.clone()), now, &self.replay) {
+            Ok(()) => Ok(()),
+            Err(ReplayRejection::Seen) => Err(ACPError::Security(format!("Replayed message {} from {}", message.id, message.from))),
+            Err(ReplayRejection::Full) => Err(ACPError::Security(format!(
+                "Replay cache full, refusing message {} from {}",
+                message.id, message.from
+            ))),
+        }
+    }
+
+    /// Sign arbitrary bytes with this node's identity key
+    pub fn sign(&self, bytes: &[u8]) -> Signature {
+        self.signing_key.sign(bytes)
+    }
+
+    /// Handshake state for dialing a peer
+    pub fn initiator(&self) -> Result<snow::HandshakeState> {
+        self.handshake_builder().build_initiator().map_err(noise_error)
+    }
+
+    /// Handshake state for answering a peer
+    pub fn responder(&self) -> Result<snow::HandshakeState> {
+        self.handshake_builder().build_responder().map_err(noise_error)
+    }
+
+    /// This node's identity proof for the handshake
+    pub fn identity_payload(&self) -> Result<Vec<u8>> {
+        let identity = HandshakeIdentity {
+            node_id: self.node_id.clone(),
+            verifying_key: self.verifying_key().to_bytes(),
+            signature: self.signing_key.sign(&self.static_keypair.public).to_bytes().to_vec(),
+        };
+        bincode::serialize(&identity).map_err(|e| ACPError::Security(format!("Identity encoding failed: {}", e)))
+    }
+
+    /// Check a peer's identity proof against the Noise static key it handshook with
+    pub fn verify_peer(&self, payload: &[u8], remote_static: &[u8]) -> Result<PeerIdentity> {
+        let identity: HandshakeIdentity = bincode::deserialize(payload)
+            .map_err(|e| ACPError::Security(format!("Malformed peer identity: {}", e)))?;
+        let verifying_key = VerifyingKey::from_bytes(&identity.verifying_key)
+            .map_err(|_| ACPError::Security(format!("Invalid identity key for {}", identity.node_id)))?;
+        let signature = Signature::from_slice(&identity.signature)
+            .map_err(|_| ACPError::Security(format!("Invalid identity signature from {}", identity.node_id)))?;
+        verifying_key.verify(remote_static, &signature).map_err(|_| {
+            ACPError::Security(format!("{} did not sign its handshake key", identity.node_id))
+        })?;
+
+        match self.pinned.read().get(&identity.node_id) {
+            Some(pinned) if *pinned != verifying_key => {
+                return Err(ACPError::Security(format!("Identity key mismatch for {}", identity.node_id)));
+            }
+            None if self.pinned_only => {
+                return Err(ACPError::Security(format!("Unknown peer {}", identity.node_id)));
+            }
+            _ => {}
+        }
+
+        Ok(PeerIdentity {
+            node_id: identity.node_id,
+            verifying_key,
+            static_key: remote_static.to_vec(),
+        })
+    }
+
+    fn handshake_builder(&self) -> snow::Builder<'_> {
+        snow::Builder::new(noise_params()).local_private_key(&self.static_keypair.private)
+    }
+}
+
+impl MessageAuthentication for SecurityManager {
+    fn sign_message(&self, mut message: ACPMessage) -> Result<ACPMessage> {
+        message.add_header(NONCE_HEADER, hex(&rand::random::<[u8; 16]>()));
+        let signature = self.signing_key.sign(&signing_bytes(&message)?);
+        message.set_signature(signature.to_bytes().to_vec());
+        Ok(message)
+    }
+
+    fn verify_message(&self, message: &ACPMessage, key: &VerifyingKey) -> Result<()> {
+        // A forged message must not use up the nonce of the real one
+        self.verify_signature(message, key)?;
+        self.check_replay(message, Utc::now())
+    }
+}
+
+/// Canonical bytes a message signature covers: everything but the signature,
+/// with headers in key order
+fn signing_bytes(message: &ACPMessage) -> Result<Vec<u8>> {
+    let headers: BTreeMap<_, _> = message.headers.iter().collect();
+    bincode::serialize(&(
+        &message.id,
+        &message.message_type,
+        &message.from,
+        &message.to,
+        &message.timestamp,
+        &message.version,
+        &message.payload,
+        headers,
+    ))
+    .map_err(|e| ACPError::Security(format!("Message encoding failed: {}", e)))
+}
+
+fn noise_params() -> snow::params::NoiseParams {
+    NOISE_PATTERN.parse().expect("NOISE_PATTERN is a valid Noise pattern")
+}
+
+fn noise_error(error: snow::Error) -> ACPError {
+    ACPError::Security(format!("Noise error: {}", error))
+}
+
+/// Ban settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerScorerConfig {
+    pub ban_threshold: f64,               // Score that gets a peer banned
+    pub score_half_life: Duration,
+}
+
+impl Default for PeerScorerConfig {
+    fn default() -> Self {
+        Self {
+            ban_threshold: 500.0,
+            score_half_life: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// Long-term violation record of one peer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoredPeer {
+    pub score: f64,
+    pub updated_at: DateTime<Utc>,
+    pub violations: BTreeMap<Violation, u32>,
+}
+
+impl ScoredPeer {
+    fn decay(&mut self, half_life: Duration, now: DateTime<Utc>) {
+        let elapsed = (now - self.updated_at).to_std().unwrap_or_default().as_secs_f64();
+        self.score *= 0.5f64.powf(elapsed / half_life.as_secs_f64().max(f64::EPSILON));
+        self.updated_at = now;
+    }
+}
+
+/// Everything a `PeerScorer` persists
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerScorerState {
+    pub peers: BTreeMap<String, ScoredPeer>,
+    pub banned: BTreeMap<String, DateTime<Utc>>,  // Peer -> when it was banned
+}
+
+/// Per-peer violation scoring with permanent bans
+pub struct PeerScorer {
+    config: PeerScorerConfig,
+    state: RwLock<PeerScorerState>,
+    new_bans: Mutex<Vec<String>>,         // Bans not yet applied by discovery
+}
+
+impl PeerScorer {
+    pub fn new(config: PeerScorerConfig) -> Self {
+        Self {
+            config,
+            state: RwLock::new(PeerScorerState::default()),
+            new_bans: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn config(&self) -> &PeerScorerConfig {
+        &self.config
+    }
+
+    /// Charge a violation to a peer, returning whether it was just banned
+    pub fn record(&self, peer_id: &str, violation: Violation, now: DateTime<Utc>) -> bool {
+        let mut state = self.state.write();
+        if state.banned.contains_key(peer_id) {
+            return false;
+        }
+        let peer = state.peers.entry(peer_id.to_string()).or_insert_with(|| ScoredPeer {
+            score: 0.0,
+            updated_at: now,
+            violations: BTreeMap::new(),
+        });
+        peer.decay(self.config.score_half_life, now);
+        peer.score += violation.penalty();
+        *peer.violations.entry(violation).or_default() += 1;
+        if peer.score < self.config.ban_threshold {
+            return false;
+        }
+        tracing::warn!("Banning {} (long-term score {:.1})", peer_id, peer.score);
+        state.banned.insert(peer_id.to_string(), now);
+        self.new_bans.lock().push(peer_id.to_string());
+        true
+    }
+
+    /// Ban a peer regardless of its score
+    pub fn ban(&self, peer_id: &str, now: DateTime<Utc>) {
+        if self.state.write().banned.insert(peer_id.to_string(), now).is_none() {
+            self.new_bans.lock().push(peer_id.to_string());
+        }
+    }
+
+    /// Lift a ban and forget the peer's record
+    pub fn unban(&self, peer_id: &str) {
+        let mut state = self.state.write();
+        state.banned.remove(peer_id);
+        state.peers.remove(peer_id);
+    }
+
+    /// Long-term score of a peer, decayed to `now`
+    pub fn score(&self, peer_id: &str, now: DateTime<Utc>) -> f64 {
+        self.state.write().peers.get_mut(peer_id).map_or(0.0, |peer| {
+            peer.decay(self.config.score_half_life, now);
+            peer.score
+        })
+    }
+
+    pub fn is_banned(&self, peer_id: &str) -> bool {
+        self.state.read().banned.contains_key(peer_id)
+    }
+
+    pub fn banned(&self) -> Vec<String> {
+        self.state.read().banned.keys().cloned().collect()
+    }
+
+    /// Bans made since the last call, for discovery to blacklist
+    pub fn take_new_bans(&self) -> Vec<String> {
+        std::mem::take(&mut *self.new_bans.lock())
+    }
+
+    /// Copy of the scores and bans, for persisting
+    pub fn snapshot(&self) -> PeerScorerState {
+        self.state.read().clone()
+    }
+
+    /// Resume from a persisted snapshot, replacing the current state
+    pub fn restore(&self, state: PeerScorerState) {
+        *self.state.write() = state;
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging::MessageType;
+
+    #[test]
+    fn test_messages_are_signed_and_verified() {
+        let security = SecurityManager::for_node("alice");
+        let mut message = ACPMessage::new(MessageType::Heartbeat, "alice".to_string(), None, vec![1, 2, 3]);
+        message.add_header("a", "1");
+        message.add_header("b", "2");
+
+        let signed = security.sign_message(message).unwrap();
+        let received = ACPMessage::deserialize(&signed.serialize().unwrap()).unwrap();
+        security.verify_message(&received, &security.verifying_key()).unwrap();
+
+        let mut tampered = received.clone();
+        tampered.payload.push(4);
+        assert!(security.verify_message(&tampered, &security.verifying_key()).is_err());
+        let other = SecurityManager::new();
+        assert!(other.verify_message(&received, &other.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_replayed_and_stale_messages_are_rejected() {
+        let alice = SecurityManager::for_node("alice");
+        let bob = SecurityManager::for_node("bob").with_replay_config(ReplayConfig { window: Duration::from_secs(60), max_entries: 2 });
+        let signed = alice.sign_message(ACPMessage::new(MessageType::Heartbeat, "alice".to_string(), None, vec![1])).unwrap();
+        assert!(signed.get_header(NONCE_HEADER).is_some());
+
+        bob.verify_message(&signed, &alice.verifying_key()).unwrap();
+        assert!(bob.verify_message(&signed, &alice.verifying_key()).is_err());
+
+        let mut stale = ACPMessage::new(MessageType::Heartbeat, "alice".to_string(), None, vec![2]);
+        stale.timestamp = Utc::now() - chrono::Duration::minutes(2);
+        let stale = alice.sign_message(stale).unwrap();
+        assert!(bob.verify_message(&stale, &alice.verifying_key()).is_err());
+        assert!(bob.verify_signature(&stale, &alice.verifying_key()).is_ok());
+
+        // A full cache refuses new messages rather than forgetting live pairs
+        let now = Utc::now();
+        let fresh = || alice.sign_message(ACPMessage::new(MessageType::Heartbeat, "alice".to_string(), None, vec![3])).unwrap();
+        bob.check_replay(&fresh(), now).unwrap();
+        assert!(bob.check_replay(&fresh(), now).is_err());
+        assert!(bob.check_replay(&signed, now).is_err());
+
+        // Pairs leave the cache only once they are out of the window
+        let later = now + chrono::Duration::minutes(3);
+        let mut message = ACPMessage::new(MessageType::Heartbeat, "alice".to_string(), None, vec![4]);
+        message.timestamp = later;
+        bob.check_replay(&alice.sign_message(message).unwrap(), later).unwrap();
+    }
+
+    #[test]
+    fn test_peer_identity_must_sign_handshake_key_and_match_pin() {
+        let alice = SecurityManager::for_node("alice");
+        let bob = SecurityManager::for_node("bob");
+        let payload = bob.identity_payload().unwrap();
+
+        let peer = alice.verify_peer(&payload, bob.static_public_key()).unwrap();
+        assert_eq!(peer.node_id, "bob");
+        assert_eq!(peer.verifying_key, bob.verifying_key());
+
+        // Replaying bob's proof over another Noise key fails
+        assert!(alice.verify_peer(&payload, alice.static_public_key()).is_err());
+
+        alice.pin_peer("bob", SecurityManager::new().verifying_key());
+        assert!(alice.verify_peer(&payload, bob.static_public_key()).is_err());
+        alice.pin_peer("bob", bob.verifying_key());
+        assert!(alice.verify_peer(&payload, bob.static_public_key()).is_ok());
+
+        let strict = SecurityManager::for_node("carol").require_pinned_peers();
+        assert!(strict.verify_peer(&payload, bob.static_public_key()).is_err());
+    }
+
+    #[test]
+    fn test_persistent_offenders_are_banned_and_bans_persist() {
+        let scorer = PeerScorer::new(PeerScorerConfig { ban_threshold: 90.0, score_half_life: Duration::from_secs(3600) });
+        let start = Utc::now();
+
+        // An occasional fault decays away
+        scorer.record("honest", Violation::InvalidSignature, start);
+        let later = start + chrono::Duration::hours(5);
+        scorer.record("honest", Violation::InvalidSignature, later);
+        assert!(scorer.score("honest", later) < 45.0);
+
+        assert!(!scorer.record("spammer", Violation::GossipFlood, start));
+        assert!(!scorer.record("spammer", Violation::InvalidSignature, start));
+        assert!(scorer.record("spammer", Violation::FakePeerRecord, start));
+        assert!(!scorer.record("spammer", Violation::FakePeerRecord, start));
+        assert_eq!(scorer.take_new_bans(), vec!["spammer".to_string()]);
+        assert!(scorer.take_new_bans().is_empty());
+
+        let restored = PeerScorer::new(scorer.config().clone());
+        let saved = serde_json::to_string(&scorer.snapshot()).unwrap();
+        restored.restore(serde_json::from_str(&saved).unwrap());
+        assert!(restored.is_banned("spammer") && !restored.is_banned("honest"));
+        assert_eq!(restored.snapshot().peers["spammer"].violations[&Violation::FakePeerRecord], 1);
+    }
+}
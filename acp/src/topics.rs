@@ -0,0 +1,151 @@
+//! Gossip Topics Module
+//!
+//! Topic-based publish/subscribe on top of gossip, in the style of
+//! gossipsub. Nodes announce the topics they subscribe to, and for each of
+//! its own topics a node keeps a mesh of up to `degree` subscribed peers.
+//! Topic messages travel only along meshes, so a node receives the
+//! categories of traffic it subscribed to and nothing else. Publishing to a
+//! topic we do not subscribe to sends to a few of its known subscribers.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::rng::NodeRng;
+
+/// Topic subscriptions and per-topic peer meshes for one node
+#[derive(Debug, Clone)]
+pub struct TopicMesh {
+    degree: usize,                        // Target mesh size per topic
+    subscriptions: HashSet<String>,
+    peer_topics: HashMap<String, BTreeSet<String>>,  // Topic -> subscribed peers
+    mesh: HashMap<String, BTreeSet<String>>,
+}
+
+impl TopicMesh {
+    pub fn new(degree: usize) -> Self {
+        Self {
+            degree,
+            subscriptions: HashSet::new(),
+            peer_topics: HashMap::new(),
+            mesh: HashMap::new(),
+        }
+    }
+
+    /// Subscribe to a topic, building its mesh from known subscribers.
+    /// Returns false if already subscribed.
+    pub fn subscribe(&mut self, topic: &str, rng: &NodeRng) -> bool {
+        if !self.subscriptions.insert(topic.to_string()) {
+            return false;
+        }
+        self.fill(topic, rng);
+        true
+    }
+
+    /// Leave a topic and its mesh. Returns false if not subscribed.
+    pub fn unsubscribe(&mut self, topic: &str) -> bool {
+        self.mesh.remove(topic);
+        self.subscriptions.remove(topic)
+    }
+
+    pub fn is_subscribed(&self, topic: &str) -> bool {
+        self.subscriptions.contains(topic)
+    }
+
+    /// Our subscriptions, sorted
+    pub fn subscriptions(&self) -> Vec<String> {
+        let mut topics: Vec<String> = self.subscriptions.iter().cloned().collect();
+        topics.sort();
+        topics
+    }
+
+    /// Record a peer's subscription, grafting it into our mesh if there is room
+    pub fn peer_subscribed(&mut self, peer_id: &str, topic: &str, rng: &NodeRng) {
+        self.peer_topics.entry(topic.to_string()).or_default().insert(peer_id.to_string());
+        if self.is_subscribed(topic) {
+            self.fill(topic, rng);
+        }
+    }
+
+    /// Record a peer leaving a topic, replacing it in our mesh
+    pub fn peer_unsubscribed(&mut self, peer_id: &str, topic: &str, rng: &NodeRng) {
+        if let Some(peers) = self.peer_topics.get_mut(topic) {
+            peers.remove(peer_id);
+        }
+        if self.mesh.get_mut(topic).is_some_and(|mesh| mesh.remove(peer_id)) {
+            self.fill(topic, rng);
+        }
+    }
+
+    /// Forget a disconnected peer, replacing it in every mesh it was in
+    pub fn remove_peer(&mut self, peer_id: &str, rng: &NodeRng) {
+        let topics: Vec<String> = self
+            .peer_topics
+            .iter()
+            .filter(|(_, peers)| peers.contains(peer_id))
+            .map(|(topic, _)| topic.clone())
+            .collect();
+        for topic in topics {
+            self.peer_unsubscribed(peer_id, &topic, rng);
+        }
+    }
+
+    /// Peers we relay a topic's messages to
+    pub fn mesh_peers(&self, topic: &str) -> Vec<String> {
+        self.mesh.get(topic).map(|mesh| mesh.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Peers known to subscribe to a topic
+    pub fn subscribers(&self, topic: &str) -> Vec<String> {
+        self.peer_topics.get(topic).map(|peers| peers.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn is_peer_subscribed(&self, peer_id: &str, topic: &str) -> bool {
+        self.peer_topics.get(topic).is_some_and(|peers| peers.contains(peer_id))
+    }
+
+    /// Graft random subscribers into a topic's mesh up to the target degree
+    fn fill(&mut self, topic: &str, rng: &NodeRng) {
+        use rand::seq::SliceRandom;
+
+        let mesh = self.mesh.entry(topic.to_string()).or_default();
+        let wanted = self.degree.saturating_sub(mesh.len());
+        let candidates: Vec<&String> = self
+            .peer_topics
+            .get(topic)
+            .map(|peers| peers.iter().filter(|peer| !mesh.contains(*peer)).collect())
+            .unwrap_or_default();
+        let grafted: Vec<String> = rng.with(|rng| candidates.choose_multiple(rng, wanted).map(|peer| (*peer).clone()).collect());
+        mesh.extend(grafted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mesh_stays_within_degree_and_heals() {
+        let rng = NodeRng::from_seed(3);
+        let mut topics = TopicMesh::new(2);
+        for peer in ["p1", "p2", "p3"] {
+            topics.peer_subscribed(peer, "market.prices", &rng);
+        }
+        topics.peer_subscribed("p4", "market.quotes", &rng);
+
+        // Subscribers are tracked before we join, the mesh is built on subscribe
+        assert!(topics.mesh_peers("market.prices").is_empty());
+        assert!(topics.subscribe("market.prices", &rng));
+        assert!(!topics.subscribe("market.prices", &rng));
+        let mesh = topics.mesh_peers("market.prices");
+        assert_eq!(mesh.len(), 2);
+        assert!(!mesh.contains(&"p4".to_string()));
+
+        // A lost mesh peer is replaced by the remaining subscriber
+        topics.remove_peer(&mesh[0], &rng);
+        assert_eq!(topics.mesh_peers("market.prices").len(), 2);
+        assert_eq!(topics.subscribers("market.prices").len(), 2);
+
+        assert!(topics.unsubscribe("market.prices"));
+        assert!(topics.mesh_peers("market.prices").is_empty());
+        assert!(topics.is_peer_subscribed("p4", "market.quotes"));
+    }
+}
@@ -0,0 +1,286 @@
+//! External Advisors
+//!
+//! An `Advisor` is an outside opinion consulted during negotiation, typically
+//! a model service run by the operator. It sees the same `DecisionContext`
+//! as the local heuristics and may suggest a price and how likely it would be
+//! to accept an offer. `WeightedAdvisor` blends those suggestions into the
+//! local decisions with a configurable weight, scaled by the advisor's own
+//! confidence, so an unsure or unreachable advisor changes nothing.
+//!
+//! With the `http` feature, `HttpAdvisor` POSTs the context as JSON to an
+//! endpoint and expects an `AdvisorOpinion` back.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::strategy::{CounterOfferResponse, NegotiationState};
+use crate::DecisionContext;
+
+/// An advisor's view of a negotiation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdvisorOpinion {
+    #[serde(default)]
+    pub suggested_price: Option<f64>,
+    #[serde(default)]
+    pub accept_probability: Option<f64>,  // Likelihood the advisor would accept the offer
+    pub confidence: f64,                  // 0.0 to 1.0
+    #[serde(default)]
+    pub rationale: Option<String>,
+}
+
+impl AdvisorOpinion {
+    /// Opinion that leaves local decisions unchanged
+    pub fn abstain() -> Self {
+        Self {
+            suggested_price: None,
+            accept_probability: None,
+            confidence: 0.0,
+            rationale: None,
+        }
+    }
+}
+
+/// Source of outside opinions during negotiation
+#[async_trait]
+pub trait Advisor: Send + Sync + fmt::Debug {
+    /// Advisor name for logs
+    fn name(&self) -> &str;
+
+    /// Opinion on a decision; abstain rather than fail when unavailable
+    async fn advise(&self, context: &DecisionContext) -> AdvisorOpinion;
+}
+
+/// Advisor that always gives the same opinion, for tests and fixed overrides
+#[derive(Debug, Clone)]
+pub struct StaticAdvisor(pub AdvisorOpinion);
+
+#[async_trait]
+impl Advisor for StaticAdvisor {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    async fn advise(&self, _context: &DecisionContext) -> AdvisorOpinion {
+        self.0.clone()
+    }
+}
+
+/// Advisor together with the weight its opinions carry
+#[derive(Debug, Clone)]
+pub struct WeightedAdvisor {
+    pub advisor: Arc<dyn Advisor>,
+    pub weight: f64,                      // 0.0 ignores the advisor, 1.0 defers to it
+}
+
+impl WeightedAdvisor {
+    pub fn new(advisor: Arc<dyn Advisor>, weight: f64) -> Self {
+        Self {
+            advisor,
+            weight: weight.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Weight of one opinion, after the advisor's own confidence
+    fn effective_weight(&self, opinion: &AdvisorOpinion) -> f64 {
+        let confidence = if opinion.confidence.is_finite() { opinion.confidence.clamp(0.0, 1.0) } else { 0.0 };
+        self.weight * confidence
+    }
+
+    /// Move a local price toward the advisor's suggestion
+    pub fn blend_price(&self, local: f64, opinion: &AdvisorOpinion) -> f64 {
+        match opinion.suggested_price.filter(|price| price.is_finite() && *price > 0.0) {
+            Some(suggested) => {
+                let weight = self.effective_weight(opinion);
+                local * (1.0 - weight) + suggested * weight
+            }
+            None => local,
+        }
+    }
+
+    /// Blend a local answer to a counter-offer with the advisor's opinion
+    ///
+    /// The local answer votes 1.0 for accepting and 0.0 otherwise; the offer
+    /// is accepted when the weighted vote reaches one half. A local rejection
+    /// stands, since it reflects limits the advisor does not see.
+    pub fn blend_response(
+        &self,
+        state: &NegotiationState,
+        offer: f64,
+        local: CounterOfferResponse,
+        opinion: &AdvisorOpinion,
+    ) -> CounterOfferResponse {
+        let accept = opinion.accept_probability.map(|probability| {
+            let weight = self.effective_weight(opinion);
+            let local_vote = if local == CounterOfferResponse::Accept { 1.0 } else { 0.0 };
+            local_vote * (1.0 - weight) + probability.clamp(0.0, 1.0) * weight >= 0.5
+        });
+
+        match (local, accept) {
+            (CounterOfferResponse::Reject, _) => CounterOfferResponse::Reject,
+            (CounterOfferResponse::Counter(_), Some(true)) => CounterOfferResponse::Accept,
+            (CounterOfferResponse::Counter(price), _) => {
+                CounterOfferResponse::Counter(self.blend_price(price, opinion).max(offer))
+            }
+            (CounterOfferResponse::Accept, Some(false)) if state.out_of_rounds() => CounterOfferResponse::Reject,
+            (CounterOfferResponse::Accept, Some(false)) => {
+                let ask = state.current_ask().unwrap_or(state.base_price);
+                CounterOfferResponse::Counter(self.blend_price(ask, opinion).max(offer))
+            }
+            (CounterOfferResponse::Accept, _) => CounterOfferResponse::Accept,
+        }
+    }
+
+    /// Consult the advisor and blend its suggestion into a local price
+    pub async fn advise_price(&self, context: &DecisionContext, local: f64) -> f64 {
+        self.blend_price(local, &self.advisor.advise(context).await)
+    }
+
+    /// Consult the advisor and blend its opinion into a local answer
+    pub async fn advise_response(
+        &self,
+        state: &NegotiationState,
+        offer: f64,
+        local: CounterOfferResponse,
+    ) -> CounterOfferResponse {
+        let opinion = self.advisor.advise(&state.context).await;
+        self.blend_response(state, offer, local, &opinion)
+    }
+}
+
+#[cfg(feature = "http")]
+pub use http::HttpAdvisor;
+
+#[cfg(feature = "http")]
+mod http {
+    use super::*;
+    use std::time::Duration;
+
+    /// How long to wait for an opinion before negotiating without one
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Advisor backed by an HTTP model service
+    ///
+    /// Each consultation POSTs the decision context as JSON to the endpoint
+    /// and reads an `AdvisorOpinion` from the response. Errors, timeouts, and
+    /// malformed replies count as abstentions.
+    #[derive(Debug, Clone)]
+    pub struct HttpAdvisor {
+        endpoint: String,
+        api_key: Option<String>,
+        client: reqwest::Client,
+    }
+
+    impl HttpAdvisor {
+        pub fn new(endpoint: impl Into<String>) -> Self {
+            Self::with_timeout(endpoint, DEFAULT_TIMEOUT)
+        }
+
+        pub fn with_timeout(endpoint: impl Into<String>, timeout: Duration) -> Self {
+            let client = reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default();
+            Self {
+                endpoint: endpoint.into(),
+                api_key: None,
+                client,
+            }
+        }
+
+        /// Send a bearer token with every request
+        pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+            self.api_key = Some(api_key.into());
+            self
+        }
+
+        async fn request(&self, context: &DecisionContext) -> reqwest::Result<AdvisorOpinion> {
+            let mut request = self.client.post(&self.endpoint).json(context);
+            if let Some(api_key) = &self.api_key {
+                request = request.bearer_auth(api_key);
+            }
+            request.send().await?.error_for_status()?.json().await
+        }
+    }
+
+    #[async_trait]
+    impl Advisor for HttpAdvisor {
+        fn name(&self) -> &str {
+            &self.endpoint
+        }
+
+        async fn advise(&self, context: &DecisionContext) -> AdvisorOpinion {
+            self.request(context).await.unwrap_or_else(|_| AdvisorOpinion::abstain())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MarketConditions;
+
+    fn state() -> NegotiationState {
+        let context = DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        let mut state = NegotiationState::new(context, 100.0, 5);
+        state.our_asks.push(110.0);
+        state
+    }
+
+    fn opinion(suggested_price: Option<f64>, accept_probability: Option<f64>, confidence: f64) -> AdvisorOpinion {
+        AdvisorOpinion {
+            suggested_price,
+            accept_probability,
+            confidence,
+            rationale: None,
+        }
+    }
+
+    #[test]
+    fn test_opinions_blend_by_weight_and_confidence() {
+        let advisor = WeightedAdvisor::new(Arc::new(StaticAdvisor(AdvisorOpinion::abstain())), 0.5);
+        assert_eq!(advisor.blend_price(100.0, &opinion(Some(200.0), None, 1.0)), 150.0);
+        assert_eq!(advisor.blend_price(100.0, &opinion(Some(200.0), None, 0.5)), 125.0);
+        assert_eq!(advisor.blend_price(100.0, &AdvisorOpinion::abstain()), 100.0);
+
+        let state = state();
+        let local = CounterOfferResponse::Counter(105.0);
+        // A confident advisor tips a counter into acceptance; a lukewarm one does not
+        assert_eq!(
+            advisor.blend_response(&state, 100.0, local, &opinion(None, Some(1.0), 1.0)),
+            CounterOfferResponse::Accept
+        );
+        assert_eq!(advisor.blend_response(&state, 100.0, local, &opinion(None, Some(0.9), 0.5)), local);
+        assert_eq!(
+            advisor.blend_response(&state, 100.0, CounterOfferResponse::Reject, &opinion(None, Some(1.0), 1.0)),
+            CounterOfferResponse::Reject
+        );
+
+        let ignored = WeightedAdvisor::new(Arc::new(StaticAdvisor(AdvisorOpinion::abstain())), 0.0);
+        assert_eq!(ignored.blend_price(100.0, &opinion(Some(200.0), None, 1.0)), 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_advisor_can_hold_out_for_a_better_price() {
+        let advisor = WeightedAdvisor::new(Arc::new(StaticAdvisor(opinion(Some(130.0), Some(0.0), 1.0))), 0.6);
+        let state = state();
+
+        let response = advisor.advise_response(&state, 105.0, CounterOfferResponse::Accept).await;
+        assert_eq!(response, CounterOfferResponse::Counter(110.0 * 0.4 + 130.0 * 0.6));
+        assert_eq!(advisor.advise_price(&state.context, 100.0).await, 118.0);
+    }
+}
@@ -0,0 +1,263 @@
+//! Risk Anomaly Detection
+//!
+//! Tracks each `RiskIndicator` type as a time series and flags observations
+//! that break from recent history. Three detectors run side by side:
+//!
+//! - z-score: distance from the rolling mean in standard deviations
+//! - MAD: distance from the rolling median in median absolute deviations,
+//!   robust to the outliers it is looking for
+//! - CUSUM: cumulative drift, catching slow shifts no single value reveals
+//!
+//! An alert stays active for a configurable number of further observations
+//! of its indicator. Negotiation can veto transactions while the combined
+//! severity of active alerts exceeds `AnomalyConfig::veto_threshold`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::RiskIndicator;
+
+/// Scale factor making MAD consistent with the standard deviation
+const MAD_SCALE: f64 = 1.4826;
+
+/// Detection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    pub window: usize,                    // Rolling history per indicator
+    pub min_samples: usize,               // History needed before flagging
+    pub z_threshold: f64,
+    pub mad_threshold: f64,
+    pub cusum_drift: f64,                 // Slack per observation, in standard deviations
+    pub cusum_threshold: f64,
+    pub alert_ttl: usize,                 // Observations an alert stays active
+    pub veto_threshold: f64,              // Combined severity that vetoes transactions
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window: 50,
+            min_samples: 10,
+            z_threshold: 3.0,
+            mad_threshold: 3.5,
+            cusum_drift: 0.5,
+            cusum_threshold: 5.0,
+            alert_ttl: 5,
+            veto_threshold: 2.0,
+        }
+    }
+}
+
+/// Detector that flagged an observation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnomalyMethod {
+    ZScore,
+    Mad,
+    Cusum,
+}
+
+/// A flagged risk observation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyAlert {
+    pub indicator_type: String,
+    pub value: f64,
+    pub methods: Vec<AnomalyMethod>,
+    pub severity: f64,                    // Strongest score / threshold, scaled by confidence
+    pub observation: usize,               // Index in the indicator's series
+}
+
+/// Rolling state for one indicator type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Series {
+    values: VecDeque<f64>,
+    cusum_high: f64,
+    cusum_low: f64,
+    observations: usize,
+    alert: Option<AnomalyAlert>,
+}
+
+impl Series {
+    fn mean_and_std(&self) -> (f64, f64) {
+        let n = self.values.len() as f64;
+        let mean = self.values.iter().sum::<f64>() / n;
+        let variance = self.values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+        (mean, variance.sqrt())
+    }
+
+    fn median_and_mad(&self) -> (f64, f64) {
+        let median = |values: &mut Vec<f64>| {
+            values.sort_by(f64::total_cmp);
+            let mid = values.len() / 2;
+            if values.len().is_multiple_of(2) {
+                (values[mid - 1] + values[mid]) / 2.0
+            } else {
+                values[mid]
+            }
+        };
+        let mut values: Vec<f64> = self.values.iter().copied().collect();
+        let center = median(&mut values);
+        let mut deviations: Vec<f64> = values.iter().map(|v| (v - center).abs()).collect();
+        (center, median(&mut deviations) * MAD_SCALE)
+    }
+}
+
+/// Streaming anomaly detector over risk indicators
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnomalyDetector {
+    pub config: AnomalyConfig,
+    series: BTreeMap<String, Series>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            series: BTreeMap::new(),
+        }
+    }
+
+    /// Record an observation, returning an alert if it is anomalous
+    pub fn observe(&mut self, indicator: &RiskIndicator) -> Option<AnomalyAlert> {
+        let config = &self.config;
+        let series = self.series.entry(indicator.indicator_type.clone()).or_default();
+        let value = indicator.value;
+
+        let mut methods = Vec::new();
+        let mut strength: f64 = 0.0;
+        if series.values.len() >= config.min_samples {
+            let (mean, std) = series.mean_and_std();
+            if std > f64::EPSILON {
+                let z = (value - mean) / std;
+                if z.abs() > config.z_threshold {
+                    methods.push(AnomalyMethod::ZScore);
+                }
+                strength = strength.max(z.abs() / config.z_threshold);
+
+                // Standardized CUSUM in both directions, restarted after an alarm
+                series.cusum_high = (series.cusum_high + z - config.cusum_drift).max(0.0);
+                series.cusum_low = (series.cusum_low - z - config.cusum_drift).max(0.0);
+                let cusum = series.cusum_high.max(series.cusum_low);
+                if cusum > config.cusum_threshold {
+                    methods.push(AnomalyMethod::Cusum);
+                    series.cusum_high = 0.0;
+                    series.cusum_low = 0.0;
+                }
+                strength = strength.max(cusum / config.cusum_threshold);
+            }
+
+            let (median, mad) = series.median_and_mad();
+            if mad > f64::EPSILON {
+                let robust_z = (value - median) / mad;
+                if robust_z.abs() > config.mad_threshold {
+                    methods.push(AnomalyMethod::Mad);
+                }
+                strength = strength.max(robust_z.abs() / config.mad_threshold);
+            }
+        }
+
+        series.values.push_back(value);
+        if series.values.len() > config.window {
+            series.values.pop_front();
+        }
+        series.observations += 1;
+
+        if let Some(alert) = &series.alert {
+            if series.observations - alert.observation > config.alert_ttl {
+                series.alert = None;
+            }
+        }
+        if methods.is_empty() {
+            return None;
+        }
+
+        let alert = AnomalyAlert {
+            indicator_type: indicator.indicator_type.clone(),
+            value,
+            methods,
+            severity: strength * indicator.confidence.clamp(0.0, 1.0),
+            observation: series.observations,
+        };
+        series.alert = Some(alert.clone());
+        Some(alert)
+    }
+
+    /// Record several observations, returning the alerts they raised
+    pub fn observe_all(&mut self, indicators: &[RiskIndicator]) -> Vec<AnomalyAlert> {
+        indicators.iter().filter_map(|indicator| self.observe(indicator)).collect()
+    }
+
+    /// Alerts still within their TTL, most severe first
+    pub fn current_alerts(&self) -> Vec<AnomalyAlert> {
+        let mut alerts: Vec<AnomalyAlert> = self.series.values().filter_map(|series| series.alert.clone()).collect();
+        alerts.sort_by(|a, b| b.severity.total_cmp(&a.severity));
+        alerts
+    }
+
+    /// Combined severity of the active alerts
+    pub fn total_severity(&self) -> f64 {
+        self.series.values().filter_map(|series| series.alert.as_ref()).map(|alert| alert.severity).sum()
+    }
+
+    /// Whether active anomalies are severe enough to veto transactions
+    pub fn should_veto(&self) -> bool {
+        self.total_severity() >= self.config.veto_threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indicator(kind: &str, value: f64) -> RiskIndicator {
+        RiskIndicator {
+            indicator_type: kind.to_string(),
+            value,
+            confidence: 1.0,
+        }
+    }
+
+    fn warmed_up() -> AnomalyDetector {
+        let mut detector = AnomalyDetector::default();
+        for i in 0..30 {
+            let noise = [0.0, 0.01, -0.01, 0.02, -0.02][i % 5];
+            assert!(detector.observe(&indicator("volatility", 0.2 + noise)).is_none());
+            assert!(detector.observe(&indicator("default_rate", 0.05 + noise / 2.0)).is_none());
+        }
+        detector
+    }
+
+    #[test]
+    fn test_spike_is_flagged_and_expires() {
+        let mut detector = warmed_up();
+        let alert = detector.observe(&indicator("volatility", 0.9)).unwrap();
+        assert!(alert.methods.contains(&AnomalyMethod::ZScore));
+        assert!(alert.methods.contains(&AnomalyMethod::Mad));
+        assert_eq!(detector.current_alerts().len(), 1);
+
+        for _ in 0..=detector.config.alert_ttl {
+            detector.observe(&indicator("volatility", 0.2));
+        }
+        assert!(detector.current_alerts().is_empty());
+    }
+
+    #[test]
+    fn test_cusum_catches_gradual_drift() {
+        let mut detector = warmed_up();
+        let alerts: Vec<AnomalyAlert> = (0..10)
+            .filter_map(|_| detector.observe(&indicator("default_rate", 0.066)))
+            .collect();
+
+        assert!(alerts.iter().any(|alert| alert.methods.contains(&AnomalyMethod::Cusum)));
+    }
+
+    #[test]
+    fn test_veto_requires_combined_severity() {
+        let mut detector = warmed_up();
+        assert!(!detector.should_veto());
+
+        detector.observe(&indicator("volatility", 0.9));
+        detector.observe(&indicator("default_rate", 0.5));
+        assert!(detector.total_severity() >= 2.0);
+        assert!(detector.should_veto());
+    }
+}
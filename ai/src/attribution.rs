@@ -0,0 +1,312 @@
+//! Outcome Attribution
+//!
+//! Explains where a period's profit or loss came from. Each closed deal is
+//! taken from its negotiation transcript (the pricing breakdown of the
+//! opening ask) and the explanation of the decision that closed it, and the
+//! gap between the realized price and the base price is split as a
+//! waterfall, in the order the adjustments are applied:
+//!
+//! ```text
+//! base → ×reputation → ×market → ×risk → price bounds → concession → realized
+//! ```
+//!
+//! Each step's change in price is credited to its factor, so contributions
+//! always add up to `revenue - baseline`. A concession only accepted because
+//! a deadline lowered the acceptance threshold is credited to deadline
+//! pressure rather than ordinary concession. Reports are grouped into fixed
+//! periods and export as JSON or Markdown for strategy reviews.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::transcript::{NegotiationResult, NegotiationTranscript, PricingBreakdown};
+use crate::DecisionExplanation;
+
+/// Decision factor a share of profit or loss is credited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DecisionFactor {
+    ReputationPremium,
+    MarketTiming,
+    RiskAdjustment,
+    PriceBounds,                          // Clamping to sane and governance limits
+    Concession,                           // Ground given between ask and agreed price
+    DeadlinePressure,                     // Concession accepted only because of a deadline
+}
+
+impl DecisionFactor {
+    pub const ALL: [DecisionFactor; 6] = [
+        DecisionFactor::ReputationPremium,
+        DecisionFactor::MarketTiming,
+        DecisionFactor::RiskAdjustment,
+        DecisionFactor::PriceBounds,
+        DecisionFactor::Concession,
+        DecisionFactor::DeadlinePressure,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            DecisionFactor::ReputationPremium => "Reputation premium",
+            DecisionFactor::MarketTiming => "Market timing",
+            DecisionFactor::RiskAdjustment => "Risk adjustment",
+            DecisionFactor::PriceBounds => "Price bounds",
+            DecisionFactor::Concession => "Concession",
+            DecisionFactor::DeadlinePressure => "Deadline pressure",
+        }
+    }
+}
+
+/// One finished negotiation, as recorded in the audit trail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DealRecord {
+    pub negotiation: String,
+    pub closed_at: u64,                   // Unix seconds
+    pub base_price: f64,
+    pub cost: Option<f64>,                // Execution cost, if known
+    pub pricing: PricingBreakdown,
+    pub decision: Option<DecisionExplanation>,  // Explanation of the closing decision
+    pub realized_price: Option<f64>,      // None when no deal was struck
+}
+
+impl DealRecord {
+    /// Build a record from a negotiation transcript and the explanation of
+    /// its final decision
+    pub fn from_transcript(
+        transcript: &NegotiationTranscript,
+        base_price: f64,
+        closed_at: u64,
+        decision: Option<DecisionExplanation>,
+    ) -> Self {
+        let realized_price = match transcript.result {
+            NegotiationResult::Agreed { price, .. } => Some(price),
+            NegotiationResult::NoDeal => None,
+        };
+        Self {
+            negotiation: transcript.script.clone(),
+            closed_at,
+            base_price,
+            cost: None,
+            pricing: transcript.pricing.clone(),
+            decision,
+            realized_price,
+        }
+    }
+
+    pub fn with_cost(mut self, cost: f64) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+
+    /// Price change credited to each factor; empty when no deal was struck
+    pub fn attribute(&self) -> Vec<(DecisionFactor, f64)> {
+        let Some(realized) = self.realized_price else {
+            return Vec::new();
+        };
+        let pricing = &self.pricing;
+        let after_reputation = self.base_price * pricing.reputation_factor;
+        let after_market = after_reputation * pricing.market_factor;
+        let unbounded_ask = after_market * pricing.risk_factor;
+
+        // The threshold without the deadline's relief; below it, the
+        // concession was only made because time was running out
+        let deadline_driven = self.decision.as_ref().is_some_and(|decision| {
+            decision.urgency_adjustment < 0.0 && decision.offer_ratio < decision.threshold - decision.urgency_adjustment
+        });
+        let concession_factor = if deadline_driven { DecisionFactor::DeadlinePressure } else { DecisionFactor::Concession };
+
+        vec![
+            (DecisionFactor::ReputationPremium, after_reputation - self.base_price),
+            (DecisionFactor::MarketTiming, after_market - after_reputation),
+            (DecisionFactor::RiskAdjustment, unbounded_ask - after_market),
+            (DecisionFactor::PriceBounds, pricing.ask - unbounded_ask),
+            (concession_factor, realized - pricing.ask),
+        ]
+    }
+}
+
+/// Total credited to one factor over a period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FactorContribution {
+    pub factor: DecisionFactor,
+    pub total: f64,
+    pub per_deal: f64,
+    pub share: f64,                       // Of the summed absolute contributions
+}
+
+/// Profit and loss attribution for one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributionReport {
+    pub period_start: u64,
+    pub period_end: u64,
+    pub deals: usize,
+    pub no_deals: usize,
+    pub baseline: f64,                    // Base prices of the closed deals
+    pub revenue: f64,
+    pub profit: Option<f64>,              // Only when every deal has a known cost
+    pub contributions: Vec<FactorContribution>,
+}
+
+impl AttributionReport {
+    /// Attribute a set of records to one period
+    pub fn generate(period_start: u64, period_end: u64, records: &[&DealRecord]) -> Self {
+        let closed: Vec<&&DealRecord> = records.iter().filter(|record| record.realized_price.is_some()).collect();
+        let mut totals: BTreeMap<DecisionFactor, f64> = DecisionFactor::ALL.iter().map(|factor| (*factor, 0.0)).collect();
+        for record in &closed {
+            for (factor, amount) in record.attribute() {
+                *totals.entry(factor).or_insert(0.0) += amount;
+            }
+        }
+
+        let gross: f64 = totals.values().map(|total| total.abs()).sum();
+        let deals = closed.len();
+        let contributions = totals
+            .into_iter()
+            .map(|(factor, total)| FactorContribution {
+                factor,
+                total,
+                per_deal: if deals > 0 { total / deals as f64 } else { 0.0 },
+                share: if gross > 0.0 { total.abs() / gross } else { 0.0 },
+            })
+            .collect();
+
+        let revenue = closed.iter().filter_map(|record| record.realized_price).sum();
+        let costs: Option<f64> = closed.iter().map(|record| record.cost).sum();
+        Self {
+            period_start,
+            period_end,
+            deals,
+            no_deals: records.len() - deals,
+            baseline: closed.iter().map(|record| record.base_price).sum(),
+            revenue,
+            profit: costs.map(|costs| revenue - costs),
+            contributions,
+        }
+    }
+
+    /// Contribution of one factor
+    pub fn contribution(&self, factor: DecisionFactor) -> Option<&FactorContribution> {
+        self.contributions.iter().find(|contribution| contribution.factor == factor)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize attribution report: {}", e))
+    }
+
+    /// Markdown summary for strategy reviews
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("## Outcome attribution, {} to {}\n\n", self.period_start, self.period_end);
+        out.push_str(&format!("- Deals: {} closed, {} without agreement\n", self.deals, self.no_deals));
+        out.push_str(&format!("- Revenue: {:.2} against a baseline of {:.2} ({:+.2})\n", self.revenue, self.baseline, self.revenue - self.baseline));
+        if let Some(profit) = self.profit {
+            out.push_str(&format!("- Profit: {:.2}\n", profit));
+        }
+        out.push_str("\n| Factor | Total | Per deal | Share |\n|---|---:|---:|---:|\n");
+        for contribution in &self.contributions {
+            out.push_str(&format!(
+                "| {} | {:+.2} | {:+.2} | {:.1}% |\n",
+                contribution.factor.label(),
+                contribution.total,
+                contribution.per_deal,
+                contribution.share * 100.0
+            ));
+        }
+        out
+    }
+}
+
+/// Collects deal records and reports on them period by period
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttributionReporter {
+    pub period_secs: u64,
+    records: Vec<DealRecord>,
+}
+
+impl AttributionReporter {
+    pub fn new(period_secs: u64) -> Self {
+        Self {
+            period_secs: period_secs.max(1),
+            records: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, record: DealRecord) {
+        self.records.push(record);
+    }
+
+    /// Report for the period containing `at`
+    pub fn report_for(&self, at: u64) -> AttributionReport {
+        let start = at - at % self.period_secs;
+        let end = start + self.period_secs;
+        let records: Vec<&DealRecord> = self.records.iter().filter(|record| (start..end).contains(&record.closed_at)).collect();
+        AttributionReport::generate(start, end, &records)
+    }
+
+    /// One report per period with activity, oldest first
+    pub fn reports(&self) -> Vec<AttributionReport> {
+        let mut starts: Vec<u64> = self.records.iter().map(|record| record.closed_at - record.closed_at % self.period_secs).collect();
+        starts.sort_unstable();
+        starts.dedup();
+        starts.into_iter().map(|start| self.report_for(start)).collect()
+    }
+
+    /// Report on and drop every period that ended by `now`
+    pub fn drain_completed(&mut self, now: u64) -> Vec<AttributionReport> {
+        let current = now - now % self.period_secs;
+        let reports: Vec<AttributionReport> = self.reports().into_iter().filter(|report| report.period_end <= current).collect();
+        self.records.retain(|record| record.closed_at >= current);
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::RiskCheck;
+
+    fn record(closed_at: u64, realized_price: Option<f64>, decision: Option<DecisionExplanation>) -> DealRecord {
+        DealRecord {
+            negotiation: format!("deal-{}", closed_at),
+            closed_at,
+            base_price: 100.0,
+            cost: Some(60.0),
+            pricing: PricingBreakdown { reputation_factor: 1.1, market_factor: 1.2, risk_factor: 0.9, ask: 118.8 },
+            decision,
+            realized_price,
+        }
+    }
+
+    #[test]
+    fn test_contributions_add_up_and_export() {
+        let rushed = DecisionExplanation {
+            offer_ratio: 0.75,
+            base_threshold: 0.8,
+            reputation_adjustment: 0.0,
+            market_adjustment: 0.0,
+            urgency_adjustment: -0.1,
+            threshold: 0.7,
+            within_bounds: true,
+            anomaly_veto: false,
+            risk_check: RiskCheck::Within,
+            accepted: true,
+        };
+        let mut reporter = AttributionReporter::new(3_600);
+        reporter.record(record(10, Some(110.0), None));
+        reporter.record(record(20, Some(89.1), Some(rushed)));
+        reporter.record(record(30, None, None));
+        reporter.record(record(4_000, Some(118.8), None));
+
+        let report = reporter.report_for(0);
+        assert_eq!((report.deals, report.no_deals), (2, 1));
+        let credited: f64 = report.contributions.iter().map(|contribution| contribution.total).sum();
+        assert!((credited - (report.revenue - report.baseline)).abs() < 1e-9);
+        assert!((report.contribution(DecisionFactor::ReputationPremium).unwrap().total - 20.0).abs() < 1e-9);
+        assert!((report.contribution(DecisionFactor::DeadlinePressure).unwrap().total + 29.7).abs() < 1e-9);
+        assert_eq!(report.profit, Some(199.1 - 120.0));
+
+        assert!(report.to_markdown().contains("| Deadline pressure | -29.70 |"));
+        assert!(report.to_json().unwrap().contains("\"DeadlinePressure\""));
+
+        let completed = reporter.drain_completed(4_000);
+        assert_eq!(completed.len(), 1);
+        assert_eq!(reporter.reports().len(), 1);
+    }
+}
@@ -0,0 +1,152 @@
+//! Concession Schedules
+//!
+//! A concession schedule plans every ask of a negotiation up front: it starts
+//! at an anchor and reaches the reservation price by the last round. The
+//! curve decides how fast we give ground:
+//!
+//! - `Linear`: equal steps each round
+//! - `Boulware`: holds near the anchor and concedes late
+//! - `Conceder`: gives most of the ground early
+//! - `Annealed`: a randomized walk whose jitter cools each round, so the
+//!   counterparty cannot extrapolate our next ask
+//!
+//! The time-dependent curves follow `progress = t^(1/beta)` over the share of
+//! rounds `t`, with `beta < 1` for Boulware and `beta > 1` for Conceder.
+
+use serde::{Deserialize, Serialize};
+
+use crate::rng::SeededRng;
+
+/// Shape of the path from anchor to reservation price
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ConcessionCurve {
+    Linear,
+    Boulware { beta: f64 },               // Below 1.0; smaller holds out longer
+    Conceder { beta: f64 },               // Above 1.0; larger concedes sooner
+    Annealed {
+        temperature: f64,                 // Initial jitter, as a share of the full concession
+        cooling: f64,                     // Temperature multiplier per round
+        seed: u64,
+    },
+}
+
+impl ConcessionCurve {
+    pub fn boulware() -> Self {
+        ConcessionCurve::Boulware { beta: 0.3 }
+    }
+
+    pub fn conceder() -> Self {
+        ConcessionCurve::Conceder { beta: 3.0 }
+    }
+
+    pub fn annealed(seed: u64) -> Self {
+        ConcessionCurve::Annealed { temperature: 0.2, cooling: 0.6, seed }
+    }
+
+    /// Concede sooner (`rate > 0`) or hold out longer (`rate < 0`)
+    ///
+    /// Boulware and Conceder curves stay on their side of linear; annealed
+    /// curves get more or less jitter instead.
+    pub fn tune(&mut self, rate: f64) {
+        let factor = 1.0 + rate;
+        match self {
+            ConcessionCurve::Linear => {}
+            ConcessionCurve::Boulware { beta } => *beta = (*beta * factor).clamp(0.05, 1.0),
+            ConcessionCurve::Conceder { beta } => *beta = (*beta * factor).clamp(1.0, 20.0),
+            ConcessionCurve::Annealed { temperature, .. } => *temperature = (*temperature * factor).clamp(0.0, 0.5),
+        }
+    }
+}
+
+/// Planned asks from an anchor down to a reservation price
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConcessionSchedule {
+    pub anchor: f64,
+    pub reservation: f64,
+    pub rounds: u32,
+    pub curve: ConcessionCurve,
+}
+
+impl ConcessionSchedule {
+    pub fn new(anchor: f64, reservation: f64, rounds: u32, curve: ConcessionCurve) -> Self {
+        Self { anchor, reservation, rounds, curve }
+    }
+
+    /// Ask for every round, from the anchor at round 0 to the reservation
+    /// price at the last
+    pub fn offers(&self) -> Vec<f64> {
+        let rounds = self.rounds.max(1);
+        let progress: Vec<f64> = match self.curve {
+            ConcessionCurve::Linear => (0..=rounds).map(|k| k as f64 / rounds as f64).collect(),
+            ConcessionCurve::Boulware { beta } | ConcessionCurve::Conceder { beta } => {
+                let beta = beta.max(f64::EPSILON);
+                (0..=rounds).map(|k| (k as f64 / rounds as f64).powf(1.0 / beta)).collect()
+            }
+            ConcessionCurve::Annealed { temperature, cooling, seed } => {
+                Self::annealed_progress(rounds, temperature, cooling, seed)
+            }
+        };
+        progress
+            .into_iter()
+            .map(|p| self.anchor + (self.reservation - self.anchor) * p)
+            .collect()
+    }
+
+    /// Ask planned for a round; rounds past the schedule stay at the reservation
+    pub fn offer_at(&self, round: u32) -> f64 {
+        let offers = self.offers();
+        offers[(round as usize).min(offers.len() - 1)]
+    }
+
+    /// Linear progress plus cooling jitter, never taking back a concession
+    fn annealed_progress(rounds: u32, temperature: f64, cooling: f64, seed: u64) -> Vec<f64> {
+        let mut rng = SeededRng::new(seed);
+
+        let mut progress = vec![0.0];
+        let mut heat = temperature;
+        for k in 1..rounds {
+            heat *= cooling;
+            let jitter = (rng.next_f64() * 2.0 - 1.0) * heat;
+            let previous = progress[progress.len() - 1];
+            progress.push((k as f64 / rounds as f64 + jitter).clamp(previous, 1.0));
+        }
+        progress.push(1.0);
+        progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curves_run_from_anchor_to_reservation() {
+        let schedule = |curve| ConcessionSchedule::new(130.0, 90.0, 6, curve);
+        let linear = schedule(ConcessionCurve::Linear).offers();
+        let boulware = schedule(ConcessionCurve::boulware()).offers();
+        let conceder = schedule(ConcessionCurve::conceder()).offers();
+        let annealed = schedule(ConcessionCurve::annealed(9)).offers();
+
+        for offers in [&linear, &boulware, &conceder, &annealed] {
+            assert_eq!(offers.len(), 7);
+            assert_eq!(offers[0], 130.0);
+            assert!((offers[6] - 90.0).abs() < 1e-9);
+            assert!(offers.windows(2).all(|w| w[1] <= w[0]));
+        }
+        assert_eq!(linear[3], 110.0);
+        assert!(boulware[3] > linear[3] && conceder[3] < linear[3]);
+        assert_eq!(annealed, schedule(ConcessionCurve::annealed(9)).offers());
+        assert_eq!(schedule(ConcessionCurve::Linear).offer_at(40), 90.0);
+    }
+
+    #[test]
+    fn test_tuning_stays_on_the_curve_family() {
+        let mut boulware = ConcessionCurve::boulware();
+        boulware.tune(10.0);
+        assert_eq!(boulware, ConcessionCurve::Boulware { beta: 1.0 });
+
+        let mut conceder = ConcessionCurve::conceder();
+        conceder.tune(-0.5);
+        assert_eq!(conceder, ConcessionCurve::Conceder { beta: 1.5 });
+    }
+}
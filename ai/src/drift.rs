@@ -0,0 +1,408 @@
+//! Strategy Drift Detection
+//!
+//! A pricing strategy is tuned against the market it was trained in. When
+//! that market moves, its outcomes move with it: fewer deals close, or they
+//! close at thinner margins. `DriftMonitor` keeps the outcome distribution
+//! of a training-period baseline and compares a rolling window of recent
+//! outcomes against it with the population stability index (PSI):
+//!
+//! ```text
+//! PSI = Σ (recent% - baseline%) × ln(recent% / baseline%)
+//! ```
+//!
+//! over bins cut at the baseline's quantiles. By convention a PSI under 0.1
+//! is stable, 0.1 to 0.25 a moderate shift, and over 0.25 a significant one.
+//! `DriftGuardedStrategy` wraps a deployed strategy, raises an event whenever
+//! a metric's drift grows more severe, and can fall back to a conservative
+//! strategy once drift is significant.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::mpsc::Sender;
+
+use crate::strategy::{ConservativeStrategy, CounterOfferResponse, NegotiationState, NegotiationStrategy};
+use crate::TransactionOutcome;
+
+/// Floor on bin shares, so empty bins do not make the index infinite
+const MIN_BIN_SHARE: f64 = 1e-4;
+
+/// Outcome distribution being watched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OutcomeMetric {
+    AcceptanceRate,                       // 1.0 per deal, 0.0 per failed negotiation
+    RealizedMargin,                       // Profit margin, across deals
+}
+
+/// How far a metric has moved from its baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DriftSeverity {
+    Stable,
+    Moderate,
+    Significant,
+}
+
+/// Drift settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftConfig {
+    pub baseline_size: usize,             // Outcomes forming the training-period baseline
+    pub window: usize,                    // Recent outcomes compared against it
+    pub min_samples: usize,               // Recent samples needed before scoring a metric
+    pub bins: usize,
+    pub moderate_threshold: f64,          // PSI at which drift is moderate
+    pub significant_threshold: f64,       // PSI at which drift is significant
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            baseline_size: 200,
+            window: 100,
+            min_samples: 30,
+            bins: 10,
+            moderate_threshold: 0.1,
+            significant_threshold: 0.25,
+        }
+    }
+}
+
+/// A metric whose drift grew more severe
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriftEvent {
+    pub metric: OutcomeMetric,
+    pub psi: f64,
+    pub severity: DriftSeverity,
+    pub baseline_samples: usize,
+    pub recent_samples: usize,
+}
+
+/// Population stability index of `recent` against `baseline`
+///
+/// Bins are cut at the baseline's quantiles; repeated cut points are merged,
+/// so discrete metrics such as acceptance get one bin per value.
+pub fn population_stability_index(baseline: &[f64], recent: &[f64], bins: usize) -> f64 {
+    if baseline.is_empty() || recent.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = baseline.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let bins = bins.max(1);
+    let mut edges: Vec<f64> = (1..bins).map(|k| sorted[k * (sorted.len() - 1) / bins]).collect();
+    edges.dedup();
+
+    let shares = |values: &[f64]| {
+        let mut counts = vec![0usize; edges.len() + 1];
+        for value in values {
+            counts[edges.partition_point(|edge| edge < value)] += 1;
+        }
+        counts
+            .into_iter()
+            .map(|count| (count as f64 / values.len() as f64).max(MIN_BIN_SHARE))
+            .collect::<Vec<f64>>()
+    };
+
+    shares(baseline)
+        .iter()
+        .zip(shares(recent))
+        .map(|(expected, actual)| (actual - expected) * (actual / expected).ln())
+        .sum()
+}
+
+/// Samples of one metric: frozen baseline plus a rolling window
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MetricSeries {
+    baseline: Vec<f64>,
+    recent: VecDeque<f64>,
+    severity: Option<DriftSeverity>,      // Last severity reported
+}
+
+/// Compares recent strategy outcomes against a training-period baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftMonitor {
+    pub config: DriftConfig,
+    baseline_outcomes: usize,
+    acceptance: MetricSeries,
+    margin: MetricSeries,
+}
+
+impl DriftMonitor {
+    pub fn new(config: DriftConfig) -> Self {
+        Self {
+            config,
+            baseline_outcomes: 0,
+            acceptance: MetricSeries::default(),
+            margin: MetricSeries::default(),
+        }
+    }
+
+    /// Whether the baseline is complete and outcomes are being compared
+    pub fn baseline_ready(&self) -> bool {
+        self.baseline_outcomes >= self.config.baseline_size
+    }
+
+    /// Record an outcome, returning events for metrics whose drift grew
+    /// more severe
+    pub fn observe(&mut self, outcome: &TransactionOutcome) -> Vec<DriftEvent> {
+        let acceptance = if outcome.success { 1.0 } else { 0.0 };
+        let margin = outcome.success.then_some(outcome.profit_margin);
+
+        if !self.baseline_ready() {
+            self.baseline_outcomes += 1;
+            self.acceptance.baseline.push(acceptance);
+            self.margin.baseline.extend(margin);
+            return Vec::new();
+        }
+
+        let window = self.config.window;
+        let push = |series: &mut MetricSeries, value: f64| {
+            series.recent.push_back(value);
+            if series.recent.len() > window {
+                series.recent.pop_front();
+            }
+        };
+        push(&mut self.acceptance, acceptance);
+        if let Some(margin) = margin {
+            push(&mut self.margin, margin);
+        }
+
+        [OutcomeMetric::AcceptanceRate, OutcomeMetric::RealizedMargin]
+            .into_iter()
+            .filter_map(|metric| self.check(metric))
+            .collect()
+    }
+
+    /// Current PSI of a metric, once enough recent samples are in
+    pub fn psi(&self, metric: OutcomeMetric) -> Option<f64> {
+        let series = self.series(metric);
+        if !self.baseline_ready() || series.recent.len() < self.config.min_samples {
+            return None;
+        }
+        let recent: Vec<f64> = series.recent.iter().copied().collect();
+        Some(population_stability_index(&series.baseline, &recent, self.config.bins))
+    }
+
+    /// Severity of a metric's current drift
+    pub fn severity(&self, metric: OutcomeMetric) -> DriftSeverity {
+        match self.psi(metric) {
+            Some(psi) if psi >= self.config.significant_threshold => DriftSeverity::Significant,
+            Some(psi) if psi >= self.config.moderate_threshold => DriftSeverity::Moderate,
+            _ => DriftSeverity::Stable,
+        }
+    }
+
+    /// Start a new baseline, e.g. after retraining the strategy
+    pub fn reset_baseline(&mut self) {
+        *self = Self::new(self.config.clone());
+    }
+
+    fn series(&self, metric: OutcomeMetric) -> &MetricSeries {
+        match metric {
+            OutcomeMetric::AcceptanceRate => &self.acceptance,
+            OutcomeMetric::RealizedMargin => &self.margin,
+        }
+    }
+
+    /// Event if the metric's severity rose since it was last reported
+    fn check(&mut self, metric: OutcomeMetric) -> Option<DriftEvent> {
+        let psi = self.psi(metric)?;
+        let severity = self.severity(metric);
+        let baseline_samples = self.series(metric).baseline.len();
+        let series = match metric {
+            OutcomeMetric::AcceptanceRate => &mut self.acceptance,
+            OutcomeMetric::RealizedMargin => &mut self.margin,
+        };
+        let escalated = series.severity.is_none_or(|reported| severity > reported);
+        series.severity = Some(severity);
+        (escalated && severity > DriftSeverity::Stable).then_some(DriftEvent {
+            metric,
+            psi,
+            severity,
+            baseline_samples,
+            recent_samples: series.recent.len(),
+        })
+    }
+}
+
+impl Default for DriftMonitor {
+    fn default() -> Self {
+        Self::new(DriftConfig::default())
+    }
+}
+
+/// Deployed strategy under drift monitoring, with an optional fallback
+#[derive(Debug)]
+pub struct DriftGuardedStrategy {
+    pub monitor: DriftMonitor,
+    pub auto_revert: bool,                // Switch to the fallback on significant drift
+    primary: Box<dyn NegotiationStrategy>,
+    fallback: Box<dyn NegotiationStrategy>,
+    reverted: bool,
+    events: Vec<DriftEvent>,
+    sink: Option<Sender<DriftEvent>>,
+}
+
+impl DriftGuardedStrategy {
+    /// Monitor a strategy, falling back to `ConservativeStrategy`
+    pub fn new(primary: Box<dyn NegotiationStrategy>) -> Self {
+        Self {
+            monitor: DriftMonitor::default(),
+            auto_revert: true,
+            primary,
+            fallback: Box::new(ConservativeStrategy::default()),
+            reverted: false,
+            events: Vec::new(),
+            sink: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: DriftConfig) -> Self {
+        self.monitor = DriftMonitor::new(config);
+        self
+    }
+
+    pub fn with_fallback(mut self, fallback: Box<dyn NegotiationStrategy>) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Keep the primary strategy whatever the drift; events are still raised
+    pub fn without_auto_revert(mut self) -> Self {
+        self.auto_revert = false;
+        self
+    }
+
+    /// Also send each drift event to a channel
+    pub fn with_events(mut self, sink: Sender<DriftEvent>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Whether negotiation has fallen back
+    pub fn reverted(&self) -> bool {
+        self.reverted
+    }
+
+    /// Drift events raised so far
+    pub fn events(&self) -> &[DriftEvent] {
+        &self.events
+    }
+
+    /// Return to the primary strategy against a fresh baseline
+    pub fn restore(&mut self) {
+        self.reverted = false;
+        self.monitor.reset_baseline();
+    }
+
+    fn active(&self) -> &dyn NegotiationStrategy {
+        if self.reverted { self.fallback.as_ref() } else { self.primary.as_ref() }
+    }
+}
+
+impl NegotiationStrategy for DriftGuardedStrategy {
+    fn name(&self) -> &str {
+        self.active().name()
+    }
+
+    fn propose_price(&self, state: &NegotiationState) -> f64 {
+        self.active().propose_price(state)
+    }
+
+    fn evaluate_counter_offer(&self, state: &NegotiationState, offer: f64) -> CounterOfferResponse {
+        self.active().evaluate_counter_offer(state, offer)
+    }
+
+    fn should_walk_away(&self, state: &NegotiationState) -> bool {
+        self.active().should_walk_away(state)
+    }
+
+    fn observe_outcome(&mut self, state: &NegotiationState, outcome: &TransactionOutcome) {
+        if self.reverted {
+            self.fallback.observe_outcome(state, outcome);
+            return;
+        }
+        self.primary.observe_outcome(state, outcome);
+
+        for event in self.monitor.observe(outcome) {
+            if self.auto_revert && event.severity == DriftSeverity::Significant {
+                self.reverted = true;
+            }
+            if let Some(sink) = &self.sink {
+                // A dropped receiver only means nobody is listening
+                let _ = sink.send(event.clone());
+            }
+            self.events.push(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::AggressiveStrategy;
+    use crate::{DecisionContext, MarketConditions};
+
+    fn outcome(success: bool, profit_margin: f64) -> TransactionOutcome {
+        TransactionOutcome { success, profit_margin, satisfaction_score: 1.0, completion_time: 0 }
+    }
+
+    fn state() -> NegotiationState {
+        let context = DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        NegotiationState::new(context, 100.0, 5)
+    }
+
+    #[test]
+    fn test_psi_separates_stable_from_shifted_distributions() {
+        let baseline: Vec<f64> = (0..200).map(|i| (i % 20) as f64 / 100.0).collect();
+        let same: Vec<f64> = (0..100).map(|i| (i % 20) as f64 / 100.0).collect();
+        let shifted: Vec<f64> = same.iter().map(|margin| margin * 0.3).collect();
+        assert!(population_stability_index(&baseline, &same, 10) < 0.01);
+        assert!(population_stability_index(&baseline, &shifted, 10) > 0.25);
+
+        // Acceptance falling from 80% to 40%
+        let accepted = |rate: usize, n: usize| (0..n).map(|i| if i % 10 < rate { 1.0 } else { 0.0 }).collect::<Vec<f64>>();
+        assert!(population_stability_index(&accepted(8, 200), &accepted(4, 100), 10) > 0.25);
+    }
+
+    #[test]
+    fn test_significant_drift_reverts_to_the_fallback() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let config = DriftConfig { baseline_size: 50, window: 40, min_samples: 20, ..DriftConfig::default() };
+        let mut strategy = DriftGuardedStrategy::new(Box::new(AggressiveStrategy::default()))
+            .with_config(config)
+            .with_events(sender);
+        let state = state();
+
+        for i in 0..90 {
+            strategy.observe_outcome(&state, &outcome(i % 5 != 0, 0.3));
+        }
+        assert!(strategy.events().is_empty());
+        assert_eq!(strategy.name(), "aggressive");
+
+        // The market turns: most negotiations now fail
+        for i in 0..40 {
+            strategy.observe_outcome(&state, &outcome(i % 5 == 0, 0.3));
+        }
+        assert!(strategy.reverted());
+        assert_eq!(strategy.name(), "conservative");
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.metric, OutcomeMetric::AcceptanceRate);
+        assert_eq!(strategy.events().last().unwrap().severity, DriftSeverity::Significant);
+
+        strategy.restore();
+        assert_eq!(strategy.name(), "aggressive");
+        assert!(!strategy.monitor.baseline_ready());
+    }
+}
@@ -0,0 +1,481 @@
+//! Price Forecasting
+//!
+//! `Forecaster` is the extension point for predicting where service prices
+//! are heading. Every forecast carries a 95% prediction interval, so callers
+//! such as `NegotiationAI::decide_pricing_with_forecast` can lean on a
+//! confident forecast and discount an uncertain one. Bundled models:
+//!
+//! - `EmaForecaster`: simple exponential smoothing, a flat forecast
+//! - `HoltWintersForecaster`: additive level, trend, and seasonality
+//! - `ArimaLiteForecaster`: autoregressive model on first differences,
+//!   i.e. ARIMA(p, 1, 0), fitted with Yule-Walker equations
+//!
+//! `ForecastModel` wraps the bundled models so a fitted model, including its
+//! smoothed state, round-trips through JSON.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// z-score of the two-sided 95% prediction interval
+const Z_95: f64 = 1.96;
+
+/// Forecast for one step ahead or further
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PricePrediction {
+    pub horizon: usize,
+    pub mean: f64,
+    pub lower: f64,                       // 95% interval
+    pub upper: f64,
+    pub std_error: f64,
+}
+
+impl PricePrediction {
+    fn new(horizon: usize, mean: f64, variance: f64) -> Self {
+        let std_error = variance.max(0.0).sqrt();
+        Self {
+            horizon,
+            mean,
+            lower: mean - Z_95 * std_error,
+            upper: mean + Z_95 * std_error,
+            std_error,
+        }
+    }
+
+    /// Half-width of the interval relative to the mean
+    pub fn relative_uncertainty(&self) -> f64 {
+        if self.mean.abs() < f64::EPSILON {
+            return f64::INFINITY;
+        }
+        (self.upper - self.lower) / (2.0 * self.mean.abs())
+    }
+}
+
+/// Pluggable price forecasting model
+pub trait Forecaster: fmt::Debug + Send + Sync {
+    /// Model name for logs and metrics
+    fn name(&self) -> &str;
+
+    /// Fit the model to a price history, oldest first
+    fn fit(&mut self, history: &[f64]);
+
+    /// Forecast `horizon` steps past the fitted history; `None` until fitted
+    fn forecast(&self, horizon: usize) -> Option<PricePrediction>;
+}
+
+/// Mean squared one-step error
+fn residual_variance(sum_squared_errors: f64, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        sum_squared_errors / count as f64
+    }
+}
+
+/// Simple exponential smoothing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmaForecaster {
+    pub alpha: f64,                       // Smoothing factor, 0-1
+    level: Option<f64>,
+    residual_variance: f64,
+}
+
+impl EmaForecaster {
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha: alpha.clamp(0.01, 1.0),
+            level: None,
+            residual_variance: 0.0,
+        }
+    }
+}
+
+impl Forecaster for EmaForecaster {
+    fn name(&self) -> &str {
+        "ema"
+    }
+
+    fn fit(&mut self, history: &[f64]) {
+        let Some((&first, rest)) = history.split_first() else {
+            self.level = None;
+            return;
+        };
+
+        let mut level = first;
+        let mut sse = 0.0;
+        for &price in rest {
+            let error = price - level;
+            sse += error * error;
+            level += self.alpha * error;
+        }
+        self.level = Some(level);
+        self.residual_variance = residual_variance(sse, rest.len());
+    }
+
+    fn forecast(&self, horizon: usize) -> Option<PricePrediction> {
+        let level = self.level?;
+        let horizon = horizon.max(1);
+        let variance = self.residual_variance * (1.0 + (horizon - 1) as f64 * self.alpha.powi(2));
+        Some(PricePrediction::new(horizon, level, variance))
+    }
+}
+
+/// Additive Holt-Winters; without two full seasons of data it fits level
+/// and trend only (Holt's linear method)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoltWintersForecaster {
+    pub alpha: f64,                       // Level smoothing
+    pub beta: f64,                        // Trend smoothing
+    pub gamma: f64,                       // Seasonal smoothing
+    pub season_length: usize,
+    level: Option<f64>,
+    trend: f64,
+    seasonals: Vec<f64>,
+    observations: usize,
+    residual_variance: f64,
+}
+
+impl HoltWintersForecaster {
+    pub fn new(alpha: f64, beta: f64, gamma: f64, season_length: usize) -> Self {
+        Self {
+            alpha: alpha.clamp(0.01, 1.0),
+            beta: beta.clamp(0.0, 1.0),
+            gamma: gamma.clamp(0.0, 1.0),
+            season_length,
+            level: None,
+            trend: 0.0,
+            seasonals: Vec::new(),
+            observations: 0,
+            residual_variance: 0.0,
+        }
+    }
+
+    /// Whether the fitted model includes a seasonal component
+    pub fn is_seasonal(&self) -> bool {
+        !self.seasonals.is_empty()
+    }
+}
+
+impl Forecaster for HoltWintersForecaster {
+    fn name(&self) -> &str {
+        "holt_winters"
+    }
+
+    fn fit(&mut self, history: &[f64]) {
+        let m = self.season_length;
+        self.observations = history.len();
+        self.seasonals.clear();
+        if history.len() < 2 {
+            self.level = None;
+            return;
+        }
+
+        let (mut level, mut trend, start) = if m >= 2 && history.len() >= 2 * m {
+            let first = history[..m].iter().sum::<f64>() / m as f64;
+            let second = history[m..2 * m].iter().sum::<f64>() / m as f64;
+            self.seasonals = history[..m].iter().map(|price| price - first).collect();
+            (first, (second - first) / m as f64, m)
+        } else {
+            (history[0], history[1] - history[0], 1)
+        };
+
+        let mut sse = 0.0;
+        for (t, &price) in history.iter().enumerate().skip(start) {
+            let seasonal = if self.seasonals.is_empty() { 0.0 } else { self.seasonals[t % m] };
+            let error = price - (level + trend + seasonal);
+            sse += error * error;
+
+            let previous_level = level;
+            level = self.alpha * (price - seasonal) + (1.0 - self.alpha) * (level + trend);
+            trend = self.beta * (level - previous_level) + (1.0 - self.beta) * trend;
+            if !self.seasonals.is_empty() {
+                self.seasonals[t % m] = self.gamma * (price - level) + (1.0 - self.gamma) * seasonal;
+            }
+        }
+
+        self.level = Some(level);
+        self.trend = trend;
+        self.residual_variance = residual_variance(sse, history.len() - start);
+    }
+
+    fn forecast(&self, horizon: usize) -> Option<PricePrediction> {
+        let level = self.level?;
+        let horizon = horizon.max(1);
+        let seasonal = if self.seasonals.is_empty() {
+            0.0
+        } else {
+            self.seasonals[(self.observations + horizon - 1) % self.season_length]
+        };
+
+        // Uncertainty grows with the horizon as level and trend errors compound
+        let variance = self.residual_variance
+            * (1.0 + (horizon - 1) as f64 * self.alpha.powi(2) * (1.0 + horizon as f64 * self.beta));
+        Some(PricePrediction::new(horizon, level + horizon as f64 * self.trend + seasonal, variance))
+    }
+}
+
+/// AR(p) on first differences, i.e. ARIMA(p, 1, 0)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArimaLiteForecaster {
+    pub order: usize,
+    coefficients: Vec<f64>,
+    mean_difference: f64,
+    recent_differences: Vec<f64>,         // Last `order` differences, oldest first
+    last_price: Option<f64>,
+    residual_variance: f64,
+}
+
+impl ArimaLiteForecaster {
+    pub fn new(order: usize) -> Self {
+        Self {
+            order: order.max(1),
+            coefficients: Vec::new(),
+            mean_difference: 0.0,
+            recent_differences: Vec::new(),
+            last_price: None,
+            residual_variance: 0.0,
+        }
+    }
+
+    /// Fitted AR coefficients
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+}
+
+/// Solve the Yule-Walker equations for AR coefficients (Levinson-Durbin)
+fn yule_walker(autocovariance: &[f64], order: usize) -> Vec<f64> {
+    let mut phi = vec![0.0; order];
+    if autocovariance[0] <= f64::EPSILON {
+        return phi;
+    }
+
+    let mut error = autocovariance[0];
+    for k in 0..order {
+        let acc: f64 = (0..k).map(|j| phi[j] * autocovariance[k - j]).sum();
+        let reflection = (autocovariance[k + 1] - acc) / error;
+
+        let previous = phi.clone();
+        phi[k] = reflection;
+        for j in 0..k {
+            phi[j] = previous[j] - reflection * previous[k - 1 - j];
+        }
+        error *= 1.0 - reflection * reflection;
+        if error <= f64::EPSILON {
+            break;
+        }
+    }
+    phi
+}
+
+impl Forecaster for ArimaLiteForecaster {
+    fn name(&self) -> &str {
+        "arima_lite"
+    }
+
+    fn fit(&mut self, history: &[f64]) {
+        self.last_price = history.last().copied();
+        let differences: Vec<f64> = history.windows(2).map(|w| w[1] - w[0]).collect();
+        let n = differences.len();
+        if n == 0 {
+            self.coefficients.clear();
+            self.recent_differences.clear();
+            self.mean_difference = 0.0;
+            self.residual_variance = 0.0;
+            return;
+        }
+
+        self.mean_difference = differences.iter().sum::<f64>() / n as f64;
+        let centered: Vec<f64> = differences.iter().map(|d| d - self.mean_difference).collect();
+
+        // Too little data for the full order: fit what the history supports
+        let order = self.order.min(n.saturating_sub(1));
+        let autocovariance: Vec<f64> = (0..=order)
+            .map(|lag| (lag..n).map(|t| centered[t] * centered[t - lag]).sum::<f64>() / n as f64)
+            .collect();
+        self.coefficients = yule_walker(&autocovariance, order);
+
+        let mut sse = 0.0;
+        for t in order..n {
+            let predicted: f64 = self.coefficients.iter().enumerate().map(|(i, phi)| phi * centered[t - 1 - i]).sum();
+            sse += (centered[t] - predicted).powi(2);
+        }
+        self.residual_variance = residual_variance(sse, n - order);
+        self.recent_differences = differences[n - order..].to_vec();
+    }
+
+    fn forecast(&self, horizon: usize) -> Option<PricePrediction> {
+        let mut price = self.last_price?;
+        let horizon = horizon.max(1);
+        let mut differences = self.recent_differences.clone();
+
+        for _ in 0..horizon {
+            let next = self.mean_difference
+                + self.coefficients.iter().enumerate()
+                    .map(|(i, phi)| phi * (differences[differences.len() - 1 - i] - self.mean_difference))
+                    .sum::<f64>();
+            price += next;
+            differences.push(next);
+        }
+
+        // Psi weights of the differenced AR process, accumulated for the integration
+        let mut psi = vec![1.0];
+        for j in 1..horizon {
+            let weight = self.coefficients.iter().enumerate()
+                .filter(|(i, _)| *i < j)
+                .map(|(i, phi)| phi * psi[j - 1 - i])
+                .sum();
+            psi.push(weight);
+        }
+        let variance = psi
+            .iter()
+            .scan(0.0, |cumulative, weight| {
+                *cumulative += weight;
+                Some(*cumulative * *cumulative)
+            })
+            .sum::<f64>()
+            * self.residual_variance;
+
+        Some(PricePrediction::new(horizon, price, variance))
+    }
+}
+
+/// Bundled forecasting models, serializable with their fitted state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum ForecastModel {
+    Ema(EmaForecaster),
+    HoltWinters(HoltWintersForecaster),
+    ArimaLite(ArimaLiteForecaster),
+}
+
+impl ForecastModel {
+    /// Simple exponential smoothing
+    pub fn ema(alpha: f64) -> Self {
+        Self::Ema(EmaForecaster::new(alpha))
+    }
+
+    /// Holt-Winters with typical smoothing factors
+    pub fn holt_winters(season_length: usize) -> Self {
+        Self::HoltWinters(HoltWintersForecaster::new(0.4, 0.1, 0.3, season_length))
+    }
+
+    /// ARIMA(order, 1, 0)
+    pub fn arima_lite(order: usize) -> Self {
+        Self::ArimaLite(ArimaLiteForecaster::new(order))
+    }
+
+    fn inner(&self) -> &dyn Forecaster {
+        match self {
+            Self::Ema(model) => model,
+            Self::HoltWinters(model) => model,
+            Self::ArimaLite(model) => model,
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut dyn Forecaster {
+        match self {
+            Self::Ema(model) => model,
+            Self::HoltWinters(model) => model,
+            Self::ArimaLite(model) => model,
+        }
+    }
+
+    /// Serialize the model and its fitted state
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Deserialize a fitted model
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("invalid forecast model: {}", e))
+    }
+
+    /// Save the model to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, self.to_json()? + "\n").map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    }
+
+    /// Load a model from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&content)
+    }
+}
+
+impl Default for ForecastModel {
+    fn default() -> Self {
+        Self::ema(0.3)
+    }
+}
+
+impl Forecaster for ForecastModel {
+    fn name(&self) -> &str {
+        self.inner().name()
+    }
+
+    fn fit(&mut self, history: &[f64]) {
+        self.inner_mut().fit(history)
+    }
+
+    fn forecast(&self, horizon: usize) -> Option<PricePrediction> {
+        self.inner().forecast(horizon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seasonal_series(cycles: usize) -> Vec<f64> {
+        let pattern = [0.0, 5.0, 10.0, 5.0];
+        (0..cycles * pattern.len())
+            .map(|t| 100.0 + t as f64 * 0.5 + pattern[t % pattern.len()])
+            .collect()
+    }
+
+    #[test]
+    fn test_models_track_level_and_widen_with_horizon() {
+        let history: Vec<f64> = (0..30).map(|t| 100.0 + t as f64 + if t % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        for mut model in [ForecastModel::ema(0.5), ForecastModel::holt_winters(4), ForecastModel::arima_lite(2)] {
+            assert!(model.forecast(1).is_none(), "{}", model.name());
+            model.fit(&history);
+
+            let near = model.forecast(1).unwrap();
+            let far = model.forecast(10).unwrap();
+            assert!(near.lower <= near.mean && near.mean <= near.upper);
+            assert!((near.mean - 129.0).abs() < 10.0, "{} forecast {}", model.name(), near.mean);
+            assert!(far.upper - far.lower >= near.upper - near.lower, "{}", model.name());
+        }
+    }
+
+    #[test]
+    fn test_holt_winters_captures_seasonality() {
+        let mut model = HoltWintersForecaster::new(0.3, 0.1, 0.5, 4);
+        let history = seasonal_series(6);
+        model.fit(&history);
+        assert!(model.is_seasonal());
+
+        // Next period is the trough of the cycle, the one after is higher
+        let trough = model.forecast(1).unwrap().mean;
+        let rise = model.forecast(2).unwrap().mean;
+        assert!(rise - trough > 3.0, "trough {} rise {}", trough, rise);
+    }
+
+    #[test]
+    fn test_fitted_model_round_trips() {
+        let mut model = ForecastModel::arima_lite(2);
+        model.fit(&seasonal_series(5));
+        let restored = ForecastModel::from_json(&model.to_json().unwrap()).unwrap();
+
+        assert_eq!(restored.name(), "arima_lite");
+        assert_eq!(restored.forecast(3), model.forecast(3));
+        assert!(ForecastModel::from_json("{\"model\":\"prophet\"}").is_err());
+    }
+}
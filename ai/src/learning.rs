@@ -0,0 +1,392 @@
+//! Online Learning for Negotiation Decisions
+//!
+//! A contextual bandit that learns which pricing multiplier and which
+//! counter-offer acceptance threshold pay off in each kind of negotiation.
+//! The context is discretized (reputation gap, demand, competition, deal
+//! size), each decision picks an action epsilon-greedily, and the reward
+//! computed from the resulting `TransactionOutcome` updates that action's
+//! value estimate.
+//!
+//! Untrained states fall back to the action closest to the heuristic
+//! decision, so apart from exploration a fresh policy behaves like
+//! `NegotiationAI` without learning. The whole policy, including its exploration RNG, serializes to
+//! JSON so an agent can persist it and resume learning on the next run.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::rng::SeededRng;
+use crate::{DecisionContext, TransactionOutcome};
+
+/// Format version of saved policies
+pub const POLICY_VERSION: u32 = 1;
+
+/// Multipliers applied to the heuristic ask
+pub const PRICE_MULTIPLIERS: [f64; 7] = [0.85, 0.9, 0.95, 1.0, 1.05, 1.1, 1.2];
+
+/// Minimum offer/ask ratios to accept a counter-offer
+pub const ACCEPTANCE_THRESHOLDS: [f64; 8] = [0.6, 0.65, 0.7, 0.75, 0.8, 0.85, 0.9, 0.95];
+
+/// Which decision a learned action belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionKind {
+    Pricing,
+    CounterOffer,
+}
+
+/// A decision taken by the policy, kept by the caller until its outcome is known
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decision {
+    pub kind: DecisionKind,
+    pub state: String,
+    pub action: usize,
+    pub explored: bool,
+}
+
+/// A decision paired with the outcome it led to, for offline training
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledOutcome {
+    pub decision: Decision,
+    pub outcome: TransactionOutcome,
+}
+
+/// How a transaction outcome is turned into a reward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardConfig {
+    pub profit_weight: f64,
+    pub satisfaction_weight: f64,
+    pub failure_penalty: f64,
+    pub time_penalty_per_hour: f64,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            profit_weight: 1.0,
+            satisfaction_weight: 0.5,
+            failure_penalty: 1.0,
+            time_penalty_per_hour: 0.01,
+        }
+    }
+}
+
+impl RewardConfig {
+    /// Reward for an outcome
+    pub fn reward(&self, outcome: &TransactionOutcome) -> f64 {
+        let base = if outcome.success {
+            self.profit_weight * outcome.profit_margin + self.satisfaction_weight * outcome.satisfaction_score
+        } else {
+            -self.failure_penalty
+        };
+        base - self.time_penalty_per_hour * outcome.completion_time as f64 / 3600.0
+    }
+}
+
+/// Exploration schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplorationConfig {
+    pub initial_epsilon: f64,
+    pub min_epsilon: f64,
+    pub decay: f64,                        // Per update
+}
+
+impl Default for ExplorationConfig {
+    fn default() -> Self {
+        Self {
+            initial_epsilon: 0.2,
+            min_epsilon: 0.02,
+            decay: 0.995,
+        }
+    }
+}
+
+/// Value estimates for the actions of one state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActionValues {
+    pub values: Vec<f64>,
+    pub counts: Vec<u64>,
+}
+
+impl ActionValues {
+    fn new(actions: usize) -> Self {
+        Self {
+            values: vec![0.0; actions],
+            counts: vec![0; actions],
+        }
+    }
+
+    /// Best tried action; an untried `neutral` action counts as a zero prior
+    /// and wins ties by being closest to itself
+    fn best(&self, neutral: usize) -> usize {
+        let mut best = neutral;
+        let mut best_value = if self.counts[neutral] > 0 { self.values[neutral] } else { 0.0 };
+        for action in (0..self.values.len()).filter(|&a| self.counts[a] > 0) {
+            let value = self.values[action];
+            if value > best_value || (value == best_value && action.abs_diff(neutral) < best.abs_diff(neutral)) {
+                best = action;
+                best_value = value;
+            }
+        }
+        best
+    }
+}
+
+/// Learned pricing and counter-offer policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegotiationPolicy {
+    pub version: u32,
+    pub learning_rate: f64,
+    pub reward: RewardConfig,
+    pub exploration: ExplorationConfig,
+    pub updates: u64,
+    pricing: BTreeMap<String, ActionValues>,
+    counter_offer: BTreeMap<String, ActionValues>,
+    #[serde(rename = "rng_state")]
+    rng: SeededRng,
+}
+
+impl NegotiationPolicy {
+    /// Create an untrained policy
+    pub fn new(learning_rate: f64, seed: u64) -> Self {
+        Self {
+            version: POLICY_VERSION,
+            learning_rate,
+            reward: RewardConfig::default(),
+            exploration: ExplorationConfig::default(),
+            updates: 0,
+            pricing: BTreeMap::new(),
+            counter_offer: BTreeMap::new(),
+            rng: SeededRng::new(seed),
+        }
+    }
+
+    /// Discretized context key
+    pub fn state_key(context: &DecisionContext) -> String {
+        let bucket = |value: f64, low: f64, high: f64| if value < low { 0 } else if value > high { 2 } else { 1 };
+        let reputation_gap = context.agent_reputation - context.counterparty_reputation;
+        let size = context.transaction_value.max(1.0).log10().floor().clamp(0.0, 3.0) as u8;
+
+        format!(
+            "r{}-d{}-c{}-v{}",
+            bucket(reputation_gap, -0.15, 0.15),
+            bucket(context.market_conditions.demand_level, 0.33, 0.66),
+            bucket(context.market_conditions.competition_level, 0.33, 0.66),
+            size
+        )
+    }
+
+    /// Current exploration rate
+    pub fn epsilon(&self) -> f64 {
+        let e = &self.exploration;
+        (e.initial_epsilon * e.decay.powf(self.updates as f64)).max(e.min_epsilon)
+    }
+
+    /// Choose the ask, starting from the heuristic ask
+    pub fn choose_price(&mut self, context: &DecisionContext, heuristic_ask: f64) -> (f64, Decision) {
+        let neutral = nearest(&PRICE_MULTIPLIERS, 1.0);
+        let decision = self.choose(DecisionKind::Pricing, context, neutral);
+        (heuristic_ask * PRICE_MULTIPLIERS[decision.action], decision)
+    }
+
+    /// Choose whether to accept a counter-offer, starting from the heuristic threshold
+    pub fn choose_acceptance(
+        &mut self,
+        context: &DecisionContext,
+        counter_offer: f64,
+        ask: f64,
+        heuristic_threshold: f64,
+    ) -> (bool, Decision) {
+        let neutral = nearest(&ACCEPTANCE_THRESHOLDS, heuristic_threshold);
+        let decision = self.choose(DecisionKind::CounterOffer, context, neutral);
+        (counter_offer / ask >= ACCEPTANCE_THRESHOLDS[decision.action], decision)
+    }
+
+    /// Learn from the outcome of an earlier decision
+    pub fn update(&mut self, decision: &Decision, outcome: &TransactionOutcome) {
+        let reward = self.reward.reward(outcome);
+        let learning_rate = self.learning_rate.clamp(0.0, 1.0);
+        let actions = action_count(decision.kind);
+        let values = self.table_mut(decision.kind)
+            .entry(decision.state.clone())
+            .or_insert_with(|| ActionValues::new(actions));
+        if decision.action >= values.values.len() {
+            return;
+        }
+
+        // The first observation replaces the zero prior instead of being averaged into it
+        let value = &mut values.values[decision.action];
+        if values.counts[decision.action] == 0 {
+            *value = reward;
+        } else {
+            *value += learning_rate * (reward - *value);
+        }
+        values.counts[decision.action] += 1;
+        self.updates += 1;
+    }
+
+    /// Replay a history of decisions and outcomes
+    pub fn train(&mut self, history: &[LabeledOutcome]) {
+        for labeled in history {
+            self.update(&labeled.decision, &labeled.outcome);
+        }
+    }
+
+    /// Value estimates for a decision kind
+    pub fn values(&self, kind: DecisionKind) -> &BTreeMap<String, ActionValues> {
+        match kind {
+            DecisionKind::Pricing => &self.pricing,
+            DecisionKind::CounterOffer => &self.counter_offer,
+        }
+    }
+
+    /// Serialize the policy
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Deserialize a policy, rejecting unknown format versions
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let policy: Self = serde_json::from_str(json).map_err(|e| format!("invalid policy: {}", e))?;
+        if policy.version != POLICY_VERSION {
+            return Err(format!("unsupported policy version {} (expected {})", policy.version, POLICY_VERSION));
+        }
+        Ok(policy)
+    }
+
+    /// Save the policy to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, self.to_json()? + "\n").map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    }
+
+    /// Load a policy from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&content)
+    }
+
+    fn choose(&mut self, kind: DecisionKind, context: &DecisionContext, neutral: usize) -> Decision {
+        let state = Self::state_key(context);
+        let actions = action_count(kind);
+        let explore = self.rng.next_f64() < self.epsilon();
+
+        let action = if explore {
+            (self.rng.next_u64() % actions as u64) as usize
+        } else {
+            self.values(kind).get(&state).map_or(neutral, |values| values.best(neutral))
+        };
+
+        Decision { kind, state, action, explored: explore }
+    }
+
+    fn table_mut(&mut self, kind: DecisionKind) -> &mut BTreeMap<String, ActionValues> {
+        match kind {
+            DecisionKind::Pricing => &mut self.pricing,
+            DecisionKind::CounterOffer => &mut self.counter_offer,
+        }
+    }
+}
+
+fn action_count(kind: DecisionKind) -> usize {
+    match kind {
+        DecisionKind::Pricing => PRICE_MULTIPLIERS.len(),
+        DecisionKind::CounterOffer => ACCEPTANCE_THRESHOLDS.len(),
+    }
+}
+
+/// Index of the option closest to a target
+fn nearest(options: &[f64], target: f64) -> usize {
+    (0..options.len())
+        .min_by(|&a, &b| (options[a] - target).abs().total_cmp(&(options[b] - target).abs()))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MarketConditions;
+
+    fn context(demand: f64) -> DecisionContext {
+        DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: demand,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        }
+    }
+
+    fn outcome(success: bool, profit_margin: f64) -> TransactionOutcome {
+        TransactionOutcome {
+            success,
+            profit_margin,
+            satisfaction_score: 0.8,
+            completion_time: 0,
+        }
+    }
+
+    fn greedy(policy: &mut NegotiationPolicy) {
+        policy.exploration.initial_epsilon = 0.0;
+        policy.exploration.min_epsilon = 0.0;
+    }
+
+    #[test]
+    fn test_untrained_policy_matches_heuristic() {
+        let mut policy = NegotiationPolicy::new(0.1, 1);
+        greedy(&mut policy);
+
+        let (price, _) = policy.choose_price(&context(0.5), 100.0);
+        assert_eq!(price, 100.0);
+        let (accepted, decision) = policy.choose_acceptance(&context(0.5), 79.0, 100.0, 0.8);
+        assert!(!accepted);
+        assert_eq!(ACCEPTANCE_THRESHOLDS[decision.action], 0.8);
+    }
+
+    #[test]
+    fn test_learns_profitable_multiplier() {
+        // High asks fail in this market, a 5% discount always closes
+        let mut policy = NegotiationPolicy::new(0.2, 42);
+        for _ in 0..2000 {
+            let (price, decision) = policy.choose_price(&context(0.2), 100.0);
+            let success = price <= 95.0;
+            policy.update(&decision, &outcome(success, (price - 80.0) / price));
+        }
+
+        greedy(&mut policy);
+        let (price, _) = policy.choose_price(&context(0.2), 100.0);
+        assert_eq!(price, 95.0);
+
+        // Other market states are untouched
+        assert_eq!(policy.choose_price(&context(0.9), 100.0).0, 100.0);
+    }
+
+    #[test]
+    fn test_policy_round_trips_through_json() {
+        let mut policy = NegotiationPolicy::new(0.1, 7);
+        let (_, decision) = policy.choose_price(&context(0.5), 100.0);
+        policy.update(&decision, &outcome(true, 0.2));
+
+        let restored = NegotiationPolicy::from_json(&policy.to_json().unwrap()).unwrap();
+        assert_eq!(restored.updates, 1);
+        assert_eq!(restored.values(DecisionKind::Pricing).len(), 1);
+        assert_eq!(restored.rng, policy.rng);
+
+        let mut wrong_version = policy.clone();
+        wrong_version.version = POLICY_VERSION + 1;
+        assert!(NegotiationPolicy::from_json(&wrong_version.to_json().unwrap()).is_err());
+    }
+}
@@ -4,7 +4,32 @@
 //! including decision-making, negotiation strategies, and learning capabilities.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+
+pub mod advisor;
+pub mod anomaly;
+pub mod attribution;
+pub mod concession;
+pub mod drift;
+pub mod forecast;
+pub mod learning;
+pub mod model;
+pub mod profile;
+pub mod risk;
+pub mod rng;
+pub mod sharing;
+pub mod simulation;
+pub mod strategy;
+pub mod transcript;
+pub mod urgency;
+
+use anomaly::{AnomalyAlert, AnomalyDetector};
+use forecast::{ForecastModel, Forecaster, PricePrediction};
+use learning::{Decision, LabeledOutcome, NegotiationPolicy};
+use model::ModelSnapshot;
+use profile::CounterpartyProfile;
+use risk::{Exposure, RiskBudget, RiskCheck};
+use sharing::MarketObservation;
+use urgency::{TimePressure, UrgencyPolicy};
 
 /// AI decision-making context
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +39,12 @@ pub struct DecisionContext {
     pub transaction_value: f64,
     pub market_conditions: MarketConditions,
     pub historical_performance: Vec<TransactionOutcome>,
+    /// Observed behavior of this counterparty, if known
+    #[serde(default)]
+    pub counterparty_profile: Option<CounterpartyProfile>,
+    /// How close the deadline and round limit are, if tracked
+    #[serde(default)]
+    pub time_pressure: Option<TimePressure>,
 }
 
 /// Market conditions that influence decision-making
@@ -42,12 +73,85 @@ pub struct TransactionOutcome {
     pub completion_time: u64,  // seconds
 }
 
+/// Governance price limits for a service
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceBounds {
+    pub floor: f64,
+    pub ceiling: f64,
+}
+
+impl PriceBounds {
+    /// Limits from `floor` to `ceiling`, or `None` if the floor is above the ceiling
+    pub fn new(floor: f64, ceiling: f64) -> Option<Self> {
+        (floor <= ceiling).then_some(Self { floor, ceiling })
+    }
+
+    /// Whether a price is within the limits
+    pub fn contains(&self, price: f64) -> bool {
+        price >= self.floor && price <= self.ceiling
+    }
+
+    /// Nearest price within the limits
+    pub fn clamp(&self, price: f64) -> f64 {
+        price.max(self.floor).min(self.ceiling)
+    }
+}
+
+/// Estimated cost of executing a service, in SOL
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionCost {
+    pub estimated: f64,
+    pub min_margin: f64,                  // Required margin over cost, as a fraction
+}
+
+impl ExecutionCost {
+    pub fn new(estimated: f64, min_margin: f64) -> Self {
+        Self { estimated, min_margin }
+    }
+
+    /// Lowest price that covers the cost plus the minimum margin
+    pub fn floor(&self) -> f64 {
+        self.estimated * (1.0 + self.min_margin)
+    }
+
+    /// Whether a price covers the cost plus the minimum margin
+    pub fn covered_by(&self, price: f64) -> bool {
+        price >= self.floor()
+    }
+}
+
+/// Why a counter-offer was accepted or refused
+///
+/// The acceptance threshold is the base threshold plus each adjustment,
+/// clamped; the offer must reach it and pass every veto.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionExplanation {
+    pub offer_ratio: f64,                 // Offer / our ask
+    pub base_threshold: f64,
+    pub reputation_adjustment: f64,
+    pub market_adjustment: f64,
+    pub urgency_adjustment: f64,          // Zero or negative: deadlines lower the bar
+    pub threshold: f64,
+    pub within_bounds: bool,
+    pub anomaly_veto: bool,
+    pub risk_check: RiskCheck,
+    pub accepted: bool,
+}
+
+/// Accept offers >= 80% of the asking price, before adjustments
+const BASE_ACCEPTANCE_THRESHOLD: f64 = 0.8;
+
 /// AI-powered negotiation strategy
 #[derive(Debug, Clone)]
 pub struct NegotiationAI {
     learning_rate: f64,
     risk_tolerance: f64,
     historical_data: Vec<TransactionOutcome>,
+    policy: Option<NegotiationPolicy>,
+    price_bounds: Option<PriceBounds>,
+    anomaly_detector: Option<AnomalyDetector>,
+    risk_budget: Option<RiskBudget>,
+    urgency: UrgencyPolicy,
 }
 
 impl NegotiationAI {
@@ -57,9 +161,113 @@ impl NegotiationAI {
             learning_rate,
             risk_tolerance,
             historical_data: Vec::new(),
+            policy: None,
+            price_bounds: None,
+            anomaly_detector: None,
+            risk_budget: None,
+            urgency: UrgencyPolicy::default(),
+        }
+    }
+
+    /// Veto transactions while risk indicators are anomalous
+    pub fn with_anomaly_detector(mut self, detector: AnomalyDetector) -> Self {
+        self.anomaly_detector = Some(detector);
+        self
+    }
+
+    /// Anomaly detector, if enabled
+    pub fn anomaly_detector(&self) -> Option<&AnomalyDetector> {
+        self.anomaly_detector.as_ref()
+    }
+
+    /// Feed the context's risk indicators to the anomaly detector
+    pub fn observe_risk(&mut self, context: &DecisionContext) -> Vec<AnomalyAlert> {
+        match self.anomaly_detector.as_mut() {
+            Some(detector) => detector.observe_all(&context.market_conditions.risk_indicators),
+            None => Vec::new(),
         }
     }
 
+    /// Whether active anomalies currently veto new transactions
+    pub fn anomaly_veto(&self) -> bool {
+        self.anomaly_detector.as_ref().is_some_and(|detector| detector.should_veto())
+    }
+
+    /// Refuse or reprice offers that would push portfolio value-at-risk over a limit
+    pub fn with_risk_budget(mut self, budget: RiskBudget) -> Self {
+        self.risk_budget = Some(budget);
+        self
+    }
+
+    /// Risk budget, if enabled
+    pub fn risk_budget(&self) -> Option<&RiskBudget> {
+        self.risk_budget.as_ref()
+    }
+
+    /// Risk budget for tracking and releasing exposures, if enabled
+    pub fn risk_budget_mut(&mut self) -> Option<&mut RiskBudget> {
+        self.risk_budget.as_mut()
+    }
+
+    /// Check a deal at `value` against the risk budget
+    pub fn check_risk(&self, context: &DecisionContext, value: f64) -> RiskCheck {
+        match &self.risk_budget {
+            Some(budget) => budget.check(&Exposure::for_context(context, value)),
+            None => RiskCheck::Within,
+        }
+    }
+
+    /// Lower the acceptance threshold along `policy` as deadlines approach
+    pub fn with_urgency(mut self, policy: UrgencyPolicy) -> Self {
+        self.urgency = policy;
+        self
+    }
+
+    /// Urgency policy applied to acceptance
+    pub fn urgency(&self) -> UrgencyPolicy {
+        self.urgency
+    }
+
+    /// Keep asks and accepted offers within governance price limits
+    pub fn with_price_bounds(mut self, bounds: PriceBounds) -> Self {
+        self.price_bounds = Some(bounds);
+        self
+    }
+
+    /// Replace or clear the governance price limits
+    pub fn set_price_bounds(&mut self, bounds: Option<PriceBounds>) {
+        self.price_bounds = bounds;
+    }
+
+    /// Current governance price limits
+    pub fn price_bounds(&self) -> Option<PriceBounds> {
+        self.price_bounds
+    }
+
+    fn bound_price(&self, price: f64) -> f64 {
+        self.price_bounds.map_or(price, |bounds| bounds.clamp(price))
+    }
+
+    fn within_bounds(&self, price: f64) -> bool {
+        self.price_bounds.is_none_or(|bounds| bounds.contains(price))
+    }
+
+    /// Use a learned policy for pricing and counter-offer decisions
+    pub fn with_policy(mut self, policy: NegotiationPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Start learning with a fresh policy
+    pub fn enable_learning(&mut self, seed: u64) {
+        self.policy = Some(NegotiationPolicy::new(self.learning_rate, seed));
+    }
+
+    /// Learned policy, if learning is enabled
+    pub fn policy(&self) -> Option<&NegotiationPolicy> {
+        self.policy.as_ref()
+    }
+
     /// Make a pricing decision based on context
     pub fn decide_pricing(&self, context: &DecisionContext, base_price: f64) -> f64 {
         let reputation_factor = self.calculate_reputation_factor(context);
@@ -68,16 +276,152 @@ impl NegotiationAI {
 
         let adjusted_price = base_price * reputation_factor * market_factor * risk_factor;
         
-        // Ensure price is within reasonable bounds
-        adjusted_price.max(base_price * 0.5).min(base_price * 2.0)
+        // Ensure price is within reasonable bounds, then governance limits
+        self.bound_price(adjusted_price.max(base_price * 0.5).min(base_price * 2.0))
+    }
+
+    /// Make a pricing decision that keeps a margin over execution cost
+    ///
+    /// The base price is raised to cover the cost plus the minimum margin
+    /// before market and reputation adjustments, and the result never drops
+    /// below that floor (governance limits still have the last word).
+    pub fn decide_pricing_with_cost(&self, context: &DecisionContext, base_price: f64, cost: &ExecutionCost) -> f64 {
+        let floor = cost.floor();
+        self.bound_price(self.decide_pricing(context, base_price.max(floor)).max(floor))
+    }
+
+    /// Make a pricing decision anchored on a price forecast
+    ///
+    /// The base price moves toward the forecast mean in proportion to the
+    /// forecast's confidence; a wide interval barely moves it. Uncertainty
+    /// also adds a premium that shrinks as risk tolerance grows.
+    pub fn decide_pricing_with_forecast(&self, context: &DecisionContext, base_price: f64, forecast: &PricePrediction) -> f64 {
+        let uncertainty = forecast.relative_uncertainty().min(1.0);
+        let confidence = 1.0 - uncertainty;
+        let anchored = base_price + (forecast.mean - base_price) * confidence * 0.5;
+        let premium = 1.0 + uncertainty * (1.0 - self.risk_tolerance) * 0.1;
+
+        self.bound_price(self.decide_pricing(context, anchored) * premium)
     }
 
     /// Decide whether to accept a counter-offer
     pub fn should_accept_counter_offer(&self, context: &DecisionContext, counter_offer: f64, original_ask: f64) -> bool {
-        let acceptance_threshold = self.calculate_acceptance_threshold(context);
+        self.explain_counter_offer(context, counter_offer, original_ask).accepted
+    }
+
+    /// Decide on a counter-offer, showing how the threshold was reached
+    pub fn explain_counter_offer(&self, context: &DecisionContext, counter_offer: f64, original_ask: f64) -> DecisionExplanation {
+        let (reputation_adjustment, market_adjustment, urgency_adjustment) = self.threshold_adjustments(context);
+        let threshold = Self::clamp_threshold(reputation_adjustment + market_adjustment + urgency_adjustment);
         let offer_ratio = counter_offer / original_ask;
-        
-        offer_ratio >= acceptance_threshold
+        let within_bounds = self.within_bounds(counter_offer);
+        let anomaly_veto = self.anomaly_veto();
+        let risk_check = self.check_risk(context, counter_offer);
+
+        DecisionExplanation {
+            offer_ratio,
+            base_threshold: BASE_ACCEPTANCE_THRESHOLD,
+            reputation_adjustment,
+            market_adjustment,
+            urgency_adjustment,
+            threshold,
+            within_bounds,
+            anomaly_veto,
+            risk_check,
+            accepted: !anomaly_veto && within_bounds && risk_check == RiskCheck::Within && offer_ratio >= threshold,
+        }
+    }
+
+    /// Make a pricing decision with the learned policy, if any
+    ///
+    /// The returned decision is passed back to `learn_from_decision` once the
+    /// transaction's outcome is known.
+    pub fn decide_pricing_learned(&mut self, context: &DecisionContext, base_price: f64) -> (f64, Option<Decision>) {
+        let heuristic_ask = self.decide_pricing(context, base_price);
+        match self.policy.as_mut() {
+            Some(policy) => {
+                let (ask, decision) = policy.choose_price(context, heuristic_ask);
+                (self.bound_price(ask.max(base_price * 0.5).min(base_price * 2.0)), Some(decision))
+            }
+            None => (heuristic_ask, None),
+        }
+    }
+
+    /// Decide on a counter-offer with the learned policy, if any
+    ///
+    /// As in `explain_counter_offer`, an offer over the risk budget is never
+    /// accepted and the policy is not consulted; `check_risk` gives the value
+    /// it could be repriced to.
+    pub fn should_accept_counter_offer_learned(
+        &mut self,
+        context: &DecisionContext,
+        counter_offer: f64,
+        original_ask: f64,
+    ) -> (bool, Option<Decision>) {
+        if self.check_risk(context, counter_offer) != RiskCheck::Within {
+            return (false, None);
+        }
+        let threshold = self.calculate_acceptance_threshold(context);
+        let within_bounds = self.within_bounds(counter_offer) && !self.anomaly_veto();
+        match self.policy.as_mut() {
+            Some(policy) => {
+                let (accepted, decision) = policy.choose_acceptance(context, counter_offer, original_ask, threshold);
+                (accepted && within_bounds, Some(decision))
+            }
+            None => (within_bounds && counter_offer / original_ask >= threshold, None),
+        }
+    }
+
+    /// Record an outcome and train the policy on the decision that led to it
+    pub fn learn_from_decision(&mut self, decision: &Decision, outcome: TransactionOutcome) {
+        if let Some(policy) = self.policy.as_mut() {
+            policy.update(decision, &outcome);
+        }
+        self.learn_from_outcome(outcome);
+    }
+
+    /// Train the policy from a recorded history of decisions and outcomes
+    pub fn train_from_history(&mut self, history: &[LabeledOutcome]) {
+        for labeled in history {
+            self.learn_from_decision(&labeled.decision, labeled.outcome.clone());
+        }
+    }
+
+    /// Persist the learned policy
+    pub fn save_policy(&self, path: impl AsRef<std::path::Path>) -> Result<(), String> {
+        match &self.policy {
+            Some(policy) => policy.save(path),
+            None => Err("learning is not enabled".to_string()),
+        }
+    }
+
+    /// Reload a persisted policy
+    pub fn load_policy(&mut self, path: impl AsRef<std::path::Path>) -> Result<(), String> {
+        self.policy = Some(NegotiationPolicy::load(path)?);
+        Ok(())
+    }
+
+    /// Export the learned policy and outcome history in the versioned
+    /// binary model format
+    pub fn export_model(&self) -> Result<Vec<u8>, String> {
+        ModelSnapshot {
+            learning_rate: self.learning_rate,
+            policy: self.policy.clone(),
+            history: self.historical_data.clone(),
+        }
+        .encode()
+    }
+
+    /// Replace what this AI has learned with an exported model
+    ///
+    /// Risk tolerance, bounds, and budgets are local configuration and stay
+    /// as they are.
+    pub fn import_model(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let snapshot = ModelSnapshot::decode(bytes)?;
+        self.learning_rate = snapshot.learning_rate;
+        self.policy = snapshot.policy;
+        self.historical_data = snapshot.history;
+        Ok(())
     }
 
     /// Update the AI model with new transaction outcomes
@@ -91,11 +435,21 @@ impl NegotiationAI {
     }
 
     /// Calculate reputation-based pricing factor
+    ///
+    /// The counterparty's own track record takes over from the global
+    /// reputation score as its profile accumulates history.
     fn calculate_reputation_factor(&self, context: &DecisionContext) -> f64 {
         let reputation_diff = context.agent_reputation - context.counterparty_reputation;
         
         // Higher reputation allows for premium pricing
-        1.0 + (reputation_diff * 0.2)
+        let reputation_factor = 1.0 + (reputation_diff * 0.2);
+        match &context.counterparty_profile {
+            Some(profile) => {
+                let weight = profile.confidence();
+                reputation_factor * (1.0 - weight) + profile.pricing_factor() * weight
+            }
+            None => reputation_factor,
+        }
     }
 
     /// Calculate market condition factor
@@ -119,11 +473,26 @@ impl NegotiationAI {
 
     /// Calculate the minimum acceptable offer ratio
     fn calculate_acceptance_threshold(&self, context: &DecisionContext) -> f64 {
-        let base_threshold = 0.8; // Accept offers >= 80% of asking price
-        let reputation_adjustment = (context.counterparty_reputation - 0.5) * 0.2;
+        let (reputation_adjustment, market_adjustment, urgency_adjustment) = self.threshold_adjustments(context);
+        Self::clamp_threshold(reputation_adjustment + market_adjustment + urgency_adjustment)
+    }
+
+    /// Reputation, market, and urgency adjustments to the base threshold
+    fn threshold_adjustments(&self, context: &DecisionContext) -> (f64, f64, f64) {
+        let mut reputation_adjustment = (context.counterparty_reputation - 0.5) * 0.2;
+        if let Some(profile) = &context.counterparty_profile {
+            let weight = profile.confidence();
+            reputation_adjustment = reputation_adjustment * (1.0 - weight) + profile.threshold_shift() * weight;
+        }
         let market_adjustment = (context.market_conditions.demand_level - 0.5) * 0.1;
-        
-        (base_threshold + reputation_adjustment + market_adjustment).clamp(0.6, 0.95)
+        // Approaching deadlines make a smaller deal better than none
+        let urgency_adjustment = -context.time_pressure.map_or(0.0, |pressure| self.urgency.relief(&pressure));
+
+        (reputation_adjustment, market_adjustment, urgency_adjustment)
+    }
+
+    fn clamp_threshold(adjustment: f64) -> f64 {
+        (BASE_ACCEPTANCE_THRESHOLD + adjustment).clamp(0.6, 0.95)
     }
 
     /// Get success rate from historical data
@@ -148,21 +517,94 @@ impl NegotiationAI {
 }
 
 /// Predictive market analysis using simple statistical methods
+#[derive(Debug, Clone)]
 pub struct MarketPredictor {
     price_history: Vec<f64>,
     demand_history: Vec<f64>,
+    model: ForecastModel,
+    stale: bool,                                 // History changed since the model was fitted
+    local_observations: Vec<MarketObservation>,  // Our own data points, for sharing
+    next_sequence: u64,
+}
+
+impl Default for MarketPredictor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MarketPredictor {
     pub fn new() -> Self {
+        Self::with_model(ForecastModel::default())
+    }
+
+    /// Forecast prices with a specific model, e.g. one loaded from disk
+    pub fn with_model(model: ForecastModel) -> Self {
         Self {
             price_history: Vec::new(),
             demand_history: Vec::new(),
+            model,
+            stale: false,
+            local_observations: Vec::new(),
+            next_sequence: 0,
+        }
+    }
+
+    /// Forecasting model, fitted to the current history
+    pub fn model(&mut self) -> &ForecastModel {
+        self.refit();
+        &self.model
+    }
+
+    /// Forecast the price `horizon` data points ahead
+    pub fn forecast(&mut self, horizon: usize) -> Option<PricePrediction> {
+        self.refit();
+        self.model.forecast(horizon)
+    }
+
+    /// Fit the model to the history once, when a forecast needs it,
+    /// rather than on every data point
+    fn refit(&mut self) {
+        if self.stale {
+            self.model.fit(&self.price_history);
+            self.stale = false;
         }
     }
 
     /// Add new market data point
     pub fn add_data_point(&mut self, price: f64, demand: f64) {
+        self.local_observations.push(MarketObservation { sequence: self.next_sequence, price, demand });
+        self.next_sequence += 1;
+        if self.local_observations.len() > 100 {
+            self.local_observations.remove(0);
+        }
+        self.record(price, demand);
+    }
+
+    /// Add a data point observed by another agent, trusted by `weight`
+    ///
+    /// The point is pulled toward our latest price in proportion to the
+    /// distrust, so a fully trusted source enters as-is and an untrusted one
+    /// barely moves the model.
+    pub fn add_shared_point(&mut self, price: f64, demand: f64, weight: f64) {
+        let weight = weight.clamp(0.0, 1.0);
+        let (level, demand_level) = match (self.price_history.last(), self.demand_history.last()) {
+            (Some(&level), Some(&demand_level)) => (level, demand_level),
+            _ => (price, demand),
+        };
+        self.record(level + (price - level) * weight, demand_level + (demand - demand_level) * weight);
+    }
+
+    /// Our own observations with a sequence number after `after`
+    pub fn local_observations_since(&self, after: Option<u64>) -> Vec<MarketObservation> {
+        self.local_observations
+            .iter()
+            .filter(|observation| after.is_none_or(|after| observation.sequence > after))
+            .copied()
+            .collect()
+    }
+
+    fn record(&mut self, price: f64, demand: f64) {
         self.price_history.push(price);
         self.demand_history.push(demand);
 
@@ -171,6 +613,7 @@ impl MarketPredictor {
             self.price_history.remove(0);
             self.demand_history.remove(0);
         }
+        self.stale = true;
     }
 
     /// Predict future price trend
@@ -179,14 +622,10 @@ impl MarketPredictor {
             return PriceTrend::Stable;
         }
 
-        let recent_prices: Vec<f64> = self.price_history
-            .iter()
-            .rev()
-            .take(5)
-            .cloned()
-            .collect();
+        // The last five prices, oldest first, so a positive slope is a rise
+        let recent_prices = &self.price_history[self.price_history.len().saturating_sub(5)..];
 
-        let trend = self.calculate_linear_trend(&recent_prices);
+        let trend = self.calculate_linear_trend(recent_prices);
 
         if trend > 0.05 {
             PriceTrend::Rising
@@ -247,16 +686,333 @@ mod tests {
                 risk_indicators: vec![],
             },
             historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
         };
 
         let price = ai.decide_pricing(&context, 100.0);
         assert!(price > 50.0 && price < 200.0);
     }
 
+    #[test]
+    fn test_counterparty_profile_adjusts_pricing() {
+        let ai = NegotiationAI::new(0.1, 0.6);
+        let mut context = DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        let unknown_price = ai.decide_pricing(&context, 100.0);
+        assert!(ai.should_accept_counter_offer(&context, 85.0, 100.0));
+
+        let mut profiles = profile::CounterpartyProfiles::new();
+        for _ in 0..20 {
+            profiles.record_negotiation("deadbeat", true, 4);
+            profiles.record_settlement("deadbeat", false);
+        }
+        context.counterparty_profile = profiles.get("deadbeat").cloned();
+
+        assert!(ai.decide_pricing(&context, 100.0) > unknown_price);
+        assert!(!ai.should_accept_counter_offer(&context, 85.0, 100.0));
+    }
+
+    #[test]
+    fn test_pricing_covers_execution_cost() {
+        let ai = NegotiationAI::new(0.1, 0.6);
+        let context = DecisionContext {
+            agent_reputation: 0.5,
+            counterparty_reputation: 0.9,
+            transaction_value: 10.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.0,
+                competition_level: 1.0,
+                average_pricing: 10.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        let cost = ExecutionCost::new(12.0, 0.25);
+
+        assert!(ai.decide_pricing(&context, 10.0) < cost.floor());
+        let price = ai.decide_pricing_with_cost(&context, 10.0, &cost);
+        assert!(cost.covered_by(price));
+        assert_eq!(price, 15.0);
+    }
+
+    #[test]
+    fn test_price_bounds_enforced() {
+        assert!(PriceBounds::new(110.0, 90.0).is_none());
+        let ai =
+            NegotiationAI::new(0.1, 0.6).with_price_bounds(PriceBounds::new(90.0, 110.0).unwrap());
+        let context = DecisionContext {
+            agent_reputation: 0.9,
+            counterparty_reputation: 0.9,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 1.0,
+                competition_level: 0.0,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+
+        assert!(ai.decide_pricing(&context, 100.0) <= 110.0);
+        assert!(ai.decide_pricing(&context, 10.0) >= 90.0);
+        assert!(!ai.should_accept_counter_offer(&context, 85.0, 86.0));
+        assert!(ai.should_accept_counter_offer(&context, 100.0, 100.0));
+    }
+
+    #[test]
+    fn test_deadlines_lower_the_acceptance_threshold() {
+        let ai = NegotiationAI::new(0.1, 0.5);
+        let mut context = DecisionContext {
+            agent_reputation: 0.5,
+            counterparty_reputation: 0.5,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        let relaxed = ai.explain_counter_offer(&context, 72.0, 100.0);
+        assert!(!relaxed.accepted);
+        assert_eq!(relaxed.urgency_adjustment, 0.0);
+
+        context.time_pressure = Some(TimePressure::from_deadline(48.0, 60.0));
+        let pressed = ai.explain_counter_offer(&context, 72.0, 100.0);
+        assert!(pressed.accepted);
+        assert!((pressed.urgency_adjustment + 0.12).abs() < 1e-9);
+        assert!((pressed.threshold - 0.68).abs() < 1e-9);
+
+        let patient = ai.with_urgency(UrgencyPolicy {
+            curve: urgency::UrgencyCurve::Step { at: 0.9 },
+            max_relief: 0.15,
+        });
+        assert!(!patient.should_accept_counter_offer(&context, 72.0, 100.0));
+    }
+
+    #[test]
+    fn test_forecast_weighted_pricing() {
+        let ai = NegotiationAI::new(0.1, 0.5);
+        let context = DecisionContext {
+            agent_reputation: 0.5,
+            counterparty_reputation: 0.5,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+
+        let confident = PricePrediction {
+            horizon: 1,
+            mean: 120.0,
+            lower: 118.0,
+            upper: 122.0,
+            std_error: 1.0,
+        };
+        let uncertain = PricePrediction {
+            horizon: 1,
+            mean: 120.0,
+            lower: 20.0,
+            upper: 220.0,
+            std_error: 51.0,
+        };
+        let plain = ai.decide_pricing(&context, 100.0);
+        assert!(ai.decide_pricing_with_forecast(&context, 100.0, &confident) > plain * 1.05);
+        assert!(ai.decide_pricing_with_forecast(&context, 100.0, &uncertain) < plain * 1.06);
+
+        let mut predictor = MarketPredictor::with_model(ForecastModel::holt_winters(4));
+        for i in 0..12 {
+            predictor.add_data_point(100.0 + i as f64, 0.5);
+        }
+        assert!(predictor.forecast(1).unwrap().mean > 108.0);
+    }
+
+    #[test]
+    fn test_anomalies_veto_acceptance() {
+        let mut ai = NegotiationAI::new(0.1, 0.5).with_anomaly_detector(AnomalyDetector::default());
+        let mut context = DecisionContext {
+            agent_reputation: 0.5,
+            counterparty_reputation: 0.5,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+
+        for i in 0..20 {
+            context.market_conditions.risk_indicators = vec![
+                RiskIndicator {
+                    indicator_type: "volatility".to_string(),
+                    value: 0.2 + (i % 3) as f64 * 0.01,
+                    confidence: 1.0,
+                },
+                RiskIndicator {
+                    indicator_type: "slippage".to_string(),
+                    value: 0.1 + (i % 2) as f64 * 0.01,
+                    confidence: 1.0,
+                },
+            ];
+            assert!(ai.observe_risk(&context).is_empty());
+        }
+        assert!(ai.should_accept_counter_offer(&context, 100.0, 100.0));
+
+        context.market_conditions.risk_indicators[0].value = 0.9;
+        context.market_conditions.risk_indicators[1].value = 0.6;
+        assert_eq!(ai.observe_risk(&context).len(), 2);
+        assert!(ai.anomaly_veto());
+        assert!(!ai.should_accept_counter_offer(&context, 100.0, 100.0));
+    }
+
+    #[test]
+    fn test_learned_acceptance_respects_risk_budget() {
+        let mut ai = NegotiationAI::new(0.1, 0.6).with_risk_budget(RiskBudget::new(150.0));
+        ai.enable_learning(5);
+        ai.risk_budget_mut()
+            .unwrap()
+            .add("open", Exposure::new(200.0, 0.1));
+        let context = DecisionContext {
+            agent_reputation: 0.8,
+            counterparty_reputation: 0.5,
+            transaction_value: 150.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 150.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+
+        // A full-price offer the budget cannot carry is refused, with a reprice on offer
+        assert_eq!(
+            ai.should_accept_counter_offer_learned(&context, 150.0, 150.0),
+            (false, None)
+        );
+        let RiskCheck::Reprice(max_value) = ai.check_risk(&context, 150.0) else {
+            panic!("expected a reprice");
+        };
+        assert!(ai
+            .should_accept_counter_offer_learned(&context, max_value, max_value)
+            .1
+            .is_some());
+
+        assert_eq!(
+            ai.should_accept_counter_offer_learned(&context, 1_000.0, 1_000.0),
+            (false, None)
+        );
+    }
+
+    #[test]
+    fn test_learned_policy_persists() {
+        let mut ai = NegotiationAI::new(0.1, 0.6);
+        ai.enable_learning(3);
+        let context = DecisionContext {
+            agent_reputation: 0.8,
+            counterparty_reputation: 0.6,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.7,
+                competition_level: 0.4,
+                average_pricing: 95.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+
+        let (_, decision) = ai.decide_pricing_learned(&context, 100.0);
+        ai.learn_from_decision(
+            &decision.unwrap(),
+            TransactionOutcome {
+                success: true,
+                profit_margin: 0.2,
+                satisfaction_score: 0.9,
+                completion_time: 60,
+            },
+        );
+
+        let path = std::env::temp_dir().join(format!("solace-policy-{}.json", std::process::id()));
+        ai.save_policy(&path).unwrap();
+        let mut restored = NegotiationAI::new(0.1, 0.6);
+        restored.load_policy(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(restored.policy().unwrap().updates, 1);
+        assert_eq!(ai.get_success_rate(), 1.0);
+    }
+
+    #[test]
+    fn test_model_export_round_trips() {
+        let mut trained = NegotiationAI::new(0.2, 0.6);
+        trained.enable_learning(5);
+        let history: Vec<LabeledOutcome> = (0..3)
+            .map(|i| LabeledOutcome {
+                decision: Decision {
+                    kind: learning::DecisionKind::Pricing,
+                    state: "r1-d1-c1-v2".to_string(),
+                    action: i,
+                    explored: false,
+                },
+                outcome: TransactionOutcome {
+                    success: i != 1,
+                    profit_margin: 0.1,
+                    satisfaction_score: 0.8,
+                    completion_time: 30,
+                },
+            })
+            .collect();
+        trained.train_from_history(&history);
+
+        let bytes = trained.export_model().unwrap();
+        let mut fleet_member = NegotiationAI::new(0.05, 0.9);
+        fleet_member.import_model(&bytes).unwrap();
+
+        assert_eq!(fleet_member.learning_rate, 0.2);
+        assert_eq!(fleet_member.risk_tolerance, 0.9);
+        assert_eq!(fleet_member.policy().unwrap().updates, 3);
+        assert_eq!(fleet_member.get_success_rate(), trained.get_success_rate());
+        assert!(fleet_member.import_model(&bytes[..8]).is_err());
+    }
+
     #[test]
     fn test_market_predictor() {
         let mut predictor = MarketPredictor::new();
-        
+
         // Add some rising price data
         for i in 0..10 {
             predictor.add_data_point(100.0 + i as f64 * 2.0, 0.5);
@@ -265,4 +1021,23 @@ mod tests {
         let trend = predictor.predict_price_trend();
         assert_eq!(trend, PriceTrend::Rising);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_price_trend_follows_the_latest_prices() {
+        // Rose for a long time, then fell over the last five points
+        let mut predictor = MarketPredictor::new();
+        for i in 0..10 {
+            predictor.add_data_point(100.0 + i as f64 * 2.0, 0.5);
+        }
+        for i in 1..=5 {
+            predictor.add_data_point(118.0 - i as f64 * 3.0, 0.5);
+        }
+        assert_eq!(predictor.predict_price_trend(), PriceTrend::Falling);
+
+        let mut flat = MarketPredictor::new();
+        for _ in 0..5 {
+            flat.add_data_point(100.0, 0.5);
+        }
+        assert_eq!(flat.predict_price_trend(), PriceTrend::Stable);
+    }
+}
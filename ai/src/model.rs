@@ -0,0 +1,98 @@
+//! Model Export Format
+//!
+//! What a `NegotiationAI` has learned (its learning rate, learned policy, and
+//! outcome history) is exported as a compact binary blob so one agent's
+//! training can be distributed across a fleet. The layout is a fixed header
+//! followed by a bincode body:
+//!
+//! ```text
+//! magic "SLCM" (4 bytes) | schema version (u16, little endian) | bincode(ModelSnapshot)
+//! ```
+//!
+//! The schema version is bumped whenever `ModelSnapshot` changes shape;
+//! readers reject versions they do not know instead of misreading them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::learning::{NegotiationPolicy, POLICY_VERSION};
+use crate::TransactionOutcome;
+
+/// Leading bytes of every exported model
+pub const MODEL_MAGIC: [u8; 4] = *b"SLCM";
+
+/// Current layout of `ModelSnapshot`
+pub const MODEL_SCHEMA_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = MODEL_MAGIC.len() + 2;
+
+/// Learned state of a `NegotiationAI`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelSnapshot {
+    pub learning_rate: f64,
+    pub policy: Option<NegotiationPolicy>,
+    pub history: Vec<TransactionOutcome>,
+}
+
+impl ModelSnapshot {
+    /// Encode with the versioned header
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let body = bincode::serialize(self).map_err(|e| format!("failed to encode model: {}", e))?;
+        let mut bytes = Vec::with_capacity(HEADER_LEN + body.len());
+        bytes.extend_from_slice(&MODEL_MAGIC);
+        bytes.extend_from_slice(&MODEL_SCHEMA_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    /// Decode, rejecting foreign data and unknown schema versions
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let version = schema_version(bytes)?;
+        if version != MODEL_SCHEMA_VERSION {
+            return Err(format!("unsupported model schema version {} (expected {})", version, MODEL_SCHEMA_VERSION));
+        }
+
+        let snapshot: Self = bincode::deserialize(&bytes[HEADER_LEN..]).map_err(|e| format!("invalid model: {}", e))?;
+        if let Some(policy) = &snapshot.policy {
+            if policy.version != POLICY_VERSION {
+                return Err(format!("unsupported policy version {} (expected {})", policy.version, POLICY_VERSION));
+            }
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Schema version of an exported model, read from its header
+pub fn schema_version(bytes: &[u8]) -> Result<u16, String> {
+    if bytes.len() < HEADER_LEN || bytes[..MODEL_MAGIC.len()] != MODEL_MAGIC {
+        return Err("not a Solace model export".to_string());
+    }
+    Ok(u16::from_le_bytes([bytes[4], bytes[5]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_guards_the_body() {
+        let snapshot = ModelSnapshot {
+            learning_rate: 0.1,
+            policy: Some(NegotiationPolicy::new(0.1, 7)),
+            history: vec![TransactionOutcome {
+                success: true,
+                profit_margin: 0.2,
+                satisfaction_score: 0.9,
+                completion_time: 60,
+            }],
+        };
+        let mut bytes = snapshot.encode().unwrap();
+        assert_eq!(&bytes[..4], b"SLCM");
+        assert_eq!(schema_version(&bytes), Ok(MODEL_SCHEMA_VERSION));
+        assert_eq!(ModelSnapshot::decode(&bytes).unwrap().history.len(), 1);
+
+        assert!(ModelSnapshot::decode(b"{\"policy\": null}").is_err());
+        assert!(ModelSnapshot::decode(&bytes[..HEADER_LEN + 3]).is_err());
+        bytes[4] = 99;
+        assert!(ModelSnapshot::decode(&bytes).unwrap_err().contains("schema version 99"));
+    }
+}
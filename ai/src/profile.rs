@@ -0,0 +1,205 @@
+//! Counterparty Profiles
+//!
+//! Per-counterparty behavior observed across negotiations, keyed by the
+//! counterparty's ID string so the store stays independent of the
+//! framework's identity types. Profiles record how quickly a counterparty
+//! answers and how often it goes silent, which matchmaking uses to prefer
+//! responsive partners, and how its deals end: how often negotiations close,
+//! how many rounds it haggles, and how often it defaults after agreeing.
+//!
+//! A profile snapshot travels in `DecisionContext::counterparty_profile`;
+//! once it holds enough history, its pricing and acceptance adjustments take
+//! over from the generic reputation-based ones.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Negotiations after which a profile's adjustments carry half the weight
+const CONFIDENCE_HALF_LIFE: f64 = 5.0;
+
+/// Observed behavior of one counterparty
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CounterpartyProfile {
+    pub responses: u64,
+    pub total_response_secs: f64,
+    pub timeouts: u64,                    // Rounds the counterparty let expire
+    pub negotiations: u64,                // Negotiations that ran to a decision
+    pub agreements: u64,
+    pub total_rounds: u64,                // Haggling rounds across all negotiations
+    pub settlements: u64,                 // Agreed deals that came due for payment
+    pub defaults: u64,                    // Agreed deals left unpaid
+}
+
+impl CounterpartyProfile {
+    /// Mean seconds to answer, if the counterparty ever answered
+    pub fn average_response_secs(&self) -> Option<f64> {
+        (self.responses > 0).then(|| self.total_response_secs / self.responses as f64)
+    }
+
+    /// Share of rounds answered before the deadline
+    pub fn responsiveness(&self) -> f64 {
+        let rounds = self.responses + self.timeouts;
+        if rounds == 0 {
+            1.0
+        } else {
+            self.responses as f64 / rounds as f64
+        }
+    }
+
+    /// Share of negotiations that ended in agreement
+    pub fn acceptance_rate(&self) -> Option<f64> {
+        (self.negotiations > 0).then(|| self.agreements as f64 / self.negotiations as f64)
+    }
+
+    /// Mean haggling rounds per negotiation
+    pub fn average_haggle_depth(&self) -> Option<f64> {
+        (self.negotiations > 0).then(|| self.total_rounds as f64 / self.negotiations as f64)
+    }
+
+    /// Share of agreed deals the counterparty failed to pay
+    pub fn default_rate(&self) -> Option<f64> {
+        (self.settlements > 0).then(|| self.defaults as f64 / self.settlements as f64)
+    }
+
+    /// How much weight the observed history deserves, from 0.0 to 1.0
+    pub fn confidence(&self) -> f64 {
+        let n = self.negotiations as f64;
+        n / (n + CONFIDENCE_HALF_LIFE)
+    }
+
+    /// Price multiplier earned by this counterparty's track record
+    ///
+    /// Defaulters pay a risk premium, counterparties that take most deals
+    /// bear a small premium, and ones that rarely close get a discount.
+    /// Deep hagglers get a higher opening anchor to leave room to concede.
+    pub fn pricing_factor(&self) -> f64 {
+        let default_premium = self.default_rate().unwrap_or(0.0) * 0.3;
+        let acceptance = (self.acceptance_rate().unwrap_or(0.5) - 0.5) * 0.1;
+        let haggle_anchor = (self.average_haggle_depth().unwrap_or(0.0) - 2.0).clamp(0.0, 5.0) * 0.02;
+        (1.0 + default_premium + acceptance + haggle_anchor).clamp(0.85, 1.3)
+    }
+
+    /// Shift in the minimum acceptable offer ratio for this counterparty
+    ///
+    /// Defaulters must come closer to the ask; counterparties that rarely
+    /// close are met a little sooner; hagglers are held firmer because they
+    /// will counter again.
+    pub fn threshold_shift(&self) -> f64 {
+        let default_shift = self.default_rate().unwrap_or(0.0) * 0.2;
+        let closing_shift = -(0.5 - self.acceptance_rate().unwrap_or(0.5)).max(0.0) * 0.1;
+        let haggle_shift = (self.average_haggle_depth().unwrap_or(0.0) - 2.0).clamp(0.0, 5.0) * 0.01;
+        default_shift + closing_shift + haggle_shift
+    }
+}
+
+/// Profiles of every counterparty seen
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CounterpartyProfiles {
+    profiles: HashMap<String, CounterpartyProfile>,
+}
+
+impl CounterpartyProfiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Profile of a counterparty, if seen
+    pub fn get(&self, counterparty: &str) -> Option<&CounterpartyProfile> {
+        self.profiles.get(counterparty)
+    }
+
+    /// Record an answer that arrived after `secs`
+    pub fn record_response(&mut self, counterparty: &str, secs: f64) {
+        let profile = self.profiles.entry(counterparty.to_string()).or_default();
+        profile.responses += 1;
+        profile.total_response_secs += secs.max(0.0);
+    }
+
+    /// Record a round the counterparty let expire
+    pub fn record_timeout(&mut self, counterparty: &str) {
+        self.profiles.entry(counterparty.to_string()).or_default().timeouts += 1;
+    }
+
+    /// Record a finished negotiation and how many rounds it took
+    pub fn record_negotiation(&mut self, counterparty: &str, agreed: bool, rounds: u32) {
+        let profile = self.profiles.entry(counterparty.to_string()).or_default();
+        profile.negotiations += 1;
+        profile.total_rounds += rounds as u64;
+        if agreed {
+            profile.agreements += 1;
+        }
+    }
+
+    /// Record whether the counterparty paid for an agreed deal
+    pub fn record_settlement(&mut self, counterparty: &str, paid: bool) {
+        let profile = self.profiles.entry(counterparty.to_string()).or_default();
+        profile.settlements += 1;
+        if !paid {
+            profile.defaults += 1;
+        }
+    }
+
+    /// Mean seconds a counterparty takes to answer
+    pub fn average_response_secs(&self, counterparty: &str) -> Option<f64> {
+        self.get(counterparty)?.average_response_secs()
+    }
+
+    /// Counterparties ordered for matchmaking: most responsive first, then fastest
+    pub fn rank_by_responsiveness<'a>(&self, candidates: &[&'a str]) -> Vec<&'a str> {
+        let mut ranked = candidates.to_vec();
+        let key = |id: &str| {
+            let profile = self.get(id).cloned().unwrap_or_default();
+            (profile.responsiveness(), profile.average_response_secs().unwrap_or(f64::MAX))
+        };
+        ranked.sort_by(|a, b| {
+            let (a_rate, a_secs) = key(a);
+            let (b_rate, b_secs) = key(b);
+            b_rate.total_cmp(&a_rate).then(a_secs.total_cmp(&b_secs))
+        });
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_times_and_ranking() {
+        let mut profiles = CounterpartyProfiles::new();
+        profiles.record_response("fast", 2.0);
+        profiles.record_response("fast", 4.0);
+        profiles.record_response("slow", 30.0);
+        profiles.record_response("flaky", 1.0);
+        profiles.record_timeout("flaky");
+
+        assert_eq!(profiles.average_response_secs("fast"), Some(3.0));
+        assert_eq!(profiles.get("flaky").unwrap().responsiveness(), 0.5);
+        assert_eq!(profiles.average_response_secs("unknown"), None);
+        assert_eq!(profiles.rank_by_responsiveness(&["flaky", "slow", "fast"]), vec!["fast", "slow", "flaky"]);
+    }
+
+    #[test]
+    fn test_outcome_aggregates_and_adjustments() {
+        let mut profiles = CounterpartyProfiles::new();
+        for i in 0..10 {
+            profiles.record_negotiation("reliable", true, 2);
+            profiles.record_settlement("reliable", true);
+            profiles.record_negotiation("deadbeat", i % 2 == 0, 6);
+            if i % 2 == 0 {
+                profiles.record_settlement("deadbeat", i % 4 != 0);
+            }
+        }
+
+        let reliable = profiles.get("reliable").unwrap();
+        let deadbeat = profiles.get("deadbeat").unwrap();
+        assert_eq!(reliable.acceptance_rate(), Some(1.0));
+        assert_eq!(deadbeat.average_haggle_depth(), Some(6.0));
+        assert_eq!(deadbeat.default_rate(), Some(0.6));
+        assert!(deadbeat.pricing_factor() > reliable.pricing_factor());
+        assert!(deadbeat.threshold_shift() > reliable.threshold_shift());
+        assert!(reliable.confidence() > 0.5);
+        assert_eq!(CounterpartyProfile::default().confidence(), 0.0);
+    }
+}
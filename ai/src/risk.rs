@@ -0,0 +1,235 @@
+//! Portfolio Risk Budget
+//!
+//! Negotiations are decided one deal at a time, but losses arrive across the
+//! whole book. `RiskBudget` tracks the exposure of every active transaction,
+//! each a value that is lost if the counterparty defaults, and estimates the
+//! portfolio's value-at-risk with a normal approximation:
+//!
+//! ```text
+//! VaR = Σ vᵢ·pᵢ + z · √(Σ vᵢ²·pᵢ·(1 − pᵢ))
+//! ```
+//!
+//! treating defaults as independent. A new deal is admitted only if the VaR
+//! with it stays under the configured limit; otherwise the largest value that
+//! still fits is offered as a reprice, or the deal is rejected outright.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::DecisionContext;
+
+/// z-score for a 95% one-sided value-at-risk
+pub const Z_95: f64 = 1.645;
+
+/// Smallest share of a deal worth repricing to rather than rejecting
+const MIN_REPRICE_FRACTION: f64 = 0.5;
+
+/// Value at stake in one transaction
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Exposure {
+    pub value: f64,
+    pub default_probability: f64,
+}
+
+impl Exposure {
+    pub fn new(value: f64, default_probability: f64) -> Self {
+        Self {
+            value: value.max(0.0),
+            default_probability: default_probability.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Exposure to a counterparty, estimating its default probability from
+    /// its observed default rate, falling back on reputation until the
+    /// profile has history
+    pub fn for_context(context: &DecisionContext, value: f64) -> Self {
+        let prior = (1.0 - context.counterparty_reputation).clamp(0.0, 1.0) * 0.2;
+        let probability = match &context.counterparty_profile {
+            Some(profile) => {
+                let weight = profile.confidence();
+                prior * (1.0 - weight) + profile.default_rate().unwrap_or(prior) * weight
+            }
+            None => prior,
+        };
+        Self::new(value, probability)
+    }
+
+    fn expected_loss(&self) -> f64 {
+        self.value * self.default_probability
+    }
+
+    fn variance(&self) -> f64 {
+        self.value.powi(2) * self.default_probability * (1.0 - self.default_probability)
+    }
+}
+
+/// Outcome of checking a deal against the risk budget
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RiskCheck {
+    Within,
+    Reprice(f64),                         // Largest value the budget can carry
+    Reject,
+}
+
+/// Value-at-risk limit across an agent's active transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskBudget {
+    pub var_limit: f64,
+    pub z: f64,                           // Confidence level as a one-sided z-score
+    exposures: BTreeMap<String, Exposure>,
+}
+
+impl RiskBudget {
+    pub fn new(var_limit: f64) -> Self {
+        Self {
+            var_limit,
+            z: Z_95,
+            exposures: BTreeMap::new(),
+        }
+    }
+
+    /// Budget that admits every deal
+    pub fn unlimited() -> Self {
+        Self::new(f64::MAX)
+    }
+
+    /// Use a different confidence level
+    pub fn with_z(mut self, z: f64) -> Self {
+        self.z = z;
+        self
+    }
+
+    /// Track a transaction's exposure, replacing any previous entry
+    pub fn add(&mut self, transaction_id: impl Into<String>, exposure: Exposure) {
+        self.exposures.insert(transaction_id.into(), exposure);
+    }
+
+    /// Stop tracking a settled or abandoned transaction
+    pub fn remove(&mut self, transaction_id: &str) -> Option<Exposure> {
+        self.exposures.remove(transaction_id)
+    }
+
+    /// Number of tracked transactions
+    pub fn len(&self) -> usize {
+        self.exposures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exposures.is_empty()
+    }
+
+    /// Total value across tracked transactions
+    pub fn total_exposure(&self) -> f64 {
+        self.exposures.values().map(|exposure| exposure.value).sum()
+    }
+
+    /// Value-at-risk of the tracked transactions
+    pub fn value_at_risk(&self) -> f64 {
+        self.value_at_risk_with(None)
+    }
+
+    /// Value-at-risk if `extra` were added
+    pub fn value_at_risk_with(&self, extra: Option<&Exposure>) -> f64 {
+        let exposures = self.exposures.values().chain(extra);
+        let (expected, variance) = exposures.fold((0.0, 0.0), |(expected, variance), exposure| {
+            (expected + exposure.expected_loss(), variance + exposure.variance())
+        });
+        expected + self.z * variance.sqrt()
+    }
+
+    /// Remaining value-at-risk before the limit
+    pub fn headroom(&self) -> f64 {
+        (self.var_limit - self.value_at_risk()).max(0.0)
+    }
+
+    /// Whether a new deal keeps the portfolio within the limit
+    pub fn admits(&self, exposure: &Exposure) -> bool {
+        self.value_at_risk_with(Some(exposure)) <= self.var_limit
+    }
+
+    /// Largest deal value with the given default probability that still fits
+    pub fn max_admissible_value(&self, default_probability: f64) -> f64 {
+        let fits = |value: f64| self.admits(&Exposure::new(value, default_probability));
+        if !fits(0.0) {
+            return 0.0;
+        }
+        // VaR grows monotonically with the deal's value, so bisect on it
+        let mut high = 1.0;
+        while fits(high) {
+            if high > 1e15 {
+                return f64::INFINITY;
+            }
+            high *= 2.0;
+        }
+        let mut low = 0.0;
+        for _ in 0..60 {
+            let mid = (low + high) / 2.0;
+            if fits(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// Check a new deal: admit it, offer the largest value that fits, or
+    /// reject it when too little of it would fit
+    pub fn check(&self, exposure: &Exposure) -> RiskCheck {
+        if self.admits(exposure) {
+            return RiskCheck::Within;
+        }
+        let max_value = self.max_admissible_value(exposure.default_probability);
+        if max_value >= exposure.value * MIN_REPRICE_FRACTION {
+            RiskCheck::Reprice(max_value)
+        } else {
+            RiskCheck::Reject
+        }
+    }
+}
+
+impl Default for RiskBudget {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_at_risk_accumulates_across_deals() {
+        let mut budget = RiskBudget::new(50.0);
+        assert_eq!(budget.value_at_risk(), 0.0);
+
+        budget.add("a", Exposure::new(100.0, 0.1));
+        let single = budget.value_at_risk();
+        assert!((single - (10.0 + Z_95 * 30.0)).abs() < 1e-9);
+
+        budget.add("b", Exposure::new(100.0, 0.1));
+        assert!(budget.value_at_risk() > single);
+        assert!(budget.value_at_risk() < single * 2.0);
+        assert_eq!(budget.total_exposure(), 200.0);
+
+        budget.remove("b");
+        assert_eq!(budget.value_at_risk(), single);
+    }
+
+    #[test]
+    fn test_deals_are_admitted_repriced_or_rejected() {
+        let mut budget = RiskBudget::new(150.0);
+        budget.add("open", Exposure::new(200.0, 0.1));
+
+        assert_eq!(budget.check(&Exposure::new(100.0, 0.1)), RiskCheck::Within);
+
+        let RiskCheck::Reprice(max_value) = budget.check(&Exposure::new(150.0, 0.1)) else {
+            panic!("expected a reprice");
+        };
+        assert!(max_value > 100.0 && max_value < 150.0);
+        assert!(budget.admits(&Exposure::new(max_value, 0.1)));
+
+        assert_eq!(budget.check(&Exposure::new(1_000.0, 0.1)), RiskCheck::Reject);
+        assert_eq!(RiskBudget::unlimited().check(&Exposure::new(1e9, 0.9)), RiskCheck::Within);
+    }
+}
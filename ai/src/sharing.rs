@@ -0,0 +1,118 @@
+//! Shared Market Observations
+//!
+//! Agents run by the same owner can pool what their `MarketPredictor`s have
+//! seen. Each agent publishes batches of its own observations, numbered so
+//! receivers skip anything they already merged, and never re-publishes what
+//! it learned from others. An `ObservationMerger` folds incoming batches into
+//! the local predictor, weighting each source by how much it is trusted.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::MarketPredictor;
+
+/// One market data point as observed by its source
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MarketObservation {
+    pub sequence: u64,                    // Increasing per source
+    pub price: f64,
+    pub demand: f64,
+}
+
+/// Observations published by one source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObservationBatch {
+    pub source: String,
+    pub observations: Vec<MarketObservation>,
+}
+
+/// Merges other agents' observations into a local predictor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObservationMerger {
+    pub default_weight: f64,              // Trust in sources without an explicit weight
+    weights: HashMap<String, f64>,
+    merged_up_to: HashMap<String, u64>,   // Highest sequence merged per source
+}
+
+impl ObservationMerger {
+    pub fn new(default_weight: f64) -> Self {
+        Self {
+            default_weight: default_weight.clamp(0.0, 1.0),
+            weights: HashMap::new(),
+            merged_up_to: HashMap::new(),
+        }
+    }
+
+    /// Trust a source more or less than the default
+    pub fn set_weight(&mut self, source: impl Into<String>, weight: f64) {
+        self.weights.insert(source.into(), weight.clamp(0.0, 1.0));
+    }
+
+    /// Trust in a source, from 0.0 to 1.0
+    pub fn weight(&self, source: &str) -> f64 {
+        self.weights.get(source).copied().unwrap_or(self.default_weight)
+    }
+
+    /// Merge a batch, returning how many new observations went in
+    pub fn merge(&mut self, predictor: &mut MarketPredictor, batch: &ObservationBatch) -> usize {
+        let weight = self.weight(&batch.source);
+        if weight <= 0.0 {
+            return 0;
+        }
+
+        let mut observations: Vec<&MarketObservation> = batch.observations.iter().collect();
+        observations.sort_by_key(|observation| observation.sequence);
+
+        let mut merged = 0;
+        for observation in observations {
+            let seen = self.merged_up_to.get(&batch.source).copied();
+            if seen.is_some_and(|seen| observation.sequence <= seen) {
+                continue;
+            }
+            predictor.add_shared_point(observation.price, observation.demand, weight);
+            self.merged_up_to.insert(batch.source.clone(), observation.sequence);
+            merged += 1;
+        }
+        merged
+    }
+}
+
+impl Default for ObservationMerger {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sources_are_weighted_and_deduplicated() {
+        let mut alice = MarketPredictor::new();
+        for i in 0..5 {
+            alice.add_data_point(100.0 + i as f64, 0.5);
+        }
+        let batch = ObservationBatch {
+            source: "alice".to_string(),
+            observations: alice.local_observations_since(Some(1)),
+        };
+        assert_eq!(batch.observations.len(), 3);
+
+        let mut bob = MarketPredictor::new();
+        bob.add_data_point(90.0, 0.5);
+        let mut merger = ObservationMerger::default();
+        merger.set_weight("alice", 1.0);
+        assert_eq!(merger.merge(&mut bob, &batch), 3);
+        assert_eq!(merger.merge(&mut bob, &batch), 0);
+        assert_eq!(bob.forecast(1).map(|f| f.mean > 90.0), Some(true));
+        // Merged points are not ours to share again
+        assert_eq!(bob.local_observations_since(None).len(), 1);
+
+        let mut carol = MarketPredictor::new();
+        carol.add_data_point(90.0, 0.5);
+        let mut wary = ObservationMerger::new(0.1);
+        wary.merge(&mut carol, &batch);
+        assert!(carol.forecast(1).unwrap().mean < bob.forecast(1).unwrap().mean);
+    }
+}
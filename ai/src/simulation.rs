@@ -0,0 +1,281 @@
+//! Strategy Simulation
+//!
+//! Evaluates `NegotiationStrategy` implementations offline before they
+//! negotiate for real. A seeded generator draws thousands of synthetic
+//! scenarios (a `DecisionContext`, a base price, an execution cost, and a
+//! buyer with a hidden reservation price that concedes each round) and every
+//! strategy sells into the same scenarios. Per scenario the strategy that
+//! closed at the highest price wins; the report gives each strategy's win
+//! rate, deal rate, average margin over cost, and rounds to agreement.
+
+use serde::{Deserialize, Serialize};
+
+use crate::rng::SeededRng;
+use crate::strategy::{CounterOfferResponse, NegotiationState, NegotiationStrategy};
+use crate::{DecisionContext, ExecutionCost, MarketConditions, TransactionOutcome};
+
+/// Simulated counterparty: opens low and concedes toward a hidden limit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SyntheticBuyer {
+    pub opening: f64,                     // First offer
+    pub reservation: f64,                 // Most it will ever pay
+    pub concession: f64,                  // Share of the gap closed per round
+}
+
+impl SyntheticBuyer {
+    /// Offer in a round, starting from round 1
+    pub fn offer(&self, round: u32) -> f64 {
+        let gap = self.reservation - self.opening;
+        self.opening + gap * (1.0 - (1.0 - self.concession).powi(round.saturating_sub(1) as i32))
+    }
+
+    /// Whether the buyer takes an ask outright
+    pub fn accepts(&self, ask: f64, round: u32) -> bool {
+        ask <= self.offer(round).max(self.opening) * 1.02 && ask <= self.reservation
+    }
+}
+
+/// One synthetic negotiation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub context: DecisionContext,
+    pub base_price: f64,
+    pub cost: ExecutionCost,
+    pub max_rounds: u32,
+    pub buyer: SyntheticBuyer,
+}
+
+impl Scenario {
+    fn state(&self) -> NegotiationState {
+        NegotiationState::new(self.context.clone(), self.base_price, self.max_rounds).with_execution_cost(Some(self.cost))
+    }
+}
+
+/// How one negotiation ended
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedOutcome {
+    pub price: Option<f64>,               // Agreed price, if any
+    pub rounds: u32,
+}
+
+/// Per-strategy results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyReport {
+    pub name: String,
+    pub deals: usize,
+    pub wins: usize,
+    pub win_rate: f64,                    // Scenarios where it closed at the best price
+    pub deal_rate: f64,
+    pub average_margin: f64,              // Over execution cost, across deals
+    pub average_rounds: f64,              // Rounds to agreement, across deals
+}
+
+/// Results of a simulation run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub scenarios: usize,
+    pub strategies: Vec<StrategyReport>,
+}
+
+impl SimulationReport {
+    /// Report for a strategy by name
+    pub fn strategy(&self, name: &str) -> Option<&StrategyReport> {
+        self.strategies.iter().find(|report| report.name == name)
+    }
+}
+
+/// Seeded source of synthetic scenarios
+#[derive(Debug, Clone)]
+pub struct ScenarioGenerator {
+    rng: SeededRng,
+    pub max_rounds: u32,
+}
+
+impl ScenarioGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SeededRng::new(seed),
+            max_rounds: 5,
+        }
+    }
+
+    /// Draw the next scenario
+    pub fn scenario(&mut self) -> Scenario {
+        let base_price = self.rng.uniform(10.0, 200.0);
+        let demand_level = self.rng.uniform(0.0, 1.0);
+        let context = DecisionContext {
+            agent_reputation: self.rng.uniform(0.3, 1.0),
+            counterparty_reputation: self.rng.uniform(0.1, 1.0),
+            transaction_value: base_price,
+            market_conditions: MarketConditions {
+                demand_level,
+                competition_level: self.rng.uniform(0.0, 1.0),
+                average_pricing: base_price,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+
+        // Busier markets leave buyers willing to pay more
+        let reservation = base_price * self.rng.uniform(0.8, 1.3) * (0.9 + demand_level * 0.2);
+        let buyer = SyntheticBuyer {
+            opening: reservation * self.rng.uniform(0.5, 0.85),
+            reservation,
+            concession: self.rng.uniform(0.2, 0.6),
+        };
+
+        Scenario {
+            context,
+            base_price,
+            cost: ExecutionCost::new(base_price * self.rng.uniform(0.4, 0.75), 0.05),
+            max_rounds: self.max_rounds,
+            buyer,
+        }
+    }
+}
+
+/// Sell into a scenario with one strategy, round by round
+pub fn negotiate(strategy: &dyn NegotiationStrategy, scenario: &Scenario) -> (SimulatedOutcome, NegotiationState) {
+    let mut state = scenario.state();
+    let mut ask = state.bound_ask(strategy.propose_price(&state));
+
+    loop {
+        state.our_asks.push(ask);
+        state.round += 1;
+        if scenario.buyer.accepts(ask, state.round) {
+            return (SimulatedOutcome { price: Some(ask), rounds: state.round }, state);
+        }
+
+        let offer = scenario.buyer.offer(state.round);
+        state.their_offers.push(offer);
+        if strategy.should_walk_away(&state) {
+            return (SimulatedOutcome { price: None, rounds: state.round }, state);
+        }
+        match state.bound_response(offer, strategy.evaluate_counter_offer(&state, offer)) {
+            CounterOfferResponse::Accept => return (SimulatedOutcome { price: Some(offer), rounds: state.round }, state),
+            CounterOfferResponse::Counter(price) if !state.out_of_rounds() => ask = price,
+            _ => return (SimulatedOutcome { price: None, rounds: state.round }, state),
+        }
+    }
+}
+
+/// Head-to-head evaluation of negotiation strategies
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub generator: ScenarioGenerator,
+    pub scenarios: usize,
+    pub learn: bool,                      // Feed outcomes back to learning strategies
+}
+
+impl Simulation {
+    pub fn new(seed: u64, scenarios: usize) -> Self {
+        Self {
+            generator: ScenarioGenerator::new(seed),
+            scenarios,
+            learn: false,
+        }
+    }
+
+    /// Let strategies learn from each simulated outcome as the run goes
+    pub fn with_learning(mut self) -> Self {
+        self.learn = true;
+        self
+    }
+
+    /// Run every strategy through the same scenarios
+    pub fn run(&mut self, strategies: &mut [Box<dyn NegotiationStrategy>]) -> SimulationReport {
+        let mut deals = vec![0usize; strategies.len()];
+        let mut wins = vec![0usize; strategies.len()];
+        let mut margins = vec![0.0; strategies.len()];
+        let mut rounds = vec![0u32; strategies.len()];
+
+        for _ in 0..self.scenarios {
+            let scenario = self.generator.scenario();
+            let mut prices = Vec::with_capacity(strategies.len());
+            for (i, strategy) in strategies.iter_mut().enumerate() {
+                let (outcome, state) = negotiate(strategy.as_ref(), &scenario);
+                let margin = outcome.price.map(|price| (price - scenario.cost.estimated) / price);
+                if let Some(margin) = margin {
+                    deals[i] += 1;
+                    margins[i] += margin;
+                    rounds[i] += outcome.rounds;
+                }
+                if self.learn {
+                    strategy.observe_outcome(&state, &TransactionOutcome {
+                        success: outcome.price.is_some(),
+                        profit_margin: margin.unwrap_or(0.0),
+                        satisfaction_score: if outcome.price.is_some() { 1.0 } else { 0.0 },
+                        completion_time: 0,
+                    });
+                }
+                prices.push(outcome.price);
+            }
+
+            // Ties share the win
+            if let Some(best) = prices.iter().flatten().copied().reduce(f64::max) {
+                for (i, price) in prices.iter().enumerate() {
+                    if *price == Some(best) {
+                        wins[i] += 1;
+                    }
+                }
+            }
+        }
+
+        let scenarios = self.scenarios.max(1) as f64;
+        let strategies = strategies
+            .iter()
+            .enumerate()
+            .map(|(i, strategy)| {
+                let per_deal = |total: f64| if deals[i] > 0 { total / deals[i] as f64 } else { 0.0 };
+                StrategyReport {
+                    name: strategy.name().to_string(),
+                    deals: deals[i],
+                    wins: wins[i],
+                    win_rate: wins[i] as f64 / scenarios,
+                    deal_rate: deals[i] as f64 / scenarios,
+                    average_margin: per_deal(margins[i]),
+                    average_rounds: per_deal(rounds[i] as f64),
+                }
+            })
+            .collect();
+
+        SimulationReport {
+            scenarios: self.scenarios,
+            strategies,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{AggressiveStrategy, AiStrategy, ConservativeStrategy, TitForTatStrategy};
+
+    fn strategies() -> Vec<Box<dyn NegotiationStrategy>> {
+        vec![
+            Box::new(AggressiveStrategy::default()),
+            Box::new(ConservativeStrategy::default()),
+            Box::new(TitForTatStrategy::default()),
+            Box::new(AiStrategy::default()),
+        ]
+    }
+
+    #[test]
+    fn test_strategies_are_compared_on_the_same_scenarios() {
+        let report = Simulation::new(42, 2_000).run(&mut strategies());
+        assert_eq!(report.scenarios, 2_000);
+        assert_eq!(report.strategies.len(), 4);
+        assert_eq!(report, Simulation::new(42, 2_000).run(&mut strategies()));
+
+        let aggressive = report.strategy("aggressive").unwrap();
+        let conservative = report.strategy("conservative").unwrap();
+        assert!(conservative.deal_rate > aggressive.deal_rate);
+        assert!(aggressive.average_margin > conservative.average_margin);
+        for strategy in &report.strategies {
+            assert!(strategy.wins <= strategy.deals);
+            assert!(strategy.average_rounds >= 1.0 && strategy.average_rounds <= 5.0, "{:?}", strategy);
+        }
+    }
+}
@@ -0,0 +1,646 @@
+//! Negotiation Strategies
+//!
+//! `NegotiationStrategy` is the extension point for how an agent haggles:
+//! what it asks first, how it answers a counter-offer, and when it gives up;
+//! and, buying, which of the proposals it solicited it takes.
+//! Agents hold a boxed strategy, so custom behavior plugs in without forking
+//! the framework. Bundled implementations:
+//!
+//! - `AggressiveStrategy`: high opening ask, small concessions
+//! - `ConservativeStrategy`: modest ask, concedes quickly to close deals
+//! - `TitForTatStrategy`: mirrors the counterparty's concessions
+//! - `ScheduledStrategy`: follows a planned concession schedule, retuned
+//!   from outcomes
+//! - `AiStrategy`: delegates to `NegotiationAI`, learning from outcomes
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::concession::{ConcessionCurve, ConcessionSchedule};
+use crate::risk::{Exposure, RiskBudget, RiskCheck};
+use crate::{DecisionContext, ExecutionCost, NegotiationAI, PriceBounds, TransactionOutcome};
+
+/// Negotiation so far, as seen by the seller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegotiationState {
+    pub context: DecisionContext,
+    pub base_price: f64,
+    pub round: u32,
+    pub max_rounds: u32,
+    pub our_asks: Vec<f64>,
+    pub their_offers: Vec<f64>,
+    /// Governance limits for this service, if any
+    #[serde(default)]
+    pub price_bounds: Option<PriceBounds>,
+    /// Our cost of executing the service, if modeled
+    #[serde(default)]
+    pub execution_cost: Option<ExecutionCost>,
+    /// Our portfolio risk budget, if limited
+    #[serde(default)]
+    pub risk_budget: Option<RiskBudget>,
+}
+
+impl NegotiationState {
+    /// Start a negotiation
+    pub fn new(context: DecisionContext, base_price: f64, max_rounds: u32) -> Self {
+        Self {
+            context,
+            base_price,
+            round: 0,
+            max_rounds,
+            our_asks: Vec::new(),
+            their_offers: Vec::new(),
+            price_bounds: None,
+            execution_cost: None,
+            risk_budget: None,
+        }
+    }
+
+    /// Negotiate within governance price limits
+    pub fn with_price_bounds(mut self, bounds: Option<PriceBounds>) -> Self {
+        self.price_bounds = bounds;
+        self
+    }
+
+    /// Never go below execution cost plus margin
+    pub fn with_execution_cost(mut self, cost: Option<ExecutionCost>) -> Self {
+        self.execution_cost = cost;
+        self
+    }
+
+    /// Never take on a deal that breaks the portfolio risk budget
+    pub fn with_risk_budget(mut self, budget: Option<RiskBudget>) -> Self {
+        self.risk_budget = budget;
+        self
+    }
+
+    /// Keep an ask above cost and within the price limits
+    pub fn bound_ask(&self, price: f64) -> f64 {
+        let price = self.execution_cost.map_or(price, |cost| price.max(cost.floor()));
+        self.price_bounds.map_or(price, |bounds| bounds.clamp(price))
+    }
+
+    /// Keep a response above cost, within the risk budget, and within the
+    /// price limits: never accept an offer below cost, over budget, or out of
+    /// bounds, and never counter outside them
+    pub fn bound_response(&self, offer: f64, response: CounterOfferResponse) -> CounterOfferResponse {
+        let response = self.bound_risk(offer, response);
+        let response = match (self.execution_cost, response) {
+            (Some(cost), CounterOfferResponse::Accept) if !cost.covered_by(offer) => {
+                if self.out_of_rounds() {
+                    CounterOfferResponse::Reject
+                } else {
+                    CounterOfferResponse::Counter(cost.floor())
+                }
+            }
+            (Some(cost), CounterOfferResponse::Counter(price)) => CounterOfferResponse::Counter(price.max(cost.floor())),
+            (_, response) => response,
+        };
+        let Some(bounds) = self.price_bounds else {
+            return response;
+        };
+        match response {
+            CounterOfferResponse::Accept if !bounds.contains(offer) => CounterOfferResponse::Counter(bounds.clamp(offer)),
+            CounterOfferResponse::Counter(price) => CounterOfferResponse::Counter(bounds.clamp(price)),
+            other => other,
+        }
+    }
+
+    /// Reprice or reject a response whose deal would exceed the risk budget
+    fn bound_risk(&self, offer: f64, response: CounterOfferResponse) -> CounterOfferResponse {
+        let Some(budget) = &self.risk_budget else {
+            return response;
+        };
+        let price = match response {
+            CounterOfferResponse::Accept => offer,
+            CounterOfferResponse::Counter(price) => price,
+            CounterOfferResponse::Reject => return response,
+        };
+        match budget.check(&Exposure::for_context(&self.context, price)) {
+            RiskCheck::Within => response,
+            RiskCheck::Reprice(max_value) if !self.out_of_rounds() && self.execution_cost.is_none_or(|cost| cost.covered_by(max_value)) => {
+                CounterOfferResponse::Counter(max_value)
+            }
+            _ => CounterOfferResponse::Reject,
+        }
+    }
+
+    /// Most recent ask, if any
+    pub fn current_ask(&self) -> Option<f64> {
+        self.our_asks.last().copied()
+    }
+
+    /// Whether the round limit has been reached
+    pub fn out_of_rounds(&self) -> bool {
+        self.round >= self.max_rounds
+    }
+
+    /// Decision context with the rounds used so far counted as time pressure
+    pub fn pressured_context(&self) -> DecisionContext {
+        let mut context = self.context.clone();
+        context.time_pressure = Some(context.time_pressure.unwrap_or_default().with_rounds(self.round, self.max_rounds));
+        context
+    }
+}
+
+/// Response to a counter-offer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CounterOfferResponse {
+    Accept,
+    Counter(f64),
+    Reject,
+}
+
+/// A provider's proposal as the requester weighs it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProposalTerms {
+    pub price: f64,
+    pub counterparty_reputation: f64,
+    pub score: f64,                       // From the requester's selection weights
+}
+
+/// Pluggable negotiation behavior
+pub trait NegotiationStrategy: Send + Sync + fmt::Debug {
+    /// Strategy name for logs and metrics
+    fn name(&self) -> &str;
+
+    /// Opening ask
+    fn propose_price(&self, state: &NegotiationState) -> f64;
+
+    /// Answer a counter-offer
+    fn evaluate_counter_offer(&self, state: &NegotiationState, offer: f64) -> CounterOfferResponse;
+
+    /// Whether to end the negotiation without a deal
+    fn should_walk_away(&self, state: &NegotiationState) -> bool;
+
+    /// Learn from a finished negotiation
+    fn observe_outcome(&mut self, _state: &NegotiationState, _outcome: &TransactionOutcome) {}
+
+    /// As the requester, pick the proposal to take from those ranked best
+    /// first, or none; `state.base_price` is our budget. By default the best
+    /// ranked one within budget.
+    fn choose_proposal(&self, state: &NegotiationState, proposals: &[ProposalTerms]) -> Option<usize> {
+        proposals.iter().position(|terms| terms.price <= state.base_price)
+    }
+}
+
+/// Accept, counter, or reject based on a concession step and a floor
+fn concede(state: &NegotiationState, offer: f64, accept_ratio: f64, concession: f64, floor: f64) -> CounterOfferResponse {
+    let ask = state.current_ask().unwrap_or(state.base_price);
+    let floor = state.base_price * floor;
+
+    if offer >= ask * accept_ratio {
+        CounterOfferResponse::Accept
+    } else if state.out_of_rounds() {
+        CounterOfferResponse::Reject
+    } else {
+        // Never counter below our floor or below what they already offered
+        CounterOfferResponse::Counter((ask * (1.0 - concession)).max(floor).max(offer))
+    }
+}
+
+/// High opening ask, small concessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggressiveStrategy {
+    pub opening_premium: f64,             // Multiplier on the base price
+    pub concession: f64,                  // Fraction of the ask given up per round
+    pub floor: f64,                       // Lowest acceptable price / base price
+}
+
+impl Default for AggressiveStrategy {
+    fn default() -> Self {
+        Self {
+            opening_premium: 1.3,
+            concession: 0.03,
+            floor: 0.95,
+        }
+    }
+}
+
+impl NegotiationStrategy for AggressiveStrategy {
+    fn name(&self) -> &str {
+        "aggressive"
+    }
+
+    fn propose_price(&self, state: &NegotiationState) -> f64 {
+        let demand = state.context.market_conditions.demand_level;
+        state.base_price * self.opening_premium * (0.9 + demand * 0.2)
+    }
+
+    fn evaluate_counter_offer(&self, state: &NegotiationState, offer: f64) -> CounterOfferResponse {
+        concede(state, offer, 0.98, self.concession, self.floor)
+    }
+
+    fn should_walk_away(&self, state: &NegotiationState) -> bool {
+        state.out_of_rounds()
+            || state.their_offers.last().is_some_and(|&offer| offer < state.base_price * self.floor * 0.7)
+    }
+
+    /// Buys on price alone
+    fn choose_proposal(&self, state: &NegotiationState, proposals: &[ProposalTerms]) -> Option<usize> {
+        proposals
+            .iter()
+            .enumerate()
+            .filter(|(_, terms)| terms.price <= state.base_price)
+            .min_by(|(_, a), (_, b)| a.price.total_cmp(&b.price))
+            .map(|(index, _)| index)
+    }
+}
+
+/// Modest opening ask, concedes quickly to close deals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConservativeStrategy {
+    pub opening_premium: f64,
+    pub concession: f64,
+    pub floor: f64,
+    pub min_counterparty_reputation: f64,
+}
+
+impl Default for ConservativeStrategy {
+    fn default() -> Self {
+        Self {
+            opening_premium: 1.05,
+            concession: 0.08,
+            floor: 0.8,
+            min_counterparty_reputation: 0.4,
+        }
+    }
+}
+
+impl NegotiationStrategy for ConservativeStrategy {
+    fn name(&self) -> &str {
+        "conservative"
+    }
+
+    fn propose_price(&self, state: &NegotiationState) -> f64 {
+        state.base_price * self.opening_premium
+    }
+
+    fn evaluate_counter_offer(&self, state: &NegotiationState, offer: f64) -> CounterOfferResponse {
+        concede(state, offer, 0.9, self.concession, self.floor)
+    }
+
+    fn should_walk_away(&self, state: &NegotiationState) -> bool {
+        // Avoids risky counterparties rather than haggling with them
+        state.out_of_rounds() || state.context.counterparty_reputation < self.min_counterparty_reputation
+    }
+
+    fn choose_proposal(&self, state: &NegotiationState, proposals: &[ProposalTerms]) -> Option<usize> {
+        proposals
+            .iter()
+            .position(|terms| terms.price <= state.base_price && terms.counterparty_reputation >= self.min_counterparty_reputation)
+    }
+}
+
+/// Mirrors the counterparty: concedes as much as they last conceded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitForTatStrategy {
+    pub opening_premium: f64,
+    pub floor: f64,
+    pub stall_rounds: usize,              // Walk away after this many offers without movement
+}
+
+impl Default for TitForTatStrategy {
+    fn default() -> Self {
+        Self {
+            opening_premium: 1.15,
+            floor: 0.85,
+            stall_rounds: 2,
+        }
+    }
+}
+
+impl NegotiationStrategy for TitForTatStrategy {
+    fn name(&self) -> &str {
+        "tit_for_tat"
+    }
+
+    fn propose_price(&self, state: &NegotiationState) -> f64 {
+        state.base_price * self.opening_premium
+    }
+
+    fn evaluate_counter_offer(&self, state: &NegotiationState, offer: f64) -> CounterOfferResponse {
+        let ask = state.current_ask().unwrap_or(state.base_price);
+        if offer >= ask {
+            return CounterOfferResponse::Accept;
+        }
+        if state.out_of_rounds() {
+            return CounterOfferResponse::Reject;
+        }
+
+        // Match the fraction they moved since their previous offer; the first
+        // offer gets a small goodwill concession
+        let concession = match state.their_offers.iter().rev().nth(1) {
+            Some(&previous) if previous > 0.0 => ((offer - previous) / previous).max(0.0),
+            _ => 0.02,
+        };
+        let counter = (ask * (1.0 - concession)).max(state.base_price * self.floor).max(offer);
+        if counter <= offer {
+            CounterOfferResponse::Accept
+        } else {
+            CounterOfferResponse::Counter(counter)
+        }
+    }
+
+    fn should_walk_away(&self, state: &NegotiationState) -> bool {
+        let recent = &state.their_offers[state.their_offers.len().saturating_sub(self.stall_rounds + 1)..];
+        let stalled = recent.len() > self.stall_rounds && recent.windows(2).all(|w| w[1] <= w[0]);
+        state.out_of_rounds() || stalled
+    }
+}
+
+/// Follows a concession schedule from an opening anchor to a reservation price
+///
+/// Failed negotiations lower the reservation price and make the curve
+/// concede sooner; deals closed in the first half of the rounds raise it and
+/// make the curve hold out longer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledStrategy {
+    pub curve: ConcessionCurve,
+    pub opening_premium: f64,             // Anchor / base price
+    pub reservation: f64,                 // Lowest planned ask / base price
+    pub learning_rate: f64,               // Step size when retuning from outcomes
+}
+
+impl ScheduledStrategy {
+    pub fn new(curve: ConcessionCurve) -> Self {
+        Self {
+            curve,
+            opening_premium: 1.25,
+            reservation: 0.85,
+            learning_rate: 0.05,
+        }
+    }
+
+    /// Planned asks for a negotiation
+    pub fn schedule(&self, state: &NegotiationState) -> ConcessionSchedule {
+        let mut curve = self.curve;
+        if let ConcessionCurve::Annealed { seed, .. } = &mut curve {
+            // Vary the walk between negotiations while keeping each one stable
+            *seed ^= state.base_price.to_bits();
+        }
+        ConcessionSchedule::new(
+            state.base_price * self.opening_premium,
+            state.base_price * self.reservation,
+            state.max_rounds,
+            curve,
+        )
+    }
+}
+
+impl Default for ScheduledStrategy {
+    fn default() -> Self {
+        Self::new(ConcessionCurve::Linear)
+    }
+}
+
+impl NegotiationStrategy for ScheduledStrategy {
+    fn name(&self) -> &str {
+        "scheduled"
+    }
+
+    fn propose_price(&self, state: &NegotiationState) -> f64 {
+        self.schedule(state).offer_at(0)
+    }
+
+    fn evaluate_counter_offer(&self, state: &NegotiationState, offer: f64) -> CounterOfferResponse {
+        // Asks made so far index the next planned ask
+        let next_ask = self.schedule(state).offer_at(state.round);
+        if offer >= next_ask {
+            CounterOfferResponse::Accept
+        } else if state.out_of_rounds() {
+            CounterOfferResponse::Reject
+        } else {
+            CounterOfferResponse::Counter(next_ask)
+        }
+    }
+
+    fn should_walk_away(&self, state: &NegotiationState) -> bool {
+        state.out_of_rounds() && state.their_offers.last().is_none_or(|&offer| offer < state.base_price * self.reservation)
+    }
+
+    fn observe_outcome(&mut self, state: &NegotiationState, outcome: &TransactionOutcome) {
+        let quick = state.round * 2 <= state.max_rounds;
+        let rate = match (outcome.success, quick) {
+            (false, _) => self.learning_rate,
+            (true, true) => -self.learning_rate,
+            (true, false) => return,
+        };
+        self.reservation = (self.reservation * (1.0 - rate)).clamp(0.5, self.opening_premium);
+        self.curve.tune(rate);
+    }
+}
+
+/// Delegates to `NegotiationAI` and feeds it outcomes
+#[derive(Debug, Clone)]
+pub struct AiStrategy {
+    pub ai: NegotiationAI,
+    pub floor: f64,
+}
+
+impl AiStrategy {
+    pub fn new(ai: NegotiationAI) -> Self {
+        Self { ai, floor: 0.7 }
+    }
+}
+
+impl Default for AiStrategy {
+    fn default() -> Self {
+        Self::new(NegotiationAI::new(0.1, 0.5))
+    }
+}
+
+impl NegotiationStrategy for AiStrategy {
+    fn name(&self) -> &str {
+        "ai"
+    }
+
+    fn propose_price(&self, state: &NegotiationState) -> f64 {
+        match &state.execution_cost {
+            Some(cost) => self.ai.decide_pricing_with_cost(&state.context, state.base_price, cost),
+            None => self.ai.decide_pricing(&state.context, state.base_price),
+        }
+    }
+
+    fn evaluate_counter_offer(&self, state: &NegotiationState, offer: f64) -> CounterOfferResponse {
+        let ask = state.current_ask().unwrap_or_else(|| self.propose_price(state));
+        match self.ai.check_risk(&state.context, offer) {
+            RiskCheck::Reject => return CounterOfferResponse::Reject,
+            RiskCheck::Reprice(max_value) if !state.out_of_rounds() => return CounterOfferResponse::Counter(max_value),
+            RiskCheck::Reprice(_) => return CounterOfferResponse::Reject,
+            RiskCheck::Within => {}
+        }
+        if self.ai.should_accept_counter_offer(&state.pressured_context(), offer, ask) {
+            CounterOfferResponse::Accept
+        } else if state.out_of_rounds() {
+            CounterOfferResponse::Reject
+        } else {
+            // Meet halfway, but not below the floor
+            CounterOfferResponse::Counter(((ask + offer) / 2.0).max(state.base_price * self.floor))
+        }
+    }
+
+    fn should_walk_away(&self, state: &NegotiationState) -> bool {
+        state.out_of_rounds() || self.ai.anomaly_veto()
+    }
+
+    fn observe_outcome(&mut self, _state: &NegotiationState, outcome: &TransactionOutcome) {
+        self.ai.learn_from_outcome(outcome.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MarketConditions;
+
+    fn state(counterparty_reputation: f64) -> NegotiationState {
+        NegotiationState::new(
+            DecisionContext {
+                agent_reputation: 0.7,
+                counterparty_reputation,
+                transaction_value: 100.0,
+                market_conditions: MarketConditions {
+                    demand_level: 0.5,
+                    competition_level: 0.5,
+                    average_pricing: 100.0,
+                    risk_indicators: vec![],
+                },
+                historical_performance: vec![],
+                counterparty_profile: None,
+                time_pressure: None,
+            },
+            100.0,
+            5,
+        )
+    }
+
+    fn strategies() -> Vec<Box<dyn NegotiationStrategy>> {
+        vec![
+            Box::new(AggressiveStrategy::default()),
+            Box::new(ConservativeStrategy::default()),
+            Box::new(TitForTatStrategy::default()),
+            Box::new(AiStrategy::default()),
+            Box::new(ScheduledStrategy::default()),
+        ]
+    }
+
+    #[test]
+    fn test_strategies_accept_full_price_and_respect_floor() {
+        for strategy in strategies() {
+            let mut s = state(0.7);
+            let ask = strategy.propose_price(&s);
+            s.our_asks.push(ask);
+            s.their_offers.push(ask);
+            assert_eq!(strategy.evaluate_counter_offer(&s, ask), CounterOfferResponse::Accept, "{}", strategy.name());
+
+            s.their_offers.push(10.0);
+            match strategy.evaluate_counter_offer(&s, 10.0) {
+                CounterOfferResponse::Counter(price) => assert!(price >= 70.0, "{} countered {}", strategy.name(), price),
+                other => panic!("{} answered {:?}", strategy.name(), other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_aggressive_concedes_less_than_conservative() {
+        let aggressive = AggressiveStrategy::default();
+        let conservative = ConservativeStrategy::default();
+        let mut s = state(0.7);
+        s.our_asks.push(120.0);
+
+        let counter = |response| match response {
+            CounterOfferResponse::Counter(price) => price,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert!(counter(aggressive.evaluate_counter_offer(&s, 90.0)) > counter(conservative.evaluate_counter_offer(&s, 90.0)));
+        assert!(conservative.should_walk_away(&state(0.2)));
+    }
+
+    #[test]
+    fn test_requesters_choose_among_ranked_proposals() {
+        let terms = |price, counterparty_reputation, score| ProposalTerms { price, counterparty_reputation, score };
+        let proposals = [terms(95.0, 0.3, 0.9), terms(80.0, 0.9, 0.8), terms(60.0, 0.6, 0.7), terms(50.0, 0.9, 0.6)];
+        let s = state(0.7);
+        assert_eq!(TitForTatStrategy::default().choose_proposal(&s, &proposals), Some(0));
+        assert_eq!(ConservativeStrategy::default().choose_proposal(&s, &proposals), Some(1));
+        assert_eq!(AggressiveStrategy::default().choose_proposal(&s, &proposals), Some(3));
+        assert_eq!(AiStrategy::default().choose_proposal(&s, &[terms(120.0, 0.9, 1.0)]), None);
+    }
+
+    #[test]
+    fn test_tit_for_tat_mirrors_concessions() {
+        let strategy = TitForTatStrategy::default();
+        let mut s = state(0.7);
+        s.our_asks.push(115.0);
+        s.their_offers.extend([80.0, 88.0]);
+
+        // They moved 10%, so do we
+        assert_eq!(strategy.evaluate_counter_offer(&s, 88.0), CounterOfferResponse::Counter(115.0 * 0.9));
+
+        s.their_offers.extend([88.0, 88.0]);
+        assert!(strategy.should_walk_away(&s));
+    }
+
+    #[test]
+    fn test_scheduled_strategy_follows_and_retunes_its_curve() {
+        let mut strategy = ScheduledStrategy::new(ConcessionCurve::boulware());
+        let mut s = state(0.7);
+        s.our_asks.push(strategy.propose_price(&s));
+        s.round = 1;
+        assert_eq!(s.our_asks[0], 125.0);
+
+        // Boulware barely moves off the anchor early on
+        let CounterOfferResponse::Counter(ask) = strategy.evaluate_counter_offer(&s, 90.0) else {
+            panic!("expected a counter");
+        };
+        assert!(ask > 124.0);
+        assert_eq!(strategy.evaluate_counter_offer(&s, ask), CounterOfferResponse::Accept);
+
+        s.round = s.max_rounds;
+        let failed = TransactionOutcome { success: false, profit_margin: 0.0, satisfaction_score: 0.0, completion_time: 0 };
+        strategy.observe_outcome(&s, &failed);
+        assert!(strategy.reservation < 0.85);
+        assert_eq!(strategy.curve, ConcessionCurve::Boulware { beta: 0.3 * 1.05 });
+    }
+
+    #[test]
+    fn test_price_bounds_constrain_responses() {
+        let s = state(0.7).with_price_bounds(PriceBounds::new(90.0, 110.0));
+
+        assert_eq!(s.bound_ask(130.0), 110.0);
+        assert_eq!(s.bound_response(80.0, CounterOfferResponse::Accept), CounterOfferResponse::Counter(90.0));
+        assert_eq!(s.bound_response(95.0, CounterOfferResponse::Counter(150.0)), CounterOfferResponse::Counter(110.0));
+        assert_eq!(s.bound_response(95.0, CounterOfferResponse::Accept), CounterOfferResponse::Accept);
+    }
+
+    #[test]
+    fn test_execution_cost_sets_a_floor() {
+        let mut s = state(0.7).with_execution_cost(Some(ExecutionCost::new(80.0, 0.25)));
+
+        assert_eq!(s.bound_ask(90.0), 100.0);
+        assert_eq!(s.bound_response(95.0, CounterOfferResponse::Accept), CounterOfferResponse::Counter(100.0));
+        assert_eq!(s.bound_response(95.0, CounterOfferResponse::Counter(98.0)), CounterOfferResponse::Counter(100.0));
+        assert_eq!(s.bound_response(105.0, CounterOfferResponse::Accept), CounterOfferResponse::Accept);
+
+        s.round = s.max_rounds;
+        assert_eq!(s.bound_response(95.0, CounterOfferResponse::Accept), CounterOfferResponse::Reject);
+    }
+
+    #[test]
+    fn test_risk_budget_reprices_or_rejects() {
+        let mut budget = RiskBudget::new(60.0);
+        budget.add("open", Exposure::new(100.0, 0.06));
+        let s = state(0.7).with_risk_budget(Some(budget));
+
+        assert_eq!(s.bound_response(50.0, CounterOfferResponse::Accept), CounterOfferResponse::Accept);
+        let CounterOfferResponse::Counter(repriced) = s.bound_response(120.0, CounterOfferResponse::Accept) else {
+            panic!("expected a reprice");
+        };
+        assert!(repriced < 120.0 && repriced > 60.0);
+        assert_eq!(s.bound_response(1_000.0, CounterOfferResponse::Accept), CounterOfferResponse::Reject);
+
+        let ai = AiStrategy::new(NegotiationAI::new(0.1, 0.5).with_risk_budget(RiskBudget::new(1.0)));
+        assert_eq!(ai.evaluate_counter_offer(&state(0.7), 100.0), CounterOfferResponse::Reject);
+    }
+}
@@ -0,0 +1,368 @@
+//! Negotiation Transcripts
+//!
+//! Records complete negotiation runs of `NegotiationAI` (pricing breakdown,
+//! offers per round, decisions, and explanations) and compares them against
+//! golden fixtures. Numeric values are compared with a tolerance so harmless
+//! floating point changes pass, while any changed decision or a price drift
+//! beyond the threshold is reported as an economic behavior change.
+//!
+//! Fixtures are regenerated by running the tests with
+//! `SOLACE_UPDATE_GOLDEN=1`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{DecisionContext, NegotiationAI};
+
+/// Environment variable that rewrites golden fixtures instead of comparing
+pub const UPDATE_GOLDEN_ENV: &str = "SOLACE_UPDATE_GOLDEN";
+
+/// Scripted negotiation: a fixed context and the counterparty's offers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegotiationScript {
+    pub name: String,
+    pub context: DecisionContext,
+    pub base_price: f64,
+    pub counter_offers: Vec<f64>,
+}
+
+/// Breakdown of the opening ask
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingBreakdown {
+    pub reputation_factor: f64,
+    pub market_factor: f64,
+    pub risk_factor: f64,
+    pub ask: f64,
+}
+
+/// Decision taken in a negotiation round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundDecision {
+    Accept,
+    Reject,
+}
+
+/// Single negotiation round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundRecord {
+    pub round: u32,
+    pub ask: f64,
+    pub offer: f64,
+    pub offer_ratio: f64,
+    pub acceptance_threshold: f64,
+    pub decision: RoundDecision,
+    pub explanation: String,
+}
+
+/// Final negotiation result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NegotiationResult {
+    Agreed { price: f64, round: u32 },
+    NoDeal,
+}
+
+/// Full record of a negotiation run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NegotiationTranscript {
+    pub script: String,
+    pub pricing: PricingBreakdown,
+    pub rounds: Vec<RoundRecord>,
+    pub result: NegotiationResult,
+}
+
+impl NegotiationTranscript {
+    /// Run a script through the negotiation AI and record every step
+    pub fn record(ai: &NegotiationAI, script: &NegotiationScript) -> Self {
+        let context = &script.context;
+        let ask = ai.decide_pricing(context, script.base_price);
+        let pricing = PricingBreakdown {
+            reputation_factor: ai.calculate_reputation_factor(context),
+            market_factor: ai.calculate_market_factor(&context.market_conditions),
+            risk_factor: ai.calculate_risk_factor(context),
+            ask,
+        };
+
+        let acceptance_threshold = ai.calculate_acceptance_threshold(context);
+        let mut rounds = Vec::new();
+        let mut result = NegotiationResult::NoDeal;
+
+        for (round, &offer) in script.counter_offers.iter().enumerate() {
+            let round = round as u32;
+            let offer_ratio = offer / ask;
+            let accepted = ai.should_accept_counter_offer(context, offer, ask);
+
+            let (decision, explanation) = if accepted {
+                (RoundDecision::Accept, format!(
+                    "offer {:.4} is {:.4} of ask, at or above threshold {:.4}",
+                    offer, offer_ratio, acceptance_threshold
+                ))
+            } else {
+                (RoundDecision::Reject, format!(
+                    "offer {:.4} is {:.4} of ask, below threshold {:.4}",
+                    offer, offer_ratio, acceptance_threshold
+                ))
+            };
+
+            rounds.push(RoundRecord {
+                round,
+                ask,
+                offer,
+                offer_ratio,
+                acceptance_threshold,
+                decision,
+                explanation,
+            });
+
+            if accepted {
+                result = NegotiationResult::Agreed { price: offer, round };
+                break;
+            }
+        }
+
+        Self {
+            script: script.name.clone(),
+            pricing,
+            rounds,
+            result,
+        }
+    }
+
+    /// Compare against a golden transcript, returning every difference found
+    pub fn diff(&self, golden: &NegotiationTranscript, tolerance: &TranscriptTolerance) -> Vec<String> {
+        let mut diffs = Vec::new();
+
+        let mut number = |field: String, actual: f64, expected: f64| {
+            if !tolerance.within(actual, expected) {
+                diffs.push(format!("{}: expected {}, got {}", field, expected, actual));
+            }
+        };
+
+        number("pricing.reputation_factor".into(), self.pricing.reputation_factor, golden.pricing.reputation_factor);
+        number("pricing.market_factor".into(), self.pricing.market_factor, golden.pricing.market_factor);
+        number("pricing.risk_factor".into(), self.pricing.risk_factor, golden.pricing.risk_factor);
+        number("pricing.ask".into(), self.pricing.ask, golden.pricing.ask);
+
+        for (actual, expected) in self.rounds.iter().zip(&golden.rounds) {
+            let prefix = format!("round {}", expected.round);
+            number(format!("{}.ask", prefix), actual.ask, expected.ask);
+            number(format!("{}.offer", prefix), actual.offer, expected.offer);
+            number(format!("{}.offer_ratio", prefix), actual.offer_ratio, expected.offer_ratio);
+            number(format!("{}.acceptance_threshold", prefix), actual.acceptance_threshold, expected.acceptance_threshold);
+        }
+
+        for (actual, expected) in self.rounds.iter().zip(&golden.rounds) {
+            if actual.decision != expected.decision {
+                diffs.push(format!(
+                    "round {}: decision changed from {:?} to {:?} ({})",
+                    expected.round, expected.decision, actual.decision, actual.explanation
+                ));
+            }
+        }
+
+        if self.rounds.len() != golden.rounds.len() {
+            diffs.push(format!("expected {} rounds, got {}", golden.rounds.len(), self.rounds.len()));
+        }
+
+        match (&self.result, &golden.result) {
+            (
+                NegotiationResult::Agreed { price: actual_price, round: actual_round },
+                NegotiationResult::Agreed { price: expected_price, round: expected_round },
+            ) => {
+                if actual_round != expected_round {
+                    diffs.push(format!("agreement moved from round {} to {}", expected_round, actual_round));
+                }
+                if !tolerance.within(*actual_price, *expected_price) {
+                    diffs.push(format!("agreed price: expected {}, got {}", expected_price, actual_price));
+                }
+            }
+            (actual, expected) if actual != expected => {
+                diffs.push(format!("result: expected {:?}, got {:?}", expected, actual));
+            }
+            _ => {}
+        }
+
+        diffs
+    }
+}
+
+/// Tolerance for numeric transcript comparisons
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TranscriptTolerance {
+    pub relative: f64,
+    pub absolute: f64,
+}
+
+impl Default for TranscriptTolerance {
+    fn default() -> Self {
+        Self {
+            relative: 1e-3,
+            absolute: 1e-9,
+        }
+    }
+}
+
+impl TranscriptTolerance {
+    /// Check whether two values are equal within tolerance
+    pub fn within(&self, actual: f64, expected: f64) -> bool {
+        let difference = (actual - expected).abs();
+        difference <= self.absolute || difference <= self.relative * expected.abs()
+    }
+}
+
+/// Store of golden transcripts on disk
+#[derive(Debug, Clone)]
+pub struct GoldenStore {
+    dir: PathBuf,
+}
+
+impl GoldenStore {
+    /// Open a fixture directory
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Fixture directory bundled with this crate
+    pub fn bundled() -> Self {
+        Self::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/transcripts"))
+    }
+
+    /// Path of a named fixture
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Load a golden transcript
+    pub fn load(&self, name: &str) -> Result<NegotiationTranscript, String> {
+        let path = self.path(name);
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content).map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Write a transcript as the new golden
+    pub fn save(&self, transcript: &NegotiationTranscript) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(transcript).map_err(|e| e.to_string())?;
+        fs::write(self.path(&transcript.script), content + "\n").map_err(|e| e.to_string())
+    }
+
+    /// Compare a transcript with its golden, or rewrite the golden in update mode
+    pub fn verify(&self, transcript: &NegotiationTranscript, tolerance: &TranscriptTolerance) -> Result<(), String> {
+        if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+            return self.save(transcript);
+        }
+
+        let golden = self.load(&transcript.script)?;
+        let diffs = transcript.diff(&golden, tolerance);
+        if diffs.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "negotiation transcript '{}' diverged from golden (rerun with {}=1 to accept):\n  {}",
+                transcript.script, UPDATE_GOLDEN_ENV, diffs.join("\n  ")
+            ))
+        }
+    }
+}
+
+/// Standard scripts covered by the bundled golden fixtures
+pub fn standard_scripts() -> Vec<(NegotiationAI, NegotiationScript)> {
+    use crate::{MarketConditions, RiskIndicator};
+
+    let context = |agent: f64, counterparty: f64, demand: f64, competition: f64, risks: Vec<RiskIndicator>| {
+        DecisionContext {
+            agent_reputation: agent,
+            counterparty_reputation: counterparty,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: demand,
+                competition_level: competition,
+                average_pricing: 95.0,
+                risk_indicators: risks,
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        }
+    };
+    let risk = |indicator_type: &str, value: f64, confidence: f64| RiskIndicator {
+        indicator_type: indicator_type.to_string(),
+        value,
+        confidence,
+    };
+
+    vec![
+        (
+            NegotiationAI::new(0.1, 0.6),
+            NegotiationScript {
+                name: "balanced_market".to_string(),
+                context: context(0.8, 0.6, 0.7, 0.4, vec![]),
+                base_price: 100.0,
+                counter_offers: vec![100.0, 112.0, 120.0],
+            },
+        ),
+        (
+            NegotiationAI::new(0.1, 0.9),
+            NegotiationScript {
+                name: "risky_low_reputation".to_string(),
+                context: context(0.4, 0.9, 0.2, 0.9, vec![
+                    risk("volatility", 0.5, 0.8),
+                    risk("counterparty_default", 0.3, 0.5),
+                ]),
+                base_price: 50.0,
+                counter_offers: vec![40.0, 45.0, 48.0],
+            },
+        ),
+        (
+            NegotiationAI::new(0.1, 1.0),
+            NegotiationScript {
+                name: "clamped_premium".to_string(),
+                context: context(1.0, 0.0, 1.0, 0.0, vec![risk("volatility", 1.0, 1.0)]),
+                base_price: 10.0,
+                counter_offers: vec![12.0, 16.0],
+            },
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_transcripts_match_golden() {
+        let store = GoldenStore::bundled();
+        let tolerance = TranscriptTolerance::default();
+
+        for (ai, script) in standard_scripts() {
+            let transcript = NegotiationTranscript::record(&ai, &script);
+            store.verify(&transcript, &tolerance).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_behavior_change() {
+        let (ai, script) = standard_scripts().remove(0);
+        let golden = NegotiationTranscript::record(&ai, &script);
+
+        // A more reputable counterparty lowers the ask but raises the acceptance threshold
+        let mut context = script.context.clone();
+        context.counterparty_reputation = 0.9;
+        let changed = NegotiationTranscript::record(&ai, &NegotiationScript { context, ..script });
+
+        let diffs = changed.diff(&golden, &TranscriptTolerance::default());
+        assert!(diffs.iter().any(|d| d.contains("decision changed")));
+        assert!(diffs.iter().any(|d| d.starts_with("pricing.ask")));
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let tolerance = TranscriptTolerance::default();
+        assert!(tolerance.within(100.05, 100.0));
+        assert!(!tolerance.within(100.5, 100.0));
+        assert!(tolerance.within(0.0, 0.0));
+    }
+}
@@ -0,0 +1,119 @@
+//! Time Pressure
+//!
+//! A deal that is not closed before the requester's deadline, or before the
+//! round limit runs out, is worth nothing. `TimePressure` records how much of
+//! the time and rounds are used up, and an `UrgencyCurve` turns that into how
+//! far the acceptance threshold drops: a linear curve concedes steadily, a
+//! steep power curve holds firm until late (Boulware), and a step gives way
+//! all at once.
+
+use serde::{Deserialize, Serialize};
+
+/// How much of the negotiation window is used up, each from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimePressure {
+    pub time_elapsed: Option<f64>,        // Share of the time to the deadline gone
+    pub rounds_elapsed: Option<f64>,      // Share of the round limit gone
+}
+
+impl TimePressure {
+    /// Pressure from a deadline `total_secs` after the negotiation opened
+    pub fn from_deadline(elapsed_secs: f64, total_secs: f64) -> Self {
+        Self::default().with_deadline(elapsed_secs, total_secs)
+    }
+
+    /// Pressure from the round limit
+    pub fn from_rounds(round: u32, max_rounds: u32) -> Self {
+        Self::default().with_rounds(round, max_rounds)
+    }
+
+    pub fn with_deadline(mut self, elapsed_secs: f64, total_secs: f64) -> Self {
+        self.time_elapsed = Some(Self::share(elapsed_secs, total_secs));
+        self
+    }
+
+    pub fn with_rounds(mut self, round: u32, max_rounds: u32) -> Self {
+        self.rounds_elapsed = Some(Self::share(round as f64, max_rounds as f64));
+        self
+    }
+
+    fn share(used: f64, total: f64) -> f64 {
+        if total > 0.0 { (used / total).clamp(0.0, 1.0) } else { 1.0 }
+    }
+
+    /// Overall pressure: whichever limit is closer
+    pub fn level(&self) -> f64 {
+        self.time_elapsed.unwrap_or(0.0).max(self.rounds_elapsed.unwrap_or(0.0))
+    }
+}
+
+/// Shape of the concession as pressure builds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum UrgencyCurve {
+    /// Deadlines never move the threshold
+    None,
+    /// Urgency grows in step with pressure
+    Linear,
+    /// Urgency is pressure raised to `exponent`: above 1.0 holds firm until
+    /// late, below 1.0 concedes early
+    Power { exponent: f64 },
+    /// Full urgency once pressure reaches `at`
+    Step { at: f64 },
+}
+
+impl UrgencyCurve {
+    /// Urgency from 0.0 to 1.0 at a pressure level
+    pub fn urgency(&self, pressure: f64) -> f64 {
+        let pressure = pressure.clamp(0.0, 1.0);
+        match *self {
+            UrgencyCurve::None => 0.0,
+            UrgencyCurve::Linear => pressure,
+            UrgencyCurve::Power { exponent } => pressure.powf(exponent.max(0.0)),
+            UrgencyCurve::Step { at } => if pressure >= at { 1.0 } else { 0.0 },
+        }
+    }
+}
+
+/// Urgency curve with how far full urgency lowers the acceptance threshold
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UrgencyPolicy {
+    pub curve: UrgencyCurve,
+    pub max_relief: f64,                  // Threshold drop at full urgency, as an offer ratio
+}
+
+impl Default for UrgencyPolicy {
+    fn default() -> Self {
+        Self {
+            curve: UrgencyCurve::Linear,
+            max_relief: 0.15,
+        }
+    }
+}
+
+impl UrgencyPolicy {
+    /// Amount to lower the acceptance threshold by under this pressure
+    pub fn relief(&self, pressure: &TimePressure) -> f64 {
+        self.curve.urgency(pressure.level()) * self.max_relief
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curves_shape_the_relief() {
+        let halfway = TimePressure::from_deadline(30.0, 60.0).with_rounds(1, 5);
+        assert_eq!(halfway.level(), 0.5);
+        assert_eq!(TimePressure::from_rounds(9, 0).level(), 1.0);
+
+        let linear = UrgencyPolicy::default();
+        let boulware = UrgencyPolicy { curve: UrgencyCurve::Power { exponent: 3.0 }, ..linear };
+        let step = UrgencyPolicy { curve: UrgencyCurve::Step { at: 0.8 }, ..linear };
+        assert!((linear.relief(&halfway) - 0.075).abs() < 1e-9);
+        assert!(boulware.relief(&halfway) < linear.relief(&halfway));
+        assert_eq!(step.relief(&halfway), 0.0);
+        assert_eq!(step.relief(&TimePressure::from_rounds(4, 5)), 0.15);
+        assert_eq!(UrgencyPolicy { curve: UrgencyCurve::None, ..linear }.relief(&halfway), 0.0);
+    }
+}
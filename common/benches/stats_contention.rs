@@ -0,0 +1,91 @@
+//! Statistics Contention Benchmarks
+//!
+//! Compares the `RwLock<GossipStats>` update path gossip used before its
+//! counters were sharded against `ShardedCounter`, with one task per peer
+//! recording every message it receives, across runtimes of different sizes.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use solace_common::stats::ShardedCounter;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::RwLock;
+
+/// Messages each peer task records per iteration
+const MESSAGES_PER_PEER: u64 = 100;
+
+/// Size of each recorded message
+const MESSAGE_BYTES: u64 = 256;
+
+/// The counters gossip kept behind a single lock
+#[derive(Debug, Default)]
+struct LockedStats {
+    messages_received: u64,
+    bytes_received: u64,
+}
+
+fn runtime(worker_threads: usize) -> Runtime {
+    Builder::new_multi_thread().worker_threads(worker_threads).build().unwrap()
+}
+
+async fn record_locked(peer_count: u64) -> u64 {
+    let stats = Arc::new(RwLock::new(LockedStats::default()));
+
+    let tasks = (0..peer_count).map(|_| {
+        let stats = stats.clone();
+        tokio::spawn(async move {
+            for _ in 0..MESSAGES_PER_PEER {
+                let mut stats = stats.write().await;
+                stats.messages_received += 1;
+                stats.bytes_received += MESSAGE_BYTES;
+            }
+        })
+    });
+
+    futures::future::join_all(tasks).await;
+    let received = stats.read().await.messages_received;
+    received
+}
+
+async fn record_sharded(peer_count: u64) -> u64 {
+    let received = Arc::new(ShardedCounter::new());
+    let bytes = Arc::new(ShardedCounter::new());
+
+    let tasks = (0..peer_count).map(|_| {
+        let received = received.clone();
+        let bytes = bytes.clone();
+        tokio::spawn(async move {
+            for _ in 0..MESSAGES_PER_PEER {
+                received.increment();
+                bytes.add(MESSAGE_BYTES);
+            }
+        })
+    });
+
+    futures::future::join_all(tasks).await;
+    received.get()
+}
+
+/// Messages recorded per second by peer count, for each runtime size
+fn bench_stats_contention(c: &mut Criterion) {
+    for worker_threads in [1, 4, 8] {
+        let rt = runtime(worker_threads);
+        let mut group = c.benchmark_group(format!("stats_contention/{}_threads", worker_threads));
+
+        for peer_count in [10u64, 100, 1000] {
+            group.throughput(Throughput::Elements(peer_count * MESSAGES_PER_PEER));
+
+            group.bench_with_input(BenchmarkId::new("rwlock", peer_count), &peer_count, |b, &peer_count| {
+                b.to_async(&rt).iter(|| async move { black_box(record_locked(peer_count).await) });
+            });
+
+            group.bench_with_input(BenchmarkId::new("sharded", peer_count), &peer_count, |b, &peer_count| {
+                b.to_async(&rt).iter(|| async move { black_box(record_sharded(peer_count).await) });
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_stats_contention);
+criterion_main!(benches);
@@ -0,0 +1,8 @@
+//! Solace Common
+//!
+//! Small building blocks shared by the framework and ACP crates. It has no
+//! heavy dependencies, so either crate can use it without pulling in the
+//! other.
+
+pub mod rng;
+pub mod stats;
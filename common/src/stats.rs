@@ -0,0 +1,145 @@
+//! Statistics Counters Module
+//!
+//! Lock-free counters for statistics updated on hot paths. A single
+//! `RwLock<Stats>` serializes every message through one write lock; the
+//! counters here spread writes across cache-line padded shards and only
+//! aggregate them when a snapshot is read. Shards are signed, so a value
+//! removed on a different thread than it was added on never wraps a shard.
+
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+
+/// Number of shards per counter
+const SHARD_COUNT: usize = 16;
+
+/// Source of per-thread shard assignments
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SHARD_INDEX: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed) % SHARD_COUNT;
+}
+
+/// Single shard padded to its own cache line to avoid false sharing
+#[repr(align(64))]
+#[derive(Debug, Default)]
+struct Shard(AtomicI64);
+
+/// Counter sharded across threads
+///
+/// Each thread writes to its own shard, so concurrent updates never contend
+/// on the same cache line. `get` sums all shards without blocking writers, so
+/// it can observe a subtraction before the addition it cancels; such a sum is
+/// reported as zero rather than wrapping.
+#[derive(Debug)]
+pub struct ShardedCounter {
+    shards: [Shard; SHARD_COUNT],
+}
+
+impl ShardedCounter {
+    /// Create a new counter starting at zero
+    pub fn new() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| Shard::default()),
+        }
+    }
+
+    /// Add a value to the counter
+    pub fn add(&self, value: u64) {
+        self.shard().fetch_add(to_delta(value), Ordering::Relaxed);
+    }
+
+    /// Subtract a value from the counter
+    pub fn sub(&self, value: u64) {
+        self.shard().fetch_sub(to_delta(value), Ordering::Relaxed);
+    }
+
+    /// Increment the counter by one
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    /// Get the aggregated counter value, never below zero
+    pub fn get(&self) -> u64 {
+        let total = self
+            .shards
+            .iter()
+            .fold(0i64, |total, shard| total.saturating_add(shard.0.load(Ordering::Relaxed)));
+        total.max(0) as u64
+    }
+
+    /// Shard owned by the calling thread
+    fn shard(&self) -> &AtomicI64 {
+        let index = SHARD_INDEX.with(|index| *index);
+        &self.shards[index].0
+    }
+}
+
+/// Convert an update to a shard delta, saturating at `i64::MAX`
+fn to_delta(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+impl Default for ShardedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sharded_counter() {
+        let counter = ShardedCounter::new();
+        counter.increment();
+        counter.add(41);
+        assert_eq!(counter.get(), 42);
+    }
+
+    #[test]
+    fn test_concurrent_increments() {
+        let counter = Arc::new(ShardedCounter::new());
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let counter = counter.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        counter.increment();
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(counter.get(), 8000);
+    }
+
+    #[test]
+    fn test_sub_on_another_thread() {
+        let counter = Arc::new(ShardedCounter::new());
+        counter.add(10);
+
+        let remote = counter.clone();
+        std::thread::spawn(move || remote.sub(4)).join().unwrap();
+        assert_eq!(counter.get(), 6);
+
+        let remote = counter.clone();
+        std::thread::spawn(move || remote.sub(6)).join().unwrap();
+        assert_eq!(counter.get(), 0);
+    }
+
+    #[test]
+    fn test_get_never_wraps_below_zero() {
+        let counter = ShardedCounter::new();
+        counter.sub(5);
+        assert_eq!(counter.get(), 0);
+
+        counter.add(7);
+        assert_eq!(counter.get(), 2);
+    }
+}
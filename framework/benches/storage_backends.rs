@@ -0,0 +1,302 @@
+//! Storage Backend Comparison Benchmarks
+//!
+//! Runs the same workloads against every available `Storage` backend and
+//! writes a markdown comparison table to `target/criterion/storage_comparison.md`
+//! so operators can pick a backend for their workload.
+//!
+//! Run with `cargo bench --bench storage_backends` (add `--features storage`
+//! to include RocksDB). `SOLACE_BENCH_SCAN_KEYS` overrides the number of keys
+//! loaded for the prefix scan workload.
+
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use solace_protocol::storage::{MemoryStorage, Storage, StorageKey};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::runtime::Runtime;
+
+/// Size of values in the small put workload
+const SMALL_VALUE_BYTES: usize = 64;
+
+/// Size of values in the large value workload
+const LARGE_VALUE_BYTES: usize = 1024 * 1024;
+
+/// Entries written per batch in the batch write workload
+const BATCH_SIZE: usize = 1_000;
+
+/// Default number of keys loaded for the prefix scan workload
+const DEFAULT_SCAN_KEYS: usize = 1_000_000;
+
+/// Number of prefix buckets the scan keys are spread over
+const SCAN_BUCKETS: usize = 100;
+
+/// Workloads run against every backend, in table column order
+const WORKLOADS: [Workload; 4] = [
+    Workload::SmallPut,
+    Workload::LargeValue,
+    Workload::PrefixScan,
+    Workload::BatchWrite,
+];
+
+/// Benchmark workload
+#[derive(Debug, Clone, Copy)]
+enum Workload {
+    SmallPut,
+    LargeValue,
+    PrefixScan,
+    BatchWrite,
+}
+
+impl Workload {
+    /// Criterion group name
+    fn group(&self) -> &'static str {
+        match self {
+            Workload::SmallPut => "storage_small_put",
+            Workload::LargeValue => "storage_large_value",
+            Workload::PrefixScan => "storage_prefix_scan",
+            Workload::BatchWrite => "storage_batch_write",
+        }
+    }
+
+    /// Column heading in the comparison table
+    fn heading(&self) -> String {
+        match self {
+            Workload::SmallPut => format!("Small put ({} B)", SMALL_VALUE_BYTES),
+            Workload::LargeValue => "1 MB value put".to_string(),
+            Workload::PrefixScan => format!("Prefix scan ({} keys)", scan_keys()),
+            Workload::BatchWrite => format!("Batch write ({} entries)", BATCH_SIZE),
+        }
+    }
+
+    /// Elements processed by one iteration
+    fn elements(&self) -> u64 {
+        match self {
+            Workload::SmallPut | Workload::LargeValue => 1,
+            Workload::PrefixScan => (scan_keys() / SCAN_BUCKETS) as u64,
+            Workload::BatchWrite => BATCH_SIZE as u64,
+        }
+    }
+}
+
+/// A backend instance under test, with any resources it must outlive
+struct Backend<S> {
+    storage: S,
+    _dir: Option<tempfile::TempDir>,
+}
+
+/// In-memory backend
+fn memory_backend() -> Backend<MemoryStorage> {
+    Backend {
+        storage: MemoryStorage::new(),
+        _dir: None,
+    }
+}
+
+/// RocksDB backend in a fresh temporary directory
+#[cfg(feature = "storage")]
+fn rocksdb_backend() -> Backend<solace_protocol::storage::RocksDbStorage> {
+    use solace_protocol::storage::{RocksDbStorage, StorageConfig};
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = StorageConfig {
+        data_dir: dir.path().to_path_buf(),
+        ..StorageConfig::default()
+    };
+
+    Backend {
+        storage: RocksDbStorage::new(&config).unwrap(),
+        _dir: Some(dir),
+    }
+}
+
+/// Number of keys loaded for the prefix scan workload
+fn scan_keys() -> usize {
+    std::env::var("SOLACE_BENCH_SCAN_KEYS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_SCAN_KEYS)
+}
+
+/// Key for entry `i` of the prefix scan data set
+fn scan_key(i: usize) -> StorageKey {
+    StorageKey::Custom(format!("scan/{:02}/{:07}", i % SCAN_BUCKETS, i))
+}
+
+/// Run every workload against one backend
+fn bench_backend<S, F>(c: &mut Criterion, rt: &Runtime, name: &str, make: F)
+where
+    S: Storage,
+    F: Fn() -> Backend<S>,
+{
+    for workload in WORKLOADS {
+        let backend = make();
+        let storage = &backend.storage;
+        let counter = AtomicU64::new(0);
+
+        let mut group = c.benchmark_group(workload.group());
+        group.throughput(Throughput::Elements(workload.elements()));
+
+        match workload {
+            Workload::SmallPut => {
+                let value = "x".repeat(SMALL_VALUE_BYTES);
+                group.bench_function(BenchmarkId::from_parameter(name), |b| {
+                    b.to_async(rt).iter(|| {
+                        let i = counter.fetch_add(1, Ordering::Relaxed);
+                        let value = &value;
+                        async move {
+                            storage.put(StorageKey::Custom(format!("small/{}", i)), value).await.unwrap();
+                        }
+                    });
+                });
+            }
+            Workload::LargeValue => {
+                let value = "x".repeat(LARGE_VALUE_BYTES);
+                group.sample_size(10);
+                group.bench_function(BenchmarkId::from_parameter(name), |b| {
+                    b.to_async(rt).iter(|| {
+                        // Rotate over a small key set so memory stays bounded
+                        let i = counter.fetch_add(1, Ordering::Relaxed) % 16;
+                        let value = &value;
+                        async move {
+                            storage.put(StorageKey::Custom(format!("large/{}", i)), value).await.unwrap();
+                        }
+                    });
+                });
+            }
+            Workload::PrefixScan => {
+                let total = scan_keys();
+                rt.block_on(async {
+                    for chunk in (0..total).collect::<Vec<_>>().chunks(BATCH_SIZE * 10) {
+                        let operations = chunk.iter().map(|&i| (scan_key(i), i as u64)).collect();
+                        storage.batch_put(operations).await.unwrap();
+                    }
+                });
+
+                group.sample_size(10);
+                group.bench_function(BenchmarkId::from_parameter(name), |b| {
+                    b.to_async(rt).iter(|| {
+                        let bucket = counter.fetch_add(1, Ordering::Relaxed) as usize % SCAN_BUCKETS;
+                        async move {
+                            let prefix = format!("custom:scan/{:02}/", bucket);
+                            black_box(storage.list_keys(&prefix).await.unwrap());
+                        }
+                    });
+                });
+            }
+            Workload::BatchWrite => {
+                group.bench_function(BenchmarkId::from_parameter(name), |b| {
+                    b.to_async(rt).iter(|| {
+                        let batch = counter.fetch_add(1, Ordering::Relaxed);
+                        let operations: Vec<_> = (0..BATCH_SIZE)
+                            .map(|i| (StorageKey::Custom(format!("batch/{}/{}", batch, i)), i as u64))
+                            .collect();
+                        async move {
+                            storage.batch_put(operations).await.unwrap();
+                        }
+                    });
+                });
+            }
+        }
+
+        group.finish();
+    }
+}
+
+/// Criterion output directory
+fn criterion_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"))
+        .join("criterion")
+}
+
+/// Mean time per iteration in nanoseconds, read from criterion's estimates
+fn mean_nanos(workload: Workload, backend: &str) -> Option<f64> {
+    let path = criterion_dir()
+        .join(workload.group())
+        .join(backend)
+        .join("new")
+        .join("estimates.json");
+    let estimates: serde_json::Value = serde_json::from_slice(&std::fs::read(path).ok()?).ok()?;
+    estimates["mean"]["point_estimate"].as_f64()
+}
+
+/// Format a duration given in nanoseconds
+fn format_nanos(nanos: f64) -> String {
+    if nanos >= 1e9 {
+        format!("{:.2} s", nanos / 1e9)
+    } else if nanos >= 1e6 {
+        format!("{:.2} ms", nanos / 1e6)
+    } else if nanos >= 1e3 {
+        format!("{:.2} µs", nanos / 1e3)
+    } else {
+        format!("{:.0} ns", nanos)
+    }
+}
+
+/// Format a rate given in elements per second
+fn format_rate(per_sec: f64) -> String {
+    if per_sec >= 1e6 {
+        format!("{:.2}M/s", per_sec / 1e6)
+    } else if per_sec >= 1e3 {
+        format!("{:.2}K/s", per_sec / 1e3)
+    } else {
+        format!("{:.1}/s", per_sec)
+    }
+}
+
+/// Build the markdown comparison table from criterion's results
+fn comparison_table(backends: &[&str]) -> String {
+    let mut table = String::from("# Storage Backend Comparison\n\n");
+    table.push_str("Mean time per operation, with throughput in parentheses. Lower is better.\n\n");
+
+    table.push_str("| Backend |");
+    for workload in WORKLOADS {
+        let _ = write!(table, " {} |", workload.heading());
+    }
+    table.push_str("\n|---|");
+    table.push_str(&"---|".repeat(WORKLOADS.len()));
+    table.push('\n');
+
+    for backend in backends {
+        let _ = write!(table, "| {} |", backend);
+        for workload in WORKLOADS {
+            match mean_nanos(workload, backend) {
+                Some(nanos) => {
+                    let rate = workload.elements() as f64 / (nanos / 1e9);
+                    let _ = write!(table, " {} ({}) |", format_nanos(nanos), format_rate(rate));
+                }
+                None => table.push_str(" n/a |"),
+            }
+        }
+        table.push('\n');
+    }
+
+    table
+}
+
+fn main() {
+    let rt = Runtime::new().unwrap();
+    let mut criterion = Criterion::default().configure_from_args();
+    let mut backends = Vec::new();
+
+    // Register new backends here so they show up in the comparison table
+    bench_backend(&mut criterion, &rt, "memory", memory_backend);
+    backends.push("memory");
+
+    #[cfg(feature = "storage")]
+    {
+        bench_backend(&mut criterion, &rt, "rocksdb", rocksdb_backend);
+        backends.push("rocksdb");
+    }
+
+    criterion.final_summary();
+
+    let path = criterion_dir().join("storage_comparison.md");
+    match std::fs::create_dir_all(criterion_dir())
+        .and_then(|_| std::fs::write(&path, comparison_table(&backends)))
+    {
+        Ok(()) => println!("Storage comparison written to {}", path.display()),
+        Err(e) => eprintln!("Failed to write storage comparison: {}", e),
+    }
+}
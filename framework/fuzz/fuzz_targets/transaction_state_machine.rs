@@ -0,0 +1,191 @@
+//! Fuzz target for the transaction state machine and escrow saga
+//!
+//! Input bytes are decoded into a typed event sequence that is applied to a
+//! `Transaction`. Alongside it runs the escrow saga driven by the transaction:
+//! funds are locked when a proposal is accepted, released to the provider on
+//! completion, and refunded when the transaction fails or expires. After every
+//! event, and again after driving the transaction to a final status, the
+//! target asserts that no funds are locked forever or released twice.
+//!
+//! Input layout: the first byte selects the deadline (odd = already passed),
+//! then each event is an opcode byte followed by an operand byte.
+//!
+//! Run with `cargo fuzz run transaction_state_machine` from `framework/`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solace_protocol::transaction::{ExecutionData, TransactionEvaluation, TransactionProposal};
+use solace_protocol::types::ServiceType;
+use solace_protocol::*;
+use std::collections::HashMap;
+
+/// Request budget in lamports
+const BUDGET: u64 = 1_000_000;
+
+/// Number of distinct providers the fuzzer can address
+const PROVIDERS: usize = 4;
+
+/// Typed transaction event
+#[derive(Debug, Clone, Copy)]
+enum Event {
+    Propose { provider: usize, price: u64 },
+    Accept { provider: usize, price: u64 },
+    Complete,
+    Evaluate,
+    Cancel,
+    Fail,
+    Expire,
+}
+
+impl Event {
+    /// Decode an event from an opcode and operand byte
+    fn decode(opcode: u8, operand: u8) -> Self {
+        // Low two bits pick the provider, the rest a price of 0-315% of budget
+        let provider = (operand & 0b11) as usize % PROVIDERS;
+        let price = BUDGET * (operand >> 2) as u64 * 5 / 100;
+
+        match opcode % 7 {
+            0 => Event::Propose { provider, price },
+            1 => Event::Accept { provider, price },
+            2 => Event::Complete,
+            3 => Event::Evaluate,
+            4 => Event::Cancel,
+            5 => Event::Fail,
+            _ => Event::Expire,
+        }
+    }
+}
+
+/// Escrow saga state mirrored from transaction transitions
+#[derive(Debug, Default)]
+struct Escrow {
+    locked: u64,
+    released: u64,
+    refunded: u64,
+    releases: u32,
+    refunds: u32,
+}
+
+impl Escrow {
+    /// React to a status transition of the transaction
+    fn on_transition(&mut self, before: TransactionStatus, transaction: &Transaction) {
+        match (before, transaction.status) {
+            (TransactionStatus::Pending, TransactionStatus::InProgress) => {
+                let price = transaction.agreed_price.expect("accepted transaction has a price").0;
+                assert_eq!(self.locked, 0, "funds locked twice");
+                self.locked = price;
+            }
+            (_, TransactionStatus::Completed) if before != TransactionStatus::Completed => {
+                self.released += self.locked;
+                self.locked = 0;
+                self.releases += 1;
+            }
+            (_, TransactionStatus::Failed | TransactionStatus::Expired | TransactionStatus::Cancelled)
+                if before != transaction.status =>
+            {
+                if self.locked > 0 {
+                    self.refunded += self.locked;
+                    self.locked = 0;
+                    self.refunds += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Invariants that must hold after every step
+    fn check(&self, transaction: &Transaction) {
+        assert!(self.releases <= 1, "escrow released {} times", self.releases);
+        assert!(self.refunds <= 1, "escrow refunded {} times", self.refunds);
+        assert!(self.releases + self.refunds <= 1, "escrow both released and refunded");
+        assert!(self.locked + self.released + self.refunded <= BUDGET, "escrow moved more than the budget");
+
+        if let Some(price) = transaction.agreed_price {
+            assert!(price.0 <= transaction.request.budget.0, "agreed price {} over budget", price);
+            assert!(transaction.provider.is_some(), "agreed price without provider");
+        }
+
+        if transaction.is_terminal() {
+            assert_eq!(self.locked, 0, "funds locked in final status {:?}", transaction.status);
+        }
+
+        if transaction.status == TransactionStatus::Completed {
+            assert_eq!(Some(self.released), transaction.agreed_price.map(|p| p.0));
+        }
+    }
+}
+
+/// Apply an event, ignoring rejected transitions
+fn apply(transaction: &mut Transaction, providers: &[AgentId], event: Event) {
+    let _ = match event {
+        Event::Propose { provider, price } => transaction.add_proposal(TransactionProposal {
+            id: TransactionId::new(),
+            request_id: transaction.id,
+            provider: providers[provider],
+            proposed_price: Balance::new(price),
+            estimated_completion: Timestamp::now(),
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: transaction.request.deadline,
+        }),
+        Event::Accept { provider, price } => transaction.accept_proposal(providers[provider], Balance::new(price)),
+        Event::Complete => transaction.complete_execution(ExecutionData {
+            result: String::new(),
+            artifacts: Vec::new(),
+            completion_time: Timestamp::now(),
+            quality_metrics: HashMap::new(),
+        }),
+        Event::Evaluate => transaction.add_evaluation(TransactionEvaluation {
+            requester_rating: 1.0,
+            provider_rating: 1.0,
+            requester_feedback: String::new(),
+            provider_feedback: String::new(),
+            quality_score: 1.0,
+            timeliness_score: 1.0,
+            overall_satisfaction: 1.0,
+        }),
+        Event::Cancel => transaction.cancel(),
+        Event::Fail => transaction.fail("fuzz".to_string()),
+        Event::Expire => transaction.expire(),
+    };
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&deadline_byte, events)) = data.split_first() else {
+        return;
+    };
+
+    let offset = if deadline_byte & 1 == 1 { -1 } else { 1 };
+    let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(offset));
+    let request = TransactionRequest::new(
+        AgentId::new(),
+        ServiceType::DataAnalysis,
+        "fuzz".to_string(),
+        Balance::new(BUDGET),
+        deadline,
+    );
+
+    let providers: Vec<AgentId> = (0..PROVIDERS).map(|_| AgentId::new()).collect();
+    let mut transaction = Transaction::new(request);
+    let mut escrow = Escrow::default();
+
+    for pair in events.chunks_exact(2) {
+        let before = transaction.status;
+        apply(&mut transaction, &providers, Event::decode(pair[0], pair[1]));
+        escrow.on_transition(before, &transaction);
+        escrow.check(&transaction);
+    }
+
+    // Every reachable state must still have a way to a final status
+    if !transaction.is_terminal() {
+        for event in [Event::Cancel, Event::Fail, Event::Evaluate] {
+            let before = transaction.status;
+            apply(&mut transaction, &providers, event);
+            escrow.on_transition(before, &transaction);
+        }
+        assert!(transaction.is_terminal(), "stuck in {:?}/{:?}", transaction.phase, transaction.status);
+        escrow.check(&transaction);
+    }
+});
@@ -5,13 +5,22 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProtocolVersion(pub String);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MessageType {
     TransactionRequest,
     TransactionProposal,
     TransactionAcceptance,
     TransactionCompletion,
     ReputationUpdate,
+    ProtocolParamsUpdate,
+    QuoteRequest,
+    Quote,
+    BlockHeader,
+    ValidatorSet,
+    CapacityAd,
+    ServiceOffer,
+    MarketStats,
+    PartialSignature,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
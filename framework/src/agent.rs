@@ -1,14 +1,43 @@
 //! Agent implementation for autonomous commerce
 
 use crate::{
-    error::{AgentError, Result},
+    acp::{ACPMessage, MessageType},
+    analytics::MarketAnalytics,
+    capability::{CapabilityInfo, CapabilityRegistry},
+    capacity::{CapacityAd, CapacityBoard, ProviderCapacity, AD_TTL_SECS},
+    compliance::{ComplianceChecker, ComplianceRules, ComplianceViolation},
+    cost::CostModel,
+    crypto::{KeyPair, MultisigWallet, NodeRole},
+    error::{AgentError, Result, TransactionError},
+    escrow::{Escrow, ReleaseApproval},
+    fast_path::{FastPath, FastPathMetrics, FastPathPolicy},
+    governance::{ParamsUpdate, ProtocolParams},
+    knowledge::{DomainMembership, KnowledgeMember, SharedObservations, TrustDomain},
+    marketplace::{Marketplace, OfferSla, ProviderCandidate, ProviderFilters, ServiceOffer, OFFER_TTL_SECS},
+    matching::{QuoteChannel, ServiceMatch, ServiceRequest},
+    negotiation::NegotiationSession,
+    observer::ReputationUpdate,
+    offer_book::{Offer, OfferBook, OfferPoint},
+    privacy::{PrivacyPolicy, PublishedMarketStats},
     reputation::ReputationScore,
-    types::{AgentId, Balance, NetworkAddress, ServiceType, Timestamp, WalletInfo},
+    standby::{ActiveReplicator, StateChange, Takeover},
+    rfq::{Quote, QuoteIntent, RfqMessage, RfqSession, SelectionWeights},
+    storage::StorageManager,
+    transaction::{Transaction, TransactionProposal, TransactionRequest},
+    transaction_manager::{TransactionManager, Transition},
+    types::{AgentId, Balance, NetworkAddress, Payment, PaymentAsset, ServiceType, Timestamp, TransactionId, WalletInfo},
 };
+use solace_common::rng::NodeRng;
 use serde::{Deserialize, Serialize};
+use solace_ai::advisor::WeightedAdvisor;
+use solace_ai::profile::CounterpartyProfiles;
+use solace_ai::risk::{Exposure, RiskBudget};
+use solace_ai::strategy::{AiStrategy, CounterOfferResponse, NegotiationState, NegotiationStrategy as AiNegotiationStrategy, ProposalTerms};
+use solace_ai::{DecisionContext, MarketConditions, MarketPredictor, TransactionOutcome};
 use solana_sdk::{pubkey::Pubkey, signature::Keypair};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::{BTreeMap, HashMap}, sync::Arc};
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 /// Agent capabilities that define what services an agent can provide
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -23,16 +52,10 @@ pub enum AgentCapability {
 }
 
 impl AgentCapability {
+    /// Capabilities and services match on their registry key, see
+    /// `crate::capability`
     pub fn matches_service(&self, service: &ServiceType) -> bool {
-        match (self, service) {
-            (AgentCapability::DataAnalysis, ServiceType::DataAnalysis) => true,
-            (AgentCapability::ComputationalTask, ServiceType::ComputationalTask) => true,
-            (AgentCapability::MarketResearch, ServiceType::MarketResearch) => true,
-            (AgentCapability::ContentCreation, ServiceType::ContentCreation) => true,
-            (AgentCapability::TradingService, ServiceType::TradingService) => true,
-            (AgentCapability::CustomCapability(cap), ServiceType::CustomService(srv)) => cap == srv,
-            _ => false,
-        }
+        self.key() == service.key()
     }
 }
 
@@ -45,9 +68,10 @@ pub struct AgentPreferences {
     pub max_transaction_value: Balance,
     /// Minimum reputation score required for counterparties
     pub min_counterparty_reputation: f64,
-    /// Preferred payment methods
-    pub preferred_payment_methods: Vec<String>,
-    /// Automatic acceptance threshold for reputation scores
+    /// Assets the agent pays and accepts payment in
+    pub preferred_payment_methods: Vec<PaymentAsset>,
+    /// Counterparty reputation at which micro-transaction offers close to
+    /// our ask are accepted without full evaluation
     pub auto_accept_threshold: f64,
     /// Geographic preferences (optional)
     pub geographic_preferences: Option<Vec<String>>,
@@ -59,18 +83,27 @@ impl Default for AgentPreferences {
             risk_tolerance: 0.5,
             max_transaction_value: Balance::from_sol(100.0),
             min_counterparty_reputation: 0.3,
-            preferred_payment_methods: vec!["SOL".to_string()],
+            preferred_payment_methods: vec![PaymentAsset::Sol],
             auto_accept_threshold: 0.8,
             geographic_preferences: None,
         }
     }
 }
 
+impl AgentPreferences {
+    /// Whether the agent pays or takes payment in `asset`
+    pub fn accepts(&self, asset: &PaymentAsset) -> bool {
+        self.preferred_payment_methods.contains(asset)
+    }
+}
+
 /// Configuration for creating a new agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentConfig {
     /// Agent's wallet keypair
     pub keypair: Option<Keypair>,
+    /// Public key of a watch-only agent, whose keypair is held by an external signer
+    pub watch_key: Option<Pubkey>,
     /// Agent's display name
     pub name: String,
     /// Agent's description
@@ -83,6 +116,169 @@ pub struct AgentConfig {
     pub network_address: Option<NetworkAddress>,
     /// Initial reputation score (for testing, normally starts at 0.5)
     pub initial_reputation: Option<f64>,
+    /// Random ID the agent had before IDs were derived from its key; records
+    /// under it are moved by `migrate_legacy_id`
+    pub legacy_id: Option<AgentId>,
+    /// Descriptions of the custom capabilities offered, registered with the
+    /// agent's `CapabilityRegistry`
+    #[serde(default)]
+    pub custom_services: Vec<CapabilityInfo>,
+}
+
+impl AgentConfig {
+    /// Start building a configuration for an agent named `name`
+    pub fn builder(name: impl Into<String>) -> AgentConfigBuilder {
+        AgentConfigBuilder::new(name)
+    }
+
+    /// Check the configuration before an agent is created from it
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(keypair), Some(watch_key)) = (&self.keypair, self.watch_key) {
+            if keypair.pubkey() != watch_key {
+                return Err(AgentError::InvalidConfig {
+                    reason: "Watch key does not match the agent's keypair".to_string(),
+                }.into());
+            }
+        }
+
+        if self.name.trim().is_empty() {
+            return Err(AgentError::InvalidConfig {
+                reason: "Agent name cannot be empty".to_string(),
+            }.into());
+        }
+
+        if self.capabilities.is_empty() {
+            return Err(AgentError::InvalidConfig {
+                reason: "Agent must have at least one capability".to_string(),
+            }.into());
+        }
+
+        let custom = self.capabilities.iter().filter(|capability| matches!(capability, AgentCapability::CustomCapability(_)));
+        for capability in custom {
+            if !self.custom_services.iter().any(|info| info.key == capability.key()) {
+                return Err(AgentError::InvalidConfig {
+                    reason: format!("Custom capability {} has no entry in custom_services", capability.key()),
+                }.into());
+            }
+        }
+
+        if self.preferences.risk_tolerance < 0.0 || self.preferences.risk_tolerance > 1.0 {
+            return Err(AgentError::InvalidConfig {
+                reason: "Risk tolerance must be between 0.0 and 1.0".to_string(),
+            }.into());
+        }
+
+        if self.preferences.min_counterparty_reputation < 0.0 || self.preferences.min_counterparty_reputation > 1.0 {
+            return Err(AgentError::InvalidConfig {
+                reason: "Minimum counterparty reputation must be between 0.0 and 1.0".to_string(),
+            }.into());
+        }
+
+        if self.preferences.preferred_payment_methods.is_empty() {
+            return Err(AgentError::InvalidConfig {
+                reason: "At least one payment method is required".to_string(),
+            }.into());
+        }
+
+        if !(0.0..=1.0).contains(&self.preferences.auto_accept_threshold) {
+            return Err(AgentError::InvalidConfig {
+                reason: "Auto-accept threshold must be between 0.0 and 1.0".to_string(),
+            }.into());
+        }
+
+        if let Some(reputation) = self.initial_reputation {
+            if !(0.0..=1.0).contains(&reputation) {
+                return Err(AgentError::InvalidConfig {
+                    reason: "Initial reputation must be between 0.0 and 1.0".to_string(),
+                }.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds an `AgentConfig`, validating it at `build`
+#[derive(Debug)]
+pub struct AgentConfigBuilder {
+    config: AgentConfig,
+}
+
+impl AgentConfigBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            config: AgentConfig {
+                keypair: None,
+                watch_key: None,
+                name: name.into(),
+                description: String::new(),
+                capabilities: Vec::new(),
+                preferences: AgentPreferences::default(),
+                network_address: None,
+                initial_reputation: None,
+                legacy_id: None,
+                custom_services: Vec::new(),
+            },
+        }
+    }
+
+    pub fn with_keypair(mut self, keypair: Keypair) -> Self {
+        self.config.keypair = Some(keypair);
+        self
+    }
+
+    /// Watch-only agents are created with `Agent::watch_only`
+    pub fn with_watch_key(mut self, watch_key: Pubkey) -> Self {
+        self.config.watch_key = Some(watch_key);
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.config.description = description.into();
+        self
+    }
+
+    pub fn with_capability(mut self, capability: AgentCapability) -> Self {
+        if !self.config.capabilities.contains(&capability) {
+            self.config.capabilities.push(capability);
+        }
+        self
+    }
+
+    /// Offer the custom service `info` describes, registering the description with the agent
+    pub fn with_custom_service(mut self, info: CapabilityInfo) -> Self {
+        let capability = info.service_type().map(|service| AgentCapability::from(&service));
+        self.config.custom_services.push(info);
+        match capability {
+            Some(capability) => self.with_capability(capability),
+            None => self,
+        }
+    }
+
+    pub fn with_preferences(mut self, preferences: AgentPreferences) -> Self {
+        self.config.preferences = preferences;
+        self
+    }
+
+    pub fn with_network_address(mut self, address: NetworkAddress) -> Self {
+        self.config.network_address = Some(address);
+        self
+    }
+
+    pub fn with_initial_reputation(mut self, reputation: f64) -> Self {
+        self.config.initial_reputation = Some(reputation);
+        self
+    }
+
+    pub fn with_legacy_id(mut self, legacy_id: AgentId) -> Self {
+        self.config.legacy_id = Some(legacy_id);
+        self
+    }
+
+    pub fn build(self) -> Result<AgentConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
 }
 
 /// Agent state enumeration
@@ -113,24 +309,90 @@ pub struct Agent {
     pub created_at: Timestamp,
     /// Last activity timestamp
     pub last_active: Arc<RwLock<Timestamp>>,
+    /// Negotiation behavior
+    pub negotiation: Arc<RwLock<Box<dyn AiNegotiationStrategy>>>,
+    /// Governance parameters, including per-service price bounds
+    pub protocol_params: Arc<RwLock<ProtocolParams>>,
+    /// Council whose approvals a gossiped params update needs
+    pub governance_council: Arc<RwLock<Option<MultisigWallet>>>,
+    /// Durable store every transition of our transactions goes through
+    pub transactions: Arc<TransactionManager>,
+    /// Replication to a warm standby, if paired; fences the agent when the
+    /// standby cannot be reached
+    pub replicator: Option<Arc<ActiveReplicator>>,
+    /// Reliable messages awaiting acknowledgment, by id
+    pub outbox: Arc<RwLock<BTreeMap<String, ACPMessage>>>,
+    /// Escrows locked for our transactions, collecting treasury approvals
+    pub escrows: Arc<RwLock<HashMap<TransactionId, Escrow>>>,
+    /// Reputation penalties for price violations, awaiting broadcast
+    pub reputation_penalties: Arc<RwLock<Vec<ReputationUpdate>>>,
+    /// Open negotiations by transaction
+    pub negotiations: Arc<RwLock<HashMap<TransactionId, NegotiationSession>>>,
+    /// Observed counterparty behavior
+    pub counterparty_profiles: Arc<RwLock<CounterpartyProfiles>>,
+    /// Compute cost per service, for margins and budget screening
+    pub cost_model: Arc<RwLock<CostModel>>,
+    /// Value-at-risk limit across agreed deals, if set
+    pub risk_budget: Arc<RwLock<Option<RiskBudget>>>,
+    /// Per-service statistics from observed transactions
+    pub market_analytics: Arc<RwLock<MarketAnalytics>>,
+    /// External advisor consulted during negotiation, if any
+    pub advisor: Arc<RwLock<Option<WeightedAdvisor>>>,
+    /// Our open requests for quotes, by intent
+    pub rfqs: Arc<RwLock<HashMap<TransactionId, RfqSession>>>,
+    /// Proposals collected for our open requests, by request
+    pub offer_books: Arc<RwLock<HashMap<TransactionId, OfferBook>>>,
+    /// Price models per service, fed by our deals and our trust domain's
+    pub market_predictors: Arc<RwLock<HashMap<ServiceType, MarketPredictor>>>,
+    /// Seat in the owner's trust domain, if joined
+    pub knowledge: Arc<RwLock<Option<KnowledgeMember>>>,
+    /// Auto-accept fast path for micro-transactions, with its metrics
+    pub fast_path: Arc<RwLock<FastPath>>,
+    /// Services the agent knows how to request and screen
+    pub capability_registry: Arc<RwLock<CapabilityRegistry>>,
+    /// Protocol rules inbound negotiation messages are checked against
+    pub compliance: Arc<RwLock<ComplianceChecker>>,
+    /// Job slots we have committed as a provider
+    pub capacity: Arc<RwLock<ProviderCapacity>>,
+    /// Latest capacity ads from providers we may propose to
+    pub capacity_board: Arc<RwLock<CapacityBoard>>,
+    /// Service offers published on the marketplace, ours included
+    pub marketplace: Arc<RwLock<Marketplace>>,
+    /// Where `request_service` sends quote requests, if anywhere
+    pub quote_channel: Arc<RwLock<Option<Arc<dyn QuoteChannel>>>>,
+    /// Whether the agent may sign and transact, or only observe
+    pub role: NodeRole,
+    /// Seeded randomness, such as the noise on published statistics;
+    /// replayable with `SOLACE_SEED`
+    pub rng: Arc<NodeRng>,
 }
 
 impl Agent {
-    /// Create a new agent with the given configuration
-    pub async fn new(mut config: AgentConfig) -> Result<Self> {
-        // Generate keypair if not provided
-        if config.keypair.is_none() {
+    /// Create a new agent with the given configuration and the AI-driven negotiation strategy
+    pub async fn new(config: AgentConfig) -> Result<Self> {
+        Self::with_strategy(config, Box::new(AiStrategy::default())).await
+    }
+
+    /// Create a new agent with a custom negotiation strategy
+    pub async fn with_strategy(mut config: AgentConfig, strategy: Box<dyn AiNegotiationStrategy>) -> Result<Self> {
+        // Generate keypair if not provided, unless the agent is watch-only
+        if config.keypair.is_none() && config.watch_key.is_none() {
             config.keypair = Some(Keypair::new());
         }
 
-        let keypair = config.keypair.as_ref().unwrap();
-        let pubkey = keypair.pubkey();
-
         // Validate configuration
-        Self::validate_config(&config)?;
+        config.validate()?;
+
+        let pubkey = config.keypair.as_ref().map(|keypair| keypair.pubkey()).or(config.watch_key).unwrap();
 
-        let id = AgentId::new();
+        // The same key always yields the same ID, so reputation survives restarts
+        let id = AgentId::from_public_key(&pubkey);
         let initial_reputation = config.initial_reputation.unwrap_or(0.5);
+        let fast_path = FastPath::new(FastPathPolicy::with_reputation_threshold(config.preferences.auto_accept_threshold));
+        let mut capability_registry = CapabilityRegistry::built_in();
+        for info in &config.custom_services {
+            capability_registry.register(info.clone())?;
+        }
         
         let agent = Self {
             id,
@@ -141,44 +403,84 @@ impl Agent {
             active_transactions: Arc::new(RwLock::new(HashMap::new())),
             created_at: Timestamp::now(),
             last_active: Arc::new(RwLock::new(Timestamp::now())),
+            negotiation: Arc::new(RwLock::new(strategy)),
+            protocol_params: Arc::new(RwLock::new(ProtocolParams::default())),
+            governance_council: Arc::new(RwLock::new(None)),
+            transactions: Arc::new(TransactionManager::new(Arc::new(StorageManager::memory()))),
+            replicator: None,
+            outbox: Arc::new(RwLock::new(BTreeMap::new())),
+            escrows: Arc::new(RwLock::new(HashMap::new())),
+            reputation_penalties: Arc::new(RwLock::new(Vec::new())),
+            negotiations: Arc::new(RwLock::new(HashMap::new())),
+            counterparty_profiles: Arc::new(RwLock::new(CounterpartyProfiles::new())),
+            cost_model: Arc::new(RwLock::new(CostModel::default())),
+            risk_budget: Arc::new(RwLock::new(None)),
+            market_analytics: Arc::new(RwLock::new(MarketAnalytics::default())),
+            advisor: Arc::new(RwLock::new(None)),
+            rfqs: Arc::new(RwLock::new(HashMap::new())),
+            offer_books: Arc::new(RwLock::new(HashMap::new())),
+            market_predictors: Arc::new(RwLock::new(HashMap::new())),
+            knowledge: Arc::new(RwLock::new(None)),
+            fast_path: Arc::new(RwLock::new(fast_path)),
+            capability_registry: Arc::new(RwLock::new(capability_registry)),
+            compliance: Arc::new(RwLock::new(ComplianceChecker::default())),
+            capacity: Arc::new(RwLock::new(ProviderCapacity::default())),
+            capacity_board: Arc::new(RwLock::new(CapacityBoard::new())),
+            marketplace: Arc::new(RwLock::new(Marketplace::new())),
+            quote_channel: Arc::new(RwLock::new(None)),
+            role: NodeRole::Participant,
+            rng: Arc::new(NodeRng::for_node(None, &id.to_string())),
         };
 
-        tracing::info!("Created new agent {} ({})", agent.config.name, agent.id);
+        tracing::info!("Created new agent {} ({}) with {} negotiation",
+            agent.config.name, agent.id, agent.negotiation.read().await.name());
         Ok(agent)
     }
 
-    /// Validate agent configuration
-    fn validate_config(config: &AgentConfig) -> Result<()> {
-        if config.name.trim().is_empty() {
-            return Err(AgentError::InvalidConfig {
-                reason: "Agent name cannot be empty".to_string(),
-            }.into());
-        }
-
-        if config.capabilities.is_empty() {
-            return Err(AgentError::InvalidConfig {
-                reason: "Agent must have at least one capability".to_string(),
-            }.into());
-        }
-
-        if config.preferences.risk_tolerance < 0.0 || config.preferences.risk_tolerance > 1.0 {
-            return Err(AgentError::InvalidConfig {
-                reason: "Risk tolerance must be between 0.0 and 1.0".to_string(),
-            }.into());
-        }
-
-        if config.preferences.min_counterparty_reputation < 0.0 || config.preferences.min_counterparty_reputation > 1.0 {
-            return Err(AgentError::InvalidConfig {
-                reason: "Minimum counterparty reputation must be between 0.0 and 1.0".to_string(),
-            }.into());
-        }
+    /// Create a read-only observer agent
+    ///
+    /// Observers track markets, counterparties, and reputation like any
+    /// agent, but every operation that signs, proposes, or commits to a
+    /// transaction is refused.
+    pub async fn observer(config: AgentConfig) -> Result<Self> {
+        let mut agent = Self::new(config).await?;
+        agent.role = NodeRole::Observer;
+        tracing::info!("Agent {} ({}) is running as an observer", agent.config.name, agent.id);
+        Ok(agent)
+    }
 
-        Ok(())
+    /// Create a watch-only agent from its public key alone
+    ///
+    /// The agent quotes and negotiates as usual, but holds no private key:
+    /// its settlement transactions are prepared unsigned and exported for
+    /// an external signer.
+    pub async fn watch_only(mut config: AgentConfig, public_key: Pubkey) -> Result<Self> {
+        config.keypair = None;
+        config.watch_key = Some(public_key);
+        let agent = Self::new(config).await?;
+        tracing::info!("Agent {} ({}) is watch-only; settlements are signed externally", agent.config.name, agent.id);
+        Ok(agent)
     }
 
     /// Get agent's public key
     pub fn public_key(&self) -> Pubkey {
-        self.config.keypair.as_ref().unwrap().pubkey()
+        self.config.keypair.as_ref().map(|keypair| keypair.pubkey()).or(self.config.watch_key).unwrap()
+    }
+
+    /// Whether the agent's keys are held by an external signer
+    pub fn is_watch_only(&self) -> bool {
+        self.config.keypair.is_none()
+    }
+
+    /// Keypair for signing settlement transactions in process
+    ///
+    /// Watch-only agents have none; their settlements go to the external
+    /// signer as `UnsignedTransaction`s instead.
+    pub fn signing_keypair(&self) -> Result<&Keypair> {
+        self.authorize("sign settlement transactions")?;
+        self.config.keypair.as_ref().ok_or_else(|| AgentError::NotAuthorized {
+            operation: "sign settlement transactions (watch-only; export them for the external signer)".to_string(),
+        }.into())
     }
 
     /// Get current agent state
@@ -196,10 +498,40 @@ impl Agent {
         Ok(())
     }
 
-    /// Start the agent (set to online state)
+    /// Keep transactions in `storage`, so they survive a restart
+    pub fn with_transaction_storage(mut self, storage: Arc<StorageManager>) -> Self {
+        self.transactions = Arc::new(TransactionManager::new(storage));
+        self
+    }
+
+    /// Run as the active node of a standby pair. Transaction storage should
+    /// record into the replicator's log, so the standby holds the same state.
+    pub fn with_replicator(mut self, replicator: Arc<ActiveReplicator>) -> Self {
+        self.replicator = Some(replicator);
+        self
+    }
+
+    /// Refuse to sign or commit on an observer, or on an active node fenced
+    /// off from its standby, which may be taking over
+    fn authorize(&self, operation: &str) -> Result<()> {
+        self.role.authorize(operation)?;
+        if self.replicator.as_ref().is_some_and(|replicator| replicator.is_fenced(Timestamp::now())) {
+            return Err(AgentError::NotAuthorized {
+                operation: format!("{} (fenced: standby unreachable)", operation),
+            }.into());
+        }
+        Ok(())
+    }
+
+    /// Start the agent (set to online state), first recovering the
+    /// transactions in flight when it last stopped
     pub async fn start(&self) -> Result<()> {
+        let recovered = self.transactions.recover().await?;
         self.set_state(AgentState::Online).await?;
-        tracing::info!("Agent {} ({}) started", self.config.name, self.id);
+        tracing::info!(
+            "Agent {} ({}) started with {} transactions resumed, {} expired",
+            self.config.name, self.id, recovered.resumed.len(), recovered.expired.len()
+        );
         Ok(())
     }
 
@@ -218,6 +550,17 @@ impl Agent {
             .any(|cap| cap.matches_service(service_type))
     }
 
+    /// Register or update a custom service with the agent's registry
+    pub async fn register_capability(&self, info: CapabilityInfo) -> Result<()> {
+        self.capability_registry.write().await.register(info)
+    }
+
+    /// Check an outgoing request against the registry and fill in the
+    /// service's default service levels
+    pub async fn prepare_request(&self, request: &mut TransactionRequest) -> Result<()> {
+        self.capability_registry.read().await.apply_defaults(request)
+    }
+
     /// Get current reputation score
     pub async fn get_reputation(&self) -> f64 {
         self.reputation.read().await.current_score()
@@ -268,103 +611,1915 @@ impl Agent {
         current_reputation >= min_reputation && current_balance.0 >= required_balance.0
     }
 
-    /// Get agent summary for display
-    pub async fn get_summary(&self) -> AgentSummary {
-        AgentSummary {
-            id: self.id,
-            name: self.config.name.clone(),
-            description: self.config.description.clone(),
-            capabilities: self.config.capabilities.clone(),
-            state: self.get_state().await,
-            reputation: self.get_reputation().await,
-            balance: self.get_balance().await,
-            created_at: self.created_at,
-            last_active: *self.last_active.read().await,
+    /// Apply a governance parameter update if it is newer than the current one
+    pub async fn update_protocol_params(&self, update: ProtocolParams) -> bool {
+        let applied = self.protocol_params.write().await.apply(update);
+        if applied {
+            tracing::info!("Agent {} applied protocol params v{}", self.id, self.protocol_params.read().await.version);
         }
+        applied
     }
-}
 
-/// Agent summary for display and serialization
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AgentSummary {
-    pub id: AgentId,
-    pub name: String,
-    pub description: String,
-    pub capabilities: Vec<AgentCapability>,
-    pub state: AgentState,
-    pub reputation: f64,
-    pub balance: Balance,
-    pub created_at: Timestamp,
-    pub last_active: Timestamp,
-}
+    /// Accept gossiped params updates approved by `council`
+    pub async fn set_governance_council(&self, council: MultisigWallet) {
+        *self.governance_council.write().await = Some(council);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Apply a gossiped params update once the governance council approved
+    /// it, returning whether it was newer than the current parameters
+    pub async fn receive_params_update(&self, update: ParamsUpdate) -> Result<bool> {
+        match self.governance_council.read().await.as_ref() {
+            Some(council) => update.verify(council)?,
+            None => return Err(crate::error::SolaceError::config("No governance council configured to approve params updates")),
+        }
+        Ok(self.update_protocol_params(update.params).await)
+    }
 
-    fn create_test_config() -> AgentConfig {
-        AgentConfig {
-            keypair: None,
-            name: "Test Agent".to_string(),
-            description: "A test agent".to_string(),
-            capabilities: vec![AgentCapability::DataAnalysis],
-            preferences: AgentPreferences::default(),
-            network_address: None,
-            initial_reputation: Some(0.7),
+    /// Hold a locked escrow so gossiped approvals of its payouts are collected
+    pub async fn track_escrow(&self, escrow: Escrow) {
+        self.escrows.write().await.insert(escrow.transaction_id, escrow);
+    }
+
+    /// Record a treasury signer's approval of a payout from one of our
+    /// escrows. Returns false if the signer had already approved it.
+    pub async fn receive_release_approval(&self, approval: ReleaseApproval) -> Result<bool> {
+        let mut escrows = self.escrows.write().await;
+        let escrow = escrows.get_mut(&approval.transaction_id)
+            .ok_or_else(|| TransactionError::NotFound { id: approval.transaction_id.to_string() })?;
+        escrow.approve(approval)
+    }
+
+    /// Handle a gossiped message addressed to agents. Returns false if the
+    /// agent has nothing to do with its type.
+    pub async fn handle_message(&self, message: &ACPMessage) -> Result<bool> {
+        match message.message_type {
+            MessageType::ProtocolParamsUpdate => {
+                let update = ParamsUpdate::from_payload(serde_json::from_slice(&message.payload)?)?;
+                self.receive_params_update(update).await?;
+                Ok(true)
+            }
+            MessageType::PartialSignature => {
+                let approval = ReleaseApproval::from_payload(serde_json::from_slice(&message.payload)?)?;
+                self.receive_release_approval(approval).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
         }
     }
 
-    #[tokio::test]
-    async fn test_agent_creation() {
-        let config = create_test_config();
-        let agent = Agent::new(config).await.unwrap();
-        
-        assert_eq!(agent.config.name, "Test Agent");
-        assert_eq!(agent.get_reputation().await, 0.7);
-        assert_eq!(agent.get_state().await, AgentState::Offline);
+    /// Replace the compute cost model
+    pub async fn set_cost_model(&self, model: CostModel) {
+        *self.cost_model.write().await = model;
     }
 
-    #[tokio::test]
-    async fn test_agent_state_management() {
-        let config = create_test_config();
-        let agent = Agent::new(config).await.unwrap();
-        
-        // Test starting agent
-        agent.start().await.unwrap();
-        assert_eq!(agent.get_state().await, AgentState::Online);
-        assert!(agent.is_available().await);
-        
-        // Test stopping agent
-        agent.stop().await.unwrap();
-        assert_eq!(agent.get_state().await, AgentState::Offline);
-        assert!(!agent.is_available().await);
+    /// Limit portfolio value-at-risk (in SOL), keeping tracked exposures
+    pub async fn set_risk_limit(&self, var_limit: f64) {
+        let mut budget = self.risk_budget.write().await;
+        match budget.as_mut() {
+            Some(budget) => budget.var_limit = var_limit,
+            None => *budget = Some(RiskBudget::new(var_limit)),
+        }
     }
 
-    #[tokio::test]
-    async fn test_service_capability_matching() {
-        let config = create_test_config();
-        let agent = Agent::new(config).await.unwrap();
-        
-        assert!(agent.can_handle_service(&ServiceType::DataAnalysis));
-        assert!(!agent.can_handle_service(&ServiceType::TradingService));
+    /// Stop counting a settled or abandoned deal against the risk budget
+    pub async fn release_exposure(&self, transaction_id: &TransactionId) {
+        if let Some(budget) = self.risk_budget.write().await.as_mut() {
+            budget.remove(&transaction_id.to_string());
+        }
     }
 
-    #[test]
-    fn test_config_validation() {
-        let mut config = create_test_config();
-        
-        // Test empty name
-        config.name = "".to_string();
-        assert!(Agent::validate_config(&config).is_err());
-        
-        // Test empty capabilities
-        config.name = "Test".to_string();
-        config.capabilities = vec![];
-        assert!(Agent::validate_config(&config).is_err());
-        
-        // Test invalid risk tolerance
-        config.capabilities = vec![AgentCapability::DataAnalysis];
-        config.preferences.risk_tolerance = 1.5;
-        assert!(Agent::validate_config(&config).is_err());
+    /// Screen an incoming request: we must offer the service, the request
+    /// must carry what the service requires, and its budget must cover our
+    /// estimated cost plus minimum margin
+    pub async fn screen_request(&self, request: &TransactionRequest) -> Result<()> {
+        if !self.can_handle_service(&request.service_type) {
+            return Err(AgentError::InsufficientCapabilities.into());
+        }
+        if !self.config.preferences.accepts(&request.asset) {
+            tracing::info!("Agent {} declining request {}: not paid in {}", self.id, request.id, request.asset);
+            return Err(TransactionError::InvalidRequirements {
+                reason: format!("payment in {} is not accepted", request.asset),
+            }.into());
+        }
+        self.capability_registry.read().await.validate_request(request)?;
+        // The cost model prices in SOL, so token budgets are not held to it
+        if !request.asset.is_sol() {
+            return Ok(());
+        }
+        self.cost_model.read().await.check_budget(request).inspect_err(|e| {
+            tracing::info!("Agent {} declining request {}: {}", self.id, request.id, e);
+        })
+    }
+
+    /// Feed a transaction into the market analytics
+    ///
+    /// Analytics are priced in SOL, so deals paid in tokens are left out.
+    pub async fn observe_transaction(&self, transaction: &Transaction) {
+        if !transaction.request.asset.is_sol() {
+            return;
+        }
+        let service_type = &transaction.request.service_type;
+        let demand = {
+            let mut analytics = self.market_analytics.write().await;
+            analytics.observe(transaction);
+            analytics.market_conditions(service_type, Timestamp::now()).demand_level
+        };
+        if let Some(price) = transaction.agreed_price {
+            self.market_predictors
+                .write()
+                .await
+                .entry(service_type.clone())
+                .or_insert_with(MarketPredictor::new)
+                .add_data_point(price.to_sol(), demand);
+        }
+    }
+
+    /// Market conditions for a service type, from observed transactions
+    pub async fn market_conditions(&self, service_type: &ServiceType) -> MarketConditions {
+        self.market_analytics.read().await.market_conditions(service_type, Timestamp::now())
+    }
+
+    /// Our market statistics as they may be published on the analytics
+    /// topic, thresholded and noised by `policy`
+    pub async fn publishable_market_stats(&self, policy: &PrivacyPolicy) -> Result<Vec<PublishedMarketStats>> {
+        let analytics = self.market_analytics.read().await;
+        self.rng.with(|rng| policy.publish(&analytics, Timestamp::now(), rng))
+    }
+
+    /// Decision context for a deal, with our reputation, observed market
+    /// conditions, and the counterparty's profile filled in
+    pub async fn decision_context(
+        &self,
+        service_type: &ServiceType,
+        counterparty: &AgentId,
+        counterparty_reputation: f64,
+        transaction_value: f64,
+    ) -> DecisionContext {
+        DecisionContext {
+            agent_reputation: self.get_reputation().await,
+            counterparty_reputation,
+            transaction_value,
+            market_conditions: self.market_conditions(service_type).await,
+            historical_performance: Vec::new(),
+            counterparty_profile: self.counterparty_profiles.read().await.get(&counterparty.to_string()).cloned(),
+            time_pressure: None,
+        }
+    }
+
+    /// Start a negotiation for a service paid in `asset`, within its
+    /// governance price bounds, above its execution cost plus margin, and
+    /// within the risk budget
+    ///
+    /// Prices are in whole units of `asset`. The cost model and the risk
+    /// budget are kept in SOL, so they only apply to SOL deals.
+    pub async fn negotiation_state(
+        &self,
+        service_type: &ServiceType,
+        asset: &PaymentAsset,
+        context: DecisionContext,
+        base_price: f64,
+        max_rounds: u32,
+    ) -> NegotiationState {
+        let bounds = self.protocol_params.read().await.negotiation_bounds(service_type, asset);
+        let state = NegotiationState::new(context, base_price, max_rounds).with_price_bounds(bounds);
+        if !asset.is_sol() {
+            return state;
+        }
+        let cost = self.cost_model.read().await.execution_cost(service_type);
+        state
+            .with_execution_cost(Some(cost))
+            .with_risk_budget(self.risk_budget.read().await.clone())
+    }
+
+    /// Exposure a deal adds to the risk budget, which only tracks SOL deals
+    fn exposure(context: &DecisionContext, asset: &PaymentAsset, value: f64) -> Option<Exposure> {
+        asset.is_sol().then(|| Exposure::for_context(context, value))
+    }
+
+    /// Consult an external advisor during negotiation, or stop with `None`
+    pub async fn set_advisor(&self, advisor: Option<WeightedAdvisor>) {
+        *self.advisor.write().await = advisor;
+    }
+
+    /// Opening ask for a negotiation
+    pub async fn propose_price(&self, state: &NegotiationState) -> f64 {
+        let mut ask = self.negotiation.read().await.propose_price(state);
+        if let Some(advisor) = self.advisor.read().await.as_ref() {
+            ask = advisor.advise_price(&state.context, ask).await;
+        }
+        state.bound_ask(ask)
+    }
+
+    /// Answer a counter-offer, walking away when the strategy says so
+    ///
+    /// An advisor's opinion is blended in before governance, cost, and risk
+    /// limits apply, so it can never talk the agent past them.
+    pub async fn respond_to_counter_offer(&self, state: &NegotiationState, offer: f64) -> CounterOfferResponse {
+        let strategy = self.negotiation.read().await;
+        if strategy.should_walk_away(state) {
+            return CounterOfferResponse::Reject;
+        }
+        let mut response = strategy.evaluate_counter_offer(state, offer);
+        if let Some(advisor) = self.advisor.read().await.as_ref() {
+            response = advisor.advise_response(state, offer, response).await;
+        }
+        state.bound_response(offer, response)
+    }
+
+    /// Start tracking a transaction for `request`; its transitions go
+    /// through the agent's `TransactionManager` from then on
+    pub async fn open_transaction(&self, request: TransactionRequest) -> Result<Transaction> {
+        self.transactions.open(request).await
+    }
+
+    /// Accept a provider's proposal for one of our transactions, enforcing
+    /// governance price bounds
+    ///
+    /// Out-of-bounds prices are refused and, when governance asks for it,
+    /// queued as a reputation penalty against the provider.
+    pub async fn accept_proposal(&self, transaction_id: &TransactionId, provider: AgentId, price: Balance) -> Result<Transaction> {
+        self.authorize("accept proposals")?;
+        let mut next = self.transactions.get(transaction_id).await
+            .ok_or_else(|| TransactionError::NotFound { id: transaction_id.to_string() })?;
+        let params = self.protocol_params.read().await;
+        if params.report_violations {
+            let request = &next.request;
+            if let Some(violation) = params.check_price(next.id, provider, &request.service_type, &request.asset, price) {
+                let event = violation.reputation_event(params.violation_penalty);
+                tracing::info!("Agent {} penalizing {} for pricing outside governance bounds", self.id, provider);
+                self.reputation_penalties.write().await.push(ReputationUpdate { agent_id: violation.offender, event });
+            }
+        }
+        next.accept_proposal_governed(provider, price, &params)?;
+        self.transactions.apply(transaction_id, Transition::AcceptProposal { provider, price }).await
+    }
+
+    /// Track a negotiation paid in `asset`, with the governance response
+    /// deadline per round
+    ///
+    /// The counterparty's profile, if any, is attached to the decision
+    /// context so pricing and acceptance adapt to its track record.
+    pub async fn open_negotiation(&self, transaction_id: TransactionId, counterparty: AgentId, asset: PaymentAsset, state: NegotiationState) {
+        self.open_session(transaction_id, counterparty, asset, state, None).await;
+    }
+
+    /// `open_negotiation` that abandons the negotiation when `cancel` fires
+    /// before it closes. An abandoned session is dropped when next touched,
+    /// without recording anything against the counterparty or the risk
+    /// budget; later asks and counter-offers for it are refused.
+    pub async fn open_negotiation_cancellable(
+        &self,
+        transaction_id: TransactionId,
+        counterparty: AgentId,
+        asset: PaymentAsset,
+        state: NegotiationState,
+        cancel: CancellationToken,
+    ) {
+        self.open_session(transaction_id, counterparty, asset, state, Some(cancel)).await;
+    }
+
+    async fn open_session(
+        &self,
+        transaction_id: TransactionId,
+        counterparty: AgentId,
+        asset: PaymentAsset,
+        mut state: NegotiationState,
+        cancel: Option<CancellationToken>,
+    ) {
+        state.context.counterparty_profile = self.counterparty_profiles.read().await.get(&counterparty.to_string()).cloned();
+        let mut session = NegotiationSession::with_params(transaction_id, counterparty, state, &*self.protocol_params.read().await)
+            .with_asset(asset);
+        if let Some(cancel) = cancel {
+            session = session.with_cancellation(cancel);
+        }
+        self.negotiations.write().await.insert(transaction_id, session);
+    }
+
+    /// The session for `transaction_id`, dropping it if it was cancelled
+    fn live_session<'a>(
+        negotiations: &'a mut HashMap<TransactionId, NegotiationSession>,
+        transaction_id: &TransactionId,
+    ) -> Result<&'a mut NegotiationSession> {
+        if negotiations.get(transaction_id).is_some_and(NegotiationSession::is_cancelled) {
+            negotiations.remove(transaction_id);
+            return Err(crate::error::SolaceError::cancelled(format!("negotiation {}", transaction_id)));
+        }
+        negotiations.get_mut(transaction_id).ok_or_else(|| Self::no_negotiation(transaction_id))
+    }
+
+    /// Ease acceptance as the requester's deadline for the work approaches
+    pub async fn set_request_deadline(&self, transaction_id: &TransactionId, deadline: Timestamp) -> Result<()> {
+        let mut negotiations = self.negotiations.write().await;
+        let session = Self::live_session(&mut negotiations, transaction_id)?;
+        session.set_request_deadline(Timestamp::now(), deadline);
+        Ok(())
+    }
+
+    /// Record our ask, starting the counterparty's response deadline
+    pub async fn record_ask(&self, transaction_id: &TransactionId, price: f64) -> Result<()> {
+        self.authorize("send asks")?;
+        let mut negotiations = self.negotiations.write().await;
+        let session = Self::live_session(&mut negotiations, transaction_id)?;
+        session.send_ask(price, Timestamp::now());
+        Ok(())
+    }
+
+    /// Record a counter-offer and answer it, timing the counterparty's response
+    ///
+    /// Offers that break the protocol rules are refused in `Reject` mode,
+    /// ending the negotiation, before the strategy sees them.
+    pub async fn receive_counter_offer(&self, transaction_id: &TransactionId, offer: f64) -> Result<CounterOfferResponse> {
+        self.authorize("answer counter-offers")?;
+        let mut negotiations = self.negotiations.write().await;
+        let session = Self::live_session(&mut negotiations, transaction_id)?;
+        if let Err(e) = self.compliance.write().await.check_offer(session, offer, Timestamp::now()) {
+            session.finish(false, &mut *self.counterparty_profiles.write().await);
+            return Err(e);
+        }
+        session.receive_offer(offer, Timestamp::now(), &mut *self.counterparty_profiles.write().await);
+        if let Some(budget) = self.risk_budget.read().await.as_ref().filter(|_| session.asset.is_sol()) {
+            // Deals agreed since the session opened count against this one
+            session.state.risk_budget = Some(budget.clone());
+        }
+
+        // The fast path's limits are in SOL, so token deals take the full path
+        let fast_path = session.asset.is_sol()
+            && self.fast_path.write().await.check(&session.state, offer, self.config.preferences.max_transaction_value).is_ok();
+        let response = if fast_path {
+            CounterOfferResponse::Accept
+        } else {
+            self.respond_to_counter_offer(&session.state, offer).await
+        };
+        match response {
+            CounterOfferResponse::Accept => {
+                session.finish(true, &mut *self.counterparty_profiles.write().await);
+                let exposure = Self::exposure(&session.state.context, &session.asset, offer);
+                if let (Some(budget), Some(exposure)) = (self.risk_budget.write().await.as_mut(), exposure) {
+                    budget.add(transaction_id.to_string(), exposure);
+                }
+            }
+            CounterOfferResponse::Reject => session.finish(false, &mut *self.counterparty_profiles.write().await),
+            CounterOfferResponse::Counter(_) => {}
+        }
+        Ok(response)
+    }
+
+    /// Check a provider's proposal against the protocol rules and add it
+    pub async fn receive_proposal(&self, transaction: &mut Transaction, proposal: TransactionProposal) -> Result<()> {
+        self.compliance.write().await.check_proposal(&proposal, Some(transaction.request.deadline), Timestamp::now())?;
+        transaction.add_proposal(proposal)
+    }
+
+    /// Replace the rules inbound negotiation messages are checked against
+    pub async fn set_compliance_rules(&self, rules: ComplianceRules) {
+        self.compliance.write().await.rules = rules;
+    }
+
+    /// Protocol violations recorded against a counterparty
+    pub async fn compliance_violations(&self, counterparty: &AgentId) -> Vec<ComplianceViolation> {
+        self.compliance.read().await.violations(counterparty).to_vec()
+    }
+
+    /// Turn the auto-accept fast path on or off
+    pub async fn set_fast_path_enabled(&self, enabled: bool) {
+        self.fast_path.write().await.policy.enabled = enabled;
+        tracing::info!("Agent {} auto-accept fast path {}", self.id, if enabled { "enabled" } else { "disabled" });
+    }
+
+    /// Fast path usage so far
+    pub async fn fast_path_metrics(&self) -> FastPathMetrics {
+        self.fast_path.read().await.metrics.clone()
+    }
+
+    /// Record whether a counterparty paid for an agreed deal
+    pub async fn record_settlement(&self, counterparty: &AgentId, paid: bool) {
+        self.counterparty_profiles.write().await.record_settlement(&counterparty.to_string(), paid);
+    }
+
+    /// Withdraw from negotiations whose counterparty missed its deadline
+    pub async fn withdraw_overdue_negotiations(&self) -> Vec<TransactionId> {
+        let now = Timestamp::now();
+        let mut profiles = self.counterparty_profiles.write().await;
+        let mut negotiations = self.negotiations.write().await;
+        negotiations.retain(|_, session| !session.is_cancelled());
+
+        let withdrawn: Vec<TransactionId> = negotiations
+            .values_mut()
+            .filter_map(|session| session.enforce_deadline(now, &mut profiles).then_some(session.transaction_id))
+            .collect();
+        negotiations.retain(|_, session| session.is_open());
+        withdrawn
+    }
+
+    /// Mean seconds a counterparty takes to answer, for matchmaking
+    pub async fn average_response_secs(&self, counterparty: &AgentId) -> Option<f64> {
+        self.counterparty_profiles.read().await.average_response_secs(&counterparty.to_string())
+    }
+
+    /// Order candidate counterparties for matchmaking, most responsive first
+    pub async fn rank_counterparties(&self, candidates: &[AgentId]) -> Vec<AgentId> {
+        let keys: Vec<String> = candidates.iter().map(|id| id.to_string()).collect();
+        let key_refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let profiles = self.counterparty_profiles.read().await;
+        profiles
+            .rank_by_responsiveness(&key_refs)
+            .into_iter()
+            .filter_map(|key| candidates.iter().find(|id| id.to_string() == key).copied())
+            .collect()
+    }
+
+    /// Set how many jobs we take at once as a provider
+    pub async fn set_job_slots(&self, slots: u32) {
+        self.capacity.write().await.total_slots = slots;
+    }
+
+    /// Our current capacity as an ad to publish on its capacity topics
+    pub async fn advertise_capacity(&self) -> Result<CapacityAd> {
+        self.authorize("advertise capacity")?;
+        let capabilities = self.config.capabilities.iter().map(AgentCapability::key).collect();
+        let ttl = chrono::Duration::seconds(AD_TTL_SECS);
+        Ok(self.capacity.write().await.advertise(self.id, capabilities, Timestamp::now(), ttl))
+    }
+
+    /// Take a job slot for work we accepted, returning the updated ad
+    pub async fn commit_capacity(&self, transaction_id: TransactionId, estimated_completion: Timestamp) -> Result<CapacityAd> {
+        self.capacity.write().await.commit(transaction_id, estimated_completion)?;
+        self.advertise_capacity().await
+    }
+
+    /// Free the slot of finished or abandoned work, returning the updated ad
+    pub async fn release_capacity(&self, transaction_id: &TransactionId) -> Result<CapacityAd> {
+        self.capacity.write().await.release(transaction_id);
+        self.advertise_capacity().await
+    }
+
+    /// Record a provider's capacity ad. Returns false for stale copies.
+    pub async fn observe_capacity_ad(&self, ad: CapacityAd) -> bool {
+        let mut board = self.capacity_board.write().await;
+        board.prune(Timestamp::now());
+        board.observe(ad)
+    }
+
+    /// Providers worth proposing a `service_type` job to, most responsive
+    /// first, leaving out those whose ads say they cannot start before
+    /// `deadline`
+    pub async fn match_providers(&self, candidates: &[AgentId], service_type: &ServiceType, deadline: Timestamp) -> Vec<AgentId> {
+        let admissible = self.capacity_board.read().await.admissible(candidates, service_type, deadline, Timestamp::now());
+        if admissible.len() < candidates.len() {
+            tracing::debug!("Agent {} skipping {} saturated providers", self.id, candidates.len() - admissible.len());
+        }
+        self.rank_counterparties(&admissible).await
+    }
+
+    /// Offer one of our services on the marketplace, returning the offer to
+    /// publish on its topic. Republishing renews it with the new terms.
+    pub async fn publish_offer(&self, service_type: ServiceType, price_range: (Balance, Balance), sla: OfferSla) -> Result<ServiceOffer> {
+        self.authorize("publish offers")?;
+        if !self.can_handle_service(&service_type) {
+            return Err(AgentError::InsufficientCapabilities.into());
+        }
+        let mut marketplace = self.marketplace.write().await;
+        let sequence = marketplace.offer(&self.id, &service_type).map_or(0, |held| held.sequence + 1);
+        let mut offer = ServiceOffer::new(self.id, service_type, price_range, sla, Timestamp::now(), chrono::Duration::seconds(OFFER_TTL_SECS))?;
+        offer.sequence = sequence;
+        marketplace.observe(offer.clone());
+        Ok(offer)
+    }
+
+    /// Take our offer for `service_type` off the marketplace, returning the
+    /// withdrawal to publish
+    pub async fn withdraw_offer(&self, service_type: &ServiceType) -> Result<Option<ServiceOffer>> {
+        self.authorize("publish offers")?;
+        let mut marketplace = self.marketplace.write().await;
+        let Some(withdrawal) = marketplace.offer(&self.id, service_type).map(|held| held.withdrawal(Timestamp::now())) else {
+            return Ok(None);
+        };
+        marketplace.observe(withdrawal.clone());
+        Ok(Some(withdrawal))
+    }
+
+    /// Record a gossiped service offer. Returns false for stale copies.
+    pub async fn observe_service_offer(&self, offer: ServiceOffer) -> bool {
+        let mut marketplace = self.marketplace.write().await;
+        marketplace.prune(Timestamp::now());
+        marketplace.observe(offer)
+    }
+
+    /// Providers on the marketplace for `service_type`, best first. Unless
+    /// the filters set their own floor, providers below our minimum
+    /// counterparty reputation are left out.
+    pub async fn find_providers(&self, service_type: &ServiceType, mut filters: ProviderFilters) -> Vec<ProviderCandidate> {
+        filters.min_reputation.get_or_insert(self.config.preferences.min_counterparty_reputation);
+        self.marketplace.read().await.find_providers(service_type, &filters)
+    }
+
+    /// Send `request_service`'s quote requests over `channel`, or stop with `None`
+    pub async fn set_quote_channel(&self, channel: Option<Arc<dyn QuoteChannel>>) {
+        *self.quote_channel.write().await = channel;
+    }
+
+    /// Buy a service: solicit proposals from the marketplace's providers of
+    /// it, collect them until the window closes, and take the one the
+    /// negotiation strategy chooses
+    ///
+    /// Proposals arrive through `collect_proposal` while this waits. Only
+    /// providers whose offers start within the budget are asked.
+    pub async fn request_service(&self, service: ServiceRequest) -> Result<ServiceMatch> {
+        self.request_service_cancellable(service, &CancellationToken::new()).await
+    }
+
+    /// `request_service` that gives up when `cancel` fires before a proposal
+    /// is taken. The request's offer book is dropped, so proposals still in
+    /// flight are refused and nothing is accepted.
+    pub async fn request_service_cancellable(&self, service: ServiceRequest, cancel: &CancellationToken) -> Result<ServiceMatch> {
+        self.authorize("request services")?;
+        let channel = self
+            .quote_channel
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| crate::error::SolaceError::config("No quote channel to solicit providers over"))?;
+        let mut service = service;
+        if let Some(payment) = service.max_payment {
+            service.request = service.request.with_payment(payment);
+        }
+        if !self.config.preferences.accepts(&service.request.asset) {
+            return Err(TransactionError::InvalidRequirements {
+                reason: format!("{} is not among our payment methods", service.request.asset),
+            }.into());
+        }
+        self.prepare_request(&mut service.request).await?;
+        let request_id = service.request.id;
+        // Offers are priced in SOL; token budgets leave the price filter to the caller
+        if service.request.asset.is_sol() {
+            let budget = service.request.budget;
+            service.filters.max_price = Some(service.filters.max_price.map_or(budget, |max| max.min(budget)));
+        }
+
+        let providers = self.find_providers(&service.request.service_type, service.filters.clone()).await;
+        let window = service.collection_window(Timestamp::now());
+        self.open_offer_book(service.request.clone(), window).await?;
+        let message = service.quote_request()?;
+        let mut solicited = Vec::new();
+        for candidate in providers.into_iter().filter(|candidate| candidate.offer.provider != self.id) {
+            let provider = candidate.offer.provider;
+            let sent = tokio::select! {
+                sent = channel.send(&provider, message.clone()) => sent,
+                _ = cancel.cancelled() => return self.abandon_request(&request_id).await,
+            };
+            match sent {
+                Ok(()) => solicited.push(provider),
+                Err(e) => tracing::debug!("Agent {} could not solicit {}: {}", self.id, provider, e),
+            }
+        }
+
+        if solicited.is_empty() {
+            if let Some(book) = self.offer_books.write().await.get_mut(&request_id) {
+                book.close_unfilled();
+            }
+            return Ok(ServiceMatch { solicited, proposals: 0, transaction: None });
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(window.to_std().unwrap_or_default()) => {}
+            _ = cancel.cancelled() => return self.abandon_request(&request_id).await,
+        }
+        let transaction = self.choose_offer(&request_id, &service.weights).await?;
+        let proposals = self.offer_books.read().await.get(&request_id).map_or(0, |book| book.offers.len());
+        Ok(ServiceMatch { solicited, proposals, transaction })
+    }
+
+    async fn abandon_request(&self, request_id: &TransactionId) -> Result<ServiceMatch> {
+        self.offer_books.write().await.remove(request_id);
+        Err(crate::error::SolaceError::cancelled(format!("service request {}", request_id)))
+    }
+
+    /// Call for quotes: track the intent and return the message to broadcast
+    /// on its topic
+    pub async fn request_quotes(&self, intent: QuoteIntent) -> Result<RfqMessage> {
+        self.authorize("request quotes")?;
+        self.rfqs.write().await.insert(intent.id, RfqSession::new(intent.clone()));
+        Ok(RfqMessage::Intent(intent))
+    }
+
+    /// Record a provider's quote for one of our intents
+    pub async fn receive_quote(&self, quote: Quote) -> Result<()> {
+        let mut rfqs = self.rfqs.write().await;
+        let session = rfqs.get_mut(&quote.intent_id).ok_or_else(|| Self::no_negotiation(&quote.intent_id))?;
+        session.submit(quote, Timestamp::now())
+    }
+
+    /// Quote an intent as a provider, if we can serve it in time and above cost
+    ///
+    /// The price is our opening ask for the service, capped at the top of the
+    /// requester's budget; the quote stays binding for `validity` past the
+    /// close of the quote window. Observers never quote.
+    pub async fn quote_intent(&self, intent: &QuoteIntent, validity: chrono::Duration) -> Option<Quote> {
+        let now = Timestamp::now();
+        if self.role == NodeRole::Observer || !self.can_handle_service(&intent.service_type) || !intent.accepts_quotes(now) {
+            return None;
+        }
+
+        let (minimum, estimate) = {
+            let cost_model = self.cost_model.read().await;
+            (cost_model.minimum_price(&intent.service_type), cost_model.estimate(&intent.service_type))
+        };
+        let units = |balance: Balance| Payment::new(intent.asset, balance.0).to_units();
+        let market_price = self.market_conditions(&intent.service_type).await.average_pricing;
+        let base_price = if intent.asset.is_sol() && market_price > 0.0 {
+            market_price
+        } else {
+            (units(intent.min_budget) + units(intent.max_budget)) / 2.0
+        };
+
+        // Unknown requesters are treated as average
+        let context = self.decision_context(&intent.service_type, &intent.requester, 0.5, base_price).await;
+        let state = self.negotiation_state(&intent.service_type, &intent.asset, context, base_price, 1).await;
+        let ask = self.propose_price(&state).await.min(units(intent.max_budget));
+        let price = Balance((ask * 10f64.powi(intent.asset.decimals() as i32)) as u64);
+        // The cost model prices in SOL, so token quotes are not held to it
+        if intent.asset.is_sol() && price.0 < minimum.0 {
+            return None;
+        }
+
+        // A full provider can only start once one of its jobs completes
+        let start = intent.quotes_close_at.max(now).max(self.capacity.read().await.earliest_start(now));
+        let work = chrono::Duration::milliseconds(((estimate.cpu_seconds + estimate.gpu_seconds) * 1000.0) as i64);
+        let estimated_completion = Timestamp(start.0 + work);
+        if estimated_completion.0 > intent.deadline.0 {
+            return None;
+        }
+
+        Some(Quote {
+            id: TransactionId::new(),
+            intent_id: intent.id,
+            provider: self.id,
+            price,
+            estimated_completion,
+            valid_until: Timestamp(intent.quotes_close_at.0 + validity),
+            provider_reputation: self.get_reputation().await,
+            terms: HashMap::new(),
+            created_at: now,
+        })
+    }
+
+    /// Close a request for quotes once its window has passed and award the
+    /// best acceptable quote
+    ///
+    /// Providers are judged by their claimed reputation until their profile
+    /// here has history, and quotes from providers below our minimum
+    /// reputation or that would break the risk budget are passed over.
+    /// Returns `None` when no quote qualifies.
+    pub async fn award_quotes(&self, intent_id: &TransactionId, weights: &SelectionWeights) -> Result<Option<Transaction>> {
+        self.authorize("award quotes")?;
+        let now = Timestamp::now();
+        let mut rfqs = self.rfqs.write().await;
+        let session = rfqs.get_mut(intent_id).ok_or_else(|| Self::no_negotiation(intent_id))?;
+        if session.intent.accepts_quotes(now) {
+            return Err(TransactionError::InvalidState {
+                current: "collecting quotes".to_string(),
+                expected: "quote window closed".to_string(),
+            }.into());
+        }
+
+        let profiles = self.counterparty_profiles.read().await.clone();
+        let reputation = |quote: &Quote| Self::blended_reputation(&profiles, &quote.provider, quote.provider_reputation);
+
+        let (service_type, asset) = (session.intent.service_type.clone(), session.intent.asset);
+        for (quote, _) in session.rank(now, weights, reputation) {
+            let provider_reputation = reputation(&quote);
+            if provider_reputation < self.config.preferences.min_counterparty_reputation {
+                continue;
+            }
+            let price = Payment::new(asset, quote.price.0).to_units();
+            let context = self.decision_context(&service_type, &quote.provider, provider_reputation, price).await;
+            let exposure = Self::exposure(&context, &asset, price);
+            let mut risk_budget = self.risk_budget.write().await;
+            if risk_budget.as_ref().zip(exposure.as_ref()).is_some_and(|(budget, exposure)| !budget.admits(exposure)) {
+                continue;
+            }
+
+            let transaction = session.award(&quote)?;
+            if let (Some(budget), Some(exposure)) = (risk_budget.as_mut(), exposure) {
+                budget.add(transaction.id.to_string(), exposure);
+            }
+            drop(risk_budget);
+            self.observe_transaction(&transaction).await;
+            return Ok(Some(transaction));
+        }
+
+        session.close_unfilled();
+        Ok(None)
+    }
+
+    /// A provider's claimed reputation, giving way to what we have observed
+    /// as its profile here gains history
+    fn blended_reputation(profiles: &CounterpartyProfiles, provider: &AgentId, claimed: f64) -> f64 {
+        match profiles.get(&provider.to_string()) {
+            Some(profile) => {
+                let weight = profile.confidence();
+                let observed = 1.0 - profile.default_rate().unwrap_or(1.0 - claimed);
+                claimed * (1.0 - weight) + observed * weight
+            }
+            None => claimed,
+        }
+    }
+
+    /// Collect proposals for a request of ours for `window` instead of
+    /// answering them one at a time
+    pub async fn open_offer_book(&self, request: TransactionRequest, window: chrono::Duration) -> Result<()> {
+        self.authorize("collect proposals")?;
+        self.offer_books.write().await.insert(request.id, OfferBook::new(request, window));
+        Ok(())
+    }
+
+    /// Check a provider's proposal against the protocol rules and add it to
+    /// the request's offer book
+    pub async fn collect_proposal(&self, proposal: TransactionProposal, provider_reputation: f64) -> Result<()> {
+        let now = Timestamp::now();
+        let mut books = self.offer_books.write().await;
+        let book = books.get_mut(&proposal.request_id).ok_or_else(|| Self::no_negotiation(&proposal.request_id))?;
+        self.compliance.write().await.check_proposal(&proposal, Some(book.request.deadline), now)?;
+        book.submit(proposal, provider_reputation, now)
+    }
+
+    /// The request's live offers as price / reputation / ETA points, best first
+    pub async fn compare_offers(&self, request_id: &TransactionId, weights: &SelectionWeights) -> Result<Vec<OfferPoint>> {
+        let books = self.offer_books.read().await;
+        let book = books.get(request_id).ok_or_else(|| Self::no_negotiation(request_id))?;
+        let profiles = self.counterparty_profiles.read().await;
+        Ok(book.comparison(Timestamp::now(), weights, |offer: &Offer| {
+            Self::blended_reputation(&profiles, &offer.proposal.provider, offer.provider_reputation)
+        }))
+    }
+
+    /// Close a request's offer book once its window has passed and select
+    /// the best acceptable offer
+    ///
+    /// Offers are passed over as in `award_quotes`: providers below our
+    /// minimum reputation, and deals that would break the risk budget.
+    /// Returns `None` when no offer qualifies.
+    pub async fn select_offer(&self, request_id: &TransactionId, weights: &SelectionWeights) -> Result<Option<Transaction>> {
+        self.authorize("select offers")?;
+        let points = self.compare_offers(request_id, weights).await?;
+        let mut books = self.offer_books.write().await;
+        let book = books.get_mut(request_id).ok_or_else(|| Self::no_negotiation(request_id))?;
+        if book.is_open(Timestamp::now()) {
+            return Err(TransactionError::InvalidState {
+                current: "collecting proposals".to_string(),
+                expected: "proposal window closed".to_string(),
+            }.into());
+        }
+
+        let (service_type, asset) = (book.request.service_type.clone(), book.request.asset);
+        for point in points {
+            if point.reputation < self.config.preferences.min_counterparty_reputation {
+                continue;
+            }
+            let context = self.decision_context(&service_type, &point.provider, point.reputation, point.price).await;
+            let exposure = Self::exposure(&context, &asset, point.price);
+            let mut risk_budget = self.risk_budget.write().await;
+            if risk_budget.as_ref().zip(exposure.as_ref()).is_some_and(|(budget, exposure)| !budget.admits(exposure)) {
+                continue;
+            }
+
+            let transaction = book.select(&point.proposal_id)?;
+            if let (Some(budget), Some(exposure)) = (risk_budget.as_mut(), exposure) {
+                budget.add(transaction.id.to_string(), exposure);
+            }
+            drop(risk_budget);
+            self.observe_transaction(&transaction).await;
+            return Ok(Some(transaction));
+        }
+
+        book.close_unfilled();
+        Ok(None)
+    }
+
+    /// Close a request's offer book once its window has passed and take the
+    /// offer our negotiation strategy chooses
+    ///
+    /// The strategy sees, best ranked first, the offers `select_offer` would
+    /// consider, with the request's budget as the base price.
+    pub async fn choose_offer(&self, request_id: &TransactionId, weights: &SelectionWeights) -> Result<Option<Transaction>> {
+        self.authorize("select offers")?;
+        let points = self.compare_offers(request_id, weights).await?;
+        let mut books = self.offer_books.write().await;
+        let book = books.get_mut(request_id).ok_or_else(|| Self::no_negotiation(request_id))?;
+        if book.is_open(Timestamp::now()) {
+            return Err(TransactionError::InvalidState {
+                current: "collecting proposals".to_string(),
+                expected: "proposal window closed".to_string(),
+            }.into());
+        }
+
+        let (service_type, asset) = (book.request.service_type.clone(), book.request.asset);
+        let mut eligible = Vec::new();
+        for point in points {
+            if point.reputation < self.config.preferences.min_counterparty_reputation {
+                continue;
+            }
+            let context = self.decision_context(&service_type, &point.provider, point.reputation, point.price).await;
+            let exposure = Self::exposure(&context, &asset, point.price);
+            if self.risk_budget.read().await.as_ref().zip(exposure.as_ref()).is_some_and(|(budget, exposure)| !budget.admits(exposure)) {
+                continue;
+            }
+            eligible.push((point, exposure));
+        }
+        let Some((best, _)) = eligible.first() else {
+            book.close_unfilled();
+            return Ok(None);
+        };
+
+        let budget = Payment::new(asset, book.request.budget.0).to_units();
+        let context = self.decision_context(&service_type, &best.provider, best.reputation, budget).await;
+        let state = self.negotiation_state(&service_type, &asset, context, budget, 1).await;
+        let terms: Vec<ProposalTerms> = eligible
+            .iter()
+            .map(|(point, _)| ProposalTerms { price: point.price, counterparty_reputation: point.reputation, score: point.score })
+            .collect();
+        let choice = self.negotiation.read().await.choose_proposal(&state, &terms);
+        let Some((point, exposure)) = choice.and_then(|index| eligible.into_iter().nth(index)) else {
+            book.close_unfilled();
+            return Ok(None);
+        };
+
+        let transaction = book.select(&point.proposal_id)?;
+        if let (Some(budget), Some(exposure)) = (self.risk_budget.write().await.as_mut(), exposure) {
+            budget.add(transaction.id.to_string(), exposure);
+        }
+        self.observe_transaction(&transaction).await;
+        Ok(Some(transaction))
+    }
+
+    /// Save a request's offer book, so collection survives a restart
+    pub async fn persist_offer_book(&self, request_id: &TransactionId, storage: &StorageManager) -> Result<()> {
+        let books = self.offer_books.read().await;
+        let book = books.get(request_id).ok_or_else(|| Self::no_negotiation(request_id))?;
+        storage.store_offer_book(request_id, book).await
+    }
+
+    /// Reload a request's offer book saved by `persist_offer_book`,
+    /// returning whether one was found
+    pub async fn restore_offer_book(&self, request_id: &TransactionId, storage: &StorageManager) -> Result<bool> {
+        let Some(book) = storage.get_offer_book::<OfferBook>(request_id).await? else {
+            return Ok(false);
+        };
+        self.offer_books.write().await.insert(*request_id, book);
+        Ok(true)
+    }
+
+    /// Move what storage holds under the agent's legacy random ID to its
+    /// derived ID, returning whether anything moved
+    pub async fn migrate_legacy_id(&self, storage: &StorageManager) -> Result<bool> {
+        let Some(legacy_id) = self.config.legacy_id.filter(|legacy_id| *legacy_id != self.id) else {
+            return Ok(false);
+        };
+        let moved = storage.migrate_agent_id(&legacy_id, &self.id).await?;
+        if moved {
+            tracing::info!("Migrated agent {} records from legacy ID {}", self.id, legacy_id);
+        }
+        Ok(moved)
+    }
+
+    /// Join the trust domain of the owner who issued our membership,
+    /// signing what we share with `keypair`, which must be the key the
+    /// agent's ID is derived from
+    pub async fn join_trust_domain(&self, owner: ed25519_dalek::VerifyingKey, keypair: KeyPair, membership: DomainMembership) -> Result<()> {
+        self.authorize("join trust domains")?;
+        if membership.agent_id != self.id {
+            return Err(AgentError::InvalidConfig {
+                reason: "Membership was issued to another agent".to_string(),
+            }.into());
+        }
+        let member = KnowledgeMember::new(TrustDomain::new(owner), keypair, membership)?;
+        tracing::info!("Agent {} joined trust domain {}", self.id, member.domain.topic());
+        *self.knowledge.write().await = Some(member);
+        Ok(())
+    }
+
+    /// Sign our market observations not yet shared with the trust domain,
+    /// for publishing on its topic. Returns `None` outside a domain or when
+    /// there is nothing new.
+    pub async fn share_market_observations(&self) -> Result<Option<SharedObservations>> {
+        self.authorize("sign shared observations")?;
+        match self.knowledge.write().await.as_mut() {
+            Some(member) => member.share(&*self.market_predictors.read().await),
+            None => Ok(None),
+        }
+    }
+
+    /// Merge observations another member of our trust domain published,
+    /// returning how many were new
+    pub async fn receive_market_observations(&self, shared: &SharedObservations) -> Result<usize> {
+        match self.knowledge.write().await.as_mut() {
+            Some(member) => member.receive(shared, &mut *self.market_predictors.write().await),
+            None => Ok(0),
+        }
+    }
+
+    fn no_negotiation(transaction_id: &TransactionId) -> crate::error::SolaceError {
+        TransactionError::NotFound { id: transaction_id.to_string() }.into()
+    }
+
+    /// Queue a message that must reach its recipient. It stays in the
+    /// outbox, replicated to the standby if paired, until settled.
+    pub async fn send_reliable(&self, message: ACPMessage) -> Result<String> {
+        self.authorize("send reliable messages")?;
+        let id = uuid::Uuid::new_v4().to_string();
+        if let Some(replicator) = &self.replicator {
+            replicator.log().record(StateChange::Outbound { id: id.clone(), message: serde_json::to_vec(&message)? });
+        }
+        self.outbox.write().await.insert(id.clone(), message);
+        Ok(id)
+    }
+
+    /// Drop a message acknowledged by its recipient or given up; returns
+    /// whether it was in the outbox
+    pub async fn settle_outbound(&self, id: &str) -> bool {
+        let settled = self.outbox.write().await.remove(id).is_some();
+        if let Some(replicator) = self.replicator.as_ref().filter(|_| settled) {
+            replicator.log().record(StateChange::Settled { id: id.to_string() });
+        }
+        settled
+    }
+
+    /// Reliable messages still awaiting acknowledgment, to send or resend
+    pub async fn pending_outbound(&self) -> Vec<(String, ACPMessage)> {
+        self.outbox.read().await.iter().map(|(id, message)| (id.clone(), message.clone())).collect()
+    }
+
+    /// Start as the new active after this node's standby replica took over,
+    /// resuming the former active's unsettled messages and transactions
+    pub async fn resume_takeover(&self, takeover: Takeover) -> Result<()> {
+        let mut outbox = BTreeMap::new();
+        for (id, message) in takeover.outbox {
+            outbox.insert(id, serde_json::from_slice(&message)?);
+        }
+        *self.outbox.write().await = outbox;
+        tracing::info!("Agent {} taking over at epoch {}", self.id, takeover.epoch);
+        self.start().await
+    }
+
+    /// Take the queued reputation penalties to broadcast as `ReputationUpdate`s
+    pub async fn take_reputation_penalties(&self) -> Vec<ReputationUpdate> {
+        std::mem::take(&mut *self.reputation_penalties.write().await)
+    }
+
+    /// Let the strategy learn from a finished negotiation
+    pub async fn record_negotiation_outcome(&self, state: &NegotiationState, outcome: &TransactionOutcome) {
+        self.negotiation.write().await.observe_outcome(state, outcome);
+    }
+
+    /// Get agent summary for display
+    pub async fn get_summary(&self) -> AgentSummary {
+        AgentSummary {
+            id: self.id,
+            name: self.config.name.clone(),
+            description: self.config.description.clone(),
+            capabilities: self.config.capabilities.clone(),
+            state: self.get_state().await,
+            reputation: self.get_reputation().await,
+            balance: self.get_balance().await,
+            created_at: self.created_at,
+            last_active: *self.last_active.read().await,
+        }
+    }
+}
+
+/// Agent summary for display and serialization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSummary {
+    pub id: AgentId,
+    pub name: String,
+    pub description: String,
+    pub capabilities: Vec<AgentCapability>,
+    pub state: AgentState,
+    pub reputation: f64,
+    pub balance: Balance,
+    pub created_at: Timestamp,
+    pub last_active: Timestamp,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_config() -> AgentConfig {
+        AgentConfig {
+            keypair: None,
+            watch_key: None,
+            name: "Test Agent".to_string(),
+            description: "A test agent".to_string(),
+            capabilities: vec![AgentCapability::DataAnalysis],
+            preferences: AgentPreferences::default(),
+            network_address: None,
+            initial_reputation: Some(0.7),
+            legacy_id: None,
+            custom_services: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agent_creation() {
+        let config = create_test_config();
+        let agent = Agent::new(config).await.unwrap();
+        
+        assert_eq!(agent.config.name, "Test Agent");
+        assert_eq!(agent.get_reputation().await, 0.7);
+        assert_eq!(agent.get_state().await, AgentState::Offline);
+    }
+
+    #[tokio::test]
+    async fn test_watch_only_agent_has_no_signing_key() {
+        let public_key = Keypair::new().pubkey();
+        let agent = Agent::watch_only(create_test_config(), public_key).await.unwrap();
+
+        assert!(agent.is_watch_only());
+        assert_eq!(agent.public_key(), public_key);
+        assert!(agent.signing_keypair().is_err());
+        assert_eq!(agent.wallet.read().await.public_key, public_key);
+
+        let mut mismatched = create_test_config();
+        mismatched.keypair = Some(Keypair::new());
+        mismatched.watch_key = Some(public_key);
+        assert!(Agent::new(mismatched).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_agent_id_is_stable_and_migrates_legacy_records() {
+        let keypair = Keypair::new();
+        let mut config = create_test_config();
+        config.keypair = Some(Keypair::from_bytes(&keypair.to_bytes()).unwrap());
+        let first = Agent::new(config).await.unwrap();
+
+        // Restarting with the same key keeps the ID
+        let legacy_id = AgentId::new();
+        let mut config = create_test_config();
+        config.keypair = Some(keypair);
+        config.legacy_id = Some(legacy_id);
+        let restarted = Agent::new(config).await.unwrap();
+        assert_eq!(restarted.id, first.id);
+        assert_eq!(restarted.id, AgentId::from_public_key(&restarted.public_key()));
+
+        let storage = StorageManager::memory();
+        storage.store_reputation(&legacy_id, 0.9).await.unwrap();
+        assert!(restarted.migrate_legacy_id(&storage).await.unwrap());
+        assert_eq!(storage.get_reputation(&restarted.id).await.unwrap(), Some(0.9));
+        assert_eq!(storage.get_reputation(&legacy_id).await.unwrap(), None);
+        assert!(!restarted.migrate_legacy_id(&storage).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_agent_state_management() {
+        let config = create_test_config();
+        let agent = Agent::new(config).await.unwrap();
+        
+        // Test starting agent
+        agent.start().await.unwrap();
+        assert_eq!(agent.get_state().await, AgentState::Online);
+        assert!(agent.is_available().await);
+        
+        // Test stopping agent
+        agent.stop().await.unwrap();
+        assert_eq!(agent.get_state().await, AgentState::Offline);
+        assert!(!agent.is_available().await);
+    }
+
+    #[tokio::test]
+    async fn test_service_capability_matching() {
+        let config = create_test_config();
+        let agent = Agent::new(config).await.unwrap();
+        
+        assert!(agent.can_handle_service(&ServiceType::DataAnalysis));
+        assert!(!agent.can_handle_service(&ServiceType::TradingService));
+    }
+
+    #[tokio::test]
+    async fn test_custom_capabilities_need_registry_entries() {
+        let mut config = create_test_config();
+        config.capabilities.push(AgentCapability::CustomCapability("translation".to_string()));
+        assert!(Agent::new(config.clone()).await.is_err());
+
+        config.custom_services.push(CapabilityInfo::custom("translation", "Translation").with_requirement("target_language", "ISO 639-1 code", true));
+        let agent = Agent::new(config).await.unwrap();
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let mut request = TransactionRequest::new(AgentId::new(), ServiceType::CustomService("translation".to_string()), "Translate".to_string(), Balance::from_sol(50.0), deadline);
+        assert!(agent.screen_request(&request).await.is_err());
+
+        request.requirements.insert("target_language".to_string(), "de".to_string());
+        agent.prepare_request(&mut request).await.unwrap();
+        assert!(request.requirements.contains_key(crate::capability::SLA_MAX_COMPLETION));
+    }
+
+    #[tokio::test]
+    async fn test_custom_negotiation_strategy() {
+        use solace_ai::strategy::ConservativeStrategy;
+        use solace_ai::{DecisionContext, MarketConditions};
+
+        let agent = Agent::with_strategy(create_test_config(), Box::new(ConservativeStrategy::default())).await.unwrap();
+        let mut state = NegotiationState::new(DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        }, 100.0, 3);
+
+        let ask = agent.propose_price(&state).await;
+        assert_eq!(ask, 105.0);
+        state.our_asks.push(ask);
+        state.their_offers.push(96.0);
+        assert_eq!(agent.respond_to_counter_offer(&state, 96.0).await, CounterOfferResponse::Accept);
+
+        state.context.counterparty_reputation = 0.1;
+        assert_eq!(agent.respond_to_counter_offer(&state, 96.0).await, CounterOfferResponse::Reject);
+    }
+
+    #[tokio::test]
+    async fn test_risk_budget_spans_negotiations() {
+        use solace_ai::MarketConditions;
+
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        agent.set_risk_limit(70.0).await;
+        let context = DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.5,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+
+        let first = TransactionId::new();
+        let state = agent.negotiation_state(&ServiceType::DataAnalysis, &PaymentAsset::Sol, context.clone(), 100.0, 5).await;
+        agent.open_negotiation(first, AgentId::new(), PaymentAsset::Sol, state).await;
+        agent.record_ask(&first, 100.0).await.unwrap();
+        assert_eq!(agent.receive_counter_offer(&first, 100.0).await.unwrap(), CounterOfferResponse::Accept);
+
+        let second = TransactionId::new();
+        let state = agent.negotiation_state(&ServiceType::DataAnalysis, &PaymentAsset::Sol, context, 100.0, 5).await;
+        agent.open_negotiation(second, AgentId::new(), PaymentAsset::Sol, state).await;
+        agent.record_ask(&second, 100.0).await.unwrap();
+        assert_ne!(agent.receive_counter_offer(&second, 100.0).await.unwrap(), CounterOfferResponse::Accept);
+
+        agent.release_exposure(&first).await;
+        assert!(agent.risk_budget.read().await.as_ref().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_negotiation_sessions_track_responses() {
+        use solace_ai::{DecisionContext, MarketConditions};
+
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        let counterparty = AgentId::new();
+        let transaction_id = TransactionId::new();
+        let state = NegotiationState::new(DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        }, 100.0, 3);
+
+        agent.open_negotiation(transaction_id, counterparty, PaymentAsset::Sol, state).await;
+        assert!(agent.record_ask(&TransactionId::new(), 110.0).await.is_err());
+        agent.record_ask(&transaction_id, 110.0).await.unwrap();
+        agent.receive_counter_offer(&transaction_id, 105.0).await.unwrap();
+
+        assert!(agent.average_response_secs(&counterparty).await.is_some());
+        assert!(agent.withdraw_overdue_negotiations().await.is_empty());
+        let unknown = AgentId::new();
+        assert_eq!(agent.rank_counterparties(&[unknown, counterparty]).await, vec![counterparty, unknown]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_negotiation_is_dropped_without_a_record() {
+        use solace_ai::{DecisionContext, MarketConditions};
+
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        let counterparty = AgentId::new();
+        let transaction_id = TransactionId::new();
+        let state = NegotiationState::new(DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        }, 100.0, 3);
+        let cancel = CancellationToken::new();
+
+        agent.open_negotiation_cancellable(transaction_id, counterparty, PaymentAsset::Sol, state, cancel.clone()).await;
+        agent.record_ask(&transaction_id, 110.0).await.unwrap();
+        cancel.cancel();
+
+        assert!(matches!(
+            agent.receive_counter_offer(&transaction_id, 105.0).await,
+            Err(crate::error::SolaceError::Cancelled { .. })
+        ));
+        assert!(agent.negotiations.read().await.is_empty());
+        assert!(agent.average_response_secs(&counterparty).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cost_aware_screening_and_pricing() {
+        use crate::cost::ResourceEstimate;
+        use solace_ai::MarketConditions;
+
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        agent.set_cost_model(CostModel::default().with_estimate(ServiceType::DataAnalysis, ResourceEstimate::new(0.0, 1_875_000.0))).await;
+        let minimum = agent.cost_model.read().await.minimum_price(&ServiceType::DataAnalysis);
+        assert!(minimum.to_sol() > 10.0);
+
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let request = |service: ServiceType, budget: f64| {
+            TransactionRequest::new(AgentId::new(), service, "Analysis".to_string(), Balance::from_sol(budget), deadline)
+        };
+        assert!(agent.screen_request(&request(ServiceType::DataAnalysis, 20.0)).await.is_ok());
+        assert!(agent.screen_request(&request(ServiceType::DataAnalysis, 5.0)).await.is_err());
+        assert!(agent.screen_request(&request(ServiceType::TradingService, 20.0)).await.is_err());
+
+        let context = DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 5.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 5.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        let state = agent.negotiation_state(&ServiceType::DataAnalysis, &PaymentAsset::Sol, context, 5.0, 3).await;
+        assert!(agent.propose_price(&state).await >= minimum.to_sol());
+    }
+
+    #[tokio::test]
+    async fn test_screening_by_payment_asset() {
+        use crate::types::Payment;
+
+        let usdc = PaymentAsset::Spl { mint: Pubkey::new_unique(), decimals: 6 };
+        let mut config = create_test_config();
+        config.preferences.preferred_payment_methods.push(usdc);
+        let agent = Agent::new(config).await.unwrap();
+
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let request = |payment: Payment| {
+            TransactionRequest::new(AgentId::new(), ServiceType::DataAnalysis, "Analysis".to_string(), Balance(0), deadline)
+                .with_payment(payment)
+        };
+        assert!(agent.screen_request(&request(Payment::new(usdc, 25_000_000))).await.is_ok());
+        let other = PaymentAsset::Spl { mint: Pubkey::new_unique(), decimals: 6 };
+        assert!(agent.screen_request(&request(Payment::new(other, 25_000_000))).await.is_err());
+
+        let mut config = create_test_config();
+        config.preferences.preferred_payment_methods.clear();
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_market_conditions_from_observed_transactions() {
+        use crate::transaction::TransactionProposal;
+
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        let counterparty = AgentId::new();
+        let empty = agent.decision_context(&ServiceType::DataAnalysis, &counterparty, 0.6, 3.0).await;
+        assert_eq!(empty.market_conditions.demand_level, 0.0);
+        assert_eq!(empty.agent_reputation, 0.7);
+
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        for price in [2.0, 4.0] {
+            let request = TransactionRequest::new(
+                AgentId::new(), ServiceType::DataAnalysis, "Analysis".to_string(), Balance::from_sol(5.0), deadline,
+            );
+            let mut transaction = Transaction::new(request);
+            let provider = AgentId::new();
+            transaction.add_proposal(TransactionProposal {
+                id: TransactionId::new(),
+                request_id: transaction.id,
+                provider,
+                proposed_price: Balance::from_sol(price),
+                estimated_completion: deadline,
+                proposal_details: String::new(),
+                terms: HashMap::new(),
+                created_at: Timestamp::now(),
+                expires_at: deadline,
+            }).unwrap();
+            transaction.accept_proposal(provider, Balance::from_sol(price)).unwrap();
+            agent.observe_transaction(&transaction).await;
+        }
+
+        let context = agent.decision_context(&ServiceType::DataAnalysis, &counterparty, 0.6, 3.0).await;
+        assert!(context.market_conditions.demand_level > 0.0);
+        assert_eq!(context.market_conditions.average_pricing, 3.0);
+        assert_eq!(agent.market_conditions(&ServiceType::TradingService).await.average_pricing, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_advisor_blends_into_negotiation() {
+        use solace_ai::advisor::{AdvisorOpinion, StaticAdvisor};
+        use solace_ai::strategy::ConservativeStrategy;
+
+        let agent = Agent::with_strategy(create_test_config(), Box::new(ConservativeStrategy::default())).await.unwrap();
+        let mut state = NegotiationState::new(
+            agent.decision_context(&ServiceType::DataAnalysis, &AgentId::new(), 0.7, 100.0).await,
+            100.0,
+            3,
+        );
+        assert_eq!(agent.propose_price(&state).await, 105.0);
+
+        let opinion = AdvisorOpinion {
+            suggested_price: Some(125.0),
+            accept_probability: Some(0.0),
+            confidence: 1.0,
+            rationale: Some("demand is about to spike".to_string()),
+        };
+        agent.set_advisor(Some(WeightedAdvisor::new(Arc::new(StaticAdvisor(opinion)), 0.75))).await;
+        assert_eq!(agent.propose_price(&state).await, 120.0);
+
+        // The strategy alone would take 96, but the advisor holds out
+        state.our_asks.push(105.0);
+        state.their_offers.push(96.0);
+        assert_eq!(agent.respond_to_counter_offer(&state, 96.0).await, CounterOfferResponse::Counter(120.0));
+
+        agent.set_advisor(None).await;
+        assert_eq!(agent.respond_to_counter_offer(&state, 96.0).await, CounterOfferResponse::Accept);
+    }
+
+    #[tokio::test]
+    async fn test_request_for_quotes() {
+        use crate::rfq::RfqStatus;
+
+        let requester = Agent::new(create_test_config()).await.unwrap();
+        let intent = QuoteIntent::new(
+            requester.id,
+            ServiceType::DataAnalysis,
+            "Sentiment analysis".to_string(),
+            (Balance::from_sol(1.0), Balance::from_sol(10.0)),
+            Timestamp(chrono::Utc::now() + chrono::Duration::hours(2)),
+            chrono::Duration::minutes(5),
+        );
+        let RfqMessage::Intent(broadcast) = requester.request_quotes(intent.clone()).await.unwrap() else {
+            panic!("expected an intent");
+        };
+
+        let mut providers = Vec::new();
+        for reputation in [0.9, 0.3] {
+            let mut config = create_test_config();
+            config.initial_reputation = Some(reputation);
+            let provider = Agent::new(config).await.unwrap();
+            let quote = provider.quote_intent(&broadcast, chrono::Duration::minutes(30)).await.unwrap();
+            assert!(quote.price <= intent.max_budget);
+            requester.receive_quote(quote).await.unwrap();
+            providers.push(provider);
+        }
+
+        let mut research = intent.clone();
+        research.service_type = ServiceType::MarketResearch;
+        assert!(providers[0].quote_intent(&research, chrono::Duration::minutes(30)).await.is_none());
+
+        // Quotes are awarded only once the window closes
+        assert!(requester.award_quotes(&intent.id, &SelectionWeights::default()).await.is_err());
+        requester.rfqs.write().await.get_mut(&intent.id).unwrap().intent.quotes_close_at = Timestamp::now();
+
+        let transaction = requester.award_quotes(&intent.id, &SelectionWeights::default()).await.unwrap().unwrap();
+        assert_eq!(transaction.provider, Some(providers[0].id));
+        assert_eq!(requester.rfqs.read().await[&intent.id].status, RfqStatus::Awarded);
+    }
+
+    #[tokio::test]
+    async fn test_offer_book_selects_after_the_window() {
+        use crate::transaction::TransactionProposal;
+
+        let requester = Agent::new(create_test_config()).await.unwrap();
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(4));
+        let request = TransactionRequest::new(requester.id, ServiceType::DataAnalysis, "Analysis".to_string(), Balance::from_sol(10.0), deadline);
+        requester.open_offer_book(request.clone(), chrono::Duration::minutes(10)).await.unwrap();
+
+        let proposal = |price: f64| TransactionProposal {
+            id: TransactionId::new(),
+            request_id: request.id,
+            provider: AgentId::new(),
+            proposed_price: Balance::from_sol(price),
+            estimated_completion: Timestamp(chrono::Utc::now() + chrono::Duration::hours(1)),
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: Timestamp(chrono::Utc::now() + chrono::Duration::hours(1)),
+        };
+        let acceptable = proposal(6.0);
+        let cheaper = proposal(4.0);
+        requester.collect_proposal(acceptable.clone(), 0.9).await.unwrap();
+        // Cheaper, but from a provider below our minimum reputation
+        requester.collect_proposal(cheaper.clone(), 0.1).await.unwrap();
+        assert_eq!(requester.compare_offers(&request.id, &SelectionWeights::default()).await.unwrap().len(), 2);
+
+        let storage = StorageManager::memory();
+        requester.persist_offer_book(&request.id, &storage).await.unwrap();
+        requester.offer_books.write().await.clear();
+        assert!(requester.restore_offer_book(&request.id, &storage).await.unwrap());
+
+        assert!(requester.select_offer(&request.id, &SelectionWeights::default()).await.is_err());
+        requester.offer_books.write().await.get_mut(&request.id).unwrap().closes_at = Timestamp::now();
+        let transaction = requester.select_offer(&request.id, &SelectionWeights::default()).await.unwrap().unwrap();
+        assert_eq!(transaction.provider, Some(acceptable.provider));
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingChannel {
+        sent: std::sync::Mutex<Vec<AgentId>>,
+    }
+
+    #[async_trait::async_trait]
+    impl QuoteChannel for RecordingChannel {
+        async fn send(&self, provider: &AgentId, message: crate::acp::ACPMessage) -> Result<()> {
+            assert_eq!(message.message_type, crate::acp::MessageType::QuoteRequest);
+            self.sent.lock().unwrap().push(*provider);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_requests_solicit_and_choose_providers() {
+        use crate::transaction::TransactionProposal;
+
+        let requester = Agent::new(create_test_config()).await.unwrap();
+        let channel = Arc::new(RecordingChannel::default());
+        requester.set_quote_channel(Some(channel.clone() as Arc<dyn QuoteChannel>)).await;
+        let (fast, slow, pricey) = (AgentId::new(), AgentId::new(), AgentId::new());
+        for (provider, price) in [(fast, 1.0), (slow, 2.0), (pricey, 20.0)] {
+            let range = (Balance::from_sol(price), Balance::from_sol(price * 2.0));
+            let offer = ServiceOffer::new(provider, ServiceType::DataAnalysis, range, OfferSla::default(), Timestamp::now(), chrono::Duration::minutes(30));
+            requester.observe_service_offer(offer.unwrap()).await;
+        }
+
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(4));
+        let request = TransactionRequest::new(requester.id, ServiceType::DataAnalysis, "Analysis".to_string(), Balance::from_sol(10.0), deadline);
+        let service = ServiceRequest::new(request.clone()).with_window(chrono::Duration::milliseconds(200));
+        let proposal = |provider: AgentId, price: f64, hours: i64| TransactionProposal {
+            id: TransactionId::new(),
+            request_id: request.id,
+            provider,
+            proposed_price: Balance::from_sol(price),
+            estimated_completion: Timestamp(chrono::Utc::now() + chrono::Duration::hours(hours)),
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: Timestamp(chrono::Utc::now() + chrono::Duration::hours(1)),
+        };
+        let providers_answer = async {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            requester.collect_proposal(proposal(fast, 3.0, 1), 0.8).await.unwrap();
+            requester.collect_proposal(proposal(slow, 4.0, 3), 0.8).await.unwrap();
+        };
+        let (outcome, ()) = tokio::join!(requester.request_service(service), providers_answer);
+        let outcome = outcome.unwrap();
+
+        // The provider whose offer starts above the budget is not asked
+        assert_eq!(outcome.solicited.len(), 2);
+        assert!(!channel.sent.lock().unwrap().contains(&pricey));
+        assert_eq!(outcome.proposals, 2);
+        assert_eq!(outcome.transaction.unwrap().provider, Some(fast));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_service_request_leaves_no_offer_book() {
+        use crate::transaction::TransactionProposal;
+
+        let requester = Agent::new(create_test_config()).await.unwrap();
+        requester.set_quote_channel(Some(Arc::new(RecordingChannel::default()) as Arc<dyn QuoteChannel>)).await;
+        let provider = AgentId::new();
+        let range = (Balance::from_sol(1.0), Balance::from_sol(2.0));
+        let offer = ServiceOffer::new(provider, ServiceType::DataAnalysis, range, OfferSla::default(), Timestamp::now(), chrono::Duration::minutes(30));
+        requester.observe_service_offer(offer.unwrap()).await;
+
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(4));
+        let request = TransactionRequest::new(requester.id, ServiceType::DataAnalysis, "Analysis".to_string(), Balance::from_sol(10.0), deadline);
+        let service = ServiceRequest::new(request.clone()).with_window(chrono::Duration::seconds(30));
+        let cancel = CancellationToken::new();
+        let late_proposal = TransactionProposal {
+            id: TransactionId::new(),
+            request_id: request.id,
+            provider,
+            proposed_price: Balance::from_sol(1.5),
+            estimated_completion: Timestamp(chrono::Utc::now() + chrono::Duration::hours(1)),
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: Timestamp(chrono::Utc::now() + chrono::Duration::hours(1)),
+        };
+        let cancel_midway = async {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            cancel.cancel();
+        };
+        let (outcome, ()) = tokio::join!(requester.request_service_cancellable(service, &cancel), cancel_midway);
+
+        assert!(matches!(outcome, Err(crate::error::SolaceError::Cancelled { .. })));
+        assert!(requester.offer_books.read().await.is_empty());
+        assert!(requester.collect_proposal(late_proposal, 0.8).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_marketplace_offers_reach_requesters() {
+        let provider = Agent::new(create_test_config()).await.unwrap();
+        let requester = Agent::new(create_test_config()).await.unwrap();
+        let range = (Balance::from_sol(0.1), Balance::from_sol(0.5));
+
+        assert!(provider.publish_offer(ServiceType::MachineLearning, range, OfferSla::default()).await.is_err());
+        let offer = provider.publish_offer(ServiceType::DataAnalysis, range, OfferSla::default()).await.unwrap();
+        assert!(requester.observe_service_offer(offer).await);
+        let found = requester.find_providers(&ServiceType::DataAnalysis, ProviderFilters::default()).await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].offer.provider, provider.id);
+
+        let withdrawal = provider.withdraw_offer(&ServiceType::DataAnalysis).await.unwrap().unwrap();
+        assert_eq!(withdrawal.sequence, 1);
+        assert!(requester.observe_service_offer(withdrawal).await);
+        assert!(requester.find_providers(&ServiceType::DataAnalysis, ProviderFilters::default()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_capacity_ads_steer_matchmaking() {
+        let provider = Agent::new(create_test_config()).await.unwrap();
+        let requester = Agent::new(create_test_config()).await.unwrap();
+        provider.set_job_slots(1).await;
+
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let other = AgentId::new();
+        requester.observe_capacity_ad(provider.advertise_capacity().await.unwrap()).await;
+        let candidates = [provider.id, other];
+        assert_eq!(requester.match_providers(&candidates, &ServiceType::DataAnalysis, deadline).await.len(), 2);
+
+        let job = TransactionId::new();
+        let busy = provider.commit_capacity(job, Timestamp(chrono::Utc::now() + chrono::Duration::hours(2))).await.unwrap();
+        assert_eq!(busy.free_slots, 0);
+        requester.observe_capacity_ad(busy).await;
+        assert_eq!(requester.match_providers(&candidates, &ServiceType::DataAnalysis, deadline).await, vec![other]);
+
+        requester.observe_capacity_ad(provider.release_capacity(&job).await.unwrap()).await;
+        assert_eq!(requester.match_providers(&candidates, &ServiceType::DataAnalysis, deadline).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_price_governance() {
+        use crate::governance::ServicePriceBounds;
+        use crate::reputation::ReputationEventType;
+        use crate::transaction::{TransactionProposal, TransactionRequest};
+        use solace_ai::MarketConditions;
+
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        let mut params = ProtocolParams { version: 1, report_violations: true, violation_penalty: 1.0, ..ProtocolParams::default() };
+        params.price_bounds.insert(
+            ServiceType::DataAnalysis,
+            ServicePriceBounds::new(Balance::from_sol(2.0), Balance::from_sol(8.0)).unwrap(),
+        );
+        assert!(agent.update_protocol_params(params).await);
+
+        let context = DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: 100.0,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: 100.0,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        let state = agent.negotiation_state(&ServiceType::DataAnalysis, &PaymentAsset::Sol, context, 100.0, 3).await;
+        assert!(agent.propose_price(&state).await <= 8.0);
+
+        let provider = AgentId::new();
+        let transaction = agent.open_transaction(TransactionRequest::new(
+            AgentId::new(),
+            ServiceType::DataAnalysis,
+            "Cheap analysis".to_string(),
+            Balance::from_sol(10.0),
+            Timestamp(chrono::Utc::now() + chrono::Duration::hours(1)),
+        )).await.unwrap();
+        agent.transactions.apply(&transaction.id, Transition::AddProposal(TransactionProposal {
+            id: crate::types::TransactionId::new(),
+            request_id: transaction.id,
+            provider,
+            proposed_price: Balance::from_sol(1.0),
+            estimated_completion: Timestamp::now(),
+            proposal_details: "Below floor".to_string(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: Timestamp::now(),
+        })).await.unwrap();
+
+        assert!(agent.accept_proposal(&transaction.id, provider, Balance::from_sol(1.0)).await.is_err());
+        let penalties = agent.take_reputation_penalties().await;
+        assert_eq!(penalties.len(), 1);
+        assert_eq!(penalties[0].agent_id, provider);
+        assert!(matches!(penalties[0].event.event_type, ReputationEventType::PricingViolation));
+        assert!(penalties[0].event.delta < 0.0);
+        assert!(agent.take_reputation_penalties().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_params_updates_over_acp_need_the_council() {
+        use crate::acp::ProtocolVersion;
+        use crate::crypto::KeyPair;
+
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        let members: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate().unwrap()).collect();
+        let keys: Vec<_> = members.iter().map(|member| *member.verifying_key()).collect();
+        let message = |update: &ParamsUpdate| ACPMessage {
+            message_type: update.message_type(),
+            version: ProtocolVersion(crate::PROTOCOL_VERSION.to_string()),
+            payload: serde_json::to_vec(&update.to_payload().unwrap()).unwrap(),
+        };
+        let params = ProtocolParams { version: 1, negotiation_response_timeout_secs: 5, ..ProtocolParams::default() };
+
+        let approved = ParamsUpdate::new(params.clone()).approve(&members[0]).unwrap().approve(&members[2]).unwrap();
+        assert!(agent.handle_message(&message(&approved)).await.is_err());
+
+        agent.set_governance_council(MultisigWallet::new(&keys, 2).unwrap()).await;
+        let short = ParamsUpdate::new(params).approve(&members[1]).unwrap();
+        assert!(agent.handle_message(&message(&short)).await.is_err());
+        assert_eq!(agent.protocol_params.read().await.version, 0);
+
+        assert!(agent.handle_message(&message(&approved)).await.unwrap());
+        assert_eq!(agent.protocol_params.read().await.negotiation_response_timeout_secs, 5);
+    }
+
+    #[tokio::test]
+    async fn test_transactions_survive_a_restart() {
+        use crate::transaction::{TransactionPhase, TransactionProposal};
+        use crate::transaction_manager::PendingTransition;
+
+        let storage = Arc::new(StorageManager::memory());
+        let agent = Agent::new(create_test_config()).await.unwrap().with_transaction_storage(storage.clone());
+        agent.start().await.unwrap();
+
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let request = TransactionRequest::new(agent.id, ServiceType::DataAnalysis, "report".to_string(), Balance(1_000), deadline);
+        let transaction = agent.open_transaction(request).await.unwrap();
+        let provider = AgentId::new();
+        agent.transactions.apply(&transaction.id, Transition::AddProposal(TransactionProposal {
+            id: TransactionId::new(),
+            request_id: transaction.id,
+            provider,
+            proposed_price: Balance(800),
+            estimated_completion: deadline,
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: deadline,
+        })).await.unwrap();
+
+        // The node stops after recording the acceptance but before storing it
+        let accept = PendingTransition {
+            transaction_id: transaction.id,
+            version: 2,
+            transition: Transition::AcceptProposal { provider, price: Balance(800) },
+            recorded_at: Timestamp::now(),
+        };
+        storage.store_pending_transition(&transaction.id, &accept).await.unwrap();
+        agent.stop().await.unwrap();
+
+        let restarted = Agent::new(create_test_config()).await.unwrap().with_transaction_storage(storage);
+        assert!(restarted.transactions.get(&transaction.id).await.is_none());
+        restarted.start().await.unwrap();
+        let resumed = restarted.transactions.get(&transaction.id).await.unwrap();
+        assert_eq!(resumed.phase, TransactionPhase::Execution);
+        assert_eq!(resumed.agreed_price, Some(Balance(800)));
+        assert!(restarted.accept_proposal(&transaction.id, provider, Balance(800)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reliable_messages_replicate_and_fencing_stops_the_agent() {
+        use crate::acp::ProtocolVersion;
+        use crate::standby::{ReplicationBatch, ReplicationChannel, ReplicationLog, StandbyConfig, StandbyReplica};
+        use std::time::Duration;
+
+        struct Link {
+            replica: Arc<StandbyReplica>,
+        }
+
+        impl std::fmt::Debug for Link {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("Link")
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl ReplicationChannel for Link {
+            async fn send(&self, batch: ReplicationBatch) -> Result<u64> {
+                self.replica.apply(&batch, batch.sent_at).await?;
+                Ok(self.replica.applied().await)
+            }
+        }
+
+        let config = StandbyConfig {
+            heartbeat_interval: Duration::from_millis(10),
+            failure_timeout: Duration::from_millis(200),
+            fencing_delay: Duration::from_millis(10),
+        };
+        let keypair = KeyPair::generate().unwrap();
+        let log = Arc::new(ReplicationLog::new());
+        let standby_storage = Arc::new(StorageManager::memory());
+        let replica = Arc::new(StandbyReplica::new(standby_storage.clone(), *keypair.verifying_key(), config.clone()));
+        let replicator = Arc::new(ActiveReplicator::new(log.clone(), keypair, Arc::new(Link { replica: replica.clone() }), config));
+        let active = Agent::new(create_test_config()).await.unwrap()
+            .with_transaction_storage(Arc::new(StorageManager::memory().with_replication(log.clone())))
+            .with_replicator(replicator.clone());
+
+        let message = ACPMessage {
+            message_type: MessageType::ReputationUpdate,
+            version: ProtocolVersion(crate::PROTOCOL_VERSION.to_string()),
+            payload: b"{}".to_vec(),
+        };
+        let delivered = active.send_reliable(message.clone()).await.unwrap();
+        let pending = active.send_reliable(message.clone()).await.unwrap();
+        assert!(active.settle_outbound(&delivered).await);
+        assert!(!active.settle_outbound(&delivered).await);
+        assert_eq!(replicator.ship(Timestamp::now()).await.unwrap(), 3);
+
+        // Cut off from its standby, the active stops transacting
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(active.send_reliable(message).await.is_err());
+
+        let takeover = replica.take_over(Timestamp(chrono::Utc::now() + chrono::Duration::seconds(1))).await.unwrap();
+        let standby = Agent::new(create_test_config()).await.unwrap().with_transaction_storage(standby_storage);
+        standby.resume_takeover(takeover).await.unwrap();
+        let resumed = standby.pending_outbound().await;
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].0, pending);
+        assert!(standby.is_available().await);
+    }
+
+    #[tokio::test]
+    async fn test_release_approvals_over_acp_reach_the_escrow() {
+        use crate::acp::ProtocolVersion;
+        use crate::escrow::{EscrowLedger, ReleasePolicy};
+        use crate::transaction::{TransactionProposal, TransactionRequest};
+
+        struct NoopLedger;
+
+        #[async_trait::async_trait]
+        impl EscrowLedger for NoopLedger {
+            async fn lock(&self, _escrow: &Escrow) -> Result<String> { Ok("lock".to_string()) }
+            async fn release_partial(&self, _escrow: &Escrow, _amount: Balance) -> Result<String> { Ok("partial".to_string()) }
+            async fn release(&self, _escrow: &Escrow) -> Result<String> { Ok("release".to_string()) }
+            async fn refund(&self, _escrow: &Escrow) -> Result<String> { Ok("refund".to_string()) }
+        }
+
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        let signers: Vec<KeyPair> = (0..2).map(|_| KeyPair::generate().unwrap()).collect();
+        let keys: Vec<_> = signers.iter().map(|signer| *signer.verifying_key()).collect();
+        let policy = ReleasePolicy::new(MultisigWallet::new(&keys, 2).unwrap()).with_limit(PaymentAsset::Sol, Balance(100));
+
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let request = TransactionRequest::new(agent.id, ServiceType::DataAnalysis, "report".to_string(), Balance(1_000), deadline);
+        let transaction = agent.open_transaction(request).await.unwrap();
+        let provider = AgentId::new();
+        agent.transactions.apply(&transaction.id, Transition::AddProposal(TransactionProposal {
+            id: TransactionId::new(),
+            request_id: transaction.id,
+            provider,
+            proposed_price: Balance(800),
+            estimated_completion: deadline,
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: deadline,
+        })).await.unwrap();
+        let escrow = Escrow::lock_with_policy(&NoopLedger, &agent.transactions, &transaction.id, provider, Balance(800), "provider", policy)
+            .await
+            .unwrap();
+
+        let approval = ReleaseApproval::sign(&escrow, escrow.remaining(), &signers[0]);
+        let message = ACPMessage {
+            message_type: approval.message_type(),
+            version: ProtocolVersion(crate::PROTOCOL_VERSION.to_string()),
+            payload: serde_json::to_vec(&approval.to_payload().unwrap()).unwrap(),
+        };
+        assert!(agent.handle_message(&message).await.is_err());
+
+        agent.track_escrow(escrow).await;
+        assert!(agent.handle_message(&message).await.unwrap());
+        assert_eq!(agent.escrows.read().await[&transaction.id].approvals.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_token_proposals_use_token_bounds() {
+        use crate::governance::ServicePriceBounds;
+        use crate::transaction::{TransactionProposal, TransactionRequest};
+        use crate::types::Payment;
+
+        let usdc = PaymentAsset::Spl { mint: Pubkey::new_unique(), decimals: 6 };
+        let agent = Agent::new(create_test_config()).await.unwrap();
+        let mut params = ProtocolParams { version: 1, report_violations: true, violation_penalty: 1.0, ..ProtocolParams::default() };
+        params.price_bounds.insert(
+            ServiceType::DataAnalysis,
+            ServicePriceBounds::new(Balance::from_sol(2.0), Balance::from_sol(8.0)).unwrap(),
+        );
+        params.token_price_bounds.entry(usdc).or_default().insert(
+            ServiceType::DataAnalysis,
+            ServicePriceBounds::new(Balance(5_000_000), Balance(50_000_000)).unwrap(),
+        );
+        assert!(agent.update_protocol_params(params).await);
+
+        // 20 USDC is within the token bounds, though far below the SOL floor in lamports
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let proposal = |transaction: &Transaction, provider: AgentId, price: Balance| TransactionProposal {
+            id: crate::types::TransactionId::new(),
+            request_id: transaction.id,
+            provider,
+            proposed_price: price,
+            estimated_completion: Timestamp::now(),
+            proposal_details: "Analysis".to_string(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: deadline,
+        };
+        let request = || TransactionRequest::new(AgentId::new(), ServiceType::DataAnalysis, "Analysis".to_string(), Balance(0), deadline)
+            .with_payment(Payment::new(usdc, 100_000_000));
+
+        let provider = AgentId::new();
+        let transaction = agent.open_transaction(request()).await.unwrap();
+        agent.transactions.apply(&transaction.id, Transition::AddProposal(proposal(&transaction, provider, Balance(20_000_000)))).await.unwrap();
+        let accepted = agent.accept_proposal(&transaction.id, provider, Balance(20_000_000)).await.unwrap();
+        assert_eq!(accepted.agreed_price, Some(Balance(20_000_000)));
+        assert!(agent.take_reputation_penalties().await.is_empty());
+
+        let transaction = agent.open_transaction(request()).await.unwrap();
+        agent.transactions.apply(&transaction.id, Transition::AddProposal(proposal(&transaction, provider, Balance(80_000_000)))).await.unwrap();
+        assert!(agent.accept_proposal(&transaction.id, provider, Balance(80_000_000)).await.is_err());
+        let penalties = agent.take_reputation_penalties().await;
+        assert_eq!((penalties.len(), penalties[0].agent_id), (1, provider));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = create_test_config();
+        
+        // Test empty name
+        config.name = "".to_string();
+        assert!(config.validate().is_err());
+        
+        // Test empty capabilities
+        config.name = "Test".to_string();
+        config.capabilities = vec![];
+        assert!(config.validate().is_err());
+        
+        // Test invalid risk tolerance
+        config.capabilities = vec![AgentCapability::DataAnalysis];
+        config.preferences.risk_tolerance = 1.5;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_builder_validates_at_build() {
+        assert!(AgentConfig::builder("Builder").build().is_err());
+        assert!(AgentConfig::builder("Builder")
+            .with_capability(AgentCapability::DataAnalysis)
+            .with_initial_reputation(1.5)
+            .build()
+            .is_err());
+
+        let config = AgentConfig::builder("Builder")
+            .with_description("Built agent")
+            .with_capability(AgentCapability::DataAnalysis)
+            .with_custom_service(CapabilityInfo::custom("Forecasting", "Demand forecasts"))
+            .build()
+            .unwrap();
+        assert_eq!(config.capabilities.len(), 2);
+        assert!(config.capabilities.contains(&AgentCapability::CustomCapability("forecasting".to_string())));
     }
 }
\ No newline at end of file
@@ -0,0 +1,324 @@
+//! Market Analytics
+//!
+//! Per-service market statistics aggregated from the transactions an agent
+//! has observed: clearing prices, demand volume, fill rate, and time to fill.
+//! The same aggregates are turned into `MarketConditions` for the negotiation
+//! layer, so pricing decisions rest on what the agent actually saw instead of
+//! numbers each caller has to make up.
+//!
+//! A transaction counts as filled once a proposal was accepted; its time to
+//! fill runs from the request to the accepted proposal. Requests that ended
+//! (failed, cancelled, or expired) without an accepted proposal count as
+//! unfilled. Only transactions requested within the rolling window count.
+//! What of these statistics may be shared is decided in `privacy`.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use solace_ai::{MarketConditions, RiskIndicator};
+use std::collections::HashMap;
+
+use crate::{
+    storage::StorageManager,
+    transaction::{Transaction, TransactionStatus},
+    types::{Balance, ServiceType, Timestamp, TransactionId},
+    Result,
+};
+
+/// Default rolling window
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+
+/// Request rate at which demand reads as 0.5
+const DEMAND_REFERENCE_PER_HOUR: f64 = 10.0;
+
+/// Resolved requests at which the unfilled-rate indicator reaches half confidence
+const INDICATOR_CONFIDENCE_SAMPLES: f64 = 5.0;
+
+/// What the analytics keep of one transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Observation {
+    service_type: ServiceType,
+    requested_at: Timestamp,
+    budget: Balance,
+    proposals: usize,
+    clearing_price: Option<Balance>,
+    time_to_fill_secs: Option<f64>,
+    abandoned: bool,                      // Ended without an accepted proposal
+}
+
+impl Observation {
+    fn from_transaction(tx: &Transaction) -> Self {
+        let accepted = tx.provider.zip(tx.agreed_price).and_then(|(provider, price)| {
+            tx.proposals.iter().find(|p| p.provider == provider && p.proposed_price == price)
+        });
+        let abandoned = tx.agreed_price.is_none()
+            && matches!(tx.status, TransactionStatus::Failed | TransactionStatus::Cancelled | TransactionStatus::Expired);
+
+        Self {
+            service_type: tx.request.service_type.clone(),
+            requested_at: tx.request.created_at,
+            budget: tx.request.budget,
+            proposals: tx.proposals.len(),
+            clearing_price: tx.agreed_price,
+            time_to_fill_secs: accepted.map(|p| {
+                (p.created_at.0 - tx.request.created_at.0).num_milliseconds().max(0) as f64 / 1000.0
+            }),
+            abandoned,
+        }
+    }
+}
+
+/// Market statistics for one service type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceMarketStats {
+    pub service_type: ServiceType,
+    pub window_secs: i64,
+    pub requests: usize,
+    pub fills: usize,
+    pub unfilled: usize,                              // Ended without an accepted proposal
+    pub demand_per_hour: f64,
+    pub fill_rate: Option<f64>,                       // Fills over resolved requests
+    pub average_clearing_price: Option<Balance>,
+    pub median_time_to_fill_secs: Option<f64>,
+    pub average_proposals: f64,                       // Provider proposals per request
+    pub average_budget: Option<Balance>,
+}
+
+impl ServiceMarketStats {
+    /// Market conditions for the negotiation layer
+    ///
+    /// Demand reads the request rate against a reference rate, competition
+    /// the number of proposals per request, and the pricing anchor is the
+    /// average clearing price (or the average budget before any fills). A
+    /// poor fill rate surfaces as an `unfilled_rate` risk indicator.
+    pub fn market_conditions(&self) -> MarketConditions {
+        let demand_level = self.demand_per_hour / (self.demand_per_hour + DEMAND_REFERENCE_PER_HOUR);
+        let competition_level = self.average_proposals / (self.average_proposals + 1.0);
+        let average_pricing = self
+            .average_clearing_price
+            .or(self.average_budget)
+            .map_or(0.0, |price| price.to_sol());
+
+        let resolved = (self.fills + self.unfilled) as f64;
+        let risk_indicators = self
+            .fill_rate
+            .map(|rate| RiskIndicator {
+                indicator_type: "unfilled_rate".to_string(),
+                value: 1.0 - rate,
+                confidence: resolved / (resolved + INDICATOR_CONFIDENCE_SAMPLES),
+            })
+            .into_iter()
+            .collect();
+
+        MarketConditions {
+            demand_level,
+            competition_level,
+            average_pricing,
+            risk_indicators,
+        }
+    }
+}
+
+/// Rolling per-service market analytics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketAnalytics {
+    window_secs: i64,
+    observations: HashMap<TransactionId, Observation>,
+}
+
+impl MarketAnalytics {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window_secs: window.num_seconds().max(1),
+            observations: HashMap::new(),
+        }
+    }
+
+    /// Aggregate every transaction in storage
+    pub async fn from_storage(storage: &StorageManager) -> Result<Self> {
+        let mut analytics = Self::default();
+        for tx in storage.list_transactions::<Transaction>().await? {
+            analytics.observe(&tx);
+        }
+        Ok(analytics)
+    }
+
+    /// Record a transaction, or update it as it progresses
+    pub fn observe(&mut self, tx: &Transaction) {
+        self.observations.insert(tx.id, Observation::from_transaction(tx));
+    }
+
+    /// Number of transactions observed
+    pub fn len(&self) -> usize {
+        self.observations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observations.is_empty()
+    }
+
+    /// Drop transactions requested before the window
+    pub fn prune(&mut self, now: Timestamp) {
+        let cutoff = now.0 - Duration::seconds(self.window_secs);
+        self.observations.retain(|_, observation| observation.requested_at.0 >= cutoff);
+    }
+
+    /// Statistics for one service type over the window ending at `now`
+    pub fn stats(&self, service_type: &ServiceType, now: Timestamp) -> ServiceMarketStats {
+        let cutoff = now.0 - Duration::seconds(self.window_secs);
+        let observations: Vec<&Observation> = self
+            .observations
+            .values()
+            .filter(|o| &o.service_type == service_type && o.requested_at.0 >= cutoff && o.requested_at.0 <= now.0)
+            .collect();
+
+        let requests = observations.len();
+        let prices: Vec<u64> = observations.iter().filter_map(|o| o.clearing_price).map(|p| p.0).collect();
+        let fills = prices.len();
+        let unfilled = observations.iter().filter(|o| o.abandoned).count();
+        let mut fill_times: Vec<f64> = observations.iter().filter_map(|o| o.time_to_fill_secs).collect();
+        fill_times.sort_by(f64::total_cmp);
+
+        let mean_balance = |values: &[u64]| {
+            (!values.is_empty()).then(|| Balance(values.iter().sum::<u64>() / values.len() as u64))
+        };
+        let budgets: Vec<u64> = observations.iter().map(|o| o.budget.0).collect();
+
+        ServiceMarketStats {
+            service_type: service_type.clone(),
+            window_secs: self.window_secs,
+            requests,
+            fills,
+            unfilled,
+            demand_per_hour: requests as f64 * 3600.0 / self.window_secs as f64,
+            fill_rate: (fills + unfilled > 0).then(|| fills as f64 / (fills + unfilled) as f64),
+            average_clearing_price: mean_balance(&prices),
+            median_time_to_fill_secs: median(&fill_times),
+            average_proposals: if requests == 0 {
+                0.0
+            } else {
+                observations.iter().map(|o| o.proposals).sum::<usize>() as f64 / requests as f64
+            },
+            average_budget: mean_balance(&budgets),
+        }
+    }
+
+    /// Statistics for every service type seen in the window, busiest first
+    pub fn all_stats(&self, now: Timestamp) -> Vec<ServiceMarketStats> {
+        let mut services: Vec<&ServiceType> = self.observations.values().map(|o| &o.service_type).collect();
+        services.sort_by_key(|service| service.to_string());
+        services.dedup();
+
+        let mut stats: Vec<ServiceMarketStats> = services
+            .into_iter()
+            .map(|service| self.stats(service, now))
+            .filter(|stats| stats.requests > 0)
+            .collect();
+        stats.sort_by_key(|stats| std::cmp::Reverse(stats.requests));
+        stats
+    }
+
+    /// Market conditions for a service type, from observed data
+    pub fn market_conditions(&self, service_type: &ServiceType, now: Timestamp) -> MarketConditions {
+        self.stats(service_type, now).market_conditions()
+    }
+}
+
+impl Default for MarketAnalytics {
+    fn default() -> Self {
+        Self::new(Duration::hours(DEFAULT_WINDOW_HOURS))
+    }
+}
+
+fn median(sorted: &[f64]) -> Option<f64> {
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n.is_multiple_of(2) => Some((sorted[mid - 1] + sorted[mid]) / 2.0),
+        _ => Some(sorted[mid]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{TransactionProposal, TransactionRequest};
+    use crate::types::AgentId;
+
+    fn at(secs: i64) -> Timestamp {
+        Timestamp(chrono::DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap())
+    }
+
+    fn transaction(service_type: ServiceType, requested: i64, fill: Option<(f64, i64)>) -> Transaction {
+        let mut request = TransactionRequest::new(
+            AgentId::new(),
+            service_type,
+            "analysis".to_string(),
+            Balance::from_sol(10.0),
+            at(requested + 3600),
+        );
+        request.created_at = at(requested);
+        let mut tx = Transaction::new(request);
+
+        if let Some((price, after)) = fill {
+            let provider = AgentId::new();
+            tx.add_proposal(TransactionProposal {
+                id: TransactionId::new(),
+                request_id: tx.id,
+                provider,
+                proposed_price: Balance::from_sol(price),
+                estimated_completion: at(requested + 7200),
+                proposal_details: String::new(),
+                terms: HashMap::new(),
+                created_at: at(requested + after),
+                expires_at: at(requested + 3600),
+            })
+            .unwrap();
+            tx.accept_proposal(provider, Balance::from_sol(price)).unwrap();
+        }
+        tx
+    }
+
+    #[test]
+    fn test_stats_aggregate_observed_transactions() {
+        let mut analytics = MarketAnalytics::new(Duration::hours(1));
+        analytics.observe(&transaction(ServiceType::DataAnalysis, 0, Some((4.0, 60))));
+        analytics.observe(&transaction(ServiceType::DataAnalysis, 10, Some((6.0, 120))));
+        analytics.observe(&transaction(ServiceType::DataAnalysis, 20, Some((5.0, 300))));
+        let mut expired = transaction(ServiceType::DataAnalysis, 30, None);
+        expired.status = TransactionStatus::Expired;
+        analytics.observe(&expired);
+        analytics.observe(&transaction(ServiceType::DataAnalysis, 40, None));
+        analytics.observe(&transaction(ServiceType::MarketResearch, -7200, Some((1.0, 60))));
+
+        let stats = analytics.stats(&ServiceType::DataAnalysis, at(600));
+        assert_eq!(stats.requests, 5);
+        assert_eq!(stats.fills, 3);
+        assert_eq!(stats.unfilled, 1);
+        assert_eq!(stats.demand_per_hour, 5.0);
+        assert_eq!(stats.fill_rate, Some(0.75));
+        assert_eq!(stats.average_clearing_price, Some(Balance::from_sol(5.0)));
+        assert_eq!(stats.median_time_to_fill_secs, Some(120.0));
+
+        // The research request is outside the window
+        assert_eq!(analytics.all_stats(at(600)).len(), 1);
+        analytics.prune(at(600));
+        assert_eq!(analytics.len(), 5);
+    }
+
+    #[test]
+    fn test_market_conditions_come_from_stats() {
+        let mut analytics = MarketAnalytics::default();
+        let empty = analytics.market_conditions(&ServiceType::DataAnalysis, at(0));
+        assert_eq!(empty.demand_level, 0.0);
+        assert!(empty.risk_indicators.is_empty());
+
+        for i in 0..240 {
+            analytics.observe(&transaction(ServiceType::DataAnalysis, i * 60, Some((2.0, 30))));
+        }
+        let conditions = analytics.market_conditions(&ServiceType::DataAnalysis, at(240 * 60));
+        assert!((conditions.demand_level - 0.5).abs() < 1e-9);
+        assert_eq!(conditions.competition_level, 0.5);
+        assert_eq!(conditions.average_pricing, 2.0);
+        assert_eq!(conditions.risk_indicators[0].value, 0.0);
+    }
+}
@@ -0,0 +1,301 @@
+//! Transaction Archive
+//!
+//! Agents that run for months accumulate finished transactions that every
+//! listing and index rebuild still has to read. Archiving moves finished
+//! transactions (completed, failed, cancelled or expired) older than a
+//! threshold out of live storage into zstd-compressed segment files, then
+//! compacts the live store and reports the space reclaimed.
+//!
+//! Archived transactions stay queryable through a slower path: the archive
+//! keeps an index of the segment holding each transaction, and a lookup
+//! decompresses that segment and scans it.
+//!
+//! A segment is synced to disk and the index saved before anything leaves
+//! live storage, so an interrupted run at worst leaves a transaction in both
+//! places; the next run removes it from live storage without archiving it
+//! twice.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    storage::StorageManager,
+    transaction::{Transaction, TransactionStatus},
+    types::{Timestamp, TransactionId},
+};
+
+const INDEX_FILE: &str = "index.json";
+
+/// Archival settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveConfig {
+    /// Directory holding segments and their index
+    pub archive_dir: PathBuf,
+    /// How long a transaction must have been finished before it is archived
+    pub min_age: Duration,
+    /// Maximum transactions per segment
+    pub segment_size: usize,
+    /// zstd compression level
+    pub compression_level: i32,
+}
+
+impl ArchiveConfig {
+    /// Default settings with the archive inside a node's data directory
+    pub fn for_data_dir(data_dir: &Path) -> Self {
+        Self {
+            archive_dir: data_dir.join("archive"),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            archive_dir: PathBuf::from("./solace_data/archive"),
+            min_age: Duration::from_secs(90 * 24 * 60 * 60),
+            segment_size: 5_000,
+            compression_level: 9,
+        }
+    }
+}
+
+/// One compressed segment file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentInfo {
+    pub file: String,
+    pub transactions: usize,
+    pub raw_bytes: u64,
+    pub compressed_bytes: u64,
+    /// Finish times of the oldest and newest transactions inside
+    pub oldest: Timestamp,
+    pub newest: Timestamp,
+}
+
+/// Segments and the segment each archived transaction is in
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ArchiveIndex {
+    segments: Vec<SegmentInfo>,
+    locations: HashMap<TransactionId, usize>,
+}
+
+/// Outcome of an archival run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveReport {
+    /// Transactions removed from live storage; drop them from in-memory indexes
+    pub archived: Vec<TransactionId>,
+    pub segments_written: usize,
+    pub live_bytes_freed: u64,
+    pub archive_bytes_written: u64,
+}
+
+impl ArchiveReport {
+    /// Live bytes freed net of the archive space they now take
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.live_bytes_freed.saturating_sub(self.archive_bytes_written)
+    }
+}
+
+/// Compressed, append-only store of finished transactions
+pub struct TransactionArchive {
+    config: ArchiveConfig,
+    index: ArchiveIndex,
+}
+
+impl TransactionArchive {
+    /// Open the archive in `config.archive_dir`, creating it if needed
+    pub fn open(config: ArchiveConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.archive_dir)
+            .with_context(|| format!("Failed to create {}", config.archive_dir.display()))?;
+        let index_path = config.archive_dir.join(INDEX_FILE);
+        let index = if index_path.exists() {
+            let json = std::fs::read(&index_path).with_context(|| format!("Failed to read {}", index_path.display()))?;
+            serde_json::from_slice(&json).with_context(|| format!("Corrupt archive index {}", index_path.display()))?
+        } else {
+            ArchiveIndex::default()
+        };
+        Ok(Self { config, index })
+    }
+
+    /// Number of archived transactions
+    pub fn len(&self) -> usize {
+        self.index.locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.locations.is_empty()
+    }
+
+    pub fn contains(&self, id: &TransactionId) -> bool {
+        self.index.locations.contains_key(id)
+    }
+
+    pub fn segments(&self) -> &[SegmentInfo] {
+        &self.index.segments
+    }
+
+    /// Read an archived transaction by decompressing its segment
+    pub fn get(&self, id: &TransactionId) -> Result<Option<Transaction>> {
+        let Some(segment) = self.index.locations.get(id).and_then(|&i| self.index.segments.get(i)) else {
+            return Ok(None);
+        };
+        let path = self.config.archive_dir.join(&segment.file);
+        let file = File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        for line in BufReader::new(zstd::Decoder::new(file)?).lines() {
+            let tx: Transaction = serde_json::from_str(&line?)?;
+            if tx.id == *id {
+                return Ok(Some(tx));
+            }
+        }
+        Ok(None)
+    }
+
+    /// A transaction from live storage, or from the archive if it has moved there
+    pub async fn lookup(&self, storage: &StorageManager, id: &TransactionId) -> Result<Option<Transaction>> {
+        match storage.get_transaction(id).await? {
+            Some(tx) => Ok(Some(tx)),
+            None => self.get(id),
+        }
+    }
+
+    /// Move finished transactions older than `min_age` out of live storage
+    pub async fn archive(&mut self, storage: &StorageManager, now: Timestamp) -> Result<ArchiveReport> {
+        let cutoff = now.0 - chrono::Duration::from_std(self.config.min_age)?;
+        let mut due: Vec<Transaction> = storage
+            .list_transactions::<Transaction>()
+            .await?
+            .into_iter()
+            .filter(|tx| is_finished(tx.status) && tx.updated_at.0 <= cutoff)
+            .collect();
+        due.sort_by_key(|tx| tx.updated_at);
+
+        let mut report = ArchiveReport::default();
+        for chunk in due.chunks(self.config.segment_size.max(1)) {
+            let fresh: Vec<&Transaction> = chunk.iter().filter(|tx| !self.contains(&tx.id)).collect();
+            if !fresh.is_empty() {
+                report.archive_bytes_written += self.write_segment(&fresh)?.compressed_bytes;
+                report.segments_written += 1;
+            }
+            for tx in chunk {
+                report.live_bytes_freed += serde_json::to_vec(tx)?.len() as u64;
+                storage.delete_transaction(&tx.id).await?;
+                report.archived.push(tx.id);
+            }
+        }
+
+        if !report.archived.is_empty() {
+            storage.maintenance().await?;
+            info!(
+                "Archived {} transactions into {} segments, reclaiming {} bytes",
+                report.archived.len(),
+                report.segments_written,
+                report.bytes_reclaimed()
+            );
+        }
+        Ok(report)
+    }
+
+    fn write_segment(&mut self, transactions: &[&Transaction]) -> Result<SegmentInfo> {
+        let number = self.index.segments.len();
+        let file = format!("segment-{:06}.jsonl.zst", number);
+
+        let mut raw = Vec::new();
+        for tx in transactions {
+            serde_json::to_writer(&mut raw, tx)?;
+            raw.push(b'\n');
+        }
+        let compressed = zstd::encode_all(raw.as_slice(), self.config.compression_level)?;
+        let path = self.config.archive_dir.join(&file);
+        let mut out = File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        out.write_all(&compressed)?;
+        out.sync_all()?;
+
+        let segment = SegmentInfo {
+            file,
+            transactions: transactions.len(),
+            raw_bytes: raw.len() as u64,
+            compressed_bytes: compressed.len() as u64,
+            oldest: transactions.iter().map(|tx| tx.updated_at).min().unwrap_or_else(Timestamp::now),
+            newest: transactions.iter().map(|tx| tx.updated_at).max().unwrap_or_else(Timestamp::now),
+        };
+        self.index.segments.push(segment.clone());
+        self.index.locations.extend(transactions.iter().map(|tx| (tx.id, number)));
+        self.save_index()?;
+        Ok(segment)
+    }
+
+    /// Replace the index file atomically
+    fn save_index(&self) -> Result<()> {
+        let path = self.config.archive_dir.join(INDEX_FILE);
+        let staging = path.with_extension("json.tmp");
+        let mut out = File::create(&staging)?;
+        out.write_all(&serde_json::to_vec(&self.index)?)?;
+        out.sync_all()?;
+        std::fs::rename(&staging, &path)?;
+        Ok(())
+    }
+}
+
+/// Whether a transaction has reached a final status
+fn is_finished(status: TransactionStatus) -> bool {
+    matches!(
+        status,
+        TransactionStatus::Completed | TransactionStatus::Failed | TransactionStatus::Cancelled | TransactionStatus::Expired
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionRequest;
+    use crate::types::{AgentId, Balance, ServiceType};
+
+    fn transaction(status: TransactionStatus, age_days: i64) -> Transaction {
+        let request = TransactionRequest::new(
+            AgentId::new(),
+            ServiceType::DataAnalysis,
+            "Quarterly sales analysis".to_string(),
+            Balance::from_sol(1.0),
+            Timestamp::now(),
+        );
+        let mut tx = Transaction::new(request);
+        tx.status = status;
+        tx.updated_at = Timestamp(chrono::Utc::now() - chrono::Duration::days(age_days));
+        tx
+    }
+
+    #[tokio::test]
+    async fn test_archive_moves_old_finished_transactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = StorageManager::memory();
+        let old_done = transaction(TransactionStatus::Completed, 200);
+        let old_failed = transaction(TransactionStatus::Failed, 120);
+        let old_open = transaction(TransactionStatus::InProgress, 200);
+        let recent_done = transaction(TransactionStatus::Completed, 1);
+        for tx in [&old_done, &old_failed, &old_open, &recent_done] {
+            storage.store_transaction(&tx.id, tx).await.unwrap();
+        }
+
+        let config = ArchiveConfig { segment_size: 1, ..ArchiveConfig::for_data_dir(dir.path()) };
+        let mut archive = TransactionArchive::open(config.clone()).unwrap();
+        let report = archive.archive(&storage, Timestamp::now()).await.unwrap();
+        assert_eq!(report.archived, vec![old_done.id, old_failed.id]);
+        assert_eq!(report.segments_written, 2);
+        assert!(report.live_bytes_freed > 0);
+        assert_eq!(storage.list_transaction_ids().await.unwrap().len(), 2);
+
+        // Still reachable after reopening, through the slow path
+        let archive = TransactionArchive::open(config).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert_eq!(archive.lookup(&storage, &old_failed.id).await.unwrap().unwrap().status, TransactionStatus::Failed);
+        assert!(archive.lookup(&storage, &recent_done.id).await.unwrap().is_some());
+        assert!(archive.get(&old_open.id).unwrap().is_none());
+    }
+}
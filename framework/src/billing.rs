@@ -0,0 +1,209 @@
+//! Billing Statements
+//!
+//! A `Statement` totals an agent's settled transactions over a period: what
+//! it spent as requester and earned as provider, line by line, and grouped
+//! by one tag dimension (project, cost center, environment, or free-form
+//! label) for cost-center reporting. Transactions with no value in the
+//! dimension are grouped under `UNTAGGED`; one with several labels counts
+//! toward each label's group, so label groups may sum to more than the total.
+//!
+//! `volume_by` groups the same way without taking either party's side, for
+//! reports over every transaction a node has seen.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    transaction::{TagDimension, Transaction, TransactionStatus, TransactionTags},
+    types::{AgentId, Balance, ServiceType, Timestamp, TransactionId},
+};
+
+/// Group of transactions without a value in the statement's dimension
+pub const UNTAGGED: &str = "(untagged)";
+
+/// Which side of a transaction the agent was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Spent,                                // As requester
+    Earned,                               // As provider
+}
+
+/// One settled transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementLine {
+    pub transaction_id: TransactionId,
+    pub service_type: ServiceType,
+    pub counterparty: Option<AgentId>,
+    pub direction: Direction,
+    pub amount: Balance,
+    pub settled_at: Timestamp,
+    pub tags: TransactionTags,
+}
+
+/// Totals of one group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupTotal {
+    pub transactions: usize,
+    pub spent: Balance,
+    pub earned: Balance,
+}
+
+impl Default for GroupTotal {
+    fn default() -> Self {
+        Self { transactions: 0, spent: Balance::new(0), earned: Balance::new(0) }
+    }
+}
+
+impl GroupTotal {
+    fn add(&mut self, line: &StatementLine) {
+        self.transactions += 1;
+        let total = match line.direction {
+            Direction::Spent => &mut self.spent,
+            Direction::Earned => &mut self.earned,
+        };
+        *total = Balance(total.0.saturating_add(line.amount.0));
+    }
+}
+
+/// An agent's settled transactions over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Statement {
+    pub agent: AgentId,
+    pub period_start: Timestamp,
+    pub period_end: Timestamp,
+    pub group_by: TagDimension,
+    pub lines: Vec<StatementLine>,        // Oldest first
+    pub groups: BTreeMap<String, GroupTotal>,
+    pub total: GroupTotal,
+}
+
+impl Statement {
+    /// Statement of the completed transactions `agent` took part in that
+    /// settled within the period
+    pub fn build<'a>(
+        agent: AgentId,
+        transactions: impl IntoIterator<Item = &'a Transaction>,
+        period_start: Timestamp,
+        period_end: Timestamp,
+        group_by: TagDimension,
+    ) -> Self {
+        let mut lines: Vec<StatementLine> = transactions
+            .into_iter()
+            .filter_map(|tx| Self::line(agent, tx))
+            .filter(|line| line.settled_at >= period_start && line.settled_at <= period_end)
+            .collect();
+        lines.sort_by_key(|line| line.settled_at);
+
+        let mut groups: BTreeMap<String, GroupTotal> = BTreeMap::new();
+        let mut total = GroupTotal::default();
+        for line in &lines {
+            total.add(line);
+            for group in groups_of(group_by, &line.tags) {
+                groups.entry(group).or_default().add(line);
+            }
+        }
+
+        Self { agent, period_start, period_end, group_by, lines, groups, total }
+    }
+
+    fn line(agent: AgentId, tx: &Transaction) -> Option<StatementLine> {
+        if tx.status != TransactionStatus::Completed {
+            return None;
+        }
+        let amount = tx.agreed_price?;
+        let (direction, counterparty) = if tx.request.requester == agent {
+            (Direction::Spent, tx.provider)
+        } else if tx.provider == Some(agent) {
+            (Direction::Earned, Some(tx.request.requester))
+        } else {
+            return None;
+        };
+        Some(StatementLine {
+            transaction_id: tx.id,
+            service_type: tx.request.service_type.clone(),
+            counterparty,
+            direction,
+            amount,
+            settled_at: tx.execution_data.as_ref().map_or(tx.updated_at, |data| data.completion_time),
+            tags: tx.request.tags.clone(),
+        })
+    }
+}
+
+/// Count and agreed value of the transactions in one group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Volume {
+    pub transactions: usize,
+    pub agreed: Balance,                  // Transactions without an agreed price add nothing
+}
+
+/// Transactions grouped by a tag dimension, with their agreed value
+pub fn volume_by<'a>(group_by: TagDimension, transactions: impl IntoIterator<Item = &'a Transaction>) -> BTreeMap<String, Volume> {
+    let mut volumes: BTreeMap<String, Volume> = BTreeMap::new();
+    for tx in transactions {
+        for group in groups_of(group_by, &tx.request.tags) {
+            let volume = volumes.entry(group).or_insert(Volume { transactions: 0, agreed: Balance::new(0) });
+            volume.transactions += 1;
+            volume.agreed = Balance(volume.agreed.0.saturating_add(tx.agreed_price.map_or(0, |price| price.0)));
+        }
+    }
+    volumes
+}
+
+/// Groups tagged transactions fall in, `UNTAGGED` if none
+fn groups_of(group_by: TagDimension, tags: &TransactionTags) -> Vec<String> {
+    let values = group_by.values(tags);
+    if values.is_empty() {
+        vec![UNTAGGED.to_string()]
+    } else {
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{ExecutionData, TransactionRequest};
+    use std::collections::HashMap;
+
+    fn settled(requester: AgentId, provider: AgentId, price: f64, tags: TransactionTags) -> Transaction {
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let request = TransactionRequest::new(requester, ServiceType::DataAnalysis, "Analysis".to_string(), Balance::from_sol(10.0), deadline).with_tags(tags);
+        let mut tx = Transaction::new(request);
+        tx.provider = Some(provider);
+        tx.agreed_price = Some(Balance::from_sol(price));
+        tx.execution_data = Some(ExecutionData {
+            result: String::new(),
+            artifacts: Vec::new(),
+            completion_time: Timestamp::now(),
+            quality_metrics: HashMap::new(),
+        });
+        tx.status = TransactionStatus::Completed;
+        tx
+    }
+
+    #[test]
+    fn test_statement_groups_by_cost_center() {
+        let (agent, other) = (AgentId::new(), AgentId::new());
+        let research = TransactionTags { cost_center: Some("research".to_string()), ..TransactionTags::default() };
+        let transactions = vec![
+            settled(agent, other, 2.0, research.clone()),
+            settled(agent, other, 3.0, research),
+            settled(other, agent, 4.0, TransactionTags::default()),
+            settled(other, AgentId::new(), 9.0, TransactionTags::default()),
+        ];
+
+        let start = Timestamp(chrono::Utc::now() - chrono::Duration::hours(1));
+        let end = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let statement = Statement::build(agent, &transactions, start, end, TagDimension::CostCenter);
+        assert_eq!(statement.lines.len(), 3);
+        assert_eq!(statement.groups["research"].spent, Balance::from_sol(5.0));
+        assert_eq!(statement.groups[UNTAGGED].earned, Balance::from_sol(4.0));
+        assert_eq!(statement.total.transactions, 3);
+        assert_eq!(statement.lines[0].tags, transactions[0].result().tags);
+
+        let volumes = volume_by(TagDimension::CostCenter, &transactions);
+        assert_eq!(volumes["research"].transactions, 2);
+        assert_eq!(volumes[UNTAGGED].agreed, Balance::from_sol(13.0));
+    }
+}
@@ -2,20 +2,77 @@
 //!
 //! Provides comprehensive integration with the Solana blockchain,
 //! including transaction submission, account management, and smart contract interaction.
+//!
+//! RPC calls go through the nonblocking client and never block the runtime.
+//! Calls failing on the network or on an unhealthy node are retried with
+//! exponential backoff up to `max_retries` times; resending a signed
+//! transaction is safe, as the cluster deduplicates it by signature. A sent
+//! transaction that has not reached its commitment level within
+//! `confirmation_timeout` fails with `TransactionError::Timeout`.
+//!
+//! A client given a token with `with_cancellation` stops retrying and
+//! waiting for confirmations when it fires, failing with
+//! `SolaceError::Cancelled`. A transaction already sent may still land, so
+//! the error names its signature for the caller to check before resending.
+//!
+//! Each operation waits for the commitment level its class needs: progress
+//! polling reads `Processed` state, escrow locks wait for `Confirmed`, and
+//! settlement and reputation anchoring wait for `Finalized`. The
+//! transaction lifecycle helpers only advance a transaction's phase, through
+//! its `TransactionManager`, once its on-chain step has reached that level.
+//!
+//! With a `FeeSponsor`, transactions of sponsored agents name the configured
+//! fee payer as fee payer, within each agent's sponsorship quota.
+//!
+//! Watch-only agents have no keypair in process. `build_transfer` and
+//! `build_instruction_tx` prepare their payments and instructions as
+//! `UnsignedTransaction`s for an external signer, and `submit_signed` sends
+//! them once its signature comes back. Built on a durable nonce, they wait
+//! for an offline signer without expiring with the blockhash.
+//!
+//! `SolanaEscrowLedger` backs `Escrow` with the program's record accounts:
+//! the lock creates the transaction record, milestone releases pay out part
+//! of it, and release and refund finalize what remains. Given the treasury's
+//! SPL multisig account, escrows under a release policy name it as release
+//! authority when locked, and each payout carries the collected approvals
+//! as ed25519 program instructions the Solace program checks against it.
+//!
+//! Escrows paid in an SPL token lock into a vault: the associated token
+//! account of a program-derived authority for the transaction. Token
+//! transfers create the recipient's associated token account if it does not
+//! exist yet, and pre-flight checks the payer's token balance as well as
+//! the lamports for fees and rent.
+//!
+//! A `MultisigWallet` treasury holds tokens through an SPL multisig
+//! account over the same signer keys, so token accounts it owns move funds
+//! only with the wallet's threshold of signatures.
+//!
+//! Instructions are encoded and addressed by `program::SolaceProgram`.
 
 use std::str::FromStr;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::future::Future;
 use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn, debug, error};
 
-use solana_client::rpc_client::RpcClient;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::RpcClientConfig;
 use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_client::rpc_request::RpcError;
+use solana_rpc_client::http_sender::HttpSender;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use spl_associated_token_account::{get_associated_token_address, instruction::create_associated_token_account_idempotent};
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     instruction::{AccountMeta, Instruction},
+    nonce::State as NonceState,
     message::Message,
+    program_pack::Pack,
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     signer::Signer,
@@ -25,20 +82,52 @@ use solana_sdk::{
 
 use crate::{
     AgentId, TransactionId, Balance, 
-    error::SolaceError,
-    types::Hash,
+    error::{ChainError, SolaceError, TransactionError},
+    escrow::{Escrow, EscrowLedger},
+    transaction::TransactionEvaluation,
+    transaction_manager::{TransactionManager, Transition},
+    failure::{FailureAnalyzer, FailureDiagnosis},
+    preflight::FundingRequirement,
+    program::{SolaceInstruction, SolaceProgram},
+    cost::SponsoredFees,
+    crypto::{MultisigWallet, PartialSignature},
+    sponsorship::{FeeSponsor, SponsorshipPolicy},
+    signing::{DurableNonce, UnsignedTransaction},
+    types::{Hash, Payment, PaymentAsset},
 };
 
+/// Space the program allocates for a transaction's escrow record
+pub const ESCROW_ACCOUNT_SPACE: usize = 256;
+
+/// First wait before retrying a failed RPC call; doubles with each retry
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Longest wait between RPC retries
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(8);
+
+/// How often a sent transaction's status is checked while confirming
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Space of an SPL token account
+pub const TOKEN_ACCOUNT_SPACE: usize = spl_token::state::Account::LEN;
+
+/// Size of an SPL multisig account
+pub const MULTISIG_ACCOUNT_SPACE: usize = spl_token::state::Multisig::LEN;
+
 /// Blockchain configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockchainConfig {
     /// Solana RPC endpoint URL
     pub rpc_url: String,
-    /// Commitment level for transactions
+    /// Commitment level for operations without a class-specific level
     pub commitment: CommitmentLevel,
-    /// Transaction confirmation timeout
+    /// Commitment level per operation class
+    #[serde(default)]
+    pub operation_commitments: CommitmentPolicy,
+    /// How long a sent transaction may take to reach its commitment level
     pub confirmation_timeout: Duration,
-    /// Maximum retry attempts for failed transactions
+    /// Retries of an RPC call that failed transiently (network errors,
+    /// unhealthy node), with exponential backoff
     pub max_retries: u32,
     /// Fee payer keypair path (optional)
     pub fee_payer_path: Option<String>,
@@ -46,6 +135,10 @@ pub struct BlockchainConfig {
     pub program_id: String,
     /// Skip preflight checks
     pub skip_preflight: bool,
+    /// Proxy for RPC requests, as a URL: `socks5h://host:port` has the proxy
+    /// resolve hostnames (as Tor requires), `http://host:port` tunnels with CONNECT
+    #[serde(default)]
+    pub rpc_proxy: Option<String>,
 }
 
 impl Default for BlockchainConfig {
@@ -53,23 +146,76 @@ impl Default for BlockchainConfig {
         Self {
             rpc_url: "https://api.devnet.solana.com".to_string(),
             commitment: CommitmentLevel::Confirmed,
+            operation_commitments: CommitmentPolicy::default(),
             confirmation_timeout: Duration::from_secs(60),
             max_retries: 3,
             fee_payer_path: None,
             program_id: "SoLaCeProgram1111111111111111111111111111111".to_string(),
             skip_preflight: false,
+            rpc_proxy: None,
         }
     }
 }
 
-/// Commitment levels for transaction confirmation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl BlockchainConfig {
+    /// Commitment level operations of `class` wait for
+    pub fn commitment_for(&self, class: OperationClass) -> CommitmentLevel {
+        self.operation_commitments.level(class).unwrap_or(self.commitment)
+    }
+}
+
+/// Commitment levels for transaction confirmation, weakest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum CommitmentLevel {
     Processed,
     Confirmed,
     Finalized,
 }
 
+/// Kinds of chain operation, each with its own commitment requirement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OperationClass {
+    ProgressPolling,
+    EscrowLock,
+    Settlement,
+    ReputationAnchor,
+    General,                              // Staking, governance and other instructions
+}
+
+/// Commitment level per operation class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitmentPolicy {
+    pub progress_polling: CommitmentLevel,
+    pub escrow_lock: CommitmentLevel,
+    pub settlement: CommitmentLevel,
+    pub reputation_anchor: CommitmentLevel,
+}
+
+impl Default for CommitmentPolicy {
+    fn default() -> Self {
+        Self {
+            progress_polling: CommitmentLevel::Processed,
+            escrow_lock: CommitmentLevel::Confirmed,
+            settlement: CommitmentLevel::Finalized,
+            reputation_anchor: CommitmentLevel::Finalized,
+        }
+    }
+}
+
+impl CommitmentPolicy {
+    /// Level for `class`; `None` for `General`, which uses the config-wide level
+    pub fn level(&self, class: OperationClass) -> Option<CommitmentLevel> {
+        match class {
+            OperationClass::ProgressPolling => Some(self.progress_polling),
+            OperationClass::EscrowLock => Some(self.escrow_lock),
+            OperationClass::Settlement => Some(self.settlement),
+            OperationClass::ReputationAnchor => Some(self.reputation_anchor),
+            OperationClass::General => None,
+        }
+    }
+}
+
 impl From<CommitmentLevel> for CommitmentConfig {
     fn from(level: CommitmentLevel) -> Self {
         match level {
@@ -100,6 +246,9 @@ pub struct BlockchainTransactionResult {
     pub confirmation_status: ConfirmationStatus,
     pub fee: u64,
     pub error: Option<String>,
+    /// Decoded cause and remediation when the transaction failed
+    #[serde(default)]
+    pub failure: Option<FailureDiagnosis>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,36 +259,26 @@ pub enum ConfirmationStatus {
     Failed,
 }
 
-/// Smart contract instruction types
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum SolaceInstruction {
-    InitializeAgent {
-        agent_id: AgentId,
-        initial_reputation: u32,
-    },
-    CreateTransaction {
-        transaction_id: TransactionId,
-        amount: u64,
-        recipient: Pubkey,
-    },
-    UpdateReputation {
-        agent_id: AgentId,
-        new_reputation: u32,
-    },
-    FinalizeTransaction {
-        transaction_id: TransactionId,
-        success: bool,
-    },
-    Stake {
-        amount: u64,
-    },
-    Unstake {
-        amount: u64,
-    },
-    Vote {
-        proposal_id: String,
-        vote: bool,
-    },
+impl From<CommitmentLevel> for ConfirmationStatus {
+    fn from(level: CommitmentLevel) -> Self {
+        match level {
+            CommitmentLevel::Processed => ConfirmationStatus::Processed,
+            CommitmentLevel::Confirmed => ConfirmationStatus::Confirmed,
+            CommitmentLevel::Finalized => ConfirmationStatus::Finalized,
+        }
+    }
+}
+
+impl SolaceInstruction {
+    /// Commitment class the instruction is confirmed under
+    pub fn operation_class(&self) -> OperationClass {
+        match self {
+            SolaceInstruction::CreateTransaction { .. } | SolaceInstruction::CreateTokenTransaction { .. } => OperationClass::EscrowLock,
+            SolaceInstruction::FinalizeTransaction { .. } | SolaceInstruction::ReleasePartial { .. } => OperationClass::Settlement,
+            SolaceInstruction::InitializeAgent { .. } | SolaceInstruction::UpdateReputation { .. } => OperationClass::ReputationAnchor,
+            SolaceInstruction::Stake { .. } | SolaceInstruction::Unstake { .. } | SolaceInstruction::Vote { .. } => OperationClass::General,
+        }
+    }
 }
 
 /// Blockchain client for Solana interaction
@@ -148,15 +287,34 @@ pub struct SolanaClient {
     config: BlockchainConfig,
     program_id: Pubkey,
     fee_payer: Option<Keypair>,
+    sponsor: Option<FeeSponsor>,          // Pays fees from `fee_payer` for covered agents
+    analyzer: FailureAnalyzer,
+    cancel: CancellationToken,            // Stops RPC retries and confirmation waits
 }
 
 impl SolanaClient {
     /// Create a new Solana client
     pub fn new(config: BlockchainConfig) -> Result<Self> {
-        let client = RpcClient::new_with_commitment(
-            config.rpc_url.clone(),
-            config.commitment.clone().into(),
-        );
+        let rpc_config = RpcClientConfig {
+            commitment_config: config.commitment.into(),
+            confirm_transaction_initial_timeout: Some(config.confirmation_timeout),
+        };
+        let client = match &config.rpc_proxy {
+            Some(proxy) => {
+                let proxy = reqwest::Proxy::all(proxy)
+                    .map_err(|e| SolaceError::config(format!("Invalid RPC proxy {}: {}", proxy, e)))?;
+                let http = reqwest::Client::builder()
+                    .proxy(proxy)
+                    .timeout(Duration::from_secs(30))  // As HttpSender::new
+                    .build()
+                    .map_err(|e| SolaceError::config(format!("Failed to build proxied RPC client: {}", e)))?;
+                RpcClient::new_sender(
+                    HttpSender::new_with_client(config.rpc_url.clone(), http),
+                    rpc_config,
+                )
+            }
+            None => RpcClient::new_sender(HttpSender::new(config.rpc_url.clone()), rpc_config),
+        };
 
         let program_id = Pubkey::from_str(&config.program_id)
             .map_err(|e| SolaceError::InvalidPubkey(e.to_string()))?;
@@ -170,14 +328,48 @@ impl SolanaClient {
         Ok(Self {
             client,
             config,
+            analyzer: FailureAnalyzer::new(&program_id),
             program_id,
             fee_payer,
+            sponsor: None,
+            cancel: CancellationToken::new(),
         })
     }
 
+    /// Stop RPC retries and confirmation waits when `cancel` fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Pay fees for the agents `policy` covers from the configured fee payer
+    pub fn with_sponsorship(mut self, policy: SponsorshipPolicy) -> Result<Self> {
+        if self.fee_payer.is_none() {
+            return Err(SolaceError::config("Fee sponsorship needs a fee_payer_path").into());
+        }
+        self.sponsor = Some(FeeSponsor::new(policy));
+        Ok(self)
+    }
+
+    /// Fees paid on behalf of sponsored agents, if sponsorship is enabled
+    pub fn sponsored_fees(&self) -> Option<SponsoredFees> {
+        self.sponsor.as_ref().map(FeeSponsor::ledger)
+    }
+
+    /// Keypair paying fees for `agent`'s transactions, if it is sponsored
+    fn sponsor_for(&self, agent: &Pubkey) -> Option<&Keypair> {
+        let sponsor = self.sponsor.as_ref()?;
+        self.fee_payer.as_ref().filter(|_| sponsor.covers(&agent.to_string()))
+    }
+
+    /// Account paying the fee for transactions signed by `signer`
+    fn fee_payer_pubkey(&self, signer: &Pubkey) -> Pubkey {
+        self.sponsor_for(signer).map(Signer::pubkey).unwrap_or(*signer)
+    }
+
     /// Get account information
     pub async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<AccountInfo>> {
-        match self.client.get_account(pubkey) {
+        match self.rpc(|| self.client.get_account(pubkey)).await {
             Ok(account) => Ok(Some(AccountInfo {
                 pubkey: *pubkey,
                 lamports: account.lamports,
@@ -198,8 +390,7 @@ impl SolanaClient {
 
     /// Get account balance in lamports
     pub async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
-        self.client
-            .get_balance(pubkey)
+        self.rpc(|| self.client.get_balance(pubkey)).await
             .map_err(|e| SolaceError::BlockchainError(e.to_string()).into())
     }
 
@@ -210,7 +401,7 @@ impl SolanaClient {
         to_pubkey: &Pubkey,
         amount_lamports: u64,
     ) -> Result<BlockchainTransactionResult> {
-        let recent_blockhash = self.client.get_latest_blockhash()
+        let recent_blockhash = self.rpc(|| self.client.get_latest_blockhash()).await
             .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
 
         let transfer_instruction = system_instruction::transfer(
@@ -219,11 +410,176 @@ impl SolanaClient {
             amount_lamports,
         );
 
-        let message = Message::new(&[transfer_instruction], Some(&from_keypair.pubkey()));
+        let fee_payer = self.fee_payer_pubkey(&from_keypair.pubkey());
+        let message = Message::new_with_blockhash(&[transfer_instruction], Some(&fee_payer), &recent_blockhash);
+        self.preflight(&from_keypair.pubkey(), &message, amount_lamports, &[]).await?;
+
+        self.sign_and_send(message, recent_blockhash, from_keypair, OperationClass::Settlement).await
+    }
+
+    /// Associated token account of `owner` for `mint`
+    pub fn token_account(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address(owner, mint)
+    }
+
+    /// The Solace program this client submits to
+    pub fn program(&self) -> SolaceProgram {
+        SolaceProgram::new(self.program_id)
+    }
+
+    /// Program-derived authority over a transaction's token vault
+    pub fn escrow_authority(&self, transaction_id: TransactionId) -> Pubkey {
+        self.program().escrow_authority(&transaction_id).0
+    }
+
+    /// Token account holding a transaction's escrowed tokens of `mint`
+    pub fn escrow_vault(&self, transaction_id: TransactionId, mint: &Pubkey) -> Pubkey {
+        Self::token_account(&self.escrow_authority(transaction_id), mint)
+    }
+
+    /// Token balance of `owner` in base units of `mint`; 0 without a token account
+    pub async fn get_token_balance(&self, owner: &Pubkey, mint: &Pubkey) -> Result<u64> {
+        let account = Self::token_account(owner, mint);
+        match self.rpc(|| self.client.get_token_account_balance(&account)).await {
+            Ok(balance) => balance.amount.parse()
+                .map_err(|e| SolaceError::BlockchainError(format!("token balance of {}: {}", account, e)).into()),
+            Err(e) if e.to_string().contains("could not find account") || e.to_string().contains("AccountNotFound") => Ok(0),
+            Err(e) => Err(SolaceError::BlockchainError(e.to_string()).into()),
+        }
+    }
+
+    /// Balance of `owner` in `asset`'s base units
+    pub async fn get_asset_balance(&self, owner: &Pubkey, asset: &PaymentAsset) -> Result<u64> {
+        match asset {
+            PaymentAsset::Sol => self.get_balance(owner).await,
+            PaymentAsset::Spl { mint, .. } => self.get_token_balance(owner, mint).await,
+        }
+    }
+
+    /// Create `owner`'s associated token account for `mint`, paid by `payer`;
+    /// succeeds without change if it already exists
+    pub async fn create_token_account(
+        &self,
+        payer: &Keypair,
+        owner: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<BlockchainTransactionResult> {
+        let recent_blockhash = self.rpc(|| self.client.get_latest_blockhash()).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        let instruction = create_associated_token_account_idempotent(&payer.pubkey(), owner, mint, &spl_token::id());
+
+        let fee_payer = self.fee_payer_pubkey(&payer.pubkey());
+        let message = Message::new_with_blockhash(&[instruction], Some(&fee_payer), &recent_blockhash);
+        self.preflight(&payer.pubkey(), &message, 0, &[TOKEN_ACCOUNT_SPACE]).await?;
+
+        self.sign_and_send(message, recent_blockhash, payer, OperationClass::General).await
+    }
+
+    /// Create the SPL multisig account through which `wallet` owns token accounts
+    pub async fn create_token_multisig(
+        &self,
+        payer: &Keypair,
+        multisig_account: &Keypair,
+        wallet: &MultisigWallet,
+    ) -> Result<BlockchainTransactionResult> {
+        if wallet.signers.len() > spl_token::instruction::MAX_SIGNERS {
+            return Err(SolaceError::config(format!(
+                "SPL multisig accounts hold at most {} signers", spl_token::instruction::MAX_SIGNERS
+            )).into());
+        }
+        let signers: Vec<Pubkey> = wallet.signers.iter().map(|key| Pubkey::new_from_array(*key)).collect();
+        let rent = self.rpc(|| self.client.get_minimum_balance_for_rent_exemption(MULTISIG_ACCOUNT_SPACE)).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        let recent_blockhash = self.rpc(|| self.client.get_latest_blockhash()).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+
+        let instructions = [
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &multisig_account.pubkey(),
+                rent,
+                MULTISIG_ACCOUNT_SPACE as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_multisig2(
+                &spl_token::id(),
+                &multisig_account.pubkey(),
+                &signers.iter().collect::<Vec<_>>(),
+                wallet.threshold as u8,
+            )?,
+        ];
+        let message = Message::new_with_blockhash(&instructions, Some(&payer.pubkey()), &recent_blockhash);
+        self.preflight(&payer.pubkey(), &message, 0, &[MULTISIG_ACCOUNT_SPACE]).await?;
+
         let mut transaction = Transaction::new_unsigned(message);
-        transaction.sign(&[from_keypair], recent_blockhash);
+        transaction.sign(&[payer, multisig_account], recent_blockhash);
+        self.send_signed(transaction, &payer.pubkey(), OperationClass::General).await
+    }
 
-        self.send_transaction_with_confirmation(transaction).await
+    /// Send SPL tokens, creating the recipient's token account if needed
+    pub async fn transfer_token(
+        &self,
+        from_keypair: &Keypair,
+        to_owner: &Pubkey,
+        mint: &Pubkey,
+        decimals: u8,
+        amount: u64,
+    ) -> Result<BlockchainTransactionResult> {
+        self.check_token_funds(&from_keypair.pubkey(), mint, amount).await?;
+        let recent_blockhash = self.rpc(|| self.client.get_latest_blockhash()).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+
+        let from = from_keypair.pubkey();
+        let destination = Self::token_account(to_owner, mint);
+        let instructions = [
+            create_associated_token_account_idempotent(&from, to_owner, mint, &spl_token::id()),
+            spl_token::instruction::transfer_checked(
+                &spl_token::id(),
+                &Self::token_account(&from, mint),
+                mint,
+                &destination,
+                &from,
+                &[],
+                amount,
+                decimals,
+            )?,
+        ];
+
+        let new_accounts: &[usize] = if self.get_account(&destination).await?.is_some() { &[] } else { &[TOKEN_ACCOUNT_SPACE] };
+        let fee_payer = self.fee_payer_pubkey(&from);
+        let message = Message::new_with_blockhash(&instructions, Some(&fee_payer), &recent_blockhash);
+        self.preflight(&from, &message, 0, new_accounts).await?;
+
+        self.sign_and_send(message, recent_blockhash, from_keypair, OperationClass::Settlement).await
+    }
+
+    /// Send `payment` in its asset
+    pub async fn pay(
+        &self,
+        from_keypair: &Keypair,
+        to: &Pubkey,
+        payment: Payment,
+    ) -> Result<BlockchainTransactionResult> {
+        match payment.asset {
+            PaymentAsset::Sol => self.transfer(from_keypair, to, payment.amount).await,
+            PaymentAsset::Spl { mint, decimals } => self.transfer_token(from_keypair, to, &mint, decimals, payment.amount).await,
+        }
+    }
+
+    /// Check that `owner` holds `amount` of `mint` before tokens are moved
+    async fn check_token_funds(&self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Result<()> {
+        let account = Self::token_account(owner, mint);
+        if self.get_account(&account).await?.is_none() {
+            return Err(SolaceError::from(ChainError::MissingTokenAccount { account: account.to_string() }).into());
+        }
+        let balance = self.get_token_balance(owner, mint).await?;
+        if balance < amount {
+            warn!("Pre-flight rejected token payment from {}: has {}, needs {}", owner, balance, amount);
+            return Err(SolaceError::from(ChainError::InsufficientFunds {
+                detail: format!("{} holds {} of mint {}, needs {}", owner, balance, mint, amount),
+            }).into());
+        }
+        Ok(())
     }
 
     /// Submit a Solace protocol instruction
@@ -233,28 +589,267 @@ impl SolanaClient {
         signer: &Keypair,
         additional_accounts: Vec<AccountMeta>,
     ) -> Result<BlockchainTransactionResult> {
-        let instruction_data = self.serialize_instruction(&instruction)?;
-        
-        let mut accounts = vec![
-            AccountMeta::new(signer.pubkey(), true),
-            AccountMeta::new_readonly(self.program_id, false),
-        ];
-        accounts.extend(additional_accounts);
+        let (message, recent_blockhash) = self.instruction_message(&instruction, &signer.pubkey(), additional_accounts)?;
+
+        self.sign_and_send(message, recent_blockhash, signer, instruction.operation_class()).await
+    }
 
-        let solana_instruction = Instruction {
-            program_id: self.program_id,
-            accounts,
-            data: instruction_data,
+    /// Submit a Solace protocol instruction after the ed25519 checks of the
+    /// approvals it needs, which the program reads from the instructions sysvar
+    pub async fn submit_approved_instruction(
+        &self,
+        instruction: SolaceInstruction,
+        signer: &Keypair,
+        mut additional_accounts: Vec<AccountMeta>,
+        approvals: Vec<Instruction>,
+    ) -> Result<BlockchainTransactionResult> {
+        additional_accounts.push(AccountMeta::new_readonly(solana_sdk::sysvar::instructions::id(), false));
+        let mut instructions = approvals;
+        instructions.push(self.program().instruction(&instruction, &signer.pubkey(), additional_accounts)?);
+
+        let recent_blockhash = self.rpc(|| self.client.get_latest_blockhash()).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        let message = Message::new_with_blockhash(&instructions, Some(&self.fee_payer_pubkey(&signer.pubkey())), &recent_blockhash);
+
+        self.sign_and_send(message, recent_blockhash, signer, instruction.operation_class()).await
+    }
+
+    /// Submit an instruction that moves `amount` lamports out of the signer,
+    /// after checking the signer can cover it
+    pub async fn submit_payment(
+        &self,
+        instruction: SolaceInstruction,
+        signer: &Keypair,
+        additional_accounts: Vec<AccountMeta>,
+        amount: u64,
+        new_account_space: &[usize],
+    ) -> Result<BlockchainTransactionResult> {
+        let (message, recent_blockhash) = self.instruction_message(&instruction, &signer.pubkey(), additional_accounts)?;
+        self.preflight(&signer.pubkey(), &message, amount, new_account_space).await?;
+
+        self.sign_and_send(message, recent_blockhash, signer, instruction.operation_class()).await
+    }
+
+    /// Prepare an instruction for an external signer, after the same
+    /// pre-flight as `submit_payment`. A sponsor paying the fee signs first.
+    /// With a durable nonce, the transaction waits on the signer for as long
+    /// as the nonce is not advanced.
+    pub async fn build_instruction_tx(
+        &self,
+        instruction: SolaceInstruction,
+        signer: &Pubkey,
+        additional_accounts: Vec<AccountMeta>,
+        amount: u64,
+        new_account_space: &[usize],
+        nonce: Option<&DurableNonce>,
+    ) -> Result<UnsignedTransaction> {
+        let description = format!("{:?} signed by {}", instruction, signer);
+        let instruction = self.program().instruction(&instruction, signer, additional_accounts)?;
+        self.build_unsigned(&[instruction], signer, amount, new_account_space, nonce, description).await
+    }
+
+    /// SOL transfer exported for an external signer, as `build_instruction_tx`
+    pub async fn build_transfer(
+        &self,
+        from: &Pubkey,
+        to: &Pubkey,
+        amount_lamports: u64,
+        nonce: Option<&DurableNonce>,
+    ) -> Result<UnsignedTransaction> {
+        let instruction = system_instruction::transfer(from, to, amount_lamports);
+        let description = format!("Transfer of {} lamports from {} to {}", amount_lamports, from, to);
+        self.build_unsigned(&[instruction], from, amount_lamports, &[], nonce, description).await
+    }
+
+    async fn build_unsigned(
+        &self,
+        instructions: &[Instruction],
+        signer: &Pubkey,
+        amount: u64,
+        new_account_space: &[usize],
+        nonce: Option<&DurableNonce>,
+        description: String,
+    ) -> Result<UnsignedTransaction> {
+        let fee_payer = self.fee_payer_pubkey(signer);
+        let recent_blockhash = self.rpc(|| self.client.get_latest_blockhash()).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        let message = match nonce {
+            Some(nonce) => {
+                nonce.check_authority(signer)?;
+                nonce.message(instructions, &fee_payer)
+            }
+            None => Message::new_with_blockhash(instructions, Some(&fee_payer), &recent_blockhash),
         };
+        // Fees are looked up by blockhash, which a nonce is not
+        let mut priced = message.clone();
+        priced.recent_blockhash = recent_blockhash;
+        self.preflight(signer, &priced, amount, new_account_space).await?;
+
+        let mut transaction = Transaction::new_unsigned(message);
+        if let Some(fee_payer) = self.sponsor_for(signer) {
+            let blockhash = transaction.message.recent_blockhash;
+            transaction.partial_sign(&[fee_payer], blockhash);
+        }
+        let unsigned = UnsignedTransaction::new(&transaction, signer, description)?;
+        Ok(match nonce {
+            Some(nonce) => unsigned.with_nonce(nonce),
+            None => unsigned,
+        })
+    }
+
+    /// Current value of the durable nonce in `account`
+    pub async fn get_durable_nonce(&self, account: &Pubkey) -> Result<DurableNonce> {
+        let info = self.get_account(account).await?
+            .ok_or_else(|| SolaceError::config(format!("Nonce account {} does not exist", account)))?;
+        Ok(DurableNonce::from_account_data(*account, &info.data)?)
+    }
 
-        let recent_blockhash = self.client.get_latest_blockhash()
+    /// Create a durable nonce account advanced by `authority`, funded by `payer`
+    pub async fn create_nonce_account(
+        &self,
+        payer: &Keypair,
+        nonce_account: &Keypair,
+        authority: &Pubkey,
+    ) -> Result<BlockchainTransactionResult> {
+        let space = NonceState::size();
+        let rent = self.rpc(|| self.client.get_minimum_balance_for_rent_exemption(space)).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        let recent_blockhash = self.rpc(|| self.client.get_latest_blockhash()).await
             .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
 
-        let message = Message::new(&[solana_instruction], Some(&signer.pubkey()));
+        let instructions = system_instruction::create_nonce_account(&payer.pubkey(), &nonce_account.pubkey(), authority, rent);
+        let message = Message::new_with_blockhash(&instructions, Some(&payer.pubkey()), &recent_blockhash);
+        self.preflight(&payer.pubkey(), &message, 0, &[space]).await?;
+
+        let mut transaction = Transaction::new_unsigned(message);
+        transaction.sign(&[payer, nonce_account], recent_blockhash);
+        self.send_signed(transaction, &payer.pubkey(), OperationClass::General).await
+    }
+
+    /// Submit an exported transaction with the external signer's signature.
+    /// One built on a durable nonce is refused once the nonce has advanced.
+    pub async fn submit_signed(
+        &self,
+        unsigned: &UnsignedTransaction,
+        signature: Signature,
+        class: OperationClass,
+    ) -> Result<BlockchainTransactionResult> {
+        let transaction = unsigned.with_signature(signature)?;
+        let signer = Pubkey::from_str(&unsigned.signer)
+            .map_err(|e| SolaceError::InvalidPubkey(e.to_string()))?;
+        if let Some(account) = &unsigned.nonce_account {
+            let account = Pubkey::from_str(account)
+                .map_err(|e| SolaceError::InvalidPubkey(e.to_string()))?;
+            if self.get_durable_nonce(&account).await?.nonce != transaction.message.recent_blockhash {
+                return Err(SolaceError::config(format!(
+                    "Nonce account {} has advanced since the transaction was built; it was submitted already or must be rebuilt",
+                    account
+                )).into());
+            }
+        }
+
+        self.send_signed(transaction, &signer, class).await
+    }
+
+    /// Sign `message` by `signer`, and by the sponsor if it pays the fee,
+    /// then send it
+    async fn sign_and_send(
+        &self,
+        message: Message,
+        recent_blockhash: solana_sdk::hash::Hash,
+        signer: &Keypair,
+        class: OperationClass,
+    ) -> Result<BlockchainTransactionResult> {
         let mut transaction = Transaction::new_unsigned(message);
-        transaction.sign(&[signer], recent_blockhash);
+        match self.sponsor_for(&signer.pubkey()) {
+            Some(fee_payer) => transaction.sign(&[fee_payer, signer], recent_blockhash),
+            None => transaction.sign(&[signer], recent_blockhash),
+        }
+
+        self.send_signed(transaction, &signer.pubkey(), class).await
+    }
+
+    /// Send a fully signed transaction. A fee paid by the sponsor counts
+    /// against `agent`'s quota.
+    async fn send_signed(
+        &self,
+        transaction: Transaction,
+        agent: &Pubkey,
+        class: OperationClass,
+    ) -> Result<BlockchainTransactionResult> {
+        let fee_payer = transaction.message.account_keys.first().copied();
+        let sponsored = self.sponsor_for(agent).is_some_and(|sponsor| Some(sponsor.pubkey()) == fee_payer);
+        let Some(sponsor) = self.sponsor.as_ref().filter(|_| sponsored) else {
+            return self.send_transaction_with_confirmation(transaction, class).await;
+        };
+
+        let agent = agent.to_string();
+        let fee = self.rpc(|| self.client.get_fee_for_message(&transaction.message)).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        sponsor.reserve(&agent, fee).map_err(SolaceError::from)?;
 
-        self.send_transaction_with_confirmation(transaction).await
+        let result = self.send_transaction_with_confirmation(transaction, class).await;
+        // Transactions that never landed were not charged
+        let charged = result.as_ref().ok().map(|result| result.fee).filter(|fee| *fee > 0);
+        sponsor.settle(&agent, fee, charged);
+        result
+    }
+
+    /// Check that `payer` can fund `message`: `amount`, the fee, and rent
+    /// for accounts of `new_account_space` bytes it creates
+    ///
+    /// A sponsor paying the fee must cover it from its own balance instead.
+    /// Fails with `ChainError::Underfunded` carrying the shortfall.
+    pub async fn preflight(
+        &self,
+        payer: &Pubkey,
+        message: &Message,
+        amount: u64,
+        new_account_space: &[usize],
+    ) -> Result<FundingRequirement> {
+        let fees = self.rpc(|| self.client.get_fee_for_message(message)).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        let mut rent = 0u64;
+        for space in new_account_space {
+            rent += self.rpc(|| self.client.get_minimum_balance_for_rent_exemption(*space)).await
+                .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        }
+        let reserve = self.rpc(|| self.client.get_minimum_balance_for_rent_exemption(0)).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        let fee_payer = message.account_keys.first().copied().unwrap_or(*payer);
+        let sponsored = fee_payer != *payer;
+        let requirement = FundingRequirement { amount, fees: if sponsored { 0 } else { fees }, rent, reserve };
+
+        let balance = self.get_balance(payer).await?;
+        if let Err(shortfall) = requirement.check(&payer.to_string(), balance) {
+            warn!("Pre-flight rejected payment from {}: {}", payer, shortfall);
+            return Err(SolaceError::from(shortfall).into());
+        }
+        if sponsored {
+            let sponsor_requirement = FundingRequirement { fees, reserve, ..FundingRequirement::default() };
+            let balance = self.get_balance(&fee_payer).await?;
+            if let Err(shortfall) = sponsor_requirement.check(&fee_payer.to_string(), balance) {
+                warn!("Pre-flight rejected fee sponsor {}: {}", fee_payer, shortfall);
+                return Err(SolaceError::from(shortfall).into());
+            }
+        }
+        Ok(requirement)
+    }
+
+    /// Message carrying a Solace instruction, with the blockhash it was built on
+    fn instruction_message(
+        &self,
+        instruction: &SolaceInstruction,
+        signer: &Pubkey,
+        additional_accounts: Vec<AccountMeta>,
+    ) -> Result<(Message, solana_sdk::hash::Hash)> {
+        let solana_instruction = self.program().instruction(instruction, signer, additional_accounts)?;
+
+        let recent_blockhash = self.rpc(|| self.client.get_latest_blockhash()).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+
+        let message = Message::new_with_blockhash(&[solana_instruction], Some(&self.fee_payer_pubkey(signer)), &recent_blockhash);
+        Ok((message, recent_blockhash))
     }
 
     /// Initialize a new agent on the blockchain
@@ -274,13 +869,15 @@ impl SolanaClient {
         self.submit_instruction(instruction, agent_keypair, vec![]).await
     }
 
-    /// Create a transaction record on the blockchain
+    /// Create a transaction record on the blockchain. A record naming a
+    /// `release_authority` pays out only with that multisig's approvals.
     pub async fn create_blockchain_transaction(
         &self,
         creator_keypair: &Keypair,
         transaction_id: TransactionId,
         amount: Balance,
         recipient: Pubkey,
+        release_authority: Option<&Pubkey>,
     ) -> Result<BlockchainTransactionResult> {
         let instruction = SolaceInstruction::CreateTransaction {
             transaction_id,
@@ -288,9 +885,60 @@ impl SolanaClient {
             recipient,
         };
 
-        self.submit_instruction(instruction, creator_keypair, vec![
-            AccountMeta::new(recipient, false),
-        ]).await
+        // Escrows the amount in a new record account
+        let mut accounts = vec![AccountMeta::new(recipient, false)];
+        accounts.extend(release_authority.map(|authority| AccountMeta::new_readonly(*authority, false)));
+        self.submit_payment(instruction, creator_keypair, accounts, amount.lamports(), &[ESCROW_ACCOUNT_SPACE]).await
+    }
+
+    /// Create a transaction record escrowing `amount` of `mint` in the
+    /// transaction's token vault, as `create_blockchain_transaction`
+    pub async fn create_token_transaction(
+        &self,
+        creator_keypair: &Keypair,
+        transaction_id: TransactionId,
+        mint: Pubkey,
+        amount: u64,
+        recipient: Pubkey,
+        release_authority: Option<&Pubkey>,
+    ) -> Result<BlockchainTransactionResult> {
+        let creator = creator_keypair.pubkey();
+        self.check_token_funds(&creator, &mint, amount).await?;
+        let instruction = SolaceInstruction::CreateTokenTransaction {
+            transaction_id,
+            mint,
+            amount,
+            recipient,
+        };
+
+        // Fees and rent for the record and the vault are paid in lamports
+        let mut accounts = self.token_escrow_accounts(transaction_id, &mint, &creator, &recipient);
+        accounts.extend(release_authority.map(|authority| AccountMeta::new_readonly(*authority, false)));
+        self.submit_payment(instruction, creator_keypair, accounts, 0, &[ESCROW_ACCOUNT_SPACE, TOKEN_ACCOUNT_SPACE]).await
+    }
+
+    /// Accounts the program moves a token escrow between: the recipient,
+    /// the vault and its authority, both parties' token accounts, and the
+    /// programs that create and transfer them
+    pub fn token_escrow_accounts(
+        &self,
+        transaction_id: TransactionId,
+        mint: &Pubkey,
+        requester: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Vec<AccountMeta> {
+        let authority = self.escrow_authority(transaction_id);
+        vec![
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new(Self::token_account(&authority, mint), false),
+            AccountMeta::new(Self::token_account(requester, mint), false),
+            AccountMeta::new(Self::token_account(recipient, mint), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ]
     }
 
     /// Update agent reputation on the blockchain
@@ -325,6 +973,21 @@ impl SolanaClient {
         self.submit_instruction(instruction, finalizer_keypair, vec![]).await
     }
 
+    /// Pay part of a transaction's escrow to its recipient, leaving the rest locked
+    pub async fn release_partial(
+        &self,
+        finalizer_keypair: &Keypair,
+        transaction_id: TransactionId,
+        amount: Balance,
+    ) -> Result<BlockchainTransactionResult> {
+        let instruction = SolaceInstruction::ReleasePartial {
+            transaction_id,
+            amount: amount.lamports(),
+        };
+
+        self.submit_instruction(instruction, finalizer_keypair, vec![]).await
+    }
+
     /// Stake tokens for consensus participation
     pub async fn stake(
         &self,
@@ -335,7 +998,7 @@ impl SolanaClient {
             amount: amount.lamports(),
         };
 
-        self.submit_instruction(instruction, staker_keypair, vec![]).await
+        self.submit_payment(instruction, staker_keypair, vec![], amount.lamports(), &[]).await
     }
 
     /// Unstake tokens
@@ -366,29 +1029,77 @@ impl SolanaClient {
         self.submit_instruction(instruction, voter_keypair, vec![]).await
     }
 
+    /// Lock the agreed price in escrow, then move the transaction into
+    /// execution once the lock reaches the escrow commitment level
+    pub async fn lock_escrow(
+        &self,
+        requester_keypair: &Keypair,
+        transactions: &TransactionManager,
+        transaction_id: &TransactionId,
+        provider_id: AgentId,
+        price: Balance,
+        provider: Pubkey,
+    ) -> Result<BlockchainTransactionResult> {
+        let transition = Transition::AcceptProposal { provider: provider_id, price };
+        transactions.check(transaction_id, &transition).await?;
+        let result = self.create_blockchain_transaction(requester_keypair, *transaction_id, price, provider, None).await?;
+        if result.failure.is_none() {
+            transactions.apply(transaction_id, transition).await?;
+        }
+        Ok(result)
+    }
+
+    /// Settle on chain, then record the evaluation once settlement is final
+    pub async fn settle(
+        &self,
+        finalizer_keypair: &Keypair,
+        transactions: &TransactionManager,
+        transaction_id: &TransactionId,
+        evaluation: TransactionEvaluation,
+    ) -> Result<BlockchainTransactionResult> {
+        let transition = Transition::Evaluate(evaluation);
+        transactions.check(transaction_id, &transition).await?;
+        let result = self.finalize_transaction(finalizer_keypair, *transaction_id, true).await?;
+        if result.failure.is_none() {
+            transactions.apply(transaction_id, transition).await?;
+        }
+        Ok(result)
+    }
+
+    /// Status of a submitted transaction at the progress polling level;
+    /// `None` if the cluster has not seen it yet
+    pub async fn poll_progress(&self, signature: &Signature) -> Result<Option<ConfirmationStatus>> {
+        let commitment = self.config.commitment_for(OperationClass::ProgressPolling);
+        let status = self.rpc(|| self.client.get_signature_status_with_commitment(signature, commitment.into())).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+
+        Ok(status.map(|result| match result {
+            Ok(()) => commitment.into(),
+            Err(_) => ConfirmationStatus::Failed,
+        }))
+    }
+
     /// Get transaction history for an account
     pub async fn get_transaction_history(
         &self,
         pubkey: &Pubkey,
         limit: usize,
     ) -> Result<Vec<BlockchainTransactionResult>> {
-        let signatures = self.client
-            .get_signatures_for_address_with_config(
+        let signatures = self
+            .rpc(|| self.client.get_signatures_for_address_with_config(
                 pubkey,
                 solana_client::rpc_client_api::config::GetConfirmedSignaturesForAddress2Config {
                     limit: Some(limit),
                     ..Default::default()
                 },
-            )
+            ))
+            .await
             .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
 
         let mut results = Vec::new();
         for signature_info in signatures {
             if let Ok(signature) = Signature::from_str(&signature_info.signature) {
-                if let Ok(transaction) = self.client.get_transaction(
-                    &signature,
-                    solana_sdk::transaction_status::UiTransactionEncoding::Json,
-                ) {
+                if let Ok(transaction) = self.fetch_transaction(&signature).await {
                     results.push(BlockchainTransactionResult {
                         signature: signature_info.signature,
                         slot: signature_info.slot,
@@ -401,6 +1112,9 @@ impl SolanaClient {
                         fee: transaction.transaction.meta
                             .and_then(|meta| meta.fee)
                             .unwrap_or(0),
+                        failure: signature_info.err.as_ref().map(|e| {
+                            self.analyzer.analyze(Some(e), &log_messages(&transaction))
+                        }),
                         error: signature_info.err.map(|e| format!("{:?}", e)),
                     });
                 }
@@ -412,13 +1126,13 @@ impl SolanaClient {
 
     /// Get current network status
     pub async fn get_network_status(&self) -> Result<NetworkStatus> {
-        let health = self.client.get_health()
+        let health = self.rpc(|| self.client.get_health()).await
             .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
 
-        let slot = self.client.get_slot()
+        let slot = self.rpc(|| self.client.get_slot()).await
             .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
 
-        let epoch_info = self.client.get_epoch_info()
+        let epoch_info = self.rpc(|| self.client.get_epoch_info()).await
             .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
 
         Ok(NetworkStatus {
@@ -430,48 +1144,248 @@ impl SolanaClient {
         })
     }
 
+    /// Explain why a landed transaction failed; `None` if it succeeded
+    pub async fn diagnose(&self, signature: &Signature) -> Result<Option<FailureDiagnosis>> {
+        let transaction = self.fetch_transaction(signature).await
+            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+
+        let error = transaction.transaction.meta.as_ref().and_then(|meta| meta.err.clone());
+        Ok(error.map(|e| self.analyzer.analyze(Some(&e), &log_messages(&transaction))))
+    }
+
+    /// Run an RPC call, retrying transient failures up to `max_retries`
+    /// times with exponential backoff, until the client is cancelled
+    async fn rpc<T, F, Fut>(&self, call: F) -> std::result::Result<T, ClientError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = std::result::Result<T, ClientError>>,
+    {
+        retry(self.config.max_retries, &self.cancel, call).await
+    }
+
+    /// Landed transaction with its status metadata and logs
+    async fn fetch_transaction(&self, signature: &Signature) -> std::result::Result<EncodedConfirmedTransactionWithStatusMeta, ClientError> {
+        self.rpc(|| self.client.get_transaction(signature, UiTransactionEncoding::Json)).await
+    }
+
+    /// Wait until `signature` reaches `commitment`, for at most
+    /// `confirmation_timeout`; the inner result is the transaction's own
+    /// outcome once it landed
+    async fn confirm(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentLevel,
+    ) -> Result<std::result::Result<(), solana_sdk::transaction::TransactionError>> {
+        if self.cancel.is_cancelled() {
+            return Err(SolaceError::cancelled(format!("confirmation of transaction {}", signature)).into());
+        }
+        let poll = async {
+            loop {
+                let status = self.rpc(|| self.client.get_signature_status_with_commitment(signature, commitment.into())).await
+                    .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+                if let Some(status) = status {
+                    return Ok::<_, anyhow::Error>(status);
+                }
+                tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+            }
+        };
+        let status = tokio::select! {
+            biased;
+            _ = self.cancel.cancelled() => {
+                return Err(SolaceError::cancelled(format!("confirmation of transaction {}", signature)).into());
+            }
+            status = tokio::time::timeout(self.config.confirmation_timeout, poll) => status,
+        };
+        match status {
+            Ok(status) => status,
+            Err(_) => {
+                warn!("Transaction {} not {:?} within {:?}", signature, commitment, self.config.confirmation_timeout);
+                Err(SolaceError::from(TransactionError::Timeout { duration: self.config.confirmation_timeout.as_secs() }).into())
+            }
+        }
+    }
+
     /// Send transaction with confirmation
+    ///
+    /// An instruction failure is returned as a `Failed` result carrying its
+    /// diagnosis rather than as an error; RPC and network errors still fail.
     async fn send_transaction_with_confirmation(
         &self,
         transaction: Transaction,
+        class: OperationClass,
     ) -> Result<BlockchainTransactionResult> {
-        let signature = self.client
-            .send_and_confirm_transaction_with_spinner_and_config(
-                &transaction,
-                self.config.commitment.clone().into(),
-                RpcSendTransactionConfig {
-                    skip_preflight: self.config.skip_preflight,
-                    ..Default::default()
-                },
-            )
-            .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
+        let commitment = self.config.commitment_for(class);
+        let send_config = RpcSendTransactionConfig {
+            skip_preflight: self.config.skip_preflight,
+            ..Default::default()
+        };
+        let signature = match self.rpc(|| self.client.send_transaction_with_config(&transaction, send_config)).await {
+            Ok(signature) => signature,
+            Err(e) if is_cancelled(&e) => {
+                return Err(SolaceError::cancelled(format!("send of transaction {}", transaction.signatures[0])).into());
+            }
+            Err(e) => return self.failed_transaction(&transaction, e).await,
+        };
+        if let Err(e) = self.confirm(&signature, commitment).await? {
+            return self.failed_transaction(&transaction, e.into()).await;
+        }
 
         // Get transaction details
-        let transaction_result = self.client
-            .get_transaction(
-                &signature,
-                solana_sdk::transaction_status::UiTransactionEncoding::Json,
-            )
+        let transaction_result = self.fetch_transaction(&signature).await
             .map_err(|e| SolaceError::BlockchainError(e.to_string()))?;
 
         Ok(BlockchainTransactionResult {
             signature: signature.to_string(),
             slot: transaction_result.slot,
             block_time: transaction_result.block_time,
-            confirmation_status: ConfirmationStatus::Confirmed,
+            confirmation_status: commitment.into(),
             fee: transaction_result.transaction.meta
                 .and_then(|meta| meta.fee)
                 .unwrap_or(0),
             error: None,
+            failure: None,
         })
     }
 
-    /// Serialize instruction data
-    fn serialize_instruction(&self, instruction: &SolaceInstruction) -> Result<Vec<u8>> {
-        // In a real implementation, this would use a proper serialization format
-        // like Borsh that matches the on-chain program expectations
-        serde_json::to_vec(instruction)
-            .map_err(|e| SolaceError::SerializationError(e.to_string()).into())
+    /// Turn a rejected send into a diagnosed `Failed` result
+    async fn failed_transaction(
+        &self,
+        transaction: &Transaction,
+        error: solana_client::client_error::ClientError,
+    ) -> Result<BlockchainTransactionResult> {
+        let Some(mut diagnosis) = self.analyzer.analyze_client_error(&error) else {
+            return Err(SolaceError::BlockchainError(error.to_string()).into());
+        };
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+
+        // Without preflight the transaction landed; its logs say what went wrong
+        let landed = self.fetch_transaction(&signature).await.ok();
+        if diagnosis.logs.is_empty() {
+            if let Some(landed) = &landed {
+                let logs = log_messages(landed);
+                let runtime_error = landed.transaction.meta.as_ref().and_then(|meta| meta.err.clone());
+                diagnosis = self.analyzer.analyze(runtime_error.as_ref().or(error.get_transaction_error().as_ref()), &logs);
+            }
+        }
+        warn!("Transaction {} failed: {} ({})", signature, diagnosis.error, diagnosis.remediation);
+
+        Ok(BlockchainTransactionResult {
+            signature: signature.to_string(),
+            slot: landed.as_ref().map(|landed| landed.slot).unwrap_or(0),
+            block_time: landed.as_ref().and_then(|landed| landed.block_time),
+            confirmation_status: ConfirmationStatus::Failed,
+            fee: landed.as_ref()
+                .and_then(|landed| landed.transaction.meta.as_ref())
+                .map(|meta| meta.fee)
+                .unwrap_or(0),
+            error: Some(error.to_string()),
+            failure: Some(diagnosis),
+        })
+    }
+}
+
+/// Escrow ledger holding locked funds in the Solace program's record accounts
+pub struct SolanaEscrowLedger {
+    client: Arc<SolanaClient>,
+    keypair: Arc<Keypair>,                // Signs as the requester
+    treasury: Option<Pubkey>,             // SPL multisig account of the release policy's wallet
+}
+
+impl SolanaEscrowLedger {
+    pub fn new(client: Arc<SolanaClient>, keypair: Arc<Keypair>) -> Self {
+        Self { client, keypair, treasury: None }
+    }
+
+    /// Make escrows under a release policy pay out only with approvals of
+    /// `multisig_account`, created with `SolanaClient::create_token_multisig`
+    pub fn with_treasury(mut self, multisig_account: Pubkey) -> Self {
+        self.treasury = Some(multisig_account);
+        self
+    }
+
+    /// Multisig that must approve the escrow's payouts, if it has a policy
+    fn release_authority(&self, escrow: &Escrow) -> crate::Result<Option<Pubkey>> {
+        match (escrow.release_policy(), self.treasury) {
+            (None, _) => Ok(None),
+            (Some(_), Some(treasury)) => Ok(Some(treasury)),
+            (Some(_), None) => Err(SolaceError::config(format!(
+                "escrow for {} has a release policy but the ledger has no treasury multisig", escrow.transaction_id
+            ))),
+        }
+    }
+
+    /// Pay out `amount` with `instruction`, through the treasury multisig
+    /// with the collected approvals when the escrow has a release policy
+    async fn pay_out(&self, escrow: &Escrow, instruction: SolaceInstruction, amount: Balance) -> crate::Result<String> {
+        let mut accounts = self.settlement_accounts(escrow)?;
+        let Some(authority) = self.release_authority(escrow)? else {
+            return Self::landed(self.client.submit_instruction(instruction, &self.keypair, accounts).await);
+        };
+        accounts.push(AccountMeta::new_readonly(authority, false));
+        let message = escrow.release_message(amount);
+        let approvals = escrow.approvals.iter().map(|share| approval_instruction(share, &message)).collect();
+        Self::landed(self.client.submit_approved_instruction(instruction, &self.keypair, accounts, approvals).await)
+    }
+
+    /// Accounts a settlement of `escrow` touches beyond the record
+    fn settlement_accounts(&self, escrow: &Escrow) -> crate::Result<Vec<AccountMeta>> {
+        match escrow.asset {
+            PaymentAsset::Sol => Ok(vec![]),
+            PaymentAsset::Spl { mint, .. } => {
+                let provider = Self::provider_pubkey(escrow)?;
+                Ok(self.client.token_escrow_accounts(escrow.transaction_id, &mint, &self.keypair.pubkey(), &provider))
+            }
+        }
+    }
+
+    fn provider_pubkey(escrow: &Escrow) -> crate::Result<Pubkey> {
+        Pubkey::from_str(&escrow.provider_account)
+            .map_err(|e| SolaceError::config(format!("provider account {}: {}", escrow.provider_account, e)))
+    }
+
+    /// Signature of a landed step, or why it failed on chain
+    fn landed(result: Result<BlockchainTransactionResult>) -> crate::Result<String> {
+        let result = result.map_err(|e| SolaceError::internal(e.to_string()))?;
+        match result.failure {
+            Some(failure) => Err(failure.error.into()),
+            None => Ok(result.signature),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EscrowLedger for SolanaEscrowLedger {
+    async fn lock(&self, escrow: &Escrow) -> crate::Result<String> {
+        let provider = Self::provider_pubkey(escrow)?;
+        let authority = self.release_authority(escrow)?;
+        match escrow.asset {
+            PaymentAsset::Sol => Self::landed(
+                self.client.create_blockchain_transaction(
+                    &self.keypair, escrow.transaction_id, escrow.amount, provider, authority.as_ref(),
+                ).await,
+            ),
+            PaymentAsset::Spl { mint, .. } => Self::landed(
+                self.client.create_token_transaction(
+                    &self.keypair, escrow.transaction_id, mint, escrow.amount.0, provider, authority.as_ref(),
+                ).await,
+            ),
+        }
+    }
+
+    async fn release_partial(&self, escrow: &Escrow, amount: Balance) -> crate::Result<String> {
+        let instruction = SolaceInstruction::ReleasePartial { transaction_id: escrow.transaction_id, amount: amount.0 };
+        self.pay_out(escrow, instruction, amount).await
+    }
+
+    async fn release(&self, escrow: &Escrow) -> crate::Result<String> {
+        let instruction = SolaceInstruction::FinalizeTransaction { transaction_id: escrow.transaction_id, success: true };
+        self.pay_out(escrow, instruction, escrow.remaining()).await
+    }
+
+    async fn refund(&self, escrow: &Escrow) -> crate::Result<String> {
+        let instruction = SolaceInstruction::FinalizeTransaction { transaction_id: escrow.transaction_id, success: false };
+        let accounts = self.settlement_accounts(escrow)?;
+        Self::landed(self.client.submit_instruction(instruction, &self.keypair, accounts).await)
     }
 }
 
@@ -497,6 +1411,109 @@ fn read_keypair_file(path: &str) -> Result<Keypair> {
         .map_err(|e| SolaceError::InvalidKeypair(e.to_string()).into())
 }
 
+/// JSON-RPC error code of a node that is behind or unhealthy
+const NODE_UNHEALTHY: i64 = -32005;
+
+/// Message of the error an RPC call fails with once the client is cancelled
+const RPC_CANCELLED: &str = "RPC call cancelled";
+
+/// Run `call`, retrying transient failures up to `max_retries` times with
+/// exponential backoff. Once `cancel` fires, the call or the wait for the
+/// next attempt is abandoned with an `RPC_CANCELLED` error.
+async fn retry<T, F, Fut>(max_retries: u32, cancel: &CancellationToken, call: F) -> std::result::Result<T, ClientError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = std::result::Result<T, ClientError>>,
+{
+    let cancelled = || ClientError::from(ClientErrorKind::Custom(RPC_CANCELLED.to_string()));
+    let mut attempt = 0;
+    loop {
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return Err(cancelled()),
+            result = call() => result,
+        };
+        match result {
+            Err(e) if attempt < max_retries && is_transient(&e) => {
+                let delay = retry_delay(attempt);
+                debug!("RPC call failed ({}), retrying in {:?}", e, delay);
+                tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => return Err(cancelled()),
+                    _ = tokio::time::sleep(delay) => {}
+                }
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether an RPC call failed because the client was cancelled
+fn is_cancelled(error: &ClientError) -> bool {
+    matches!(error.kind(), ClientErrorKind::Custom(message) if message == RPC_CANCELLED)
+}
+
+/// Whether a failed RPC call may succeed if simply tried again
+fn is_transient(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(e) => {
+            e.is_timeout() || e.is_connect() || e.status().is_some_and(|status| status.is_server_error() || status.as_u16() == 429)
+        }
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => *code == NODE_UNHEALTHY,
+        _ => false,
+    }
+}
+
+/// Wait before retry number `attempt`, counted from 0
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY.saturating_mul(1 << attempt.min(16)).min(RETRY_MAX_DELAY)
+}
+
+/// Log lines recorded for a landed transaction
+fn log_messages(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<String> {
+    transaction.transaction.meta
+        .as_ref()
+        .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages.clone()))
+        .unwrap_or_default()
+}
+
+/// Offset of the first signature's data in an ed25519 program instruction,
+/// after the signature count, padding and one set of offsets
+const ED25519_DATA_START: u16 = 16;
+
+/// Ed25519 program instruction checking a treasury signer's approval of
+/// `message`, so the Solace program can require it in the same transaction
+pub fn approval_instruction(share: &PartialSignature, message: &[u8]) -> Instruction {
+    let public_key_offset = ED25519_DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = vec![1, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,                         // Signature in this instruction
+        public_key_offset,
+        u16::MAX,                         // Public key in this instruction
+        message_offset,
+        message.len() as u16,
+        u16::MAX,                         // Message in this instruction
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(&share.signer);
+    data.extend_from_slice(&share.signature.to_bytes());
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: solana_sdk::ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
 /// Blockchain event listener for monitoring on-chain activity
 pub struct BlockchainEventListener {
     client: SolanaClient,
@@ -564,13 +1581,79 @@ mod tests {
     }
 
     #[test]
-    fn test_instruction_serialization() {
-        let instruction = SolaceInstruction::InitializeAgent {
-            agent_id: AgentId::new(),
-            initial_reputation: 800,
+    fn test_commitment_per_operation_class() {
+        let mut config = BlockchainConfig::default();
+        assert_eq!(config.commitment_for(OperationClass::ProgressPolling), CommitmentLevel::Processed);
+        assert_eq!(config.commitment_for(OperationClass::EscrowLock), CommitmentLevel::Confirmed);
+        assert_eq!(config.commitment_for(OperationClass::Settlement), CommitmentLevel::Finalized);
+        assert_eq!(config.commitment_for(OperationClass::ReputationAnchor), CommitmentLevel::Finalized);
+
+        config.commitment = CommitmentLevel::Processed;
+        assert_eq!(config.commitment_for(OperationClass::General), CommitmentLevel::Processed);
+        assert!(CommitmentLevel::Processed < CommitmentLevel::Confirmed && CommitmentLevel::Confirmed < CommitmentLevel::Finalized);
+
+        let settle = SolaceInstruction::FinalizeTransaction { transaction_id: TransactionId::new(), success: true };
+        assert_eq!(settle.operation_class(), OperationClass::Settlement);
+    }
+
+    #[test]
+    fn test_retries_back_off_on_transient_errors() {
+        assert_eq!(retry_delay(0), RETRY_BASE_DELAY);
+        assert_eq!(retry_delay(2), RETRY_BASE_DELAY * 4);
+        assert_eq!(retry_delay(30), RETRY_MAX_DELAY);
+
+        let io = ClientError::from(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset"));
+        assert!(is_transient(&io));
+        let unhealthy = ClientError::from(RpcError::RpcResponseError {
+            code: NODE_UNHEALTHY,
+            message: "Node is behind".to_string(),
+            data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+        });
+        assert!(is_transient(&unhealthy));
+        let failed = ClientError::from(solana_sdk::transaction::TransactionError::InsufficientFundsForFee);
+        assert!(!is_transient(&failed));
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_stops_retries() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = AtomicU32::new(0);
+        let flaky = || async {
+            calls.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>(ClientError::from(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset")))
         };
+        let cancel = CancellationToken::new();
+        let cancel_soon = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            cancel.cancel();
+        };
+
+        // Cancelled while backing off before the second attempt
+        let started = std::time::Instant::now();
+        let (result, ()) = tokio::join!(retry(5, &cancel, flaky), cancel_soon);
+        assert!(is_cancelled(&result.unwrap_err()));
+        assert!(started.elapsed() < RETRY_BASE_DELAY);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // A cancelled client makes no more calls
+        assert!(is_cancelled(&retry(5, &cancel, flaky).await.unwrap_err()));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_approval_instruction_passes_the_ed25519_program() {
+        use solana_sdk::{ed25519_instruction, feature_set::FeatureSet};
+
+        let signer = crate::crypto::KeyPair::generate().unwrap();
+        let message = b"solace-escrow-release:payout";
+        let share = PartialSignature::sign(&signer, message);
+
+        let instruction = approval_instruction(&share, message);
+        assert_eq!(instruction.program_id, solana_sdk::ed25519_program::id());
+        assert!(ed25519_instruction::verify(&instruction.data, &[&instruction.data], &FeatureSet::all_enabled()).is_ok());
 
-        let serialized = serde_json::to_vec(&instruction).unwrap();
-        assert!(!serialized.is_empty());
+        let forged = approval_instruction(&share, b"solace-escrow-release:other");
+        assert!(ed25519_instruction::verify(&forged.data, &[&forged.data], &FeatureSet::all_enabled()).is_err());
     }
 } 
\ No newline at end of file
@@ -0,0 +1,292 @@
+//! Capability Registry
+//!
+//! `ServiceType` (what a request asks for) and `AgentCapability` (what an
+//! agent offers) name the same set of services. Both convert to one
+//! canonical key, e.g. `data_analysis` or `custom.translation`, and the
+//! conversions between them are exhaustive, so a service added to one enum
+//! does not compile until the other knows it too.
+//!
+//! The `CapabilityRegistry` attaches metadata to those keys: a description,
+//! the requirements a request must or may carry, and default service levels
+//! filled into requests that do not set their own. Built-in services are
+//! always registered; custom services are registered at runtime, and
+//! requests for custom services nobody registered are refused.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::{
+    agent::AgentCapability,
+    error::{Result, TransactionError},
+    transaction::TransactionRequest,
+    types::ServiceType,
+};
+
+/// Key prefix of custom services
+pub const CUSTOM_PREFIX: &str = "custom.";
+
+/// Requirement keys the default service levels are written under
+pub const SLA_MAX_COMPLETION: &str = "sla.max_completion_secs";
+pub const SLA_MIN_REPUTATION: &str = "sla.min_provider_reputation";
+pub const SLA_DISPUTE_WINDOW: &str = "sla.dispute_window_secs";
+
+impl ServiceType {
+    /// Canonical registry key
+    pub fn key(&self) -> String {
+        match self {
+            ServiceType::DataAnalysis => "data_analysis".to_string(),
+            ServiceType::ComputationalTask => "computational_task".to_string(),
+            ServiceType::MarketResearch => "market_research".to_string(),
+            ServiceType::ContentCreation => "content_creation".to_string(),
+            ServiceType::TradingService => "trading_service".to_string(),
+            ServiceType::MachineLearning => "machine_learning".to_string(),
+            ServiceType::CustomService(name) => format!("{}{}", CUSTOM_PREFIX, name.to_lowercase().replace(char::is_whitespace, "_")),
+        }
+    }
+
+    /// Service type for a registry key
+    pub fn from_key(key: &str) -> Option<Self> {
+        if let Some(name) = key.strip_prefix(CUSTOM_PREFIX) {
+            return (!name.is_empty()).then(|| ServiceType::CustomService(name.to_string()));
+        }
+        BUILT_IN.iter().map(|(service_type, _)| service_type()).find(|service_type| service_type.key() == key)
+    }
+}
+
+impl From<&AgentCapability> for ServiceType {
+    fn from(capability: &AgentCapability) -> Self {
+        match capability {
+            AgentCapability::DataAnalysis => ServiceType::DataAnalysis,
+            AgentCapability::ComputationalTask => ServiceType::ComputationalTask,
+            AgentCapability::MarketResearch => ServiceType::MarketResearch,
+            AgentCapability::ContentCreation => ServiceType::ContentCreation,
+            AgentCapability::TradingService => ServiceType::TradingService,
+            AgentCapability::MachineLearning => ServiceType::MachineLearning,
+            AgentCapability::CustomCapability(name) => ServiceType::CustomService(name.clone()),
+        }
+    }
+}
+
+impl From<&ServiceType> for AgentCapability {
+    fn from(service_type: &ServiceType) -> Self {
+        match service_type {
+            ServiceType::DataAnalysis => AgentCapability::DataAnalysis,
+            ServiceType::ComputationalTask => AgentCapability::ComputationalTask,
+            ServiceType::MarketResearch => AgentCapability::MarketResearch,
+            ServiceType::ContentCreation => AgentCapability::ContentCreation,
+            ServiceType::TradingService => AgentCapability::TradingService,
+            ServiceType::MachineLearning => AgentCapability::MachineLearning,
+            ServiceType::CustomService(name) => AgentCapability::CustomCapability(name.clone()),
+        }
+    }
+}
+
+impl AgentCapability {
+    /// Canonical registry key
+    pub fn key(&self) -> String {
+        ServiceType::from(self).key()
+    }
+}
+
+/// A field a request for the service carries in its `requirements`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequirementSpec {
+    pub key: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// Service levels a request gets unless it sets its own
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ServiceLevel {
+    pub max_completion: Duration,
+    pub min_provider_reputation: f64,
+    pub dispute_window: Duration,
+}
+
+impl Default for ServiceLevel {
+    fn default() -> Self {
+        Self {
+            max_completion: Duration::from_secs(60 * 60),
+            min_provider_reputation: 0.5,
+            dispute_window: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// What the registry knows about one service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityInfo {
+    pub key: String,
+    pub description: String,
+    pub requirements: Vec<RequirementSpec>,
+    pub default_sla: ServiceLevel,
+}
+
+impl CapabilityInfo {
+    /// Metadata for a custom service
+    pub fn custom(name: &str, description: impl Into<String>) -> Self {
+        Self {
+            key: ServiceType::CustomService(name.to_string()).key(),
+            description: description.into(),
+            requirements: Vec::new(),
+            default_sla: ServiceLevel::default(),
+        }
+    }
+
+    /// Add a requirement field
+    pub fn with_requirement(mut self, key: impl Into<String>, description: impl Into<String>, required: bool) -> Self {
+        self.requirements.push(RequirementSpec { key: key.into(), description: description.into(), required });
+        self
+    }
+
+    pub fn with_default_sla(mut self, sla: ServiceLevel) -> Self {
+        self.default_sla = sla;
+        self
+    }
+
+    pub fn service_type(&self) -> Option<ServiceType> {
+        ServiceType::from_key(&self.key)
+    }
+}
+
+/// Built-in services and their descriptions
+const BUILT_IN: &[(fn() -> ServiceType, &str)] = &[
+    (|| ServiceType::DataAnalysis, "Analysis of datasets supplied or referenced by the requester"),
+    (|| ServiceType::ComputationalTask, "General computation run on the provider's resources"),
+    (|| ServiceType::MarketResearch, "Research into markets, prices and competitors"),
+    (|| ServiceType::ContentCreation, "Writing, media and other generated content"),
+    (|| ServiceType::TradingService, "Order execution and trading strategies"),
+    (|| ServiceType::MachineLearning, "Model training, fine-tuning and inference"),
+];
+
+/// Metadata for every service agents can offer and request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityRegistry {
+    services: BTreeMap<String, CapabilityInfo>,
+}
+
+impl Default for CapabilityRegistry {
+    fn default() -> Self {
+        Self::built_in()
+    }
+}
+
+impl CapabilityRegistry {
+    /// The built-in services only
+    pub fn built_in() -> Self {
+        let services = BUILT_IN
+            .iter()
+            .map(|(service_type, description)| {
+                let key = service_type().key();
+                let default_sla = match service_type() {
+                    // Trades go stale within minutes
+                    ServiceType::TradingService => ServiceLevel { max_completion: Duration::from_secs(5 * 60), ..ServiceLevel::default() },
+                    ServiceType::MachineLearning => ServiceLevel { max_completion: Duration::from_secs(24 * 60 * 60), ..ServiceLevel::default() },
+                    _ => ServiceLevel::default(),
+                };
+                let info = CapabilityInfo { key: key.clone(), description: description.to_string(), requirements: Vec::new(), default_sla };
+                (key, info)
+            })
+            .collect();
+        Self { services }
+    }
+
+    /// Register a custom service, or update one registered before
+    pub fn register(&mut self, info: CapabilityInfo) -> Result<()> {
+        if !info.key.starts_with(CUSTOM_PREFIX) || info.service_type().is_none() {
+            return Err(TransactionError::InvalidRequirements {
+                reason: format!("{} is not a custom service key", info.key),
+            }.into());
+        }
+        self.services.insert(info.key.clone(), info);
+        Ok(())
+    }
+
+    pub fn get(&self, service_type: &ServiceType) -> Option<&CapabilityInfo> {
+        self.services.get(&service_type.key())
+    }
+
+    pub fn is_registered(&self, capability: &AgentCapability) -> bool {
+        self.services.contains_key(&capability.key())
+    }
+
+    pub fn services(&self) -> impl Iterator<Item = &CapabilityInfo> {
+        self.services.values()
+    }
+
+    /// Check that a request names a registered service and carries every
+    /// requirement the service needs
+    pub fn validate_request(&self, request: &TransactionRequest) -> Result<()> {
+        let info = self.get(&request.service_type).ok_or_else(|| TransactionError::InvalidRequirements {
+            reason: format!("{} is not a registered service", request.service_type.key()),
+        })?;
+        let missing: Vec<&str> = info
+            .requirements
+            .iter()
+            .filter(|spec| spec.required && !request.requirements.contains_key(&spec.key))
+            .map(|spec| spec.key.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(TransactionError::InvalidRequirements {
+                reason: format!("{} requests need {}", info.key, missing.join(", ")),
+            }.into());
+        }
+        Ok(())
+    }
+
+    /// Validate a request and fill in the service levels it does not set
+    pub fn apply_defaults(&self, request: &mut TransactionRequest) -> Result<()> {
+        self.validate_request(request)?;
+        let sla = self.get(&request.service_type).map(|info| info.default_sla).unwrap_or_default();
+        let defaults = [
+            (SLA_MAX_COMPLETION, sla.max_completion.as_secs().to_string()),
+            (SLA_MIN_REPUTATION, sla.min_provider_reputation.to_string()),
+            (SLA_DISPUTE_WINDOW, sla.dispute_window.as_secs().to_string()),
+        ];
+        for (key, value) in defaults {
+            request.requirements.entry(key.to_string()).or_insert(value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgentId, Balance, Timestamp};
+
+    #[test]
+    fn test_capabilities_and_services_share_keys() {
+        let registry = CapabilityRegistry::built_in();
+        for info in registry.services() {
+            let service_type = info.service_type().unwrap();
+            assert_eq!(AgentCapability::from(&service_type).key(), info.key);
+            assert_eq!(ServiceType::from(&AgentCapability::from(&service_type)), service_type);
+        }
+        assert_eq!(ServiceType::from_key("custom.translation"), Some(ServiceType::CustomService("translation".to_string())));
+        assert!(AgentCapability::MachineLearning.matches_service(&ServiceType::MachineLearning));
+    }
+
+    #[test]
+    fn test_custom_services_must_be_registered_and_satisfied() {
+        let mut registry = CapabilityRegistry::built_in();
+        let translation = ServiceType::CustomService("translation".to_string());
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let mut request = TransactionRequest::new(AgentId::new(), translation, "Translate".to_string(), Balance::from_sol(1.0), deadline);
+        assert!(registry.validate_request(&request).is_err());
+
+        registry
+            .register(CapabilityInfo::custom("translation", "Translation between languages").with_requirement("target_language", "ISO 639-1 code", true))
+            .unwrap();
+        assert!(registry.register(CapabilityInfo { key: "data_analysis".to_string(), ..CapabilityInfo::custom("x", "") }).is_err());
+        assert!(registry.validate_request(&request).is_err());
+
+        request.requirements.insert("target_language".to_string(), "fr".to_string());
+        request.requirements.insert(SLA_MAX_COMPLETION.to_string(), "600".to_string());
+        registry.apply_defaults(&mut request).unwrap();
+        assert_eq!(request.requirements[SLA_MAX_COMPLETION], "600");
+        assert_eq!(request.requirements[SLA_DISPUTE_WINDOW], "86400");
+    }
+}
@@ -0,0 +1,260 @@
+//! Provider Capacity
+//!
+//! Providers advertise how much more work they can take: a `CapacityAd`
+//! lists the services they offer, their free job slots, and the earliest
+//! time a new job could start, and is republished on the services' capacity
+//! topics whenever work is committed or finished. Requesters keep the latest
+//! ad per provider on a `CapacityBoard` and forecast whether a provider
+//! could admit a job before its deadline, so the matchmaker stops proposing
+//! to saturated providers and wasting negotiation rounds on them.
+//!
+//! Ads travel as gossip with the `CapacityAd` message type. Ads are ordered
+//! by publication time and then sequence number, so a late copy of an older
+//! ad never overwrites a newer one, and expire so a provider that went quiet
+//! is no longer ruled out.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    acp::MessageType,
+    error::{AgentError, Result},
+    types::{AgentId, ServiceType, Timestamp, TransactionId},
+};
+
+/// Prefix of the per-service capacity topics
+pub const CAPACITY_TOPIC_PREFIX: &str = "capacity";
+
+/// Job slots a provider has unless configured otherwise
+pub const DEFAULT_SLOTS: u32 = 4;
+
+/// How long an ad stays current, in seconds
+pub const AD_TTL_SECS: i64 = 5 * 60;
+
+/// Gossip topic carrying ads for a service type, e.g. `capacity.data_analysis`
+pub fn capacity_topic(service_type: &ServiceType) -> String {
+    format!("{}.{}", CAPACITY_TOPIC_PREFIX, service_type.key())
+}
+
+/// A provider's remaining capacity, as published
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapacityAd {
+    pub provider: AgentId,
+    pub capabilities: Vec<String>,        // Capability registry keys
+    pub total_slots: u32,
+    pub free_slots: u32,
+    pub earliest_start: Timestamp,        // When the next job could begin
+    pub sequence: u64,                    // Orders ads published in the same instant
+    pub published_at: Timestamp,
+    pub valid_until: Timestamp,
+}
+
+impl CapacityAd {
+    /// Topics the ad is published on, one per service offered
+    pub fn topics(&self) -> Vec<String> {
+        self.capabilities.iter().map(|key| format!("{}.{}", CAPACITY_TOPIC_PREFIX, key)).collect()
+    }
+
+    pub fn offers(&self, service_type: &ServiceType) -> bool {
+        self.capabilities.contains(&service_type.key())
+    }
+
+    pub fn is_current(&self, now: Timestamp) -> bool {
+        now.0 <= self.valid_until.0
+    }
+
+    /// Gossip message type carrying ads
+    pub fn message_type(&self) -> MessageType {
+        MessageType::CapacityAd
+    }
+
+    /// Gossip payload
+    pub fn to_payload(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    pub fn from_payload(payload: serde_json::Value) -> Result<Self> {
+        Ok(serde_json::from_value(payload)?)
+    }
+}
+
+/// Job slots a provider has committed, and until when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCapacity {
+    pub total_slots: u32,
+    committed: HashMap<TransactionId, Timestamp>,  // Job -> expected completion
+    sequence: u64,
+}
+
+impl Default for ProviderCapacity {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOTS)
+    }
+}
+
+impl ProviderCapacity {
+    pub fn new(total_slots: u32) -> Self {
+        Self { total_slots, committed: HashMap::new(), sequence: 0 }
+    }
+
+    pub fn free_slots(&self) -> u32 {
+        self.total_slots.saturating_sub(self.committed.len() as u32)
+    }
+
+    /// When a job accepted now could start: immediately with a free slot,
+    /// otherwise once the soonest committed job completes
+    pub fn earliest_start(&self, now: Timestamp) -> Timestamp {
+        if self.free_slots() > 0 {
+            return now;
+        }
+        let mut completions: Vec<Timestamp> = self.committed.values().copied().collect();
+        completions.sort();
+        // Every slot past the total must free up before a new one does
+        let overbooked = self.committed.len() - self.total_slots as usize;
+        completions.get(overbooked).copied().map_or(now, |soonest| soonest.max(now))
+    }
+
+    /// Take a slot for a job until its expected completion
+    ///
+    /// Committing a job already held only updates its completion.
+    pub fn commit(&mut self, transaction_id: TransactionId, completion: Timestamp) -> Result<()> {
+        if self.free_slots() == 0 && !self.committed.contains_key(&transaction_id) {
+            return Err(AgentError::InsufficientCapabilities.into());
+        }
+        self.committed.insert(transaction_id, completion);
+        Ok(())
+    }
+
+    /// Free a finished or abandoned job's slot. Returns false if it held none.
+    pub fn release(&mut self, transaction_id: &TransactionId) -> bool {
+        self.committed.remove(transaction_id).is_some()
+    }
+
+    /// Current capacity as a new ad, valid for `ttl`
+    pub fn advertise(&mut self, provider: AgentId, capabilities: Vec<String>, now: Timestamp, ttl: Duration) -> CapacityAd {
+        self.sequence += 1;
+        CapacityAd {
+            provider,
+            capabilities,
+            total_slots: self.total_slots,
+            free_slots: self.free_slots(),
+            earliest_start: self.earliest_start(now),
+            sequence: self.sequence,
+            published_at: now,
+            valid_until: Timestamp(now.0 + ttl),
+        }
+    }
+}
+
+/// Whether a provider could take a job, as forecast from its last ad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Admission {
+    Available,                            // Has a free slot now
+    Queued { starts_at: Timestamp },      // Full, but a slot frees up before the deadline
+    Saturated,                            // No slot before the deadline, or not offering the service
+    Unknown,                              // No current ad
+}
+
+impl Admission {
+    pub fn is_saturated(&self) -> bool {
+        matches!(self, Admission::Saturated)
+    }
+}
+
+/// Latest capacity ad from each provider, kept by requesters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapacityBoard {
+    ads: HashMap<AgentId, CapacityAd>,
+}
+
+impl CapacityBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an ad unless a newer one from the provider is already held.
+    /// Returns whether it was taken.
+    pub fn observe(&mut self, ad: CapacityAd) -> bool {
+        // Sequences restart with the provider, publication times do not
+        let newer = |held: &CapacityAd| (held.published_at, held.sequence) >= (ad.published_at, ad.sequence);
+        if self.ads.get(&ad.provider).is_some_and(newer) {
+            return false;
+        }
+        self.ads.insert(ad.provider, ad);
+        true
+    }
+
+    pub fn get(&self, provider: &AgentId) -> Option<&CapacityAd> {
+        self.ads.get(provider)
+    }
+
+    /// Forecast whether `provider` could start a `service_type` job before
+    /// `deadline`
+    pub fn admission(&self, provider: &AgentId, service_type: &ServiceType, deadline: Timestamp, now: Timestamp) -> Admission {
+        let Some(ad) = self.ads.get(provider).filter(|ad| ad.is_current(now)) else {
+            return Admission::Unknown;
+        };
+        if !ad.offers(service_type) {
+            return Admission::Saturated;
+        }
+        if ad.free_slots > 0 {
+            return Admission::Available;
+        }
+        let starts_at = ad.earliest_start.max(now);
+        if starts_at.0 < deadline.0 {
+            Admission::Queued { starts_at }
+        } else {
+            Admission::Saturated
+        }
+    }
+
+    /// Candidates worth proposing to, in their original order
+    pub fn admissible(&self, candidates: &[AgentId], service_type: &ServiceType, deadline: Timestamp, now: Timestamp) -> Vec<AgentId> {
+        candidates
+            .iter()
+            .filter(|provider| !self.admission(provider, service_type, deadline, now).is_saturated())
+            .copied()
+            .collect()
+    }
+
+    /// Drop ads that have expired
+    pub fn prune(&mut self, now: Timestamp) {
+        self.ads.retain(|_, ad| ad.is_current(now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saturated_providers_are_not_admissible() {
+        let now = Timestamp::now();
+        let service = ServiceType::DataAnalysis;
+        let (busy, free, quiet) = (AgentId::new(), AgentId::new(), AgentId::new());
+
+        let mut capacity = ProviderCapacity::new(2);
+        capacity.commit(TransactionId::new(), Timestamp(now.0 + Duration::hours(3))).unwrap();
+        capacity.commit(TransactionId::new(), Timestamp(now.0 + Duration::hours(2))).unwrap();
+        assert!(capacity.commit(TransactionId::new(), now).is_err());
+        let full = capacity.advertise(busy, vec![service.key()], now, Duration::minutes(5));
+        assert_eq!(full.free_slots, 0);
+        assert_eq!(full.earliest_start, Timestamp(now.0 + Duration::hours(2)));
+
+        let mut board = CapacityBoard::new();
+        assert!(board.observe(full.clone()));
+        board.observe(ProviderCapacity::new(1).advertise(free, vec![service.key()], now, Duration::minutes(5)));
+
+        let soon = Timestamp(now.0 + Duration::hours(1));
+        let later = Timestamp(now.0 + Duration::hours(4));
+        assert_eq!(board.admissible(&[busy, free, quiet], &service, soon, now), vec![free, quiet]);
+        assert_eq!(board.admission(&busy, &service, later, now), Admission::Queued { starts_at: full.earliest_start });
+        assert!(board.admission(&free, &ServiceType::TradingService, later, now).is_saturated());
+
+        // A stale copy does not undo the provider's newer ad
+        let stale = CapacityAd { sequence: 0, free_slots: 2, ..full.clone() };
+        assert!(!board.observe(stale));
+        assert_eq!(board.get(&busy), Some(&full));
+    }
+}
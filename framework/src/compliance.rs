@@ -0,0 +1,292 @@
+//! Negotiation Protocol Compliance
+//!
+//! Inbound negotiation messages are checked against the protocol rules
+//! before the strategy or advisor sees them: offers must come within the
+//! round limit, be positive finite prices, and, if required, concede
+//! monotonically toward our ask; proposals must carry well-formed terms and
+//! sane deadlines. Every breach is recorded against the counterparty. In
+//! `Flag` mode the message still goes through; in `Reject` mode it is
+//! refused, and a counterparty with `ban_after` breaches is refused outright.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    error::{Result, TransactionError},
+    negotiation::NegotiationSession,
+    reputation::{ReputationEvent, ReputationEventType, ReputationWeight},
+    transaction::TransactionProposal,
+    types::{AgentId, Timestamp, TransactionId},
+};
+
+/// Prices within this of each other count as equal
+const PRICE_EPSILON: f64 = 1e-9;
+
+/// What happens to a message that breaks the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComplianceMode {
+    Flag,
+    Reject,
+}
+
+/// Shape of a proposal's `terms`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TermsSchema {
+    pub required: Vec<String>,
+    pub allowed: Option<Vec<String>>,     // Any key if unset
+    pub max_value_len: usize,             // Unlimited if zero
+}
+
+/// Protocol rules inbound negotiation messages are held to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceRules {
+    pub mode: ComplianceMode,
+    pub max_rounds: u32,
+    pub monotonic_concessions: bool,      // Offers may not move away from our ask
+    pub terms: TermsSchema,
+    pub max_proposal_validity_secs: u64,  // Furthest a proposal may set its expiry
+    pub ban_after: Option<u32>,           // Breaches before a counterparty is refused outright
+}
+
+impl Default for ComplianceRules {
+    fn default() -> Self {
+        Self {
+            mode: ComplianceMode::Flag,
+            max_rounds: crate::constants::MAX_NEGOTIATION_ROUNDS,
+            monotonic_concessions: false,
+            terms: TermsSchema::default(),
+            max_proposal_validity_secs: 7 * 24 * 60 * 60,
+            ban_after: None,
+        }
+    }
+}
+
+/// Which rule a message broke
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ViolationKind {
+    RoundLimitExceeded { round: u32, max_rounds: u32 },
+    InvalidPrice { price: f64 },
+    ConcessionReversed { previous: f64, offer: f64 },
+    InvalidTerms { reason: String },
+    InvalidDeadline { reason: String },
+}
+
+/// A rule broken by a counterparty
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceViolation {
+    pub transaction_id: TransactionId,
+    pub offender: AgentId,
+    pub kind: ViolationKind,
+    pub detected_at: Timestamp,
+}
+
+impl ComplianceViolation {
+    /// Reputation penalty for the offender
+    pub fn reputation_event(&self, penalty: f64) -> ReputationEvent {
+        ReputationEvent {
+            timestamp: self.detected_at,
+            event_type: ReputationEventType::ProtocolViolation,
+            weight: ReputationWeight::Low,
+            delta: -penalty.abs(),
+            counterparty: None,
+        }
+    }
+}
+
+/// Checks inbound negotiation messages and keeps each counterparty's record
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComplianceChecker {
+    pub rules: ComplianceRules,
+    violations: HashMap<AgentId, Vec<ComplianceViolation>>,
+}
+
+impl ComplianceChecker {
+    pub fn new(rules: ComplianceRules) -> Self {
+        Self { rules, violations: HashMap::new() }
+    }
+
+    /// Check a counter-offer before it is recorded in the session
+    pub fn check_offer(&mut self, session: &NegotiationSession, offer: f64, now: Timestamp) -> Result<()> {
+        let mut kinds = Vec::new();
+        let round = session.state.their_offers.len() as u32 + 1;
+        if round > self.rules.max_rounds {
+            kinds.push(ViolationKind::RoundLimitExceeded { round, max_rounds: self.rules.max_rounds });
+        }
+        if !offer.is_finite() || offer <= 0.0 {
+            kinds.push(ViolationKind::InvalidPrice { price: offer });
+        } else if self.rules.monotonic_concessions {
+            if let (Some(&previous), Some(&ask)) = (session.state.their_offers.last(), session.state.our_asks.last()) {
+                if (ask - offer).abs() > (ask - previous).abs() + PRICE_EPSILON {
+                    kinds.push(ViolationKind::ConcessionReversed { previous, offer });
+                }
+            }
+        }
+        if session.request_deadline().is_some_and(|deadline| now > deadline) {
+            kinds.push(ViolationKind::InvalidDeadline { reason: "offer arrived after the request deadline".to_string() });
+        }
+        self.judge(session.transaction_id, session.counterparty, kinds, now)
+    }
+
+    /// Check a provider's proposal, against the request deadline if known
+    pub fn check_proposal(&mut self, proposal: &TransactionProposal, request_deadline: Option<Timestamp>, now: Timestamp) -> Result<()> {
+        let mut kinds = Vec::new();
+        if let Err(reason) = self.check_terms(proposal) {
+            kinds.push(ViolationKind::InvalidTerms { reason });
+        }
+        let latest_expiry = now.0 + chrono::Duration::seconds(self.rules.max_proposal_validity_secs as i64);
+        let deadline_problem = if proposal.expires_at <= now {
+            Some("proposal has already expired".to_string())
+        } else if proposal.expires_at.0 > latest_expiry {
+            Some(format!("proposal stays open longer than {}s", self.rules.max_proposal_validity_secs))
+        } else if proposal.estimated_completion < now {
+            Some("estimated completion is in the past".to_string())
+        } else if request_deadline.is_some_and(|deadline| proposal.estimated_completion > deadline) {
+            Some("estimated completion is after the request deadline".to_string())
+        } else {
+            None
+        };
+        if let Some(reason) = deadline_problem {
+            kinds.push(ViolationKind::InvalidDeadline { reason });
+        }
+        self.judge(proposal.request_id, proposal.provider, kinds, now)
+    }
+
+    fn check_terms(&self, proposal: &TransactionProposal) -> std::result::Result<(), String> {
+        let schema = &self.rules.terms;
+        if let Some(missing) = schema.required.iter().find(|key| !proposal.terms.contains_key(*key)) {
+            return Err(format!("missing term {}", missing));
+        }
+        for (key, value) in &proposal.terms {
+            if schema.allowed.as_ref().is_some_and(|allowed| !allowed.contains(key) && !schema.required.contains(key)) {
+                return Err(format!("unknown term {}", key));
+            }
+            if key.trim().is_empty() || value.trim().is_empty() {
+                return Err(format!("empty term {:?}", key));
+            }
+            if schema.max_value_len > 0 && value.len() > schema.max_value_len {
+                return Err(format!("term {} is longer than {} bytes", key, schema.max_value_len));
+            }
+        }
+        Ok(())
+    }
+
+    /// Record any breaches and decide whether the message goes through
+    fn judge(&mut self, transaction_id: TransactionId, offender: AgentId, kinds: Vec<ViolationKind>, now: Timestamp) -> Result<()> {
+        let banned = self.is_banned(&offender);
+        if kinds.is_empty() && !banned {
+            return Ok(());
+        }
+        let reasons: Vec<String> = kinds.iter().map(|kind| format!("{:?}", kind)).collect();
+        let record = self.violations.entry(offender).or_default();
+        record.extend(kinds.into_iter().map(|kind| ComplianceViolation { transaction_id, offender, kind, detected_at: now }));
+
+        if banned {
+            return Err(TransactionError::ProtocolViolation {
+                reason: format!("{} is refused after {} violations", offender, record.len()),
+            }.into());
+        }
+        match self.rules.mode {
+            ComplianceMode::Flag => {
+                tracing::warn!(%transaction_id, %offender, "Non-compliant negotiation message: {}", reasons.join(", "));
+                Ok(())
+            }
+            ComplianceMode::Reject => Err(TransactionError::ProtocolViolation { reason: reasons.join(", ") }.into()),
+        }
+    }
+
+    /// Whether a counterparty has broken the rules too often to deal with
+    pub fn is_banned(&self, counterparty: &AgentId) -> bool {
+        self.rules.ban_after.is_some_and(|limit| self.violation_count(counterparty) >= limit as usize)
+    }
+
+    pub fn violations(&self, counterparty: &AgentId) -> &[ComplianceViolation] {
+        self.violations.get(counterparty).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn violation_count(&self, counterparty: &AgentId) -> usize {
+        self.violations(counterparty).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Balance;
+    use solace_ai::strategy::NegotiationState;
+    use solace_ai::{DecisionContext, MarketConditions};
+
+    fn session(max_rounds: u32) -> NegotiationSession {
+        let state = NegotiationState::new(
+            DecisionContext {
+                agent_reputation: 0.5,
+                counterparty_reputation: 0.5,
+                transaction_value: 10.0,
+                market_conditions: MarketConditions {
+                    demand_level: 0.5,
+                    competition_level: 0.5,
+                    average_pricing: 10.0,
+                    risk_indicators: vec![],
+                },
+                historical_performance: vec![],
+                counterparty_profile: None,
+                time_pressure: None,
+            },
+            10.0,
+            max_rounds,
+        );
+        NegotiationSession::new(TransactionId::new(), AgentId::new(), state, 60)
+    }
+
+    #[test]
+    fn test_offers_are_checked_before_the_strategy() {
+        let now = Timestamp::now();
+        let mut checker = ComplianceChecker::new(ComplianceRules {
+            mode: ComplianceMode::Reject,
+            max_rounds: 2,
+            monotonic_concessions: true,
+            ..ComplianceRules::default()
+        });
+        let mut session = session(5);
+        session.state.our_asks.push(12.0);
+        session.state.their_offers.push(9.0);
+
+        assert!(checker.check_offer(&session, 10.0, now).is_ok());
+        assert!(checker.check_offer(&session, 8.0, now).is_err());
+        assert!(checker.check_offer(&session, f64::NAN, now).is_err());
+        session.state.their_offers.push(10.0);
+        assert!(checker.check_offer(&session, 11.0, now).is_err());
+
+        let kinds: Vec<_> = checker.violations(&session.counterparty).iter().map(|v| v.kind.clone()).collect();
+        assert_eq!(kinds[0], ViolationKind::ConcessionReversed { previous: 9.0, offer: 8.0 });
+        assert_eq!(kinds[2], ViolationKind::RoundLimitExceeded { round: 3, max_rounds: 2 });
+    }
+
+    #[test]
+    fn test_flagged_counterparties_are_banned_at_the_limit() {
+        let now = Timestamp::now();
+        let mut checker = ComplianceChecker::new(ComplianceRules {
+            terms: TermsSchema { required: vec!["delivery_format".to_string()], allowed: None, max_value_len: 64 },
+            ban_after: Some(2),
+            ..ComplianceRules::default()
+        });
+        let mut proposal = TransactionProposal {
+            id: TransactionId::new(),
+            request_id: TransactionId::new(),
+            provider: AgentId::new(),
+            proposed_price: Balance::from_sol(1.0),
+            estimated_completion: Timestamp(now.0 + chrono::Duration::hours(2)),
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: now,
+            expires_at: Timestamp(now.0 + chrono::Duration::hours(1)),
+        };
+        let deadline = Some(Timestamp(now.0 + chrono::Duration::hours(1)));
+
+        // Flagged, not refused: missing term, and completion after the deadline
+        assert!(checker.check_proposal(&proposal, deadline, now).is_ok());
+        assert_eq!(checker.violation_count(&proposal.provider), 2);
+
+        proposal.terms.insert("delivery_format".to_string(), "csv".to_string());
+        assert!(checker.check_proposal(&proposal, None, now).is_err());
+    }
+}
@@ -3,10 +3,17 @@
 //! Implements a Proof-of-Reputation consensus algorithm specifically designed
 //! for autonomous agent networks. This consensus mechanism considers agent
 //! reputation, stake, and participation history to determine block producers.
+//!
+//! Callers that need to follow the engine can use
+//! `ConsensusEngine::subscribe` rather than polling its state: each receiver
+//! sees every `ConsensusEvent` published after it subscribed, and one that
+//! falls more than `DEFAULT_CONSENSUS_EVENT_CAPACITY` behind is told how
+//! many it missed.
 
 use std::collections::{HashMap, BTreeMap, VecDeque};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
 use anyhow::Result;
 use tracing::{info, warn, debug, error};
 
@@ -80,7 +87,7 @@ impl Validator {
 
         let stake_normalized = self.stake as f64 / 1_000_000.0; // Normalize to millions
         let reputation_component = self.reputation * config.reputation_weight;
-        let stake_component = stake_normalized.ln() * config.stake_weight;
+        let stake_component = stake_normalized.ln_1p() * config.stake_weight;
         
         // Apply penalties
         let consecutive_penalty = if self.consecutive_blocks >= config.max_consecutive_blocks {
@@ -135,6 +142,39 @@ pub struct Epoch {
     pub block_producers: BTreeMap<u64, AgentId>,
 }
 
+/// Events kept per subscriber before the oldest are dropped
+pub const DEFAULT_CONSENSUS_EVENT_CAPACITY: usize = 1024;
+
+/// Something the consensus engine decided
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConsensusEvent {
+    BlockFinalized { header: BlockHeader, hash: Hash },
+    EpochStarted { epoch: Epoch, validators: Vec<Validator> },  // Validators in selection order
+    ValidatorSlashed { agent_id: AgentId, reason: String, stake: u64, deactivated: bool },  // Stake after the slash
+    VoteQuorumReached { block_hash: Hash, block_height: u64, approvals: usize, required: usize },
+}
+
+/// Application logic run at an epoch boundary with the epoch's descriptor
+pub type EpochHook = Box<dyn Fn(&Epoch) -> Result<()> + Send + Sync>;
+
+/// Hooks run once per epoch, in registration order
+#[derive(Default)]
+struct EpochHooks {
+    on_start: Vec<EpochHook>,
+    on_end: Vec<EpochHook>,
+}
+
+impl EpochHooks {
+    /// Run every hook; one failing does not stop the others or the epoch change
+    fn run(hooks: &[EpochHook], stage: &str, epoch: &Epoch) {
+        for hook in hooks {
+            if let Err(e) = hook(epoch) {
+                error!("Epoch {} {} hook failed: {}", epoch.number, stage, e);
+            }
+        }
+    }
+}
+
 /// Consensus engine implementation
 pub struct ConsensusEngine {
     config: ConsensusConfig,
@@ -143,6 +183,8 @@ pub struct ConsensusEngine {
     pending_votes: HashMap<Hash, Vec<ConsensusVote>>,
     block_history: VecDeque<BlockHeader>,
     validator_performance: HashMap<AgentId, ValidatorPerformance>,
+    epoch_hooks: EpochHooks,
+    events: broadcast::Sender<ConsensusEvent>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -158,7 +200,6 @@ impl ConsensusEngine {
     /// Create a new consensus engine
     pub fn new(config: ConsensusConfig) -> Self {
         Self {
-            config,
             validators: HashMap::new(),
             current_epoch: Epoch {
                 number: 0,
@@ -167,12 +208,48 @@ impl ConsensusEngine {
                 validators: Vec::new(),
                 block_producers: BTreeMap::new(),
             },
+            config,
             pending_votes: HashMap::new(),
             block_history: VecDeque::new(),
             validator_performance: HashMap::new(),
+            epoch_hooks: EpochHooks::default(),
+            events: broadcast::channel(DEFAULT_CONSENSUS_EVENT_CAPACITY).0,
         }
     }
 
+    /// Receiver of every event the engine publishes from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ConsensusEvent> {
+        self.events.subscribe()
+    }
+
+    /// Publish to every subscriber; dropped if there are none
+    fn publish(&self, event: ConsensusEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Run `hook` as each epoch starts, after its validators are selected.
+    /// Epoch 0 starts with the engine, so hooks first see epoch 1.
+    pub fn on_epoch_start<F>(&mut self, hook: F)
+    where
+        F: Fn(&Epoch) -> Result<()> + Send + Sync + 'static,
+    {
+        self.epoch_hooks.on_start.push(Box::new(hook));
+    }
+
+    /// Run `hook` as each epoch ends, with the block producers it recorded,
+    /// before the next epoch's validators are selected
+    pub fn on_epoch_end<F>(&mut self, hook: F)
+    where
+        F: Fn(&Epoch) -> Result<()> + Send + Sync + 'static,
+    {
+        self.epoch_hooks.on_end.push(Box::new(hook));
+    }
+
+    /// The epoch blocks are currently produced in
+    pub fn current_epoch(&self) -> &Epoch {
+        &self.current_epoch
+    }
+
     /// Register a new validator
     pub fn register_validator(&mut self, agent_id: AgentId, stake: u64, reputation: f64) -> Result<()> {
         if stake < self.config.min_validator_stake {
@@ -314,21 +391,38 @@ impl ConsensusEngine {
         debug!("Processed vote from validator {} for block {}", 
             vote.voter, vote.block_hash);
 
+        // Announce the quorum once, on the approval that reaches it
+        if matches!(vote.vote_type, VoteType::Approve) {
+            let approvals = self.approvals(&vote.block_hash);
+            let required = self.required_approvals();
+            if approvals == required {
+                self.publish(ConsensusEvent::VoteQuorumReached {
+                    block_hash: vote.block_hash,
+                    block_height: vote.block_height,
+                    approvals,
+                    required,
+                });
+            }
+        }
+
         Ok(())
     }
 
     /// Check if a block has enough votes to be finalized
     pub fn check_finalization(&self, block_hash: &Hash) -> bool {
-        if let Some(votes) = self.pending_votes.get(block_hash) {
-            let approve_votes = votes.iter()
-                .filter(|vote| matches!(vote.vote_type, VoteType::Approve))
-                .count();
+        self.approvals(block_hash) >= self.required_approvals()
+    }
 
-            let required_votes = (self.current_epoch.validators.len() * 2) / 3 + 1;
-            approve_votes >= required_votes
-        } else {
-            false
-        }
+    /// Approving votes pending for a block
+    fn approvals(&self, block_hash: &Hash) -> usize {
+        self.pending_votes
+            .get(block_hash)
+            .map_or(0, |votes| votes.iter().filter(|vote| matches!(vote.vote_type, VoteType::Approve)).count())
+    }
+
+    /// Approvals needed to finalize a block: more than two thirds of the epoch's validators
+    fn required_approvals(&self) -> usize {
+        (self.current_epoch.validators.len() * 2) / 3 + 1
     }
 
     /// Finalize a block and update validator state
@@ -359,22 +453,29 @@ impl ConsensusEngine {
             self.block_history.pop_front();
         }
 
-        // Check if we need to start a new epoch
-        if header.height >= self.current_epoch.end_block {
-            self.start_new_epoch(header.height + 1)?;
-        }
+        // Record the producer for the epoch's end-of-epoch hooks
+        self.current_epoch.block_producers.insert(header.height, header.producer);
 
         // Clean up old votes
         let block_hash = self.calculate_block_hash(&header);
         self.pending_votes.remove(&block_hash);
 
         info!("Finalized block {} produced by {}", header.height, header.producer);
+        let height = header.height;
+        self.publish(ConsensusEvent::BlockFinalized { header, hash: block_hash });
+
+        // Check if we need to start a new epoch
+        if height >= self.current_epoch.end_block {
+            self.start_new_epoch(height + 1)?;
+        }
 
         Ok(())
     }
 
     /// Start a new epoch with validator rotation
     fn start_new_epoch(&mut self, start_block: u64) -> Result<()> {
+        EpochHooks::run(&self.epoch_hooks.on_end, "end", &self.current_epoch);
+
         let new_epoch_number = self.current_epoch.number + 1;
         let selected_validators = self.select_validators_for_epoch(new_epoch_number)?;
 
@@ -394,6 +495,14 @@ impl ConsensusEngine {
         info!("Started epoch {} with {} validators", 
             new_epoch_number, self.current_epoch.validators.len());
 
+        EpochHooks::run(&self.epoch_hooks.on_start, "start", &self.current_epoch);
+
+        let validators = self.current_epoch.validators
+            .iter()
+            .filter_map(|agent_id| self.validators.get(agent_id).cloned())
+            .collect();
+        self.publish(ConsensusEvent::EpochStarted { epoch: self.current_epoch.clone(), validators });
+
         Ok(())
     }
 
@@ -403,11 +512,19 @@ impl ConsensusEngine {
             validator.slashing_events += 1;
             validator.stake = (validator.stake as f64 * 0.9) as u64; // 10% slash
             
-            if validator.stake < self.config.min_validator_stake {
+            let deactivated = validator.is_active && validator.stake < self.config.min_validator_stake;
+            if deactivated {
                 validator.is_active = false;
             }
+            let stake = validator.stake;
 
             warn!("Slashed validator {} for: {}", agent_id, reason);
+            self.publish(ConsensusEvent::ValidatorSlashed {
+                agent_id: *agent_id,
+                reason: reason.to_string(),
+                stake,
+                deactivated,
+            });
         } else {
             return Err(SolaceError::ValidatorNotFound(agent_id.clone()).into());
         }
@@ -490,6 +607,105 @@ mod tests {
         assert!(engine.validators.contains_key(&agent_id));
     }
 
+    #[test]
+    fn test_epoch_hooks_run_once_per_boundary() {
+        use std::sync::{Arc, Mutex};
+
+        let config = ConsensusConfig { epoch_duration: 3, ..ConsensusConfig::default() };
+        let mut engine = ConsensusEngine::new(config);
+        let producer = AgentId::new();
+        engine.register_validator(producer, 5000, 0.7).unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        engine.on_epoch_end(move |epoch| {
+            log.lock().unwrap().push(format!("end {} ({} blocks)", epoch.number, epoch.block_producers.len()));
+            Ok(())
+        });
+        let log = seen.clone();
+        engine.on_epoch_start(move |epoch| {
+            log.lock().unwrap().push(format!("start {} ({} validators)", epoch.number, epoch.validators.len()));
+            Ok(())
+        });
+        engine.on_epoch_start(|_| Err(anyhow::anyhow!("a failing hook does not stop the epoch")));
+
+        for height in 0..=7 {
+            let header = BlockHeader {
+                height,
+                previous_hash: String::new(),
+                merkle_root: String::new(),
+                timestamp: SystemTime::now(),
+                producer,
+                epoch: engine.current_epoch().number,
+                nonce: 0,
+            };
+            engine.finalize_block(header).unwrap();
+        }
+
+        assert_eq!(engine.current_epoch().number, 2);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["end 0 (4 blocks)", "start 1 (1 validators)", "end 1 (4 blocks)", "start 2 (1 validators)"],
+        );
+    }
+
+    #[test]
+    fn test_subscribers_see_consensus_decisions() {
+        let config = ConsensusConfig { epoch_duration: 1, ..ConsensusConfig::default() };
+        let mut engine = ConsensusEngine::new(config);
+        let validators: Vec<AgentId> = (0..3).map(|_| AgentId::new()).collect();
+        for (i, validator) in validators.iter().enumerate() {
+            engine.register_validator(*validator, 1000 + i as u64 * 1000, 0.8).unwrap();
+        }
+        let mut events = engine.subscribe();
+
+        for height in 0..=1 {
+            let header = BlockHeader {
+                height,
+                previous_hash: String::new(),
+                merkle_root: String::new(),
+                timestamp: SystemTime::now(),
+                producer: validators[2],
+                epoch: engine.current_epoch().number,
+                nonce: 0,
+            };
+            engine.finalize_block(header).unwrap();
+        }
+        assert!(matches!(events.try_recv().unwrap(), ConsensusEvent::BlockFinalized { header, .. } if header.height == 0));
+        assert!(matches!(events.try_recv().unwrap(), ConsensusEvent::BlockFinalized { header, .. } if header.height == 1));
+        match events.try_recv().unwrap() {
+            ConsensusEvent::EpochStarted { epoch, validators: selected } => {
+                assert_eq!(epoch.number, 1);
+                assert_eq!(selected.len(), 3);
+                assert_eq!(selected[0].agent_id, validators[2]);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        // Quorum is announced once, on the third of three approvals
+        for voter in &validators {
+            engine.process_vote(ConsensusVote {
+                block_hash: "block-2".to_string(),
+                block_height: 2,
+                voter: *voter,
+                vote_type: VoteType::Approve,
+                timestamp: SystemTime::now(),
+                signature: Signature::from_bytes(&[0; 64]).unwrap(),
+            }).unwrap();
+        }
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            ConsensusEvent::VoteQuorumReached { block_height: 2, approvals: 3, required: 3, .. }
+        ));
+
+        engine.slash_validator(&validators[0], "double signing").unwrap();
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            ConsensusEvent::ValidatorSlashed { stake: 900, deactivated: true, .. }
+        ));
+        assert!(events.try_recv().is_err());
+    }
+
     #[test]
     fn test_insufficient_stake_rejection() {
         let mut engine = ConsensusEngine::new(ConsensusConfig::default());
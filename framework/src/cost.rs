@@ -0,0 +1,205 @@
+//! Execution Cost Model
+//!
+//! Providers running on metered compute pay for every CPU and GPU second a
+//! service consumes. The cost model estimates those seconds per service type,
+//! prices them at configurable USD rates, and converts the result to SOL so
+//! negotiation can keep a minimum margin over cost and requests whose budget
+//! cannot cover it are turned away before any haggling starts.
+//!
+//! Network fees an operator pays on behalf of sponsored agents are tracked in
+//! a `SponsoredFees` ledger, so the operator can see what each agent costs.
+
+use serde::{Deserialize, Serialize};
+use solace_ai::ExecutionCost;
+use std::collections::HashMap;
+
+use crate::{
+    error::{Result, TransactionError},
+    transaction::TransactionRequest,
+    types::{Balance, ServiceType},
+};
+
+/// Estimated compute a service consumes per request
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResourceEstimate {
+    pub cpu_seconds: f64,
+    pub gpu_seconds: f64,
+}
+
+impl ResourceEstimate {
+    pub fn new(cpu_seconds: f64, gpu_seconds: f64) -> Self {
+        Self { cpu_seconds, gpu_seconds }
+    }
+}
+
+/// Prices of compute resources
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResourceRates {
+    pub cpu_usd_per_second: f64,
+    pub gpu_usd_per_second: f64,
+    pub usd_per_sol: f64,                 // Exchange rate for converting costs to SOL
+}
+
+impl Default for ResourceRates {
+    fn default() -> Self {
+        Self {
+            cpu_usd_per_second: 0.00005,
+            gpu_usd_per_second: 0.0008,
+            usd_per_sol: 150.0,
+        }
+    }
+}
+
+/// Per-service resource cost model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostModel {
+    pub rates: ResourceRates,
+    pub estimates: HashMap<ServiceType, ResourceEstimate>,
+    pub default_estimate: ResourceEstimate,   // For services without an estimate
+    pub min_margin: f64,                      // Required margin over cost, as a fraction
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        let estimates = HashMap::from([
+            (ServiceType::DataAnalysis, ResourceEstimate::new(600.0, 0.0)),
+            (ServiceType::ComputationalTask, ResourceEstimate::new(1_800.0, 600.0)),
+            (ServiceType::MarketResearch, ResourceEstimate::new(300.0, 0.0)),
+            (ServiceType::ContentCreation, ResourceEstimate::new(120.0, 300.0)),
+            (ServiceType::TradingService, ResourceEstimate::new(60.0, 0.0)),
+            (ServiceType::MachineLearning, ResourceEstimate::new(3_600.0, 1_200.0)),
+        ]);
+        Self {
+            rates: ResourceRates::default(),
+            estimates,
+            default_estimate: ResourceEstimate::new(300.0, 0.0),
+            min_margin: 0.1,
+        }
+    }
+}
+
+impl CostModel {
+    /// Set the resource estimate for a service type
+    pub fn with_estimate(mut self, service_type: ServiceType, estimate: ResourceEstimate) -> Self {
+        self.estimates.insert(service_type, estimate);
+        self
+    }
+
+    /// Resource estimate for a service type
+    pub fn estimate(&self, service_type: &ServiceType) -> ResourceEstimate {
+        self.estimates.get(service_type).copied().unwrap_or(self.default_estimate)
+    }
+
+    /// Estimated cost of one request in USD
+    pub fn estimated_cost_usd(&self, service_type: &ServiceType) -> f64 {
+        let estimate = self.estimate(service_type);
+        estimate.cpu_seconds * self.rates.cpu_usd_per_second + estimate.gpu_seconds * self.rates.gpu_usd_per_second
+    }
+
+    /// Estimated cost of one request
+    pub fn estimated_cost(&self, service_type: &ServiceType) -> Balance {
+        Balance::from_sol(self.estimated_cost_usd(service_type) / self.rates.usd_per_sol)
+    }
+
+    /// Lowest price that covers the estimated cost plus the minimum margin
+    pub fn minimum_price(&self, service_type: &ServiceType) -> Balance {
+        Balance::from_sol(self.execution_cost(service_type).floor())
+    }
+
+    /// Cost and margin for the negotiation layer, in SOL
+    pub fn execution_cost(&self, service_type: &ServiceType) -> ExecutionCost {
+        ExecutionCost::new(self.estimated_cost(service_type).to_sol(), self.min_margin)
+    }
+
+    /// Refuse a request whose budget cannot cover cost plus margin
+    pub fn check_budget(&self, request: &TransactionRequest) -> Result<()> {
+        let required = self.minimum_price(&request.service_type);
+        if request.budget.0 < required.0 {
+            return Err(TransactionError::BudgetBelowCost {
+                budget: request.budget.to_string(),
+                required: required.to_string(),
+            }.into());
+        }
+        Ok(())
+    }
+}
+
+/// Transaction fees paid by a sponsor, per sponsored agent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SponsoredFees {
+    by_agent: HashMap<String, u64>,       // Agent account -> lamports paid for it
+    transactions: u64,
+}
+
+impl SponsoredFees {
+    /// Record a fee paid for one of `agent`'s transactions
+    pub fn record(&mut self, agent: &str, lamports: u64) {
+        *self.by_agent.entry(agent.to_string()).or_default() += lamports;
+        self.transactions += 1;
+    }
+
+    /// Fees paid on behalf of `agent`
+    pub fn agent_total(&self, agent: &str) -> Balance {
+        Balance(self.by_agent.get(agent).copied().unwrap_or(0))
+    }
+
+    /// Fees paid on behalf of every agent
+    pub fn total(&self) -> Balance {
+        Balance(self.by_agent.values().sum())
+    }
+
+    /// Sponsored transactions recorded
+    pub fn transactions(&self) -> u64 {
+        self.transactions
+    }
+
+    /// Agents ordered by the fees paid for them, highest first
+    pub fn by_agent(&self) -> Vec<(String, Balance)> {
+        let mut agents: Vec<(String, Balance)> = self.by_agent.iter().map(|(agent, fees)| (agent.clone(), Balance(*fees))).collect();
+        agents.sort_by(|a, b| b.1.0.cmp(&a.1.0).then_with(|| a.0.cmp(&b.0)));
+        agents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgentId, Timestamp};
+
+    fn request(service_type: ServiceType, budget_sol: f64) -> TransactionRequest {
+        TransactionRequest::new(
+            AgentId::new(),
+            service_type,
+            "test".to_string(),
+            Balance::from_sol(budget_sol),
+            Timestamp::now(),
+        )
+    }
+
+    #[test]
+    fn test_costs_follow_estimates_and_rates() {
+        let model = CostModel {
+            rates: ResourceRates { cpu_usd_per_second: 0.01, gpu_usd_per_second: 0.1, usd_per_sol: 100.0 },
+            min_margin: 0.5,
+            ..CostModel::default()
+        }
+        .with_estimate(ServiceType::ComputationalTask, ResourceEstimate::new(1_000.0, 100.0));
+
+        assert_eq!(model.estimated_cost_usd(&ServiceType::ComputationalTask), 20.0);
+        assert_eq!(model.estimated_cost(&ServiceType::ComputationalTask), Balance::from_sol(0.2));
+        assert!((model.minimum_price(&ServiceType::ComputationalTask).to_sol() - 0.3).abs() < 1e-9);
+
+        let custom = ServiceType::CustomService("translation".to_string());
+        assert_eq!(model.estimate(&custom), model.default_estimate);
+    }
+
+    #[test]
+    fn test_budget_must_cover_cost_and_margin() {
+        let model = CostModel::default();
+        let minimum = model.minimum_price(&ServiceType::ComputationalTask).to_sol();
+
+        assert!(model.check_budget(&request(ServiceType::ComputationalTask, minimum * 2.0)).is_ok());
+        let err = model.check_budget(&request(ServiceType::ComputationalTask, minimum / 2.0)).unwrap_err();
+        assert!(err.to_string().contains("cannot cover"));
+    }
+}
@@ -1,6 +1,13 @@
 //! Cryptographic utilities for the Solace Protocol
+//!
+//! Besides single-key signing, agents can hold a treasury in a
+//! `MultisigWallet`: spending from it needs signatures from `threshold` of
+//! its signers, each sent as a `PartialSignature` and collected until enough
+//! distinct signers approved the same message.
 
 use crate::error::{CryptoError, Result};
+use crate::SolaceError;
+use crate::types::AgentId;
 use ed25519_dalek::{Signature as Ed25519Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -61,6 +68,111 @@ impl Signature {
     }
 }
 
+/// One signer's signature over a message its multisig wallet must approve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub signer: [u8; 32],                 // Signer's public key
+    pub signature: Signature,
+}
+
+impl PartialSignature {
+    pub fn sign(keypair: &KeyPair, message: &[u8]) -> Self {
+        Self {
+            signer: keypair.verifying_key().to_bytes(),
+            signature: keypair.sign(message),
+        }
+    }
+}
+
+/// Treasury that spends only with signatures from `threshold` of its `signers`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigWallet {
+    pub signers: Vec<[u8; 32]>,           // Public keys allowed to approve
+    pub threshold: usize,                 // Distinct approvals a spend needs
+}
+
+impl MultisigWallet {
+    /// An m-of-n wallet over distinct signer keys
+    pub fn new(signers: &[VerifyingKey], threshold: usize) -> Result<Self> {
+        let signers: Vec<[u8; 32]> = signers.iter().map(VerifyingKey::to_bytes).collect();
+        let mut distinct = signers.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() != signers.len() {
+            return Err(SolaceError::config("Multisig signers must be distinct"));
+        }
+        if threshold == 0 || threshold > signers.len() {
+            return Err(SolaceError::config(format!(
+                "Multisig threshold {} is out of range for {} signers", threshold, signers.len()
+            )));
+        }
+        Ok(Self { signers, threshold })
+    }
+
+    pub fn is_signer(&self, key: &[u8; 32]) -> bool {
+        self.signers.contains(key)
+    }
+
+    /// Add a share to those `collected` for `message` once it verifies,
+    /// replacing its signer's share over any other message. Returns false
+    /// if the signer already approved `message`.
+    pub fn collect(&self, message: &[u8], collected: &mut Vec<PartialSignature>, share: PartialSignature) -> Result<bool> {
+        self.verify_share(message, &share)?;
+        if collected.iter().any(|existing| existing.signer == share.signer && self.verify_share(message, existing).is_ok()) {
+            return Ok(false);
+        }
+        collected.retain(|existing| existing.signer != share.signer);
+        collected.push(share);
+        Ok(true)
+    }
+
+    /// Check that enough distinct signers validly signed `message`;
+    /// shares from other keys or over other messages do not count
+    pub fn verify(&self, message: &[u8], shares: &[PartialSignature]) -> Result<()> {
+        let mut approved: Vec<&[u8; 32]> = Vec::new();
+        for share in shares {
+            if !approved.contains(&&share.signer) && self.verify_share(message, share).is_ok() {
+                approved.push(&share.signer);
+            }
+        }
+        if approved.len() < self.threshold {
+            return Err(CryptoError::InsufficientSignatures {
+                collected: approved.len(),
+                required: self.threshold,
+            }.into());
+        }
+        Ok(())
+    }
+
+    fn verify_share(&self, message: &[u8], share: &PartialSignature) -> Result<()> {
+        if !self.is_signer(&share.signer) {
+            return Err(CryptoError::NotAWalletSigner(AgentId::from_key_bytes(&share.signer).to_string()).into());
+        }
+        let key = VerifyingKey::from_bytes(&share.signer).map_err(|_| CryptoError::InvalidKeyFormat)?;
+        share.signature.verify(message, &key)
+    }
+}
+
+/// What a node is allowed to do with its keys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeRole {
+    /// Signs, proposes, and transacts
+    #[default]
+    Participant,
+    /// Listens and indexes only; never signs
+    Observer,
+}
+
+impl NodeRole {
+    /// Refuse an operation that signs or commits to anything on a read-only node
+    pub fn authorize(self, operation: &str) -> Result<()> {
+        match self {
+            NodeRole::Participant => Ok(()),
+            NodeRole::Observer => Err(CryptoError::ReadOnlyNode(operation.to_string()).into()),
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum SignatureError {
     #[error("Invalid signature format")]
@@ -69,6 +181,16 @@ pub enum SignatureError {
     VerificationFailed,
 }
 
+/// Check that `agent_id` is the ID derived from the key a message was
+/// signed with, so no agent can sign under another's ID
+pub(crate) fn verify_sender(agent_id: &AgentId, key: &VerifyingKey) -> Result<()> {
+    if agent_id.is_derived_from(key.as_bytes()) {
+        Ok(())
+    } else {
+        Err(CryptoError::SenderKeyMismatch(agent_id.to_string()).into())
+    }
+}
+
 /// Hash a message using SHA-256
 pub fn hash_message(data: &[u8]) -> Result<[u8; 32]> {
     let mut hasher = Sha256::new();
@@ -96,4 +218,29 @@ mod tests {
         let hash2 = hash_message(data).unwrap();
         assert_eq!(hash1, hash2);
     }
+
+    #[test]
+    fn test_multisig_wallet_needs_threshold_of_distinct_signers() {
+        let keys: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate().unwrap()).collect();
+        let public: Vec<VerifyingKey> = keys.iter().map(|key| *key.verifying_key()).collect();
+        assert!(MultisigWallet::new(&public, 0).is_err());
+        assert!(MultisigWallet::new(&public, 4).is_err());
+        assert!(MultisigWallet::new(&[public[0], public[0]], 1).is_err());
+
+        let wallet = MultisigWallet::new(&public, 2).unwrap();
+        let message = b"release 1000";
+        let mut collected = Vec::new();
+        assert!(wallet.collect(message, &mut collected, PartialSignature::sign(&keys[0], message)).unwrap());
+        assert!(!wallet.collect(message, &mut collected, PartialSignature::sign(&keys[0], message)).unwrap());
+        assert!(wallet.verify(message, &collected).is_err());
+
+        // Outsiders and signatures over another message are refused
+        let outsider = KeyPair::generate().unwrap();
+        assert!(wallet.collect(message, &mut collected, PartialSignature::sign(&outsider, message)).is_err());
+        assert!(wallet.collect(message, &mut collected, PartialSignature::sign(&keys[1], b"release 9999")).is_err());
+
+        assert!(wallet.collect(message, &mut collected, PartialSignature::sign(&keys[1], message)).unwrap());
+        assert!(wallet.verify(message, &collected).is_ok());
+        assert!(wallet.verify(b"release 9999", &collected).is_err());
+    }
 } 
\ No newline at end of file
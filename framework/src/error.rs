@@ -1,5 +1,6 @@
 //! Error types and handling for the Solace Protocol
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Main result type for the Solace Protocol
@@ -32,6 +33,10 @@ pub enum SolaceError {
     #[error("Solana error: {0}")]
     Solana(#[from] solana_client::client_error::ClientError),
 
+    /// On-chain instruction failures
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainError),
+
     /// Serialization errors
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
@@ -48,6 +53,18 @@ pub enum SolaceError {
     #[error("Protocol version mismatch: expected {expected}, got {actual}")]
     VersionMismatch { expected: String, actual: String },
 
+    /// Validator stake below the consensus minimum
+    #[error("Insufficient stake: {0}, minimum required: {1}")]
+    InsufficientStake(u64, u64),
+
+    /// Unknown validator
+    #[error("Validator not found: {0}")]
+    ValidatorNotFound(crate::types::AgentId),
+
+    /// Operation stopped by its cancellation token
+    #[error("Cancelled: {operation}")]
+    Cancelled { operation: String },
+
     /// Generic internal error
     #[error("Internal error: {message}")]
     Internal { message: String },
@@ -99,6 +116,15 @@ pub enum TransactionError {
     #[error("Transaction amount invalid: {amount}")]
     InvalidAmount { amount: u64 },
 
+    #[error("Transaction price {price} outside governance bounds [{floor}, {ceiling}]")]
+    PriceOutOfBounds { price: String, floor: String, ceiling: String },
+
+    #[error("Transaction budget {budget} cannot cover estimated cost plus margin: {required}")]
+    BudgetBelowCost { budget: String, required: String },
+
+    #[error("Quote rejected: {reason}")]
+    QuoteRejected { reason: String },
+
     #[error("Transaction signature invalid")]
     InvalidSignature,
 
@@ -110,6 +136,12 @@ pub enum TransactionError {
 
     #[error("Transaction timeout after {duration} seconds")]
     Timeout { duration: u64 },
+
+    #[error("Negotiation protocol violation: {reason}")]
+    ProtocolViolation { reason: String },
+
+    #[error("Transaction requirements invalid: {reason}")]
+    InvalidRequirements { reason: String },
 }
 
 /// Network-specific errors
@@ -149,6 +181,21 @@ pub enum CryptoError {
     #[error("Signature verification failed")]
     SignatureVerificationFailed,
 
+    #[error("Not a member of the trust domain: {0}")]
+    NotADomainMember(String),
+
+    #[error("Agent {0} signed with a key its ID is not derived from")]
+    SenderKeyMismatch(String),
+
+    #[error("Observer nodes cannot {0}")]
+    ReadOnlyNode(String),
+
+    #[error("Agent {0} is not a signer of the wallet")]
+    NotAWalletSigner(String),
+
+    #[error("Only {collected} of {required} required signatures collected")]
+    InsufficientSignatures { collected: usize, required: usize },
+
     #[error("Key generation failed")]
     KeyGenerationFailed,
 
@@ -184,6 +231,62 @@ pub enum ReputationError {
     NotInitialized,
 }
 
+/// Why an on-chain instruction failed, decoded from its logs
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChainError {
+    #[error("Account {account} would fall below rent exemption")]
+    InsufficientRent { account: String },
+
+    #[error("Token account {account} does not exist")]
+    MissingTokenAccount { account: String },
+
+    #[error("Reputation account {account} is stale")]
+    StaleReputationAccount { account: String },
+
+    #[error("Account {account} is not initialized")]
+    AccountNotInitialized { account: String },
+
+    #[error("Insufficient funds: {detail}")]
+    InsufficientFunds { detail: String },
+
+    #[error("Insufficient funds by {shortfall} lamports: {payer} has {available}, needs {required}")]
+    Underfunded { payer: String, available: u64, required: u64, shortfall: u64 },
+
+    #[error("Fee sponsorship quota exhausted for {agent}: {reason}")]
+    SponsorshipExhausted { agent: String, reason: String },
+
+    #[error("Program {program} failed with custom error {code:#x}")]
+    ProgramError { program: String, code: u32 },
+
+    #[error("Instruction failed: {message}")]
+    InstructionFailed { message: String },
+}
+
+impl ChainError {
+    /// What to do about the failure
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            ChainError::InsufficientRent { .. } => "Fund the account up to the rent-exempt minimum for its size, or close it",
+            ChainError::MissingTokenAccount { .. } => "Create the associated token account for the owner and mint before transferring",
+            ChainError::StaleReputationAccount { .. } => "Refresh the agent's reputation on chain, then retry the instruction",
+            ChainError::AccountNotInitialized { .. } => "Initialize the account (e.g. register the agent) before using it",
+            ChainError::InsufficientFunds { .. } => "Top up the paying account; transfers and fees must leave it rent exempt",
+            ChainError::Underfunded { .. } => "Renegotiate the amount down by the shortfall, or top up the payer",
+            ChainError::SponsorshipExhausted { .. } => "Wait for the sponsorship window to reset, raise the agent's quota, or pay fees from the agent's own account",
+            ChainError::ProgramError { .. } => "Look the code up in the failing program's error enum",
+            ChainError::InstructionFailed { .. } => "Inspect the transaction logs for the failing instruction",
+        }
+    }
+
+    /// Lamports the payer is missing, if pre-flight found it underfunded
+    pub fn shortfall(&self) -> Option<u64> {
+        match self {
+            ChainError::Underfunded { shortfall, .. } => Some(*shortfall),
+            _ => None,
+        }
+    }
+}
+
 impl SolaceError {
     /// Create a configuration error
     pub fn config<S: Into<String>>(message: S) -> Self {
@@ -192,6 +295,13 @@ impl SolaceError {
         }
     }
 
+    /// Create a cancellation error
+    pub fn cancelled<S: Into<String>>(operation: S) -> Self {
+        Self::Cancelled {
+            operation: operation.into(),
+        }
+    }
+
     /// Create an internal error
     pub fn internal<S: Into<String>>(message: S) -> Self {
         Self::Internal {
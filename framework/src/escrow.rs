@@ -0,0 +1,614 @@
+//! Escrow
+//!
+//! Holds the requester's payment while the provider works. `Escrow::lock`
+//! locks the agreed price on an `EscrowLedger` and only then moves the
+//! transaction into execution. The escrow is released to the provider with
+//! the evaluation of delivered work, and refunded to the requester if the
+//! deadline passes, execution fails, or the requester disputes the result.
+//! For transactions split into milestones, `release_milestone` pays each
+//! milestone's amount as it is delivered; the final release and any refund
+//! cover only what is still locked.
+//!
+//! Amounts are in base units of the request's payment asset, which the
+//! escrow records so the ledger moves SOL or the right SPL token.
+//!
+//! Escrows paid from a multisig treasury can carry a `ReleasePolicy`: any
+//! payout larger than its limit for the escrow's asset needs the treasury
+//! wallet's threshold of signers to approve it. The policy is fixed when the
+//! escrow is locked, with `Escrow::lock_with_policy`, and cannot be changed
+//! or dropped afterwards. Signers send `ReleaseApproval`s over ACP, each
+//! signing the payout amount and what was paid before it, so an approval
+//! never carries over to another payout; collected approvals are cleared
+//! once a payout goes through.
+//!
+//! Each step checks the transaction transition with the agent's
+//! `TransactionManager` before touching the ledger, and applies it through
+//! the manager only once the ledger step succeeded, so a transaction's phase
+//! never runs ahead of its funds and every step is persisted.
+//! `SolanaEscrowLedger` keeps escrows in the Solace program; tests use a
+//! simulated ledger.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    acp::MessageType,
+    crypto::{KeyPair, MultisigWallet, PartialSignature},
+    error::TransactionError,
+    transaction::{ExecutionData, TransactionEvaluation},
+    transaction_manager::{TransactionManager, Transition},
+    types::{AgentId, Balance, Payment, PaymentAsset, Timestamp, TransactionId},
+    Result,
+};
+
+/// Where locked funds are held
+#[async_trait::async_trait]
+pub trait EscrowLedger: Send + Sync {
+    /// Move the escrow's amount out of the requester's account; returns a reference to the lock
+    async fn lock(&self, escrow: &Escrow) -> Result<String>;
+    /// Pay part of the locked amount to the provider
+    async fn release_partial(&self, escrow: &Escrow, amount: Balance) -> Result<String>;
+    /// Pay what remains locked to the provider
+    async fn release(&self, escrow: &Escrow) -> Result<String>;
+    /// Return what remains locked to the requester
+    async fn refund(&self, escrow: &Escrow) -> Result<String>;
+}
+
+/// Escrow state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscrowState {
+    Locked,
+    Released,
+    Refunded,
+}
+
+/// Why locked funds went back to the requester
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RefundReason {
+    Timeout,                              // Deadline passed before the work was evaluated
+    Failed(String),                       // Execution failed
+    Dispute(String),                      // Requester disputed the delivered work
+}
+
+/// Approvals payouts of a high-value escrow need
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleasePolicy {
+    pub wallet: MultisigWallet,           // Treasury whose signers approve
+    pub limits: HashMap<PaymentAsset, Balance>, // Per asset; payouts above need the threshold
+}
+
+impl ReleasePolicy {
+    /// Policy under which every payout needs approvals until limits are set
+    pub fn new(wallet: MultisigWallet) -> Self {
+        Self { wallet, limits: HashMap::new() }
+    }
+
+    /// Let payouts in `asset` of up to `limit` through without approvals
+    pub fn with_limit(mut self, asset: PaymentAsset, limit: Balance) -> Self {
+        self.limits.insert(asset, limit);
+        self
+    }
+
+    /// Largest payout in `asset` needing no approvals; zero for assets
+    /// without a limit
+    pub fn limit(&self, asset: PaymentAsset) -> Balance {
+        self.limits.get(&asset).copied().unwrap_or(Balance(0))
+    }
+}
+
+/// A treasury signer's approval of one payout, gossiped to the releasing agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseApproval {
+    pub transaction_id: TransactionId,
+    pub amount: Balance,
+    pub share: PartialSignature,
+}
+
+impl ReleaseApproval {
+    /// Approve paying `amount` out of `escrow` next
+    pub fn sign(escrow: &Escrow, amount: Balance, keypair: &KeyPair) -> Self {
+        Self {
+            transaction_id: escrow.transaction_id,
+            amount,
+            share: PartialSignature::sign(keypair, &escrow.release_message(amount)),
+        }
+    }
+
+    /// Gossip message type carrying approvals
+    pub fn message_type(&self) -> MessageType {
+        MessageType::PartialSignature
+    }
+
+    /// Gossip payload
+    pub fn to_payload(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    pub fn from_payload(payload: serde_json::Value) -> Result<Self> {
+        Ok(serde_json::from_value(payload)?)
+    }
+}
+
+/// Funds locked for one transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Escrow {
+    pub transaction_id: TransactionId,
+    pub requester: AgentId,
+    pub provider: AgentId,
+    pub provider_account: String,         // Ledger account the release pays
+    pub amount: Balance,
+    #[serde(default)]
+    pub asset: PaymentAsset,              // What `amount` and `released` are counted in
+    #[serde(default)]
+    pub released: Balance,                // Paid out for completed milestones
+    pub state: EscrowState,
+    pub lock_reference: String,
+    #[serde(default)]
+    pub milestone_references: Vec<String>,
+    pub settle_reference: Option<String>, // Release or refund reference
+    pub refund_reason: Option<RefundReason>,
+    #[serde(default)]
+    release_policy: Option<ReleasePolicy>, // Fixed at lock
+    #[serde(default)]
+    pub approvals: Vec<PartialSignature>, // Collected for the next payout
+    pub locked_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl Escrow {
+    /// Lock `price` for the accepted proposal, then move the transaction into execution
+    pub async fn lock(
+        ledger: &dyn EscrowLedger,
+        transactions: &TransactionManager,
+        transaction_id: &TransactionId,
+        provider: AgentId,
+        price: Balance,
+        provider_account: impl Into<String>,
+    ) -> Result<Self> {
+        Self::lock_with(ledger, transactions, transaction_id, provider, price, provider_account.into(), None).await
+    }
+
+    /// Lock as `lock`, requiring treasury approvals for payouts above the
+    /// policy's limit
+    pub async fn lock_with_policy(
+        ledger: &dyn EscrowLedger,
+        transactions: &TransactionManager,
+        transaction_id: &TransactionId,
+        provider: AgentId,
+        price: Balance,
+        provider_account: impl Into<String>,
+        policy: ReleasePolicy,
+    ) -> Result<Self> {
+        Self::lock_with(ledger, transactions, transaction_id, provider, price, provider_account.into(), Some(policy)).await
+    }
+
+    async fn lock_with(
+        ledger: &dyn EscrowLedger,
+        transactions: &TransactionManager,
+        transaction_id: &TransactionId,
+        provider: AgentId,
+        price: Balance,
+        provider_account: String,
+        release_policy: Option<ReleasePolicy>,
+    ) -> Result<Self> {
+        let transition = Transition::AcceptProposal { provider, price };
+        let next = transactions.check(transaction_id, &transition).await?;
+
+        let mut escrow = Self {
+            transaction_id: next.id,
+            requester: next.request.requester,
+            provider,
+            provider_account,
+            amount: price,
+            asset: next.request.asset,
+            released: Balance(0),
+            state: EscrowState::Locked,
+            lock_reference: String::new(),
+            milestone_references: Vec::new(),
+            settle_reference: None,
+            refund_reason: None,
+            release_policy,
+            approvals: Vec::new(),
+            locked_at: Timestamp::now(),
+            updated_at: Timestamp::now(),
+        };
+        escrow.lock_reference = ledger.lock(&escrow).await?;
+
+        transactions.apply(transaction_id, transition).await?;
+        tracing::debug!("Locked {} in escrow for transaction {}", escrow.payment(price), transaction_id);
+        Ok(escrow)
+    }
+
+    /// Approvals payouts need, as fixed when the escrow was locked
+    pub fn release_policy(&self) -> Option<&ReleasePolicy> {
+        self.release_policy.as_ref()
+    }
+
+    /// Record a signer's approval of a payout. Returns false if the signer
+    /// had already approved it.
+    pub fn approve(&mut self, approval: ReleaseApproval) -> Result<bool> {
+        if approval.transaction_id != self.transaction_id {
+            return Err(TransactionError::InvalidState {
+                current: format!("approval for {}", approval.transaction_id),
+                expected: format!("approval for {}", self.transaction_id),
+            }.into());
+        }
+        let Some(policy) = &self.release_policy else {
+            return Err(TransactionError::InvalidState {
+                current: "escrow without release policy".to_string(),
+                expected: "escrow requiring approvals".to_string(),
+            }.into());
+        };
+        let message = self.release_message(approval.amount);
+        policy.wallet.collect(&message, &mut self.approvals, approval.share)
+    }
+
+    /// What signers approving a payout of `amount` sign
+    pub fn release_message(&self, amount: Balance) -> Vec<u8> {
+        format!(
+            "solace-escrow-release:{}:{}:{}:{}:{}",
+            self.transaction_id, self.provider_account, self.asset, self.released.0, amount.0
+        ).into_bytes()
+    }
+
+    /// Check whether a payout of `amount` needs treasury approvals
+    pub fn needs_approval(&self, amount: Balance) -> bool {
+        self.release_policy.as_ref().is_some_and(|policy| amount.0 > policy.limit(self.asset).0)
+    }
+
+    /// Pay the provider for a delivered milestone
+    pub async fn release_milestone(
+        &mut self,
+        ledger: &dyn EscrowLedger,
+        transactions: &TransactionManager,
+        index: usize,
+        execution_data: ExecutionData,
+    ) -> Result<()> {
+        self.ensure_locked()?;
+        let transition = Transition::CompleteMilestone { index, execution_data };
+        let next = transactions.check(&self.transaction_id, &transition).await?;
+        let due = next.milestones[index].amount;
+        if due.0 > self.remaining().0 {
+            return Err(TransactionError::InvalidAmount { amount: due.0 }.into());
+        }
+        self.ensure_approved(due)?;
+
+        self.milestone_references.push(ledger.release_partial(self, due).await?);
+        self.released = Balance(self.released.0 + due.0);
+        self.approvals.clear();
+        self.updated_at = Timestamp::now();
+        transactions.apply(&self.transaction_id, transition).await?;
+        tracing::debug!("Released {} for milestone {} of transaction {}", self.payment(due), index, self.transaction_id);
+        Ok(())
+    }
+
+    /// Pay the provider what remains and record the evaluation of delivered work
+    pub async fn release(
+        &mut self,
+        ledger: &dyn EscrowLedger,
+        transactions: &TransactionManager,
+        evaluation: TransactionEvaluation,
+    ) -> Result<()> {
+        self.ensure_locked()?;
+        let transition = Transition::Evaluate(evaluation);
+        transactions.check(&self.transaction_id, &transition).await?;
+        self.ensure_approved(self.remaining())?;
+
+        self.settle_reference = Some(ledger.release(self).await?);
+        self.state = EscrowState::Released;
+        self.approvals.clear();
+        self.updated_at = Timestamp::now();
+        transactions.apply(&self.transaction_id, transition).await?;
+        Ok(())
+    }
+
+    /// Return the remaining funds to the requester, ending the transaction for `reason`
+    pub async fn refund(
+        &mut self,
+        ledger: &dyn EscrowLedger,
+        transactions: &TransactionManager,
+        reason: RefundReason,
+    ) -> Result<()> {
+        self.ensure_locked()?;
+        let transition = match &reason {
+            RefundReason::Timeout => Transition::Expire,
+            RefundReason::Failed(detail) => Transition::Fail(detail.clone()),
+            RefundReason::Dispute(detail) => Transition::Dispute(detail.clone()),
+        };
+        transactions.check(&self.transaction_id, &transition).await?;
+
+        self.settle_reference = Some(ledger.refund(self).await?);
+        self.state = EscrowState::Refunded;
+        self.refund_reason = Some(reason);
+        self.updated_at = Timestamp::now();
+        transactions.apply(&self.transaction_id, transition).await?;
+        Ok(())
+    }
+
+    /// What is still locked
+    pub fn remaining(&self) -> Balance {
+        Balance(self.amount.0.saturating_sub(self.released.0))
+    }
+
+    /// `amount` as a payment in the escrow's asset
+    pub fn payment(&self, amount: Balance) -> Payment {
+        Payment::new(self.asset, amount.0)
+    }
+
+    /// Check whether the funds have been released or refunded
+    pub fn is_settled(&self) -> bool {
+        self.state != EscrowState::Locked
+    }
+
+    fn ensure_approved(&self, amount: Balance) -> Result<()> {
+        match &self.release_policy {
+            Some(policy) if self.needs_approval(amount) => {
+                policy.wallet.verify(&self.release_message(amount), &self.approvals)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn ensure_locked(&self) -> Result<()> {
+        if self.state != EscrowState::Locked {
+            return Err(TransactionError::InvalidState {
+                current: format!("escrow {:?}", self.state),
+                expected: "Locked escrow".to_string(),
+            }.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageManager;
+    use crate::transaction::{Transaction, TransactionPhase, TransactionProposal, TransactionRequest, TransactionStatus};
+    use crate::types::ServiceType;
+    use std::sync::{Arc, Mutex};
+
+    /// In-memory balances keyed by account
+    #[derive(Default)]
+    struct MemoryLedger {
+        balances: Mutex<HashMap<String, u64>>,
+    }
+
+    impl MemoryLedger {
+        fn balance(&self, account: &str) -> u64 {
+            *self.balances.lock().unwrap().get(account).unwrap_or(&0)
+        }
+
+        fn transfer(&self, from: &str, to: &str, amount: u64) -> Result<String> {
+            let mut balances = self.balances.lock().unwrap();
+            let available = *balances.get(from).unwrap_or(&0);
+            if available < amount {
+                return Err(crate::SolaceError::internal(format!("{} has {}, needs {}", from, available, amount)));
+            }
+            balances.insert(from.to_string(), available - amount);
+            *balances.entry(to.to_string()).or_default() += amount;
+            Ok(format!("{}->{}", from, to))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl EscrowLedger for MemoryLedger {
+        async fn lock(&self, escrow: &Escrow) -> Result<String> {
+            self.transfer(&escrow.requester.to_string(), &escrow.transaction_id.to_string(), escrow.amount.0)
+        }
+
+        async fn release_partial(&self, escrow: &Escrow, amount: Balance) -> Result<String> {
+            self.transfer(&escrow.transaction_id.to_string(), &escrow.provider_account, amount.0)
+        }
+
+        async fn release(&self, escrow: &Escrow) -> Result<String> {
+            self.transfer(&escrow.transaction_id.to_string(), &escrow.provider_account, escrow.remaining().0)
+        }
+
+        async fn refund(&self, escrow: &Escrow) -> Result<String> {
+            self.transfer(&escrow.transaction_id.to_string(), &escrow.requester.to_string(), escrow.remaining().0)
+        }
+    }
+
+    fn manager() -> (TransactionManager, Arc<StorageManager>) {
+        let storage = Arc::new(StorageManager::memory());
+        (TransactionManager::new(storage.clone()), storage)
+    }
+
+    async fn negotiating(transactions: &TransactionManager, deadline: Timestamp) -> (Transaction, AgentId) {
+        let requester = AgentId::new();
+        let provider = AgentId::new();
+        let request = TransactionRequest::new(requester, ServiceType::DataAnalysis, "report".to_string(), Balance(1_000), deadline);
+        let transaction = transactions.open(request).await.unwrap();
+        let transaction = transactions.apply(&transaction.id, Transition::AddProposal(TransactionProposal {
+            id: TransactionId::new(),
+            request_id: transaction.id,
+            provider,
+            proposed_price: Balance(800),
+            estimated_completion: deadline,
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: deadline,
+        })).await.unwrap();
+        (transaction, provider)
+    }
+
+    fn execution() -> ExecutionData {
+        ExecutionData {
+            result: "done".to_string(),
+            artifacts: Vec::new(),
+            completion_time: Timestamp::now(),
+            quality_metrics: HashMap::new(),
+        }
+    }
+
+    async fn deliver(transactions: &TransactionManager, id: &TransactionId) {
+        transactions.apply(id, Transition::CompleteExecution(execution())).await.unwrap();
+    }
+
+    async fn add_milestone(transactions: &TransactionManager, id: &TransactionId, deadline: Timestamp, amount: Balance) -> Result<Transaction> {
+        let transition = Transition::AddMilestone { deliverable: "draft".to_string(), deadline, amount };
+        transactions.apply(id, transition).await
+    }
+
+    fn evaluation() -> TransactionEvaluation {
+        TransactionEvaluation {
+            requester_rating: 0.9,
+            provider_rating: 0.9,
+            requester_feedback: String::new(),
+            provider_feedback: String::new(),
+            quality_score: 0.9,
+            timeliness_score: 0.9,
+            overall_satisfaction: 0.9,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_escrow_follows_transaction_phases() {
+        let ledger = MemoryLedger::default();
+        let (transactions, storage) = manager();
+        let future = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+
+        // An unfunded requester never reaches execution
+        let (transaction, provider) = negotiating(&transactions, future).await;
+        let id = transaction.id;
+        assert!(Escrow::lock(&ledger, &transactions, &id, provider, Balance(800), "provider").await.is_err());
+        assert_eq!(transactions.get(&id).await.unwrap().phase, TransactionPhase::Negotiation);
+
+        // Locked at execution start, released with the evaluation
+        ledger.balances.lock().unwrap().insert(transaction.request.requester.to_string(), 1_000);
+        let mut escrow = Escrow::lock(&ledger, &transactions, &id, provider, Balance(800), "provider").await.unwrap();
+        assert_eq!(transactions.get(&id).await.unwrap().phase, TransactionPhase::Execution);
+        assert_eq!(ledger.balance(&transaction.request.requester.to_string()), 200);
+
+        assert!(escrow.release(&ledger, &transactions, evaluation()).await.is_err());
+        deliver(&transactions, &id).await;
+        escrow.release(&ledger, &transactions, evaluation()).await.unwrap();
+        assert_eq!(transactions.get(&id).await.unwrap().status, TransactionStatus::Completed);
+        assert_eq!(ledger.balance("provider"), 800);
+        assert!(escrow.refund(&ledger, &transactions, RefundReason::Timeout).await.is_err());
+
+        // Every step reached storage
+        let stored = storage.get_transaction::<Transaction>(&id).await.unwrap().unwrap();
+        assert_eq!(stored.status, TransactionStatus::Completed);
+
+        // A disputed delivery goes back to the requester
+        let (disputed, provider) = negotiating(&transactions, future).await;
+        ledger.balances.lock().unwrap().insert(disputed.request.requester.to_string(), 800);
+        let mut escrow = Escrow::lock(&ledger, &transactions, &disputed.id, provider, Balance(800), "provider").await.unwrap();
+        deliver(&transactions, &disputed.id).await;
+        escrow.refund(&ledger, &transactions, RefundReason::Dispute("wrong format".to_string())).await.unwrap();
+        assert_eq!(transactions.get(&disputed.id).await.unwrap().status, TransactionStatus::Failed);
+        assert_eq!(escrow.state, EscrowState::Refunded);
+        assert_eq!(ledger.balance(&disputed.request.requester.to_string()), 800);
+
+        // A timeout refund waits for the deadline
+        let (late, provider) = negotiating(&transactions, future).await;
+        ledger.balances.lock().unwrap().insert(late.request.requester.to_string(), 800);
+        let mut escrow = Escrow::lock(&ledger, &transactions, &late.id, provider, Balance(800), "provider").await.unwrap();
+        assert!(escrow.refund(&ledger, &transactions, RefundReason::Timeout).await.is_err());
+
+        // The deadline passes while the node is down
+        let mut overdue = transactions.get(&late.id).await.unwrap();
+        overdue.request.deadline = Timestamp(chrono::Utc::now() - chrono::Duration::seconds(1));
+        storage.store_transaction(&late.id, &overdue).await.unwrap();
+        let restarted = TransactionManager::new(storage);
+        escrow.refund(&ledger, &restarted, RefundReason::Timeout).await.unwrap();
+        assert_eq!(restarted.get(&late.id).await.unwrap().status, TransactionStatus::Expired);
+        assert_eq!(ledger.balance(&late.request.requester.to_string()), 800);
+    }
+
+    #[tokio::test]
+    async fn test_milestones_release_incrementally() {
+        let ledger = MemoryLedger::default();
+        let (transactions, _) = manager();
+        let future = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let (transaction, provider) = negotiating(&transactions, future).await;
+        let id = transaction.id;
+        add_milestone(&transactions, &id, future, Balance(300)).await.unwrap();
+        add_milestone(&transactions, &id, future, Balance(200)).await.unwrap();
+        ledger.balances.lock().unwrap().insert(transaction.request.requester.to_string(), 1_000);
+
+        let mut escrow = Escrow::lock(&ledger, &transactions, &id, provider, Balance(800), "provider").await.unwrap();
+        assert!(add_milestone(&transactions, &id, future, Balance(100)).await.is_err());
+
+        // Final delivery waits for every milestone
+        escrow.release_milestone(&ledger, &transactions, 1, execution()).await.unwrap();
+        assert_eq!(ledger.balance("provider"), 200);
+        assert!(escrow.release_milestone(&ledger, &transactions, 1, execution()).await.is_err());
+        assert!(transactions.apply(&id, Transition::CompleteExecution(execution())).await.is_err());
+
+        escrow.release_milestone(&ledger, &transactions, 0, execution()).await.unwrap();
+        assert_eq!(escrow.remaining(), Balance(300));
+        assert_eq!(escrow.milestone_references.len(), 2);
+
+        // A refund returns only what is still locked
+        deliver(&transactions, &id).await;
+        escrow.refund(&ledger, &transactions, RefundReason::Dispute("incomplete".to_string())).await.unwrap();
+        assert_eq!(ledger.balance("provider"), 500);
+        assert_eq!(ledger.balance(&transaction.request.requester.to_string()), 500);
+    }
+
+    #[tokio::test]
+    async fn test_high_value_release_needs_treasury_approvals() {
+        let ledger = MemoryLedger::default();
+        let (transactions, _) = manager();
+        let future = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let (transaction, provider) = negotiating(&transactions, future).await;
+        let id = transaction.id;
+        add_milestone(&transactions, &id, future, Balance(100)).await.unwrap();
+        ledger.balances.lock().unwrap().insert(transaction.request.requester.to_string(), 1_000);
+
+        let signers: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate().unwrap()).collect();
+        let keys: Vec<_> = signers.iter().map(|signer| *signer.verifying_key()).collect();
+        let policy = ReleasePolicy::new(MultisigWallet::new(&keys, 2).unwrap())
+            .with_limit(PaymentAsset::Sol, Balance(500));
+        let mut escrow = Escrow::lock_with_policy(&ledger, &transactions, &id, provider, Balance(800), "provider", policy)
+            .await
+            .unwrap();
+
+        // Payouts within the limit need no approval
+        escrow.release_milestone(&ledger, &transactions, 0, execution()).await.unwrap();
+        deliver(&transactions, &id).await;
+        assert!(escrow.needs_approval(escrow.remaining()));
+
+        // Approvals of another amount do not count towards the release
+        let stale = ReleaseApproval::sign(&escrow, Balance(100), &signers[0]);
+        assert!(escrow.approve(stale).unwrap());
+        assert!(escrow.approve(ReleaseApproval::sign(&escrow, Balance(100), &signers[1])).unwrap());
+        assert!(escrow.release(&ledger, &transactions, evaluation()).await.is_err());
+
+        let first = ReleaseApproval::sign(&escrow, escrow.remaining(), &signers[0]);
+        let decoded = ReleaseApproval::from_payload(first.to_payload().unwrap()).unwrap();
+        assert_eq!(decoded.message_type(), MessageType::PartialSignature);
+        assert!(escrow.approve(decoded).unwrap());
+        assert!(!escrow.approve(first).unwrap());
+        assert!(escrow.release(&ledger, &transactions, evaluation()).await.is_err());
+
+        let outsider = KeyPair::generate().unwrap();
+        assert!(escrow.approve(ReleaseApproval::sign(&escrow, escrow.remaining(), &outsider)).is_err());
+        assert!(escrow.approve(ReleaseApproval::sign(&escrow, escrow.remaining(), &signers[2])).unwrap());
+        escrow.release(&ledger, &transactions, evaluation()).await.unwrap();
+        assert_eq!(ledger.balance("provider"), 800);
+        assert!(escrow.approvals.is_empty());
+    }
+
+    #[test]
+    fn test_release_limits_are_per_asset() {
+        let signer = KeyPair::generate().unwrap();
+        let usdc = PaymentAsset::Spl { mint: solana_sdk::pubkey::Pubkey::new_unique(), decimals: 6 };
+        let policy = ReleasePolicy::new(MultisigWallet::new(&[*signer.verifying_key()], 1).unwrap())
+            .with_limit(PaymentAsset::Sol, Balance(500))
+            .with_limit(usdc, Balance(5_000_000));
+
+        assert_eq!(policy.limit(PaymentAsset::Sol), Balance(500));
+        assert_eq!(policy.limit(usdc), Balance(5_000_000));
+        let other = PaymentAsset::Spl { mint: solana_sdk::pubkey::Pubkey::new_unique(), decimals: 6 };
+        assert_eq!(policy.limit(other), Balance(0));
+
+        let decoded: ReleasePolicy = serde_json::from_value(serde_json::to_value(&policy).unwrap()).unwrap();
+        assert_eq!(decoded, policy);
+    }
+}
@@ -0,0 +1,425 @@
+//! Network Explorer API
+//!
+//! Read-only queries over what an `ObserverNode` has indexed, shaped for a
+//! web frontend: recent transactions, agent profiles, reputation history,
+//! block headers, the current validator set, and network-wide statistics.
+//! List queries are paginated by offset and limit, and every answer is
+//! cached as JSON for a short TTL so popular pages do not rescan the index
+//! on each request. With the `explorer` feature, `router` exposes the
+//! queries over HTTP:
+//!
+//! ```text
+//! GET /explorer/transactions?offset=&limit=
+//! GET /explorer/agents/{id}
+//! GET /explorer/agents/{id}/reputation?offset=&limit=
+//! GET /explorer/blocks?offset=&limit=
+//! GET /explorer/validators
+//! GET /explorer/stats
+//! ```
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::{
+    analytics::ServiceMarketStats,
+    consensus::BlockHeader,
+    observer::{ObserverNode, ReputationPoint, ValidatorSet},
+    search::SearchQuery,
+    transaction::{Transaction, TransactionPhase, TransactionStatus},
+    types::{AgentId, Balance, ServiceType, Timestamp, TransactionId},
+    Result,
+};
+
+/// Explorer tuning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplorerConfig {
+    pub cache_ttl: Duration,
+    pub default_page_size: usize,
+    pub max_page_size: usize,
+}
+
+impl Default for ExplorerConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl: Duration::from_secs(5),
+            default_page_size: 20,
+            max_page_size: 100,
+        }
+    }
+}
+
+/// Requested slice of a list
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// One page of a list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub next_offset: Option<usize>,       // None on the last page
+}
+
+impl<T> Paginated<T> {
+    fn new(items: Vec<T>, total: usize, offset: usize, limit: usize) -> Self {
+        let end = offset + items.len();
+        Self {
+            items,
+            total,
+            offset,
+            limit,
+            next_offset: (end < total).then_some(end),
+        }
+    }
+}
+
+/// Transaction as listed by the explorer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionSummary {
+    pub id: TransactionId,
+    pub service_type: ServiceType,
+    pub status: TransactionStatus,
+    pub phase: TransactionPhase,
+    pub requester: AgentId,
+    pub provider: Option<AgentId>,
+    pub price: Balance,                   // Agreed price, or the budget before agreement
+    pub created_at: Timestamp,
+}
+
+impl From<&Transaction> for TransactionSummary {
+    fn from(tx: &Transaction) -> Self {
+        Self {
+            id: tx.id,
+            service_type: tx.request.service_type.clone(),
+            status: tx.status,
+            phase: tx.phase,
+            requester: tx.request.requester,
+            provider: tx.provider,
+            price: tx.agreed_price.unwrap_or(tx.request.budget),
+            created_at: tx.request.created_at,
+        }
+    }
+}
+
+/// An agent's activity as seen by the observer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentProfile {
+    pub agent_id: AgentId,
+    pub reputation: Option<f64>,
+    pub requested: usize,
+    pub provided: usize,
+    pub completed: usize,                 // Completed, on either side
+    pub volume: Balance,                  // Agreed prices on either side
+    pub services: Vec<ServiceType>,
+    pub first_seen: Option<Timestamp>,
+    pub last_seen: Option<Timestamp>,
+}
+
+/// Network-wide summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkStats {
+    pub transactions: usize,
+    pub reputation_updates: u64,
+    pub rated_agents: usize,
+    pub blocks: u64,
+    pub latest_height: Option<u64>,
+    pub epoch: Option<u32>,
+    pub validators: usize,
+    pub markets: Vec<ServiceMarketStats>,
+}
+
+/// Query the explorer answers
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExplorerQuery {
+    Transactions(Page),
+    Agent(AgentId),
+    ReputationHistory(AgentId, Page),
+    Blocks(Page),
+    Validators,
+    Stats,
+}
+
+/// Paginated, cached queries over an observer's index
+pub struct Explorer {
+    observer: Arc<RwLock<ObserverNode>>,
+    config: ExplorerConfig,
+    cache: Mutex<HashMap<ExplorerQuery, (Instant, serde_json::Value)>>,
+}
+
+impl Explorer {
+    pub fn new(observer: Arc<RwLock<ObserverNode>>, config: ExplorerConfig) -> Self {
+        Self {
+            observer,
+            config,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ExplorerConfig {
+        &self.config
+    }
+
+    /// Answer a query as JSON, from the cache while fresh. Returns `None`
+    /// for an agent the observer has never seen, or before any validator
+    /// set was announced.
+    pub async fn query(&self, query: ExplorerQuery) -> Result<Option<serde_json::Value>> {
+        let now = Instant::now();
+        if let Some((at, value)) = self.cache.lock().get(&query) {
+            if now.duration_since(*at) < self.config.cache_ttl {
+                return Ok(Some(value.clone()));
+            }
+        }
+
+        let value = match &query {
+            ExplorerQuery::Transactions(page) => Some(serde_json::to_value(self.recent_transactions(page).await)?),
+            ExplorerQuery::Agent(agent_id) => self.agent_profile(agent_id).await?.map(serde_json::to_value).transpose()?,
+            ExplorerQuery::ReputationHistory(agent_id, page) => {
+                Some(serde_json::to_value(self.reputation_history(agent_id, page).await)?)
+            }
+            ExplorerQuery::Blocks(page) => Some(serde_json::to_value(self.blocks(page).await)?),
+            ExplorerQuery::Validators => self.validators().await.map(serde_json::to_value).transpose()?,
+            ExplorerQuery::Stats => Some(serde_json::to_value(self.network_stats().await)?),
+        };
+
+        if let Some(value) = &value {
+            let mut cache = self.cache.lock();
+            cache.retain(|_, (at, _)| now.duration_since(*at) < self.config.cache_ttl);
+            cache.insert(query, (now, value.clone()));
+        }
+        Ok(value)
+    }
+
+    /// Most recent transactions first
+    pub async fn recent_transactions(&self, page: &Page) -> Paginated<TransactionSummary> {
+        let limit = self.limit(page);
+        let query = SearchQuery {
+            offset: page.offset,
+            limit: Some(limit),
+            ..SearchQuery::default()
+        };
+        let results = self.observer.read().await.search(&query);
+        let items = results.hits.iter().map(|hit| TransactionSummary::from(&hit.transaction)).collect();
+        Paginated::new(items, results.total, page.offset, limit)
+    }
+
+    /// Profile of an agent seen in any transaction or reputation update
+    pub async fn agent_profile(&self, agent_id: &AgentId) -> Result<Option<AgentProfile>> {
+        let observer = self.observer.read().await;
+        let everything = |query: SearchQuery| SearchQuery {
+            limit: Some(usize::MAX),
+            ..query
+        };
+        let requested = observer.search(&everything(SearchQuery {
+            requester: Some(*agent_id),
+            ..SearchQuery::default()
+        }));
+        let provided = observer.search(&everything(SearchQuery {
+            provider: Some(*agent_id),
+            ..SearchQuery::default()
+        }));
+        let reputation = observer.reputation(agent_id).await?;
+        if requested.total == 0 && provided.total == 0 && reputation.is_none() {
+            return Ok(None);
+        }
+
+        let transactions: Vec<&Transaction> = requested.hits.iter().chain(&provided.hits).map(|hit| &hit.transaction).collect();
+        let mut services: Vec<ServiceType> = Vec::new();
+        for tx in &transactions {
+            if !services.contains(&tx.request.service_type) {
+                services.push(tx.request.service_type.clone());
+            }
+        }
+        services.sort_by_key(|service| service.to_string());
+
+        Ok(Some(AgentProfile {
+            agent_id: *agent_id,
+            reputation,
+            requested: requested.total,
+            provided: provided.total,
+            completed: transactions.iter().filter(|tx| tx.status == TransactionStatus::Completed).count(),
+            volume: Balance::new(transactions.iter().filter_map(|tx| tx.agreed_price).map(|price| price.0).sum()),
+            services,
+            first_seen: transactions.iter().map(|tx| tx.request.created_at).min(),
+            last_seen: transactions.iter().map(|tx| tx.request.created_at).max(),
+        }))
+    }
+
+    /// An agent's reputation updates, newest first
+    pub async fn reputation_history(&self, agent_id: &AgentId, page: &Page) -> Paginated<ReputationPoint> {
+        let limit = self.limit(page);
+        let observer = self.observer.read().await;
+        let history = observer.reputation_history(agent_id);
+        let items = history.iter().rev().skip(page.offset).take(limit).cloned().collect();
+        Paginated::new(items, history.len(), page.offset, limit)
+    }
+
+    /// Block headers, highest first
+    pub async fn blocks(&self, page: &Page) -> Paginated<BlockHeader> {
+        let limit = self.limit(page);
+        let observer = self.observer.read().await;
+        let blocks = observer.blocks();
+        let items = blocks.iter().skip(page.offset).take(limit).cloned().collect();
+        Paginated::new(items, blocks.len(), page.offset, limit)
+    }
+
+    pub async fn validators(&self) -> Option<ValidatorSet> {
+        self.observer.read().await.validators().cloned()
+    }
+
+    pub async fn network_stats(&self) -> NetworkStats {
+        let observer = self.observer.read().await;
+        let stats = observer.stats();
+        let validators = observer.validators();
+        NetworkStats {
+            transactions: observer.search(&SearchQuery { limit: Some(0), ..SearchQuery::default() }).total,
+            reputation_updates: stats.reputation_updates,
+            rated_agents: observer.rated_agents(),
+            blocks: stats.blocks,
+            latest_height: observer.blocks().front().map(|header| header.height),
+            epoch: validators.map(|set| set.epoch),
+            validators: validators.map_or(0, |set| set.validators.len()),
+            markets: observer.all_market_stats(Timestamp::now()),
+        }
+    }
+
+    /// Drop every cached answer
+    pub fn clear_cache(&self) {
+        self.cache.lock().clear();
+    }
+
+    fn limit(&self, page: &Page) -> usize {
+        page.limit.unwrap_or(self.config.default_page_size).clamp(1, self.config.max_page_size)
+    }
+}
+
+#[cfg(feature = "explorer")]
+pub use http::{router, serve};
+
+#[cfg(feature = "explorer")]
+mod http {
+    use super::*;
+    use axum::extract::{Path, Query, State};
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use std::net::SocketAddr;
+
+    /// HTTP routes for the explorer
+    pub fn router(explorer: Arc<Explorer>) -> Router {
+        Router::new()
+            .route("/explorer/transactions", get(transactions))
+            .route("/explorer/agents/{id}", get(agent))
+            .route("/explorer/agents/{id}/reputation", get(reputation))
+            .route("/explorer/blocks", get(blocks))
+            .route("/explorer/validators", get(validators))
+            .route("/explorer/stats", get(stats))
+            .with_state(explorer)
+    }
+
+    /// Serve the explorer until the server fails
+    pub async fn serve(explorer: Arc<Explorer>, addr: SocketAddr) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::info!("Explorer API listening on {}", addr);
+        axum::serve(listener, router(explorer)).await?;
+        Ok(())
+    }
+
+    async fn transactions(State(explorer): State<Arc<Explorer>>, Query(page): Query<Page>) -> Response {
+        respond(&explorer, ExplorerQuery::Transactions(page)).await
+    }
+
+    async fn agent(State(explorer): State<Arc<Explorer>>, Path(id): Path<String>) -> Response {
+        match AgentId::from_string(&id) {
+            Ok(agent_id) => respond(&explorer, ExplorerQuery::Agent(agent_id)).await,
+            Err(_) => error(StatusCode::BAD_REQUEST, "invalid agent id"),
+        }
+    }
+
+    async fn reputation(State(explorer): State<Arc<Explorer>>, Path(id): Path<String>, Query(page): Query<Page>) -> Response {
+        match AgentId::from_string(&id) {
+            Ok(agent_id) => respond(&explorer, ExplorerQuery::ReputationHistory(agent_id, page)).await,
+            Err(_) => error(StatusCode::BAD_REQUEST, "invalid agent id"),
+        }
+    }
+
+    async fn blocks(State(explorer): State<Arc<Explorer>>, Query(page): Query<Page>) -> Response {
+        respond(&explorer, ExplorerQuery::Blocks(page)).await
+    }
+
+    async fn validators(State(explorer): State<Arc<Explorer>>) -> Response {
+        respond(&explorer, ExplorerQuery::Validators).await
+    }
+
+    async fn stats(State(explorer): State<Arc<Explorer>>) -> Response {
+        respond(&explorer, ExplorerQuery::Stats).await
+    }
+
+    async fn respond(explorer: &Explorer, query: ExplorerQuery) -> Response {
+        match explorer.query(query).await {
+            Ok(Some(body)) => {
+                let max_age = format!("public, max-age={}", explorer.config().cache_ttl.as_secs());
+                ([(header::CACHE_CONTROL, max_age.as_str()), (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")], Json(body)).into_response()
+            }
+            Ok(None) => error(StatusCode::NOT_FOUND, "not found"),
+            Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+        }
+    }
+
+    fn error(status: StatusCode, message: &str) -> Response {
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StorageManager;
+    use crate::transaction::TransactionRequest;
+
+    #[tokio::test]
+    async fn test_pages_profiles_and_cache() {
+        let observer = Arc::new(RwLock::new(ObserverNode::new(StorageManager::memory()).await.unwrap()));
+        let requester = AgentId::new();
+        for i in 0..5 {
+            let request = TransactionRequest::new(
+                requester,
+                ServiceType::MarketResearch,
+                format!("Survey {}", i),
+                Balance::from_sol(1.0),
+                Timestamp::now(),
+            );
+            observer.write().await.observe_transaction(Transaction::new(request)).await.unwrap();
+        }
+        let explorer = Explorer::new(observer.clone(), ExplorerConfig::default());
+
+        let first = explorer.recent_transactions(&Page { offset: 0, limit: Some(2) }).await;
+        assert_eq!((first.items.len(), first.total, first.next_offset), (2, 5, Some(2)));
+        let last = explorer.recent_transactions(&Page { offset: 4, limit: Some(2) }).await;
+        assert_eq!((last.items.len(), last.next_offset), (1, None));
+
+        let profile = explorer.agent_profile(&requester).await.unwrap().unwrap();
+        assert_eq!((profile.requested, profile.provided), (5, 0));
+        assert_eq!(profile.services, vec![ServiceType::MarketResearch]);
+        assert!(explorer.query(ExplorerQuery::Agent(AgentId::new())).await.unwrap().is_none());
+
+        // Answers are served from cache until it is cleared or expires
+        let stats = explorer.query(ExplorerQuery::Stats).await.unwrap().unwrap();
+        assert_eq!(stats["transactions"], 5);
+        let request = TransactionRequest::new(AgentId::new(), ServiceType::DataAnalysis, "Audit".to_string(), Balance::from_sol(1.0), Timestamp::now());
+        observer.write().await.observe_transaction(Transaction::new(request)).await.unwrap();
+        assert_eq!(explorer.query(ExplorerQuery::Stats).await.unwrap().unwrap()["transactions"], 5);
+        explorer.clear_cache();
+        assert_eq!(explorer.query(ExplorerQuery::Stats).await.unwrap().unwrap()["transactions"], 6);
+    }
+}
@@ -0,0 +1,243 @@
+//! Instruction Failure Analysis
+//!
+//! RPC reports a failed instruction as an opaque string such as
+//! `Error processing Instruction 0: custom program error: 0x1770`. The
+//! analyzer combines the runtime's `TransactionError` with the transaction's
+//! logs (from preflight simulation, or fetched once the transaction landed)
+//! to name the failing program and account, maps known error codes of the
+//! system, token and Solace programs to `ChainError` variants, and attaches
+//! a remediation hint.
+
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+
+use crate::error::ChainError;
+
+const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const ASSOCIATED_TOKEN_PROGRAM: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+/// `SystemError::ResultWithNegativeLamports`
+const SYSTEM_INSUFFICIENT_LAMPORTS: u32 = 1;
+/// `TokenError::NotRentExempt` and `TokenError::InsufficientFunds`
+const TOKEN_NOT_RENT_EXEMPT: u32 = 0;
+const TOKEN_INSUFFICIENT_FUNDS: u32 = 1;
+/// Anchor's `ErrorCode::AccountNotInitialized`
+const ANCHOR_ACCOUNT_NOT_INITIALIZED: u32 = 3012;
+/// Solace program's `StaleReputation`, the first of its custom errors
+const SOLACE_STALE_REPUTATION: u32 = 6000;
+
+/// A decoded instruction failure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureDiagnosis {
+    pub error: ChainError,
+    pub instruction: Option<u8>,          // Index of the failing instruction
+    pub program: Option<String>,          // Program that reported the failure
+    pub remediation: String,
+    pub logs: Vec<String>,
+}
+
+/// Explains instruction failures for transactions against one Solace program
+#[derive(Debug, Clone)]
+pub struct FailureAnalyzer {
+    program_id: String,
+}
+
+impl FailureAnalyzer {
+    pub fn new(program_id: &Pubkey) -> Self {
+        Self { program_id: program_id.to_string() }
+    }
+
+    /// Diagnose a failure from the runtime error and the transaction's logs
+    pub fn analyze(&self, error: Option<&TransactionError>, logs: &[String]) -> FailureDiagnosis {
+        let failed = failed_program(logs);
+        let anchor = anchor_error(logs);
+        let (instruction, chain_error) = match error {
+            Some(TransactionError::InsufficientFundsForRent { account_index }) => {
+                (None, ChainError::InsufficientRent { account: format!("#{}", account_index) })
+            }
+            Some(TransactionError::InstructionError(index, instruction_error)) => {
+                (Some(*index), self.instruction_error(instruction_error, failed.as_ref(), anchor.as_ref(), logs))
+            }
+            Some(other) => (None, ChainError::InstructionFailed { message: other.to_string() }),
+            None => (None, self.logged_error(failed.as_ref(), anchor.as_ref(), logs)),
+        };
+
+        FailureDiagnosis {
+            remediation: chain_error.remediation().to_string(),
+            error: chain_error,
+            instruction,
+            program: failed.map(|(program, _)| program),
+            logs: logs.to_vec(),
+        }
+    }
+
+    /// Diagnose a failed send; `None` if the error is not a transaction failure
+    pub fn analyze_client_error(&self, error: &ClientError) -> Option<FailureDiagnosis> {
+        match error.kind() {
+            // Preflight simulation carries the logs with it
+            ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::SendTransactionPreflightFailure(simulation),
+                ..
+            }) => Some(self.analyze(simulation.err.as_ref(), simulation.logs.as_deref().unwrap_or_default())),
+            ClientErrorKind::TransactionError(transaction_error) => Some(self.analyze(Some(transaction_error), &[])),
+            _ => None,
+        }
+    }
+
+    fn instruction_error(
+        &self,
+        error: &InstructionError,
+        failed: Option<&(String, String)>,
+        anchor: Option<&AnchorError>,
+        logs: &[String],
+    ) -> ChainError {
+        let program = failed.map(|(program, _)| program.as_str()).unwrap_or_default();
+        match error {
+            InstructionError::Custom(code) => self.custom_error(program, *code, anchor),
+            InstructionError::InsufficientFunds => ChainError::InsufficientFunds { detail: insufficient_lamports(logs).unwrap_or_else(|| error.to_string()) },
+            InstructionError::InvalidAccountData | InstructionError::UninitializedAccount | InstructionError::NotEnoughAccountKeys
+                if program == TOKEN_PROGRAM || program == ASSOCIATED_TOKEN_PROGRAM =>
+            {
+                ChainError::MissingTokenAccount { account: anchor_account(anchor).unwrap_or_else(|| "token account".to_string()) }
+            }
+            InstructionError::UninitializedAccount => ChainError::AccountNotInitialized { account: anchor_account(anchor).unwrap_or_default() },
+            _ => ChainError::InstructionFailed { message: error.to_string() },
+        }
+    }
+
+    fn custom_error(&self, program: &str, code: u32, anchor: Option<&AnchorError>) -> ChainError {
+        let account = anchor_account(anchor);
+        match (program, code) {
+            (SYSTEM_PROGRAM, SYSTEM_INSUFFICIENT_LAMPORTS) | (TOKEN_PROGRAM, TOKEN_INSUFFICIENT_FUNDS) => {
+                ChainError::InsufficientFunds { detail: format!("{} rejected the transfer", program) }
+            }
+            (TOKEN_PROGRAM, TOKEN_NOT_RENT_EXEMPT) => ChainError::InsufficientRent { account: account.unwrap_or_else(|| "token account".to_string()) },
+            (_, ANCHOR_ACCOUNT_NOT_INITIALIZED) => {
+                let account = account.unwrap_or_default();
+                if is_token_account(&account) {
+                    ChainError::MissingTokenAccount { account }
+                } else {
+                    ChainError::AccountNotInitialized { account }
+                }
+            }
+            (program, SOLACE_STALE_REPUTATION) if program == self.program_id => {
+                ChainError::StaleReputationAccount { account: account.unwrap_or_else(|| "reputation".to_string()) }
+            }
+            _ => ChainError::ProgramError { program: program.to_string(), code },
+        }
+    }
+
+    /// Best guess when only logs are available
+    fn logged_error(&self, failed: Option<&(String, String)>, anchor: Option<&AnchorError>, logs: &[String]) -> ChainError {
+        if let Some(detail) = insufficient_lamports(logs) {
+            return ChainError::InsufficientFunds { detail };
+        }
+        if let Some(anchor) = anchor {
+            let program = failed.map(|(program, _)| program.as_str()).unwrap_or_default();
+            return self.custom_error(program, anchor.number, Some(anchor));
+        }
+        match failed {
+            Some((_, reason)) => ChainError::InstructionFailed { message: reason.clone() },
+            None => ChainError::InstructionFailed { message: "no failure found in logs".to_string() },
+        }
+    }
+}
+
+/// Error logged by an Anchor program
+#[derive(Debug, Clone, PartialEq)]
+struct AnchorError {
+    account: Option<String>,
+    number: u32,
+}
+
+/// The last `Program <id> failed: <reason>` line
+fn failed_program(logs: &[String]) -> Option<(String, String)> {
+    logs.iter().rev().find_map(|line| {
+        let rest = line.strip_prefix("Program ")?;
+        let (program, reason) = rest.split_once(" failed: ")?;
+        Some((program.to_string(), reason.to_string()))
+    })
+}
+
+/// `Program log: AnchorError caused by account: <name>. Error Code: <code>. Error Number: <n>. ...`
+fn anchor_error(logs: &[String]) -> Option<AnchorError> {
+    logs.iter().rev().find_map(|line| {
+        let rest = line.strip_prefix("Program log: AnchorError")?;
+        let account = rest
+            .split_once("caused by account: ")
+            .and_then(|(_, after)| after.split_once('.'))
+            .map(|(name, _)| name.to_string());
+        let (_, after) = rest.split_once("Error Number: ")?;
+        let number = after.split('.').next()?.trim().parse().ok()?;
+        Some(AnchorError { account, number })
+    })
+}
+
+fn anchor_account(anchor: Option<&AnchorError>) -> Option<String> {
+    anchor.and_then(|anchor| anchor.account.clone())
+}
+
+/// Anchor names token accounts like `payer_token_account` or `recipient_ata`
+fn is_token_account(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("token") || name.contains("ata")
+}
+
+/// `Transfer: insufficient lamports <have>, need <need>` from the system program
+fn insufficient_lamports(logs: &[String]) -> Option<String> {
+    logs.iter().find_map(|line| line.strip_prefix("Transfer: ").filter(|rest| rest.starts_with("insufficient lamports")).map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_program_errors_map_to_typed_failures() {
+        let program = Pubkey::new_unique();
+        let analyzer = FailureAnalyzer::new(&program);
+
+        let stale = analyzer.analyze(
+            Some(&TransactionError::InstructionError(0, InstructionError::Custom(6000))),
+            &logs(&[
+                &format!("Program {} invoke [1]", program),
+                "Program log: AnchorError caused by account: agent_reputation. Error Code: StaleReputation. Error Number: 6000. Error Message: Reputation is stale.",
+                &format!("Program {} failed: custom program error: 0x1770", program),
+            ]),
+        );
+        assert_eq!(stale.error, ChainError::StaleReputationAccount { account: "agent_reputation".to_string() });
+        assert_eq!((stale.instruction, stale.program), (Some(0), Some(program.to_string())));
+        assert_eq!(stale.remediation, stale.error.remediation());
+
+        let missing_ata = analyzer.analyze(
+            Some(&TransactionError::InstructionError(1, InstructionError::Custom(3012))),
+            &logs(&["Program log: AnchorError caused by account: recipient_token_account. Error Code: AccountNotInitialized. Error Number: 3012. Error Message: The program expected this account to be already initialized."]),
+        );
+        assert_eq!(missing_ata.error, ChainError::MissingTokenAccount { account: "recipient_token_account".to_string() });
+
+        let rent = analyzer.analyze(Some(&TransactionError::InsufficientFundsForRent { account_index: 2 }), &[]);
+        assert_eq!(rent.error, ChainError::InsufficientRent { account: "#2".to_string() });
+
+        let broke = analyzer.analyze(
+            Some(&TransactionError::InstructionError(0, InstructionError::Custom(1))),
+            &logs(&["Transfer: insufficient lamports 5000, need 10000", &format!("Program {} failed: custom program error: 0x1", SYSTEM_PROGRAM)]),
+        );
+        assert!(matches!(broke.error, ChainError::InsufficientFunds { .. }));
+
+        // Unknown codes from other programs are kept, not guessed at
+        let other = analyzer.analyze(
+            Some(&TransactionError::InstructionError(0, InstructionError::Custom(6000))),
+            &logs(&["Program Other111 failed: custom program error: 0x1770"]),
+        );
+        assert_eq!(other.error, ChainError::ProgramError { program: "Other111".to_string(), code: 6000 });
+    }
+}
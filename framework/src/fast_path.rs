@@ -0,0 +1,170 @@
+//! Auto-Accept Fast Path
+//!
+//! Latency-sensitive micro-transactions should not wait on a full strategy
+//! evaluation when the answer is obvious. The fast path accepts a
+//! counter-offer outright when the deal is small, the counterparty's
+//! reputation clears `AgentPreferences::auto_accept_threshold`, the offer is
+//! close to our ask, and accepting passes every hard limit: governance price
+//! bounds, execution cost, the risk budget, and our maximum transaction
+//! value. Anything else falls through to the normal evaluation. Every
+//! decision is counted, and the fast path can be switched off at runtime.
+
+use serde::{Deserialize, Serialize};
+use solace_ai::strategy::{CounterOfferResponse, NegotiationState};
+use std::collections::BTreeMap;
+
+use crate::types::Balance;
+
+/// When counter-offers may skip full evaluation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastPathPolicy {
+    pub enabled: bool,                    // Kill switch
+    pub min_counterparty_reputation: f64,
+    pub min_offer_ratio: f64,             // Offer / our current ask
+    pub max_value: Balance,               // Largest deal treated as a micro-transaction
+}
+
+impl FastPathPolicy {
+    /// Policy gated on the given counterparty reputation
+    pub fn with_reputation_threshold(min_counterparty_reputation: f64) -> Self {
+        Self {
+            min_counterparty_reputation,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for FastPathPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_counterparty_reputation: 0.8,
+            min_offer_ratio: 0.95,
+            max_value: Balance::from_sol(0.1),
+        }
+    }
+}
+
+/// Why a counter-offer took the full evaluation instead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum FastPathMiss {
+    Disabled,
+    NotMicro,                             // Above the micro-transaction limit
+    Reputation,
+    OfferRatio,
+    Limits,                               // Bounds, cost, risk budget, or max transaction value
+}
+
+/// Fast path usage counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FastPathMetrics {
+    pub evaluated: u64,
+    pub accepted: u64,
+    pub misses: BTreeMap<FastPathMiss, u64>,
+}
+
+impl FastPathMetrics {
+    /// Share of counter-offers accepted on the fast path
+    pub fn usage_rate(&self) -> f64 {
+        if self.evaluated == 0 {
+            0.0
+        } else {
+            self.accepted as f64 / self.evaluated as f64
+        }
+    }
+}
+
+/// Fast path policy with its metrics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FastPath {
+    pub policy: FastPathPolicy,
+    pub metrics: FastPathMetrics,
+}
+
+impl FastPath {
+    pub fn new(policy: FastPathPolicy) -> Self {
+        Self {
+            policy,
+            metrics: FastPathMetrics::default(),
+        }
+    }
+
+    /// Check whether an offer can be accepted without full evaluation,
+    /// recording the outcome
+    pub fn check(&mut self, state: &NegotiationState, offer: f64, max_transaction_value: Balance) -> Result<(), FastPathMiss> {
+        self.metrics.evaluated += 1;
+        let result = self.evaluate(state, offer, max_transaction_value);
+        match result {
+            Ok(()) => self.metrics.accepted += 1,
+            Err(miss) => *self.metrics.misses.entry(miss).or_insert(0) += 1,
+        }
+        result
+    }
+
+    fn evaluate(&self, state: &NegotiationState, offer: f64, max_transaction_value: Balance) -> Result<(), FastPathMiss> {
+        let policy = &self.policy;
+        if !policy.enabled {
+            return Err(FastPathMiss::Disabled);
+        }
+        if offer > policy.max_value.to_sol() {
+            return Err(FastPathMiss::NotMicro);
+        }
+        if state.context.counterparty_reputation < policy.min_counterparty_reputation {
+            return Err(FastPathMiss::Reputation);
+        }
+        let ask = state.current_ask().unwrap_or(state.base_price);
+        if ask <= 0.0 || offer / ask < policy.min_offer_ratio {
+            return Err(FastPathMiss::OfferRatio);
+        }
+        if offer > max_transaction_value.to_sol() || state.bound_response(offer, CounterOfferResponse::Accept) != CounterOfferResponse::Accept {
+            return Err(FastPathMiss::Limits);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solace_ai::{DecisionContext, ExecutionCost, MarketConditions};
+
+    fn state(counterparty_reputation: f64, ask: f64) -> NegotiationState {
+        let context = DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation,
+            transaction_value: ask,
+            market_conditions: MarketConditions {
+                demand_level: 0.5,
+                competition_level: 0.5,
+                average_pricing: ask,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        let mut state = NegotiationState::new(context, ask, 5);
+        state.our_asks.push(ask);
+        state
+    }
+
+    #[test]
+    fn test_fast_path_accepts_only_safe_micro_deals() {
+        let mut fast_path = FastPath::new(FastPathPolicy::with_reputation_threshold(0.8));
+        let max_value = Balance::from_sol(100.0);
+
+        assert_eq!(fast_path.check(&state(0.9, 0.05), 0.049, max_value), Ok(()));
+        assert_eq!(fast_path.check(&state(0.9, 5.0), 4.9, max_value), Err(FastPathMiss::NotMicro));
+        assert_eq!(fast_path.check(&state(0.5, 0.05), 0.049, max_value), Err(FastPathMiss::Reputation));
+        assert_eq!(fast_path.check(&state(0.9, 0.05), 0.04, max_value), Err(FastPathMiss::OfferRatio));
+
+        let below_cost = state(0.9, 0.05).with_execution_cost(Some(ExecutionCost::new(0.05, 0.1)));
+        assert_eq!(fast_path.check(&below_cost, 0.049, max_value), Err(FastPathMiss::Limits));
+
+        fast_path.policy.enabled = false;
+        assert_eq!(fast_path.check(&state(0.9, 0.05), 0.049, max_value), Err(FastPathMiss::Disabled));
+
+        assert_eq!((fast_path.metrics.evaluated, fast_path.metrics.accepted), (6, 1));
+        assert_eq!(fast_path.metrics.misses.get(&FastPathMiss::Reputation), Some(&1));
+    }
+}
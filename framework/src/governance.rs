@@ -0,0 +1,343 @@
+//! Protocol Governance
+//!
+//! Network-wide protocol parameters distributed through governance. Each
+//! update carries a version and agents only apply newer ones. Per-service
+//! price bounds guard against predatory pricing: they constrain what the
+//! negotiation layer asks and accepts, and proposals outside them are refused
+//! at acceptance. Violations become reputation penalties against the offender.
+//! Updates with a floor above a ceiling are refused.
+//!
+//! Updates travel over ACP as `ParamsUpdate`s, which agents apply only once
+//! the governance council's multisig threshold has signed them.
+//!
+//! Bounds are in base units of the payment asset: the SOL table in lamports,
+//! and one table per SPL mint in that token's smallest unit.
+
+use serde::{Deserialize, Serialize};
+use solace_ai::PriceBounds;
+use std::collections::HashMap;
+
+use crate::{
+    acp::MessageType,
+    crypto::{KeyPair, MultisigWallet, PartialSignature},
+    error::{Result, SolaceError},
+    reputation::{ReputationEvent, ReputationEventType, ReputationWeight},
+    types::{AgentId, Balance, Payment, PaymentAsset, ServiceType, Timestamp, TransactionId},
+};
+
+/// Price floor and ceiling for a service type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServicePriceBounds {
+    pub floor: Balance,
+    pub ceiling: Balance,
+}
+
+impl ServicePriceBounds {
+    /// Bounds from `floor` to `ceiling`, refusing a floor above the ceiling
+    pub fn new(floor: Balance, ceiling: Balance) -> Result<Self> {
+        let bounds = Self { floor, ceiling };
+        bounds.validate()?;
+        Ok(bounds)
+    }
+
+    /// Refuse a floor above the ceiling
+    pub fn validate(&self) -> Result<()> {
+        if self.floor > self.ceiling {
+            return Err(SolaceError::config(format!(
+                "price floor {} is above ceiling {}", self.floor.0, self.ceiling.0
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether a price is within the bounds
+    pub fn contains(&self, price: Balance) -> bool {
+        price >= self.floor && price <= self.ceiling
+    }
+
+    /// Bounds in whole units of `asset` for the negotiation layer, if valid
+    pub fn to_units(&self, asset: &PaymentAsset) -> Option<PriceBounds> {
+        PriceBounds::new(
+            Payment::new(*asset, self.floor.0).to_units(),
+            Payment::new(*asset, self.ceiling.0).to_units(),
+        )
+    }
+}
+
+/// Governance-controlled protocol parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolParams {
+    pub version: u64,                                          // Monotonic; only newer updates apply
+    pub price_bounds: HashMap<ServiceType, ServicePriceBounds>,  // SOL prices, in lamports
+    #[serde(default)]
+    pub token_price_bounds: HashMap<PaymentAsset, HashMap<ServiceType, ServicePriceBounds>>,
+    pub report_violations: bool,                               // Queue violations for reputation penalties
+    pub violation_penalty: f64,                                // Reputation delta per violation
+    #[serde(default = "default_response_timeout_secs")]
+    pub negotiation_response_timeout_secs: u64,                // Per-round answer deadline
+}
+
+fn default_response_timeout_secs() -> u64 {
+    crate::constants::NEGOTIATION_RESPONSE_TIMEOUT.as_secs()
+}
+
+impl Default for ProtocolParams {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            price_bounds: HashMap::new(),
+            token_price_bounds: HashMap::new(),
+            report_violations: false,
+            violation_penalty: 0.0,
+            negotiation_response_timeout_secs: default_response_timeout_secs(),
+        }
+    }
+}
+
+impl ProtocolParams {
+    /// Apply a governance update if it is newer and valid, returning
+    /// whether it applied
+    pub fn apply(&mut self, update: ProtocolParams) -> bool {
+        if update.version <= self.version {
+            return false;
+        }
+        if let Err(e) = update.validate() {
+            tracing::warn!("Ignoring protocol params v{}: {}", update.version, e);
+            return false;
+        }
+        *self = update;
+        true
+    }
+
+    /// Refuse bounds with a floor above the ceiling
+    pub fn validate(&self) -> Result<()> {
+        let tokens = self.token_price_bounds.values().flat_map(|bounds| bounds.values());
+        self.price_bounds.values().chain(tokens).try_for_each(ServicePriceBounds::validate)
+    }
+
+    /// Price bounds for a service type paid in `asset`, if governed
+    pub fn bounds_for(&self, service_type: &ServiceType, asset: &PaymentAsset) -> Option<ServicePriceBounds> {
+        match asset {
+            PaymentAsset::Sol => self.price_bounds.get(service_type).copied(),
+            PaymentAsset::Spl { .. } => self.token_price_bounds.get(asset)?.get(service_type).copied(),
+        }
+    }
+
+    /// Price bounds in whole units of `asset` for negotiation, if governed
+    pub fn negotiation_bounds(&self, service_type: &ServiceType, asset: &PaymentAsset) -> Option<PriceBounds> {
+        self.bounds_for(service_type, asset).and_then(|bounds| bounds.to_units(asset))
+    }
+
+    /// Check an agreed price, in base units of `asset`, against the bounds
+    /// for its service
+    pub fn check_price(
+        &self,
+        transaction_id: TransactionId,
+        offender: AgentId,
+        service_type: &ServiceType,
+        asset: &PaymentAsset,
+        price: Balance,
+    ) -> Option<PriceViolation> {
+        let bounds = self.bounds_for(service_type, asset)?;
+        if bounds.contains(price) {
+            return None;
+        }
+        Some(PriceViolation {
+            transaction_id,
+            offender,
+            service_type: service_type.clone(),
+            asset: *asset,
+            price,
+            bounds,
+            detected_at: Timestamp::now(),
+        })
+    }
+}
+
+/// Protocol parameters as broadcast, with the council's approvals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParamsUpdate {
+    pub params: ProtocolParams,
+    pub approvals: Vec<PartialSignature>,
+}
+
+impl ParamsUpdate {
+    pub fn new(params: ProtocolParams) -> Self {
+        Self { params, approvals: Vec::new() }
+    }
+
+    /// What council members sign: the parameters in canonical JSON
+    pub fn signing_message(&self) -> Result<Vec<u8>> {
+        // Going through `Value` sorts map keys, so every node signs the same bytes
+        let canonical = serde_json::to_vec(&serde_json::to_value(&self.params)?)?;
+        Ok([b"solace-params:".as_slice(), &canonical].concat())
+    }
+
+    /// Add a council member's approval
+    pub fn approve(mut self, keypair: &KeyPair) -> Result<Self> {
+        let share = PartialSignature::sign(keypair, &self.signing_message()?);
+        self.approvals.retain(|existing| existing.signer != share.signer);
+        self.approvals.push(share);
+        Ok(self)
+    }
+
+    /// Check that enough of the council approved these parameters
+    pub fn verify(&self, council: &MultisigWallet) -> Result<()> {
+        council.verify(&self.signing_message()?, &self.approvals)
+    }
+
+    /// Gossip message type carrying updates
+    pub fn message_type(&self) -> MessageType {
+        MessageType::ProtocolParamsUpdate
+    }
+
+    /// Gossip payload
+    pub fn to_payload(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    pub fn from_payload(payload: serde_json::Value) -> Result<Self> {
+        Ok(serde_json::from_value(payload)?)
+    }
+}
+
+/// A price outside governance bounds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceViolation {
+    pub transaction_id: TransactionId,
+    pub offender: AgentId,
+    pub service_type: ServiceType,
+    #[serde(default)]
+    pub asset: PaymentAsset,
+    pub price: Balance,                   // In base units of `asset`
+    pub bounds: ServicePriceBounds,
+    pub detected_at: Timestamp,
+}
+
+impl PriceViolation {
+    /// Whether the price undercut the floor (as opposed to exceeding the ceiling)
+    pub fn below_floor(&self) -> bool {
+        self.price < self.bounds.floor
+    }
+
+    /// Reputation penalty for the offender
+    pub fn reputation_event(&self, penalty: f64) -> ReputationEvent {
+        ReputationEvent {
+            timestamp: self.detected_at,
+            event_type: ReputationEventType::PricingViolation,
+            weight: ReputationWeight::Medium,
+            delta: -penalty.abs(),
+            counterparty: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(version: u64) -> ProtocolParams {
+        let mut params = ProtocolParams {
+            version,
+            report_violations: true,
+            violation_penalty: 1.0,
+            ..ProtocolParams::default()
+        };
+        params.price_bounds.insert(
+            ServiceType::DataAnalysis,
+            ServicePriceBounds::new(Balance::from_sol(1.0), Balance::from_sol(10.0)).unwrap(),
+        );
+        params
+    }
+
+    #[test]
+    fn test_only_newer_params_apply() {
+        let mut current = params(2);
+        assert!(!current.apply(ProtocolParams { version: 1, ..ProtocolParams::default() }));
+        assert!(current.bounds_for(&ServiceType::DataAnalysis, &PaymentAsset::Sol).is_some());
+
+        assert!(current.apply(ProtocolParams { version: 3, ..ProtocolParams::default() }));
+        assert!(current.bounds_for(&ServiceType::DataAnalysis, &PaymentAsset::Sol).is_none());
+    }
+
+    #[test]
+    fn test_inverted_bounds_are_refused() {
+        assert!(ServicePriceBounds::new(Balance::from_sol(10.0), Balance::from_sol(1.0)).is_err());
+
+        let mut update = params(2);
+        update.price_bounds.insert(
+            ServiceType::MarketResearch,
+            ServicePriceBounds { floor: Balance::from_sol(10.0), ceiling: Balance::from_sol(1.0) },
+        );
+        assert!(update.validate().is_err());
+
+        let mut current = params(1);
+        assert!(!current.apply(update));
+        assert_eq!(current.version, 1);
+    }
+
+    #[test]
+    fn test_price_violations() {
+        let params = params(1);
+        let check = |service: &ServiceType, sol: f64| {
+            params.check_price(TransactionId::new(), AgentId::new(), service, &PaymentAsset::Sol, Balance::from_sol(sol))
+        };
+
+        assert!(check(&ServiceType::DataAnalysis, 5.0).is_none());
+        assert!(check(&ServiceType::MarketResearch, 500.0).is_none());
+
+        let violation = check(&ServiceType::DataAnalysis, 0.1).unwrap();
+        assert!(violation.below_floor());
+        assert!(violation.reputation_event(params.violation_penalty).delta < 0.0);
+        assert!(!check(&ServiceType::DataAnalysis, 50.0).unwrap().below_floor());
+    }
+
+    #[test]
+    fn test_token_bounds_are_kept_apart_from_sol() {
+        let usdc = PaymentAsset::Spl { mint: solana_sdk::pubkey::Pubkey::new_unique(), decimals: 6 };
+        let mut params = params(1);
+        params.token_price_bounds.entry(usdc).or_default().insert(
+            ServiceType::DataAnalysis,
+            ServicePriceBounds::new(Balance(5_000_000), Balance(50_000_000)).unwrap(),
+        );
+
+        // 20 USDC is in bounds even though 20_000_000 lamports is below the SOL floor
+        let price = Balance(20_000_000);
+        let check = |asset: &PaymentAsset| params.check_price(TransactionId::new(), AgentId::new(), &ServiceType::DataAnalysis, asset, price);
+        assert!(check(&usdc).is_none());
+        assert!(check(&PaymentAsset::Sol).unwrap().below_floor());
+
+        let other = PaymentAsset::Spl { mint: solana_sdk::pubkey::Pubkey::new_unique(), decimals: 6 };
+        assert!(check(&other).is_none());
+        assert_eq!(
+            params.negotiation_bounds(&ServiceType::DataAnalysis, &usdc),
+            PriceBounds::new(5.0, 50.0),
+        );
+
+        let json = serde_json::to_string(&params).unwrap();
+        let restored: ProtocolParams = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.bounds_for(&ServiceType::DataAnalysis, &usdc), params.bounds_for(&ServiceType::DataAnalysis, &usdc));
+    }
+
+    #[test]
+    fn test_updates_need_the_council_threshold() {
+        let members: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate().unwrap()).collect();
+        let keys: Vec<_> = members.iter().map(|member| *member.verifying_key()).collect();
+        let council = MultisigWallet::new(&keys, 2).unwrap();
+
+        let update = ParamsUpdate::new(params(2)).approve(&members[0]).unwrap();
+        assert!(update.verify(&council).is_err());
+
+        let update = update.approve(&members[1]).unwrap();
+        let received = ParamsUpdate::from_payload(update.to_payload().unwrap()).unwrap();
+        assert!(received.verify(&council).is_ok());
+
+        let mut tampered = received;
+        tampered.params.violation_penalty = 100.0;
+        assert!(tampered.verify(&council).is_err());
+
+        let outsider = KeyPair::generate().unwrap();
+        let forged = ParamsUpdate::new(params(3)).approve(&outsider).unwrap().approve(&members[0]).unwrap();
+        assert!(forged.verify(&council).is_err());
+    }
+}
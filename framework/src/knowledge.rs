@@ -0,0 +1,215 @@
+//! Fleet Knowledge Sharing
+//!
+//! Agents with the same owner form a trust domain and pool their market
+//! observations on a private gossip topic named after the owner's key.
+//! Membership is authenticated: the owner signs a `DomainMembership` binding
+//! each agent to the key it signs with, and every published batch carries
+//! that certificate and the agent's signature. Members verify both, and that
+//! the agent's ID is derived from its key, before merging, so agents outside
+//! the domain can neither read themselves in nor forge a member's
+//! observations.
+
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use solace_ai::sharing::{ObservationBatch, ObservationMerger};
+use solace_ai::MarketPredictor;
+use std::collections::HashMap;
+
+use crate::{
+    crypto::{verify_sender, KeyPair, Signature},
+    error::{CryptoError, Result},
+    types::{AgentId, ServiceType},
+};
+
+/// Prefix of knowledge-sharing gossip topics
+pub const KNOWLEDGE_TOPIC_PREFIX: &str = "knowledge.";
+
+/// Domain separation for membership certificates
+const MEMBERSHIP_CONTEXT: &[u8] = b"solace-domain-membership";
+
+/// Owner's certificate that an agent belongs to its trust domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainMembership {
+    pub agent_id: AgentId,
+    pub member_key: [u8; 32],             // Key the agent signs shared batches with
+    pub signature: Signature,             // By the owner
+}
+
+impl DomainMembership {
+    /// Admit an agent to the owner's trust domain
+    pub fn issue(owner: &KeyPair, agent_id: AgentId, member_key: &VerifyingKey) -> Result<Self> {
+        let member_key = member_key.to_bytes();
+        let signature = owner.sign(&Self::signed_bytes(&agent_id, &member_key)?);
+        Ok(Self { agent_id, member_key, signature })
+    }
+
+    fn signed_bytes(agent_id: &AgentId, member_key: &[u8; 32]) -> Result<Vec<u8>> {
+        let mut bytes = MEMBERSHIP_CONTEXT.to_vec();
+        bytes.extend_from_slice(&serde_json::to_vec(agent_id)?);
+        bytes.extend_from_slice(member_key);
+        Ok(bytes)
+    }
+
+    fn member_key(&self) -> Result<VerifyingKey> {
+        VerifyingKey::from_bytes(&self.member_key).map_err(|_| CryptoError::InvalidKeyFormat.into())
+    }
+}
+
+/// Signed observations published by one member
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedObservations {
+    pub membership: DomainMembership,
+    pub markets: Vec<(ServiceType, ObservationBatch)>,
+    pub signature: Signature,             // By the member key
+}
+
+impl SharedObservations {
+    fn signed_bytes(markets: &[(ServiceType, ObservationBatch)]) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(markets)?)
+    }
+}
+
+/// Agents run by one owner
+#[derive(Debug, Clone)]
+pub struct TrustDomain {
+    owner: VerifyingKey,
+}
+
+impl TrustDomain {
+    pub fn new(owner: VerifyingKey) -> Self {
+        Self { owner }
+    }
+
+    /// Private gossip topic for this domain
+    pub fn topic(&self) -> String {
+        let fingerprint: String = self.owner.to_bytes()[..8].iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}{}", KNOWLEDGE_TOPIC_PREFIX, fingerprint)
+    }
+
+    /// Check that observations come from a member and were not altered
+    pub fn verify(&self, shared: &SharedObservations) -> Result<()> {
+        let membership = &shared.membership;
+        membership
+            .signature
+            .verify(&DomainMembership::signed_bytes(&membership.agent_id, &membership.member_key)?, &self.owner)
+            .map_err(|_| CryptoError::NotADomainMember(membership.agent_id.to_string()))?;
+        verify_sender(&membership.agent_id, &membership.member_key()?)?;
+
+        let source = membership.agent_id.to_string();
+        if shared.markets.iter().any(|(_, batch)| batch.source != source) {
+            return Err(CryptoError::NotADomainMember(format!("{} published another agent's observations", source)).into());
+        }
+        shared.signature.verify(&SharedObservations::signed_bytes(&shared.markets)?, &membership.member_key()?)
+    }
+}
+
+/// An agent's seat in its trust domain
+#[derive(Debug)]
+pub struct KnowledgeMember {
+    pub domain: TrustDomain,
+    pub membership: DomainMembership,
+    pub merger: ObservationMerger,
+    keypair: KeyPair,
+    shared_up_to: HashMap<ServiceType, u64>,  // Last of our own observations published
+}
+
+impl KnowledgeMember {
+    /// Join a domain with the key the membership was issued for
+    pub fn new(domain: TrustDomain, keypair: KeyPair, membership: DomainMembership) -> Result<Self> {
+        if keypair.verifying_key().to_bytes() != membership.member_key {
+            return Err(CryptoError::InvalidKeyFormat.into());
+        }
+        verify_sender(&membership.agent_id, keypair.verifying_key())?;
+        Ok(Self {
+            domain,
+            membership,
+            merger: ObservationMerger::default(),
+            keypair,
+            shared_up_to: HashMap::new(),
+        })
+    }
+
+    /// Sign our observations not yet published, or `None` if there are none
+    pub fn share(&mut self, predictors: &HashMap<ServiceType, MarketPredictor>) -> Result<Option<SharedObservations>> {
+        let source = self.membership.agent_id.to_string();
+        let markets: Vec<(ServiceType, ObservationBatch)> = predictors
+            .iter()
+            .filter_map(|(service_type, predictor)| {
+                let observations = predictor.local_observations_since(self.shared_up_to.get(service_type).copied());
+                (!observations.is_empty())
+                    .then(|| (service_type.clone(), ObservationBatch { source: source.clone(), observations }))
+            })
+            .collect();
+        if markets.is_empty() {
+            return Ok(None);
+        }
+
+        let signature = self.keypair.sign(&SharedObservations::signed_bytes(&markets)?);
+        for (service_type, batch) in &markets {
+            if let Some(last) = batch.observations.last() {
+                self.shared_up_to.insert(service_type.clone(), last.sequence);
+            }
+        }
+        Ok(Some(SharedObservations { membership: self.membership.clone(), markets, signature }))
+    }
+
+    /// Verify and merge another member's observations, returning how many
+    /// went in
+    pub fn receive(&mut self, shared: &SharedObservations, predictors: &mut HashMap<ServiceType, MarketPredictor>) -> Result<usize> {
+        self.domain.verify(shared)?;
+        if shared.membership.agent_id == self.membership.agent_id {
+            return Ok(0);
+        }
+        Ok(shared
+            .markets
+            .iter()
+            .map(|(service_type, batch)| self.merger.merge(predictors.entry(service_type.clone()).or_insert_with(MarketPredictor::new), batch))
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(owner: &KeyPair) -> KnowledgeMember {
+        let keypair = KeyPair::generate().unwrap();
+        let agent_id = AgentId::from_key_bytes(keypair.verifying_key().as_bytes());
+        let membership = DomainMembership::issue(owner, agent_id, keypair.verifying_key()).unwrap();
+        KnowledgeMember::new(TrustDomain::new(*owner.verifying_key()), keypair, membership).unwrap()
+    }
+
+    #[test]
+    fn test_members_share_and_outsiders_are_refused() {
+        let owner = KeyPair::generate().unwrap();
+        let mut alice = member(&owner);
+        let mut bob = member(&owner);
+        assert_eq!(alice.domain.topic(), bob.domain.topic());
+
+        let mut alice_markets = HashMap::from([(ServiceType::DataAnalysis, MarketPredictor::new())]);
+        for price in [10.0, 11.0, 12.0] {
+            alice_markets.get_mut(&ServiceType::DataAnalysis).unwrap().add_data_point(price, 0.5);
+        }
+        let shared = alice.share(&alice_markets).unwrap().unwrap();
+        assert!(alice.share(&alice_markets).unwrap().is_none());
+
+        let mut bob_markets = HashMap::new();
+        assert_eq!(bob.receive(&shared, &mut bob_markets).unwrap(), 3);
+        assert!(bob_markets.contains_key(&ServiceType::DataAnalysis));
+
+        // Another owner's agent cannot publish into the domain
+        let mut mallory = member(&KeyPair::generate().unwrap());
+        let forged = mallory.share(&alice_markets).unwrap().unwrap();
+        assert!(bob.receive(&forged, &mut bob_markets).is_err());
+
+        // Nor can a member's observations be altered in flight
+        let mut tampered = shared.clone();
+        tampered.markets[0].1.observations[0].price = 1_000.0;
+        assert!(alice.domain.verify(&tampered).is_err());
+
+        // Nor can an owner certify a key under an ID not derived from it
+        let mut impostor = shared.clone();
+        impostor.membership = DomainMembership::issue(&owner, AgentId::new(), &alice.membership.member_key().unwrap()).unwrap();
+        assert!(alice.domain.verify(&impostor).is_err());
+    }
+}
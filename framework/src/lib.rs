@@ -3,28 +3,63 @@
 //! A decentralized autonomous agent commerce framework built on Solana.
 //! This library provides the core functionality for creating, managing, and
 //! coordinating autonomous agents that can engage in commercial transactions.
+//!
+//! Most agents only need `use solace_protocol::prelude::*;`. Configurations
+//! are put together with `AgentConfig::builder` and
+//! `TransactionRequest::builder`, which validate when built.
 
 pub mod agent;
 pub mod acp;
+pub mod analytics;
+pub mod archive;
+pub mod billing;
+pub mod capability;
+pub mod capacity;
+pub mod compliance;
+pub mod consensus;
+pub mod cost;
 pub mod crypto;
 pub mod error;
+pub mod escrow;
+pub mod explorer;
+pub(crate) mod failure;
+pub mod fast_path;
+pub mod governance;
+pub mod knowledge;
+pub mod marketplace;
+pub mod matching;
+pub mod negotiation;
 pub mod network;
+pub mod observer;
+pub mod offer_book;
+pub(crate) mod preflight;
+pub mod prelude;
+pub mod privacy;
+pub mod program;
 pub mod reputation;
+pub mod rfq;
+pub mod search;
+pub mod signing;
+pub mod sponsorship;
+pub mod standby;
+pub mod storage;
 pub mod transaction;
+pub mod transaction_manager;
 pub mod types;
 pub mod utils;
 
-// Re-export core types and functions
-pub use agent::{Agent, AgentConfig, AgentCapability, AgentPreferences};
+// Re-export core types and functions; everything else is reached through
+// its module, or `prelude`
+pub use agent::{Agent, AgentConfig, AgentConfigBuilder, AgentCapability, AgentPreferences};
 pub use acp::{ACPMessage, MessageType, NegotiationStrategy, ProtocolVersion};
 pub use crypto::{KeyPair, Signature, SignatureError};
-pub use error::{SolaceError, Result};
+pub use error::{ChainError, SolaceError, Result};
 pub use network::{NetworkConfig, P2PNetwork, PeerManager};
 pub use reputation::{ReputationScore, ReputationSystem, ReputationWeight};
 pub use transaction::{
-    Transaction, TransactionPhase, TransactionRequest, TransactionResult, TransactionStatus,
+    Transaction, TransactionPhase, TransactionRequest, TransactionRequestBuilder, TransactionResult, TransactionStatus,
 };
-pub use types::{AgentId, Balance, Timestamp, TransactionId};
+pub use types::{AgentId, Balance, Payment, PaymentAsset, Timestamp, TransactionId};
 
 /// The current version of the Solace Protocol
 pub const PROTOCOL_VERSION: &str = "1.0.0";
@@ -36,6 +71,9 @@ pub mod constants {
     /// Maximum number of negotiation rounds
     pub const MAX_NEGOTIATION_ROUNDS: u32 = 10;
 
+    /// Default time a counterparty has to answer each negotiation round
+    pub const NEGOTIATION_RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
     /// Default transaction timeout
     pub const DEFAULT_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(300); // 5 minutes
 
@@ -0,0 +1,331 @@
+//! Service Marketplace
+//!
+//! Providers publish `ServiceOffer`s: a service they perform, the price range
+//! they charge for it, and the service level they commit to. Offers are
+//! gossiped on the service's marketplace topic with the `ServiceOffer`
+//! message type, and every node following the topic keeps them in its
+//! `Marketplace`, so the registry lives wherever the gossip reaches rather
+//! than on any one server. Requesters ask it for `find_providers`: the
+//! current offers for a service that pass their filters, ranked by price and
+//! by the reputation the requester holds for each provider.
+//!
+//! Like capacity ads, offers are ordered by publication time and then
+//! sequence number, so a late copy of an older offer never overwrites a
+//! newer one. Offers expire unless republished, and a provider withdraws one
+//! by publishing it again with `withdrawn` set.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    acp::MessageType,
+    error::{Result, TransactionError},
+    reputation::ReputationSystem,
+    rfq::SelectionWeights,
+    types::{AgentId, Balance, ServiceType, Timestamp},
+};
+
+/// Prefix of the per-service marketplace topics
+pub const MARKETPLACE_TOPIC_PREFIX: &str = "market";
+
+/// How long an offer stays listed unless renewed, in seconds
+pub const OFFER_TTL_SECS: i64 = 30 * 60;
+
+/// Reputation assumed for providers the requester has no score for
+pub const UNRATED_REPUTATION: f64 = 0.5;
+
+/// Gossip topic carrying offers for a service type, e.g. `market.data_analysis`
+pub fn marketplace_topic(service_type: &ServiceType) -> String {
+    format!("{}.{}", MARKETPLACE_TOPIC_PREFIX, service_type.key())
+}
+
+/// Service level a provider commits to in an offer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OfferSla {
+    pub max_completion: std::time::Duration,
+    pub availability: f64,                // Share of jobs delivered within `max_completion`
+    pub dispute_window: std::time::Duration,
+}
+
+impl Default for OfferSla {
+    fn default() -> Self {
+        Self {
+            max_completion: std::time::Duration::from_secs(60 * 60),
+            availability: 0.95,
+            dispute_window: std::time::Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// A provider's standing offer for one service, as published
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceOffer {
+    pub provider: AgentId,
+    pub service_type: ServiceType,
+    pub min_price: Balance,
+    pub max_price: Balance,
+    pub sla: OfferSla,
+    pub description: String,
+    pub sequence: u64,                    // Orders offers published in the same instant
+    pub published_at: Timestamp,
+    pub valid_until: Timestamp,
+    pub withdrawn: bool,
+}
+
+impl ServiceOffer {
+    pub fn new(provider: AgentId, service_type: ServiceType, price_range: (Balance, Balance), sla: OfferSla, now: Timestamp, ttl: Duration) -> Result<Self> {
+        if price_range.0 > price_range.1 {
+            return Err(TransactionError::InvalidAmount { amount: price_range.0 .0 }.into());
+        }
+        Ok(Self {
+            provider,
+            service_type,
+            min_price: price_range.0,
+            max_price: price_range.1,
+            sla,
+            description: String::new(),
+            sequence: 0,
+            published_at: now,
+            valid_until: Timestamp(now.0 + ttl),
+            withdrawn: false,
+        })
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// The same offer republished at `now`, listed for another `ttl`
+    pub fn renewed(&self, now: Timestamp, ttl: Duration) -> Self {
+        Self {
+            sequence: self.sequence + 1,
+            published_at: now,
+            valid_until: Timestamp(now.0 + ttl),
+            ..self.clone()
+        }
+    }
+
+    /// The publication that takes the offer off the market
+    pub fn withdrawal(&self, now: Timestamp) -> Self {
+        Self { withdrawn: true, ..self.renewed(now, Duration::zero()) }
+    }
+
+    /// Topic the offer is published on
+    pub fn topic(&self) -> String {
+        marketplace_topic(&self.service_type)
+    }
+
+    pub fn is_current(&self, now: Timestamp) -> bool {
+        !self.withdrawn && now.0 <= self.valid_until.0
+    }
+
+    /// Gossip message type carrying offers
+    pub fn message_type(&self) -> MessageType {
+        MessageType::ServiceOffer
+    }
+
+    /// Gossip payload
+    pub fn to_payload(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    pub fn from_payload(payload: serde_json::Value) -> Result<Self> {
+        Ok(serde_json::from_value(payload)?)
+    }
+}
+
+/// What a requester needs from a provider; unset fields admit anyone
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderFilters {
+    pub max_price: Option<Balance>,       // Compared with the offer's minimum price
+    pub min_reputation: Option<f64>,
+    pub max_completion: Option<std::time::Duration>,
+    pub min_availability: Option<f64>,
+    pub limit: Option<usize>,             // Most candidates returned
+}
+
+impl ProviderFilters {
+    fn admits(&self, offer: &ServiceOffer, reputation: f64) -> bool {
+        self.max_price.is_none_or(|max| offer.min_price <= max)
+            && self.min_reputation.is_none_or(|min| reputation >= min)
+            && self.max_completion.is_none_or(|max| offer.sla.max_completion <= max)
+            && self.min_availability.is_none_or(|min| offer.sla.availability >= min)
+    }
+}
+
+/// A provider worth asking, with the score it was ranked by
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderCandidate {
+    pub offer: ServiceOffer,
+    pub reputation: f64,
+    pub score: f64,                       // Between 0.0 and 1.0, higher is better
+}
+
+/// Registry of the offers this node has seen, and how to rank them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Marketplace {
+    offers: HashMap<(AgentId, String), ServiceOffer>,  // By provider and service key
+    reputations: HashMap<AgentId, f64>,
+    weights: SelectionWeights,            // `speed` rewards shorter committed completion
+}
+
+impl Default for Marketplace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Marketplace {
+    /// Rank by price and reputation only
+    pub fn new() -> Self {
+        Self::with_weights(SelectionWeights { price: 0.6, reputation: 0.4, speed: 0.0 })
+    }
+
+    pub fn with_weights(weights: SelectionWeights) -> Self {
+        Self { offers: HashMap::new(), reputations: HashMap::new(), weights }
+    }
+
+    /// Record an offer, ours or gossiped, unless a newer one from the
+    /// provider for the service is already held. Returns whether it was taken.
+    pub fn observe(&mut self, offer: ServiceOffer) -> bool {
+        let key = (offer.provider, offer.service_type.key());
+        let newer = |held: &ServiceOffer| (held.published_at, held.sequence) >= (offer.published_at, offer.sequence);
+        if self.offers.get(&key).is_some_and(newer) {
+            return false;
+        }
+        self.offers.insert(key, offer);
+        true
+    }
+
+    /// Latest offer held from `provider` for a service, withdrawn or not
+    pub fn offer(&self, provider: &AgentId, service_type: &ServiceType) -> Option<&ServiceOffer> {
+        self.offers.get(&(*provider, service_type.key()))
+    }
+
+    /// Set the reputation a provider is ranked with
+    pub fn record_reputation(&mut self, provider: AgentId, reputation: f64) {
+        self.reputations.insert(provider, reputation.clamp(0.0, 1.0));
+    }
+
+    /// Take the scores of every listed provider from `system`
+    pub fn sync_reputations(&mut self, system: &ReputationSystem) {
+        let providers: Vec<AgentId> = self.offers.keys().map(|(provider, _)| *provider).collect();
+        for provider in providers {
+            if let Some(score) = system.get_score(&provider) {
+                self.record_reputation(provider, score);
+            }
+        }
+    }
+
+    pub fn reputation(&self, provider: &AgentId) -> f64 {
+        self.reputations.get(provider).copied().unwrap_or(UNRATED_REPUTATION)
+    }
+
+    /// Current offers for `service_type` that pass `filters`, best first
+    pub fn find_providers(&self, service_type: &ServiceType, filters: &ProviderFilters) -> Vec<ProviderCandidate> {
+        self.find_providers_at(service_type, filters, Timestamp::now())
+    }
+
+    pub fn find_providers_at(&self, service_type: &ServiceType, filters: &ProviderFilters, now: Timestamp) -> Vec<ProviderCandidate> {
+        let key = service_type.key();
+        let matching: Vec<(&ServiceOffer, f64)> = self
+            .offers
+            .iter()
+            .filter(|((_, service), offer)| *service == key && offer.is_current(now))
+            .map(|(_, offer)| (offer, self.reputation(&offer.provider)))
+            .filter(|(offer, reputation)| filters.admits(offer, *reputation))
+            .collect();
+
+        // Prices and completion times score as a fraction of the best candidate's
+        let relative = |values: Vec<f64>| {
+            let best = values.into_iter().fold(f64::INFINITY, f64::min);
+            move |value: f64| if value > 0.0 { best / value } else { 1.0 }
+        };
+        let price = relative(matching.iter().map(|(offer, _)| offer.min_price.0 as f64).collect());
+        let speed = relative(matching.iter().map(|(offer, _)| offer.sla.max_completion.as_secs_f64()).collect());
+
+        let mut candidates: Vec<ProviderCandidate> = matching
+            .into_iter()
+            .map(|(offer, reputation)| ProviderCandidate {
+                score: self.weights.combine(price(offer.min_price.0 as f64), reputation, speed(offer.sla.max_completion.as_secs_f64())),
+                offer: offer.clone(),
+                reputation,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.offer.min_price.cmp(&b.offer.min_price)));
+        if let Some(limit) = filters.limit {
+            candidates.truncate(limit);
+        }
+        candidates
+    }
+
+    /// Drop offers that expired or were withdrawn
+    pub fn prune(&mut self, now: Timestamp) {
+        self.offers.retain(|_, offer| offer.is_current(now));
+    }
+
+    /// Offers held, withdrawn ones included until pruned
+    pub fn len(&self) -> usize {
+        self.offers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer(provider: AgentId, price: f64, completion_mins: u64, now: Timestamp) -> ServiceOffer {
+        let sla = OfferSla { max_completion: std::time::Duration::from_secs(completion_mins * 60), ..OfferSla::default() };
+        let range = (Balance::from_sol(price), Balance::from_sol(price * 2.0));
+        ServiceOffer::new(provider, ServiceType::DataAnalysis, range, sla, now, Duration::minutes(30)).unwrap()
+    }
+
+    #[test]
+    fn test_providers_are_filtered_and_ranked_by_price_and_reputation() {
+        let now = Timestamp::now();
+        let (cheap, trusted, pricey, gone) = (AgentId::new(), AgentId::new(), AgentId::new(), AgentId::new());
+        let mut market = Marketplace::new();
+        for listed in [offer(cheap, 1.0, 30, now), offer(trusted, 2.0, 30, now), offer(pricey, 9.0, 10, now), offer(gone, 0.5, 30, now)] {
+            assert!(market.observe(listed));
+        }
+        let withdrawn = market.offer(&gone, &ServiceType::DataAnalysis).unwrap().withdrawal(now);
+        assert!(market.observe(withdrawn));
+        market.record_reputation(cheap, 0.4);
+        market.record_reputation(trusted, 0.95);
+        assert_eq!(market.reputation(&pricey), UNRATED_REPUTATION);
+
+        let filters = ProviderFilters { max_price: Some(Balance::from_sol(5.0)), ..Default::default() };
+        let found = market.find_providers_at(&ServiceType::DataAnalysis, &filters, now);
+        let providers: Vec<AgentId> = found.iter().map(|candidate| candidate.offer.provider).collect();
+        assert_eq!(providers, vec![cheap, trusted]);
+        assert!(found[0].score > found[1].score);
+
+        // Weighting reputation over price lets the trusted provider outrank the cheap one
+        let mut cautious = market.clone();
+        cautious.weights = SelectionWeights { price: 0.2, reputation: 0.8, speed: 0.0 };
+        let found = cautious.find_providers_at(&ServiceType::DataAnalysis, &filters, now);
+        assert_eq!(found.iter().map(|candidate| candidate.offer.provider).collect::<Vec<_>>(), vec![trusted, cheap]);
+        assert!(market.find_providers_at(&ServiceType::TradingService, &ProviderFilters::default(), now).is_empty());
+
+        // Reputation floors and SLA requirements narrow the field
+        let strict = ProviderFilters { min_reputation: Some(0.5), max_completion: Some(std::time::Duration::from_secs(20 * 60)), ..Default::default() };
+        let found = market.find_providers_at(&ServiceType::DataAnalysis, &strict, now);
+        assert_eq!(found.iter().map(|candidate| candidate.offer.provider).collect::<Vec<_>>(), vec![pricey]);
+
+        // A stale copy does not bring back a withdrawn offer, and expiry delists
+        assert!(!market.observe(offer(gone, 0.5, 30, now)));
+        let later = Timestamp(now.0 + Duration::hours(1));
+        assert!(market.find_providers_at(&ServiceType::DataAnalysis, &ProviderFilters::default(), later).is_empty());
+        market.prune(later);
+        assert!(market.is_empty());
+
+        let decoded = ServiceOffer::from_payload(offer(cheap, 1.0, 30, now).to_payload().unwrap()).unwrap();
+        assert_eq!(decoded.topic(), "market.data_analysis");
+    }
+}
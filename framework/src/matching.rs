@@ -0,0 +1,100 @@
+//! Service Matching
+//!
+//! Turns a need into a deal without the caller picking providers by hand.
+//! `Agent::request_service` looks the service up on the marketplace, sends
+//! each matching provider a `QuoteRequest` carrying the transaction request
+//! over the agent's `QuoteChannel`, and collects the `TransactionProposal`s
+//! that come back in the request's offer book. When the collection window
+//! closes, proposals are ranked by the request's selection weights and the
+//! agent's negotiation strategy picks the winner among those within budget.
+//!
+//! The framework does not own a network connection, so delivering quote
+//! requests is left to the channel, and proposals arriving from the network
+//! are handed to `Agent::collect_proposal` as for any offer book.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    acp::{ACPMessage, MessageType, ProtocolVersion},
+    marketplace::ProviderFilters,
+    rfq::SelectionWeights,
+    transaction::{Transaction, TransactionRequest},
+    types::{AgentId, Payment, Timestamp},
+    Result,
+};
+
+/// How long proposals are collected by default, in seconds
+pub const DEFAULT_COLLECTION_SECS: i64 = 30;
+
+/// Delivers quote requests to providers
+#[async_trait::async_trait]
+pub trait QuoteChannel: Send + Sync + std::fmt::Debug {
+    async fn send(&self, provider: &AgentId, message: ACPMessage) -> Result<()>;
+}
+
+/// A service to buy, and how to find and choose its provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceRequest {
+    pub request: TransactionRequest,
+    pub filters: ProviderFilters,         // Narrow the marketplace's providers
+    pub window: Duration,                 // Proposals are collected this long, or until the deadline
+    pub weights: SelectionWeights,        // Rank proposals before the strategy chooses
+    #[serde(default)]
+    pub max_payment: Option<Payment>,     // Replaces the request's budget and asset
+}
+
+impl ServiceRequest {
+    pub fn new(request: TransactionRequest) -> Self {
+        Self {
+            request,
+            filters: ProviderFilters::default(),
+            window: Duration::seconds(DEFAULT_COLLECTION_SECS),
+            weights: SelectionWeights::default(),
+            max_payment: None,
+        }
+    }
+
+    pub fn with_filters(mut self, filters: ProviderFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_weights(mut self, weights: SelectionWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Pay at most `payment`, in its asset
+    pub fn with_max_payment(mut self, payment: Payment) -> Self {
+        self.max_payment = Some(payment);
+        self
+    }
+
+    /// Collection window starting at `now`, cut short by the deadline
+    pub fn collection_window(&self, now: Timestamp) -> Duration {
+        self.window.min(self.request.deadline.0 - now.0).max(Duration::zero())
+    }
+
+    /// The quote request sent to each provider
+    pub fn quote_request(&self) -> Result<ACPMessage> {
+        Ok(ACPMessage {
+            message_type: MessageType::QuoteRequest,
+            version: ProtocolVersion(crate::PROTOCOL_VERSION.to_string()),
+            payload: serde_json::to_vec(&self.request)?,
+        })
+    }
+}
+
+/// What came of a service request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceMatch {
+    pub solicited: Vec<AgentId>,          // Providers the quote request reached
+    pub proposals: usize,                 // Proposals collected
+    pub transaction: Option<Transaction>, // None if no proposal was taken
+}
@@ -0,0 +1,320 @@
+//! Negotiation Sessions
+//!
+//! A session tracks one negotiation with one counterparty: the strategy's
+//! view of the haggling so far, whose turn it is, and when the counterparty's
+//! answer is due. Each ask starts a response deadline (by default the
+//! governance `negotiation_response_timeout_secs`); a counterparty that lets
+//! it pass is withdrawn from, and every answer or timeout is recorded in the
+//! counterparty's profile for matchmaking. Finished sessions record whether
+//! they closed and how many rounds they took, which feeds the counterparty's
+//! pricing adjustment in later negotiations.
+//!
+//! A session may also know the requester's deadline for the work itself.
+//! Each offer then refreshes the time pressure in the decision context, so
+//! the strategy's acceptance threshold eases as that deadline approaches.
+//!
+//! An agreement whose payment fails the pre-flight funding check is not
+//! lost: the session reopens with prices capped at what the payer can cover,
+//! so the parties can settle on a smaller deal.
+//!
+//! A session opened with a cancellation token is abandoned once the token
+//! fires while it is still open. Abandoning records nothing against the
+//! counterparty, since the negotiation ended on our side.
+//!
+//! Prices in a session are whole units of the deal's payment asset.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use solace_ai::profile::CounterpartyProfiles;
+use solace_ai::strategy::NegotiationState;
+use solace_ai::PriceBounds;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    governance::ProtocolParams,
+    types::{AgentId, Balance, Payment, PaymentAsset, Timestamp, TransactionId},
+};
+
+/// Lifecycle of a negotiation session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionStatus {
+    Open,
+    Agreed,
+    Rejected,
+    Withdrawn,
+}
+
+/// One negotiation with one counterparty
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegotiationSession {
+    pub transaction_id: TransactionId,
+    pub counterparty: AgentId,
+    pub state: NegotiationState,
+    pub response_timeout_secs: u64,
+    pub status: SessionStatus,
+    #[serde(default)]
+    pub asset: PaymentAsset,              // What prices are counted in
+    awaiting_since: Option<Timestamp>,    // Set while the counterparty owes an answer
+    #[serde(default)]
+    request_window: Option<(Timestamp, Timestamp)>,  // Opened at, requester's deadline
+    #[serde(skip)]
+    cancel: Option<CancellationToken>,    // Fires to abandon the negotiation
+}
+
+impl NegotiationSession {
+    pub fn new(transaction_id: TransactionId, counterparty: AgentId, state: NegotiationState, response_timeout_secs: u64) -> Self {
+        Self {
+            transaction_id,
+            counterparty,
+            state,
+            response_timeout_secs,
+            status: SessionStatus::Open,
+            asset: PaymentAsset::Sol,
+            awaiting_since: None,
+            request_window: None,
+            cancel: None,
+        }
+    }
+
+    /// Abandon the session when `cancel` fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Whether the session was abandoned while still open
+    pub fn is_cancelled(&self) -> bool {
+        self.is_open() && self.cancel.as_ref().is_some_and(|cancel| cancel.is_cancelled())
+    }
+
+    /// Session for a deal paid in `asset`
+    pub fn with_asset(mut self, asset: PaymentAsset) -> Self {
+        self.asset = asset;
+        self
+    }
+
+    /// Track time pressure from the requester's deadline, counted from `opened_at`
+    pub fn set_request_deadline(&mut self, opened_at: Timestamp, deadline: Timestamp) {
+        self.request_window = Some((opened_at, deadline));
+    }
+
+    /// Requester's deadline for the work, if known
+    pub fn request_deadline(&self) -> Option<Timestamp> {
+        self.request_window.map(|(_, deadline)| deadline)
+    }
+
+    /// Session using the governance response timeout
+    pub fn with_params(transaction_id: TransactionId, counterparty: AgentId, state: NegotiationState, params: &ProtocolParams) -> Self {
+        Self::new(transaction_id, counterparty, state, params.negotiation_response_timeout_secs)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.status == SessionStatus::Open
+    }
+
+    /// When the counterparty's answer is due, if one is owed
+    pub fn deadline(&self) -> Option<Timestamp> {
+        self.awaiting_since
+            .map(|since| Timestamp(since.0 + Duration::seconds(self.response_timeout_secs as i64)))
+    }
+
+    /// Whether the counterparty missed its deadline
+    pub fn is_overdue(&self, now: Timestamp) -> bool {
+        self.is_open() && self.deadline().is_some_and(|deadline| now > deadline)
+    }
+
+    /// Record our ask and start the counterparty's response deadline
+    pub fn send_ask(&mut self, price: f64, now: Timestamp) {
+        self.state.our_asks.push(price);
+        self.state.round += 1;
+        self.awaiting_since = Some(now);
+    }
+
+    /// Record the counterparty's offer, returning its response time in seconds
+    pub fn receive_offer(&mut self, offer: f64, now: Timestamp, profiles: &mut CounterpartyProfiles) -> Option<f64> {
+        self.state.their_offers.push(offer);
+        if let Some((opened_at, deadline)) = self.request_window {
+            let elapsed = (now.0 - opened_at.0).num_milliseconds() as f64 / 1000.0;
+            let total = (deadline.0 - opened_at.0).num_milliseconds() as f64 / 1000.0;
+            let pressure = self.state.context.time_pressure.unwrap_or_default();
+            self.state.context.time_pressure = Some(pressure.with_deadline(elapsed, total));
+        }
+        let since = self.awaiting_since.take()?;
+        let secs = (now.0 - since.0).num_milliseconds() as f64 / 1000.0;
+        profiles.record_response(&self.counterparty.to_string(), secs);
+        Some(secs)
+    }
+
+    /// Withdraw if the counterparty missed its deadline, returning whether it did
+    pub fn enforce_deadline(&mut self, now: Timestamp, profiles: &mut CounterpartyProfiles) -> bool {
+        if !self.is_overdue(now) {
+            return false;
+        }
+        tracing::info!(
+            transaction_id = %self.transaction_id,
+            counterparty = %self.counterparty,
+            "Withdrawing from negotiation: no response within {}s", self.response_timeout_secs
+        );
+        profiles.record_timeout(&self.counterparty.to_string());
+        self.status = SessionStatus::Withdrawn;
+        self.awaiting_since = None;
+        true
+    }
+
+    /// Reopen an agreement whose payment is short by `shortfall`, capping
+    /// further prices at what the payer can cover. Returns the cap, or
+    /// withdraws and returns `None` if no price within bounds is affordable.
+    pub fn reopen_underfunded(&mut self, agreed_price: f64, shortfall: Balance) -> Option<f64> {
+        let shortfall = Payment::new(self.asset, shortfall.0);
+        let affordable = agreed_price - shortfall.to_units();
+        let floor = self.state.price_bounds.map(|bounds| bounds.floor).unwrap_or(0.0);
+        self.awaiting_since = None;
+        if affordable <= 0.0 || affordable < floor {
+            self.status = SessionStatus::Withdrawn;
+            return None;
+        }
+        tracing::info!(
+            transaction_id = %self.transaction_id,
+            counterparty = %self.counterparty,
+            "Renegotiating: payment short by {}, capping price at {}", shortfall, affordable
+        );
+        self.state.price_bounds = PriceBounds::new(floor, affordable);
+        self.state.max_rounds = self.state.max_rounds.max(self.state.round + 1);
+        self.status = SessionStatus::Open;
+        Some(affordable)
+    }
+
+    /// Close the session with an agreement or a rejection, recording the outcome
+    pub fn finish(&mut self, agreed: bool, profiles: &mut CounterpartyProfiles) {
+        self.status = if agreed { SessionStatus::Agreed } else { SessionStatus::Rejected };
+        self.awaiting_since = None;
+        profiles.record_negotiation(&self.counterparty.to_string(), agreed, self.state.round);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solace_ai::{DecisionContext, MarketConditions};
+
+    fn session(timeout_secs: u64) -> NegotiationSession {
+        let state = NegotiationState::new(
+            DecisionContext {
+                agent_reputation: 0.5,
+                counterparty_reputation: 0.5,
+                transaction_value: 10.0,
+                market_conditions: MarketConditions {
+                    demand_level: 0.5,
+                    competition_level: 0.5,
+                    average_pricing: 10.0,
+                    risk_indicators: vec![],
+                },
+                historical_performance: vec![],
+                counterparty_profile: None,
+                time_pressure: None,
+            },
+            10.0,
+            5,
+        );
+        NegotiationSession::new(TransactionId::new(), AgentId::new(), state, timeout_secs)
+    }
+
+    fn at(secs: i64) -> Timestamp {
+        Timestamp(chrono::DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap())
+    }
+
+    #[test]
+    fn test_responses_are_timed() {
+        let mut profiles = CounterpartyProfiles::new();
+        let mut session = session(60);
+
+        session.send_ask(12.0, at(0));
+        assert_eq!(session.deadline(), Some(at(60)));
+        assert_eq!(session.receive_offer(9.0, at(15), &mut profiles), Some(15.0));
+        assert!(session.deadline().is_none());
+        assert!(!session.enforce_deadline(at(1_000), &mut profiles));
+
+        let counterparty = session.counterparty.to_string();
+        assert_eq!(profiles.average_response_secs(&counterparty), Some(15.0));
+    }
+
+    #[test]
+    fn test_silent_counterparty_is_withdrawn_from() {
+        let mut profiles = CounterpartyProfiles::new();
+        let mut session = session(30);
+
+        session.send_ask(12.0, at(0));
+        assert!(!session.enforce_deadline(at(30), &mut profiles));
+        assert!(session.enforce_deadline(at(31), &mut profiles));
+        assert_eq!(session.status, SessionStatus::Withdrawn);
+        assert!(!session.is_overdue(at(100)));
+
+        let profile = profiles.get(&session.counterparty.to_string()).unwrap();
+        assert_eq!(profile.timeouts, 1);
+        assert_eq!(profile.responsiveness(), 0.0);
+    }
+
+    #[test]
+    fn test_request_deadline_builds_time_pressure() {
+        let mut profiles = CounterpartyProfiles::new();
+        let mut session = session(60);
+        session.set_request_deadline(at(0), at(100));
+        assert_eq!(session.request_deadline(), Some(at(100)));
+
+        session.send_ask(12.0, at(0));
+        session.receive_offer(9.0, at(25), &mut profiles);
+        assert_eq!(session.state.context.time_pressure.unwrap().time_elapsed, Some(0.25));
+
+        session.send_ask(11.0, at(30));
+        session.receive_offer(9.5, at(150), &mut profiles);
+        assert_eq!(session.state.context.time_pressure.unwrap().level(), 1.0);
+    }
+
+    #[test]
+    fn test_finished_sessions_feed_the_profile() {
+        let mut profiles = CounterpartyProfiles::new();
+        let mut session = session(60);
+
+        session.send_ask(12.0, at(0));
+        session.receive_offer(9.0, at(5), &mut profiles);
+        session.send_ask(11.0, at(10));
+        session.receive_offer(10.5, at(12), &mut profiles);
+        session.finish(true, &mut profiles);
+
+        let profile = profiles.get(&session.counterparty.to_string()).unwrap();
+        assert_eq!(profile.acceptance_rate(), Some(1.0));
+        assert_eq!(profile.average_haggle_depth(), Some(2.0));
+    }
+
+    #[test]
+    fn test_underfunded_agreement_reopens_with_a_cap() {
+        let mut profiles = CounterpartyProfiles::new();
+        let mut session = session(60);
+        session.send_ask(12.0, at(0));
+        session.receive_offer(10.0, at(5), &mut profiles);
+        session.finish(true, &mut profiles);
+
+        assert_eq!(session.reopen_underfunded(10.0, Balance::from_sol(1.5)), Some(8.5));
+        assert!(session.is_open());
+        assert_eq!(session.state.price_bounds, PriceBounds::new(0.0, 8.5));
+
+        // A shortfall that eats the whole price ends the negotiation
+        assert_eq!(session.reopen_underfunded(8.5, Balance::from_sol(9.0)), None);
+        assert_eq!(session.status, SessionStatus::Withdrawn);
+    }
+
+    #[test]
+    fn test_only_open_sessions_are_cancelled() {
+        let mut profiles = CounterpartyProfiles::new();
+        let cancel = CancellationToken::new();
+        let mut agreed = session(60).with_cancellation(cancel.clone());
+        let open = session(60).with_cancellation(cancel.clone());
+        agreed.finish(true, &mut profiles);
+        assert!(!open.is_cancelled());
+
+        cancel.cancel();
+        assert!(open.is_cancelled());
+        assert!(!agreed.is_cancelled());
+        assert!(!session(60).is_cancelled());
+    }
+}
@@ -0,0 +1,299 @@
+//! Observer Nodes
+//!
+//! A read-only participant for explorers and compliance monitors. An
+//! observer listens to gossip, indexes every transaction and reputation
+//! update it sees into storage, follows block headers and validator sets,
+//! and answers search, market analytics, and reputation queries over what
+//! it has indexed. An observer co-located with a consensus engine follows
+//! its `ConsensusEvent`s instead of waiting for the same blocks and
+//! validator sets to arrive over gossip. It holds no signing keys
+//! and runs as `NodeRole::Observer`, so it can never sign, propose, or
+//! transact; agents started with `Agent::observer` are refused the same
+//! operations.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::broadcast;
+
+use crate::{
+    acp::{ACPMessage, MessageType},
+    analytics::{MarketAnalytics, ServiceMarketStats},
+    consensus::{BlockHeader, ConsensusEvent, Validator},
+    crypto::NodeRole,
+    reputation::{ReputationEvent, ReputationEventType, ReputationSystem},
+    search::{SearchQuery, SearchResults, TransactionSearchIndex},
+    storage::StorageManager,
+    transaction::Transaction,
+    types::{AgentId, ServiceType, Timestamp},
+    Result,
+};
+
+/// Reputation change announced on the network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReputationUpdate {
+    pub agent_id: AgentId,
+    pub event: ReputationEvent,
+}
+
+/// Validators elected for an epoch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorSet {
+    pub epoch: u32,
+    pub validators: Vec<Validator>,
+}
+
+/// An agent's reputation right after one update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReputationPoint {
+    pub timestamp: Timestamp,
+    pub event_type: ReputationEventType,
+    pub delta: f64,
+    pub score: f64,
+}
+
+/// Most block headers an observer keeps
+pub const MAX_RECENT_BLOCKS: usize = 10_000;
+
+/// What an observer has indexed so far
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObserverStats {
+    pub transactions: u64,                // Transaction updates indexed
+    pub reputation_updates: u64,
+    pub blocks: u64,
+    pub ignored: u64,                     // Messages with nothing to index
+}
+
+/// Read-only node indexing network activity
+pub struct ObserverNode {
+    storage: StorageManager,
+    search: TransactionSearchIndex,
+    analytics: MarketAnalytics,
+    reputation: ReputationSystem,
+    reputation_history: HashMap<AgentId, Vec<ReputationPoint>>,
+    blocks: VecDeque<BlockHeader>,        // Newest first
+    validators: Option<ValidatorSet>,
+    stats: ObserverStats,
+}
+
+impl ObserverNode {
+    /// Start an observer over `storage`, resuming from what it already holds
+    pub async fn new(storage: StorageManager) -> Result<Self> {
+        let search = TransactionSearchIndex::from_storage(&storage).await?;
+        let analytics = MarketAnalytics::from_storage(&storage).await?;
+        Ok(Self {
+            storage,
+            search,
+            analytics,
+            reputation: ReputationSystem::new(),
+            reputation_history: HashMap::new(),
+            blocks: VecDeque::new(),
+            validators: None,
+            stats: ObserverStats::default(),
+        })
+    }
+
+    /// Always `NodeRole::Observer`
+    pub fn role(&self) -> NodeRole {
+        NodeRole::Observer
+    }
+
+    /// Index a gossiped message. Returns false if it carried nothing to index.
+    pub async fn ingest(&mut self, message: &ACPMessage) -> Result<bool> {
+        match message.message_type {
+            MessageType::TransactionRequest
+            | MessageType::TransactionProposal
+            | MessageType::TransactionAcceptance
+            | MessageType::TransactionCompletion => {
+                let transaction: Transaction = serde_json::from_slice(&message.payload)?;
+                self.observe_transaction(transaction).await?;
+                Ok(true)
+            }
+            MessageType::ReputationUpdate => {
+                let update: ReputationUpdate = serde_json::from_slice(&message.payload)?;
+                self.observe_reputation(update).await?;
+                Ok(true)
+            }
+            MessageType::BlockHeader => {
+                self.observe_block(serde_json::from_slice(&message.payload)?);
+                Ok(true)
+            }
+            MessageType::ValidatorSet => {
+                self.observe_validators(serde_json::from_slice(&message.payload)?);
+                Ok(true)
+            }
+            _ => {
+                self.stats.ignored += 1;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Store and index a transaction, replacing any earlier version
+    pub async fn observe_transaction(&mut self, transaction: Transaction) -> Result<()> {
+        self.storage.store_transaction(&transaction.id, &transaction).await?;
+        self.analytics.observe(&transaction);
+        self.search.insert(transaction);
+        self.stats.transactions += 1;
+        Ok(())
+    }
+
+    /// Apply a reputation update and store the resulting score
+    pub async fn observe_reputation(&mut self, update: ReputationUpdate) -> Result<f64> {
+        let point = ReputationPoint {
+            timestamp: update.event.timestamp,
+            event_type: update.event.event_type.clone(),
+            delta: update.event.delta,
+            score: 0.0,
+        };
+        let score = self.reputation.update_reputation(update.agent_id, update.event)?;
+        self.storage.store_reputation(&update.agent_id, score).await?;
+        self.reputation_history.entry(update.agent_id).or_default().push(ReputationPoint { score, ..point });
+        self.stats.reputation_updates += 1;
+        Ok(score)
+    }
+
+    /// Record a block header, keeping the most recent
+    pub fn observe_block(&mut self, header: BlockHeader) {
+        self.blocks.push_front(header);
+        self.blocks.truncate(MAX_RECENT_BLOCKS);
+        self.stats.blocks += 1;
+    }
+
+    /// Replace the current validator set with a newer epoch's
+    pub fn observe_validators(&mut self, set: ValidatorSet) {
+        if self.validators.as_ref().is_none_or(|current| set.epoch >= current.epoch) {
+            self.validators = Some(set);
+        }
+    }
+
+    /// Index what a consensus engine decided. Returns false for events with
+    /// nothing to index.
+    pub fn observe_consensus(&mut self, event: ConsensusEvent) -> bool {
+        match event {
+            ConsensusEvent::BlockFinalized { header, .. } => self.observe_block(header),
+            ConsensusEvent::EpochStarted { epoch, validators } => {
+                self.observe_validators(ValidatorSet { epoch: epoch.number, validators })
+            }
+            ConsensusEvent::ValidatorSlashed { .. } | ConsensusEvent::VoteQuorumReached { .. } => return false,
+        }
+        true
+    }
+
+    /// Index a consensus engine's events until it is dropped
+    pub async fn follow_consensus(&mut self, mut events: broadcast::Receiver<ConsensusEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    self.observe_consensus(event);
+                }
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!("Observer fell behind consensus and missed {} events", missed);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    }
+
+    /// Search indexed transactions
+    pub fn search(&self, query: &SearchQuery) -> SearchResults {
+        self.search.search(query)
+    }
+
+    pub fn market_stats(&self, service_type: &ServiceType, now: Timestamp) -> ServiceMarketStats {
+        self.analytics.stats(service_type, now)
+    }
+
+    /// Statistics for every service type seen
+    pub fn all_market_stats(&self, now: Timestamp) -> Vec<ServiceMarketStats> {
+        self.analytics.all_stats(now)
+    }
+
+    /// Latest known reputation score, from this session or storage
+    pub async fn reputation(&self, agent_id: &AgentId) -> Result<Option<f64>> {
+        match self.reputation.get_score(agent_id) {
+            Some(score) => Ok(Some(score)),
+            None => Ok(self.storage.get_reputation(agent_id).await?),
+        }
+    }
+
+    /// Reputation updates seen for an agent this session, oldest first
+    pub fn reputation_history(&self, agent_id: &AgentId) -> &[ReputationPoint] {
+        self.reputation_history.get(agent_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Agents with reputation updates this session
+    pub fn rated_agents(&self) -> usize {
+        self.reputation_history.len()
+    }
+
+    /// Recent block headers, newest first
+    pub fn blocks(&self) -> &VecDeque<BlockHeader> {
+        &self.blocks
+    }
+
+    pub fn validators(&self) -> Option<&ValidatorSet> {
+        self.validators.as_ref()
+    }
+
+    pub fn stats(&self) -> &ObserverStats {
+        &self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::ProtocolVersion;
+    use crate::reputation::{ReputationEventType, ReputationWeight};
+    use crate::transaction::TransactionRequest;
+    use crate::types::Balance;
+
+    fn message<T: Serialize>(message_type: MessageType, payload: &T) -> ACPMessage {
+        ACPMessage {
+            message_type,
+            version: ProtocolVersion(crate::PROTOCOL_VERSION.to_string()),
+            payload: serde_json::to_vec(payload).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observer_indexes_gossip_and_cannot_sign() {
+        let mut observer = ObserverNode::new(StorageManager::memory()).await.unwrap();
+        assert!(observer.role().authorize("sign transactions").is_err());
+
+        let request = TransactionRequest::new(
+            AgentId::new(),
+            ServiceType::DataAnalysis,
+            "Quarterly revenue forecast".to_string(),
+            Balance::from_sol(2.0),
+            Timestamp::now(),
+        );
+        let transaction = Transaction::new(request);
+        assert!(observer.ingest(&message(MessageType::TransactionRequest, &transaction)).await.unwrap());
+
+        let agent_id = AgentId::new();
+        let update = ReputationUpdate {
+            agent_id,
+            event: ReputationEvent {
+                timestamp: Timestamp::now(),
+                event_type: ReputationEventType::QualityBonus,
+                weight: ReputationWeight::High,
+                delta: 1.0,
+                counterparty: None,
+            },
+        };
+        assert!(observer.ingest(&message(MessageType::ReputationUpdate, &update)).await.unwrap());
+        assert!(!observer.ingest(&message(MessageType::QuoteRequest, &())).await.unwrap());
+
+        assert_eq!(observer.search(&SearchQuery::text("revenue forecast")).hits.len(), 1);
+        assert_eq!(observer.market_stats(&ServiceType::DataAnalysis, Timestamp::now()).requests, 1);
+        assert!((observer.reputation(&agent_id).await.unwrap().unwrap() - 0.55).abs() < 1e-9);
+        assert_eq!(observer.reputation_history(&agent_id).len(), 1);
+        assert_eq!(observer.stats(), &ObserverStats { transactions: 1, reputation_updates: 1, blocks: 0, ignored: 1 });
+
+        // A fresh observer over the same storage resumes from what was indexed
+        let resumed = ObserverNode::new(observer.storage).await.unwrap();
+        assert_eq!(resumed.search(&SearchQuery::default()).hits.len(), 1);
+        assert_eq!(resumed.reputation(&agent_id).await.unwrap(), Some(0.55));
+    }
+}
@@ -0,0 +1,218 @@
+//! Offer Book
+//!
+//! A requester that posts a `TransactionRequest` directly, rather than
+//! through an RFQ, used to weigh proposals one at a time and take the first
+//! acceptable one. An `OfferBook` instead collects every proposal for the
+//! request until its proposal window closes, keeping one live proposal per
+//! provider. The book can be persisted between runs, exposes the offers as
+//! price / reputation / ETA points for side-by-side comparison, and once the
+//! window closes the requester selects the best offer by `SelectionWeights`.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, TransactionError},
+    rfq::SelectionWeights,
+    transaction::{Transaction, TransactionProposal, TransactionRequest},
+    types::{AgentId, Payment, Timestamp, TransactionId},
+};
+
+/// Lifecycle of an offer book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OfferBookStatus {
+    Collecting,
+    Selected,
+    Unfilled,
+}
+
+/// A proposal in the book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Offer {
+    pub proposal: TransactionProposal,
+    pub provider_reputation: f64,         // As claimed by the provider
+    pub received_at: Timestamp,
+}
+
+/// One offer as a point for comparison
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OfferPoint {
+    pub proposal_id: TransactionId,
+    pub provider: AgentId,
+    pub price: f64,                       // Whole units of the request's asset
+    pub reputation: f64,                  // The requester's view of the provider
+    pub eta_secs: i64,                    // From the close of the window to completion
+    pub score: f64,
+}
+
+/// Every proposal received for one request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfferBook {
+    pub request: TransactionRequest,
+    pub closes_at: Timestamp,
+    pub offers: Vec<Offer>,
+    pub status: OfferBookStatus,
+}
+
+impl OfferBook {
+    /// Book taking proposals for `window` from now
+    pub fn new(request: TransactionRequest, window: Duration) -> Self {
+        Self {
+            request,
+            closes_at: Timestamp(Timestamp::now().0 + window),
+            offers: Vec::new(),
+            status: OfferBookStatus::Collecting,
+        }
+    }
+
+    /// Whether proposals are still being taken
+    pub fn is_open(&self, now: Timestamp) -> bool {
+        self.status == OfferBookStatus::Collecting && now.0 <= self.closes_at.0
+    }
+
+    /// Add a proposal, refusing it unless it is on time, in budget, and
+    /// before the request deadline
+    ///
+    /// A provider that proposes again replaces its earlier proposal.
+    pub fn submit(&mut self, proposal: TransactionProposal, provider_reputation: f64, now: Timestamp) -> Result<()> {
+        let refuse = |reason: &str| -> Result<()> {
+            Err(TransactionError::QuoteRejected { reason: reason.to_string() }.into())
+        };
+        if proposal.request_id != self.request.id {
+            return refuse("proposal is for a different request");
+        }
+        if !self.is_open(now) {
+            return refuse("proposal window has closed");
+        }
+        if proposal.proposed_price.0 > self.request.budget.0 {
+            return refuse("price is above the budget");
+        }
+        if proposal.estimated_completion.0 > self.request.deadline.0 {
+            return refuse("completion is after the deadline");
+        }
+
+        self.offers.retain(|offer| offer.proposal.provider != proposal.provider);
+        self.offers.push(Offer { proposal, provider_reputation, received_at: now });
+        Ok(())
+    }
+
+    /// Score an offer between 0.0 and 1.0; `reputation` is the requester's
+    /// view of the provider
+    pub fn score(&self, offer: &Offer, reputation: f64, weights: &SelectionWeights) -> f64 {
+        let budget = self.request.budget.0 as f64;
+        let price = if budget > 0.0 { 1.0 - (offer.proposal.proposed_price.0 as f64 / budget).clamp(0.0, 1.0) } else { 1.0 };
+        let delivery_window = (self.request.deadline.0 - self.closes_at.0).num_seconds() as f64;
+        let slack = (self.request.deadline.0 - offer.proposal.estimated_completion.0).num_seconds() as f64;
+        let speed = if delivery_window > 0.0 { (slack / delivery_window).clamp(0.0, 1.0) } else { 1.0 };
+        weights.combine(price, reputation, speed)
+    }
+
+    /// Offers still open at `now` as comparison points, best first
+    pub fn comparison(&self, now: Timestamp, weights: &SelectionWeights, reputation: impl Fn(&Offer) -> f64) -> Vec<OfferPoint> {
+        let mut points: Vec<OfferPoint> = self
+            .offers
+            .iter()
+            .filter(|offer| offer.proposal.expires_at.0 >= now.0)
+            .map(|offer| {
+                let reputation = reputation(offer);
+                OfferPoint {
+                    proposal_id: offer.proposal.id,
+                    provider: offer.proposal.provider,
+                    price: Payment::new(self.request.asset, offer.proposal.proposed_price.0).to_units(),
+                    reputation,
+                    eta_secs: (offer.proposal.estimated_completion.0 - self.closes_at.0).num_seconds(),
+                    score: self.score(offer, reputation, weights),
+                }
+            })
+            .collect();
+        points.sort_by(|a, b| b.score.total_cmp(&a.score));
+        points
+    }
+
+    /// Close the book and turn the chosen proposal into an accepted
+    /// transaction that records every offer received
+    pub fn select(&mut self, proposal_id: &TransactionId) -> Result<Transaction> {
+        if self.status != OfferBookStatus::Collecting {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.status),
+                expected: "Collecting".to_string(),
+            }.into());
+        }
+        let chosen = self
+            .offers
+            .iter()
+            .find(|offer| offer.proposal.id == *proposal_id)
+            .ok_or_else(|| TransactionError::NotFound { id: proposal_id.to_string() })?;
+        let (provider, price) = (chosen.proposal.provider, chosen.proposal.proposed_price);
+
+        let mut transaction = Transaction::new(self.request.clone());
+        for offer in &self.offers {
+            transaction.add_proposal(offer.proposal.clone())?;
+        }
+        transaction.accept_proposal(provider, price)?;
+        self.status = OfferBookStatus::Selected;
+        Ok(transaction)
+    }
+
+    /// Close the book without a winner
+    pub fn close_unfilled(&mut self) {
+        if self.status == OfferBookStatus::Collecting {
+            self.status = OfferBookStatus::Unfilled;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Balance, ServiceType};
+    use std::collections::HashMap;
+
+    fn book() -> OfferBook {
+        let deadline = Timestamp(chrono::Utc::now() + Duration::hours(4));
+        let request = TransactionRequest::new(AgentId::new(), ServiceType::DataAnalysis, "Analysis".to_string(), Balance::from_sol(10.0), deadline);
+        OfferBook::new(request, Duration::minutes(10))
+    }
+
+    fn proposal(book: &OfferBook, price: f64, hours_to_complete: i64) -> TransactionProposal {
+        let now = Timestamp::now();
+        TransactionProposal {
+            id: TransactionId::new(),
+            request_id: book.request.id,
+            provider: AgentId::new(),
+            proposed_price: Balance::from_sol(price),
+            estimated_completion: Timestamp(book.closes_at.0 + Duration::hours(hours_to_complete)),
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: now,
+            expires_at: Timestamp(book.closes_at.0 + Duration::hours(1)),
+        }
+    }
+
+    #[test]
+    fn test_best_offer_is_selected_after_the_window() {
+        let mut book = book();
+        let now = Timestamp::now();
+        let cheap_but_slow = proposal(&book, 4.0, 3);
+        let fast = proposal(&book, 5.0, 1);
+        book.submit(cheap_but_slow.clone(), 0.8, now).unwrap();
+        book.submit(fast.clone(), 0.8, now).unwrap();
+        assert!(book.submit(proposal(&book, 12.0, 1), 0.9, now).is_err());
+        assert!(book.submit(proposal(&book, 5.0, 5), 0.9, now).is_err());
+
+        let weights = SelectionWeights { price: 0.3, reputation: 0.2, speed: 0.5 };
+        let points = book.comparison(now, &weights, |offer| offer.provider_reputation);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].proposal_id, fast.id);
+        assert_eq!(points[1].eta_secs, 3 * 3600);
+
+        let late = Timestamp(book.closes_at.0 + Duration::seconds(1));
+        assert!(book.submit(proposal(&book, 3.0, 1), 0.9, late).is_err());
+
+        let transaction = book.select(&points[0].proposal_id).unwrap();
+        assert_eq!(transaction.provider, Some(fast.provider));
+        assert_eq!(transaction.proposals.len(), 2);
+        assert_eq!(book.status, OfferBookStatus::Selected);
+        assert!(book.select(&cheap_but_slow.id).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+//! Pre-flight Funding Checks
+//!
+//! A payment that cannot be covered fails on chain after the fee is already
+//! spent, and tells the negotiation layer nothing it can act on. Before an
+//! escrow or transfer is submitted, the payer's balance is checked against
+//! everything the transaction will take: the amount, the fee, rent for any
+//! account it creates, and the payer's own rent-exempt reserve if it keeps
+//! a balance. A payer that falls short gets `ChainError::Underfunded` with
+//! the exact shortfall, which a negotiation session can renegotiate around.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ChainError, SolaceError};
+
+/// Lamports a payment needs from its payer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingRequirement {
+    pub amount: u64,
+    pub fees: u64,
+    pub rent: u64,                        // Rent-exempt minimums of accounts the transaction creates
+    pub reserve: u64,                     // Rent-exempt minimum the payer must keep if not drained
+}
+
+impl FundingRequirement {
+    /// Lamports spent by the transaction
+    pub fn spend(&self) -> u64 {
+        self.amount.saturating_add(self.fees).saturating_add(self.rent)
+    }
+
+    /// Check that `balance` covers the payment without stranding the payer
+    /// below rent exemption. Draining the payer to zero is allowed.
+    pub fn check(&self, payer: &str, balance: u64) -> std::result::Result<(), ChainError> {
+        let spend = self.spend();
+        let required = match balance.checked_sub(spend) {
+            None => spend,
+            Some(0) => return Ok(()),
+            Some(left) if left >= self.reserve => return Ok(()),
+            Some(_) => spend.saturating_add(self.reserve),
+        };
+        Err(ChainError::Underfunded {
+            payer: payer.to_string(),
+            available: balance,
+            required,
+            shortfall: required - balance,
+        })
+    }
+}
+
+/// Shortfall carried by an error from a payment, if pre-flight rejected it
+pub fn funding_shortfall(error: &anyhow::Error) -> Option<u64> {
+    match error.downcast_ref::<SolaceError>() {
+        Some(SolaceError::Chain(chain)) => chain.shortfall(),
+        _ => error.downcast_ref::<ChainError>().and_then(ChainError::shortfall),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_reports_exact_shortfall() {
+        let escrow = FundingRequirement { amount: 1_000_000, fees: 5_000, rent: 2_000_000, reserve: 890_880 };
+        assert_eq!(escrow.spend(), 3_005_000);
+
+        // Covers everything and keeps the reserve, or drains exactly
+        assert!(escrow.check("payer", 3_895_880).is_ok());
+        assert!(escrow.check("payer", 3_005_000).is_ok());
+
+        let short = escrow.check("payer", 3_000_000).unwrap_err();
+        assert_eq!(short.shortfall(), Some(5_000));
+
+        // Enough to pay, but the dust left behind would not be rent exempt
+        let stranded = escrow.check("payer", 3_100_000).unwrap_err();
+        assert_eq!(
+            stranded,
+            ChainError::Underfunded { payer: "payer".to_string(), available: 3_100_000, required: 3_895_880, shortfall: 795_880 }
+        );
+        assert_eq!(funding_shortfall(&SolaceError::from(stranded).into()), Some(795_880));
+    }
+}
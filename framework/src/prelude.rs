@@ -0,0 +1,19 @@
+//! Prelude
+//!
+//! The types most agents are written against, for one glob import:
+//! `use solace_protocol::prelude::*;`. Everything else stays reachable
+//! through its module.
+
+pub use crate::{
+    acp::{ACPMessage, MessageType},
+    agent::{Agent, AgentCapability, AgentConfig, AgentConfigBuilder, AgentPreferences},
+    error::{Result, SolaceError},
+    escrow::{Escrow, EscrowLedger, RefundReason},
+    marketplace::{Marketplace, ProviderFilters, ServiceOffer},
+    matching::{QuoteChannel, ServiceMatch, ServiceRequest},
+    transaction::{
+        ExecutionData, Milestone, Transaction, TransactionEvaluation, TransactionPhase, TransactionProposal,
+        TransactionRequest, TransactionRequestBuilder, TransactionStatus,
+    },
+    types::{AgentId, Balance, Payment, PaymentAsset, ServiceType, Timestamp, TransactionId},
+};
@@ -0,0 +1,290 @@
+//! Private Market Statistics
+//!
+//! What an agent publishes of its market analytics on the analytics topic.
+//! Raw statistics can give away a business's volumes and prices, so before
+//! publication a `PrivacyPolicy` withholds services with too few requests
+//! and, when an epsilon is set, adds Laplace noise to every released
+//! aggregate. The budget is split evenly across the noisy counts and sums;
+//! rates and averages are derived from the noisy values and cost nothing
+//! more.
+//!
+//! Average prices and budgets are clamped to the policy's price bound before
+//! they are scaled back to sums, so one transaction moves a sum by at most
+//! the bound. The median time to fill has no bounded form and is withheld
+//! from noisy publications. Each published entry carries `StatsAccuracy`
+//! describing the noise, so consumers can weight it against exact figures.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    acp::MessageType,
+    analytics::{MarketAnalytics, ServiceMarketStats},
+    types::{Balance, Timestamp},
+    Result, SolaceError,
+};
+
+/// Topic market statistics are published on
+pub const ANALYTICS_TOPIC: &str = "analytics";
+
+/// Requests a service needs in the window before its statistics are published
+pub const DEFAULT_MIN_REQUESTS: usize = 5;
+
+/// Default clamp on prices and budgets, in SOL
+const DEFAULT_PRICE_BOUND_SOL: f64 = 100.0;
+
+/// Proposals per request counted towards the noisy proposal sum
+const PROPOSALS_BOUND: f64 = 20.0;
+
+/// Requests, fills, unfilled, and the price, budget, and proposal sums
+const NOISY_AGGREGATES: f64 = 6.0;
+
+/// How market statistics are protected before publication
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyPolicy {
+    pub epsilon: Option<f64>,             // Privacy budget per publication; None publishes exact figures
+    pub min_requests: usize,              // Services with fewer requests are withheld
+    pub price_bound: Balance,             // Prices and budgets are clamped to this
+}
+
+impl Default for PrivacyPolicy {
+    fn default() -> Self {
+        Self {
+            epsilon: None,
+            min_requests: DEFAULT_MIN_REQUESTS,
+            price_bound: Balance::from_sol(DEFAULT_PRICE_BOUND_SOL),
+        }
+    }
+}
+
+impl PrivacyPolicy {
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = Some(epsilon);
+        self
+    }
+
+    pub fn with_min_requests(mut self, min_requests: usize) -> Self {
+        self.min_requests = min_requests;
+        self
+    }
+
+    pub fn with_price_bound(mut self, price_bound: Balance) -> Self {
+        self.price_bound = price_bound;
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(epsilon) = self.epsilon {
+            if !epsilon.is_finite() || epsilon <= 0.0 {
+                return Err(SolaceError::config(format!("privacy epsilon must be positive, got {}", epsilon)));
+            }
+        }
+        if self.price_bound.0 == 0 {
+            return Err(SolaceError::config("privacy price bound must be positive"));
+        }
+        Ok(())
+    }
+
+    /// Publishable statistics for every service in the window ending at `now`
+    pub fn publish(&self, analytics: &MarketAnalytics, now: Timestamp, rng: &mut impl Rng) -> Result<Vec<PublishedMarketStats>> {
+        self.validate()?;
+        Ok(analytics
+            .all_stats(now)
+            .iter()
+            .filter_map(|stats| self.protect(stats, now, rng))
+            .collect())
+    }
+
+    /// Protect one service's statistics, or `None` if they must be withheld
+    pub fn protect(&self, stats: &ServiceMarketStats, now: Timestamp, rng: &mut impl Rng) -> Option<PublishedMarketStats> {
+        let Some(epsilon) = self.epsilon else {
+            return (stats.requests >= self.min_requests).then(|| PublishedMarketStats {
+                stats: stats.clone(),
+                accuracy: StatsAccuracy::exact(self.min_requests),
+                published_at: now,
+            });
+        };
+
+        let share = epsilon / NOISY_AGGREGATES;
+        let bound = self.price_bound.0 as f64;
+        let count_scale = 1.0 / share;
+        let price_scale = bound / share;
+        let proposals_scale = PROPOSALS_BOUND / share;
+
+        let mut noisy_count = |count: usize| (count as f64 + laplace(rng, count_scale)).round().max(0.0) as usize;
+        let requests = noisy_count(stats.requests);
+        if requests == 0 || requests < self.min_requests {
+            return None;
+        }
+        let fills = noisy_count(stats.fills).min(requests);
+        let unfilled = noisy_count(stats.unfilled).min(requests - fills);
+
+        let clamped_sum = |average: Option<Balance>, count: usize| {
+            average.map_or(0.0, |price| (price.0 as f64).min(bound)) * count as f64
+        };
+        let price_sum = clamped_sum(stats.average_clearing_price, stats.fills) + laplace(rng, price_scale);
+        let budget_sum = clamped_sum(stats.average_budget, stats.requests) + laplace(rng, price_scale);
+        let proposals_sum =
+            stats.average_proposals.min(PROPOSALS_BOUND) * stats.requests as f64 + laplace(rng, proposals_scale);
+        let average = |sum: f64, count: usize| Balance((sum / count as f64).clamp(0.0, bound) as u64);
+
+        let sqrt2 = std::f64::consts::SQRT_2;
+        let published = ServiceMarketStats {
+            service_type: stats.service_type.clone(),
+            window_secs: stats.window_secs,
+            requests,
+            fills,
+            unfilled,
+            demand_per_hour: requests as f64 * 3600.0 / stats.window_secs as f64,
+            fill_rate: (fills + unfilled > 0).then(|| fills as f64 / (fills + unfilled) as f64),
+            average_clearing_price: (fills > 0).then(|| average(price_sum, fills)),
+            median_time_to_fill_secs: None,
+            average_proposals: (proposals_sum / requests as f64).clamp(0.0, PROPOSALS_BOUND),
+            average_budget: Some(average(budget_sum, requests)),
+        };
+        Some(PublishedMarketStats {
+            accuracy: StatsAccuracy {
+                epsilon: Some(epsilon),
+                min_requests: self.min_requests,
+                count_stddev: sqrt2 * count_scale,
+                price_stddev: (fills > 0).then(|| Balance((sqrt2 * price_scale / fills as f64) as u64)),
+                budget_stddev: Balance((sqrt2 * price_scale / requests as f64) as u64),
+                proposals_stddev: sqrt2 * proposals_scale / requests as f64,
+            },
+            stats: published,
+            published_at: now,
+        })
+    }
+}
+
+/// How far published statistics may be from the true figures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsAccuracy {
+    pub epsilon: Option<f64>,             // None for exact statistics
+    pub min_requests: usize,              // Aggregation threshold the publisher applied
+    pub count_stddev: f64,                // Noise on requests, fills, and unfilled
+    pub price_stddev: Option<Balance>,    // Noise on the average clearing price
+    pub budget_stddev: Balance,           // Noise on the average budget
+    pub proposals_stddev: f64,            // Noise on proposals per request
+}
+
+impl StatsAccuracy {
+    pub fn exact(min_requests: usize) -> Self {
+        Self {
+            epsilon: None,
+            min_requests,
+            count_stddev: 0.0,
+            price_stddev: None,
+            budget_stddev: Balance(0),
+            proposals_stddev: 0.0,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.epsilon.is_none()
+    }
+}
+
+/// Market statistics as published on the analytics topic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishedMarketStats {
+    pub stats: ServiceMarketStats,
+    pub accuracy: StatsAccuracy,
+    pub published_at: Timestamp,
+}
+
+impl PublishedMarketStats {
+    /// Weight against exact statistics: 1 when exact, falling as count noise
+    /// approaches the request count
+    pub fn weight(&self) -> f64 {
+        let requests = self.stats.requests as f64;
+        let variance = self.accuracy.count_stddev.powi(2);
+        if requests == 0.0 {
+            return 0.0;
+        }
+        requests.powi(2) / (requests.powi(2) + variance)
+    }
+
+    pub fn topic(&self) -> &'static str {
+        ANALYTICS_TOPIC
+    }
+
+    pub fn message_type(&self) -> MessageType {
+        MessageType::MarketStats
+    }
+
+    /// Gossip payload
+    pub fn to_payload(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    pub fn from_payload(payload: serde_json::Value) -> Result<Self> {
+        Ok(serde_json::from_value(payload)?)
+    }
+}
+
+/// Laplace noise with the given scale
+fn laplace(rng: &mut impl Rng, scale: f64) -> f64 {
+    let u: f64 = rng.gen_range(-0.5..0.5);
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).max(f64::MIN_POSITIVE).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ServiceType;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn stats(requests: usize) -> ServiceMarketStats {
+        ServiceMarketStats {
+            service_type: ServiceType::DataAnalysis,
+            window_secs: 3600,
+            requests,
+            fills: requests * 3 / 4,
+            unfilled: requests / 4,
+            demand_per_hour: requests as f64,
+            fill_rate: Some(0.75),
+            average_clearing_price: Some(Balance::from_sol(5.0)),
+            median_time_to_fill_secs: Some(120.0),
+            average_proposals: 3.0,
+            average_budget: Some(Balance::from_sol(8.0)),
+        }
+    }
+
+    #[test]
+    fn test_thresholds_and_noise_before_publication() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let now = Timestamp::now();
+
+        // Exact publication only withholds small aggregates
+        let exact = PrivacyPolicy::default();
+        assert!(exact.protect(&stats(4), now, &mut rng).is_none());
+        let published = exact.protect(&stats(8), now, &mut rng).unwrap();
+        assert_eq!(published.stats, stats(8));
+        assert!(published.accuracy.is_exact());
+        assert_eq!(published.weight(), 1.0);
+
+        assert!(PrivacyPolicy::default().with_epsilon(0.0).validate().is_err());
+
+        // Noisy figures stay near the truth on average, and say how noisy they are
+        let noisy = PrivacyPolicy::default().with_epsilon(6.0).with_price_bound(Balance::from_sol(10.0));
+        let runs: Vec<PublishedMarketStats> =
+            (0..200).filter_map(|_| noisy.protect(&stats(400), now, &mut rng)).collect();
+        assert_eq!(runs.len(), 200);
+        let mean_requests = runs.iter().map(|p| p.stats.requests as f64).sum::<f64>() / 200.0;
+        assert!((mean_requests - 400.0).abs() < 1.0);
+        let mean_price = runs.iter().map(|p| p.stats.average_clearing_price.unwrap().to_sol()).sum::<f64>() / 200.0;
+        assert!((mean_price - 5.0).abs() < 0.05);
+
+        let first = &runs[0];
+        assert_eq!(first.accuracy.epsilon, Some(6.0));
+        assert!((first.accuracy.count_stddev - std::f64::consts::SQRT_2).abs() < 1e-9);
+        assert!(first.accuracy.price_stddev.is_some());
+        assert_eq!(first.stats.median_time_to_fill_secs, None);
+        assert!(first.weight() < 1.0);
+        assert!(first.stats.fills + first.stats.unfilled <= first.stats.requests);
+
+        let payload = first.to_payload().unwrap();
+        assert_eq!(&PublishedMarketStats::from_payload(payload).unwrap(), first);
+    }
+}
@@ -0,0 +1,366 @@
+//! Solace Program Interface
+//!
+//! Instructions as the Solace on-chain program reads them, and the
+//! addresses of the accounts it keeps. Instructions are Borsh encoded: one
+//! discriminant byte, then the fields in declaration order. Agent and
+//! transaction IDs travel as their 16 UUID bytes, public keys as 32 bytes,
+//! and integers little-endian.
+//!
+//! Each instruction's discriminant is fixed by `SolaceInstruction`'s
+//! constants rather than by its position in the enum, so instructions can be
+//! added anywhere without changing the bytes of existing ones. A number is
+//! never reused once assigned.
+//!
+//! Agent, reputation, and transaction records live at program-derived
+//! addresses seeded with a kind prefix and the record's ID; `SolaceProgram`
+//! derives them and puts them in each instruction's account list.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use std::io::{self, Read, Write};
+use uuid::Uuid;
+
+use crate::{
+    types::{AgentId, TransactionId},
+    Result,
+};
+
+/// Seed prefix of an agent's record
+pub const AGENT_SEED: &[u8] = b"agent";
+
+/// Seed prefix of an agent's reputation record
+pub const REPUTATION_SEED: &[u8] = b"reputation";
+
+/// Seed prefix of a transaction's record
+pub const TRANSACTION_SEED: &[u8] = b"transaction";
+
+/// Seed prefix of the authority over a transaction's token vault
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// Instructions of the Solace program
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum SolaceInstruction {
+    InitializeAgent {
+        agent_id: AgentId,
+        initial_reputation: u32,
+    },
+    CreateTransaction {
+        transaction_id: TransactionId,
+        amount: u64,
+        recipient: Pubkey,
+    },
+    CreateTokenTransaction {
+        transaction_id: TransactionId,
+        mint: Pubkey,
+        amount: u64,                      // In base units of the mint
+        recipient: Pubkey,
+    },
+    UpdateReputation {
+        agent_id: AgentId,
+        new_reputation: u32,
+    },
+    FinalizeTransaction {
+        transaction_id: TransactionId,
+        success: bool,
+    },
+    ReleasePartial {
+        transaction_id: TransactionId,
+        amount: u64,
+    },
+    Stake {
+        amount: u64,
+    },
+    Unstake {
+        amount: u64,
+    },
+    Vote {
+        proposal_id: String,
+        vote: bool,
+    },
+}
+
+impl SolaceInstruction {
+    pub const INITIALIZE_AGENT: u8 = 0;
+    pub const CREATE_TRANSACTION: u8 = 1;
+    pub const UPDATE_REPUTATION: u8 = 2;
+    pub const FINALIZE_TRANSACTION: u8 = 3;
+    pub const STAKE: u8 = 4;
+    pub const UNSTAKE: u8 = 5;
+    pub const VOTE: u8 = 6;
+    pub const RELEASE_PARTIAL: u8 = 7;
+    pub const CREATE_TOKEN_TRANSACTION: u8 = 8;
+
+    /// Byte identifying the instruction to the program
+    pub fn discriminant(&self) -> u8 {
+        match self {
+            SolaceInstruction::InitializeAgent { .. } => Self::INITIALIZE_AGENT,
+            SolaceInstruction::CreateTransaction { .. } => Self::CREATE_TRANSACTION,
+            SolaceInstruction::CreateTokenTransaction { .. } => Self::CREATE_TOKEN_TRANSACTION,
+            SolaceInstruction::UpdateReputation { .. } => Self::UPDATE_REPUTATION,
+            SolaceInstruction::FinalizeTransaction { .. } => Self::FINALIZE_TRANSACTION,
+            SolaceInstruction::ReleasePartial { .. } => Self::RELEASE_PARTIAL,
+            SolaceInstruction::Stake { .. } => Self::STAKE,
+            SolaceInstruction::Unstake { .. } => Self::UNSTAKE,
+            SolaceInstruction::Vote { .. } => Self::VOTE,
+        }
+    }
+
+    /// Instruction data as the program reads it
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        Ok(self.try_to_vec()?)
+    }
+
+    /// Read instruction data, refusing trailing bytes
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        Ok(Self::try_from_slice(data)?)
+    }
+}
+
+impl BorshSerialize for SolaceInstruction {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.discriminant().serialize(writer)?;
+        match self {
+            SolaceInstruction::InitializeAgent { agent_id, initial_reputation } => {
+                agent_id.0.as_bytes().serialize(writer)?;
+                initial_reputation.serialize(writer)
+            }
+            SolaceInstruction::CreateTransaction { transaction_id, amount, recipient } => {
+                transaction_id.0.as_bytes().serialize(writer)?;
+                amount.serialize(writer)?;
+                recipient.serialize(writer)
+            }
+            SolaceInstruction::CreateTokenTransaction { transaction_id, mint, amount, recipient } => {
+                transaction_id.0.as_bytes().serialize(writer)?;
+                mint.serialize(writer)?;
+                amount.serialize(writer)?;
+                recipient.serialize(writer)
+            }
+            SolaceInstruction::UpdateReputation { agent_id, new_reputation } => {
+                agent_id.0.as_bytes().serialize(writer)?;
+                new_reputation.serialize(writer)
+            }
+            SolaceInstruction::FinalizeTransaction { transaction_id, success } => {
+                transaction_id.0.as_bytes().serialize(writer)?;
+                success.serialize(writer)
+            }
+            SolaceInstruction::ReleasePartial { transaction_id, amount } => {
+                transaction_id.0.as_bytes().serialize(writer)?;
+                amount.serialize(writer)
+            }
+            SolaceInstruction::Stake { amount } | SolaceInstruction::Unstake { amount } => amount.serialize(writer),
+            SolaceInstruction::Vote { proposal_id, vote } => {
+                proposal_id.serialize(writer)?;
+                vote.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for SolaceInstruction {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let uuid = |reader: &mut R| <[u8; 16]>::deserialize_reader(reader).map(Uuid::from_bytes);
+        Ok(match u8::deserialize_reader(reader)? {
+            Self::INITIALIZE_AGENT => SolaceInstruction::InitializeAgent {
+                agent_id: AgentId(uuid(reader)?),
+                initial_reputation: u32::deserialize_reader(reader)?,
+            },
+            Self::CREATE_TRANSACTION => SolaceInstruction::CreateTransaction {
+                transaction_id: TransactionId(uuid(reader)?),
+                amount: u64::deserialize_reader(reader)?,
+                recipient: Pubkey::deserialize_reader(reader)?,
+            },
+            Self::CREATE_TOKEN_TRANSACTION => SolaceInstruction::CreateTokenTransaction {
+                transaction_id: TransactionId(uuid(reader)?),
+                mint: Pubkey::deserialize_reader(reader)?,
+                amount: u64::deserialize_reader(reader)?,
+                recipient: Pubkey::deserialize_reader(reader)?,
+            },
+            Self::UPDATE_REPUTATION => SolaceInstruction::UpdateReputation {
+                agent_id: AgentId(uuid(reader)?),
+                new_reputation: u32::deserialize_reader(reader)?,
+            },
+            Self::FINALIZE_TRANSACTION => SolaceInstruction::FinalizeTransaction {
+                transaction_id: TransactionId(uuid(reader)?),
+                success: bool::deserialize_reader(reader)?,
+            },
+            Self::RELEASE_PARTIAL => SolaceInstruction::ReleasePartial {
+                transaction_id: TransactionId(uuid(reader)?),
+                amount: u64::deserialize_reader(reader)?,
+            },
+            Self::STAKE => SolaceInstruction::Stake { amount: u64::deserialize_reader(reader)? },
+            Self::UNSTAKE => SolaceInstruction::Unstake { amount: u64::deserialize_reader(reader)? },
+            Self::VOTE => SolaceInstruction::Vote {
+                proposal_id: String::deserialize_reader(reader)?,
+                vote: bool::deserialize_reader(reader)?,
+            },
+            other => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown Solace instruction {}", other)));
+            }
+        })
+    }
+}
+
+/// Client side of the Solace program: account addresses and instructions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolaceProgram {
+    pub program_id: Pubkey,
+}
+
+impl SolaceProgram {
+    pub fn new(program_id: Pubkey) -> Self {
+        Self { program_id }
+    }
+
+    /// Record of an agent
+    pub fn agent_address(&self, agent_id: &AgentId) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[AGENT_SEED, agent_id.0.as_bytes()], &self.program_id)
+    }
+
+    /// Reputation record of an agent
+    pub fn reputation_address(&self, agent_id: &AgentId) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[REPUTATION_SEED, agent_id.0.as_bytes()], &self.program_id)
+    }
+
+    /// Record of a transaction, which holds its escrowed lamports
+    pub fn transaction_address(&self, transaction_id: &TransactionId) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TRANSACTION_SEED, transaction_id.0.as_bytes()], &self.program_id)
+    }
+
+    /// Authority over a transaction's token vault
+    pub fn escrow_authority(&self, transaction_id: &TransactionId) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ESCROW_SEED, transaction_id.0.as_bytes()], &self.program_id)
+    }
+
+    /// Program records an instruction reads or writes
+    pub fn derived_accounts(&self, instruction: &SolaceInstruction) -> Vec<AccountMeta> {
+        match instruction {
+            SolaceInstruction::InitializeAgent { agent_id, .. } => vec![
+                AccountMeta::new(self.agent_address(agent_id).0, false),
+                AccountMeta::new(self.reputation_address(agent_id).0, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            SolaceInstruction::UpdateReputation { agent_id, .. } => vec![
+                AccountMeta::new_readonly(self.agent_address(agent_id).0, false),
+                AccountMeta::new(self.reputation_address(agent_id).0, false),
+            ],
+            SolaceInstruction::CreateTransaction { transaction_id, .. }
+            | SolaceInstruction::CreateTokenTransaction { transaction_id, .. } => vec![
+                AccountMeta::new(self.transaction_address(transaction_id).0, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            SolaceInstruction::FinalizeTransaction { transaction_id, .. }
+            | SolaceInstruction::ReleasePartial { transaction_id, .. } => vec![
+                AccountMeta::new(self.transaction_address(transaction_id).0, false),
+            ],
+            SolaceInstruction::Stake { .. } | SolaceInstruction::Unstake { .. } | SolaceInstruction::Vote { .. } => vec![],
+        }
+    }
+
+    /// Instruction signed by `signer`: the signer, the program records it
+    /// touches, then `additional_accounts`
+    pub fn instruction(
+        &self,
+        instruction: &SolaceInstruction,
+        signer: &Pubkey,
+        additional_accounts: Vec<AccountMeta>,
+    ) -> Result<Instruction> {
+        let mut accounts = vec![AccountMeta::new(*signer, true)];
+        accounts.extend(self.derived_accounts(instruction));
+        accounts.extend(additional_accounts);
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction.encode()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_instruction() -> Vec<SolaceInstruction> {
+        let agent_id = AgentId::new();
+        let transaction_id = TransactionId::new();
+        vec![
+            SolaceInstruction::InitializeAgent { agent_id, initial_reputation: 500 },
+            SolaceInstruction::CreateTransaction { transaction_id, amount: 1_000, recipient: Pubkey::new_unique() },
+            SolaceInstruction::CreateTokenTransaction {
+                transaction_id,
+                mint: Pubkey::new_unique(),
+                amount: 2_500_000,
+                recipient: Pubkey::new_unique(),
+            },
+            SolaceInstruction::UpdateReputation { agent_id, new_reputation: 750 },
+            SolaceInstruction::FinalizeTransaction { transaction_id, success: true },
+            SolaceInstruction::ReleasePartial { transaction_id, amount: 300 },
+            SolaceInstruction::Stake { amount: 42 },
+            SolaceInstruction::Unstake { amount: 7 },
+            SolaceInstruction::Vote { proposal_id: "fee-change".to_string(), vote: false },
+        ]
+    }
+
+    #[test]
+    fn test_instructions_match_the_program_layout() {
+        let id = Uuid::from_bytes([0xab; 16]);
+        let recipient = Pubkey::new_from_array([0x11; 32]);
+
+        let create = SolaceInstruction::CreateTransaction {
+            transaction_id: TransactionId(id),
+            amount: 0x0102_0304_0506_0708,
+            recipient,
+        };
+        let mut expected = vec![1];
+        expected.extend([0xab; 16]);
+        expected.extend([0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+        expected.extend([0x11; 32]);
+        assert_eq!(create.encode().unwrap(), expected);
+
+        let finalize = SolaceInstruction::FinalizeTransaction { transaction_id: TransactionId(id), success: true };
+        let mut expected = vec![3];
+        expected.extend([0xab; 16]);
+        expected.push(1);
+        assert_eq!(finalize.encode().unwrap(), expected);
+
+        let vote = SolaceInstruction::Vote { proposal_id: "p1".to_string(), vote: true };
+        assert_eq!(vote.encode().unwrap(), vec![6, 2, 0, 0, 0, b'p', b'1', 1]);
+
+        // Every instruction round-trips under its own discriminant
+        for instruction in every_instruction() {
+            let data = instruction.encode().unwrap();
+            assert_eq!(data[0], instruction.discriminant());
+            assert_eq!(SolaceInstruction::decode(&data).unwrap(), instruction);
+        }
+        assert!(SolaceInstruction::decode(&[9]).is_err());
+        let mut trailing = finalize.encode().unwrap();
+        trailing.push(0);
+        assert!(SolaceInstruction::decode(&trailing).is_err());
+    }
+
+    #[test]
+    fn test_program_accounts_are_derived() {
+        let program = SolaceProgram::new(Pubkey::new_unique());
+        let agent_id = AgentId::new();
+        let transaction_id = TransactionId::new();
+
+        let (agent, bump) = program.agent_address(&agent_id);
+        let seeds: &[&[u8]] = &[AGENT_SEED, agent_id.0.as_bytes(), &[bump]];
+        assert_eq!(Pubkey::create_program_address(seeds, &program.program_id).unwrap(), agent);
+        assert_ne!(agent, program.reputation_address(&agent_id).0);
+        assert_ne!(program.transaction_address(&transaction_id).0, program.escrow_authority(&transaction_id).0);
+        assert_ne!(agent, SolaceProgram::new(Pubkey::new_unique()).agent_address(&agent_id).0);
+
+        let signer = Pubkey::new_unique();
+        let init = SolaceInstruction::InitializeAgent { agent_id, initial_reputation: 500 };
+        let instruction = program.instruction(&init, &signer, vec![]).unwrap();
+        assert_eq!(instruction.program_id, program.program_id);
+        assert!(instruction.accounts[0].is_signer && instruction.accounts[0].pubkey == signer);
+        assert_eq!(instruction.accounts[1].pubkey, agent);
+        assert_eq!(instruction.accounts[2].pubkey, program.reputation_address(&agent_id).0);
+        assert_eq!(SolaceInstruction::decode(&instruction.data).unwrap(), init);
+    }
+}
@@ -68,6 +68,8 @@ pub enum ReputationEventType {
     TimeoutPenalty,
     QualityBonus,
     FraudPenalty,
+    PricingViolation,
+    ProtocolViolation,
 }
 
 /// Global reputation system
@@ -86,6 +88,21 @@ impl ReputationSystem {
         self.agent_scores.get(agent_id).map(|score| score.current_score())
     }
 
+    /// Carry an agent's score over from its legacy ID, unless its derived
+    /// ID already has one
+    pub fn migrate(&mut self, legacy_id: &AgentId, agent_id: AgentId) -> bool {
+        if self.agent_scores.contains_key(&agent_id) {
+            return false;
+        }
+        match self.agent_scores.remove(legacy_id) {
+            Some(score) => {
+                self.agent_scores.insert(agent_id, score);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn update_reputation(&mut self, agent_id: AgentId, event: ReputationEvent) -> Result<f64, ReputationError> {
         let score = self.agent_scores.entry(agent_id).or_insert_with(|| ReputationScore::new(0.5));
         
@@ -0,0 +1,377 @@
+//! Request for Quote
+//!
+//! Instead of posting a request and haggling with whoever answers first, a
+//! requester can broadcast a `QuoteIntent` (service type, budget range,
+//! deadline) on the service's RFQ topic. Providers that want the work answer
+//! with a binding `Quote`: a fixed price they must honor until the quote's
+//! expiry, which is at least the close of the quote window. Once the window
+//! closes, the requester ranks the quotes it collected in an `RfqSession` and
+//! turns the winner into an accepted transaction.
+//!
+//! Intents and quotes travel as `RfqMessage`s, gossiped with the
+//! `QuoteRequest` and `Quote` message types.
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    acp::MessageType,
+    error::{Result, TransactionError},
+    transaction::{Transaction, TransactionProposal, TransactionRequest, TransactionTags},
+    types::{AgentId, Balance, PaymentAsset, ServiceType, Timestamp, TransactionId},
+};
+
+/// Prefix of the per-service RFQ topics
+pub const RFQ_TOPIC_PREFIX: &str = "rfq";
+
+/// Gossip topic carrying intents for a service type, e.g. `rfq.data_analysis`
+pub fn rfq_topic(service_type: &ServiceType) -> String {
+    format!("{}.{}", RFQ_TOPIC_PREFIX, service_type.key())
+}
+
+/// A requester's call for quotes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteIntent {
+    pub id: TransactionId,
+    pub requester: AgentId,
+    pub service_type: ServiceType,
+    pub description: String,
+    pub min_budget: Balance,
+    pub max_budget: Balance,
+    #[serde(default)]
+    pub asset: PaymentAsset,              // What budgets and quotes are counted in
+    pub deadline: Timestamp,              // When the service must be delivered
+    pub quotes_close_at: Timestamp,       // End of the quote window
+    pub requirements: HashMap<String, String>,
+    #[serde(default)]
+    pub tags: TransactionTags,            // Carried into the awarded transaction
+    pub created_at: Timestamp,
+}
+
+impl QuoteIntent {
+    pub fn new(
+        requester: AgentId,
+        service_type: ServiceType,
+        description: String,
+        budget: (Balance, Balance),
+        deadline: Timestamp,
+        quote_window: Duration,
+    ) -> Self {
+        let created_at = Timestamp::now();
+        Self {
+            id: TransactionId::new(),
+            requester,
+            service_type,
+            description,
+            min_budget: budget.0,
+            max_budget: budget.1,
+            asset: PaymentAsset::Sol,
+            deadline,
+            quotes_close_at: Timestamp(created_at.0 + quote_window),
+            requirements: HashMap::new(),
+            tags: TransactionTags::default(),
+            created_at,
+        }
+    }
+
+    /// Intent paid in `asset`, with budgets in its base units
+    pub fn with_asset(mut self, asset: PaymentAsset) -> Self {
+        self.asset = asset;
+        self
+    }
+
+    /// Topic the intent is broadcast on
+    pub fn topic(&self) -> String {
+        rfq_topic(&self.service_type)
+    }
+
+    /// Whether quotes are still being taken
+    pub fn accepts_quotes(&self, now: Timestamp) -> bool {
+        now.0 <= self.quotes_close_at.0
+    }
+
+    /// Transaction request for the deal, budgeted at the top of the range
+    pub fn to_request(&self) -> TransactionRequest {
+        TransactionRequest {
+            id: self.id,
+            requester: self.requester,
+            service_type: self.service_type.clone(),
+            description: self.description.clone(),
+            budget: self.max_budget,
+            asset: self.asset,
+            deadline: self.deadline,
+            requirements: self.requirements.clone(),
+            tags: self.tags.clone(),
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// A provider's binding offer for an intent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quote {
+    pub id: TransactionId,
+    pub intent_id: TransactionId,
+    pub provider: AgentId,
+    pub price: Balance,
+    pub estimated_completion: Timestamp,
+    pub valid_until: Timestamp,           // The provider honors the price until then
+    pub provider_reputation: f64,         // As claimed by the provider
+    pub terms: HashMap<String, String>,
+    pub created_at: Timestamp,
+}
+
+impl Quote {
+    /// Proposal form of the quote, for the transaction it wins
+    pub fn to_proposal(&self) -> TransactionProposal {
+        TransactionProposal {
+            id: self.id,
+            request_id: self.intent_id,
+            provider: self.provider,
+            proposed_price: self.price,
+            estimated_completion: self.estimated_completion,
+            proposal_details: "Binding quote".to_string(),
+            terms: self.terms.clone(),
+            created_at: self.created_at,
+            expires_at: self.valid_until,
+        }
+    }
+}
+
+/// RFQ traffic on the gossip network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RfqMessage {
+    Intent(QuoteIntent),
+    Quote(Quote),
+}
+
+impl RfqMessage {
+    /// Gossip message type carrying this message
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            RfqMessage::Intent(_) => MessageType::QuoteRequest,
+            RfqMessage::Quote(_) => MessageType::Quote,
+        }
+    }
+
+    /// Gossip payload
+    pub fn to_payload(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    pub fn from_payload(payload: serde_json::Value) -> Result<Self> {
+        Ok(serde_json::from_value(payload)?)
+    }
+}
+
+/// How the requester weighs quotes against each other
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SelectionWeights {
+    pub price: f64,
+    pub reputation: f64,
+    pub speed: f64,
+}
+
+impl SelectionWeights {
+    /// Weighted mean of per-criterion scores, each between 0.0 and 1.0
+    pub fn combine(&self, price: f64, reputation: f64, speed: f64) -> f64 {
+        let total = self.price + self.reputation + self.speed;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        (self.price * price + self.reputation * reputation.clamp(0.0, 1.0) + self.speed * speed) / total
+    }
+}
+
+impl Default for SelectionWeights {
+    fn default() -> Self {
+        Self {
+            price: 0.5,
+            reputation: 0.35,
+            speed: 0.15,
+        }
+    }
+}
+
+/// Lifecycle of an RFQ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RfqStatus {
+    Collecting,
+    Awarded,
+    Unfilled,
+}
+
+/// Quotes collected for one intent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RfqSession {
+    pub intent: QuoteIntent,
+    pub quotes: Vec<Quote>,
+    pub status: RfqStatus,
+}
+
+impl RfqSession {
+    pub fn new(intent: QuoteIntent) -> Self {
+        Self {
+            intent,
+            quotes: Vec::new(),
+            status: RfqStatus::Collecting,
+        }
+    }
+
+    /// Take a quote, refusing it unless it is binding, on time, and in budget
+    ///
+    /// A provider that quotes again replaces its earlier quote.
+    pub fn submit(&mut self, quote: Quote, now: Timestamp) -> Result<()> {
+        let refuse = |reason: &str| -> Result<()> {
+            Err(TransactionError::QuoteRejected { reason: reason.to_string() }.into())
+        };
+        if quote.intent_id != self.intent.id {
+            return refuse("quote is for a different intent");
+        }
+        if self.status != RfqStatus::Collecting || !self.intent.accepts_quotes(now) {
+            return refuse("quote window has closed");
+        }
+        if quote.price.0 > self.intent.max_budget.0 {
+            return refuse("price is above the budget");
+        }
+        if quote.valid_until.0 < self.intent.quotes_close_at.0 {
+            return refuse("quote expires before the window closes");
+        }
+        if quote.estimated_completion.0 > self.intent.deadline.0 {
+            return refuse("completion is after the deadline");
+        }
+
+        self.quotes.retain(|existing| existing.provider != quote.provider);
+        self.quotes.push(quote);
+        Ok(())
+    }
+
+    /// Score a quote between 0.0 and 1.0; `reputation` is the requester's view
+    /// of the provider
+    pub fn score(&self, quote: &Quote, reputation: f64, weights: &SelectionWeights) -> f64 {
+        let ratio = |numerator: f64, denominator: f64| {
+            if denominator > 0.0 { (numerator / denominator).clamp(0.0, 1.0) } else { 1.0 }
+        };
+        let budget_range = (self.intent.max_budget.0 - self.intent.min_budget.0.min(self.intent.max_budget.0)) as f64;
+        let price = ratio(self.intent.max_budget.0.saturating_sub(quote.price.0) as f64, budget_range);
+        let delivery_window = (self.intent.deadline.0 - self.intent.quotes_close_at.0).num_seconds() as f64;
+        let speed = ratio((self.intent.deadline.0 - quote.estimated_completion.0).num_seconds() as f64, delivery_window);
+        weights.combine(price, reputation, speed)
+    }
+
+    /// Quotes still binding at `now`, best first
+    pub fn rank(&self, now: Timestamp, weights: &SelectionWeights, reputation: impl Fn(&Quote) -> f64) -> Vec<(Quote, f64)> {
+        let mut ranked: Vec<(Quote, f64)> = self
+            .quotes
+            .iter()
+            .filter(|quote| quote.valid_until.0 >= now.0)
+            .map(|quote| (quote.clone(), self.score(quote, reputation(quote), weights)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+
+    /// Close the window and turn the chosen quote into an accepted transaction
+    pub fn award(&mut self, quote: &Quote) -> Result<Transaction> {
+        if self.status != RfqStatus::Collecting {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.status),
+                expected: "Collecting".to_string(),
+            }.into());
+        }
+
+        let mut transaction = Transaction::new(self.intent.to_request());
+        transaction.add_proposal(quote.to_proposal())?;
+        transaction.accept_proposal(quote.provider, quote.price)?;
+        self.status = RfqStatus::Awarded;
+        Ok(transaction)
+    }
+
+    /// Close the window without a winner
+    pub fn close_unfilled(&mut self) {
+        if self.status == RfqStatus::Collecting {
+            self.status = RfqStatus::Unfilled;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intent() -> QuoteIntent {
+        QuoteIntent::new(
+            AgentId::new(),
+            ServiceType::DataAnalysis,
+            "Sentiment analysis".to_string(),
+            (Balance::from_sol(2.0), Balance::from_sol(10.0)),
+            Timestamp(chrono::Utc::now() + Duration::hours(2)),
+            Duration::minutes(5),
+        )
+    }
+
+    fn quote(intent: &QuoteIntent, price: f64, hours_to_complete: i64) -> Quote {
+        Quote {
+            id: TransactionId::new(),
+            intent_id: intent.id,
+            provider: AgentId::new(),
+            price: Balance::from_sol(price),
+            estimated_completion: Timestamp(chrono::Utc::now() + Duration::hours(hours_to_complete)),
+            valid_until: Timestamp(intent.quotes_close_at.0 + Duration::minutes(10)),
+            provider_reputation: 0.8,
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+        }
+    }
+
+    #[test]
+    fn test_only_binding_quotes_are_collected() {
+        let intent = intent();
+        assert_eq!(intent.topic(), "rfq.data_analysis");
+        let mut session = RfqSession::new(intent.clone());
+        let now = Timestamp::now();
+
+        assert!(session.submit(quote(&intent, 6.0, 1), now).is_ok());
+        assert!(session.submit(quote(&intent, 12.0, 1), now).is_err());
+        assert!(session.submit(quote(&intent, 6.0, 3), now).is_err());
+
+        let mut short_lived = quote(&intent, 5.0, 1);
+        short_lived.valid_until = now;
+        assert!(session.submit(short_lived, now).is_err());
+
+        let late = Timestamp(intent.quotes_close_at.0 + Duration::seconds(1));
+        assert!(session.submit(quote(&intent, 4.0, 1), late).is_err());
+        assert_eq!(session.quotes.len(), 1);
+
+        let message = RfqMessage::Intent(intent);
+        let decoded = RfqMessage::from_payload(message.to_payload().unwrap()).unwrap();
+        assert_eq!(decoded.message_type(), MessageType::QuoteRequest);
+    }
+
+    #[test]
+    fn test_ranking_and_award() {
+        let intent = intent();
+        let mut session = RfqSession::new(intent.clone());
+        let now = Timestamp::now();
+        let cheap = quote(&intent, 5.0, 1);
+        let pricey = quote(&intent, 7.0, 1);
+        session.submit(cheap.clone(), now).unwrap();
+        session.submit(pricey.clone(), now).unwrap();
+
+        let weights = SelectionWeights::default();
+        let ranked = session.rank(now, &weights, |_| 0.8);
+        assert_eq!(ranked[0].0, cheap);
+
+        // A poor enough reputation outweighs the lower price
+        let ranked = session.rank(now, &weights, |q| if q.provider == cheap.provider { 0.2 } else { 0.9 });
+        assert_eq!(ranked[0].0, pricey);
+
+        let transaction = session.award(&pricey).unwrap();
+        assert_eq!(transaction.agreed_price, Some(pricey.price));
+        assert_eq!(transaction.provider, Some(pricey.provider));
+        assert_eq!(session.status, RfqStatus::Awarded);
+        assert!(session.award(&cheap).is_err());
+    }
+}
@@ -0,0 +1,325 @@
+//! Transaction Search
+//!
+//! Full-text search over persisted transactions and their requests. Request
+//! descriptions, requirements, proposal details, and evaluation feedback are
+//! tokenized into an in-memory inverted index and ranked with BM25, and
+//! results can be narrowed with structured filters (status, phase, service
+//! type, parties, price, creation time, and tag).
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    storage::StorageManager,
+    transaction::{Transaction, TransactionPhase, TransactionStatus},
+    types::{AgentId, Balance, ServiceType, Timestamp, TransactionId},
+    Result,
+};
+
+/// BM25 term frequency saturation
+const BM25_K1: f64 = 1.2;
+
+/// BM25 document length normalization
+const BM25_B: f64 = 0.75;
+
+/// Default number of results
+const DEFAULT_LIMIT: usize = 20;
+
+/// Search query: free text plus structured filters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    /// Free text; every term must match
+    pub text: Option<String>,
+    pub status: Option<TransactionStatus>,
+    pub phase: Option<TransactionPhase>,
+    pub service_type: Option<ServiceType>,
+    pub requester: Option<AgentId>,
+    pub provider: Option<AgentId>,
+    /// Agreed price, or the budget when no price is agreed yet
+    pub min_price: Option<Balance>,
+    pub max_price: Option<Balance>,
+    pub created_after: Option<Timestamp>,
+    pub created_before: Option<Timestamp>,
+    /// `project=x`, `cost_center=x`, `environment=x`, or a free-form label
+    pub tag: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl SearchQuery {
+    /// Query matching free text
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Whether a transaction passes the structured filters
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        let price = tx.agreed_price.unwrap_or(tx.request.budget);
+
+        self.status.is_none_or(|status| tx.status == status)
+            && self.phase.is_none_or(|phase| tx.phase == phase)
+            && self.service_type.as_ref().is_none_or(|service| &tx.request.service_type == service)
+            && self.requester.is_none_or(|requester| tx.request.requester == requester)
+            && self.provider.is_none_or(|provider| tx.provider == Some(provider))
+            && self.min_price.is_none_or(|min| price >= min)
+            && self.max_price.is_none_or(|max| price <= max)
+            && self.created_after.is_none_or(|after| tx.created_at >= after)
+            && self.created_before.is_none_or(|before| tx.created_at <= before)
+            && self.tag.as_deref().is_none_or(|tag| tx.request.tags.matches(tag))
+    }
+}
+
+/// A ranked search result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub transaction: Transaction,
+    /// BM25 score; 0 for filter-only queries
+    pub score: f64,
+    /// Indexed terms of the query that matched
+    pub matched_terms: Vec<String>,
+}
+
+/// Search results with the total before pagination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+}
+
+/// Lowercase alphanumeric terms of a text
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+/// Searchable text of a transaction
+fn document_text(tx: &Transaction) -> String {
+    let mut parts = vec![tx.request.description.clone(), tx.request.service_type.to_string()];
+    for (key, value) in &tx.request.requirements {
+        parts.push(format!("{} {}", key, value));
+    }
+    parts.extend(tx.request.tags.entries());
+    for proposal in &tx.proposals {
+        parts.push(proposal.proposal_details.clone());
+        parts.extend(proposal.terms.values().cloned());
+    }
+    if let Some(evaluation) = &tx.evaluation {
+        parts.push(evaluation.requester_feedback.clone());
+        parts.push(evaluation.provider_feedback.clone());
+    }
+    parts.join(" ")
+}
+
+/// In-memory inverted index over transactions
+#[derive(Debug, Default)]
+pub struct TransactionSearchIndex {
+    transactions: HashMap<TransactionId, Transaction>,
+    /// term -> transaction -> term frequency
+    postings: HashMap<String, HashMap<TransactionId, u32>>,
+    /// transaction -> its distinct terms, so removal touches only their postings
+    terms: HashMap<TransactionId, HashSet<String>>,
+    lengths: HashMap<TransactionId, usize>,
+    total_length: usize,
+}
+
+impl TransactionSearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build an index from every transaction in storage
+    pub async fn from_storage(storage: &StorageManager) -> Result<Self> {
+        let mut index = Self::new();
+        for tx in storage.list_transactions::<Transaction>().await? {
+            index.insert(tx);
+        }
+        Ok(index)
+    }
+
+    /// Number of indexed transactions
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Index a transaction, replacing any earlier version
+    pub fn insert(&mut self, tx: Transaction) {
+        self.remove(&tx.id);
+
+        let terms = tokenize(&document_text(&tx));
+        self.lengths.insert(tx.id, terms.len());
+        self.total_length += terms.len();
+        for term in &terms {
+            *self.postings.entry(term.clone()).or_default().entry(tx.id).or_insert(0) += 1;
+        }
+        self.terms.insert(tx.id, terms.into_iter().collect());
+        self.transactions.insert(tx.id, tx);
+    }
+
+    /// Drop a transaction from the index
+    pub fn remove(&mut self, id: &TransactionId) -> Option<Transaction> {
+        let tx = self.transactions.remove(id)?;
+        self.total_length -= self.lengths.remove(id).unwrap_or(0);
+        for term in self.terms.remove(id).unwrap_or_default() {
+            if let Some(docs) = self.postings.get_mut(&term) {
+                docs.remove(id);
+                if docs.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        Some(tx)
+    }
+
+    /// Run a query, best matches first
+    pub fn search(&self, query: &SearchQuery) -> SearchResults {
+        let terms: Vec<String> = query.text.as_deref().map(tokenize).unwrap_or_default();
+        let unique_terms: Vec<&String> = terms.iter().collect::<HashSet<_>>().into_iter().collect();
+
+        // Every term must match; with no text, every transaction is a candidate
+        let candidates: Vec<&TransactionId> = match unique_terms.first() {
+            Some(first) => match self.postings.get(*first) {
+                Some(docs) => docs
+                    .keys()
+                    .filter(|id| unique_terms.iter().all(|term| self.postings.get(*term).is_some_and(|docs| docs.contains_key(id))))
+                    .collect(),
+                None => Vec::new(),
+            },
+            None => self.transactions.keys().collect(),
+        };
+
+        let mut hits: Vec<SearchHit> = candidates
+            .into_iter()
+            .filter_map(|id| self.transactions.get(id))
+            .filter(|tx| query.matches(tx))
+            .map(|tx| SearchHit {
+                transaction: tx.clone(),
+                score: unique_terms.iter().map(|term| self.bm25(term, &tx.id)).sum(),
+                matched_terms: unique_terms.iter().map(|term| term.to_string()).collect(),
+            })
+            .collect();
+
+        // Best score first, newest first among equal scores
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.transaction.created_at.cmp(&a.transaction.created_at))
+        });
+
+        let total = hits.len();
+        let hits = hits
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(DEFAULT_LIMIT))
+            .collect();
+        SearchResults { total, hits }
+    }
+
+    fn bm25(&self, term: &str, id: &TransactionId) -> f64 {
+        let Some(docs) = self.postings.get(term) else {
+            return 0.0;
+        };
+        let tf = docs.get(id).copied().unwrap_or(0) as f64;
+        let n = self.transactions.len() as f64;
+        let df = docs.len() as f64;
+        let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+        let average_length = self.total_length as f64 / n.max(1.0);
+        let length = self.lengths.get(id).copied().unwrap_or(0) as f64;
+        let norm = 1.0 - BM25_B + BM25_B * length / average_length.max(1.0);
+
+        idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * norm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{TransactionEvaluation, TransactionRequest};
+
+    fn transaction(description: &str, service_type: ServiceType, budget_sol: f64) -> Transaction {
+        let mut request = TransactionRequest::new(
+            AgentId::new(),
+            service_type,
+            description.to_string(),
+            Balance::from_sol(budget_sol),
+            Timestamp::now(),
+        );
+        request.requirements.insert("format".to_string(), "csv".to_string());
+        Transaction::new(request)
+    }
+
+    #[test]
+    fn test_text_search_ranks_and_filters() {
+        let mut index = TransactionSearchIndex::new();
+        let sentiment = transaction("Sentiment analysis of product reviews", ServiceType::DataAnalysis, 5.0);
+        let mut market = transaction("Market research on reviews platforms", ServiceType::MarketResearch, 50.0);
+        market.evaluation = Some(TransactionEvaluation {
+            requester_rating: 5.0,
+            provider_rating: 5.0,
+            requester_feedback: "Great sentiment breakdown".to_string(),
+            provider_feedback: String::new(),
+            quality_score: 1.0,
+            timeliness_score: 1.0,
+            overall_satisfaction: 1.0,
+        });
+        index.insert(sentiment.clone());
+        index.insert(market.clone());
+        let mut render = transaction("Render video frames", ServiceType::ComputationalTask, 1.0);
+        render.request.tags.project = Some("storefront".to_string());
+        render.request.tags.labels.insert("quarterly".to_string());
+        index.insert(render);
+
+        let results = index.search(&SearchQuery::text("Sentiment REVIEWS"));
+        assert_eq!(results.total, 2);
+        assert_eq!(results.hits[0].transaction.id, sentiment.id);
+
+        let results = index.search(&SearchQuery {
+            service_type: Some(ServiceType::MarketResearch),
+            ..SearchQuery::text("sentiment")
+        });
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].transaction.id, market.id);
+
+        let results = index.search(&SearchQuery {
+            max_price: Some(Balance::from_sol(10.0)),
+            ..SearchQuery::default()
+        });
+        assert_eq!(results.total, 2);
+        assert!(index.search(&SearchQuery::text("csv missing")).hits.is_empty());
+
+        for tag in ["project=storefront", "quarterly"] {
+            let results = index.search(&SearchQuery { tag: Some(tag.to_string()), ..SearchQuery::default() });
+            assert_eq!(results.total, 1);
+        }
+        assert_eq!(index.search(&SearchQuery::text("storefront")).total, 1);
+    }
+
+    #[test]
+    fn test_reindexing_replaces_old_terms() {
+        let mut index = TransactionSearchIndex::new();
+        let mut tx = transaction("Translate documents", ServiceType::ContentCreation, 1.0);
+        index.insert(tx.clone());
+
+        tx.request.description = "Summarize documents".to_string();
+        index.insert(tx.clone());
+
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.search(&SearchQuery::text("translate")).total, 0);
+        assert_eq!(index.search(&SearchQuery::text("summarize")).total, 1);
+
+        index.remove(&tx.id);
+        assert!(index.is_empty());
+        assert_eq!(index.total_length, 0);
+        assert!(index.postings.is_empty() && index.terms.is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+//! External Signing
+//!
+//! A watch-only agent holds only its public key; settlement authority sits
+//! with a separate signer service. The agent still quotes and negotiates,
+//! and when a transaction must settle it prepares the Solana transaction
+//! unsigned and exports it as an `UnsignedTransaction`. The signer signs
+//! `message_bytes` with the agent's key and returns the signature, which
+//! `with_signature` checks and attaches, giving a transaction ready to
+//! submit. A fee sponsor's signature, if any, is already in place.
+//!
+//! A transaction built on a recent blockhash must be signed and submitted
+//! within about a minute. One that waits on an offline signer, such as a
+//! hardware wallet, is built on a `DurableNonce` instead: its first
+//! instruction advances the nonce account, and it stays valid until that
+//! happens, by this transaction or another using the same nonce.
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::nonce::state::{State as NonceState, Versions as NonceVersions};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+
+use crate::error::{CryptoError, Result, SolaceError};
+use crate::types::TransactionId;
+
+/// A transaction waiting for an external signer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub signer: String,                   // Account whose signature is missing
+    pub description: String,              // What the signer is approving
+    pub settles: Option<TransactionId>,   // Commerce transaction it settles, if any
+    pub transaction: Vec<u8>,             // bincode of the partially signed `Transaction`
+    #[serde(default)]
+    pub nonce_account: Option<String>,    // Durable nonce used in place of a recent blockhash
+}
+
+impl UnsignedTransaction {
+    /// Export `transaction` for `signer` to sign
+    pub fn new(transaction: &Transaction, signer: &Pubkey, description: impl Into<String>) -> Result<Self> {
+        signer_index(transaction, signer)?;
+        Ok(Self {
+            signer: signer.to_string(),
+            description: description.into(),
+            settles: None,
+            nonce_account: None,
+            transaction: bincode::serialize(transaction).map_err(|e| SolaceError::internal(format!("Transaction encoding failed: {}", e)))?,
+        })
+    }
+
+    /// Mark the commerce transaction this settles
+    pub fn settling(mut self, transaction_id: TransactionId) -> Self {
+        self.settles = Some(transaction_id);
+        self
+    }
+
+    /// Mark the durable nonce the transaction was built on
+    pub fn with_nonce(mut self, nonce: &DurableNonce) -> Self {
+        self.nonce_account = Some(nonce.account.to_string());
+        self
+    }
+
+    /// Whether the transaction expires with its blockhash, rather than
+    /// waiting on a durable nonce
+    pub fn expires(&self) -> bool {
+        self.nonce_account.is_none()
+    }
+
+    pub fn transaction(&self) -> Result<Transaction> {
+        bincode::deserialize(&self.transaction).map_err(|e| SolaceError::internal(format!("Transaction decoding failed: {}", e)))
+    }
+
+    /// The bytes the signer signs
+    pub fn message_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.transaction()?.message_data())
+    }
+
+    /// Attach the signer's signature, refusing one that does not verify
+    pub fn with_signature(&self, signature: Signature) -> Result<Transaction> {
+        let mut transaction = self.transaction()?;
+        let signer = self.signer.parse::<Pubkey>().map_err(|_| CryptoError::InvalidKeyFormat)?;
+        let index = signer_index(&transaction, &signer)?;
+        if !signature.verify(signer.as_ref(), &transaction.message_data()) {
+            return Err(CryptoError::SignatureVerificationFailed.into());
+        }
+        transaction.signatures[index] = signature;
+        Ok(transaction)
+    }
+}
+
+/// The current value of a durable nonce account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Pubkey,                // Must sign to advance the nonce
+    pub nonce: Hash,                      // Stands in for the recent blockhash
+}
+
+impl DurableNonce {
+    /// Read the nonce from its account's data
+    pub fn from_account_data(account: Pubkey, data: &[u8]) -> Result<Self> {
+        let versions: NonceVersions = bincode::deserialize(data)
+            .map_err(|e| SolaceError::config(format!("{} is not a nonce account: {}", account, e)))?;
+        match versions.state() {
+            NonceState::Initialized(data) => Ok(Self { account, authority: data.authority, nonce: data.blockhash() }),
+            NonceState::Uninitialized => Err(SolaceError::config(format!("Nonce account {} is not initialized", account))),
+        }
+    }
+
+    /// Message running `instructions` on this nonce, paid by `payer`
+    pub fn message(&self, instructions: &[Instruction], payer: &Pubkey) -> Message {
+        let mut message = Message::new_with_nonce(instructions.to_vec(), Some(payer), &self.account, &self.authority);
+        message.recent_blockhash = self.nonce;
+        message
+    }
+
+    /// Refuse a signer that could not advance the nonce
+    pub fn check_authority(&self, signer: &Pubkey) -> Result<()> {
+        if self.authority != *signer {
+            return Err(SolaceError::config(format!(
+                "Nonce account {} is advanced by {}, not by the signer {}",
+                self.account, self.authority, signer
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Position of `signer` among the transaction's required signers
+fn signer_index(transaction: &Transaction, signer: &Pubkey) -> Result<usize> {
+    let required = transaction.message.header.num_required_signatures as usize;
+    transaction.message.account_keys[..required.min(transaction.message.account_keys.len())]
+        .iter()
+        .position(|key| key == signer)
+        .ok_or_else(|| SolaceError::config(format!("{} is not a signer of the transaction", signer)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, message::Message, signature::Keypair, signer::Signer, system_instruction};
+
+    #[test]
+    fn test_external_signature_completes_transaction() {
+        let agent = Keypair::new();
+        let sponsor = Keypair::new();
+        let transfer = system_instruction::transfer(&agent.pubkey(), &Pubkey::new_unique(), 1_000);
+        let message = Message::new_with_blockhash(&[transfer], Some(&sponsor.pubkey()), &Hash::new_unique());
+        let mut transaction = Transaction::new_unsigned(message);
+        let blockhash = transaction.message.recent_blockhash;
+        transaction.partial_sign(&[&sponsor], blockhash);
+
+        let unsigned = UnsignedTransaction::new(&transaction, &agent.pubkey(), "settle").unwrap();
+        assert!(UnsignedTransaction::new(&transaction, &Pubkey::new_unique(), "settle").is_err());
+
+        // A signature by the wrong key is refused
+        let forged = Keypair::new().sign_message(&unsigned.message_bytes().unwrap());
+        assert!(unsigned.with_signature(forged).is_err());
+
+        let signature = agent.sign_message(&unsigned.message_bytes().unwrap());
+        let signed = unsigned.with_signature(signature).unwrap();
+        assert!(signed.verify().is_ok());
+    }
+
+    #[test]
+    fn test_durable_nonce_transactions_outlive_the_blockhash() {
+        use solana_sdk::nonce::state::{Data, DurableNonce as NonceValue};
+        use solana_sdk::system_program;
+
+        let agent = Keypair::new();
+        let account = Pubkey::new_unique();
+        let value = NonceValue::from_blockhash(&Hash::new_unique());
+        let state = NonceVersions::new(NonceState::Initialized(Data::new(agent.pubkey(), value, 5_000)));
+        let nonce = DurableNonce::from_account_data(account, &bincode::serialize(&state).unwrap()).unwrap();
+        assert_eq!(nonce.authority, agent.pubkey());
+        assert_eq!(nonce.nonce, *value.as_hash());
+
+        let empty = bincode::serialize(&NonceVersions::new(NonceState::Uninitialized)).unwrap();
+        assert!(DurableNonce::from_account_data(account, &empty).is_err());
+        assert!(nonce.check_authority(&Pubkey::new_unique()).is_err());
+
+        // The nonce is advanced first, and stands in for the blockhash
+        let transfer = system_instruction::transfer(&agent.pubkey(), &Pubkey::new_unique(), 1_000);
+        let message = nonce.message(&[transfer], &agent.pubkey());
+        assert_eq!(message.recent_blockhash, nonce.nonce);
+        let advance = &message.instructions[0];
+        assert_eq!(message.account_keys[advance.program_id_index as usize], system_program::id());
+        assert_eq!(message.account_keys[advance.accounts[0] as usize], account);
+
+        let unsigned = UnsignedTransaction::new(&Transaction::new_unsigned(message), &agent.pubkey(), "transfer")
+            .unwrap()
+            .with_nonce(&nonce);
+        assert!(!unsigned.expires());
+        let signature = agent.sign_message(&unsigned.message_bytes().unwrap());
+        assert!(unsigned.with_signature(signature).unwrap().verify().is_ok());
+    }
+}
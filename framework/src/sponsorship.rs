@@ -0,0 +1,178 @@
+//! Fee Sponsorship
+//!
+//! An operator can pay network fees for the agents it runs, so agents need
+//! lamports only for what they actually spend. Sponsored transactions name
+//! the operator's keypair (`BlockchainConfig::fee_payer_path`) as fee payer
+//! and are signed by both the operator and the agent.
+//!
+//! A `FeeSponsor` decides which agents it covers and how far: each agent has
+//! a quota of fees and transactions per window. A fee is reserved against
+//! the quota before sending and settled at the fee actually charged once the
+//! transaction lands, or released if it never did. Charged fees go to the
+//! cost accounting `SponsoredFees` ledger.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cost::SponsoredFees;
+use crate::error::ChainError;
+
+/// What a sponsor pays for one agent per window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SponsorshipQuota {
+    pub max_fees: u64,                    // Lamports of fees per window
+    pub max_transactions: u32,
+    pub window: Duration,
+}
+
+impl Default for SponsorshipQuota {
+    fn default() -> Self {
+        Self {
+            max_fees: 10_000_000,
+            max_transactions: 1_000,
+            window: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// Which agents a sponsor covers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SponsorshipPolicy {
+    pub default_quota: Option<SponsorshipQuota>,  // For agents without their own quota; `None` sponsors only listed agents
+    pub agents: HashMap<String, SponsorshipQuota>, // Agent account -> quota
+}
+
+impl SponsorshipPolicy {
+    /// Sponsor every agent under the same quota
+    pub fn open(quota: SponsorshipQuota) -> Self {
+        Self { default_quota: Some(quota), agents: HashMap::new() }
+    }
+
+    /// Give `agent` its own quota
+    pub fn with_agent(mut self, agent: impl Into<String>, quota: SponsorshipQuota) -> Self {
+        self.agents.insert(agent.into(), quota);
+        self
+    }
+
+    pub fn quota(&self, agent: &str) -> Option<SponsorshipQuota> {
+        self.agents.get(agent).copied().or(self.default_quota)
+    }
+}
+
+/// An agent's sponsored fees in its current window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SponsorshipUsage {
+    pub window_start: DateTime<Utc>,
+    pub fees: u64,                        // Charged plus reserved
+    pub transactions: u32,
+}
+
+/// Enforces a sponsorship policy and accounts for the fees it pays
+pub struct FeeSponsor {
+    policy: SponsorshipPolicy,
+    usage: Mutex<HashMap<String, SponsorshipUsage>>,
+    ledger: Mutex<SponsoredFees>,
+}
+
+impl FeeSponsor {
+    pub fn new(policy: SponsorshipPolicy) -> Self {
+        Self {
+            policy,
+            usage: Mutex::new(HashMap::new()),
+            ledger: Mutex::new(SponsoredFees::default()),
+        }
+    }
+
+    /// Whether the policy sponsors `agent` at all
+    pub fn covers(&self, agent: &str) -> bool {
+        self.policy.quota(agent).is_some()
+    }
+
+    /// Reserve `fee` for one of `agent`'s transactions
+    pub fn reserve(&self, agent: &str, fee: u64) -> std::result::Result<(), ChainError> {
+        self.reserve_at(agent, fee, Utc::now())
+    }
+
+    pub fn reserve_at(&self, agent: &str, fee: u64, now: DateTime<Utc>) -> std::result::Result<(), ChainError> {
+        let exhausted = |reason: String| ChainError::SponsorshipExhausted { agent: agent.to_string(), reason };
+        let quota = self.policy.quota(agent).ok_or_else(|| exhausted("agent is not sponsored".to_string()))?;
+
+        let mut usage = self.usage.lock().unwrap();
+        let current = usage.entry(agent.to_string()).or_insert(SponsorshipUsage { window_start: now, fees: 0, transactions: 0 });
+        let window = chrono::Duration::from_std(quota.window).unwrap_or(chrono::Duration::MAX);
+        if now.signed_duration_since(current.window_start) >= window {
+            *current = SponsorshipUsage { window_start: now, fees: 0, transactions: 0 };
+        }
+
+        if current.transactions >= quota.max_transactions {
+            return Err(exhausted(format!("{} transactions this window", current.transactions)));
+        }
+        let fees = current.fees.saturating_add(fee);
+        if fees > quota.max_fees {
+            return Err(exhausted(format!("{} of {} lamports already used, {} more needed", current.fees, quota.max_fees, fee)));
+        }
+        current.fees = fees;
+        current.transactions += 1;
+        Ok(())
+    }
+
+    /// Settle a reservation at the fee actually `charged`; `None` if the
+    /// transaction never landed and the reservation is released
+    pub fn settle(&self, agent: &str, reserved: u64, charged: Option<u64>) {
+        if let Some(current) = self.usage.lock().unwrap().get_mut(agent) {
+            current.fees = current.fees.saturating_sub(reserved).saturating_add(charged.unwrap_or(0));
+            if charged.is_none() {
+                current.transactions = current.transactions.saturating_sub(1);
+            }
+        }
+        if let Some(fee) = charged {
+            self.ledger.lock().unwrap().record(agent, fee);
+        }
+    }
+
+    pub fn usage(&self, agent: &str) -> Option<SponsorshipUsage> {
+        self.usage.lock().unwrap().get(agent).copied()
+    }
+
+    /// Fees paid so far, per agent
+    pub fn ledger(&self) -> SponsoredFees {
+        self.ledger.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotas_limit_fees_per_window() {
+        let quota = SponsorshipQuota { max_fees: 12_000, max_transactions: 2, window: Duration::from_secs(60) };
+        let sponsor = FeeSponsor::new(SponsorshipPolicy::default().with_agent("alice", quota));
+        let now = Utc::now();
+
+        assert!(!sponsor.covers("bob"));
+        assert!(matches!(sponsor.reserve_at("bob", 5_000, now), Err(ChainError::SponsorshipExhausted { .. })));
+
+        sponsor.reserve_at("alice", 5_000, now).unwrap();
+        sponsor.settle("alice", 5_000, Some(5_000));
+        // Over the fee budget
+        assert!(sponsor.reserve_at("alice", 8_000, now).is_err());
+        sponsor.reserve_at("alice", 5_000, now).unwrap();
+        // A transaction that never landed gives its reservation back
+        sponsor.settle("alice", 5_000, None);
+        sponsor.reserve_at("alice", 5_000, now).unwrap();
+        sponsor.settle("alice", 5_000, Some(4_000));
+        // Out of transactions until the window resets
+        assert!(sponsor.reserve_at("alice", 1, now).is_err());
+        sponsor.reserve_at("alice", 5_000, now + chrono::Duration::seconds(61)).unwrap();
+
+        let ledger = sponsor.ledger();
+        assert_eq!(ledger.agent_total("alice").0, 9_000);
+        assert_eq!(ledger.transactions(), 2);
+        assert_eq!(ledger.total().0, 9_000);
+    }
+}
@@ -0,0 +1,566 @@
+//! Warm Standby
+//!
+//! A critical agent can run as an active/standby pair sharing one identity.
+//! The active node's `StorageManager` records every write in a
+//! `ReplicationLog`, alongside the reliable messages entering and leaving
+//! the agent's outbox, and an `ActiveReplicator` ships the log to the
+//! standby as batches signed with the active's key. The standby's
+//! `StandbyReplica` checks each batch against that key and applies its
+//! changes in sequence to its own storage, so it holds the same
+//! transactions and unsettled messages.
+//!
+//! The active ships at least every heartbeat interval, empty batches
+//! included. Each batch carries every change the standby has not yet
+//! acknowledged, and the standby answers with the last sequence it
+//! applied, so a lost batch or acknowledgment is simply resent. A gap in
+//! the sequence therefore means the standby diverged: it reports
+//! `StandbyStatus::Diverged`, stops following and never takes over.
+//!
+//! An active that could not reach the standby for the failure timeout
+//! fences itself: an `Agent` given its replicator refuses to sign or commit
+//! from then on. The standby presumes the active failed after the same
+//! silence, counted from the last batch it applied, waits out the fencing
+//! delay so a stalled active has stopped, then takes over: it starts a new
+//! epoch, refuses batches from the old one, and hands back the open
+//! transactions and unsettled messages for `Agent::resume_takeover`.
+//!
+//! The framework does not own a network connection, so delivering batches
+//! is left to a `ReplicationChannel`.
+
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    crypto::{KeyPair, Signature},
+    error::SolaceError,
+    storage::StorageManager,
+    transaction::Transaction,
+    types::Timestamp,
+    Result,
+};
+
+/// Domain separation for replication batches
+const BATCH_CONTEXT: &[u8] = b"solace-standby-batch";
+
+/// Timing of an active/standby pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandbyConfig {
+    pub heartbeat_interval: Duration,     // The active ships at least this often
+    pub failure_timeout: Duration,        // Silence after which the active is presumed failed
+    pub fencing_delay: Duration,          // Further wait before the standby takes over
+}
+
+impl Default for StandbyConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(1),
+            failure_timeout: Duration::from_secs(5),
+            fencing_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl StandbyConfig {
+    /// Refuse timings under which the pair could both be active
+    pub fn validate(&self) -> Result<()> {
+        if self.failure_timeout <= self.heartbeat_interval {
+            return Err(SolaceError::config("Standby failure timeout must exceed the heartbeat interval"));
+        }
+        if self.fencing_delay.is_zero() {
+            return Err(SolaceError::config("Standby fencing delay must be positive"));
+        }
+        Ok(())
+    }
+
+    fn failure_timeout(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.failure_timeout).unwrap_or(chrono::Duration::MAX)
+    }
+
+    fn fencing_delay(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.fencing_delay).unwrap_or(chrono::Duration::MAX)
+    }
+}
+
+/// A change to the active node's state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StateChange {
+    Put { key: String, value: serde_json::Value },
+    Delete { key: String },
+    Outbound { id: String, message: Vec<u8> }, // Reliable message entering the outbox
+    Settled { id: String },                    // Outbox message acknowledged or given up
+}
+
+/// A change and its place in the log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplicatedChange {
+    pub sequence: u64,
+    pub change: StateChange,
+}
+
+/// Changes recorded on the active node, waiting to be shipped
+#[derive(Debug, Default)]
+pub struct ReplicationLog {
+    state: Mutex<LogState>,
+}
+
+#[derive(Debug, Default)]
+struct LogState {
+    last_sequence: u64,
+    pending: Vec<ReplicatedChange>,
+}
+
+impl ReplicationLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a change; returns its sequence number
+    pub fn record(&self, change: StateChange) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        state.last_sequence += 1;
+        let sequence = state.last_sequence;
+        state.pending.push(ReplicatedChange { sequence, change });
+        sequence
+    }
+
+    /// Number of changes the standby has not acknowledged yet
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().pending.len()
+    }
+
+    fn unacknowledged(&self) -> Vec<ReplicatedChange> {
+        self.state.lock().unwrap().pending.clone()
+    }
+
+    /// Drop changes up to `sequence`, which the standby has applied
+    fn acknowledge(&self, sequence: u64) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let before = state.pending.len();
+        state.pending.retain(|entry| entry.sequence > sequence);
+        before - state.pending.len()
+    }
+}
+
+/// Changes shipped to the standby, signed by the active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationBatch {
+    pub epoch: u64,
+    pub changes: Vec<ReplicatedChange>,   // Empty for a heartbeat
+    pub sent_at: Timestamp,
+    pub signature: Signature,
+}
+
+impl ReplicationBatch {
+    pub fn sign(keypair: &KeyPair, epoch: u64, changes: Vec<ReplicatedChange>, sent_at: Timestamp) -> Result<Self> {
+        let signature = keypair.sign(&Self::signed_bytes(epoch, &changes, sent_at)?);
+        Ok(Self { epoch, changes, sent_at, signature })
+    }
+
+    /// Check the batch was signed by `key`
+    pub fn verify(&self, key: &VerifyingKey) -> Result<()> {
+        self.signature.verify(&Self::signed_bytes(self.epoch, &self.changes, self.sent_at)?, key)
+    }
+
+    fn signed_bytes(epoch: u64, changes: &[ReplicatedChange], sent_at: Timestamp) -> Result<Vec<u8>> {
+        let mut bytes = BATCH_CONTEXT.to_vec();
+        bytes.extend_from_slice(&epoch.to_le_bytes());
+        bytes.extend_from_slice(&serde_json::to_vec(changes)?);
+        bytes.extend_from_slice(&serde_json::to_vec(&sent_at)?);
+        Ok(bytes)
+    }
+}
+
+/// Delivers replication batches to the standby
+#[async_trait::async_trait]
+pub trait ReplicationChannel: Send + Sync + std::fmt::Debug {
+    /// Deliver a batch; returns the last sequence the standby has applied
+    async fn send(&self, batch: ReplicationBatch) -> Result<u64>;
+}
+
+/// Ships the active node's log to its standby
+#[derive(Debug)]
+pub struct ActiveReplicator {
+    log: Arc<ReplicationLog>,
+    keypair: KeyPair,
+    channel: Arc<dyn ReplicationChannel>,
+    config: StandbyConfig,
+    epoch: u64,
+    last_shipped: Mutex<Timestamp>,       // Last batch the standby accepted
+}
+
+impl ActiveReplicator {
+    pub fn new(log: Arc<ReplicationLog>, keypair: KeyPair, channel: Arc<dyn ReplicationChannel>, config: StandbyConfig) -> Self {
+        Self {
+            log,
+            keypair,
+            channel,
+            config,
+            epoch: 1,
+            last_shipped: Mutex::new(Timestamp::now()),
+        }
+    }
+
+    /// Ship as the given epoch, e.g. after taking over from the former active
+    pub fn with_epoch(mut self, epoch: u64) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    /// Changes recorded on this node
+    pub fn log(&self) -> &Arc<ReplicationLog> {
+        &self.log
+    }
+
+    /// Ship unacknowledged changes, or a heartbeat if there are none;
+    /// returns how many the standby acknowledged. Changes stay in the log
+    /// until the standby acknowledges their sequence, so a lost batch or
+    /// acknowledgment is resent with the next one.
+    pub async fn ship(&self, now: Timestamp) -> Result<usize> {
+        let batch = ReplicationBatch::sign(&self.keypair, self.epoch, self.log.unacknowledged(), now)?;
+        let applied = self.channel.send(batch).await?;
+        let acknowledged = self.log.acknowledge(applied);
+        *self.last_shipped.lock().unwrap() = now;
+        Ok(acknowledged)
+    }
+
+    /// Whether the standby may be taking over because nothing reached it for
+    /// the failure timeout; a fenced active must stop transacting
+    pub fn is_fenced(&self, now: Timestamp) -> bool {
+        now.0 - self.last_shipped.lock().unwrap().0 >= self.config.failure_timeout()
+    }
+
+    /// Ship every heartbeat interval until `cancel` fires or the node fences itself
+    pub fn start(self: Arc<Self>, cancel: CancellationToken) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            while !cancel.is_cancelled() {
+                let now = Timestamp::now();
+                if let Err(e) = self.ship(now).await {
+                    tracing::warn!("Failed to ship {} changes to standby: {}", self.log.pending(), e);
+                }
+                if self.is_fenced(now) {
+                    tracing::error!("Standby unreachable for {:?}; fencing this node", self.config.failure_timeout);
+                    break;
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(self.config.heartbeat_interval) => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+        })
+    }
+}
+
+/// The standby's view of the active node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StandbyStatus {
+    Following,                            // Batches arrive in time
+    Suspect,                              // Silent past the failure timeout; waiting out the fencing delay
+    Fenced,                               // Safe to take over
+    Active,                               // Took over
+    Diverged,                             // Missed changes the active no longer holds; never takes over
+}
+
+/// What the standby resumes with after taking over
+#[derive(Debug, Clone)]
+pub struct Takeover {
+    pub epoch: u64,
+    pub open_transactions: Vec<Transaction>,
+    pub outbox: BTreeMap<String, Vec<u8>>, // Unsettled reliable messages by id, to resend
+}
+
+/// Applies the active node's changes and takes over when it fails
+pub struct StandbyReplica {
+    storage: Arc<StorageManager>,
+    active_key: VerifyingKey,
+    config: StandbyConfig,
+    state: tokio::sync::Mutex<ReplicaState>,
+}
+
+#[derive(Debug)]
+struct ReplicaState {
+    epoch: u64,
+    applied: u64,                         // Last sequence applied
+    last_heard: Timestamp,                // Last batch applied in full
+    outbox: BTreeMap<String, Vec<u8>>,
+    active: bool,
+    diverged: Option<u64>,                // First change missing from the sequence
+}
+
+impl StandbyReplica {
+    pub fn new(storage: Arc<StorageManager>, active_key: VerifyingKey, config: StandbyConfig) -> Self {
+        Self {
+            storage,
+            active_key,
+            config,
+            state: tokio::sync::Mutex::new(ReplicaState {
+                epoch: 1,
+                applied: 0,
+                last_heard: Timestamp::now(),
+                outbox: BTreeMap::new(),
+                active: false,
+                diverged: None,
+            }),
+        }
+    }
+
+    /// Apply a batch received at `now`; returns how many changes were new
+    ///
+    /// Batches must be signed by the active and come from the current
+    /// epoch. Changes already applied are skipped. The active keeps every
+    /// change until it is acknowledged, so a gap in the sequence means this
+    /// standby diverged: it stops following, and its silence timer is not
+    /// refreshed by batches it could not apply.
+    pub async fn apply(&self, batch: &ReplicationBatch, now: Timestamp) -> Result<usize> {
+        batch.verify(&self.active_key)?;
+        let mut state = self.state.lock().await;
+        if state.active {
+            return Err(SolaceError::config(format!("Standby took over at epoch {}; refusing batch from epoch {}", state.epoch, batch.epoch)));
+        }
+        if batch.epoch < state.epoch {
+            return Err(SolaceError::config(format!("Stale replication batch from epoch {}, now {}", batch.epoch, state.epoch)));
+        }
+        if let Some(missing) = state.diverged {
+            return Err(SolaceError::internal(format!("Standby diverged at change {}; resync it from the active", missing)));
+        }
+
+        let mut applied = 0;
+        for entry in &batch.changes {
+            if entry.sequence <= state.applied {
+                continue;
+            }
+            if entry.sequence != state.applied + 1 {
+                let missing = state.applied + 1;
+                state.diverged = Some(missing);
+                tracing::error!("Standby diverged: expected change {}, got {}", missing, entry.sequence);
+                return Err(SolaceError::internal(format!(
+                    "Replication gap: expected change {}, got {}",
+                    missing,
+                    entry.sequence
+                )));
+            }
+            match &entry.change {
+                StateChange::Outbound { id, message } => {
+                    state.outbox.insert(id.clone(), message.clone());
+                }
+                StateChange::Settled { id } => {
+                    state.outbox.remove(id);
+                }
+                change => self.storage.apply_replicated(change).await.map_err(|e| SolaceError::internal(e.to_string()))?,
+            }
+            state.applied = entry.sequence;
+            applied += 1;
+        }
+        state.epoch = batch.epoch;
+        state.last_heard = now;
+        Ok(applied)
+    }
+
+    /// Last change applied, which acknowledges every change up to it
+    pub async fn applied(&self) -> u64 {
+        self.state.lock().await.applied
+    }
+
+    /// How the active looks at `now`
+    pub async fn status(&self, now: Timestamp) -> StandbyStatus {
+        let state = self.state.lock().await;
+        if state.active {
+            return StandbyStatus::Active;
+        }
+        if state.diverged.is_some() {
+            return StandbyStatus::Diverged;
+        }
+        let silence = now.0 - state.last_heard.0;
+        if silence < self.config.failure_timeout() {
+            StandbyStatus::Following
+        } else if silence < self.config.failure_timeout() + self.config.fencing_delay() {
+            StandbyStatus::Suspect
+        } else {
+            StandbyStatus::Fenced
+        }
+    }
+
+    /// Take over the agent's identity once the former active is fenced,
+    /// returning what it left open
+    pub async fn take_over(&self, now: Timestamp) -> Result<Takeover> {
+        let status = self.status(now).await;
+        if status != StandbyStatus::Fenced {
+            return Err(SolaceError::config(format!("Cannot take over while the active is {:?}", status)));
+        }
+        let transactions: Vec<Transaction> = self
+            .storage
+            .list_transactions()
+            .await
+            .map_err(|e| SolaceError::internal(e.to_string()))?;
+
+        let mut state = self.state.lock().await;
+        state.active = true;
+        state.epoch += 1;
+        tracing::info!("Standby taking over at epoch {} after change {}", state.epoch, state.applied);
+        Ok(Takeover {
+            epoch: state.epoch,
+            open_transactions: transactions.into_iter().filter(|tx| !tx.is_terminal()).collect(),
+            outbox: state.outbox.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{TransactionRequest, TransactionStatus};
+    use crate::types::{AgentId, Balance, ServiceType};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Hands batches straight to the standby
+    struct Loopback {
+        replica: Arc<StandbyReplica>,
+    }
+
+    impl std::fmt::Debug for Loopback {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("Loopback")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ReplicationChannel for Loopback {
+        async fn send(&self, batch: ReplicationBatch) -> Result<u64> {
+            self.replica.apply(&batch, batch.sent_at).await?;
+            Ok(self.replica.applied().await)
+        }
+    }
+
+    /// Applies batches but loses the acknowledgment while `drop_acks` is set
+    struct LossyAcks {
+        replica: Arc<StandbyReplica>,
+        drop_acks: AtomicBool,
+    }
+
+    impl std::fmt::Debug for LossyAcks {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("LossyAcks")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ReplicationChannel for LossyAcks {
+        async fn send(&self, batch: ReplicationBatch) -> Result<u64> {
+            self.replica.apply(&batch, batch.sent_at).await?;
+            if self.drop_acks.load(Ordering::SeqCst) {
+                return Err(SolaceError::internal("acknowledgment lost"));
+            }
+            Ok(self.replica.applied().await)
+        }
+    }
+
+    fn transaction(status: TransactionStatus) -> Transaction {
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let request = TransactionRequest::new(AgentId::new(), ServiceType::DataAnalysis, "Analysis".to_string(), Balance::from_sol(1.0), deadline);
+        let mut transaction = Transaction::new(request);
+        transaction.status = status;
+        transaction
+    }
+
+    fn after(start: Timestamp, secs: i64) -> Timestamp {
+        Timestamp(start.0 + chrono::Duration::seconds(secs))
+    }
+
+    #[tokio::test]
+    async fn test_standby_replicates_and_takes_over_after_fencing() {
+        let config = StandbyConfig::default();
+        let keypair = KeyPair::generate().unwrap();
+        let log = Arc::new(ReplicationLog::new());
+        let active_storage = StorageManager::memory().with_replication(log.clone());
+        let standby_storage = Arc::new(StorageManager::memory());
+        let replica = Arc::new(StandbyReplica::new(standby_storage.clone(), *keypair.verifying_key(), config.clone()));
+        let channel = Arc::new(Loopback { replica: replica.clone() });
+        let active = ActiveReplicator::new(log.clone(), keypair, channel, config);
+
+        let open = transaction(TransactionStatus::InProgress);
+        let done = transaction(TransactionStatus::Completed);
+        let dropped = transaction(TransactionStatus::Pending);
+        for tx in [&open, &done, &dropped] {
+            active_storage.store_transaction(&tx.id, tx).await.unwrap();
+        }
+        active_storage.delete_transaction(&dropped.id).await.unwrap();
+        log.record(StateChange::Outbound { id: "m1".to_string(), message: b"settled".to_vec() });
+        log.record(StateChange::Outbound { id: "m2".to_string(), message: b"pending".to_vec() });
+        log.record(StateChange::Settled { id: "m1".to_string() });
+
+        let start = Timestamp::now();
+        assert_eq!(active.ship(start).await.unwrap(), 7);
+        assert_eq!(log.pending(), 0);
+        assert_eq!(standby_storage.list_transaction_ids().await.unwrap().len(), 2);
+
+        // Batches not signed by the active are refused
+        let forged = ReplicationBatch::sign(&KeyPair::generate().unwrap(), 1, Vec::new(), after(start, 1)).unwrap();
+        assert!(replica.apply(&forged, after(start, 1)).await.is_err());
+
+        // The active falls silent: it fences itself, and the standby waits out the fencing delay
+        assert_eq!(replica.status(after(start, 3)).await, StandbyStatus::Following);
+        assert!(active.is_fenced(after(start, 6)));
+        assert_eq!(replica.status(after(start, 6)).await, StandbyStatus::Suspect);
+        assert!(replica.take_over(after(start, 6)).await.is_err());
+
+        let takeover = replica.take_over(after(start, 16)).await.unwrap();
+        assert_eq!(takeover.epoch, 2);
+        assert_eq!(takeover.open_transactions.len(), 1);
+        assert_eq!(takeover.open_transactions[0].id, open.id);
+        assert_eq!(takeover.outbox, BTreeMap::from([("m2".to_string(), b"pending".to_vec())]));
+
+        // A former active coming back cannot overwrite the new one
+        active_storage.store_transaction(&dropped.id, &dropped).await.unwrap();
+        assert!(active.ship(after(start, 17)).await.is_err());
+        assert_eq!(log.pending(), 1);
+        assert_eq!(replica.status(after(start, 17)).await, StandbyStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn test_changes_stay_logged_until_acknowledged() {
+        let config = StandbyConfig::default();
+        let keypair = KeyPair::generate().unwrap();
+        let log = Arc::new(ReplicationLog::new());
+        let replica = Arc::new(StandbyReplica::new(Arc::new(StorageManager::memory()), *keypair.verifying_key(), config.clone()));
+        let channel = Arc::new(LossyAcks { replica: replica.clone(), drop_acks: AtomicBool::new(true) });
+        let active = ActiveReplicator::new(log.clone(), keypair, channel.clone(), config);
+
+        log.record(StateChange::Outbound { id: "m1".to_string(), message: b"hello".to_vec() });
+        let start = Timestamp::now();
+        assert!(active.ship(start).await.is_err());
+        assert_eq!(log.pending(), 1);
+        assert_eq!(replica.applied().await, 1);
+
+        // The change is resent with the next batch, skipped, and acknowledged
+        channel.drop_acks.store(false, Ordering::SeqCst);
+        log.record(StateChange::Settled { id: "m1".to_string() });
+        assert_eq!(active.ship(after(start, 1)).await.unwrap(), 2);
+        assert_eq!(log.pending(), 0);
+        assert_eq!(replica.applied().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_gap_leaves_the_standby_diverged() {
+        let keypair = KeyPair::generate().unwrap();
+        let replica = StandbyReplica::new(Arc::new(StorageManager::memory()), *keypair.verifying_key(), StandbyConfig::default());
+        let change = |sequence| ReplicatedChange { sequence, change: StateChange::Settled { id: "m1".to_string() } };
+        let start = Timestamp::now();
+
+        let batch = ReplicationBatch::sign(&keypair, 1, vec![change(1)], start).unwrap();
+        assert_eq!(replica.apply(&batch, start).await.unwrap(), 1);
+
+        // Change 2 never arrives
+        let gap = ReplicationBatch::sign(&keypair, 1, vec![change(3)], after(start, 1)).unwrap();
+        assert!(replica.apply(&gap, after(start, 1)).await.is_err());
+        assert_eq!(replica.applied().await, 1);
+        assert_eq!(replica.status(after(start, 1)).await, StandbyStatus::Diverged);
+
+        // Heartbeats no longer keep it following, and it never takes over
+        let heartbeat = ReplicationBatch::sign(&keypair, 1, Vec::new(), after(start, 2)).unwrap();
+        assert!(replica.apply(&heartbeat, after(start, 2)).await.is_err());
+        assert!(replica.take_over(after(start, 60)).await.is_err());
+    }
+}
@@ -3,8 +3,11 @@
 //! Provides persistent storage capabilities for agent data, transactions,
 //! reputation scores, and blockchain state. Supports multiple storage backends
 //! including RocksDB for high-performance local storage.
+//!
+//! A `StorageManager` given a `ReplicationLog` records each write it makes,
+//! for a warm standby to apply.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
@@ -12,7 +15,9 @@ use anyhow::Result;
 use tokio::sync::RwLock;
 use tracing::{info, warn, debug, error};
 
-use crate::{AgentId, TransactionId, error::SolaceError};
+use crate::{AgentId, Timestamp, TransactionId, error::SolaceError};
+use solace_common::stats::ShardedCounter;
+use crate::standby::{ReplicationLog, StateChange};
 
 /// Storage configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,24 +124,51 @@ pub struct StorageStats {
     pub delete_ops: u64,
 }
 
+/// Point-in-time copy of a node's stored state, for comparing nodes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageSnapshot {
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    pub agents: BTreeMap<String, serde_json::Value>,
+    pub transactions: BTreeMap<String, serde_json::Value>,
+    pub reputations: BTreeMap<String, f64>,
+    pub consensus: BTreeMap<String, serde_json::Value>,  // Blocks and consensus state, by storage key
+}
+
+/// Live storage counters, aggregated into `StorageStats` on read
+#[derive(Debug, Default)]
+struct StorageCounters {
+    total_keys: ShardedCounter,
+    total_size_bytes: ShardedCounter,
+    read_ops: ShardedCounter,
+    write_ops: ShardedCounter,
+    delete_ops: ShardedCounter,
+}
+
+impl StorageCounters {
+    /// Take a snapshot of the current counter values
+    fn snapshot(&self, cache_hit_rate: f64) -> StorageStats {
+        StorageStats {
+            total_keys: self.total_keys.get() as usize,
+            total_size_bytes: self.total_size_bytes.get(),
+            cache_hit_rate,
+            read_ops: self.read_ops.get(),
+            write_ops: self.write_ops.get(),
+            delete_ops: self.delete_ops.get(),
+        }
+    }
+}
+
 /// In-memory storage implementation for testing
 pub struct MemoryStorage {
     data: Arc<RwLock<HashMap<Vec<u8>, Vec<u8>>>>,
-    stats: Arc<RwLock<StorageStats>>,
+    stats: Arc<StorageCounters>,
 }
 
 impl MemoryStorage {
     pub fn new() -> Self {
         Self {
             data: Arc::new(RwLock::new(HashMap::new())),
-            stats: Arc::new(RwLock::new(StorageStats {
-                total_keys: 0,
-                total_size_bytes: 0,
-                cache_hit_rate: 1.0,
-                read_ops: 0,
-                write_ops: 0,
-                delete_ops: 0,
-            })),
+            stats: Arc::new(StorageCounters::default()),
         }
     }
 }
@@ -154,15 +186,16 @@ impl Storage for MemoryStorage {
         let mut data = self.data.write().await;
         let is_new_key = !data.contains_key(&key_bytes);
         
-        data.insert(key_bytes, serialized.clone());
+        let size = serialized.len() as u64;
+        data.insert(key_bytes, serialized);
+        drop(data);
         
         // Update stats
-        let mut stats = self.stats.write().await;
         if is_new_key {
-            stats.total_keys += 1;
+            self.stats.total_keys.increment();
         }
-        stats.total_size_bytes += serialized.len() as u64;
-        stats.write_ops += 1;
+        self.stats.total_size_bytes.add(size);
+        self.stats.write_ops.increment();
         
         debug!("Stored value for key: {:?}", key);
         Ok(())
@@ -176,8 +209,7 @@ impl Storage for MemoryStorage {
         let data = self.data.read().await;
         
         // Update stats
-        let mut stats = self.stats.write().await;
-        stats.read_ops += 1;
+        self.stats.read_ops.increment();
         
         if let Some(value_bytes) = data.get(&key_bytes) {
             let value = serde_json::from_slice(value_bytes)
@@ -195,10 +227,9 @@ impl Storage for MemoryStorage {
         
         if let Some(removed_value) = data.remove(&key_bytes) {
             // Update stats
-            let mut stats = self.stats.write().await;
-            stats.total_keys -= 1;
-            stats.total_size_bytes -= removed_value.len() as u64;
-            stats.delete_ops += 1;
+            self.stats.total_keys.sub(1);
+            self.stats.total_size_bytes.sub(removed_value.len() as u64);
+            self.stats.delete_ops.increment();
             
             debug!("Deleted key: {:?}", key);
         }
@@ -242,8 +273,7 @@ impl Storage for MemoryStorage {
     }
 
     async fn get_stats(&self) -> Result<StorageStats> {
-        let stats = self.stats.read().await;
-        Ok(stats.clone())
+        Ok(self.stats.snapshot(1.0))
     }
 
     async fn compact(&self) -> Result<()> {
@@ -277,7 +307,7 @@ impl MemoryStorage {
 #[cfg(feature = "storage")]
 pub struct RocksDbStorage {
     db: Arc<rocksdb::DB>,
-    stats: Arc<RwLock<StorageStats>>,
+    stats: Arc<StorageCounters>,
 }
 
 #[cfg(feature = "storage")]
@@ -305,14 +335,19 @@ impl RocksDbStorage {
 
         Ok(Self {
             db: Arc::new(db),
-            stats: Arc::new(RwLock::new(StorageStats {
-                total_keys: 0,
-                total_size_bytes: 0,
-                cache_hit_rate: 0.95,
-                read_ops: 0,
-                write_ops: 0,
-                delete_ops: 0,
-            })),
+            stats: Arc::new(StorageCounters::default()),
+        })
+    }
+
+    /// Open an existing database without taking its lock, so a running
+    /// node's data can be read; writes after opening are not seen
+    pub fn open_read_only(config: &StorageConfig) -> Result<Self> {
+        let db_path = config.data_dir.join("rocksdb");
+        let db = rocksdb::DB::open_for_read_only(&rocksdb::Options::default(), db_path, false)?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            stats: Arc::new(StorageCounters::default()),
         })
     }
 }
@@ -333,12 +368,11 @@ impl Storage for RocksDbStorage {
         self.db.put(&key_bytes, &serialized)?;
         
         // Update stats
-        let mut stats = self.stats.write().await;
         if is_new_key {
-            stats.total_keys += 1;
+            self.stats.total_keys.increment();
         }
-        stats.total_size_bytes += serialized.len() as u64;
-        stats.write_ops += 1;
+        self.stats.total_size_bytes.add(serialized.len() as u64);
+        self.stats.write_ops.increment();
         
         debug!("Stored value for key: {:?}", key);
         Ok(())
@@ -351,8 +385,7 @@ impl Storage for RocksDbStorage {
         let key_bytes = key.as_bytes();
         
         // Update stats
-        let mut stats = self.stats.write().await;
-        stats.read_ops += 1;
+        self.stats.read_ops.increment();
         
         match self.db.get(&key_bytes)? {
             Some(value_bytes) => {
@@ -372,9 +405,8 @@ impl Storage for RocksDbStorage {
             self.db.delete(&key_bytes)?;
             
             // Update stats
-            let mut stats = self.stats.write().await;
-            stats.total_keys -= 1;
-            stats.delete_ops += 1;
+            self.stats.total_keys.sub(1);
+            self.stats.delete_ops.increment();
             
             debug!("Deleted key: {:?}", key);
         }
@@ -421,15 +453,13 @@ impl Storage for RocksDbStorage {
         self.db.write(batch)?;
         
         // Update stats
-        let mut stats = self.stats.write().await;
-        stats.write_ops += 1;
+        self.stats.write_ops.increment();
         
         Ok(())
     }
 
     async fn get_stats(&self) -> Result<StorageStats> {
-        let stats = self.stats.read().await;
-        Ok(stats.clone())
+        Ok(self.stats.snapshot(0.95))
     }
 
     async fn compact(&self) -> Result<()> {
@@ -442,11 +472,18 @@ impl Storage for RocksDbStorage {
 /// Storage manager that provides high-level operations
 pub struct StorageManager {
     storage: Box<dyn Storage>,
+    replication: Option<Arc<ReplicationLog>>, // Writes are recorded here for a warm standby
 }
 
 impl StorageManager {
     pub fn new(storage: Box<dyn Storage>) -> Self {
-        Self { storage }
+        Self { storage, replication: None }
+    }
+
+    /// Record every write in `log`, to be shipped to a warm standby
+    pub fn with_replication(mut self, log: Arc<ReplicationLog>) -> Self {
+        self.replication = Some(log);
+        self
     }
 
     /// Create a new in-memory storage manager
@@ -461,12 +498,19 @@ impl StorageManager {
         Ok(Self::new(Box::new(storage)))
     }
 
+    /// Open a RocksDB data directory read-only, even while a node uses it
+    #[cfg(feature = "storage")]
+    pub fn rocksdb_read_only(config: &StorageConfig) -> Result<Self> {
+        let storage = RocksDbStorage::open_read_only(config)?;
+        Ok(Self::new(Box::new(storage)))
+    }
+
     /// Store agent data
     pub async fn store_agent<T>(&self, agent_id: &AgentId, data: &T) -> Result<()>
     where
         T: Serialize + Send + Sync,
     {
-        self.storage.put(StorageKey::Agent(agent_id.clone()), data).await
+        self.put(StorageKey::Agent(agent_id.clone()), data).await
     }
 
     /// Retrieve agent data
@@ -482,7 +526,7 @@ impl StorageManager {
     where
         T: Serialize + Send + Sync,
     {
-        self.storage.put(StorageKey::Transaction(tx_id.clone()), data).await
+        self.put(StorageKey::Transaction(tx_id.clone()), data).await
     }
 
     /// Retrieve transaction data
@@ -493,9 +537,84 @@ impl StorageManager {
         self.storage.get(&StorageKey::Transaction(tx_id.clone())).await
     }
 
+    /// Remove transaction data
+    pub async fn delete_transaction(&self, tx_id: &TransactionId) -> Result<()> {
+        self.delete(&StorageKey::Transaction(tx_id.clone())).await
+    }
+
+    /// Store the offer book for a request
+    pub async fn store_offer_book<T>(&self, request_id: &TransactionId, book: &T) -> Result<()>
+    where
+        T: Serialize + Send + Sync,
+    {
+        self.put(Self::offer_book_key(request_id), book).await
+    }
+
+    /// Retrieve the offer book for a request
+    pub async fn get_offer_book<T>(&self, request_id: &TransactionId) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Send + Sync,
+    {
+        self.storage.get(&Self::offer_book_key(request_id)).await
+    }
+
+    /// Remove the offer book for a request
+    pub async fn delete_offer_book(&self, request_id: &TransactionId) -> Result<()> {
+        self.delete(&Self::offer_book_key(request_id)).await
+    }
+
+    fn offer_book_key(request_id: &TransactionId) -> StorageKey {
+        StorageKey::Custom(format!("offers:{}", request_id))
+    }
+
+    /// Record a transaction transition before it is applied
+    pub async fn store_pending_transition<T>(&self, tx_id: &TransactionId, record: &T) -> Result<()>
+    where
+        T: Serialize + Send + Sync,
+    {
+        self.put(Self::transition_key(tx_id), record).await
+    }
+
+    /// Clear a transaction's pending transition once it is stored
+    pub async fn delete_pending_transition(&self, tx_id: &TransactionId) -> Result<()> {
+        self.delete(&Self::transition_key(tx_id)).await
+    }
+
+    /// Load every transition recorded but not yet cleared
+    pub async fn list_pending_transitions<T>(&self) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Send + Sync,
+    {
+        let mut records = Vec::new();
+        for key in self.storage.list_keys("custom:transition:").await? {
+            if let Some(record) = self.storage.get(&key).await? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    fn transition_key(tx_id: &TransactionId) -> StorageKey {
+        StorageKey::Custom(format!("transition:{}", tx_id))
+    }
+
+    /// Store when a scheduled job runs next
+    pub async fn store_next_run(&self, job: &str, next_run: Timestamp) -> Result<()> {
+        self.put(Self::schedule_key(job), &next_run).await
+    }
+
+    /// When a scheduled job was planned to run next
+    pub async fn get_next_run(&self, job: &str) -> Result<Option<Timestamp>> {
+        self.storage.get(&Self::schedule_key(job)).await
+    }
+
+    fn schedule_key(job: &str) -> StorageKey {
+        StorageKey::State(format!("schedule:{}", job))
+    }
+
     /// Store reputation data
     pub async fn store_reputation(&self, agent_id: &AgentId, reputation: f64) -> Result<()> {
-        self.storage.put(StorageKey::Reputation(agent_id.clone()), &reputation).await
+        self.put(StorageKey::Reputation(agent_id.clone()), &reputation).await
     }
 
     /// Get reputation data
@@ -503,6 +622,27 @@ impl StorageManager {
         self.storage.get(&StorageKey::Reputation(agent_id.clone())).await
     }
 
+    /// Move an agent's records from ID `from` to ID `to`, keeping any
+    /// already stored under `to`. Returns whether anything moved.
+    pub async fn migrate_agent_id(&self, from: &AgentId, to: &AgentId) -> Result<bool> {
+        let mut moved = false;
+        let keys = [
+            (StorageKey::Agent(*from), StorageKey::Agent(*to)),
+            (StorageKey::Reputation(*from), StorageKey::Reputation(*to)),
+        ];
+        for (old, new) in keys {
+            let Some(record) = self.storage.get::<serde_json::Value>(&old).await? else {
+                continue;
+            };
+            if !self.storage.exists(&new).await? {
+                self.put(new, &record).await?;
+                moved = true;
+            }
+            self.delete(&old).await?;
+        }
+        Ok(moved)
+    }
+
     /// List all stored agents
     pub async fn list_agents(&self) -> Result<Vec<AgentId>> {
         let keys = self.storage.list_keys("agent:").await?;
@@ -515,6 +655,113 @@ impl StorageManager {
         }).collect())
     }
 
+    /// List all stored transaction IDs
+    pub async fn list_transaction_ids(&self) -> Result<Vec<TransactionId>> {
+        let keys = self.storage.list_keys("tx:").await?;
+        Ok(keys.into_iter().filter_map(|key| {
+            if let StorageKey::Transaction(tx_id) = key {
+                Some(tx_id)
+            } else {
+                None
+            }
+        }).collect())
+    }
+
+    /// Load every stored transaction
+    pub async fn list_transactions<T>(&self) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Send + Sync,
+    {
+        let mut transactions = Vec::new();
+        for tx_id in self.list_transaction_ids().await? {
+            if let Some(tx) = self.get_transaction(&tx_id).await? {
+                transactions.push(tx);
+            }
+        }
+        Ok(transactions)
+    }
+
+    /// Copy out every agent, transaction, reputation, and consensus record
+    pub async fn snapshot(&self) -> Result<StorageSnapshot> {
+        let mut snapshot = StorageSnapshot {
+            taken_at: chrono::Utc::now(),
+            agents: BTreeMap::new(),
+            transactions: BTreeMap::new(),
+            reputations: BTreeMap::new(),
+            consensus: BTreeMap::new(),
+        };
+
+        for prefix in ["agent:", "tx:", "rep:", "block:", "state:"] {
+            for key in self.storage.list_keys(prefix).await? {
+                match &key {
+                    StorageKey::Agent(id) => {
+                        if let Some(value) = self.storage.get(&key).await? {
+                            snapshot.agents.insert(id.to_string(), value);
+                        }
+                    }
+                    StorageKey::Transaction(id) => {
+                        if let Some(value) = self.storage.get(&key).await? {
+                            snapshot.transactions.insert(id.to_string(), value);
+                        }
+                    }
+                    StorageKey::Reputation(id) => {
+                        if let Some(score) = self.storage.get(&key).await? {
+                            snapshot.reputations.insert(id.to_string(), score);
+                        }
+                    }
+                    StorageKey::Block(_) | StorageKey::State(_) => {
+                        if let Some(value) = self.storage.get(&key).await? {
+                            let name = String::from_utf8_lossy(&key.as_bytes()).into_owned();
+                            snapshot.consensus.insert(name, value);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Write through to the backend, recording the change for a standby
+    async fn put<T>(&self, key: StorageKey, value: &T) -> Result<()>
+    where
+        T: Serialize + Send + Sync,
+    {
+        let Some(log) = &self.replication else {
+            return self.storage.put(key, value).await;
+        };
+        let change = StateChange::Put { key: Self::key_name(&key), value: serde_json::to_value(value)? };
+        self.storage.put(key, value).await?;
+        log.record(change);
+        Ok(())
+    }
+
+    /// Delete from the backend, recording the change for a standby
+    async fn delete(&self, key: &StorageKey) -> Result<()> {
+        self.storage.delete(key).await?;
+        if let Some(log) = &self.replication {
+            log.record(StateChange::Delete { key: Self::key_name(key) });
+        }
+        Ok(())
+    }
+
+    /// Apply a change replicated from the active node, without recording it again
+    pub async fn apply_replicated(&self, change: &StateChange) -> Result<()> {
+        match change {
+            StateChange::Put { key, value } => self.storage.put(Self::parse_key(key)?, value).await,
+            StateChange::Delete { key } => self.storage.delete(&Self::parse_key(key)?).await,
+            StateChange::Outbound { .. } | StateChange::Settled { .. } => Ok(()),
+        }
+    }
+
+    fn key_name(key: &StorageKey) -> String {
+        String::from_utf8_lossy(&key.as_bytes()).into_owned()
+    }
+
+    fn parse_key(name: &str) -> Result<StorageKey> {
+        MemoryStorage::parse_storage_key(name).ok_or_else(|| anyhow::anyhow!("Unknown storage key {}", name))
+    }
+
     /// Get storage statistics
     pub async fn get_stats(&self) -> Result<StorageStats> {
         self.storage.get_stats().await
@@ -554,6 +801,23 @@ mod tests {
         assert!(!storage.exists(&key).await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_memory_storage_stats() {
+        let storage = MemoryStorage::new();
+        let key = StorageKey::State("counter".to_string());
+
+        storage.put(key.clone(), &1u64).await.unwrap();
+        let _: Option<u64> = storage.get(&key).await.unwrap();
+        storage.delete(&key).await.unwrap();
+
+        let stats = storage.get_stats().await.unwrap();
+        assert_eq!(stats.total_keys, 0);
+        assert_eq!(stats.total_size_bytes, 0);
+        assert_eq!(stats.write_ops, 1);
+        assert_eq!(stats.read_ops, 1);
+        assert_eq!(stats.delete_ops, 1);
+    }
+
     #[tokio::test]
     async fn test_storage_manager() {
         let manager = StorageManager::memory();
@@ -566,6 +830,22 @@ mod tests {
         assert_eq!(retrieved, Some(reputation));
     }
 
+    #[tokio::test]
+    async fn test_snapshot_groups_records_by_kind() {
+        let storage = MemoryStorage::new();
+        let agent_id = AgentId::new();
+        storage.put(StorageKey::Agent(agent_id.clone()), &"agent".to_string()).await.unwrap();
+        storage.put(StorageKey::Reputation(agent_id.clone()), &0.7).await.unwrap();
+        storage.put(StorageKey::Block(3), &serde_json::json!({ "height": 3 })).await.unwrap();
+        storage.put(StorageKey::Peer("ignored".to_string()), &1u8).await.unwrap();
+
+        let snapshot = StorageManager::new(Box::new(storage)).snapshot().await.unwrap();
+        assert_eq!(snapshot.agents.len(), 1);
+        assert_eq!(snapshot.reputations.get(&agent_id.to_string()), Some(&0.7));
+        assert_eq!(snapshot.consensus.get("block:3"), Some(&serde_json::json!({ "height": 3 })));
+        assert!(snapshot.transactions.is_empty());
+    }
+
     #[test]
     fn test_storage_key_serialization() {
         let agent_id = AgentId::new();
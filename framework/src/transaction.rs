@@ -3,10 +3,11 @@
 use crate::{
     crypto::Signature,
     error::{Result, TransactionError},
-    types::{AgentId, Balance, ServiceType, Timestamp, TransactionId},
+    governance::ProtocolParams,
+    types::{AgentId, Balance, Payment, PaymentAsset, ServiceType, Timestamp, TransactionId},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 /// Transaction phases in the commerce lifecycle
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -28,6 +29,90 @@ pub enum TransactionStatus {
     Expired,
 }
 
+/// Requester's bookkeeping tags, carried through to receipts and statements
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionTags {
+    pub project: Option<String>,
+    pub cost_center: Option<String>,
+    pub environment: Option<String>,
+    pub labels: BTreeSet<String>,         // Free-form
+}
+
+impl TransactionTags {
+    pub fn is_empty(&self) -> bool {
+        self.project.is_none() && self.cost_center.is_none() && self.environment.is_none() && self.labels.is_empty()
+    }
+
+    /// Whether the tags include `tag`: `project=x`, `cost_center=x` or
+    /// `environment=x` for a structured tag, anything else for a label
+    pub fn matches(&self, tag: &str) -> bool {
+        match tag.split_once('=') {
+            Some((dimension, value)) => match TagDimension::parse(dimension) {
+                Some(dimension) => dimension.values(self).iter().any(|v| v == value),
+                None => self.labels.contains(tag),
+            },
+            None => self.labels.contains(tag),
+        }
+    }
+
+    /// Tags as `key=value` entries, then labels
+    pub fn entries(&self) -> Vec<String> {
+        let structured = [
+            (TagDimension::Project, &self.project),
+            (TagDimension::CostCenter, &self.cost_center),
+            (TagDimension::Environment, &self.environment),
+        ];
+        structured
+            .into_iter()
+            .filter_map(|(dimension, value)| value.as_ref().map(|value| format!("{}={}", dimension.key(), value)))
+            .chain(self.labels.iter().cloned())
+            .collect()
+    }
+}
+
+/// What transactions are grouped by in reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TagDimension {
+    Project,
+    CostCenter,
+    Environment,
+    Label,
+}
+
+impl TagDimension {
+    pub fn key(&self) -> &'static str {
+        match self {
+            TagDimension::Project => "project",
+            TagDimension::CostCenter => "cost_center",
+            TagDimension::Environment => "environment",
+            TagDimension::Label => "tag",
+        }
+    }
+
+    /// Dimension by name; separators are ignored, so `cost-center` works
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace(['-', '_'], "").as_str() {
+            "project" => Some(TagDimension::Project),
+            "costcenter" => Some(TagDimension::CostCenter),
+            "environment" | "env" => Some(TagDimension::Environment),
+            "tag" | "label" => Some(TagDimension::Label),
+            _ => None,
+        }
+    }
+
+    /// The tags' values in this dimension; a transaction with several
+    /// labels counts under each
+    pub fn values(&self, tags: &TransactionTags) -> Vec<String> {
+        match self {
+            TagDimension::Project => tags.project.iter().cloned().collect(),
+            TagDimension::CostCenter => tags.cost_center.iter().cloned().collect(),
+            TagDimension::Environment => tags.environment.iter().cloned().collect(),
+            TagDimension::Label => tags.labels.iter().cloned().collect(),
+        }
+    }
+}
+
 /// Transaction request from an agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionRequest {
@@ -35,9 +120,13 @@ pub struct TransactionRequest {
     pub requester: AgentId,
     pub service_type: ServiceType,
     pub description: String,
-    pub budget: Balance,
+    pub budget: Balance,                  // In base units of `asset`, as are proposed prices
+    #[serde(default)]
+    pub asset: PaymentAsset,
     pub deadline: Timestamp,
     pub requirements: HashMap<String, String>,
+    #[serde(default)]
+    pub tags: TransactionTags,
     pub created_at: Timestamp,
 }
 
@@ -55,15 +144,107 @@ impl TransactionRequest {
             service_type,
             description,
             budget,
+            asset: PaymentAsset::Sol,
             deadline,
             requirements: HashMap::new(),
+            tags: TransactionTags::default(),
             created_at: Timestamp::now(),
         }
     }
 
+    pub fn with_tags(mut self, tags: TransactionTags) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Budget the request in `payment`'s asset
+    pub fn with_payment(mut self, payment: Payment) -> Self {
+        self.asset = payment.asset;
+        self.budget = payment.as_balance();
+        self
+    }
+
+    /// The budget as a payment in the request's asset
+    pub fn max_payment(&self) -> Payment {
+        Payment::new(self.asset, self.budget.0)
+    }
+
     pub fn is_expired(&self) -> bool {
         self.deadline.is_past()
     }
+
+    /// Start building a request, due `DEFAULT_TRANSACTION_TIMEOUT` from now
+    pub fn builder(requester: AgentId, service_type: ServiceType) -> TransactionRequestBuilder {
+        TransactionRequestBuilder::new(requester, service_type)
+    }
+}
+
+/// Builds a `TransactionRequest`, validating it at `build`
+#[derive(Debug, Clone)]
+pub struct TransactionRequestBuilder {
+    request: TransactionRequest,
+}
+
+impl TransactionRequestBuilder {
+    pub fn new(requester: AgentId, service_type: ServiceType) -> Self {
+        let timeout = chrono::Duration::seconds(crate::constants::DEFAULT_TRANSACTION_TIMEOUT.as_secs() as i64);
+        Self {
+            request: TransactionRequest::new(
+                requester,
+                service_type,
+                String::new(),
+                Balance(0),
+                Timestamp(chrono::Utc::now() + timeout),
+            ),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.request.description = description.into();
+        self
+    }
+
+    pub fn with_budget(mut self, budget: Balance) -> Self {
+        self.request.budget = budget;
+        self
+    }
+
+    /// Budget in an asset other than SOL
+    pub fn with_payment(mut self, payment: Payment) -> Self {
+        self.request = self.request.with_payment(payment);
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Timestamp) -> Self {
+        self.request.deadline = deadline;
+        self
+    }
+
+    pub fn with_requirement(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request.requirements.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_tags(mut self, tags: TransactionTags) -> Self {
+        self.request.tags = tags;
+        self
+    }
+
+    /// The request, refused without a budget or with a deadline already past
+    pub fn build(self) -> Result<TransactionRequest> {
+        if self.request.budget.0 == 0 {
+            return Err(TransactionError::InvalidAmount { amount: 0 }.into());
+        }
+        if self.request.is_expired() {
+            return Err(TransactionError::Expired { deadline: self.request.deadline.to_string() }.into());
+        }
+        if self.request.description.trim().is_empty() {
+            return Err(TransactionError::InvalidRequirements {
+                reason: "request needs a description".to_string(),
+            }.into());
+        }
+        Ok(self.request)
+    }
 }
 
 /// Transaction proposal from a service provider
@@ -80,6 +261,21 @@ pub struct TransactionProposal {
     pub expires_at: Timestamp,
 }
 
+/// A deliverable paid for on its own, ahead of the final delivery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Milestone {
+    pub deliverable: String,
+    pub deadline: Timestamp,
+    pub amount: Balance,                  // Released to the provider on completion
+    pub completion: Option<ExecutionData>,
+}
+
+impl Milestone {
+    pub fn is_complete(&self) -> bool {
+        self.completion.is_some()
+    }
+}
+
 /// Core transaction structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -94,6 +290,10 @@ pub struct Transaction {
     pub signatures: HashMap<AgentId, Signature>,
     pub execution_data: Option<ExecutionData>,
     pub evaluation: Option<TransactionEvaluation>,
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,       // Paid incrementally; the rest of the price is paid on evaluation
+    #[serde(default)]
+    pub version: u64,                     // Transitions persisted through the `TransactionManager`
     pub created_at: Timestamp,
     pub updated_at: Timestamp,
 }
@@ -112,12 +312,15 @@ impl Transaction {
             signatures: HashMap::new(),
             execution_data: None,
             evaluation: None,
+            milestones: Vec::new(),
+            version: 0,
             created_at: Timestamp::now(),
             updated_at: Timestamp::now(),
         }
     }
 
     pub fn add_proposal(&mut self, proposal: TransactionProposal) -> Result<()> {
+        self.ensure_active()?;
         if self.phase != TransactionPhase::Request && self.phase != TransactionPhase::Negotiation {
             return Err(TransactionError::InvalidState {
                 current: format!("{:?}", self.phase),
@@ -132,6 +335,7 @@ impl Transaction {
     }
 
     pub fn accept_proposal(&mut self, provider_id: AgentId, price: Balance) -> Result<()> {
+        self.ensure_active()?;
         if self.phase != TransactionPhase::Negotiation {
             return Err(TransactionError::InvalidState {
                 current: format!("{:?}", self.phase),
@@ -139,6 +343,17 @@ impl Transaction {
             }.into());
         }
 
+        if price.0 > self.request.budget.0 || price.0 < self.milestone_total().0 {
+            return Err(TransactionError::InvalidAmount { amount: price.0 }.into());
+        }
+
+        if !self.proposals.iter().any(|p| p.provider == provider_id && p.proposed_price == price) {
+            return Err(TransactionError::InvalidState {
+                current: format!("no proposal from {} at {}", provider_id, price),
+                expected: "Matching proposal".to_string(),
+            }.into());
+        }
+
         self.provider = Some(provider_id);
         self.agreed_price = Some(price);
         self.phase = TransactionPhase::Execution;
@@ -147,13 +362,113 @@ impl Transaction {
         Ok(())
     }
 
+    /// Accept a proposal, refusing prices outside governance bounds
+    pub fn accept_proposal_governed(&mut self, provider_id: AgentId, price: Balance, params: &ProtocolParams) -> Result<()> {
+        let asset = self.request.asset;
+        if let Some(violation) = params.check_price(self.id, provider_id, &self.request.service_type, &asset, price) {
+            let amount = |balance: Balance| Payment::new(asset, balance.0).to_string();
+            tracing::warn!(
+                transaction_id = %self.id,
+                provider = %provider_id,
+                "Refusing proposal at {} for {}: outside governance bounds [{}, {}]",
+                amount(price), self.request.service_type, amount(violation.bounds.floor), amount(violation.bounds.ceiling)
+            );
+            return Err(TransactionError::PriceOutOfBounds {
+                price: amount(price),
+                floor: amount(violation.bounds.floor),
+                ceiling: amount(violation.bounds.ceiling),
+            }.into());
+        }
+
+        self.accept_proposal(provider_id, price)
+    }
+
+    /// Split off a deliverable with its own deadline and payment; milestones
+    /// are agreed before a proposal is accepted. Returns its index.
+    pub fn add_milestone(&mut self, deliverable: String, deadline: Timestamp, amount: Balance) -> Result<usize> {
+        self.ensure_active()?;
+        if self.phase != TransactionPhase::Request && self.phase != TransactionPhase::Negotiation {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.phase),
+                expected: "Request or Negotiation".to_string(),
+            }.into());
+        }
+        if deadline.0 > self.request.deadline.0 {
+            return Err(TransactionError::InvalidState {
+                current: format!("milestone deadline {}", deadline),
+                expected: format!("deadline by {}", self.request.deadline),
+            }.into());
+        }
+        if amount.0 == 0 || self.milestone_total().0 + amount.0 > self.request.budget.0 {
+            return Err(TransactionError::InvalidAmount { amount: amount.0 }.into());
+        }
+
+        self.milestones.push(Milestone {
+            deliverable,
+            deadline,
+            amount,
+            completion: None,
+        });
+        self.updated_at = Timestamp::now();
+        Ok(self.milestones.len() - 1)
+    }
+
+    /// Record delivery of a milestone, returning the payment it releases
+    pub fn complete_milestone(&mut self, index: usize, execution_data: ExecutionData) -> Result<Balance> {
+        self.ensure_active()?;
+        if self.phase != TransactionPhase::Execution {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.phase),
+                expected: "Execution".to_string(),
+            }.into());
+        }
+        let milestone = self.milestones.get_mut(index).ok_or_else(|| TransactionError::InvalidState {
+            current: format!("no milestone {}", index),
+            expected: "Agreed milestone".to_string(),
+        })?;
+        if milestone.is_complete() {
+            return Err(TransactionError::InvalidState {
+                current: format!("milestone {} complete", index),
+                expected: "Outstanding milestone".to_string(),
+            }.into());
+        }
+
+        milestone.completion = Some(execution_data);
+        let amount = milestone.amount;
+        self.updated_at = Timestamp::now();
+        Ok(amount)
+    }
+
+    /// Total paid through milestones
+    pub fn milestone_total(&self) -> Balance {
+        Balance(self.milestones.iter().map(|m| m.amount.0).sum())
+    }
+
+    /// Indices of outstanding milestones past their deadline
+    pub fn overdue_milestones(&self, now: Timestamp) -> Vec<usize> {
+        self.milestones
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| !m.is_complete() && m.deadline.0 < now.0)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Deliver the final result once every milestone is complete
     pub fn complete_execution(&mut self, execution_data: ExecutionData) -> Result<()> {
+        self.ensure_active()?;
         if self.phase != TransactionPhase::Execution {
             return Err(TransactionError::InvalidState {
                 current: format!("{:?}", self.phase),
                 expected: "Execution".to_string(),
             }.into());
         }
+        if let Some(index) = self.milestones.iter().position(|m| !m.is_complete()) {
+            return Err(TransactionError::InvalidState {
+                current: format!("milestone {} outstanding", index),
+                expected: "All milestones complete".to_string(),
+            }.into());
+        }
 
         self.execution_data = Some(execution_data);
         self.phase = TransactionPhase::Evaluation;
@@ -162,6 +477,7 @@ impl Transaction {
     }
 
     pub fn add_evaluation(&mut self, evaluation: TransactionEvaluation) -> Result<()> {
+        self.ensure_active()?;
         if self.phase != TransactionPhase::Evaluation {
             return Err(TransactionError::InvalidState {
                 current: format!("{:?}", self.phase),
@@ -174,6 +490,109 @@ impl Transaction {
         self.updated_at = Timestamp::now();
         Ok(())
     }
+
+    /// Cancel before a proposal has been accepted
+    pub fn cancel(&mut self) -> Result<()> {
+        self.ensure_active()?;
+        if self.phase != TransactionPhase::Request && self.phase != TransactionPhase::Negotiation {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.phase),
+                expected: "Request or Negotiation".to_string(),
+            }.into());
+        }
+
+        self.status = TransactionStatus::Cancelled;
+        self.updated_at = Timestamp::now();
+        Ok(())
+    }
+
+    /// Mark execution as failed so locked funds can be refunded
+    pub fn fail(&mut self, reason: String) -> Result<()> {
+        self.ensure_active()?;
+        if self.phase != TransactionPhase::Execution {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.phase),
+                expected: "Execution".to_string(),
+            }.into());
+        }
+
+        tracing::debug!("Transaction {} failed: {}", self.id, reason);
+        self.status = TransactionStatus::Failed;
+        self.updated_at = Timestamp::now();
+        Ok(())
+    }
+
+    /// Reject delivered work so locked funds can be refunded
+    pub fn dispute(&mut self, reason: String) -> Result<()> {
+        self.ensure_active()?;
+        if self.phase != TransactionPhase::Evaluation {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.phase),
+                expected: "Evaluation".to_string(),
+            }.into());
+        }
+
+        tracing::debug!("Transaction {} disputed: {}", self.id, reason);
+        self.status = TransactionStatus::Failed;
+        self.updated_at = Timestamp::now();
+        Ok(())
+    }
+
+    /// Expire a transaction whose deadline passed before work was delivered
+    pub fn expire(&mut self) -> Result<()> {
+        self.ensure_active()?;
+        if !self.request.is_expired() {
+            return Err(TransactionError::InvalidState {
+                current: format!("deadline {}", self.request.deadline),
+                expected: "Past deadline".to_string(),
+            }.into());
+        }
+        if self.phase == TransactionPhase::Evaluation {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.phase),
+                expected: "Request, Negotiation or Execution".to_string(),
+            }.into());
+        }
+
+        self.status = TransactionStatus::Expired;
+        self.updated_at = Timestamp::now();
+        Ok(())
+    }
+
+    /// Receipt of the transaction as it stands, tagged like its request
+    pub fn result(&self) -> TransactionResult {
+        TransactionResult {
+            transaction_id: self.id,
+            status: self.status,
+            final_price: self.agreed_price,
+            completion_time: self.execution_data.as_ref().map(|data| data.completion_time),
+            quality_score: self.evaluation.as_ref().map(|evaluation| evaluation.quality_score),
+            reputation_delta: HashMap::new(),
+            tags: self.request.tags.clone(),
+        }
+    }
+
+    /// Check whether the transaction has reached a final status
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self.status,
+            TransactionStatus::Completed
+                | TransactionStatus::Failed
+                | TransactionStatus::Cancelled
+                | TransactionStatus::Expired
+        )
+    }
+
+    /// Reject transitions out of a final status
+    fn ensure_active(&self) -> Result<()> {
+        if self.is_terminal() {
+            return Err(TransactionError::InvalidState {
+                current: format!("{:?}", self.status),
+                expected: "Active transaction".to_string(),
+            }.into());
+        }
+        Ok(())
+    }
 }
 
 /// Execution data containing results and proofs
@@ -206,6 +625,8 @@ pub struct TransactionResult {
     pub completion_time: Option<Timestamp>,
     pub quality_score: Option<f64>,
     pub reputation_delta: HashMap<AgentId, f64>,
+    #[serde(default)]
+    pub tags: TransactionTags,
 }
 
 #[cfg(test)]
@@ -250,4 +671,100 @@ mod tests {
         assert_eq!(transaction.phase, TransactionPhase::Execution);
         assert_eq!(transaction.status, TransactionStatus::InProgress);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_terminal_transitions() {
+        let provider = AgentId::new();
+        let request = TransactionRequest::new(
+            AgentId::new(),
+            ServiceType::DataAnalysis,
+            "Test request".to_string(),
+            Balance::from_sol(10.0),
+            Timestamp(chrono::Utc::now() + chrono::Duration::hours(1)),
+        );
+
+        let mut transaction = Transaction::new(request);
+        transaction.add_proposal(TransactionProposal {
+            id: TransactionId::new(),
+            request_id: transaction.id,
+            provider,
+            proposed_price: Balance::from_sol(12.0),
+            estimated_completion: Timestamp::now(),
+            proposal_details: "Over budget".to_string(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: Timestamp::now(),
+        }).unwrap();
+
+        // Over-budget and unproposed prices are rejected
+        assert!(transaction.accept_proposal(provider, Balance::from_sol(12.0)).is_err());
+        assert!(transaction.accept_proposal(provider, Balance::from_sol(5.0)).is_err());
+        assert!(transaction.expire().is_err());
+
+        transaction.cancel().unwrap();
+        assert!(transaction.is_terminal());
+        assert_eq!(transaction.status, TransactionStatus::Cancelled);
+
+        // No transitions out of a final status
+        assert!(transaction.cancel().is_err());
+        assert!(transaction.fail("late".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_milestones_fit_the_request() {
+        let provider = AgentId::new();
+        let deadline = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let request = TransactionRequest::new(
+            AgentId::new(),
+            ServiceType::DataAnalysis,
+            "Test request".to_string(),
+            Balance::from_sol(10.0),
+            deadline,
+        );
+
+        let mut transaction = Transaction::new(request);
+        assert!(transaction.add_milestone("after".to_string(), Timestamp(deadline.0 + chrono::Duration::hours(1)), Balance::from_sol(1.0)).is_err());
+        assert!(transaction.add_milestone("over".to_string(), deadline, Balance::from_sol(11.0)).is_err());
+        assert_eq!(transaction.add_milestone("draft".to_string(), Timestamp::now(), Balance::from_sol(6.0)).unwrap(), 0);
+
+        transaction.add_proposal(TransactionProposal {
+            id: TransactionId::new(),
+            request_id: transaction.id,
+            provider,
+            proposed_price: Balance::from_sol(5.0),
+            estimated_completion: deadline,
+            proposal_details: "Below milestones".to_string(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: deadline,
+        }).unwrap();
+
+        // A price below the milestone payments is refused
+        assert!(transaction.accept_proposal(provider, Balance::from_sol(5.0)).is_err());
+        assert_eq!(transaction.overdue_milestones(deadline), vec![0]);
+    }
+
+    #[test]
+    fn test_request_builder_validates_at_build() {
+        let requester = AgentId::new();
+        assert!(TransactionRequest::builder(requester, ServiceType::DataAnalysis)
+            .with_description("No budget")
+            .build()
+            .is_err());
+        assert!(TransactionRequest::builder(requester, ServiceType::DataAnalysis)
+            .with_description("Past deadline")
+            .with_budget(Balance::from_sol(1.0))
+            .with_deadline(Timestamp(chrono::Utc::now() - chrono::Duration::seconds(1)))
+            .build()
+            .is_err());
+
+        let request = TransactionRequest::builder(requester, ServiceType::DataAnalysis)
+            .with_description("Quarterly report")
+            .with_budget(Balance::from_sol(1.0))
+            .with_requirement("format", "pdf")
+            .build()
+            .unwrap();
+        assert!(!request.is_expired());
+        assert_eq!(request.requirements["format"], "pdf");
+    }
+}
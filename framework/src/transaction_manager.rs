@@ -0,0 +1,318 @@
+//! Transaction Manager
+//!
+//! Keeps transactions durable across crashes. Every state transition goes
+//! through `TransactionManager::apply`, which checks it against a copy of
+//! the transaction, writes it to storage as a pending record, stores the
+//! transaction under its next version, and only then clears the record and
+//! updates memory. A record is numbered with the version it produces, so
+//! replaying it against a transaction that already reached that version
+//! does nothing: a crash between any two writes never applies a
+//! transition twice, nor loses one that was recorded.
+//!
+//! On restart, `recover` first finishes pending transitions, then reloads
+//! every transaction without a final status. Those past their deadline
+//! before any work was delivered are expired; the rest, including delivered
+//! work awaiting evaluation, resume where they stopped.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::{
+    error::TransactionError,
+    storage::StorageManager,
+    transaction::{ExecutionData, Transaction, TransactionEvaluation, TransactionPhase, TransactionProposal, TransactionRequest},
+    types::{AgentId, Balance, Timestamp, TransactionId},
+    Result, SolaceError,
+};
+
+/// A state change applied to a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Transition {
+    AddProposal(TransactionProposal),
+    AcceptProposal { provider: AgentId, price: Balance },
+    AddMilestone { deliverable: String, deadline: Timestamp, amount: Balance },
+    CompleteMilestone { index: usize, execution_data: ExecutionData },
+    CompleteExecution(ExecutionData),
+    Evaluate(TransactionEvaluation),
+    Cancel,
+    Fail(String),
+    Dispute(String),
+    Expire,
+}
+
+impl Transition {
+    fn apply_to(&self, transaction: &mut Transaction) -> Result<()> {
+        match self.clone() {
+            Transition::AddProposal(proposal) => transaction.add_proposal(proposal),
+            Transition::AcceptProposal { provider, price } => transaction.accept_proposal(provider, price),
+            Transition::AddMilestone { deliverable, deadline, amount } => {
+                transaction.add_milestone(deliverable, deadline, amount).map(|_| ())
+            }
+            Transition::CompleteMilestone { index, execution_data } => {
+                transaction.complete_milestone(index, execution_data).map(|_| ())
+            }
+            Transition::CompleteExecution(execution_data) => transaction.complete_execution(execution_data),
+            Transition::Evaluate(evaluation) => transaction.add_evaluation(evaluation),
+            Transition::Cancel => transaction.cancel(),
+            Transition::Fail(reason) => transaction.fail(reason),
+            Transition::Dispute(reason) => transaction.dispute(reason),
+            Transition::Expire => transaction.expire(),
+        }
+    }
+}
+
+/// A transition written ahead of the transaction it changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct PendingTransition {
+    pub transaction_id: TransactionId,
+    pub version: u64,                     // Version of the transaction once applied
+    pub transition: Transition,
+    pub recorded_at: Timestamp,
+}
+
+/// What `TransactionManager::recover` found
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    pub replayed: usize,                  // Pending transitions applied on recovery
+    pub discarded: usize,                 // Pending transitions already stored, or no longer valid
+    pub resumed: Vec<TransactionId>,
+    pub expired: Vec<TransactionId>,
+}
+
+/// Durable owner of an agent's in-flight transactions
+pub struct TransactionManager {
+    storage: Arc<StorageManager>,
+    transactions: RwLock<HashMap<TransactionId, Transaction>>,
+}
+
+impl TransactionManager {
+    pub fn new(storage: Arc<StorageManager>) -> Self {
+        Self {
+            storage,
+            transactions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Start a transaction for `request`; opening the same request again
+    /// returns the transaction as it stands
+    pub async fn open(&self, request: TransactionRequest) -> Result<Transaction> {
+        let mut transactions = self.transactions.write().await;
+        if let Some(existing) = transactions.get(&request.id) {
+            return Ok(existing.clone());
+        }
+        let transaction = match self.load(&request.id).await? {
+            Some(stored) => stored,
+            None => {
+                let transaction = Transaction::new(request);
+                self.storage.store_transaction(&transaction.id, &transaction).await.map_err(storage_error)?;
+                transaction
+            }
+        };
+        transactions.insert(transaction.id, transaction.clone());
+        Ok(transaction)
+    }
+
+    /// The transaction as `transition` would leave it, without applying it;
+    /// lets callers check a step before acting on it elsewhere
+    pub async fn check(&self, id: &TransactionId, transition: &Transition) -> Result<Transaction> {
+        let mut next = self.current(id).await?;
+        transition.apply_to(&mut next)?;
+        Ok(next)
+    }
+
+    /// Apply a transition, persisting it before it takes effect
+    pub async fn apply(&self, id: &TransactionId, transition: Transition) -> Result<Transaction> {
+        let mut transactions = self.transactions.write().await;
+        let current = match transactions.get(id) {
+            Some(transaction) => transaction.clone(),
+            None => self.load(id).await?.ok_or_else(|| TransactionError::NotFound { id: id.to_string() })?,
+        };
+
+        let mut next = current.clone();
+        transition.apply_to(&mut next)?;
+        next.version = current.version + 1;
+
+        let pending = PendingTransition {
+            transaction_id: *id,
+            version: next.version,
+            transition,
+            recorded_at: Timestamp::now(),
+        };
+        self.storage.store_pending_transition(id, &pending).await.map_err(storage_error)?;
+        self.storage.store_transaction(id, &next).await.map_err(storage_error)?;
+        self.storage.delete_pending_transition(id).await.map_err(storage_error)?;
+
+        transactions.insert(*id, next.clone());
+        Ok(next)
+    }
+
+    /// A transaction as it stands
+    pub async fn get(&self, id: &TransactionId) -> Option<Transaction> {
+        self.transactions.read().await.get(id).cloned()
+    }
+
+    /// Transactions without a final status, oldest first
+    pub async fn in_flight(&self) -> Vec<Transaction> {
+        let mut open: Vec<Transaction> =
+            self.transactions.read().await.values().filter(|tx| !tx.is_terminal()).cloned().collect();
+        open.sort_by_key(|tx| (tx.created_at, tx.id.to_string()));
+        open
+    }
+
+    /// Finish interrupted transitions and reload in-flight transactions
+    /// after a restart, expiring those whose deadline passed
+    pub async fn recover(&self) -> Result<RecoveryReport> {
+        let mut report = RecoveryReport::default();
+
+        let mut pending: Vec<PendingTransition> =
+            self.storage.list_pending_transitions().await.map_err(storage_error)?;
+        pending.sort_by_key(|record| (record.recorded_at, record.transaction_id.to_string()));
+        for record in pending {
+            if self.replay(&record).await? {
+                report.replayed += 1;
+            } else {
+                report.discarded += 1;
+            }
+            self.storage.delete_pending_transition(&record.transaction_id).await.map_err(storage_error)?;
+        }
+
+        let mut stored: Vec<Transaction> = self.storage.list_transactions().await.map_err(storage_error)?;
+        stored.retain(|tx| !tx.is_terminal());
+        stored.sort_by_key(|tx| (tx.created_at, tx.id.to_string()));
+
+        for transaction in stored {
+            let id = transaction.id;
+            let overdue = transaction.request.is_expired() && transaction.phase != TransactionPhase::Evaluation;
+            self.transactions.write().await.insert(id, transaction);
+            if overdue {
+                self.apply(&id, Transition::Expire).await?;
+                report.expired.push(id);
+            } else {
+                report.resumed.push(id);
+            }
+        }
+
+        tracing::info!(
+            "Recovered transactions: {} resumed, {} expired, {} transitions replayed",
+            report.resumed.len(), report.expired.len(), report.replayed
+        );
+        Ok(report)
+    }
+
+    /// Store a recorded transition unless the transaction already has it;
+    /// returns whether it was applied now
+    async fn replay(&self, record: &PendingTransition) -> Result<bool> {
+        let Some(mut transaction) = self.load(&record.transaction_id).await? else {
+            return Ok(false);
+        };
+        if transaction.version + 1 != record.version {
+            return Ok(false);
+        }
+        if let Err(e) = record.transition.apply_to(&mut transaction) {
+            tracing::warn!("Dropping pending transition of {}: {}", record.transaction_id, e);
+            return Ok(false);
+        }
+        transaction.version = record.version;
+        self.storage.store_transaction(&transaction.id, &transaction).await.map_err(storage_error)?;
+        Ok(true)
+    }
+
+    async fn current(&self, id: &TransactionId) -> Result<Transaction> {
+        if let Some(transaction) = self.get(id).await {
+            return Ok(transaction);
+        }
+        self.load(id).await?.ok_or_else(|| TransactionError::NotFound { id: id.to_string() }.into())
+    }
+
+    async fn load(&self, id: &TransactionId) -> Result<Option<Transaction>> {
+        self.storage.get_transaction(id).await.map_err(storage_error)
+    }
+}
+
+impl std::fmt::Debug for TransactionManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransactionManager").finish_non_exhaustive()
+    }
+}
+
+fn storage_error(error: anyhow::Error) -> SolaceError {
+    SolaceError::internal(format!("Transaction storage failed: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ServiceType;
+
+    fn request(deadline: Timestamp) -> TransactionRequest {
+        TransactionRequest::new(AgentId::new(), ServiceType::DataAnalysis, "report".to_string(), Balance(1_000), deadline)
+    }
+
+    fn proposal(transaction: &Transaction, provider: AgentId) -> TransactionProposal {
+        TransactionProposal {
+            id: TransactionId::new(),
+            request_id: transaction.id,
+            provider,
+            proposed_price: Balance(800),
+            estimated_completion: transaction.request.deadline,
+            proposal_details: String::new(),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: transaction.request.deadline,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recovery_replays_pending_transitions_once() {
+        let storage = Arc::new(StorageManager::memory());
+        let future = Timestamp(chrono::Utc::now() + chrono::Duration::hours(1));
+        let provider = AgentId::new();
+
+        let manager = TransactionManager::new(storage.clone());
+        let transaction = manager.open(request(future)).await.unwrap();
+        assert_eq!(manager.open(transaction.request.clone()).await.unwrap().version, 0);
+        let negotiating = manager.apply(&transaction.id, Transition::AddProposal(proposal(&transaction, provider))).await.unwrap();
+        assert_eq!(negotiating.version, 1);
+        assert!(manager.apply(&transaction.id, Transition::Evaluate(TransactionEvaluation {
+            requester_rating: 1.0,
+            provider_rating: 1.0,
+            requester_feedback: String::new(),
+            provider_feedback: String::new(),
+            quality_score: 1.0,
+            timeliness_score: 1.0,
+            overall_satisfaction: 1.0,
+        })).await.is_err());
+
+        // Crash after recording the acceptance but before storing it
+        let accept = PendingTransition {
+            transaction_id: transaction.id,
+            version: 2,
+            transition: Transition::AcceptProposal { provider, price: Balance(800) },
+            recorded_at: Timestamp::now(),
+        };
+        storage.store_pending_transition(&transaction.id, &accept).await.unwrap();
+
+        // A transaction whose deadline passed while the node was down
+        let mut late = Transaction::new(request(future));
+        late.request.deadline = Timestamp(chrono::Utc::now() - chrono::Duration::seconds(1));
+        storage.store_transaction(&late.id, &late).await.unwrap();
+
+        let restarted = TransactionManager::new(storage.clone());
+        let report = restarted.recover().await.unwrap();
+        assert_eq!(report.replayed, 1);
+        assert_eq!(report.resumed, vec![transaction.id]);
+        assert_eq!(report.expired, vec![late.id]);
+        let resumed = restarted.get(&transaction.id).await.unwrap();
+        assert_eq!(resumed.phase, TransactionPhase::Execution);
+        assert_eq!(resumed.version, 2);
+        assert_eq!(restarted.in_flight().await.len(), 1);
+
+        // Replaying the same record again changes nothing
+        storage.store_pending_transition(&transaction.id, &accept).await.unwrap();
+        let again = TransactionManager::new(storage).recover().await.unwrap();
+        assert_eq!((again.replayed, again.discarded), (0, 1));
+        assert_eq!(again.resumed, vec![transaction.id]);
+    }
+}
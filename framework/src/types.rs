@@ -2,11 +2,20 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use solana_sdk::pubkey::Pubkey;
 use std::fmt;
 use uuid::Uuid;
 
+/// Domain separation for agent IDs derived from public keys
+const AGENT_ID_CONTEXT: &[u8] = b"solace-agent-id";
+
 /// Unique identifier for an agent
+///
+/// Agents derive their ID from their public key, so a keypair keeps the
+/// same ID, and the reputation attached to it, across restarts. Random IDs
+/// remain for ephemeral identities and for agents created before
+/// derivation, which migrate with `AgentConfig::legacy_id`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct AgentId(pub Uuid);
 
@@ -16,6 +25,24 @@ impl AgentId {
         Self(Uuid::new_v4())
     }
 
+    /// The ID of the agent holding `public_key`
+    pub fn from_public_key(public_key: &Pubkey) -> Self {
+        Self::from_key_bytes(&public_key.to_bytes())
+    }
+
+    /// The ID of the agent whose ed25519 public key is `key`
+    pub fn from_key_bytes(key: &[u8; 32]) -> Self {
+        let digest = Sha256::new().chain_update(AGENT_ID_CONTEXT).chain_update(key).finalize();
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+        Self(uuid::Builder::from_custom_bytes(bytes).into_uuid())
+    }
+
+    /// Whether this ID is the one derived from `key`
+    pub fn is_derived_from(&self, key: &[u8; 32]) -> bool {
+        *self == Self::from_key_bytes(key)
+    }
+
     /// Create an agent ID from a string
     pub fn from_string(s: &str) -> Result<Self, uuid::Error> {
         Ok(Self(Uuid::parse_str(s)?))
@@ -58,7 +85,7 @@ impl Default for TransactionId {
 }
 
 /// Balance representation in lamports (1 SOL = 1,000,000,000 lamports)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Balance(pub u64);
 
 impl Balance {
@@ -99,6 +126,119 @@ impl fmt::Display for Balance {
     }
 }
 
+/// What a payment is made in: native SOL, or tokens of an SPL mint
+///
+/// Written as `SOL` or `spl:<mint>:<decimals>` in configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PaymentAsset {
+    #[default]
+    Sol,
+    Spl { mint: Pubkey, decimals: u8 },
+}
+
+impl PaymentAsset {
+    pub fn is_sol(&self) -> bool {
+        matches!(self, PaymentAsset::Sol)
+    }
+
+    /// Decimal places between base units and whole tokens
+    pub fn decimals(&self) -> u8 {
+        match self {
+            PaymentAsset::Sol => 9,
+            PaymentAsset::Spl { decimals, .. } => *decimals,
+        }
+    }
+
+    /// The SPL mint, if the asset is a token
+    pub fn mint(&self) -> Option<Pubkey> {
+        match self {
+            PaymentAsset::Sol => None,
+            PaymentAsset::Spl { mint, .. } => Some(*mint),
+        }
+    }
+}
+
+impl fmt::Display for PaymentAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentAsset::Sol => write!(f, "SOL"),
+            PaymentAsset::Spl { mint, decimals } => write!(f, "spl:{}:{}", mint, decimals),
+        }
+    }
+}
+
+impl std::str::FromStr for PaymentAsset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("SOL") {
+            return Ok(PaymentAsset::Sol);
+        }
+        let invalid = || format!("Not a payment asset: {} (expected SOL or spl:<mint>:<decimals>)", s);
+        let mut parts = s.split(':');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some("spl"), Some(mint), Some(decimals), None) => Ok(PaymentAsset::Spl {
+                mint: mint.parse().map_err(|_| invalid())?,
+                decimals: decimals.parse().map_err(|_| invalid())?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for PaymentAsset {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<PaymentAsset> for String {
+    fn from(asset: PaymentAsset) -> Self {
+        asset.to_string()
+    }
+}
+
+/// An amount of a payment asset, in its base units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Payment {
+    pub asset: PaymentAsset,
+    pub amount: u64,
+}
+
+impl Payment {
+    pub fn new(asset: PaymentAsset, amount: u64) -> Self {
+        Self { asset, amount }
+    }
+
+    pub fn sol(balance: Balance) -> Self {
+        Self::new(PaymentAsset::Sol, balance.0)
+    }
+
+    /// The amount as a budget or price, which is in the asset's base units
+    pub fn as_balance(&self) -> Balance {
+        Balance(self.amount)
+    }
+
+    /// Amount in whole units of the asset
+    pub fn to_units(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.asset.decimals() as i32)
+    }
+}
+
+impl fmt::Display for Payment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.asset {
+            PaymentAsset::Sol => write!(f, "{}", Balance(self.amount)),
+            PaymentAsset::Spl { mint, decimals } => {
+                write!(f, "{:.*} of {}", decimals as usize, self.to_units(), mint)
+            }
+        }
+    }
+}
+
 /// Timestamp type for consistent time handling
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Timestamp(pub DateTime<Utc>);
@@ -147,6 +287,9 @@ impl Default for Timestamp {
     }
 }
 
+/// Hex-encoded SHA-256 digest identifying a block
+pub type Hash = String;
+
 /// Network address for peer communication
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NetworkAddress {
@@ -174,6 +317,7 @@ pub enum ServiceType {
     MarketResearch,
     ContentCreation,
     TradingService,
+    MachineLearning,
     CustomService(String),
 }
 
@@ -185,6 +329,7 @@ impl fmt::Display for ServiceType {
             ServiceType::MarketResearch => write!(f, "Market Research"),
             ServiceType::ContentCreation => write!(f, "Content Creation"),
             ServiceType::TradingService => write!(f, "Trading Service"),
+            ServiceType::MachineLearning => write!(f, "Machine Learning"),
             ServiceType::CustomService(name) => write!(f, "Custom: {}", name),
         }
     }
@@ -234,6 +379,16 @@ mod tests {
         assert_ne!(id1, id2);
     }
 
+    #[test]
+    fn test_agent_id_derived_from_public_key() {
+        let key = Pubkey::new_unique();
+        let id = AgentId::from_public_key(&key);
+        assert_eq!(id, AgentId::from_public_key(&key));
+        assert!(id.is_derived_from(&key.to_bytes()));
+        assert_ne!(id, AgentId::from_public_key(&Pubkey::new_unique()));
+        assert!(!AgentId::new().is_derived_from(&key.to_bytes()));
+    }
+
     #[test]
     fn test_balance_operations() {
         let balance1 = Balance::from_sol(1.5);
@@ -248,6 +403,25 @@ mod tests {
         assert_eq!(diff.to_sol(), 1.0);
     }
 
+    #[test]
+    fn test_payment_asset_parsing() {
+        let mint = Pubkey::new_unique();
+        let usdc = PaymentAsset::Spl { mint, decimals: 6 };
+        assert_eq!("sol".parse::<PaymentAsset>().unwrap(), PaymentAsset::Sol);
+        assert_eq!(format!("spl:{}:6", mint).parse::<PaymentAsset>().unwrap(), usdc);
+        assert!("spl:not-a-mint:6".parse::<PaymentAsset>().is_err());
+        assert!("USDC".parse::<PaymentAsset>().is_err());
+
+        // Configurations written before SPL support still load
+        let assets: Vec<PaymentAsset> = serde_json::from_str(r#"["SOL"]"#).unwrap();
+        assert_eq!(assets, vec![PaymentAsset::Sol]);
+        let json = serde_json::to_string(&usdc).unwrap();
+        assert_eq!(serde_json::from_str::<PaymentAsset>(&json).unwrap(), usdc);
+
+        assert_eq!(Payment::new(usdc, 2_500_000).to_units(), 2.5);
+        assert_eq!(Payment::sol(Balance::from_sol(1.5)).to_units(), 1.5);
+    }
+
     #[test]
     fn test_timestamp_operations() {
         let ts = Timestamp::now();
@@ -1,6 +1,28 @@
 //! Utility functions for the Solace Protocol
+//!
+//! Also home to the shared job `Scheduler`. Recurring work (reputation
+//! decay, retention cleanup, statements, availability windows) registers a
+//! job with a cron expression or a fixed interval instead of spawning its
+//! own timer loop. Next-run times can be persisted, so a restarted node
+//! resumes the same plan; runs may be jittered so a fleet of nodes does not
+//! fire at once; and a run discovered too late, because the node was down
+//! or the previous run was still going, is a misfire that the job's
+//! `MisfirePolicy` either skips or runs once, never repeatedly.
 
+use crate::error::SolaceError;
+use crate::storage::StorageManager;
 use crate::types::Timestamp;
+use crate::Result;
+use solace_common::rng::NodeRng;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc};
+use futures::future::BoxFuture;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 /// Generate a unique identifier string
 pub fn generate_id() -> String {
@@ -15,6 +37,559 @@ pub fn is_within_duration(timestamp: Timestamp, duration_seconds: i64) -> bool {
 }
 
 /// Format a timestamp for display
-pub fn format_timestamp(timestamp: Timestamp) -> String {
+pub(crate) fn format_timestamp(timestamp: Timestamp) -> String {
     timestamp.0.format("%Y-%m-%d %H:%M:%S UTC").to_string()
-} 
\ No newline at end of file
+}
+
+/// Five-field cron expression in UTC: minute, hour, day of month, month,
+/// day of week (0 or 7 is Sunday)
+///
+/// Fields take `*`, values, ranges `a-b`, steps `*/n` or `a-b/n`, and lists
+/// of those. As in cron, when both day fields are restricted a day matching
+/// either runs. `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`
+/// are accepted too.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,                         // Bit n set: runs at minute n
+    hours: u64,
+    days: u64,                            // Days of the month, from bit 1
+    months: u64,                          // From bit 1
+    weekdays: u64,                        // Bit 0 is Sunday
+    any_day: bool,                        // Day of month was `*`
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// First run strictly after `after`, if any within five years
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let bit = |set: u64, value: u32| set & (1 << value) != 0;
+        let start_of_day = |date: NaiveDate| Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?));
+        let limit = after + chrono::Duration::days(5 * 366);
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        while t <= limit {
+            let date = t.date_naive();
+            if !bit(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = start_of_day(NaiveDate::from_ymd_opt(year, month, 1)?)?;
+            } else if !self.day_matches(date) {
+                t = start_of_day(date.succ_opt()?)?;
+            } else if !bit(self.hours, t.hour()) {
+                t = t.with_minute(0)? + chrono::Duration::hours(1);
+            } else if !bit(self.minutes, t.minute()) {
+                t += chrono::Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    /// Parse one field into a bit set of the values in `min..=max`
+    fn field(text: &str, min: u32, max: u32) -> Result<u64> {
+        let invalid = || SolaceError::config(format!("Invalid cron field '{}' (values {}-{})", text, min, max));
+        let mut set = 0u64;
+        for part in text.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0).ok_or_else(invalid)?),
+                None => (part, 1),
+            };
+            let (low, high) = match range {
+                "*" => (min, max),
+                _ => match range.split_once('-') {
+                    Some((low, high)) => (low.parse().map_err(|_| invalid())?, high.parse().map_err(|_| invalid())?),
+                    // `a/n` runs from `a` to the end of the range
+                    None => {
+                        let value = range.parse().map_err(|_| invalid())?;
+                        (value, if part.contains('/') { max } else { value })
+                    }
+                },
+            };
+            if low < min || high > max || low > high {
+                return Err(invalid());
+            }
+            for value in (low..=high).step_by(step as usize) {
+                set |= 1 << value;
+            }
+        }
+        Ok(set)
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = SolaceError;
+
+    fn from_str(expression: &str) -> Result<Self> {
+        let fields = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = fields.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(SolaceError::config(format!("Cron expression '{}' needs five fields", expression)));
+        };
+        let mut weekdays = Self::field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: Self::field(minute, 0, 59)?,
+            hours: Self::field(hour, 0, 23)?,
+            days: Self::field(day, 1, 31)?,
+            months: Self::field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = SolaceError;
+
+    fn try_from(expression: String) -> Result<Self> {
+        expression.parse()
+    }
+}
+
+impl From<CronSchedule> for String {
+    fn from(schedule: CronSchedule) -> Self {
+        schedule.expression
+    }
+}
+
+impl std::fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+/// When a job runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Schedule {
+    Cron(CronSchedule),
+    Every(Duration),
+}
+
+impl Schedule {
+    /// Parse a cron expression
+    pub fn cron(expression: &str) -> Result<Self> {
+        Ok(Self::Cron(expression.parse()?))
+    }
+
+    /// First run strictly after `after`
+    pub fn next_after(&self, after: Timestamp) -> Option<Timestamp> {
+        match self {
+            Self::Cron(cron) => cron.next_after(after.0).map(Timestamp),
+            Self::Every(interval) => chrono::Duration::from_std(*interval).ok().map(|interval| Timestamp(after.0 + interval)),
+        }
+    }
+}
+
+/// What to do about a run found more than the grace period late
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MisfirePolicy {
+    Skip,                                 // Wait for the next scheduled run
+    RunOnce,                              // Run now, once, however many were missed
+}
+
+/// A recurring job's name and timing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobSpec {
+    pub name: String,                     // Unique; keys the persisted next run
+    pub schedule: Schedule,
+    pub jitter: Duration,                 // Each run is delayed by up to this much
+    pub misfire: MisfirePolicy,
+    pub misfire_grace: Duration,          // Lateness still run as scheduled
+}
+
+impl JobSpec {
+    pub fn new(name: impl Into<String>, schedule: Schedule) -> Self {
+        Self {
+            name: name.into(),
+            schedule,
+            jitter: Duration::ZERO,
+            misfire: MisfirePolicy::RunOnce,
+            misfire_grace: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_misfire(mut self, misfire: MisfirePolicy, grace: Duration) -> Self {
+        self.misfire = misfire;
+        self.misfire_grace = grace;
+        self
+    }
+
+    /// Next run after `after`, jittered from `rng`
+    fn plan(&self, after: Timestamp, rng: &NodeRng) -> Option<Timestamp> {
+        let next = self.schedule.next_after(after)?;
+        if self.jitter.is_zero() {
+            return Some(next);
+        }
+        let delay = rng.with(|rng| rng.gen_range(Duration::ZERO..=self.jitter));
+        Some(Timestamp(next.0 + chrono::Duration::from_std(delay).ok()?))
+    }
+}
+
+/// Work a job runs
+type JobTask = Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// A job's state, as reported by `Scheduler::jobs`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub next_run: Option<Timestamp>,      // None once the schedule has no more runs
+    pub runs: u64,
+    pub failures: u64,
+    pub misfires: u64,                    // Runs skipped or collapsed into one
+    pub running: bool,
+}
+
+struct Job {
+    spec: JobSpec,
+    task: JobTask,
+    next_run: Option<Timestamp>,
+    runs: u64,
+    misfires: u64,
+    failures: Arc<AtomicU64>,
+    running: Arc<AtomicBool>,
+}
+
+/// A run `take_due` started the clock on
+struct DueRun {
+    name: String,
+    task: JobTask,
+    failures: Arc<AtomicU64>,
+    running: Arc<AtomicBool>,
+}
+
+/// Runs registered jobs on their schedules
+pub struct Scheduler {
+    jobs: Mutex<Vec<Job>>,
+    storage: Option<Arc<StorageManager>>,
+    dirty: AtomicBool,                    // Next runs changed since last persisted
+    rng: NodeRng,                         // Jitter source, replayable with SOLACE_SEED
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler").field("jobs", &self.jobs()).finish()
+    }
+}
+
+/// Longest the scheduler sleeps before looking at its jobs again
+const MAX_SCHEDULER_SLEEP: Duration = Duration::from_secs(60);
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::with_rng(NodeRng::for_node(None, "scheduler"))
+    }
+
+    /// Draw jitter from `rng` instead of a generator seeded from `SOLACE_SEED`
+    pub fn with_rng(rng: NodeRng) -> Self {
+        Self { jobs: Mutex::new(Vec::new()), storage: None, dirty: AtomicBool::new(false), rng }
+    }
+
+    /// Persist next-run times in `storage`
+    pub fn with_storage(mut self, storage: Arc<StorageManager>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Register a job, first run planned from now. A job with the same
+    /// name is replaced.
+    pub fn add<F, Fut>(&self, spec: JobSpec, task: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        let task: JobTask = Arc::new(move || Box::pin(task()));
+        let next_run = spec.plan(Timestamp::now(), &self.rng);
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.retain(|job| job.spec.name != spec.name);
+        jobs.push(Job {
+            spec,
+            task,
+            next_run,
+            runs: 0,
+            misfires: 0,
+            failures: Arc::new(AtomicU64::new(0)),
+            running: Arc::new(AtomicBool::new(false)),
+        });
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Take up the next-run times persisted before a restart. Those that
+    /// passed while the node was down are misfires when next due.
+    pub async fn restore(&self) -> Result<usize> {
+        let Some(storage) = &self.storage else {
+            return Ok(0);
+        };
+        let names: Vec<String> = self.jobs.lock().unwrap().iter().map(|job| job.spec.name.clone()).collect();
+        let mut restored = 0;
+        for name in names {
+            let stored = storage.get_next_run(&name).await.map_err(|e| SolaceError::internal(format!("Failed to load schedule of {}: {}", name, e)))?;
+            if let Some(next_run) = stored {
+                if let Some(job) = self.jobs.lock().unwrap().iter_mut().find(|job| job.spec.name == name) {
+                    job.next_run = Some(next_run);
+                    restored += 1;
+                }
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Save every job's next-run time, if they changed
+    pub async fn persist(&self) -> Result<()> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let planned: Vec<(String, Timestamp)> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|job| Some((job.spec.name.clone(), job.next_run?)))
+            .collect();
+        for (name, next_run) in planned {
+            if let Err(e) = storage.store_next_run(&name, next_run).await {
+                self.dirty.store(true, Ordering::Relaxed);
+                return Err(SolaceError::internal(format!("Failed to save schedule of {}: {}", name, e)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn jobs(&self) -> Vec<JobStatus> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|job| JobStatus {
+                name: job.spec.name.clone(),
+                next_run: job.next_run,
+                runs: job.runs,
+                failures: job.failures.load(Ordering::Relaxed),
+                misfires: job.misfires,
+                running: job.running.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Earliest planned run
+    pub fn next_wakeup(&self) -> Option<Timestamp> {
+        self.jobs.lock().unwrap().iter().filter_map(|job| job.next_run).min()
+    }
+
+    /// Start the jobs due at `now`, planning their next runs. Returns how
+    /// many started.
+    pub fn run_due(&self, now: Timestamp) -> usize {
+        let due = self.take_due(now);
+        let started = due.len();
+        for run in due {
+            tokio::spawn(async move {
+                if let Err(e) = (run.task)().await {
+                    tracing::warn!("Scheduled job {} failed: {}", run.name, e);
+                    run.failures.fetch_add(1, Ordering::Relaxed);
+                }
+                run.running.store(false, Ordering::Release);
+            });
+        }
+        started
+    }
+
+    fn take_due(&self, now: Timestamp) -> Vec<DueRun> {
+        let mut due = Vec::new();
+        for job in self.jobs.lock().unwrap().iter_mut() {
+            let Some(scheduled) = job.next_run.filter(|scheduled| *scheduled <= now) else {
+                continue;
+            };
+            job.next_run = job.spec.plan(now, &self.rng);
+            self.dirty.store(true, Ordering::Relaxed);
+
+            let grace = chrono::Duration::from_std(job.spec.misfire_grace).unwrap_or(chrono::Duration::MAX);
+            let late = now.0 - scheduled.0 > grace;
+            let overlapping = job.running.load(Ordering::Acquire);
+            if late || overlapping {
+                job.misfires += 1;
+            }
+            if overlapping || (late && job.spec.misfire == MisfirePolicy::Skip) {
+                tracing::debug!("Skipping misfired run of {} scheduled at {}", job.spec.name, format_timestamp(scheduled));
+                continue;
+            }
+            job.runs += 1;
+            job.running.store(true, Ordering::Release);
+            due.push(DueRun {
+                name: job.spec.name.clone(),
+                task: job.task.clone(),
+                failures: job.failures.clone(),
+                running: job.running.clone(),
+            });
+        }
+        due
+    }
+
+    /// Run jobs as they come due until the handle is aborted, persisting
+    /// next-run times as they change
+    pub fn start(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        self.start_cancellable(CancellationToken::new())
+    }
+
+    /// `start` that returns once `cancel` fires. The scheduler stops between
+    /// passes, so no job is half taken; runs already started finish on their
+    /// own, and the schedule is persisted once more on the way out.
+    pub fn start_cancellable(self: Arc<Self>, cancel: CancellationToken) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            while !cancel.is_cancelled() {
+                let now = Timestamp::now();
+                self.run_due(now);
+                if let Err(e) = self.persist().await {
+                    tracing::warn!("Failed to persist job schedule: {}", e);
+                }
+                let wait = match self.next_wakeup() {
+                    Some(next) => (next.0 - Timestamp::now().0).to_std().unwrap_or(Duration::ZERO),
+                    None => MAX_SCHEDULER_SLEEP,
+                };
+                tokio::select! {
+                    _ = tokio::time::sleep(wait.min(MAX_SCHEDULER_SLEEP)) => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+            if let Err(e) = self.persist().await {
+                tracing::warn!("Failed to persist job schedule: {}", e);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_cron_expressions_find_their_next_run() {
+        // 2024-03-01 is a Friday
+        let office_hours: CronSchedule = "*/15 9-17 * * 1-5".parse().unwrap();
+        assert_eq!(office_hours.next_after(at("2024-03-01T09:07:30Z")), Some(at("2024-03-01T09:15:00Z")));
+        assert_eq!(office_hours.next_after(at("2024-03-01T17:50:00Z")), Some(at("2024-03-04T09:00:00Z")));
+
+        let monthly: CronSchedule = "@monthly".parse().unwrap();
+        assert_eq!(monthly.next_after(at("2024-12-15T00:00:00Z")), Some(at("2025-01-01T00:00:00Z")));
+        // With both day fields restricted, either one matching is enough
+        let either: CronSchedule = "0 0 13 * 5".parse().unwrap();
+        assert_eq!(either.next_after(at("2024-03-01T12:00:00Z")), Some(at("2024-03-08T00:00:00Z")));
+        let sundays: CronSchedule = "30 2 * * 7".parse().unwrap();
+        assert_eq!(sundays.next_after(at("2024-03-01T00:00:00Z")), Some(at("2024-03-03T02:30:00Z")));
+        assert_eq!("0 0 30 2 *".parse::<CronSchedule>().unwrap().next_after(at("2024-01-01T00:00:00Z")), None);
+
+        for invalid in ["61 * * * *", "* * *", "*/0 * * * *", "5-1 * * * *", "* * 0 * *"] {
+            assert!(invalid.parse::<CronSchedule>().is_err(), "{}", invalid);
+        }
+        let json = serde_json::to_string(&office_hours).unwrap();
+        assert_eq!(serde_json::from_str::<CronSchedule>(&json).unwrap(), office_hours);
+    }
+
+    #[test]
+    fn test_jitter_replays_from_the_seed() {
+        let spec = JobSpec::new("jittered", Schedule::Every(Duration::from_secs(3600))).with_jitter(Duration::from_secs(600));
+        let after = Timestamp(at("2024-03-01T00:00:00Z"));
+        let plan = |seed| {
+            let rng = NodeRng::from_seed(seed);
+            (0..4).map(|_| spec.plan(after, &rng).unwrap()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(plan(7), plan(7));
+        assert_ne!(plan(7), plan(8));
+        assert!(plan(7).iter().all(|run| run.0 >= at("2024-03-01T01:00:00Z") && run.0 <= at("2024-03-01T01:10:00Z")));
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_persists_runs_and_handles_misfires() {
+        let storage = Arc::new(StorageManager::memory());
+        let hourly = Schedule::Every(Duration::from_secs(3600));
+        let grace = Duration::from_secs(60);
+        let scheduler = Scheduler::new().with_storage(storage.clone());
+        let counter = Arc::new(AtomicU64::new(0));
+        let counted = counter.clone();
+        scheduler.add(JobSpec::new("catch-up", hourly.clone()).with_misfire(MisfirePolicy::RunOnce, grace), move || {
+            let counted = counted.clone();
+            async move {
+                counted.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+        });
+        scheduler.add(JobSpec::new("skip", hourly).with_misfire(MisfirePolicy::Skip, grace), || async { Ok(()) });
+
+        // The node was down through several planned runs
+        let now = Timestamp::now();
+        let missed = Timestamp(now.0 - chrono::Duration::hours(5));
+        storage.store_next_run("catch-up", missed).await.unwrap();
+        storage.store_next_run("skip", missed).await.unwrap();
+        assert_eq!(scheduler.restore().await.unwrap(), 2);
+
+        assert_eq!(scheduler.run_due(now), 1);
+        assert_eq!(scheduler.run_due(now), 0);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+        let jobs = scheduler.jobs();
+        assert!(jobs.iter().all(|job| job.misfires == 1 && job.next_run.unwrap() > now));
+        assert_eq!(jobs.iter().map(|job| job.runs).sum::<u64>(), 1);
+
+        scheduler.persist().await.unwrap();
+        assert_eq!(storage.get_next_run("skip").await.unwrap(), jobs[1].next_run);
+    }
+
+    #[tokio::test]
+    async fn test_started_scheduler_runs_interval_jobs() {
+        let scheduler = Arc::new(Scheduler::new());
+        scheduler.add(JobSpec::new("tick", Schedule::Every(Duration::from_millis(20))), || async {
+            Err(SolaceError::internal("always fails"))
+        });
+        let handle = scheduler.clone().start();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        handle.abort();
+        let status = &scheduler.jobs()[0];
+        assert!(status.runs >= 2, "{:?}", status);
+        assert_eq!(status.failures, status.runs - u64::from(status.running));
+
+        let cancel = CancellationToken::new();
+        let handle = scheduler.clone().start_cancellable(cancel.clone());
+        cancel.cancel();
+        tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+    }
+}
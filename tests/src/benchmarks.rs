@@ -162,6 +162,7 @@ fn bench_network_discovery(c: &mut Criterion) {
                     last_seen: chrono::Utc::now(),
                     protocol_version: "1.0.0".to_string(),
                     node_type: NodeType::Agent,
+                    rtt_ms: None,
                 };
                 
                 discovery.add_peer(peer, DiscoveryMethod::DHT).await;
@@ -6,7 +6,7 @@
 use solace_protocol::{
     Agent, AgentConfig, AgentCapability, AgentPreferences,
     Transaction, TransactionRequest, TransactionPhase, TransactionStatus,
-    ReputationScore, Balance, ServiceType, Timestamp,
+    ReputationScore, Balance, PaymentAsset, ServiceType, Timestamp,
 };
 use acp::{ACP, ACPConfig, messaging::ACPMessage};
 use tokio_test;
@@ -21,6 +21,7 @@ impl TestAgentFactory {
     pub fn create_basic_config(name: &str) -> AgentConfig {
         AgentConfig {
             keypair: None,
+            watch_key: None,
             name: name.to_string(),
             description: format!("Test agent: {}", name),
             capabilities: vec![
@@ -31,12 +32,14 @@ impl TestAgentFactory {
                 risk_tolerance: 0.5,
                 max_transaction_value: Balance::from_sol(100.0),
                 min_counterparty_reputation: 0.3,
-                preferred_payment_methods: vec!["SOL".to_string()],
+                preferred_payment_methods: vec![PaymentAsset::Sol],
                 auto_accept_threshold: 0.8,
                 geographic_preferences: None,
             },
             network_address: None,
             initial_reputation: Some(0.7),
+            legacy_id: None,
+            custom_services: Vec::new(),
         }
     }
 
@@ -0,0 +1,6 @@
+//! Solace Protocol Test Harness
+//!
+//! Shared utilities and the scenario DSL used by the integration tests.
+
+pub mod scenario;
+pub mod test_utils;
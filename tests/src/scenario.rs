@@ -0,0 +1,694 @@
+//! Scenario DSL for Solace Protocol Integration Tests
+//!
+//! Declarative description of multi-agent test scenarios. A scenario lists
+//! agents with their capabilities and budgets, scripted service requests,
+//! injected faults, and expected outcomes. Scenarios can be built in Rust
+//! with the builder API or loaded from YAML, and are executed against the
+//! `NetworkSimulator`. After every run the harness checks protocol invariants
+//! (balance conservation, budget limits, reputation bounds) in addition to
+//! the scenario's own expectations.
+//!
+//! ```yaml
+//! name: offline_provider_falls_back
+//! agents:
+//!   - name: alice
+//!     capabilities: [DataAnalysis]
+//!     budget_sol: 10.0
+//!   - name: bob
+//!     capabilities: [DataAnalysis]
+//!     reputation: 0.9
+//!   - name: carol
+//!     capabilities: [DataAnalysis]
+//! requests:
+//!   - requester: alice
+//!     service: DataAnalysis
+//!     budget_sol: 2.0
+//! faults:
+//!   - !AgentOffline { agent: bob }
+//! expectations:
+//!   - !Provider { request: 0, agent: carol }
+//! ```
+
+use solace_protocol::agent::AgentState;
+use solace_protocol::transaction::{ExecutionData, TransactionEvaluation, TransactionProposal};
+use solace_protocol::types::ServiceType;
+use solace_protocol::*;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use anyhow::{anyhow, Result};
+
+use crate::test_utils::NetworkSimulator;
+
+/// Agent declared in a scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSpec {
+    pub name: String,
+    pub capabilities: Vec<AgentCapability>,
+    #[serde(default)]
+    pub budget_sol: f64,
+    #[serde(default = "default_reputation")]
+    pub reputation: f64,
+    #[serde(default = "default_min_counterparty_reputation")]
+    pub min_counterparty_reputation: f64,
+    #[serde(default = "default_ask_ratio")]
+    pub ask_ratio: f64,                   // Quoted price as a fraction of the request budget
+}
+
+fn default_reputation() -> f64 {
+    0.7
+}
+
+fn default_min_counterparty_reputation() -> f64 {
+    0.3
+}
+
+fn default_ask_ratio() -> f64 {
+    0.9
+}
+
+impl AgentSpec {
+    /// Declare an agent with default reputation and no funds
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            capabilities: Vec::new(),
+            budget_sol: 0.0,
+            reputation: default_reputation(),
+            min_counterparty_reputation: default_min_counterparty_reputation(),
+            ask_ratio: default_ask_ratio(),
+        }
+    }
+
+    /// Add a capability
+    pub fn capability(mut self, capability: AgentCapability) -> Self {
+        self.capabilities.push(capability);
+        self
+    }
+
+    /// Set the starting wallet balance
+    pub fn budget_sol(mut self, sol: f64) -> Self {
+        self.budget_sol = sol;
+        self
+    }
+
+    /// Set the starting reputation
+    pub fn reputation(mut self, reputation: f64) -> Self {
+        self.reputation = reputation;
+        self
+    }
+
+    /// Set the minimum reputation this agent accepts from providers
+    pub fn min_counterparty_reputation(mut self, reputation: f64) -> Self {
+        self.min_counterparty_reputation = reputation;
+        self
+    }
+
+    /// Set the quoted price as a fraction of the request budget
+    pub fn ask_ratio(mut self, ratio: f64) -> Self {
+        self.ask_ratio = ratio;
+        self
+    }
+
+    /// Build the framework agent configuration
+    fn to_config(&self) -> AgentConfig {
+        AgentConfig {
+            keypair: None,
+            watch_key: None,
+            name: self.name.clone(),
+            description: format!("Scenario agent: {}", self.name),
+            capabilities: self.capabilities.clone(),
+            preferences: AgentPreferences {
+                min_counterparty_reputation: self.min_counterparty_reputation,
+                ..Default::default()
+            },
+            network_address: None,
+            initial_reputation: Some(self.reputation),
+            legacy_id: None,
+            custom_services: Vec::new(),
+        }
+    }
+}
+
+/// Service request issued during a scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptedRequest {
+    pub requester: String,
+    pub service: ServiceType,
+    pub budget_sol: f64,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_deadline_millis")]
+    pub deadline_millis: u64,             // Time the provider has to deliver
+}
+
+fn default_deadline_millis() -> u64 {
+    60 * 60 * 1000
+}
+
+impl ScriptedRequest {
+    /// Script a request from `requester`
+    pub fn new(requester: &str, service: ServiceType, budget_sol: f64) -> Self {
+        Self {
+            requester: requester.to_string(),
+            service,
+            budget_sol,
+            description: String::new(),
+            deadline_millis: default_deadline_millis(),
+        }
+    }
+
+    pub fn deadline_millis(mut self, millis: u64) -> Self {
+        self.deadline_millis = millis;
+        self
+    }
+}
+
+/// Fault injected into a scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Fault {
+    /// Agent never comes online
+    AgentOffline { agent: String },
+    /// All messages between two agents are lost
+    DropMessages { between: (String, String) },
+    /// Fixed latency between two agents
+    Latency { between: (String, String), millis: u64 },
+    /// Provider accepts work but fails during execution
+    ExecutionFailure { agent: String },
+    /// Provider accepts work but does not deliver before the deadline
+    MissedDeadline { agent: String },
+}
+
+/// Expected scenario outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Expectation {
+    /// Final status of a request, by index
+    Status { request: usize, status: TransactionStatus },
+    /// Agent that served a request
+    Provider { request: usize, agent: String },
+    /// Final balance lower bound
+    BalanceAtLeast { agent: String, sol: f64 },
+    /// Final balance upper bound
+    BalanceAtMost { agent: String, sol: f64 },
+    /// Number of completed requests
+    CompletedCount(usize),
+}
+
+/// Declarative multi-agent test scenario
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub agents: Vec<AgentSpec>,
+    #[serde(default)]
+    pub requests: Vec<ScriptedRequest>,
+    #[serde(default)]
+    pub faults: Vec<Fault>,
+    #[serde(default)]
+    pub expectations: Vec<Expectation>,
+}
+
+impl Scenario {
+    /// Start building a scenario
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Parse a scenario from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Declare an agent
+    pub fn agent(mut self, agent: AgentSpec) -> Self {
+        self.agents.push(agent);
+        self
+    }
+
+    /// Script a service request
+    pub fn request(mut self, request: ScriptedRequest) -> Self {
+        self.requests.push(request);
+        self
+    }
+
+    /// Inject a fault
+    pub fn fault(mut self, fault: Fault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    /// Add an expected outcome
+    pub fn expect(mut self, expectation: Expectation) -> Self {
+        self.expectations.push(expectation);
+        self
+    }
+
+    /// Execute the scenario, failing if any expectation or invariant is violated
+    pub async fn run(&self) -> Result<ScenarioReport> {
+        let report = ScenarioRunner::new(self).await?.execute().await?;
+
+        let mut failures = report.check_invariants();
+        failures.extend(self.expectations.iter().filter_map(|e| report.check(e)));
+
+        if failures.is_empty() {
+            Ok(report)
+        } else {
+            Err(anyhow!("Scenario '{}' failed:\n  {}", self.name, failures.join("\n  ")))
+        }
+    }
+}
+
+/// Result of a single scripted request
+#[derive(Debug, Clone)]
+pub struct RequestOutcome {
+    pub status: TransactionStatus,
+    pub provider: Option<String>,
+    pub price: Option<Balance>,
+    pub budget: Balance,
+}
+
+/// Final state of an executed scenario
+#[derive(Debug, Clone)]
+pub struct ScenarioReport {
+    pub outcomes: Vec<RequestOutcome>,
+    pub balances: HashMap<String, Balance>,
+    pub reputations: HashMap<String, f64>,
+    pub initial_supply: u64,
+}
+
+impl ScenarioReport {
+    /// Check protocol invariants that must hold for every scenario
+    pub fn check_invariants(&self) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        let final_supply: u64 = self.balances.values().map(|b| b.0).sum();
+        if final_supply != self.initial_supply {
+            failures.push(format!(
+                "balance not conserved: started with {} lamports, ended with {}",
+                self.initial_supply, final_supply
+            ));
+        }
+
+        for (index, outcome) in self.outcomes.iter().enumerate() {
+            if let Some(price) = outcome.price {
+                if price.0 > outcome.budget.0 {
+                    failures.push(format!("request {} paid {} over budget {}", index, price, outcome.budget));
+                }
+            }
+            if outcome.status == TransactionStatus::Completed && outcome.provider.is_none() {
+                failures.push(format!("request {} completed without a provider", index));
+            }
+        }
+
+        for (agent, reputation) in &self.reputations {
+            if !(0.0..=1.0).contains(reputation) {
+                failures.push(format!("{} reputation {} out of range", agent, reputation));
+            }
+        }
+
+        failures
+    }
+
+    /// Check a single expectation, returning a failure message if it does not hold
+    pub fn check(&self, expectation: &Expectation) -> Option<String> {
+        match expectation {
+            Expectation::Status { request, status } => {
+                let actual = self.outcomes.get(*request).map(|o| o.status);
+                (actual != Some(*status))
+                    .then(|| format!("request {}: expected status {:?}, got {:?}", request, status, actual))
+            }
+            Expectation::Provider { request, agent } => {
+                let actual = self.outcomes.get(*request).and_then(|o| o.provider.as_deref());
+                (actual != Some(agent.as_str()))
+                    .then(|| format!("request {}: expected provider {}, got {:?}", request, agent, actual))
+            }
+            Expectation::BalanceAtLeast { agent, sol } => {
+                let actual = self.balance_sol(agent);
+                (actual < *sol).then(|| format!("{}: expected balance >= {} SOL, got {}", agent, sol, actual))
+            }
+            Expectation::BalanceAtMost { agent, sol } => {
+                let actual = self.balance_sol(agent);
+                (actual > *sol).then(|| format!("{}: expected balance <= {} SOL, got {}", agent, sol, actual))
+            }
+            Expectation::CompletedCount(expected) => {
+                let actual = self.outcomes.iter().filter(|o| o.status == TransactionStatus::Completed).count();
+                (actual != *expected).then(|| format!("expected {} completed requests, got {}", expected, actual))
+            }
+        }
+    }
+
+    /// Final balance of an agent in SOL
+    pub fn balance_sol(&self, agent: &str) -> f64 {
+        self.balances.get(agent).map(|b| b.to_sol()).unwrap_or(0.0)
+    }
+}
+
+/// Executes a scenario against the network simulator
+struct ScenarioRunner<'a> {
+    scenario: &'a Scenario,
+    simulator: NetworkSimulator,
+    agents: HashMap<String, usize>,
+    dropped: HashSet<(String, String)>,
+    failing: HashSet<String>,
+    stalling: HashSet<String>,
+}
+
+impl<'a> ScenarioRunner<'a> {
+    /// Create agents and apply faults
+    async fn new(scenario: &'a Scenario) -> Result<Self> {
+        let mut simulator = NetworkSimulator::new();
+        // Faults are injected explicitly; random loss would make runs flaky
+        simulator.message_loss_rate = 0.0;
+
+        let mut agents = HashMap::new();
+        let mut ids = HashMap::new();
+        for spec in &scenario.agents {
+            if agents.contains_key(&spec.name) {
+                return Err(anyhow!("duplicate agent '{}'", spec.name));
+            }
+
+            let id = simulator.add_agent(spec.to_config()).await?;
+            let agent = simulator.agents.last().expect("agent was just added");
+            agent.update_balance(Balance::from_sol(spec.budget_sol)).await?;
+            agent.start().await?;
+
+            agents.insert(spec.name.clone(), simulator.agents.len() - 1);
+            ids.insert(spec.name.clone(), id);
+        }
+
+        let mut runner = Self {
+            scenario,
+            simulator,
+            agents,
+            dropped: HashSet::new(),
+            failing: HashSet::new(),
+            stalling: HashSet::new(),
+        };
+
+        for fault in &scenario.faults {
+            match fault {
+                Fault::AgentOffline { agent } => {
+                    runner.agent(agent)?.set_state(AgentState::Offline).await?;
+                }
+                Fault::DropMessages { between: (a, b) } => {
+                    runner.agent(a)?;
+                    runner.agent(b)?;
+                    runner.dropped.insert((a.clone(), b.clone()));
+                    runner.dropped.insert((b.clone(), a.clone()));
+                }
+                Fault::Latency { between: (a, b), millis } => {
+                    let (a, b) = (lookup(&ids, a)?, lookup(&ids, b)?);
+                    runner.simulator.set_latency(a, b, Duration::from_millis(*millis));
+                }
+                Fault::ExecutionFailure { agent } => {
+                    runner.agent(agent)?;
+                    runner.failing.insert(agent.clone());
+                }
+                Fault::MissedDeadline { agent } => {
+                    runner.agent(agent)?;
+                    runner.stalling.insert(agent.clone());
+                }
+            }
+        }
+
+        Ok(runner)
+    }
+
+    /// Look up a declared agent by name
+    fn agent(&self, name: &str) -> Result<&Agent> {
+        self.agents
+            .get(name)
+            .map(|&index| &self.simulator.agents[index])
+            .ok_or_else(|| anyhow!("unknown agent '{}'", name))
+    }
+
+    /// Run every scripted request in order
+    async fn execute(self) -> Result<ScenarioReport> {
+        let initial_supply = self.scenario.agents.iter().map(|a| Balance::from_sol(a.budget_sol).0).sum();
+
+        let mut outcomes = Vec::with_capacity(self.scenario.requests.len());
+        for request in &self.scenario.requests {
+            outcomes.push(self.execute_request(request).await?);
+        }
+
+        let mut balances = HashMap::new();
+        let mut reputations = HashMap::new();
+        for name in self.agents.keys() {
+            let agent = self.agent(name)?;
+            balances.insert(name.clone(), agent.get_balance().await);
+            reputations.insert(name.clone(), agent.get_reputation().await);
+        }
+
+        Ok(ScenarioReport { outcomes, balances, reputations, initial_supply })
+    }
+
+    /// Drive one request through the transaction lifecycle
+    async fn execute_request(&self, scripted: &ScriptedRequest) -> Result<RequestOutcome> {
+        let requester = self.agent(&scripted.requester)?;
+        let budget = Balance::from_sol(scripted.budget_sol);
+
+        let deadline = chrono::Utc::now() + chrono::Duration::milliseconds(scripted.deadline_millis as i64);
+        let request = TransactionRequest::new(
+            requester.id,
+            scripted.service.clone(),
+            scripted.description.clone(),
+            budget,
+            Timestamp(deadline),
+        );
+        let mut transaction = Transaction::new(request);
+
+        let Some((provider_name, provider_spec)) = self.select_provider(scripted).await? else {
+            transaction.cancel()?;
+            return Ok(outcome(&transaction, None));
+        };
+        let provider = self.agent(provider_name)?;
+        let price = Balance((budget.0 as f64 * provider_spec.ask_ratio) as u64);
+
+        if !requester.meets_requirements(0.0, price).await {
+            transaction.cancel()?;
+            return Ok(outcome(&transaction, None));
+        }
+
+        if let Some(latency) = self.simulator.latency_matrix.get(&(requester.id, provider.id)) {
+            tokio::time::sleep(*latency).await;
+        }
+
+        transaction.add_proposal(TransactionProposal {
+            id: TransactionId::new(),
+            request_id: transaction.id,
+            provider: provider.id,
+            proposed_price: price,
+            estimated_completion: Timestamp::now(),
+            proposal_details: format!("{} quote", provider_name),
+            terms: HashMap::new(),
+            created_at: Timestamp::now(),
+            expires_at: transaction.request.deadline,
+        })?;
+        transaction.accept_proposal(provider.id, price)?;
+
+        if self.failing.contains(provider_name) {
+            transaction.fail(format!("{} failed during execution", provider_name))?;
+            return Ok(outcome(&transaction, Some(provider_name)));
+        }
+
+        if self.stalling.contains(provider_name) {
+            let remaining = (deadline - chrono::Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(remaining + Duration::from_millis(1)).await;
+            transaction.expire()?;
+            return Ok(outcome(&transaction, Some(provider_name)));
+        }
+
+        transaction.complete_execution(ExecutionData {
+            result: "scenario result".to_string(),
+            artifacts: Vec::new(),
+            completion_time: Timestamp::now(),
+            quality_metrics: HashMap::new(),
+        })?;
+        transaction.add_evaluation(TransactionEvaluation {
+            requester_rating: 1.0,
+            provider_rating: 1.0,
+            requester_feedback: String::new(),
+            provider_feedback: String::new(),
+            quality_score: 1.0,
+            timeliness_score: 1.0,
+            overall_satisfaction: 1.0,
+        })?;
+
+        // Settle payment
+        let requester_balance = requester.get_balance().await.sub(price)
+            .ok_or_else(|| anyhow!("{} cannot cover {}", scripted.requester, price))?;
+        let provider_balance = provider.get_balance().await.add(price)
+            .ok_or_else(|| anyhow!("{} balance overflow", provider_name))?;
+        requester.update_balance(requester_balance).await?;
+        provider.update_balance(provider_balance).await?;
+
+        Ok(outcome(&transaction, Some(provider_name)))
+    }
+
+    /// Pick the most reputable reachable provider for a request
+    async fn select_provider(&self, scripted: &ScriptedRequest) -> Result<Option<(&'a str, &'a AgentSpec)>> {
+        let requester_spec = self.spec(&scripted.requester)?;
+        let mut best: Option<(&'a AgentSpec, f64)> = None;
+
+        for spec in &self.scenario.agents {
+            if spec.name == scripted.requester
+                || self.dropped.contains(&(scripted.requester.clone(), spec.name.clone()))
+            {
+                continue;
+            }
+
+            let agent = self.agent(&spec.name)?;
+            if !agent.is_available().await || !agent.can_handle_service(&scripted.service) {
+                continue;
+            }
+
+            let reputation = agent.get_reputation().await;
+            if reputation < requester_spec.min_counterparty_reputation {
+                continue;
+            }
+
+            if best.map_or(true, |(_, best_reputation)| reputation > best_reputation) {
+                best = Some((spec, reputation));
+            }
+        }
+
+        Ok(best.map(|(spec, _)| (spec.name.as_str(), spec)))
+    }
+
+    /// Look up an agent declaration by name
+    fn spec(&self, name: &str) -> Result<&'a AgentSpec> {
+        self.scenario
+            .agents
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| anyhow!("unknown agent '{}'", name))
+    }
+}
+
+/// Outcome of a request whose transaction has reached a final state; only
+/// completed requests report a price, since nothing else is paid
+fn outcome(transaction: &Transaction, provider: Option<&str>) -> RequestOutcome {
+    RequestOutcome {
+        status: transaction.status,
+        provider: provider.map(str::to_string),
+        price: transaction.agreed_price.filter(|_| transaction.status == TransactionStatus::Completed),
+        budget: transaction.request.budget,
+    }
+}
+
+/// Resolve an agent name to its id
+fn lookup(ids: &HashMap<String, AgentId>, name: &str) -> Result<AgentId> {
+    ids.get(name).copied().ok_or_else(|| anyhow!("unknown agent '{}'", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyst(name: &str) -> AgentSpec {
+        AgentSpec::new(name).capability(AgentCapability::DataAnalysis)
+    }
+
+    #[tokio::test]
+    async fn test_basic_purchase() {
+        let report = Scenario::new("basic_purchase")
+            .agent(analyst("alice").budget_sol(10.0))
+            .agent(analyst("bob").ask_ratio(0.5))
+            .request(ScriptedRequest::new("alice", ServiceType::DataAnalysis, 4.0))
+            .expect(Expectation::Status { request: 0, status: TransactionStatus::Completed })
+            .expect(Expectation::Provider { request: 0, agent: "bob".to_string() })
+            .expect(Expectation::BalanceAtLeast { agent: "bob".to_string(), sol: 2.0 })
+            .expect(Expectation::BalanceAtMost { agent: "alice".to_string(), sol: 8.0 })
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(report.outcomes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_faults_change_outcomes() {
+        Scenario::new("faults")
+            .agent(analyst("alice").budget_sol(10.0))
+            .agent(analyst("bob").reputation(0.9))
+            .agent(analyst("carol").reputation(0.8))
+            .agent(analyst("dave").reputation(0.6))
+            .request(ScriptedRequest::new("alice", ServiceType::DataAnalysis, 1.0))
+            .request(ScriptedRequest::new("alice", ServiceType::MarketResearch, 1.0))
+            .fault(Fault::AgentOffline { agent: "bob".to_string() })
+            .fault(Fault::DropMessages { between: ("alice".to_string(), "carol".to_string()) })
+            .expect(Expectation::Provider { request: 0, agent: "dave".to_string() })
+            .expect(Expectation::Status { request: 1, status: TransactionStatus::Cancelled })
+            .expect(Expectation::CompletedCount(1))
+            .run()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execution_failure_is_not_paid() {
+        Scenario::new("execution_failure")
+            .agent(analyst("alice").budget_sol(5.0))
+            .agent(analyst("bob"))
+            .request(ScriptedRequest::new("alice", ServiceType::DataAnalysis, 2.0))
+            .fault(Fault::ExecutionFailure { agent: "bob".to_string() })
+            .expect(Expectation::Status { request: 0, status: TransactionStatus::Failed })
+            .expect(Expectation::BalanceAtLeast { agent: "alice".to_string(), sol: 5.0 })
+            .run()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missed_deadline_expires_unpaid() {
+        Scenario::new("missed_deadline")
+            .agent(analyst("alice").budget_sol(5.0))
+            .agent(analyst("bob"))
+            .request(ScriptedRequest::new("alice", ServiceType::DataAnalysis, 2.0).deadline_millis(20))
+            .fault(Fault::MissedDeadline { agent: "bob".to_string() })
+            .expect(Expectation::Status { request: 0, status: TransactionStatus::Expired })
+            .expect(Expectation::BalanceAtLeast { agent: "alice".to_string(), sol: 5.0 })
+            .run()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_expectation_is_reported() {
+        let err = Scenario::new("unmet")
+            .agent(analyst("alice").budget_sol(1.0))
+            .request(ScriptedRequest::new("alice", ServiceType::DataAnalysis, 1.0))
+            .expect(Expectation::CompletedCount(1))
+            .run()
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("expected 1 completed requests, got 0"));
+    }
+
+    #[tokio::test]
+    async fn test_yaml_scenario() {
+        let scenario = Scenario::from_yaml(r#"
+name: yaml_budget_limit
+agents:
+  - name: alice
+    capabilities: [DataAnalysis]
+    budget_sol: 1.0
+  - name: bob
+    capabilities: [DataAnalysis]
+requests:
+  - requester: alice
+    service: DataAnalysis
+    budget_sol: 5.0
+faults:
+  - !Latency { between: [alice, bob], millis: 5 }
+expectations:
+  - !Status { request: 0, status: Cancelled }
+  - !BalanceAtLeast { agent: alice, sol: 1.0 }
+"#).unwrap();
+
+        scenario.run().await.unwrap();
+    }
+}
@@ -4,12 +4,39 @@
 //! test data generators, and helper functions for protocol testing.
 
 use solace_protocol::*;
+use solace_protocol::escrow::{Escrow, EscrowLedger};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::sync::OnceLock;
+
+/// Base seed for all randomized test data in this run
+///
+/// Taken from `SOLACE_SEED` when set, otherwise chosen randomly and printed
+/// so a failing run can be reproduced.
+pub fn test_seed() -> u64 {
+    static SEED: OnceLock<u64> = OnceLock::new();
+    *SEED.get_or_init(|| {
+        let seed = acp::rng::seed_from_env().unwrap_or_else(rand::random);
+        eprintln!("test RNG seed: {} (set {}={} to reproduce)", seed, acp::rng::SEED_ENV, seed);
+        seed
+    })
+}
+
+thread_local! {
+    static TEST_RNG: RefCell<StdRng> = RefCell::new(StdRng::seed_from_u64(test_seed()));
+}
+
+/// Run a closure with the calling test thread's seeded RNG
+pub fn with_test_rng<R>(f: impl FnOnce(&mut StdRng) -> R) -> R {
+    TEST_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
 
 /// Mock blockchain client for testing
 pub struct MockBlockchainClient {
@@ -51,7 +78,7 @@ impl MockBlockchainClient {
         tokio::time::sleep(self.latency).await;
         
         // Simulate occasional failures
-        if rand::random::<f64>() < self.failure_rate {
+        if with_test_rng(|rng| rng.gen::<f64>()) < self.failure_rate {
             return Err(anyhow::anyhow!("Mock transaction failed"));
         }
         
@@ -100,6 +127,38 @@ impl MockBlockchainClient {
     }
 }
 
+/// Escrow account holding a transaction's locked funds
+pub fn escrow_account(transaction_id: &TransactionId) -> String {
+    format!("escrow:{}", transaction_id)
+}
+
+#[async_trait::async_trait]
+impl EscrowLedger for MockBlockchainClient {
+    async fn lock(&self, escrow: &Escrow) -> solace_protocol::Result<String> {
+        self.transfer(&escrow.requester.to_string(), &escrow_account(&escrow.transaction_id), escrow.amount.0)
+            .await
+            .map_err(|e| SolaceError::internal(e.to_string()))
+    }
+
+    async fn release_partial(&self, escrow: &Escrow, amount: Balance) -> solace_protocol::Result<String> {
+        self.transfer(&escrow_account(&escrow.transaction_id), &escrow.provider_account, amount.0)
+            .await
+            .map_err(|e| SolaceError::internal(e.to_string()))
+    }
+
+    async fn release(&self, escrow: &Escrow) -> solace_protocol::Result<String> {
+        self.transfer(&escrow_account(&escrow.transaction_id), &escrow.provider_account, escrow.remaining().0)
+            .await
+            .map_err(|e| SolaceError::internal(e.to_string()))
+    }
+
+    async fn refund(&self, escrow: &Escrow) -> solace_protocol::Result<String> {
+        self.transfer(&escrow_account(&escrow.transaction_id), &escrow.requester.to_string(), escrow.remaining().0)
+            .await
+            .map_err(|e| SolaceError::internal(e.to_string()))
+    }
+}
+
 /// Test data generators
 pub struct TestDataGenerator;
 
@@ -114,7 +173,7 @@ impl TestDataGenerator {
         
         let selected_capabilities = capabilities
             .into_iter()
-            .filter(|_| rand::random::<bool>())
+            .filter(|_| with_test_rng(|rng| rng.gen::<bool>()))
             .collect::<Vec<_>>();
         
         AgentConfig {
@@ -126,13 +185,13 @@ impl TestDataGenerator {
                 selected_capabilities
             },
             preferences: AgentPreferences {
-                risk_tolerance: rand::random::<f64>(),
+                risk_tolerance: with_test_rng(|rng| rng.gen::<f64>()),
                 max_transaction_value: Balance::from_lamports(
-                    1000 + rand::random::<u64>() % 10000
+                    1000 + with_test_rng(|rng| rng.gen::<u64>()) % 10000
                 ),
-                min_counterparty_reputation: 0.3 + rand::random::<f64>() * 0.4,
-                preferred_payment_methods: vec!["SOL".to_string()],
-                auto_accept_threshold: 0.7 + rand::random::<f64>() * 0.2,
+                min_counterparty_reputation: 0.3 + with_test_rng(|rng| rng.gen::<f64>()) * 0.4,
+                preferred_payment_methods: vec![PaymentAsset::Sol],
+                auto_accept_threshold: 0.7 + with_test_rng(|rng| rng.gen::<f64>()) * 0.2,
                 geographic_preferences: None,
             },
             ..Default::default()
@@ -150,10 +209,10 @@ impl TestDataGenerator {
         
         ServiceRequest {
             id: TransactionId::new(),
-            service_type: service_types[rand::random::<usize>() % service_types.len()].clone(),
+            service_type: service_types[with_test_rng(|rng| rng.gen::<usize>()) % service_types.len()].clone(),
             requirements: format!("Test requirement {}", uuid::Uuid::new_v4()),
-            max_payment: Balance::from_lamports(1000 + rand::random::<u64>() % 5000),
-            deadline: chrono::Utc::now() + chrono::Duration::hours(1 + rand::random::<i64>() % 24),
+            max_payment: Balance::from_lamports(1000 + with_test_rng(|rng| rng.gen::<u64>()) % 5000),
+            deadline: chrono::Utc::now() + chrono::Duration::hours(1 + with_test_rng(|rng| rng.gen::<i64>()) % 24),
             requester_id: AgentId::new(),
         }
     }
@@ -179,10 +238,11 @@ impl TestDataGenerator {
                 address: format!("192.168.1.{}:8080", i + 1).parse().unwrap(),
                 public_key: format!("test_key_{}", i),
                 capabilities: vec!["agent".to_string(), "relay".to_string()],
-                reputation: 0.5 + rand::random::<f64>() * 0.5,
+                reputation: 0.5 + with_test_rng(|rng| rng.gen::<f64>()) * 0.5,
                 last_seen: chrono::Utc::now(),
                 protocol_version: "1.0.0".to_string(),
                 node_type: NodeType::Agent,
+                rtt_ms: None,
             })
             .collect()
     }
@@ -230,7 +290,7 @@ impl NetworkSimulator {
         }
         
         // Simulate message loss
-        if rand::random::<f64>() < self.message_loss_rate {
+        if with_test_rng(|rng| rng.gen::<f64>()) < self.message_loss_rate {
             return Err(anyhow::anyhow!("Message lost in network simulation"));
         }
         
@@ -497,7 +557,7 @@ impl TestConfigBuilder {
                 risk_tolerance: 0.8,
                 max_transaction_value: Balance::from_lamports(1000),
                 min_counterparty_reputation: 0.1,
-                preferred_payment_methods: vec!["SOL".to_string()],
+                preferred_payment_methods: vec![PaymentAsset::Sol],
                 auto_accept_threshold: 0.9,
                 geographic_preferences: None,
             },
@@ -515,6 +575,8 @@ impl TestConfigBuilder {
             heartbeat_interval: Duration::from_secs(5),
             enable_anti_entropy: true,
             compression: true,
+            journal: None,
+            rng_seed: None,
         }
     }
     
@@ -0,0 +1,477 @@
+//! Environment Diagnostics
+//!
+//! Implements `solace-agent doctor`: checks RPC reachability and version, the
+//! configured program account, the keystore, the data directory, and the
+//! bootstrap peers, then prints an actionable fix for every failed check.
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use sysinfo::{DiskExt, SystemExt};
+
+/// Bootstrap peers probed when none are configured
+pub const DEFAULT_BOOTSTRAP_PEERS: [&str; 2] = [
+    "bootstrap1.solace.network:8080",
+    "bootstrap2.solace.network:8080",
+];
+
+/// Default Solace program ID
+pub const DEFAULT_PROGRAM_ID: &str = "SoLaCeProgram1111111111111111111111111111111";
+
+/// Oldest cluster version the framework is tested against
+const MIN_CLUSTER_VERSION: (u64, u64) = (1, 17);
+
+/// Options for a doctor run
+#[derive(Debug, Clone)]
+pub struct DoctorOptions {
+    pub network: String,
+    pub rpc_url: String,
+    pub program_id: String,
+    pub keypair_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub bootstrap_peers: Vec<String>,
+    pub min_free_space_mb: u64,
+    pub timeout: Duration,
+}
+
+/// Default RPC endpoint for a network name
+pub fn default_rpc_url(network: &str) -> String {
+    match network {
+        "mainnet" | "mainnet-beta" => solace_protocol::constants::DEFAULT_MAINNET_RPC.to_string(),
+        "testnet" => "https://api.testnet.solana.com".to_string(),
+        "localnet" | "localhost" => "http://127.0.0.1:8899".to_string(),
+        _ => solace_protocol::constants::DEFAULT_DEVNET_RPC.to_string(),
+    }
+}
+
+/// Default keystore path used by the Solana CLI
+pub fn default_keypair_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".config")
+        .join("solana")
+        .join("id.json")
+}
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+/// Result of a single check with an optional fix
+#[derive(Debug, Clone)]
+pub struct CheckOutcome {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl CheckOutcome {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Ok, detail: detail.into(), fix: None }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Warn, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Fail, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn skipped(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Skipped, detail: detail.into(), fix: None }
+    }
+}
+
+/// Runs the environment checks
+pub struct Doctor {
+    options: DoctorOptions,
+}
+
+impl Doctor {
+    pub fn new(options: DoctorOptions) -> Self {
+        Self { options }
+    }
+
+    /// Run every check in order
+    pub async fn run(&self) -> Vec<CheckOutcome> {
+        let client = RpcClient::new_with_timeout(self.options.rpc_url.clone(), self.options.timeout);
+
+        let rpc = self.check_rpc(&client).await;
+        let program = if rpc.status == CheckStatus::Fail {
+            CheckOutcome::skipped("program", "RPC unreachable")
+        } else {
+            self.check_program(&client).await
+        };
+
+        vec![
+            rpc,
+            program,
+            self.check_keystore(),
+            self.check_data_dir(),
+            self.check_bootstrap_peers().await,
+        ]
+    }
+
+    /// RPC endpoint reachability and cluster version
+    async fn check_rpc(&self, client: &RpcClient) -> CheckOutcome {
+        let url = &self.options.rpc_url;
+        let version = match client.get_version().await {
+            Ok(version) => version.solana_core,
+            Err(e) => {
+                return CheckOutcome::fail(
+                    "rpc",
+                    format!("{} unreachable: {}", url, e),
+                    format!(
+                        "Check network connectivity and firewall rules, or pass --rpc-url for a \
+                         reachable {} endpoint",
+                        self.options.network
+                    ),
+                );
+            }
+        };
+
+        let mut parts = version.split('.').map(|part| part.parse::<u64>().unwrap_or(0));
+        let (major, minor) = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+        if (major, minor) < MIN_CLUSTER_VERSION {
+            return CheckOutcome::warn(
+                "rpc",
+                format!("{} reachable, cluster version {} is older than {}.{}",
+                    url, version, MIN_CLUSTER_VERSION.0, MIN_CLUSTER_VERSION.1),
+                "Point --rpc-url at an up-to-date cluster or upgrade your local validator",
+            );
+        }
+
+        CheckOutcome::ok("rpc", format!("{} reachable, cluster version {}", url, version))
+    }
+
+    /// Program ID is valid and deployed on the target cluster
+    async fn check_program(&self, client: &RpcClient) -> CheckOutcome {
+        let program_id = match Pubkey::from_str(&self.options.program_id) {
+            Ok(program_id) => program_id,
+            Err(e) => {
+                return CheckOutcome::fail(
+                    "program",
+                    format!("invalid program ID '{}': {}", self.options.program_id, e),
+                    "Pass a base58 program ID with --program-id",
+                );
+            }
+        };
+
+        let deploy_fix = format!(
+            "Deploy the Solace program with `anchor deploy --provider.cluster {}` or pass the \
+             deployed address with --program-id",
+            self.options.network
+        );
+
+        match client.get_account_with_commitment(&program_id, CommitmentConfig::confirmed()).await {
+            Ok(response) => match response.value {
+                Some(account) if account.executable => {
+                    CheckOutcome::ok("program", format!("{} deployed on {}", program_id, self.options.network))
+                }
+                Some(_) => CheckOutcome::fail(
+                    "program",
+                    format!("{} exists on {} but is not executable", program_id, self.options.network),
+                    deploy_fix,
+                ),
+                None => CheckOutcome::fail(
+                    "program",
+                    format!("{} not found on {}", program_id, self.options.network),
+                    deploy_fix,
+                ),
+            },
+            Err(e) => CheckOutcome::fail(
+                "program",
+                format!("failed to fetch {}: {}", program_id, e),
+                "Retry, or check that the RPC endpoint allows getAccountInfo",
+            ),
+        }
+    }
+
+    /// Keystore exists, parses, and has tight permissions
+    fn check_keystore(&self) -> CheckOutcome {
+        let path = &self.options.keypair_path;
+        let keygen_fix = format!("Create a keypair with `solana-keygen new -o {}` or pass --keypair", path.display());
+
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                return CheckOutcome::fail("keystore", format!("cannot read {}: {}", path.display(), e), keygen_fix);
+            }
+        };
+
+        let keypair = serde_json::from_str::<Vec<u8>>(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| Keypair::from_bytes(&bytes).map_err(|e| e.to_string()));
+        let keypair = match keypair {
+            Ok(keypair) => keypair,
+            Err(e) => {
+                return CheckOutcome::fail(
+                    "keystore",
+                    format!("{} is not a valid keypair: {}", path.display(), e),
+                    keygen_fix,
+                );
+            }
+        };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            if let Ok(metadata) = std::fs::metadata(path) {
+                if metadata.permissions().mode() & 0o077 != 0 {
+                    return CheckOutcome::warn(
+                        "keystore",
+                        format!("{} unlocked ({}) but readable by other users", path.display(), keypair.pubkey()),
+                        format!("Restrict access with `chmod 600 {}`", path.display()),
+                    );
+                }
+            }
+        }
+
+        CheckOutcome::ok("keystore", format!("{} unlocked ({})", path.display(), keypair.pubkey()))
+    }
+
+    /// Data directory is writable and has enough free space
+    fn check_data_dir(&self) -> CheckOutcome {
+        let dir = &self.options.data_dir;
+        let probe = dir.join(".solace-doctor-probe");
+
+        if let Err(e) = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&probe, b"ok")) {
+            return CheckOutcome::fail(
+                "data-dir",
+                format!("{} is not writable: {}", dir.display(), e),
+                format!("Fix ownership with `chown -R $(whoami) {}` or pass --config with a writable directory", dir.display()),
+            );
+        }
+        let _ = std::fs::remove_file(&probe);
+
+        let Some(available) = available_space(dir) else {
+            return CheckOutcome::warn(
+                "data-dir",
+                format!("{} writable, free space unknown", dir.display()),
+                "Verify free disk space manually",
+            );
+        };
+
+        let available_mb = available / (1024 * 1024);
+        if available_mb < self.options.min_free_space_mb {
+            return CheckOutcome::fail(
+                "data-dir",
+                format!("{} writable but only {} MB free", dir.display(), available_mb),
+                format!("Free up space or move the data directory to a volume with at least {} MB", self.options.min_free_space_mb),
+            );
+        }
+
+        CheckOutcome::ok("data-dir", format!("{} writable, {} MB free", dir.display(), available_mb))
+    }
+
+    /// Bootstrap peers accept TCP connections
+    async fn check_bootstrap_peers(&self) -> CheckOutcome {
+        let peers = &self.options.bootstrap_peers;
+        if peers.is_empty() {
+            return CheckOutcome::warn(
+                "bootstrap",
+                "no bootstrap peers configured",
+                "Pass --bootstrap host:port for at least one known peer",
+            );
+        }
+
+        let mut unreachable = Vec::new();
+        for peer in peers {
+            let connect = tokio::net::TcpStream::connect(peer.as_str());
+            match tokio::time::timeout(self.options.timeout, connect).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => unreachable.push(format!("{} ({})", peer, e)),
+                Err(_) => unreachable.push(format!("{} (timed out)", peer)),
+            }
+        }
+
+        let reachable = peers.len() - unreachable.len();
+        match (reachable, unreachable.is_empty()) {
+            (_, true) => CheckOutcome::ok("bootstrap", format!("{}/{} peers reachable", reachable, peers.len())),
+            (0, _) => CheckOutcome::fail(
+                "bootstrap",
+                format!("no peers reachable: {}", unreachable.join(", ")),
+                "Check outbound TCP access to the peer ports, or pass --bootstrap with peers you can reach",
+            ),
+            _ => CheckOutcome::warn(
+                "bootstrap",
+                format!("{}/{} peers reachable, unreachable: {}", reachable, peers.len(), unreachable.join(", ")),
+                "Remove stale peers from your bootstrap list",
+            ),
+        }
+    }
+}
+
+/// Free space on the disk holding `path`, in bytes
+fn available_space(path: &Path) -> Option<u64> {
+    let path = path.canonicalize().ok()?;
+    let mut sys = sysinfo::System::new();
+    sys.refresh_disks_list();
+
+    // The disk with the longest mount point containing the path holds it
+    sys.disks()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Print check results with fixes for anything that failed
+pub fn print_report(outcomes: &[CheckOutcome]) {
+    println!("🩺 Solace Doctor");
+    println!("────────────────");
+
+    for outcome in outcomes {
+        let icon = match outcome.status {
+            CheckStatus::Ok => "✅",
+            CheckStatus::Warn => "⚠️ ",
+            CheckStatus::Fail => "❌",
+            CheckStatus::Skipped => "⏭️ ",
+        };
+        println!("{} {:<10} {}", icon, outcome.name, outcome.detail);
+        if let Some(fix) = &outcome.fix {
+            println!("   ↳ fix: {}", fix);
+        }
+    }
+
+    let failed = outcomes.iter().filter(|o| o.status == CheckStatus::Fail).count();
+    let warned = outcomes.iter().filter(|o| o.status == CheckStatus::Warn).count();
+    println!();
+    if failed == 0 && warned == 0 {
+        println!("All checks passed.");
+    } else {
+        println!("{} failed, {} warnings.", failed, warned);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("solace-doctor-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn options(dir: &Path) -> DoctorOptions {
+        DoctorOptions {
+            network: "localnet".to_string(),
+            rpc_url: default_rpc_url("localnet"),
+            program_id: DEFAULT_PROGRAM_ID.to_string(),
+            keypair_path: dir.join("id.json"),
+            data_dir: dir.join("data"),
+            bootstrap_peers: Vec::new(),
+            min_free_space_mb: 0,
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    fn write_keypair(path: &Path) {
+        let bytes = Keypair::new().to_bytes().to_vec();
+        std::fs::write(path, serde_json::to_string(&bytes).unwrap()).unwrap();
+    }
+
+    #[cfg(unix)]
+    fn set_mode(path: &Path, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn test_keystore_outcomes() {
+        let dir = scratch_dir("keystore");
+        let opts = options(&dir);
+
+        let missing = Doctor::new(opts.clone()).check_keystore();
+        assert_eq!(missing.status, CheckStatus::Fail);
+        assert!(missing.fix.is_some());
+
+        std::fs::write(&opts.keypair_path, "not a keypair").unwrap();
+        assert_eq!(Doctor::new(opts.clone()).check_keystore().status, CheckStatus::Fail);
+
+        write_keypair(&opts.keypair_path);
+        #[cfg(unix)]
+        {
+            set_mode(&opts.keypair_path, 0o644);
+            let loose = Doctor::new(opts.clone()).check_keystore();
+            assert_eq!(loose.status, CheckStatus::Warn);
+            assert!(loose.fix.unwrap().contains("chmod 600"));
+
+            set_mode(&opts.keypair_path, 0o600);
+        }
+        let ok = Doctor::new(opts).check_keystore();
+        assert_eq!(ok.status, CheckStatus::Ok);
+        assert!(ok.fix.is_none());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_data_dir_outcomes() {
+        let dir = scratch_dir("data");
+        let mut opts = options(&dir);
+
+        // Space is reported as unknown when no disk holds the path
+        let known = {
+            std::fs::create_dir_all(&opts.data_dir).unwrap();
+            available_space(&opts.data_dir).is_some()
+        };
+        let roomy = Doctor::new(opts.clone()).check_data_dir();
+        assert_eq!(roomy.status, if known { CheckStatus::Ok } else { CheckStatus::Warn });
+        assert!(!opts.data_dir.join(".solace-doctor-probe").exists());
+
+        opts.min_free_space_mb = u64::MAX;
+        let full = Doctor::new(opts.clone()).check_data_dir();
+        assert_eq!(full.status, if known { CheckStatus::Fail } else { CheckStatus::Warn });
+
+        // A file where the directory should be is never writable
+        opts.min_free_space_mb = 0;
+        opts.data_dir = dir.join("occupied");
+        std::fs::write(&opts.data_dir, b"file").unwrap();
+        let blocked = Doctor::new(opts).check_data_dir();
+        assert_eq!(blocked.status, CheckStatus::Fail);
+        assert!(blocked.detail.contains("not writable"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_peer_outcomes() {
+        let dir = scratch_dir("bootstrap");
+        let mut opts = options(&dir);
+
+        assert_eq!(Doctor::new(opts.clone()).check_bootstrap_peers().await.status, CheckStatus::Warn);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let live = listener.local_addr().unwrap().to_string();
+        let dead = {
+            let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            closed.local_addr().unwrap().to_string()
+        };
+
+        opts.bootstrap_peers = vec![live.clone()];
+        assert_eq!(Doctor::new(opts.clone()).check_bootstrap_peers().await.status, CheckStatus::Ok);
+
+        opts.bootstrap_peers = vec![dead.clone()];
+        let none = Doctor::new(opts.clone()).check_bootstrap_peers().await;
+        assert_eq!(none.status, CheckStatus::Fail);
+        assert!(none.detail.contains(&dead));
+
+        opts.bootstrap_peers = vec![live, dead.clone()];
+        let some = Doctor::new(opts).check_bootstrap_peers().await;
+        assert_eq!(some.status, CheckStatus::Warn);
+        assert!(some.detail.starts_with("1/2 peers reachable"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
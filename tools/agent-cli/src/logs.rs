@@ -0,0 +1,373 @@
+//! Daemon Log Streaming
+//!
+//! A running agent captures its structured logs in a `LogHub` and serves them
+//! over a Unix domain socket next to its configuration. `solace-agent logs`
+//! connects to that socket, sends a `LogQuery` as one JSON line, and prints
+//! the matching records as they arrive, filtered by level, module, and
+//! correlation ID (e.g. `transaction:<id>`).
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context as LayerContext;
+use tracing_subscriber::Layer;
+
+/// Field that carries an explicit correlation ID
+const CORRELATION_FIELD: &str = "correlation_id";
+
+/// Socket path for an agent's log stream
+pub fn socket_path(config_dir: &Path, agent: &str) -> PathBuf {
+    config_dir.join(format!("{}.logs.sock", agent))
+}
+
+/// One structured log record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub level: String,
+    pub module: String,
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl LogRecord {
+    /// Parsed level, defaulting to TRACE for unknown values
+    fn level(&self) -> Level {
+        Level::from_str(&self.level).unwrap_or(Level::TRACE)
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:>5} {}: {}",
+            self.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            self.level,
+            self.module,
+            self.message
+        )?;
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Correlation filter such as `transaction:<id>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Correlation {
+    pub kind: String,
+    pub id: String,
+}
+
+impl FromStr for Correlation {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.split_once(':') {
+            Some((kind, id)) if !kind.is_empty() && !id.is_empty() => Ok(Self {
+                kind: kind.to_string(),
+                id: id.to_string(),
+            }),
+            _ => Err(anyhow!("filter must look like <kind>:<id>, e.g. transaction:<id>")),
+        }
+    }
+}
+
+/// Log subscription sent by the client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogQuery {
+    pub level: String,
+    pub module: Option<String>,
+    pub correlation: Option<Correlation>,
+    pub follow: bool,
+    pub tail: usize,
+}
+
+impl LogQuery {
+    /// Whether a record passes every filter
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        let max_level = Level::from_str(&self.level).unwrap_or(Level::INFO);
+        if record.level() > max_level {
+            return false;
+        }
+
+        if let Some(module) = &self.module {
+            if !record.module.starts_with(module.as_str()) {
+                return false;
+            }
+        }
+
+        match &self.correlation {
+            Some(correlation) => {
+                // `transaction:<id>` matches transaction_id, transaction, or correlation_id
+                let keys = [format!("{}_id", correlation.kind), correlation.kind.clone(), CORRELATION_FIELD.to_string()];
+                keys.iter().any(|key| record.fields.get(key) == Some(&correlation.id))
+            }
+            None => true,
+        }
+    }
+}
+
+/// In-process fan-out of log records with a bounded backlog
+#[derive(Debug)]
+pub struct LogHub {
+    sender: broadcast::Sender<LogRecord>,
+    backlog: parking_lot::Mutex<VecDeque<LogRecord>>,
+    capacity: usize,
+}
+
+impl LogHub {
+    /// Hub keeping the last `capacity` records, at least one
+    pub fn new(capacity: usize) -> Arc<Self> {
+        let capacity = capacity.max(1);
+        let (sender, _) = broadcast::channel(capacity);
+        Arc::new(Self {
+            sender,
+            backlog: parking_lot::Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        })
+    }
+
+    /// Record and broadcast a log entry
+    fn publish(&self, record: LogRecord) {
+        let mut backlog = self.backlog.lock();
+        while backlog.len() >= self.capacity {
+            backlog.pop_front();
+        }
+        backlog.push_back(record.clone());
+        drop(backlog);
+
+        // No subscribers is not an error
+        let _ = self.sender.send(record);
+    }
+
+    /// Most recent `count` records matching the query
+    fn recent(&self, query: &LogQuery) -> Vec<LogRecord> {
+        let backlog = self.backlog.lock();
+        let mut matching: Vec<LogRecord> = backlog
+            .iter()
+            .rev()
+            .filter(|record| query.matches(record))
+            .take(query.tail)
+            .cloned()
+            .collect();
+        matching.reverse();
+        matching
+    }
+
+    /// Tracing layer that feeds this hub
+    pub fn layer(self: &Arc<Self>) -> LogHubLayer {
+        LogHubLayer { hub: self.clone() }
+    }
+}
+
+/// Tracing layer that turns events into `LogRecord`s
+pub struct LogHubLayer {
+    hub: Arc<LogHub>,
+}
+
+impl<S: Subscriber> Layer<S> for LogHubLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+
+        self.hub.publish(LogRecord {
+            timestamp: chrono::Utc::now(),
+            level: metadata.level().to_string(),
+            module: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        });
+    }
+}
+
+/// Collects the message and fields of an event
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for RecordVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+/// Serve the hub's records on a Unix socket until the task is dropped
+#[cfg(unix)]
+pub async fn serve(hub: Arc<LogHub>, path: PathBuf) -> Result<()> {
+    use tokio::net::UnixListener;
+
+    // A stale socket from a crashed run would make bind fail
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to bind log socket {}", path.display()))?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let hub = hub.clone();
+        tokio::spawn(async move {
+            if let Err(e) = stream_to_client(hub, stream).await {
+                tracing::debug!("Log client disconnected: {}", e);
+            }
+        });
+    }
+}
+
+/// Serve the hub's records on a Unix socket until the task is dropped
+#[cfg(not(unix))]
+pub async fn serve(_hub: Arc<LogHub>, _path: PathBuf) -> Result<()> {
+    Err(anyhow!("Log streaming requires Unix domain sockets"))
+}
+
+/// Answer one client: backlog first, then live records if following
+#[cfg(unix)]
+async fn stream_to_client(hub: Arc<LogHub>, stream: tokio::net::UnixStream) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+    let query: LogQuery = serde_json::from_str(&line).context("Invalid log query")?;
+
+    // Subscribe before reading the backlog so nothing falls in between
+    let mut live = hub.sender.subscribe();
+    for record in hub.recent(&query) {
+        writer.write_all(format!("{}\n", serde_json::to_string(&record)?).as_bytes()).await?;
+    }
+    if !query.follow {
+        return Ok(());
+    }
+
+    loop {
+        match live.recv().await {
+            Ok(record) if query.matches(&record) => {
+                writer.write_all(format!("{}\n", serde_json::to_string(&record)?).as_bytes()).await?;
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                writer.write_all(format!("{}\n", serde_json::to_string(&lagged_notice(skipped))?).as_bytes()).await?;
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+/// Record telling the client that records were dropped
+#[cfg(unix)]
+fn lagged_notice(skipped: u64) -> LogRecord {
+    LogRecord {
+        timestamp: chrono::Utc::now(),
+        level: Level::WARN.to_string(),
+        module: "solace_agent_cli::logs".to_string(),
+        message: format!("{} log records dropped, client too slow", skipped),
+        fields: BTreeMap::new(),
+    }
+}
+
+/// Connect to a running agent and print its logs
+#[cfg(unix)]
+pub async fn tail(path: &Path, query: &LogQuery) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    let stream = UnixStream::connect(path).await.with_context(|| {
+        format!("No running agent at {} (start it with `solace-agent start`)", path.display())
+    })?;
+    let (reader, mut writer) = stream.into_split();
+    writer.write_all(format!("{}\n", serde_json::to_string(query)?).as_bytes()).await?;
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        match serde_json::from_str::<LogRecord>(&line) {
+            Ok(record) => println!("{}", record),
+            Err(_) => println!("{}", line),
+        }
+    }
+    Ok(())
+}
+
+/// Connect to a running agent and print its logs
+#[cfg(not(unix))]
+pub async fn tail(_path: &Path, _query: &LogQuery) -> Result<()> {
+    Err(anyhow!("Log streaming requires Unix domain sockets"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: &str, module: &str, fields: &[(&str, &str)]) -> LogRecord {
+        LogRecord {
+            timestamp: chrono::Utc::now(),
+            level: level.to_string(),
+            module: module.to_string(),
+            message: "test".to_string(),
+            fields: fields.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    fn query(level: &str) -> LogQuery {
+        LogQuery { level: level.to_string(), module: None, correlation: None, follow: false, tail: 10 }
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let mut q = query("info");
+        assert!(q.matches(&record("WARN", "solace_protocol::agent", &[])));
+        assert!(!q.matches(&record("DEBUG", "solace_protocol::agent", &[])));
+
+        q.module = Some("solace_protocol::transaction".to_string());
+        assert!(!q.matches(&record("INFO", "solace_protocol::agent", &[])));
+
+        q.module = None;
+        q.correlation = Some("transaction:abc".parse().unwrap());
+        assert!(q.matches(&record("INFO", "m", &[("transaction_id", "abc")])));
+        assert!(q.matches(&record("INFO", "m", &[("correlation_id", "abc")])));
+        assert!(!q.matches(&record("INFO", "m", &[("transaction_id", "xyz")])));
+    }
+
+    #[test]
+    fn test_hub_backlog_is_bounded() {
+        let hub = LogHub::new(3);
+        for i in 0..5 {
+            hub.publish(record("INFO", "m", &[("seq", &i.to_string())]));
+        }
+
+        let recent = hub.recent(&query("info"));
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].fields["seq"], "2");
+
+        // A zero capacity still bounds the backlog
+        let hub = LogHub::new(0);
+        for i in 0..5 {
+            hub.publish(record("INFO", "m", &[("seq", &i.to_string())]));
+        }
+        let recent = hub.recent(&query("info"));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].fields["seq"], "4");
+    }
+}
@@ -7,14 +7,27 @@
 
 use clap::{Parser, Subcommand};
 use solace_protocol::{
-    Agent, AgentConfig, AgentCapability, AgentPreferences, Balance, ServiceType,
+    Agent, AgentConfig, AgentCapability, AgentPreferences, Balance, ServiceType, Timestamp,
 };
+use solace_protocol::archive::{ArchiveConfig, TransactionArchive};
+use solace_protocol::storage::StorageManager;
+#[cfg(feature = "storage")]
+use solace_protocol::storage::StorageConfig;
 use anyhow::{Context, Result};
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio;
 use tracing::{info, warn, error};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
 use serde::{Deserialize, Serialize};
 
+mod doctor;
+mod logs;
+mod market;
+mod model;
+mod search;
+
 #[derive(Parser)]
 #[command(name = "solace-agent")]
 #[command(about = "Solace Protocol Agent Management CLI")]
@@ -48,7 +61,7 @@ enum Commands {
         #[arg(short, long)]
         description: Option<String>,
         
-        /// Agent capabilities (comma-separated)
+        /// Agent capabilities (comma-separated registry keys, e.g. data_analysis,custom.translation)
         #[arg(short = 'c', long, value_delimiter = ',')]
         capabilities: Vec<String>,
         
@@ -73,6 +86,10 @@ enum Commands {
         /// Run in background/daemon mode
         #[arg(short, long)]
         daemon: bool,
+        
+        /// Listen address for the agent API (transaction search, market analytics)
+        #[arg(long, default_value = search::DEFAULT_API_ADDR)]
+        api_addr: std::net::SocketAddr,
     },
     
     /// Stop an agent
@@ -110,6 +127,109 @@ enum Commands {
         /// Number of recent transactions to show
         #[arg(short, long, default_value = "10")]
         limit: usize,
+        
+        /// Only transactions with this tag: project=x, cost-center=x, environment=x, or a label
+        #[arg(long)]
+        tag: Option<String>,
+        
+        /// Total every transaction by tag, project, cost-center, or environment
+        #[arg(long)]
+        group_by: Option<String>,
+        
+        /// Print raw JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Search an agent's transactions by text and filters
+    Search {
+        /// Agent name
+        agent: String,
+        
+        /// Free-text query over descriptions, requirements, proposals, and feedback
+        query: Option<String>,
+        
+        /// Transaction status (pending, in-progress, completed, failed, cancelled, expired)
+        #[arg(short, long)]
+        status: Option<String>,
+        
+        /// Transaction phase (request, negotiation, execution, evaluation)
+        #[arg(long)]
+        phase: Option<String>,
+        
+        /// Service type, e.g. data-analysis
+        #[arg(long)]
+        service: Option<String>,
+        
+        /// Requester agent ID
+        #[arg(long)]
+        requester: Option<String>,
+        
+        /// Provider agent ID
+        #[arg(long)]
+        provider: Option<String>,
+        
+        /// Minimum price in SOL
+        #[arg(long)]
+        min_price: Option<f64>,
+        
+        /// Maximum price in SOL
+        #[arg(long)]
+        max_price: Option<f64>,
+        
+        /// Only transactions created after this RFC 3339 time
+        #[arg(long)]
+        after: Option<String>,
+        
+        /// Only transactions created before this RFC 3339 time
+        #[arg(long)]
+        before: Option<String>,
+        
+        /// Only transactions with this tag: project=x, cost-center=x, environment=x, or a label
+        #[arg(long)]
+        tag: Option<String>,
+        
+        /// Maximum number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+        
+        /// Print raw JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Show per-service market statistics observed by an agent
+    Market {
+        /// Agent name
+        agent: String,
+        
+        /// Only this service type, e.g. data-analysis
+        #[arg(long)]
+        service: Option<String>,
+        
+        /// Print raw JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Move an agent's old finished transactions into compressed archive segments
+    Archive {
+        /// Agent name (the agent must be stopped)
+        agent: String,
+        
+        /// Archive transactions finished more than this many days ago
+        #[arg(long, default_value = "90")]
+        older_than_days: u64,
+        
+        /// Print raw JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Export, import, or batch-train an agent's negotiation model
+    Model {
+        #[command(subcommand)]
+        action: ModelCommands,
     },
     
     /// Update agent configuration
@@ -144,6 +264,89 @@ enum Commands {
         #[command(subcommand)]
         benchmark_type: BenchmarkCommands,
     },
+    
+    /// Stream logs from a running agent
+    Logs {
+        /// Agent name
+        agent: String,
+        
+        /// Keep streaming new records
+        #[arg(short, long)]
+        follow: bool,
+        
+        /// Most verbose level to show (error, warn, info, debug, trace)
+        #[arg(short, long, default_value = "info")]
+        level: String,
+        
+        /// Only show records from modules starting with this prefix
+        #[arg(short, long)]
+        module: Option<String>,
+        
+        /// Correlation filter, e.g. transaction:<id>
+        #[arg(long)]
+        filter: Option<String>,
+        
+        /// Number of recent records to show first
+        #[arg(long, default_value = "100")]
+        lines: usize,
+    },
+    
+    /// Diagnose the local environment and suggest fixes
+    Doctor {
+        /// RPC endpoint (defaults to the endpoint for --network)
+        #[arg(long)]
+        rpc_url: Option<String>,
+        
+        /// Solace program ID expected on the cluster
+        #[arg(long, default_value = doctor::DEFAULT_PROGRAM_ID)]
+        program_id: String,
+        
+        /// Keystore path (defaults to ~/.config/solana/id.json)
+        #[arg(long)]
+        keypair: Option<PathBuf>,
+        
+        /// Bootstrap peers to probe (host:port, comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        bootstrap: Vec<String>,
+        
+        /// Minimum free disk space in MB
+        #[arg(long, default_value = "1024")]
+        min_free_space_mb: u64,
+        
+        /// Network timeout per check in seconds
+        #[arg(long, default_value = "5")]
+        timeout: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ModelCommands {
+    /// Write an agent's learned model to a file
+    Export {
+        /// Agent name
+        agent: String,
+        
+        /// Output file
+        output: PathBuf,
+    },
+    
+    /// Replace an agent's model with an exported one
+    Import {
+        /// Agent name
+        agent: String,
+        
+        /// Exported model file
+        input: PathBuf,
+    },
+    
+    /// Train an agent's model on a JSON array of labeled outcomes
+    Train {
+        /// Agent name
+        agent: String,
+        
+        /// Labeled outcomes file
+        history: PathBuf,
+    },
 }
 
 #[derive(Subcommand)]
@@ -210,14 +413,16 @@ struct CliApp {
     config_dir: PathBuf,
     network: String,
     verbose: bool,
+    log_hub: Arc<logs::LogHub>,
 }
 
 impl CliApp {
-    fn new(config_dir: PathBuf, network: String, verbose: bool) -> Self {
+    fn new(config_dir: PathBuf, network: String, verbose: bool, log_hub: Arc<logs::LogHub>) -> Self {
         Self {
             config_dir,
             network,
             verbose,
+            log_hub,
         }
     }
 
@@ -244,6 +449,10 @@ impl CliApp {
             return Err(anyhow::anyhow!("Minimum reputation must be between 0.0 and 1.0"));
         }
 
+        if let Some(unknown) = config.capabilities.iter().find(|capability| ServiceType::from_key(capability).is_none()) {
+            return Err(anyhow::anyhow!("Unknown capability '{}': use a registry key such as data_analysis, or custom.<name>", unknown));
+        }
+
         // Save configuration
         let config_path = self.config_dir.join(format!("{}.toml", args.name));
         let config_content = toml::to_string_pretty(&config)?;
@@ -264,25 +473,90 @@ impl CliApp {
         Ok(())
     }
 
-    async fn start_agent(&self, agent_name: &str, daemon: bool) -> Result<()> {
-        info!("Starting agent: {}", agent_name);
+    async fn start_agent(&self, agent_name: &str, daemon: bool, api_addr: std::net::SocketAddr) -> Result<()> {
+        info!(agent = agent_name, "Starting agent: {}", agent_name);
 
         let config_path = self.config_dir.join(format!("{}.toml", agent_name));
         if !config_path.exists() {
             return Err(anyhow::anyhow!("Agent configuration not found: {}", agent_name));
         }
 
+        // Serve structured logs to `solace-agent logs` while the agent runs
+        let socket_path = logs::socket_path(&self.config_dir, agent_name);
+        let log_server = tokio::spawn(logs::serve(self.log_hub.clone(), socket_path.clone()));
+        
+        // Serve transaction search and market analytics over the agent's persisted transactions
+        let storage = Arc::new(self.open_storage(agent_name)?);
+        let api_file = search::api_addr_path(&self.config_dir, agent_name);
+        let api_server = tokio::spawn(search::serve(storage, api_addr, api_file.clone()));
+
         if daemon {
             println!("🚀 Agent '{}' started in daemon mode", agent_name);
         } else {
             println!("🚀 Agent '{}' started", agent_name);
-            println!("Press Ctrl+C to stop...");
-            
-            // Wait for shutdown signal
-            tokio::signal::ctrl_c().await?;
-            println!("🛑 Agent '{}' stopped", agent_name);
         }
+        println!("📜 Logs: solace-agent logs {} --follow", agent_name);
+        println!("🔍 Search API: http://{}/v1/transactions/search", api_addr);
+        println!("📊 Market API: http://{}/v1/market/analytics", api_addr);
+        println!("Press Ctrl+C to stop...");
+        
+        // Wait for shutdown signal
+        tokio::signal::ctrl_c().await?;
+        info!(agent = agent_name, "Agent stopped");
+        println!("🛑 Agent '{}' stopped", agent_name);
+
+        log_server.abort();
+        api_server.abort();
+        let _ = std::fs::remove_file(&socket_path);
+        let _ = std::fs::remove_file(&api_file);
+
+        Ok(())
+    }
+
+    /// Load an agent's saved configuration
+    fn load_agent_config(&self, agent_name: &str) -> Result<CliAgentConfig> {
+        let config_path = self.config_dir.join(format!("{}.toml", agent_name));
+        let content = std::fs::read_to_string(&config_path)
+            .with_context(|| format!("Agent configuration not found: {}", agent_name))?;
+        toml::from_str(&content).with_context(|| format!("Invalid configuration in {}", config_path.display()))
+    }
+
+    /// Open the agent's transaction store
+    #[cfg(feature = "storage")]
+    fn open_storage(&self, agent_name: &str) -> Result<StorageManager> {
+        let config = StorageConfig {
+            data_dir: self.config_dir.join(format!("{}.db", agent_name)),
+            ..StorageConfig::default()
+        };
+        StorageManager::rocksdb(&config).context("Failed to open agent storage")
+    }
 
+    /// Open the agent's transaction store
+    #[cfg(not(feature = "storage"))]
+    fn open_storage(&self, agent_name: &str) -> Result<StorageManager> {
+        warn!(agent = agent_name, "Built without persistent storage; transactions are kept in memory");
+        Ok(StorageManager::memory())
+    }
+
+    /// Archive an agent's old finished transactions and report the space reclaimed
+    async fn archive_transactions(&self, agent_name: &str, older_than_days: u64, json: bool) -> Result<()> {
+        let storage = self.open_storage(agent_name)?;
+        let config = ArchiveConfig {
+            min_age: std::time::Duration::from_secs(older_than_days * 24 * 60 * 60),
+            ..ArchiveConfig::for_data_dir(&self.config_dir.join(format!("{}.db", agent_name)))
+        };
+        let mut archive = TransactionArchive::open(config)?;
+        let report = archive.archive(&storage, Timestamp::now()).await?;
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            println!("🗄️  Archived {} transactions into {} segments", report.archived.len(), report.segments_written);
+            println!("   Live storage freed: {} bytes", report.live_bytes_freed);
+            println!("   Archive written: {} bytes", report.archive_bytes_written);
+            println!("   Reclaimed: {} bytes", report.bytes_reclaimed());
+            println!("   Archive now holds {} transactions", archive.len());
+        }
         Ok(())
     }
 
@@ -393,10 +667,15 @@ struct CreateAgentArgs {
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    // Initialize logging
+    // Initialize logging; the log hub captures debug records for `solace-agent logs`
     let log_level = if cli.verbose { "debug" } else { "info" };
-    tracing_subscriber::fmt()
-        .with_env_filter(format!("solace_agent_cli={},solace_protocol={}", log_level, log_level))
+    let log_hub = logs::LogHub::new(10_000);
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::new(format!(
+            "solace_agent_cli={},solace_protocol={}",
+            log_level, log_level
+        ))))
+        .with(log_hub.layer().with_filter(EnvFilter::new("solace_agent_cli=debug,solace_protocol=debug,acp=debug")))
         .init();
 
     // Setup configuration directory
@@ -409,7 +688,7 @@ async fn main() -> Result<()> {
     std::fs::create_dir_all(&config_dir)
         .context("Failed to create configuration directory")?;
 
-    let app = CliApp::new(config_dir, cli.network, cli.verbose);
+    let app = CliApp::new(config_dir.clone(), cli.network.clone(), cli.verbose, log_hub);
 
     match cli.command {
         Commands::Create { 
@@ -431,8 +710,8 @@ async fn main() -> Result<()> {
             app.create_agent(&args).await?;
         },
         
-        Commands::Start { agent, daemon } => {
-            app.start_agent(&agent, daemon).await?;
+        Commands::Start { agent, daemon, api_addr } => {
+            app.start_agent(&agent, daemon, api_addr).await?;
         },
         
         Commands::Stop { agent: _agent } => {
@@ -447,8 +726,62 @@ async fn main() -> Result<()> {
             println!("📊 Agent status... (implementation pending)");
         },
         
-        Commands::History { agent: _agent, limit: _limit } => {
-            println!("📈 Transaction history... (implementation pending)");
+        Commands::History { agent, limit, tag, group_by, json } => {
+            search::history(&config_dir, &agent, limit, tag, group_by.as_deref(), json).await?;
+        },
+        
+        Commands::Search { agent, query, status, phase, service, requester, provider, min_price, max_price, after, before, tag, limit, json } => {
+            let params = search::SearchParams {
+                q: query,
+                status,
+                phase,
+                service,
+                requester,
+                provider,
+                min_price,
+                max_price,
+                after,
+                before,
+                tag,
+                limit: Some(limit),
+                offset: None,
+            };
+            let results = search::query(&config_dir, &agent, &params).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+            } else {
+                search::print_results(&results);
+            }
+        },
+        
+        Commands::Market { agent, service, json } => {
+            let stats = market::query(&config_dir, &agent, service.as_deref()).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                market::print_stats(&stats);
+            }
+        },
+        
+        Commands::Archive { agent, older_than_days, json } => {
+            app.archive_transactions(&agent, older_than_days, json).await?;
+        },
+        
+        Commands::Model { action } => {
+            match action {
+                ModelCommands::Export { agent, output } => {
+                    let config = app.load_agent_config(&agent)?;
+                    model::export(&config_dir, &agent, config.risk_tolerance, &output)?;
+                }
+                ModelCommands::Import { agent, input } => {
+                    let config = app.load_agent_config(&agent)?;
+                    model::import(&config_dir, &agent, config.risk_tolerance, &input)?;
+                }
+                ModelCommands::Train { agent, history } => {
+                    let config = app.load_agent_config(&agent)?;
+                    model::train(&config_dir, &agent, config.risk_tolerance, &history)?;
+                }
+            }
         },
         
         Commands::Update { .. } => {
@@ -481,6 +814,44 @@ async fn main() -> Result<()> {
                 },
             }
         },
+        
+        Commands::Logs { agent, follow, level, module, filter, lines } => {
+            if level.parse::<tracing::Level>().is_err() {
+                return Err(anyhow::anyhow!("Unknown log level '{}' (use error, warn, info, debug, or trace)", level));
+            }
+            
+            let query = logs::LogQuery {
+                level,
+                module,
+                correlation: filter.map(|filter| filter.parse()).transpose()?,
+                follow,
+                tail: lines,
+            };
+            logs::tail(&logs::socket_path(&config_dir, &agent), &query).await?;
+        },
+        
+        Commands::Doctor { rpc_url, program_id, keypair, bootstrap, min_free_space_mb, timeout } => {
+            let options = doctor::DoctorOptions {
+                rpc_url: rpc_url.unwrap_or_else(|| doctor::default_rpc_url(&cli.network)),
+                network: cli.network,
+                program_id,
+                keypair_path: keypair.unwrap_or_else(doctor::default_keypair_path),
+                data_dir: config_dir,
+                bootstrap_peers: if bootstrap.is_empty() {
+                    doctor::DEFAULT_BOOTSTRAP_PEERS.iter().map(|peer| peer.to_string()).collect()
+                } else {
+                    bootstrap
+                },
+                min_free_space_mb,
+                timeout: std::time::Duration::from_secs(timeout),
+            };
+            
+            let outcomes = doctor::Doctor::new(options).run().await;
+            doctor::print_report(&outcomes);
+            if outcomes.iter().any(|outcome| outcome.status == doctor::CheckStatus::Fail) {
+                std::process::exit(1);
+            }
+        },
     }
 
     Ok(())
@@ -0,0 +1,89 @@
+//! Market Analytics API
+//!
+//! Alongside transaction search, a running agent aggregates its persisted
+//! transactions into per-service market statistics and serves them on the
+//! same local HTTP port. `solace-agent market` fetches and prints them.
+//!
+//! ```text
+//! GET /v1/market/analytics                  every service seen in the window
+//! GET /v1/market/analytics/data-analysis    one service type
+//! ```
+
+use anyhow::{anyhow, Result};
+use axum::extract::{Path as UrlPath, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use solace_protocol::analytics::{MarketAnalytics, ServiceMarketStats};
+use solace_protocol::Timestamp;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::search;
+
+pub type SharedAnalytics = Arc<RwLock<MarketAnalytics>>;
+
+/// Routes of the analytics API
+pub fn router(analytics: SharedAnalytics) -> Router {
+    Router::new()
+        .route("/v1/market/analytics", get(all_handler))
+        .route("/v1/market/analytics/{service}", get(service_handler))
+        .with_state(analytics)
+}
+
+async fn all_handler(State(analytics): State<SharedAnalytics>) -> Json<Vec<ServiceMarketStats>> {
+    Json(analytics.read().await.all_stats(Timestamp::now()))
+}
+
+async fn service_handler(
+    State(analytics): State<SharedAnalytics>,
+    UrlPath(service): UrlPath<String>,
+) -> Json<ServiceMarketStats> {
+    let service_type = search::parse_service_type(&service);
+    Json(analytics.read().await.stats(&service_type, Timestamp::now()))
+}
+
+/// Query a running agent's market analytics, for one service or all of them
+pub async fn query(config_dir: &Path, agent: &str, service: Option<&str>) -> Result<Vec<ServiceMarketStats>> {
+    let addr = search::running_api_addr(config_dir, agent)?;
+    let url = match service {
+        Some(service) => format!("http://{}/v1/market/analytics/{}", addr, service),
+        None => format!("http://{}/v1/market/analytics", addr),
+    };
+
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| anyhow!("Agent '{}' is not answering on {}: {}", agent, addr, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(anyhow!("Market query failed ({}): {}", status, response.text().await.unwrap_or_default()));
+    }
+    if service.is_some() {
+        Ok(vec![response.json().await?])
+    } else {
+        Ok(response.json().await?)
+    }
+}
+
+/// Print market statistics, one block per service
+pub fn print_stats(stats: &[ServiceMarketStats]) {
+    if stats.iter().all(|s| s.requests == 0) {
+        println!("📊 No market activity observed");
+        return;
+    }
+
+    for s in stats {
+        println!("\n📊 {} (last {}h)", s.service_type, s.window_secs / 3600);
+        println!("   Requests: {} ({:.1}/h), fills: {}, unfilled: {}", s.requests, s.demand_per_hour, s.fills, s.unfilled);
+        if let Some(rate) = s.fill_rate {
+            println!("   Fill rate: {:.1}%", rate * 100.0);
+        }
+        if let Some(price) = s.average_clearing_price {
+            println!("   Average clearing price: {}", price);
+        }
+        if let Some(secs) = s.median_time_to_fill_secs {
+            println!("   Median time to fill: {:.0}s", secs);
+        }
+        println!("   Proposals per request: {:.2}", s.average_proposals);
+    }
+}
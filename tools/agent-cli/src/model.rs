@@ -0,0 +1,111 @@
+//! Negotiation Model Distribution
+//!
+//! Each agent keeps its learned negotiation model next to its configuration
+//! as `<agent>.model`, in the versioned binary format of
+//! `solace_ai::model`. `solace-agent model export` copies it out for
+//! distribution, `import` installs a model trained elsewhere, and `train`
+//! replays a batch of labeled outcomes into it.
+
+use anyhow::{anyhow, Context, Result};
+use solace_ai::learning::LabeledOutcome;
+use solace_ai::NegotiationAI;
+use std::path::{Path, PathBuf};
+
+/// Learning rate of a model that has never been trained
+const DEFAULT_LEARNING_RATE: f64 = 0.1;
+
+/// Seed for the exploration RNG of a fresh policy
+const DEFAULT_SEED: u64 = 1;
+
+/// File holding an agent's negotiation model
+pub fn model_path(config_dir: &Path, agent: &str) -> PathBuf {
+    config_dir.join(format!("{}.model", agent))
+}
+
+/// Load an agent's model, or a fresh learning model if it has none yet
+pub fn load(config_dir: &Path, agent: &str, risk_tolerance: f64) -> Result<NegotiationAI> {
+    let mut ai = NegotiationAI::new(DEFAULT_LEARNING_RATE, risk_tolerance);
+    let path = model_path(config_dir, agent);
+    if path.exists() {
+        let bytes = std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        ai.import_model(&bytes).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    }
+    if ai.policy().is_none() {
+        ai.enable_learning(DEFAULT_SEED);
+    }
+    Ok(ai)
+}
+
+/// Save an agent's model
+pub fn save(config_dir: &Path, agent: &str, ai: &NegotiationAI) -> Result<PathBuf> {
+    let path = model_path(config_dir, agent);
+    let bytes = ai.export_model().map_err(|e| anyhow!(e))?;
+    std::fs::write(&path, bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Write an agent's model to `output`
+pub fn export(config_dir: &Path, agent: &str, risk_tolerance: f64, output: &Path) -> Result<()> {
+    let ai = load(config_dir, agent, risk_tolerance)?;
+    let bytes = ai.export_model().map_err(|e| anyhow!(e))?;
+    std::fs::write(output, bytes).with_context(|| format!("Failed to write {}", output.display()))?;
+    print_summary("📤 Exported", agent, &ai, output);
+    Ok(())
+}
+
+/// Install the model in `input` for an agent, replacing what it had learned
+pub fn import(config_dir: &Path, agent: &str, risk_tolerance: f64, input: &Path) -> Result<()> {
+    let bytes = std::fs::read(input).with_context(|| format!("Failed to read {}", input.display()))?;
+    let mut ai = NegotiationAI::new(DEFAULT_LEARNING_RATE, risk_tolerance);
+    ai.import_model(&bytes).map_err(|e| anyhow!("{}: {}", input.display(), e))?;
+    let path = save(config_dir, agent, &ai)?;
+    print_summary("📥 Imported", agent, &ai, &path);
+    Ok(())
+}
+
+/// Train an agent's model on a JSON array of labeled outcomes
+pub fn train(config_dir: &Path, agent: &str, risk_tolerance: f64, history: &Path) -> Result<()> {
+    let content = std::fs::read_to_string(history).with_context(|| format!("Failed to read {}", history.display()))?;
+    let batch: Vec<LabeledOutcome> = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not a JSON array of labeled outcomes", history.display()))?;
+
+    let mut ai = load(config_dir, agent, risk_tolerance)?;
+    ai.train_from_history(&batch);
+    let path = save(config_dir, agent, &ai)?;
+    print_summary(&format!("🎓 Trained on {} outcomes,", batch.len()), agent, &ai, &path);
+    Ok(())
+}
+
+fn print_summary(action: &str, agent: &str, ai: &NegotiationAI, path: &Path) {
+    let updates = ai.policy().map_or(0, |policy| policy.updates);
+    println!("{} model for '{}' ({})", action, agent, path.display());
+    println!("   Policy updates: {}, success rate: {:.1}%", updates, ai.get_success_rate() * 100.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_models_move_between_agents() {
+        let dir = std::env::temp_dir().join(format!("solace-model-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let history = dir.join("history.json");
+        std::fs::write(
+            &history,
+            r#"[{"decision": {"kind": "pricing", "state": "r1-d1-c1-v2", "action": 3, "explored": false},
+                "outcome": {"success": true, "profit_margin": 0.2, "satisfaction_score": 0.9, "completion_time": 60}}]"#,
+        )
+        .unwrap();
+
+        train(&dir, "alice", 0.5, &history).unwrap();
+        let exported = dir.join("alice-export.model");
+        export(&dir, "alice", 0.5, &exported).unwrap();
+        import(&dir, "bob", 0.8, &exported).unwrap();
+
+        let bob = load(&dir, "bob", 0.8).unwrap();
+        assert_eq!(bob.policy().unwrap().updates, 1);
+        assert!(import(&dir, "bob", 0.8, &history).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,333 @@
+//! Transaction Search API
+//!
+//! A running agent indexes its persisted transactions and answers
+//! `GET /v1/transactions/search` on a local HTTP port, recorded next to its
+//! configuration. `solace-agent search` reads that address, forwards the
+//! query, and prints the ranked hits. The same port serves the market
+//! analytics API (see `market`).
+//!
+//! ```text
+//! GET /v1/transactions/search?q=sentiment+csv&status=completed&max_price=5&limit=10
+//! ```
+//!
+//! `solace-agent history` uses the same endpoint, listing recent
+//! transactions or, with `--group-by`, totalling them by tag.
+
+use anyhow::{anyhow, Context, Result};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use solace_protocol::analytics::MarketAnalytics;
+use solace_protocol::billing::{volume_by, Volume};
+use solace_protocol::search::{SearchQuery, SearchResults, TransactionSearchIndex};
+use solace_protocol::storage::StorageManager;
+use solace_protocol::transaction::TagDimension;
+use solace_protocol::types::ServiceType;
+use solace_protocol::{AgentId, Balance, Timestamp, TransactionPhase, TransactionStatus};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::market::{self, SharedAnalytics};
+
+/// Default listen address for the agent API
+pub const DEFAULT_API_ADDR: &str = "127.0.0.1:7700";
+
+/// How often the daemon re-reads transactions from storage for search and analytics
+const REINDEX_INTERVAL: Duration = Duration::from_secs(30);
+
+/// File holding a running agent's API address
+pub fn api_addr_path(config_dir: &Path, agent: &str) -> PathBuf {
+    config_dir.join(format!("{}.api", agent))
+}
+
+/// Query string of the search endpoint; prices are in SOL, times RFC 3339
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchParams {
+    pub q: Option<String>,
+    pub status: Option<String>,
+    pub phase: Option<String>,
+    pub service: Option<String>,
+    pub requester: Option<String>,
+    pub provider: Option<String>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+    pub tag: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl SearchParams {
+    /// Validate and convert to a library query
+    pub fn to_query(&self) -> Result<SearchQuery> {
+        Ok(SearchQuery {
+            text: self.q.clone().filter(|q| !q.trim().is_empty()),
+            status: self.status.as_deref().map(parse_status).transpose()?,
+            phase: self.phase.as_deref().map(parse_phase).transpose()?,
+            service_type: self.service.as_deref().map(parse_service_type),
+            requester: self.requester.as_deref().map(parse_agent_id).transpose()?,
+            provider: self.provider.as_deref().map(parse_agent_id).transpose()?,
+            min_price: self.min_price.map(Balance::from_sol),
+            max_price: self.max_price.map(Balance::from_sol),
+            created_after: self.after.as_deref().map(parse_timestamp).transpose()?,
+            created_before: self.before.as_deref().map(parse_timestamp).transpose()?,
+            tag: self.tag.as_deref().map(parse_tag),
+            limit: self.limit,
+            offset: self.offset.unwrap_or(0),
+        })
+    }
+}
+
+/// Lowercase with separators removed, so `in-progress` matches `InProgress`
+fn normalize(value: &str) -> String {
+    value.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase()
+}
+
+fn parse_status(value: &str) -> Result<TransactionStatus> {
+    match normalize(value).as_str() {
+        "pending" => Ok(TransactionStatus::Pending),
+        "inprogress" => Ok(TransactionStatus::InProgress),
+        "completed" => Ok(TransactionStatus::Completed),
+        "failed" => Ok(TransactionStatus::Failed),
+        "cancelled" => Ok(TransactionStatus::Cancelled),
+        "expired" => Ok(TransactionStatus::Expired),
+        _ => Err(anyhow!("Unknown status '{}' (use pending, in-progress, completed, failed, cancelled, or expired)", value)),
+    }
+}
+
+fn parse_phase(value: &str) -> Result<TransactionPhase> {
+    match normalize(value).as_str() {
+        "request" => Ok(TransactionPhase::Request),
+        "negotiation" => Ok(TransactionPhase::Negotiation),
+        "execution" => Ok(TransactionPhase::Execution),
+        "evaluation" => Ok(TransactionPhase::Evaluation),
+        _ => Err(anyhow!("Unknown phase '{}' (use request, negotiation, execution, or evaluation)", value)),
+    }
+}
+
+/// Built-in service types by name; anything else is a custom service
+pub fn parse_service_type(value: &str) -> ServiceType {
+    match normalize(value).as_str() {
+        "dataanalysis" => ServiceType::DataAnalysis,
+        "computationaltask" => ServiceType::ComputationalTask,
+        "marketresearch" => ServiceType::MarketResearch,
+        "contentcreation" => ServiceType::ContentCreation,
+        "tradingservice" => ServiceType::TradingService,
+        "machinelearning" => ServiceType::MachineLearning,
+        _ => ServiceType::from_key(value).unwrap_or_else(|| ServiceType::CustomService(value.to_string())),
+    }
+}
+
+/// `cost-center=x` as the library spells it, `cost_center=x`
+fn parse_tag(value: &str) -> String {
+    match value.split_once('=') {
+        Some((dimension, tag)) => match TagDimension::parse(dimension) {
+            Some(dimension) => format!("{}={}", dimension.key(), tag),
+            None => value.to_string(),
+        },
+        None => value.to_string(),
+    }
+}
+
+fn parse_group_by(value: &str) -> Result<TagDimension> {
+    TagDimension::parse(value).ok_or_else(|| anyhow!("Unknown grouping '{}' (use tag, project, cost-center, or environment)", value))
+}
+
+fn parse_agent_id(value: &str) -> Result<AgentId> {
+    AgentId::from_string(value).map_err(|e| anyhow!("Invalid agent ID '{}': {}", value, e))
+}
+
+fn parse_timestamp(value: &str) -> Result<Timestamp> {
+    let time = chrono::DateTime::parse_from_rfc3339(value)
+        .with_context(|| format!("Invalid timestamp '{}' (expected RFC 3339)", value))?;
+    Ok(Timestamp(time.with_timezone(&chrono::Utc)))
+}
+
+type SharedIndex = Arc<RwLock<TransactionSearchIndex>>;
+
+/// Index the agent's transactions and serve the search and market analytics
+/// API until the task is dropped
+pub async fn serve(storage: Arc<StorageManager>, addr: SocketAddr, addr_file: PathBuf) -> Result<()> {
+    let index: SharedIndex = Arc::new(RwLock::new(TransactionSearchIndex::from_storage(&storage).await?));
+    let analytics: SharedAnalytics = Arc::new(RwLock::new(MarketAnalytics::from_storage(&storage).await?));
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind search API on {}", addr))?;
+    std::fs::write(&addr_file, listener.local_addr()?.to_string())
+        .with_context(|| format!("Failed to write {}", addr_file.display()))?;
+
+    let refresh = tokio::spawn(refresh_index(storage, index.clone(), analytics.clone()));
+    let app = Router::new()
+        .route("/v1/transactions/search", get(search_handler))
+        .with_state(index)
+        .merge(market::router(analytics));
+    let result = axum::serve(listener, app).await;
+
+    refresh.abort();
+    result.context("Search API failed")
+}
+
+/// Pick up transactions persisted since the last pass
+async fn refresh_index(storage: Arc<StorageManager>, index: SharedIndex, analytics: SharedAnalytics) {
+    let mut interval = tokio::time::interval(REINDEX_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match TransactionSearchIndex::from_storage(&storage).await {
+            Ok(fresh) => *index.write().await = fresh,
+            Err(e) => tracing::warn!("Failed to reindex transactions: {}", e),
+        }
+        match MarketAnalytics::from_storage(&storage).await {
+            Ok(fresh) => *analytics.write().await = fresh,
+            Err(e) => tracing::warn!("Failed to refresh market analytics: {}", e),
+        }
+    }
+}
+
+async fn search_handler(
+    State(index): State<SharedIndex>,
+    Query(params): Query<SearchParams>,
+) -> std::result::Result<Json<SearchResults>, (StatusCode, String)> {
+    let query = params.to_query().map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok(Json(index.read().await.search(&query)))
+}
+
+/// Query a running agent's search API
+pub async fn query(config_dir: &Path, agent: &str, params: &SearchParams) -> Result<SearchResults> {
+    // Catch bad filters locally instead of as an HTTP 400
+    params.to_query()?;
+
+    let addr = running_api_addr(config_dir, agent)?;
+    let response = reqwest::Client::new()
+        .get(format!("http://{}/v1/transactions/search", addr))
+        .query(params)
+        .send()
+        .await
+        .with_context(|| format!("Agent '{}' is not answering on {}", agent, addr))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(anyhow!("Search failed ({}): {}", status, response.text().await.unwrap_or_default()));
+    }
+    Ok(response.json().await?)
+}
+
+/// API address recorded by a running agent
+pub fn running_api_addr(config_dir: &Path, agent: &str) -> Result<String> {
+    let addr_file = api_addr_path(config_dir, agent);
+    let addr = std::fs::read_to_string(&addr_file).with_context(|| {
+        format!("No running agent at {} (start it with `solace-agent start`)", addr_file.display())
+    })?;
+    Ok(addr.trim().to_string())
+}
+
+/// Show a running agent's most recent transactions, or every transaction
+/// totalled by `group_by`
+pub async fn history(config_dir: &Path, agent: &str, limit: usize, tag: Option<String>, group_by: Option<&str>, json: bool) -> Result<()> {
+    let group_by = group_by.map(parse_group_by).transpose()?;
+    let params = SearchParams {
+        tag,
+        limit: Some(if group_by.is_some() { usize::MAX } else { limit }),
+        ..SearchParams::default()
+    };
+    let results = query(config_dir, agent, &params).await?;
+
+    match group_by {
+        Some(dimension) => {
+            let volumes = volume_by(dimension, results.hits.iter().map(|hit| &hit.transaction));
+            if json {
+                println!("{}", serde_json::to_string_pretty(&volumes)?);
+            } else {
+                print_volumes(dimension, &volumes);
+            }
+        }
+        None if json => println!("{}", serde_json::to_string_pretty(&results)?),
+        None => print_results(&results),
+    }
+    Ok(())
+}
+
+/// Print grouped totals, largest first
+pub fn print_volumes(dimension: TagDimension, volumes: &BTreeMap<String, Volume>) {
+    if volumes.is_empty() {
+        println!("📈 No transactions");
+        return;
+    }
+
+    let mut groups: Vec<(&String, &Volume)> = volumes.iter().collect();
+    groups.sort_by_key(|(_, volume)| std::cmp::Reverse(volume.agreed));
+    println!("📈 Transactions by {}", dimension.key());
+    for (group, volume) in groups {
+        println!("  {:<24} {:>5} transactions  {}", group, volume.transactions, volume.agreed);
+    }
+}
+
+/// Print search hits, best first
+pub fn print_results(results: &SearchResults) {
+    if results.hits.is_empty() {
+        println!("🔍 No matching transactions");
+        return;
+    }
+
+    println!("🔍 {} matching transactions (showing {})", results.total, results.hits.len());
+    for hit in &results.hits {
+        let tx = &hit.transaction;
+        let price = tx.agreed_price.unwrap_or(tx.request.budget);
+        println!(
+            "\n  {} [{:?}/{:?}] {} — {}",
+            tx.id, tx.status, tx.phase, tx.request.service_type, price
+        );
+        println!("     {}", tx.request.description);
+        if !tx.request.tags.is_empty() {
+            println!("     tags: {}", tx.request.tags.entries().join(", "));
+        }
+        if !hit.matched_terms.is_empty() {
+            println!("     score {:.3}, matched: {}", hit.score, hit.matched_terms.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_convert_to_query() {
+        let params = SearchParams {
+            q: Some("sentiment csv".to_string()),
+            status: Some("in-progress".to_string()),
+            service: Some("data_analysis".to_string()),
+            max_price: Some(2.5),
+            after: Some("2024-01-01T00:00:00Z".to_string()),
+            ..SearchParams::default()
+        };
+
+        let query = params.to_query().unwrap();
+        assert_eq!(query.status, Some(TransactionStatus::InProgress));
+        assert_eq!(query.service_type, Some(ServiceType::DataAnalysis));
+        assert_eq!(query.max_price, Some(Balance::from_sol(2.5)));
+        assert!(query.created_after.is_some());
+
+        let tagged = SearchParams { tag: Some("cost-center=research".to_string()), ..SearchParams::default() };
+        assert_eq!(tagged.to_query().unwrap().tag.as_deref(), Some("cost_center=research"));
+
+        let custom = SearchParams { service: Some("translation".to_string()), ..SearchParams::default() };
+        assert_eq!(custom.to_query().unwrap().service_type, Some(ServiceType::CustomService("translation".to_string())));
+    }
+
+    #[test]
+    fn test_invalid_params_are_rejected() {
+        let bad_status = SearchParams { status: Some("done".to_string()), ..SearchParams::default() };
+        assert!(bad_status.to_query().is_err());
+
+        let bad_time = SearchParams { before: Some("yesterday".to_string()), ..SearchParams::default() };
+        assert!(bad_time.to_query().is_err());
+    }
+}
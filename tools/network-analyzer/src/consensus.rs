@@ -0,0 +1,416 @@
+//! Consensus Health Deep-Dive
+//!
+//! Inspects the validator set for the current epoch: delinquency, missed
+//! leader slots, vote participation, epoch transitions, and slashing events.
+//! Validators that would be dropped at the next selection are flagged with
+//! the reasons, and finality latency percentiles are estimated from the gap
+//! between the processed and finalized slots.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solace_simulation::ConsensusSample;
+use std::time::Duration;
+
+/// Slot duration assumed when the RPC has no performance samples
+const DEFAULT_SLOT_MS: f64 = 400.0;
+
+/// Completed epochs used to measure epoch transition times
+const RECENT_EPOCHS: u64 = 3;
+
+/// Thresholds for flagging validators at risk of removal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskThresholds {
+    pub max_skip_rate: f64,               // Fraction of leader slots missed
+    pub min_vote_participation: f64,      // Credits relative to the best validator
+    pub max_vote_lag: u64,                // Slots behind the tip
+    pub min_stake: u64,                   // Lamports
+    pub max_slashing_events: usize,
+}
+
+impl Default for RiskThresholds {
+    fn default() -> Self {
+        Self {
+            max_skip_rate: 0.25,
+            min_vote_participation: 0.9,
+            max_vote_lag: 150,
+            min_stake: 0,
+            max_slashing_events: 1,
+        }
+    }
+}
+
+/// Raw validator state, from the RPC or the simulator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorObservation {
+    pub identity: String,
+    pub vote_account: String,
+    pub stake: u64,
+    pub delinquent: bool,
+    pub last_vote: u64,
+    pub leader_slots: u64,
+    pub blocks_produced: u64,
+    pub epoch_credits: u64,
+}
+
+/// A slashing event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashingEvent {
+    pub validator: String,
+    pub epoch: u64,
+    pub slot: u64,
+    pub reason: String,
+    pub amount: u64,
+}
+
+/// Raw consensus state to analyze
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusObservation {
+    pub epoch: u64,
+    pub absolute_slot: u64,
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    pub slot_ms: f64,
+    pub recent_epoch_durations_secs: Vec<f64>,
+    pub validators: Vec<ValidatorObservation>,
+    pub slashing_events: Option<Vec<SlashingEvent>>,  // None when the source has no slashing API
+    pub finality_lag_ms: Vec<f64>,
+}
+
+impl From<ConsensusSample> for ConsensusObservation {
+    fn from(sample: ConsensusSample) -> Self {
+        Self {
+            epoch: sample.epoch,
+            absolute_slot: sample.absolute_slot,
+            slot_index: sample.slot_index,
+            slots_in_epoch: sample.slots_in_epoch,
+            slot_ms: DEFAULT_SLOT_MS,
+            recent_epoch_durations_secs: sample.recent_epoch_durations_secs,
+            validators: sample.validators.into_iter().map(|v| ValidatorObservation {
+                identity: v.identity,
+                vote_account: v.vote_account,
+                stake: v.stake,
+                delinquent: v.delinquent,
+                last_vote: v.last_vote,
+                leader_slots: v.leader_slots,
+                blocks_produced: v.blocks_produced,
+                epoch_credits: v.epoch_credits,
+            }).collect(),
+            slashing_events: Some(sample.slashing_events.into_iter().map(|e| SlashingEvent {
+                validator: e.validator,
+                epoch: e.epoch,
+                slot: e.slot,
+                reason: e.reason,
+                amount: e.amount,
+            }).collect()),
+            finality_lag_ms: sample.finality_lag_ms,
+        }
+    }
+}
+
+/// Query consensus state from a Solana RPC endpoint
+///
+/// Finality is sampled `finality_samples` times, `sample_interval` apart.
+pub async fn observe_rpc(endpoint: &str, finality_samples: usize, sample_interval: Duration) -> Result<ConsensusObservation> {
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::commitment_config::CommitmentConfig;
+    use std::collections::HashMap;
+
+    let client = RpcClient::new(endpoint.to_string());
+
+    let epoch_info = client.get_epoch_info().await.context("Failed to query epoch info")?;
+    let vote_accounts = client.get_vote_accounts().await.context("Failed to query vote accounts")?;
+    let production: HashMap<String, (usize, usize)> = client
+        .get_block_production()
+        .await
+        .context("Failed to query block production")?
+        .value
+        .by_identity;
+
+    let performance = client.get_recent_performance_samples(Some(10)).await.unwrap_or_default();
+    let (slots, secs) = performance.iter().fold((0u64, 0u64), |(slots, secs), s| {
+        (slots + s.num_slots, secs + s.sample_period_secs as u64)
+    });
+    let slot_ms = if slots > 0 { secs as f64 * 1000.0 / slots as f64 } else { DEFAULT_SLOT_MS };
+
+    // Epoch transition times from the first block of each recent epoch
+    let schedule = client.get_epoch_schedule().await?;
+    let mut epoch_starts = Vec::new();
+    for epoch in epoch_info.epoch.saturating_sub(RECENT_EPOCHS)..=epoch_info.epoch {
+        let first_slot = schedule.get_first_slot_in_epoch(epoch);
+        let first_block = client.get_blocks_with_limit(first_slot, 1).await.ok().and_then(|b| b.first().copied());
+        if let Some(slot) = first_block {
+            if let Ok(time) = client.get_block_time(slot).await {
+                epoch_starts.push(time);
+            }
+        }
+    }
+    let recent_epoch_durations_secs = epoch_starts.windows(2).map(|w| (w[1] - w[0]) as f64).collect();
+
+    let validators = vote_accounts.current.iter().map(|v| (v, false))
+        .chain(vote_accounts.delinquent.iter().map(|v| (v, true)))
+        .map(|(account, delinquent)| {
+            let (leader_slots, blocks_produced) = production.get(&account.node_pubkey).copied().unwrap_or((0, 0));
+            let epoch_credits = account.epoch_credits.iter()
+                .find(|(epoch, _, _)| *epoch == epoch_info.epoch)
+                .map(|(_, credits, previous)| credits - previous)
+                .unwrap_or(0);
+            ValidatorObservation {
+                identity: account.node_pubkey.clone(),
+                vote_account: account.vote_pubkey.clone(),
+                stake: account.activated_stake,
+                delinquent,
+                last_vote: account.last_vote,
+                leader_slots: leader_slots as u64,
+                blocks_produced: blocks_produced as u64,
+                epoch_credits,
+            }
+        })
+        .collect();
+
+    let mut finality_lag_ms = Vec::with_capacity(finality_samples);
+    for i in 0..finality_samples {
+        if i > 0 {
+            tokio::time::sleep(sample_interval).await;
+        }
+        let processed = client.get_slot_with_commitment(CommitmentConfig::processed()).await?;
+        let finalized = client.get_slot_with_commitment(CommitmentConfig::finalized()).await?;
+        finality_lag_ms.push(processed.saturating_sub(finalized) as f64 * slot_ms);
+    }
+
+    Ok(ConsensusObservation {
+        epoch: epoch_info.epoch,
+        absolute_slot: epoch_info.absolute_slot,
+        slot_index: epoch_info.slot_index,
+        slots_in_epoch: epoch_info.slots_in_epoch,
+        slot_ms,
+        recent_epoch_durations_secs,
+        validators,
+        // Solana RPC exposes no slashing history
+        slashing_events: None,
+        finality_lag_ms,
+    })
+}
+
+/// Epoch progress and transitions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochStatus {
+    pub epoch: u64,
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    pub progress: f64,
+    pub estimated_secs_remaining: f64,
+    pub recent_epoch_durations_secs: Vec<f64>,
+}
+
+/// Per-validator health
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorHealth {
+    pub identity: String,
+    pub vote_account: String,
+    pub stake: u64,
+    pub stake_share: f64,
+    pub delinquent: bool,
+    pub vote_lag: u64,                    // Slots behind the tip
+    pub leader_slots: u64,
+    pub missed_slots: u64,
+    pub skip_rate: f64,
+    pub vote_participation: f64,          // Credits relative to the best validator
+    pub slashing_events: usize,
+}
+
+/// A validator at risk of removal, with the reasons
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtRiskValidator {
+    pub identity: String,
+    pub stake_share: f64,
+    pub reasons: Vec<String>,
+}
+
+/// Finality latency percentiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinalityLatency {
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Consensus health report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusReport {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub epoch: EpochStatus,
+    pub total_validators: usize,
+    pub delinquent_validators: usize,
+    pub total_stake: u64,
+    pub delinquent_stake_share: f64,
+    pub leader_slots: u64,
+    pub missed_slots: u64,
+    pub skip_rate: f64,
+    pub vote_participation: f64,          // Stake-weighted
+    pub validators: Vec<ValidatorHealth>,
+    pub slashing_events: Option<Vec<SlashingEvent>>,
+    pub at_risk: Vec<AtRiskValidator>,
+    pub finality: FinalityLatency,
+}
+
+/// Value at a percentile (0-100) of sorted values
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (pct / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Analyze consensus state against the risk thresholds
+pub fn analyze(observation: ConsensusObservation, thresholds: &RiskThresholds) -> ConsensusReport {
+    let total_stake: u64 = observation.validators.iter().map(|v| v.stake).sum();
+    let best_credits = observation.validators.iter().map(|v| v.epoch_credits).max().unwrap_or(0).max(1);
+    let stake_share = |stake: u64| if total_stake > 0 { stake as f64 / total_stake as f64 } else { 0.0 };
+
+    let validators: Vec<ValidatorHealth> = observation.validators.iter().map(|v| {
+        let missed_slots = v.leader_slots.saturating_sub(v.blocks_produced);
+        ValidatorHealth {
+            identity: v.identity.clone(),
+            vote_account: v.vote_account.clone(),
+            stake: v.stake,
+            stake_share: stake_share(v.stake),
+            delinquent: v.delinquent,
+            vote_lag: observation.absolute_slot.saturating_sub(v.last_vote),
+            leader_slots: v.leader_slots,
+            missed_slots,
+            skip_rate: if v.leader_slots > 0 { missed_slots as f64 / v.leader_slots as f64 } else { 0.0 },
+            vote_participation: v.epoch_credits as f64 / best_credits as f64,
+            slashing_events: observation.slashing_events.iter().flatten()
+                .filter(|e| e.validator == v.identity || e.validator == v.vote_account)
+                .count(),
+        }
+    }).collect();
+
+    let mut at_risk: Vec<AtRiskValidator> = validators.iter().filter_map(|v| {
+        let mut reasons = Vec::new();
+        if v.delinquent {
+            reasons.push("delinquent".to_string());
+        }
+        if v.skip_rate > thresholds.max_skip_rate {
+            reasons.push(format!("skip rate {:.1}%", v.skip_rate * 100.0));
+        }
+        if v.vote_participation < thresholds.min_vote_participation {
+            reasons.push(format!("vote participation {:.1}%", v.vote_participation * 100.0));
+        }
+        if v.vote_lag > thresholds.max_vote_lag {
+            reasons.push(format!("{} slots behind", v.vote_lag));
+        }
+        if v.stake < thresholds.min_stake {
+            reasons.push("stake below minimum".to_string());
+        }
+        if v.slashing_events > thresholds.max_slashing_events {
+            reasons.push(format!("{} slashing events", v.slashing_events));
+        }
+        (!reasons.is_empty()).then(|| AtRiskValidator {
+            identity: v.identity.clone(),
+            stake_share: v.stake_share,
+            reasons,
+        })
+    }).collect();
+    at_risk.sort_by(|a, b| b.reasons.len().cmp(&a.reasons.len()).then(b.stake_share.total_cmp(&a.stake_share)));
+
+    let leader_slots: u64 = validators.iter().map(|v| v.leader_slots).sum();
+    let missed_slots: u64 = validators.iter().map(|v| v.missed_slots).sum();
+    let delinquent_stake: u64 = validators.iter().filter(|v| v.delinquent).map(|v| v.stake).sum();
+
+    let mut lags = observation.finality_lag_ms.clone();
+    lags.sort_by(|a, b| a.total_cmp(b));
+
+    ConsensusReport {
+        timestamp: chrono::Utc::now(),
+        epoch: EpochStatus {
+            epoch: observation.epoch,
+            slot_index: observation.slot_index,
+            slots_in_epoch: observation.slots_in_epoch,
+            progress: observation.slot_index as f64 / observation.slots_in_epoch.max(1) as f64,
+            estimated_secs_remaining: observation.slots_in_epoch.saturating_sub(observation.slot_index) as f64
+                * observation.slot_ms / 1000.0,
+            recent_epoch_durations_secs: observation.recent_epoch_durations_secs,
+        },
+        total_validators: validators.len(),
+        delinquent_validators: validators.iter().filter(|v| v.delinquent).count(),
+        total_stake,
+        delinquent_stake_share: stake_share(delinquent_stake),
+        leader_slots,
+        missed_slots,
+        skip_rate: if leader_slots > 0 { missed_slots as f64 / leader_slots as f64 } else { 0.0 },
+        vote_participation: validators.iter().map(|v| v.vote_participation * v.stake_share).sum(),
+        validators,
+        slashing_events: observation.slashing_events,
+        at_risk,
+        finality: FinalityLatency {
+            samples: lags.len(),
+            p50_ms: percentile(&lags, 50.0),
+            p90_ms: percentile(&lags, 90.0),
+            p99_ms: percentile(&lags, 99.0),
+            max_ms: lags.last().copied().unwrap_or(0.0),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator(identity: &str, leader_slots: u64, blocks_produced: u64, epoch_credits: u64) -> ValidatorObservation {
+        ValidatorObservation {
+            identity: identity.to_string(),
+            vote_account: format!("vote-{}", identity),
+            stake: 100,
+            delinquent: false,
+            last_vote: 1_000,
+            leader_slots,
+            blocks_produced,
+            epoch_credits,
+        }
+    }
+
+    fn observation(validators: Vec<ValidatorObservation>) -> ConsensusObservation {
+        ConsensusObservation {
+            epoch: 10,
+            absolute_slot: 1_000,
+            slot_index: 100,
+            slots_in_epoch: 400,
+            slot_ms: 400.0,
+            recent_epoch_durations_secs: Vec::new(),
+            validators,
+            slashing_events: None,
+            finality_lag_ms: (1..=100).map(|i| i as f64 * 100.0).collect(),
+        }
+    }
+
+    #[test]
+    fn test_flags_validators_at_risk() {
+        let mut slow = validator("slow", 100, 50, 1_000);
+        slow.last_vote = 500;
+        let report = analyze(observation(vec![
+            validator("good", 100, 99, 1_000),
+            slow,
+            validator("lazy", 100, 100, 500),
+        ]), &RiskThresholds::default());
+
+        assert_eq!(report.at_risk.len(), 2);
+        assert_eq!(report.at_risk[0].identity, "slow");
+        assert_eq!(report.at_risk[0].reasons.len(), 2);
+        assert_eq!(report.at_risk[1].identity, "lazy");
+        assert_eq!(report.missed_slots, 51);
+    }
+
+    #[test]
+    fn test_epoch_and_finality_estimates() {
+        let report = analyze(observation(vec![validator("a", 10, 10, 10)]), &RiskThresholds::default());
+
+        assert_eq!(report.epoch.progress, 0.25);
+        assert_eq!(report.epoch.estimated_secs_remaining, 120.0);
+        assert_eq!(report.finality.p50_ms, 5_100.0);
+        assert_eq!(report.finality.max_ms, 10_000.0);
+    }
+}
@@ -0,0 +1,224 @@
+//! SQLite Export
+//!
+//! Writes everything a report collected into a normalized SQLite database so
+//! analysts can query it offline. Each export is a row in `export_runs`, and
+//! every other table references it, so repeated exports into the same file
+//! accumulate history instead of overwriting it.
+//!
+//! ```sql
+//! SELECT n.node_type, AVG(n.latency_ms)
+//! FROM nodes n JOIN export_runs r ON r.id = n.run_id
+//! WHERE r.id = (SELECT MAX(id) FROM export_runs)
+//! GROUP BY n.node_type;
+//! ```
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{AgentStats, NetworkMetrics, NetworkNode, TransactionAnalysis};
+
+/// Schema, applied idempotently on every export
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS export_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at TEXT NOT NULL,
+    data_mode TEXT NOT NULL,
+    endpoint TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS nodes (
+    run_id INTEGER NOT NULL REFERENCES export_runs(id),
+    id TEXT NOT NULL,
+    address TEXT NOT NULL,
+    node_type TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    uptime_secs INTEGER NOT NULL,
+    latency_ms REAL NOT NULL,
+    throughput_tps REAL NOT NULL,
+    error_rate REAL NOT NULL,
+    reputation_score REAL NOT NULL,
+    PRIMARY KEY (run_id, id)
+);
+
+CREATE TABLE IF NOT EXISTS edges (
+    run_id INTEGER NOT NULL REFERENCES export_runs(id),
+    source_id TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    PRIMARY KEY (run_id, source_id, target_id)
+);
+
+CREATE TABLE IF NOT EXISTS metric_samples (
+    run_id INTEGER NOT NULL REFERENCES export_runs(id),
+    timestamp TEXT NOT NULL,
+    total_nodes INTEGER NOT NULL,
+    active_agents INTEGER NOT NULL,
+    transactions_per_second REAL NOT NULL,
+    average_latency_ms REAL NOT NULL,
+    network_utilization REAL NOT NULL,
+    consensus_rate REAL NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS transaction_summaries (
+    run_id INTEGER PRIMARY KEY REFERENCES export_runs(id),
+    window_hours INTEGER NOT NULL,
+    total_transactions INTEGER NOT NULL,
+    successful_transactions INTEGER NOT NULL,
+    failed_transactions INTEGER NOT NULL,
+    average_value REAL NOT NULL,
+    peak_tps REAL NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS transaction_volume (
+    run_id INTEGER NOT NULL REFERENCES export_runs(id),
+    value_range TEXT NOT NULL,
+    transactions INTEGER NOT NULL,
+    PRIMARY KEY (run_id, value_range)
+);
+
+CREATE TABLE IF NOT EXISTS agent_summaries (
+    run_id INTEGER PRIMARY KEY REFERENCES export_runs(id),
+    total_agents INTEGER NOT NULL,
+    active_agents INTEGER NOT NULL,
+    reputation_gini REAL,
+    volume_gini REAL
+);
+
+CREATE TABLE IF NOT EXISTS agent_capabilities (
+    run_id INTEGER NOT NULL REFERENCES export_runs(id),
+    capability TEXT NOT NULL,
+    agents INTEGER NOT NULL,
+    PRIMARY KEY (run_id, capability)
+);
+
+CREATE TABLE IF NOT EXISTS agent_reputations (
+    run_id INTEGER NOT NULL REFERENCES export_runs(id),
+    position INTEGER NOT NULL,
+    reputation REAL NOT NULL,
+    PRIMARY KEY (run_id, position)
+);
+
+CREATE TABLE IF NOT EXISTS health_checks (
+    run_id INTEGER NOT NULL REFERENCES export_runs(id),
+    component TEXT NOT NULL,
+    status TEXT NOT NULL,
+    PRIMARY KEY (run_id, component)
+);
+";
+
+/// Everything collected for one report
+pub struct ReportData<'a> {
+    pub data_mode: String,
+    pub endpoint: &'a str,
+    pub nodes: &'a [NetworkNode],
+    pub metrics: &'a [NetworkMetrics],
+    pub transactions: &'a TransactionAnalysis,
+    pub transaction_window_hours: u64,
+    pub agents: &'a AgentStats,
+    pub health: &'a HashMap<String, String>,
+}
+
+/// Append a report to the SQLite database at `path`, returning the run ID
+pub fn export_sqlite(path: &Path, data: &ReportData) -> Result<i64> {
+    let mut conn = Connection::open(path)
+        .with_context(|| format!("Failed to open SQLite database {}", path.display()))?;
+    conn.execute_batch(SCHEMA).context("Failed to create export schema")?;
+
+    // One transaction so a failed export leaves no partial run behind
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO export_runs (created_at, data_mode, endpoint) VALUES (?1, ?2, ?3)",
+        params![chrono::Utc::now().to_rfc3339(), data.data_mode, data.endpoint],
+    )?;
+    let run_id = tx.last_insert_rowid();
+
+    {
+        let mut insert_node = tx.prepare(
+            "INSERT INTO nodes (run_id, id, address, node_type, last_seen, uptime_secs, latency_ms, throughput_tps, error_rate, reputation_score)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        let mut insert_edge = tx.prepare("INSERT OR IGNORE INTO edges (run_id, source_id, target_id) VALUES (?1, ?2, ?3)")?;
+        for node in data.nodes {
+            insert_node.execute(params![
+                run_id,
+                node.id,
+                node.address,
+                format!("{:?}", node.node_type),
+                node.last_seen.to_rfc3339(),
+                node.metrics.uptime.as_secs() as i64,
+                node.metrics.latency_ms,
+                node.metrics.throughput_tps,
+                node.metrics.error_rate,
+                node.metrics.reputation_score,
+            ])?;
+            for target in &node.connections {
+                insert_edge.execute(params![run_id, node.id, target])?;
+            }
+        }
+
+        let mut insert_sample = tx.prepare(
+            "INSERT INTO metric_samples (run_id, timestamp, total_nodes, active_agents, transactions_per_second, average_latency_ms, network_utilization, consensus_rate)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for sample in data.metrics {
+            insert_sample.execute(params![
+                run_id,
+                sample.timestamp.to_rfc3339(),
+                sample.total_nodes as i64,
+                sample.active_agents as i64,
+                sample.transactions_per_second,
+                sample.average_latency_ms,
+                sample.network_utilization,
+                sample.consensus_rate,
+            ])?;
+        }
+
+        let transactions = data.transactions;
+        tx.execute(
+            "INSERT INTO transaction_summaries (run_id, window_hours, total_transactions, successful_transactions, failed_transactions, average_value, peak_tps)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                run_id,
+                data.transaction_window_hours as i64,
+                transactions.total_transactions as i64,
+                transactions.successful_transactions as i64,
+                transactions.failed_transactions as i64,
+                transactions.average_value,
+                transactions.peak_tps,
+            ],
+        )?;
+        let mut insert_volume = tx.prepare("INSERT INTO transaction_volume (run_id, value_range, transactions) VALUES (?1, ?2, ?3)")?;
+        for (range, count) in &transactions.volume_distribution {
+            insert_volume.execute(params![run_id, range, *count as i64])?;
+        }
+
+        let agents = data.agents;
+        tx.execute(
+            "INSERT INTO agent_summaries (run_id, total_agents, active_agents, reputation_gini, volume_gini) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                run_id,
+                agents.total_agents as i64,
+                agents.active_agents as i64,
+                agents.inequality.as_ref().map(|i| i.reputation.gini),
+                agents.inequality.as_ref().map(|i| i.volume.gini),
+            ],
+        )?;
+        let mut insert_capability = tx.prepare("INSERT INTO agent_capabilities (run_id, capability, agents) VALUES (?1, ?2, ?3)")?;
+        for (capability, count) in &agents.by_capability {
+            insert_capability.execute(params![run_id, capability, *count as i64])?;
+        }
+        let mut insert_reputation = tx.prepare("INSERT INTO agent_reputations (run_id, position, reputation) VALUES (?1, ?2, ?3)")?;
+        for (position, reputation) in agents.reputation_distribution.iter().enumerate() {
+            insert_reputation.execute(params![run_id, position as i64, reputation])?;
+        }
+
+        let mut insert_health = tx.prepare("INSERT INTO health_checks (run_id, component, status) VALUES (?1, ?2, ?3)")?;
+        for (component, status) in data.health {
+            insert_health.execute(params![run_id, component, status])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(run_id)
+}
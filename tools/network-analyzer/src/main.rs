@@ -5,6 +5,14 @@ use std::time::{Duration, Instant};
 use tokio;
 use tracing::{info, warn, error};
 use serde::{Deserialize, Serialize};
+use solace_simulation::{ChurnReport, ChurnScenario, ChurnSimulation, ConsensusParams, DataMode, DataSource, NodeRole};
+
+mod consensus;
+mod export;
+mod reputation;
+
+use consensus::{ConsensusReport, RiskThresholds};
+use reputation::{AgentReputation, InequalityReport};
 
 #[derive(Parser)]
 #[command(name = "solace-network-analyzer")]
@@ -25,6 +33,10 @@ struct Cli {
     /// Network endpoint
     #[arg(short, long, default_value = "https://api.devnet.solana.com")]
     endpoint: String,
+
+    /// Use simulated data instead of a live network
+    #[arg(long, global = true)]
+    simulated: bool,
 }
 
 #[derive(Subcommand)]
@@ -71,8 +83,102 @@ enum Commands {
         /// Capability filter
         #[arg(short, long)]
         capability: Option<String>,
+
+        /// Solace API base URL for the agent registry (live mode)
+        #[arg(long, env = "SOLACE_API_URL")]
+        api_url: Option<String>,
+
+        /// Bearer token for the Solace API
+        #[arg(long, env = "SOLACE_API_TOKEN")]
+        api_token: Option<String>,
+
+        /// Append inequality metrics to this JSON-lines file and report trends
+        #[arg(long)]
+        history: Option<String>,
+
+        /// Export reputation and inequality charts (.svg or .png)
+        #[arg(long)]
+        chart: Option<String>,
     },
     
+    /// Consensus health deep-dive
+    Consensus {
+        /// Processed-to-finalized samples for finality latency
+        #[arg(long, default_value = "10")]
+        finality_samples: usize,
+
+        /// Flag validators missing more than this fraction of leader slots
+        #[arg(long, default_value = "0.25")]
+        max_skip_rate: f64,
+
+        /// Flag validators earning less than this fraction of the best vote credits
+        #[arg(long, default_value = "0.9")]
+        min_participation: f64,
+
+        /// Flag validators whose last vote is this many slots behind
+        #[arg(long, default_value = "150")]
+        max_vote_lag: u64,
+
+        /// Flag validators with less stake than this (lamports)
+        #[arg(long, default_value = "0")]
+        min_stake: u64,
+
+        /// Export the report to file
+        #[arg(short, long)]
+        export: Option<String>,
+    },
+
+    /// Simulate consensus under validator churn to compare parameter choices
+    ChurnSim {
+        /// Epochs to simulate
+        #[arg(long, default_value = "50")]
+        epochs: u32,
+
+        /// Validator set sizes to compare (e.g. 15,21,31)
+        #[arg(long, value_delimiter = ',', default_value = "21")]
+        validators_per_epoch: Vec<usize>,
+
+        /// Blocks per epoch
+        #[arg(long, default_value = "1000")]
+        epoch_duration: u32,
+
+        /// Block time in milliseconds
+        #[arg(long, default_value = "2000")]
+        block_time_ms: u64,
+
+        /// Validators registered at the start
+        #[arg(long, default_value = "40")]
+        initial_validators: usize,
+
+        /// New validators per epoch, as a fraction of the pool
+        #[arg(long, default_value = "0.05")]
+        join_rate: f64,
+
+        /// Chance a validator leaves during an epoch
+        #[arg(long, default_value = "0.05")]
+        leave_rate: f64,
+
+        /// Largest fractional stake change per epoch
+        #[arg(long, default_value = "0.1")]
+        stake_volatility: f64,
+
+        /// Mean one-way network delay in milliseconds
+        #[arg(long, default_value = "150")]
+        delay_ms: f64,
+
+        /// Per-epoch delay variation as a fraction of the mean
+        #[arg(long, default_value = "0.5")]
+        delay_jitter: f64,
+
+        /// RNG seed
+        #[arg(long, default_value = "1")]
+        seed: u64,
+
+        /// Export the reports to file
+        #[arg(short, long)]
+        export: Option<String>,
+    },
+
     /// Real-time network dashboard
     Dashboard {
         /// Refresh rate in seconds
@@ -92,6 +198,10 @@ enum Commands {
         /// Output file
         #[arg(short, long)]
         output: Option<String>,
+
+        /// Also write all collected entities to this SQLite database
+        #[arg(long)]
+        export_db: Option<String>,
     },
 }
 
@@ -153,7 +263,8 @@ struct AgentStats {
     pub active_agents: usize,
     pub by_capability: HashMap<String, usize>,
     pub reputation_distribution: Vec<f64>,
-    pub connectivity_metrics: ConnectivityMetrics,
+    pub inequality: Option<InequalityReport>,
+    pub connectivity_metrics: Option<ConnectivityMetrics>,  // Not available from the registry API
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -168,42 +279,39 @@ struct ConnectivityMetrics {
 struct NetworkAnalyzer {
     endpoint: String,
     verbose: bool,
+    source: DataSource,
 }
 
 impl NetworkAnalyzer {
-    fn new(endpoint: String, verbose: bool) -> Self {
-        Self { endpoint, verbose }
+    fn new(endpoint: String, verbose: bool, mode: DataMode) -> Self {
+        Self { endpoint, verbose, source: DataSource::new(mode) }
     }
 
     async fn analyze_topology(&self, depth: usize) -> Result<Vec<NetworkNode>> {
         info!("Analyzing network topology with depth {}", depth);
         
-        // Simulate network discovery
-        let mut nodes = Vec::new();
-        
-        // Generate sample nodes
-        for i in 0..50 {
-            let node = NetworkNode {
-                id: format!("node-{:04}", i),
-                address: format!("192.168.1.{}", i + 1),
-                node_type: match i % 4 {
-                    0 => NodeType::Agent,
-                    1 => NodeType::Validator,
-                    2 => NodeType::Relay,
-                    _ => NodeType::Client,
+        let nodes = self.source.simulated("network topology")?.topology(50)
+            .into_iter()
+            .map(|node| NetworkNode {
+                id: node.id,
+                address: node.address,
+                node_type: match node.role {
+                    NodeRole::Agent => NodeType::Agent,
+                    NodeRole::Validator => NodeType::Validator,
+                    NodeRole::Relay => NodeType::Relay,
+                    NodeRole::Client => NodeType::Client,
                 },
-                connections: (0..5).map(|j| format!("node-{:04}", (i + j + 1) % 50)).collect(),
+                connections: node.connections,
                 last_seen: chrono::Utc::now(),
                 metrics: NodeMetrics {
-                    uptime: Duration::from_secs(3600 * 24 * (i as u64 % 30)),
-                    latency_ms: 20.0 + (i as f64 * 2.5) % 100.0,
-                    throughput_tps: 100.0 + (i as f64 * 10.0) % 500.0,
-                    error_rate: (i as f64 * 0.01) % 0.05,
-                    reputation_score: 0.5 + (i as f64 * 0.01) % 0.5,
+                    uptime: node.uptime,
+                    latency_ms: node.latency_ms,
+                    throughput_tps: node.throughput_tps,
+                    error_rate: node.error_rate,
+                    reputation_score: node.reputation_score,
                 },
-            };
-            nodes.push(node);
-        }
+            })
+            .collect();
 
         Ok(nodes)
     }
@@ -215,17 +323,7 @@ impl NetworkAnalyzer {
         let start_time = Instant::now();
         
         while start_time.elapsed() < duration {
-            let metric = NetworkMetrics {
-                timestamp: chrono::Utc::now(),
-                total_nodes: 50,
-                active_agents: 35,
-                transactions_per_second: 150.0 + (rand::random::<f64>() * 50.0),
-                average_latency_ms: 45.0 + (rand::random::<f64>() * 20.0),
-                network_utilization: 0.7 + (rand::random::<f64>() * 0.2),
-                consensus_rate: 0.98 + (rand::random::<f64>() * 0.02),
-            };
-            
-            metrics.push(metric);
+            metrics.push(self.sample_performance()?);
             
             if self.verbose {
                 println!("📊 TPS: {:.1}, Latency: {:.1}ms, Utilization: {:.1}%", 
@@ -241,67 +339,111 @@ impl NetworkAnalyzer {
         Ok(metrics)
     }
 
+    fn sample_performance(&self) -> Result<NetworkMetrics> {
+        let sample = self.source.simulated("network performance")?.network_sample();
+        Ok(NetworkMetrics {
+            timestamp: chrono::Utc::now(),
+            total_nodes: sample.total_nodes,
+            active_agents: sample.active_agents,
+            transactions_per_second: sample.transactions_per_second,
+            average_latency_ms: sample.latency_ms,
+            network_utilization: sample.utilization,
+            consensus_rate: sample.consensus_rate,
+        })
+    }
+
     async fn analyze_transactions(&self, window_hours: u64) -> Result<TransactionAnalysis> {
         info!("Analyzing transactions for the last {} hours", window_hours);
         
+        let sample = self.source.simulated("transaction history")?.transactions();
         let analysis = TransactionAnalysis {
-            total_transactions: 15_000,
-            successful_transactions: 14_750,
-            failed_transactions: 250,
-            average_value: 2.5,
-            peak_tps: 450.0,
-            volume_distribution: [
-                ("< 1 SOL".to_string(), 8_000),
-                ("1-10 SOL".to_string(), 5_000),
-                ("10-100 SOL".to_string(), 1_800),
-                ("> 100 SOL".to_string(), 200),
-            ].into_iter().collect(),
+            total_transactions: sample.total_transactions,
+            successful_transactions: sample.successful_transactions,
+            failed_transactions: sample.failed_transactions,
+            average_value: sample.average_value,
+            peak_tps: sample.peak_tps,
+            volume_distribution: sample.volume_distribution.into_iter().collect(),
         };
 
         Ok(analysis)
     }
 
-    async fn analyze_agents(&self, include_reputation: bool) -> Result<AgentStats> {
+    async fn analyze_agents(&self, include_reputation: bool, registry: Option<(&str, Option<&str>)>) -> Result<AgentStats> {
         info!("Analyzing agent network");
-        
+
+        if let (false, Some((api_url, token))) = (self.source.is_simulated(), registry) {
+            let agents = reputation::fetch_reputations(api_url, token).await?;
+            let mut by_capability = HashMap::new();
+            for capability in agents.iter().flat_map(|a| &a.capabilities) {
+                *by_capability.entry(capability.clone()).or_insert(0) += 1;
+            }
+
+            return Ok(AgentStats {
+                total_agents: agents.len(),
+                active_agents: agents.iter().filter(|a| a.active).count(),
+                by_capability,
+                reputation_distribution: if include_reputation {
+                    agents.iter().map(|a| a.reputation).collect()
+                } else {
+                    Vec::new()
+                },
+                inequality: include_reputation.then(|| reputation::analyze(&agents)),
+                connectivity_metrics: None,
+            });
+        }
+
+        let population = self.source.simulated("agent registry")?.agent_population();
+        let agents: Vec<AgentReputation> = population.reputation_scores.iter()
+            .zip(&population.transaction_counts)
+            .enumerate()
+            .map(|(i, (&reputation, &transaction_volume))| AgentReputation {
+                agent_id: format!("agent_{}", i),
+                capabilities: Vec::new(),
+                active: true,
+                reputation,
+                transaction_volume,
+            })
+            .collect();
+
         let stats = AgentStats {
-            total_agents: 150,
-            active_agents: 120,
-            by_capability: [
-                ("data_analysis".to_string(), 45),
-                ("computational_task".to_string(), 30),
-                ("market_research".to_string(), 25),
-                ("content_creation".to_string(), 20),
-                ("trading_service".to_string(), 15),
-                ("machine_learning".to_string(), 15),
-            ].into_iter().collect(),
+            total_agents: population.total_agents,
+            active_agents: population.active_agents,
+            by_capability: population.by_capability.into_iter().collect(),
             reputation_distribution: if include_reputation {
-                (0..150).map(|i| 0.3 + (i as f64 * 0.005) % 0.7).collect()
+                population.reputation_scores
             } else {
                 Vec::new()
             },
-            connectivity_metrics: ConnectivityMetrics {
-                average_connections: 8.5,
-                clustering_coefficient: 0.45,
-                network_diameter: 6,
-                isolated_nodes: 3,
-            },
+            inequality: include_reputation.then(|| reputation::analyze(&agents)),
+            connectivity_metrics: Some(ConnectivityMetrics {
+                average_connections: population.average_connections,
+                clustering_coefficient: population.clustering_coefficient,
+                network_diameter: population.network_diameter,
+                isolated_nodes: population.isolated_nodes,
+            }),
         };
 
         Ok(stats)
     }
 
+    async fn analyze_consensus(&self, finality_samples: usize, thresholds: &RiskThresholds) -> Result<ConsensusReport> {
+        info!("Analyzing consensus health");
+
+        let observation = if self.source.is_simulated() {
+            self.source.simulated("consensus state")?.consensus(100).into()
+        } else {
+            consensus::observe_rpc(&self.endpoint, finality_samples, Duration::from_secs(1)).await?
+        };
+
+        Ok(consensus::analyze(observation, thresholds))
+    }
+
     async fn health_check(&self) -> Result<HashMap<String, String>> {
         info!("Performing network health check");
         
-        let mut health = HashMap::new();
-        
-        // Simulate health checks
-        health.insert("consensus".to_string(), "✅ Healthy".to_string());
-        health.insert("connectivity".to_string(), "✅ Good".to_string());
-        health.insert("throughput".to_string(), "⚠️ Moderate".to_string());
-        health.insert("latency".to_string(), "✅ Low".to_string());
-        health.insert("error_rate".to_string(), "✅ Acceptable".to_string());
+        let health = self.source.simulated("network health")?.health()
+            .into_iter()
+            .collect();
 
         Ok(health)
     }
@@ -395,11 +537,112 @@ impl NetworkAnalyzer {
             println!("  {}: {}", capability, count);
         }
         
-        println!("\nConnectivity Metrics:");
-        println!("  Average connections: {:.1}", stats.connectivity_metrics.average_connections);
-        println!("  Clustering coefficient: {:.3}", stats.connectivity_metrics.clustering_coefficient);
-        println!("  Network diameter: {}", stats.connectivity_metrics.network_diameter);
-        println!("  Isolated nodes: {}", stats.connectivity_metrics.isolated_nodes);
+        if let Some(connectivity) = &stats.connectivity_metrics {
+            println!("\nConnectivity Metrics:");
+            println!("  Average connections: {:.1}", connectivity.average_connections);
+            println!("  Clustering coefficient: {:.3}", connectivity.clustering_coefficient);
+            println!("  Network diameter: {}", connectivity.network_diameter);
+            println!("  Isolated nodes: {}", connectivity.isolated_nodes);
+        }
+
+        if let Some(report) = &stats.inequality {
+            self.print_inequality_summary(report);
+        }
+    }
+
+    fn print_consensus_summary(&self, report: &ConsensusReport) {
+        println!("\n⛓️  Consensus Health");
+        println!("═══════════════════");
+
+        let epoch = &report.epoch;
+        println!("Epoch {}: {:.1}% complete ({}/{} slots, ~{:.1}h remaining)",
+            epoch.epoch, epoch.progress * 100.0, epoch.slot_index, epoch.slots_in_epoch,
+            epoch.estimated_secs_remaining / 3600.0);
+        if !epoch.recent_epoch_durations_secs.is_empty() {
+            let durations: Vec<String> = epoch.recent_epoch_durations_secs.iter()
+                .map(|secs| format!("{:.1}h", secs / 3600.0))
+                .collect();
+            println!("Recent epoch transitions: {}", durations.join(", "));
+        }
+
+        println!("\nValidator Set:");
+        println!("  Validators: {} ({} delinquent, {:.2}% of stake)",
+            report.total_validators, report.delinquent_validators, report.delinquent_stake_share * 100.0);
+        println!("  Missed slots: {} of {} ({:.2}%)", report.missed_slots, report.leader_slots, report.skip_rate * 100.0);
+        println!("  Vote participation: {:.2}% (stake-weighted)", report.vote_participation * 100.0);
+
+        match &report.slashing_events {
+            Some(events) => {
+                println!("\nSlashing Events: {}", events.len());
+                for event in events {
+                    println!("  epoch {} slot {}: {} ({}, {} lamports)", event.epoch, event.slot, event.validator, event.reason, event.amount);
+                }
+            }
+            None => println!("\nSlashing Events: not exposed by this endpoint"),
+        }
+
+        println!("\nFinality Latency ({} samples):", report.finality.samples);
+        println!("  p50: {:.0}ms  p90: {:.0}ms  p99: {:.0}ms  max: {:.0}ms",
+            report.finality.p50_ms, report.finality.p90_ms, report.finality.p99_ms, report.finality.max_ms);
+
+        if report.at_risk.is_empty() {
+            println!("\n✅ No validators at risk of removal");
+        } else {
+            println!("\n⚠️  Validators at risk of removal: {}", report.at_risk.len());
+            for validator in &report.at_risk {
+                println!("  {} ({:.2}% stake): {}", validator.identity, validator.stake_share * 100.0, validator.reasons.join(", "));
+            }
+        }
+    }
+
+    fn print_inequality_summary(&self, report: &InequalityReport) {
+        let distribution = &report.reputation;
+        println!("\nReputation Distribution:");
+        println!("  Mean: {:.3}  Median: {:.3}  Std dev: {:.3}", distribution.mean, distribution.median, distribution.std_dev);
+        println!("  P10: {:.3}  P90: {:.3}", distribution.p10, distribution.p90);
+        println!("  Gini coefficient: {:.3}", distribution.gini);
+        let largest = distribution.histogram.iter().copied().max().unwrap_or(0).max(1);
+        for (i, count) in distribution.histogram.iter().enumerate() {
+            let bar = "█".repeat(count * 30 / largest);
+            println!("  {:.1}-{:.1} {:>5} {}", i as f64 / 10.0, (i + 1) as f64 / 10.0, count, bar);
+        }
+
+        let volume = &report.volume;
+        println!("\nTransaction Volume Concentration:");
+        println!("  Total transactions: {}", volume.total_volume);
+        println!("  Gini coefficient: {:.3}", volume.gini);
+        println!("  Top 1% of agents: {:.1}% of volume", volume.top_1pct_share * 100.0);
+        println!("  Top 10% of agents: {:.1}% of volume", volume.top_10pct_share * 100.0);
+        println!("  HHI: {:.4}", volume.hhi);
+
+        if let Some(trend) = &report.trend {
+            println!("\nTrend over {} snapshots since {}:", trend.snapshots, trend.since.format("%Y-%m-%d %H:%M"));
+            println!("  Mean reputation: {:+.3}", trend.mean_reputation_change);
+            println!("  Reputation Gini: {:+.3}", trend.reputation_gini_change);
+            println!("  Volume Gini: {:+.3}", trend.volume_gini_change);
+            println!("  Top 10% volume share: {:+.1} pts", trend.top_10pct_share_change * 100.0);
+        }
+    }
+}
+
+/// Print one row per simulated parameter set
+fn print_churn_comparison(reports: &[ChurnReport]) {
+    println!("\n🔁 Consensus Under Churn");
+    println!("════════════════════════");
+    if let Some(first) = reports.first() {
+        let scenario = &first.scenario;
+        println!("{} epochs, {:.0}% joins / {:.0}% leaves per epoch, {:.0}ms ±{:.0}% delay, seed {}",
+            scenario.epochs, scenario.join_rate * 100.0, scenario.leave_rate * 100.0,
+            scenario.base_delay_ms, scenario.delay_jitter * 100.0, first.seed);
+    }
+
+    println!("\n{:>10} {:>9} {:>9} {:>9} {:>9} {:>9} {:>8} {:>9}",
+        "validators", "fork rate", "stalled", "p50 ms", "p95 ms", "p99 ms", "gini", "fairness");
+    for report in reports {
+        println!("{:>10} {:>8.2}% {:>9} {:>9.0} {:>9.0} {:>9.0} {:>8.3} {:>9.3}",
+            report.params.validators_per_epoch, report.fork_rate * 100.0, report.stalled,
+            report.finality_p50_ms, report.finality_p95_ms, report.finality_p99_ms,
+            report.reward_gini, report.slot_fairness);
     }
 }
 
@@ -413,7 +656,8 @@ async fn main() -> Result<()> {
         .with_env_filter(format!("solace_network_analyzer={}", log_level))
         .init();
 
-    let analyzer = NetworkAnalyzer::new(cli.endpoint.clone(), cli.verbose);
+    let analyzer = NetworkAnalyzer::new(cli.endpoint.clone(), cli.verbose, DataMode::from_flag(cli.simulated));
+    info!("Data mode: {}", analyzer.source.mode());
 
     match cli.command {
         Commands::Topology { depth, export } => {
@@ -458,8 +702,16 @@ async fn main() -> Result<()> {
             }
         },
         
-        Commands::Agents { reputation, capability: _capability } => {
-            let stats = analyzer.analyze_agents(reputation).await?;
+        Commands::Agents { reputation, capability: _capability, api_url, api_token, history, chart } => {
+            // Trends and charts are built from the inequality metrics
+            let include_reputation = reputation || history.is_some() || chart.is_some();
+            let registry = api_url.as_deref().map(|url| (url, api_token.as_deref()));
+            let mut stats = analyzer.analyze_agents(include_reputation, registry).await?;
+
+            let mut snapshots = Vec::new();
+            if let (Some(path), Some(report)) = (&history, stats.inequality.as_mut()) {
+                snapshots = reputation::record_history(std::path::Path::new(path), report)?;
+            }
             
             if cli.output == "table" {
                 analyzer.print_agent_summary(&stats);
@@ -467,8 +719,72 @@ async fn main() -> Result<()> {
                 let output = analyzer.format_output(&stats, &cli.output)?;
                 println!("{}", output);
             }
+
+            if let (Some(path), Some(report)) = (&chart, &stats.inequality) {
+                reputation::export_chart(std::path::Path::new(path), report, &snapshots)?;
+                println!("📈 Charts exported to: {}", path);
+            }
         },
         
+        Commands::Consensus { finality_samples, max_skip_rate, min_participation, max_vote_lag, min_stake, export } => {
+            let thresholds = RiskThresholds {
+                max_skip_rate,
+                min_vote_participation: min_participation,
+                max_vote_lag,
+                min_stake,
+                ..RiskThresholds::default()
+            };
+            let report = analyzer.analyze_consensus(finality_samples, &thresholds).await?;
+
+            if cli.output == "table" {
+                analyzer.print_consensus_summary(&report);
+            } else {
+                let output = analyzer.format_output(&report, &cli.output)?;
+                println!("{}", output);
+            }
+
+            if let Some(file_path) = export {
+                std::fs::write(&file_path, serde_json::to_string_pretty(&report)?)?;
+                println!("📁 Consensus report exported to: {}", file_path);
+            }
+        },
+
+        Commands::ChurnSim {
+            epochs, validators_per_epoch, epoch_duration, block_time_ms, initial_validators,
+            join_rate, leave_rate, stake_volatility, delay_ms, delay_jitter, seed, export,
+        } => {
+            let scenario = ChurnScenario {
+                epochs,
+                initial_validators,
+                join_rate,
+                leave_rate,
+                stake_volatility,
+                base_delay_ms: delay_ms,
+                delay_jitter,
+                ..ChurnScenario::default()
+            };
+            // Every candidate sees the same churn and delays
+            let reports: Vec<ChurnReport> = validators_per_epoch
+                .into_iter()
+                .map(|validators| {
+                    let params = ConsensusParams { validators_per_epoch: validators, epoch_duration, block_time_ms, ..ConsensusParams::default() };
+                    ChurnSimulation::new(params, scenario.clone(), seed).run()
+                })
+                .collect();
+
+            if cli.output == "table" {
+                print_churn_comparison(&reports);
+            } else {
+                let output = analyzer.format_output(&reports, &cli.output)?;
+                println!("{}", output);
+            }
+
+            if let Some(file_path) = export {
+                std::fs::write(&file_path, serde_json::to_string_pretty(&reports)?)?;
+                println!("📁 Churn simulation exported to: {}", file_path);
+            }
+        },
+
         Commands::Dashboard { refresh: _refresh } => {
             println!("📊 Starting real-time dashboard...");
             println!("(Interactive dashboard not implemented in this demo)");
@@ -484,22 +800,38 @@ async fn main() -> Result<()> {
             }
         },
         
-        Commands::Report { report_type: _report_type, output } => {
+        Commands::Report { report_type: _report_type, output, export_db } => {
             println!("📋 Generating comprehensive network report...");
             
             // Generate a comprehensive report
             let topology = analyzer.analyze_topology(3).await?;
-            let agents = analyzer.analyze_agents(true).await?;
+            let agents = analyzer.analyze_agents(true, None).await?;
             let transactions = analyzer.analyze_transactions(24).await?;
             let health = analyzer.health_check().await?;
+            let performance = vec![analyzer.sample_performance()?];
             
             let report = serde_json::json!({
                 "timestamp": chrono::Utc::now(),
                 "network_health": health,
                 "topology": topology,
+                "performance": performance,
                 "agent_stats": agents,
                 "transaction_analysis": transactions
             });
+
+            if let Some(db_path) = export_db {
+                let run_id = export::export_sqlite(std::path::Path::new(&db_path), &export::ReportData {
+                    data_mode: analyzer.source.mode().to_string(),
+                    endpoint: &analyzer.endpoint,
+                    nodes: &topology,
+                    metrics: &performance,
+                    transactions: &transactions,
+                    transaction_window_hours: 24,
+                    agents: &agents,
+                    health: &health,
+                })?;
+                println!("🗄️  Report exported to SQLite: {} (run {})", db_path, run_id);
+            }
             
             if let Some(file_path) = output {
                 let report_content = serde_json::to_string_pretty(&report)?;
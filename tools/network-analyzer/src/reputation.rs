@@ -0,0 +1,457 @@
+//! Reputation Distribution and Inequality Analysis
+//!
+//! Computes how reputation and transaction volume are spread across agents:
+//! the reputation histogram and percentiles, Gini coefficients, top-share and
+//! Herfindahl-Hirschman concentration of volume, and how these move over
+//! time. Each run appends a summary to a JSON-lines history file so trends
+//! can be tracked across runs, and charts can be exported as SVG or PNG.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+/// Number of reputation histogram buckets over [0, 1]
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// Agents fetched per page from the agents API
+const PAGE_SIZE: usize = 100;
+
+/// Registry entry for one agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentReputation {
+    pub agent_id: String,
+    pub capabilities: Vec<String>,
+    pub active: bool,
+    pub reputation: f64,
+    pub transaction_volume: u64,
+}
+
+/// Agent record as returned by `GET /api/v1/agents`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiAgent {
+    id: String,
+    #[serde(default)]
+    capabilities: Vec<String>,
+    #[serde(default)]
+    status: String,
+    reputation: f64,
+    #[serde(default)]
+    total_transactions: u64,
+}
+
+/// Page of agents from the agents API
+#[derive(Debug, Deserialize)]
+struct ApiAgentPage {
+    data: Vec<ApiAgent>,
+    pagination: ApiPagination,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiPagination {
+    pages: usize,
+}
+
+/// Fetch every agent's reputation and volume from the Solace API
+pub async fn fetch_reputations(api_url: &str, token: Option<&str>) -> Result<Vec<AgentReputation>> {
+    let client = reqwest::Client::new();
+    let mut agents = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let mut request = client
+            .get(format!("{}/api/v1/agents", api_url.trim_end_matches('/')))
+            .query(&[("page", page), ("limit", PAGE_SIZE)]);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+
+        let response: ApiAgentPage = request
+            .send()
+            .await
+            .context("Failed to query agents API")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Unexpected agents API response")?;
+
+        agents.extend(response.data.into_iter().map(|agent| AgentReputation {
+            agent_id: agent.id,
+            capabilities: agent.capabilities.iter().map(|capability| capability_key(capability)).collect(),
+            active: agent.status == "active",
+            reputation: agent.reputation,
+            transaction_volume: agent.total_transactions,
+        }));
+
+        if page >= response.pagination.pages {
+            return Ok(agents);
+        }
+        page += 1;
+    }
+}
+
+/// Canonical key of a capability, as the framework's `CapabilityRegistry`
+/// names it: `DataAnalysis`, `Data Analysis` and `data_analysis` all become
+/// `data_analysis`, and `Custom: Translation` becomes `custom.translation`
+pub fn capability_key(raw: &str) -> String {
+    let raw = raw.trim();
+    let custom = ["custom.", "Custom: ", "CustomService(", "CustomCapability("]
+        .iter()
+        .find_map(|prefix| raw.strip_prefix(prefix));
+    if let Some(name) = custom {
+        let name = name.trim_end_matches(')').trim();
+        return format!("custom.{}", name.to_lowercase().replace(char::is_whitespace, "_"));
+    }
+
+    let mut key = String::with_capacity(raw.len() + 4);
+    for c in raw.chars() {
+        if c.is_whitespace() || c == '-' || c == '_' {
+            if !key.is_empty() && !key.ends_with('_') {
+                key.push('_');
+            }
+        } else if c.is_uppercase() {
+            if !key.is_empty() && !key.ends_with('_') {
+                key.push('_');
+            }
+            key.extend(c.to_lowercase());
+        } else {
+            key.push(c);
+        }
+    }
+    key
+}
+
+/// Gini coefficient of non-negative values (0 = equal, 1 = one holder)
+pub fn gini(values: &[f64]) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let n = sorted.len() as f64;
+    let total: f64 = sorted.iter().sum();
+    if sorted.is_empty() || total <= 0.0 {
+        return 0.0;
+    }
+
+    let weighted: f64 = sorted.iter().enumerate().map(|(i, v)| (i + 1) as f64 * v).sum();
+    (2.0 * weighted) / (n * total) - (n + 1.0) / n
+}
+
+/// Value at a percentile (0-100) of sorted values
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (pct / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Points of the Lorenz curve: (share of agents, share of volume)
+pub fn lorenz_curve(values: &[f64]) -> Vec<(f64, f64)> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let total: f64 = sorted.iter().sum();
+    let n = sorted.len() as f64;
+
+    let mut points = vec![(0.0, 0.0)];
+    let mut cumulative = 0.0;
+    for (i, value) in sorted.iter().enumerate() {
+        cumulative += value;
+        let share = if total > 0.0 { cumulative / total } else { (i + 1) as f64 / n };
+        points.push(((i + 1) as f64 / n, share));
+    }
+    points
+}
+
+/// Reputation distribution summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReputationDistribution {
+    pub mean: f64,
+    pub median: f64,
+    pub p10: f64,
+    pub p90: f64,
+    pub std_dev: f64,
+    pub histogram: Vec<usize>,            // Counts per 0.1-wide bucket
+    pub gini: f64,
+}
+
+/// Concentration of transaction volume across agents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeConcentration {
+    pub total_volume: u64,
+    pub gini: f64,
+    pub top_1pct_share: f64,
+    pub top_10pct_share: f64,
+    pub hhi: f64,                         // Herfindahl-Hirschman index, 0-1
+    #[serde(skip)]
+    pub lorenz_curve: Vec<(f64, f64)>,    // Kept for chart export only
+}
+
+/// Change since the first recorded snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InequalityTrend {
+    pub snapshots: usize,
+    pub since: chrono::DateTime<chrono::Utc>,
+    pub mean_reputation_change: f64,
+    pub reputation_gini_change: f64,
+    pub volume_gini_change: f64,
+    pub top_10pct_share_change: f64,
+}
+
+/// Full inequality report for one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InequalityReport {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub agents: usize,
+    pub reputation: ReputationDistribution,
+    pub volume: VolumeConcentration,
+    pub trend: Option<InequalityTrend>,
+}
+
+/// One line of the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub agents: usize,
+    pub mean_reputation: f64,
+    pub reputation_gini: f64,
+    pub volume_gini: f64,
+    pub top_10pct_share: f64,
+}
+
+impl From<&InequalityReport> for HistoryEntry {
+    fn from(report: &InequalityReport) -> Self {
+        Self {
+            timestamp: report.timestamp,
+            agents: report.agents,
+            mean_reputation: report.reputation.mean,
+            reputation_gini: report.reputation.gini,
+            volume_gini: report.volume.gini,
+            top_10pct_share: report.volume.top_10pct_share,
+        }
+    }
+}
+
+/// Share of the total held by the top `fraction` of holders
+fn top_share(sorted_desc: &[f64], total: f64, fraction: f64) -> f64 {
+    if total <= 0.0 {
+        return 0.0;
+    }
+    let count = ((sorted_desc.len() as f64 * fraction).ceil() as usize).max(1);
+    sorted_desc.iter().take(count).sum::<f64>() / total
+}
+
+/// Compute the inequality report for a set of agents
+pub fn analyze(agents: &[AgentReputation]) -> InequalityReport {
+    let mut reputations: Vec<f64> = agents.iter().map(|a| a.reputation).collect();
+    reputations.sort_by(|a, b| a.total_cmp(b));
+
+    let n = reputations.len().max(1) as f64;
+    let mean = reputations.iter().sum::<f64>() / n;
+    let variance = reputations.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n;
+
+    let mut histogram = vec![0; HISTOGRAM_BUCKETS];
+    for reputation in &reputations {
+        let bucket = (reputation.clamp(0.0, 1.0) * HISTOGRAM_BUCKETS as f64) as usize;
+        histogram[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+    }
+
+    let mut volumes: Vec<f64> = agents.iter().map(|a| a.transaction_volume as f64).collect();
+    volumes.sort_by(|a, b| b.total_cmp(a));
+    let total_volume: f64 = volumes.iter().sum();
+    let hhi = if total_volume > 0.0 {
+        volumes.iter().map(|v| (v / total_volume).powi(2)).sum()
+    } else {
+        0.0
+    };
+
+    InequalityReport {
+        timestamp: chrono::Utc::now(),
+        agents: agents.len(),
+        reputation: ReputationDistribution {
+            mean,
+            median: percentile(&reputations, 50.0),
+            p10: percentile(&reputations, 10.0),
+            p90: percentile(&reputations, 90.0),
+            std_dev: variance.sqrt(),
+            histogram,
+            gini: gini(&reputations),
+        },
+        volume: VolumeConcentration {
+            total_volume: total_volume as u64,
+            gini: gini(&volumes),
+            top_1pct_share: top_share(&volumes, total_volume, 0.01),
+            top_10pct_share: top_share(&volumes, total_volume, 0.10),
+            hhi,
+            lorenz_curve: lorenz_curve(&volumes),
+        },
+        trend: None,
+    }
+}
+
+/// Load the history file, if any
+pub fn load_history(path: &Path) -> Result<Vec<HistoryEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    std::fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).context("Corrupt reputation history entry"))
+        .collect()
+}
+
+/// Append a report to the history file and fill in its trend
+pub fn record_history(path: &Path, report: &mut InequalityReport) -> Result<Vec<HistoryEntry>> {
+    let mut history = load_history(path)?;
+
+    if let Some(first) = history.first() {
+        report.trend = Some(InequalityTrend {
+            snapshots: history.len() + 1,
+            since: first.timestamp,
+            mean_reputation_change: report.reputation.mean - first.mean_reputation,
+            reputation_gini_change: report.reputation.gini - first.reputation_gini,
+            volume_gini_change: report.volume.gini - first.volume_gini,
+            top_10pct_share_change: report.volume.top_10pct_share - first.top_10pct_share,
+        });
+    }
+
+    let entry = HistoryEntry::from(&*report);
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+
+    history.push(entry);
+    Ok(history)
+}
+
+/// Export distribution, Lorenz curve, and Gini trend charts (SVG or PNG by extension)
+#[cfg(feature = "visualization")]
+pub fn export_chart(path: &Path, report: &InequalityReport, history: &[HistoryEntry]) -> Result<()> {
+    use plotters::prelude::*;
+
+    let result = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("svg") => draw_charts(SVGBackend::new(path, (1500, 450)).into_drawing_area(), report, history),
+        Some("png") => draw_charts(BitMapBackend::new(path, (1500, 450)).into_drawing_area(), report, history),
+        _ => return Err(anyhow!("Chart path must end in .svg or .png")),
+    };
+    result.map_err(|e| anyhow!("Failed to draw chart: {}", e))
+}
+
+/// Export distribution, Lorenz curve, and Gini trend charts (SVG or PNG by extension)
+#[cfg(not(feature = "visualization"))]
+pub fn export_chart(_path: &Path, _report: &InequalityReport, _history: &[HistoryEntry]) -> Result<()> {
+    Err(anyhow!("Chart export requires the 'visualization' feature"))
+}
+
+#[cfg(feature = "visualization")]
+fn draw_charts<DB: plotters::prelude::DrawingBackend>(
+    root: plotters::prelude::DrawingArea<DB, plotters::coord::Shift>,
+    report: &InequalityReport,
+    history: &[HistoryEntry],
+) -> std::result::Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    use plotters::prelude::*;
+
+    root.fill(&WHITE)?;
+    let panels = root.split_evenly((1, 3));
+
+    // Reputation histogram
+    let max_count = report.reputation.histogram.iter().copied().max().unwrap_or(0) as u32 + 1;
+    let mut chart = ChartBuilder::on(&panels[0])
+        .caption("Reputation distribution", ("sans-serif", 20))
+        .margin(10)
+        .x_label_area_size(35)
+        .y_label_area_size(45)
+        .build_cartesian_2d(0.0f64..1.0f64, 0u32..max_count)?;
+    chart.configure_mesh().x_desc("Reputation").y_desc("Agents").draw()?;
+    let width = 1.0 / HISTOGRAM_BUCKETS as f64;
+    chart.draw_series(report.reputation.histogram.iter().enumerate().map(|(i, &count)| {
+        let x = i as f64 * width;
+        Rectangle::new([(x, 0), (x + width * 0.9, count as u32)], BLUE.mix(0.6).filled())
+    }))?;
+
+    // Lorenz curve of transaction volume
+    let mut chart = ChartBuilder::on(&panels[1])
+        .caption(format!("Volume Lorenz curve (Gini {:.2})", report.volume.gini), ("sans-serif", 20))
+        .margin(10)
+        .x_label_area_size(35)
+        .y_label_area_size(45)
+        .build_cartesian_2d(0.0f64..1.0f64, 0.0f64..1.0f64)?;
+    chart.configure_mesh().x_desc("Share of agents").y_desc("Share of volume").draw()?;
+    chart.draw_series(LineSeries::new(vec![(0.0, 0.0), (1.0, 1.0)], BLACK.mix(0.4)))?;
+    chart.draw_series(LineSeries::new(report.volume.lorenz_curve.clone(), &RED))?;
+
+    // Gini coefficients over recorded snapshots
+    let mut chart = ChartBuilder::on(&panels[2])
+        .caption("Inequality over time", ("sans-serif", 20))
+        .margin(10)
+        .x_label_area_size(35)
+        .y_label_area_size(45)
+        .build_cartesian_2d(0usize..history.len().max(2), 0.0f64..1.0f64)?;
+    chart.configure_mesh().x_desc("Snapshot").y_desc("Gini").draw()?;
+    chart
+        .draw_series(LineSeries::new(history.iter().enumerate().map(|(i, e)| (i, e.reputation_gini)), &BLUE))?
+        .label("Reputation")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    chart
+        .draw_series(LineSeries::new(history.iter().enumerate().map(|(i, e)| (i, e.volume_gini)), &RED))?
+        .label("Volume")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    chart.configure_series_labels().border_style(BLACK).background_style(WHITE.mix(0.8)).draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(reputation: f64, volume: u64) -> AgentReputation {
+        AgentReputation {
+            agent_id: String::new(),
+            capabilities: Vec::new(),
+            active: true,
+            reputation,
+            transaction_volume: volume,
+        }
+    }
+
+    #[test]
+    fn test_gini_bounds() {
+        assert_eq!(gini(&[1.0, 1.0, 1.0, 1.0]), 0.0);
+        assert!((gini(&[0.0, 0.0, 0.0, 1.0]) - 0.75).abs() < 1e-9);
+        assert_eq!(gini(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_volume_concentration() {
+        let mut agents: Vec<_> = (0..9).map(|_| agent(0.5, 0)).collect();
+        agents.push(agent(0.9, 100));
+
+        let report = analyze(&agents);
+        assert_eq!(report.volume.total_volume, 100);
+        assert!((report.volume.top_10pct_share - 1.0).abs() < 1e-9);
+        assert!((report.volume.hhi - 1.0).abs() < 1e-9);
+        assert_eq!(report.reputation.histogram.iter().sum::<usize>(), 10);
+    }
+
+    #[test]
+    fn test_capability_keys_match_the_registry() {
+        for raw in ["DataAnalysis", "Data Analysis", "data_analysis", " data-analysis "] {
+            assert_eq!(capability_key(raw), "data_analysis");
+        }
+        assert_eq!(capability_key("MachineLearning"), "machine_learning");
+        assert_eq!(capability_key("Custom: Legal Review"), "custom.legal_review");
+        assert_eq!(capability_key("custom.legal_review"), "custom.legal_review");
+    }
+
+    #[test]
+    fn test_lorenz_curve_endpoints() {
+        let curve = lorenz_curve(&[1.0, 2.0, 3.0]);
+        assert_eq!(curve.first(), Some(&(0.0, 0.0)));
+        assert_eq!(curve.last(), Some(&(1.0, 1.0)));
+    }
+}
@@ -0,0 +1,279 @@
+//! Anomaly Detection
+//!
+//! Statistical detectors over the TPS, latency, and error rate series that
+//! flag unusual behavior even when no fixed alert threshold is crossed:
+//!
+//! - rolling z-score: a sample far from the mean of the recent window
+//! - EWMA control chart: a small but persistent shift in the smoothed series
+//! - seasonal baseline: a sample far from what is normal at that point in
+//!   the season (e.g. the same minute of the day)
+//!
+//! Each metric has its own sensitivity settings in the monitor config:
+//!
+//! ```toml
+//! [anomaly.latency]
+//! methods = ["z_score", "ewma"]
+//! z_threshold = 4.0
+//! window = 120
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Detection method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionMethod {
+    ZScore,
+    Ewma,
+    Seasonal,
+}
+
+/// Sensitivity settings for one metric
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricSensitivity {
+    pub enabled: bool,
+    pub methods: Vec<DetectionMethod>,
+    pub window: usize,                    // Samples in the rolling baseline
+    pub min_samples: usize,               // Samples needed before flagging
+    pub z_threshold: f64,                 // Standard deviations for z-score and seasonal
+    pub ewma_alpha: f64,                  // EWMA smoothing factor (0-1)
+    pub ewma_limit: f64,                  // EWMA control limit in standard deviations
+    pub season_length: usize,             // Samples per season, e.g. 1440 for daily at 1/min
+    pub min_seasons: usize,               // Observations per slot before seasonal checks
+}
+
+impl Default for MetricSensitivity {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            methods: vec![DetectionMethod::ZScore, DetectionMethod::Ewma],
+            window: 60,
+            min_samples: 20,
+            z_threshold: 3.0,
+            ewma_alpha: 0.3,
+            ewma_limit: 3.0,
+            season_length: 1440,
+            min_seasons: 3,
+        }
+    }
+}
+
+/// Anomaly detection configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub tps: MetricSensitivity,
+    pub latency: MetricSensitivity,
+    pub error_rate: MetricSensitivity,
+}
+
+/// A flagged sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub metric: String,
+    pub method: DetectionMethod,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub value: f64,
+    pub expected: f64,
+    pub score: f64,                       // Deviation in standard deviations
+}
+
+/// Running mean and variance (Welford)
+#[derive(Debug, Clone, Copy, Default)]
+struct RunningStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl RunningStats {
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn std_dev(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            (self.m2 / (self.count - 1) as f64).sqrt()
+        }
+    }
+}
+
+/// Standard deviation floor so a perfectly flat series still flags a jump
+fn std_floor(std_dev: f64, mean: f64) -> f64 {
+    std_dev.max(mean.abs() * 1e-6).max(1e-9)
+}
+
+/// Detector state for one metric series
+#[derive(Debug, Clone)]
+pub struct MetricDetector {
+    metric: String,
+    config: MetricSensitivity,
+    window: VecDeque<f64>,
+    ewma: Option<f64>,
+    seasons: Vec<RunningStats>,
+    observed: u64,
+}
+
+impl MetricDetector {
+    pub fn new(metric: &str, config: MetricSensitivity) -> Self {
+        Self {
+            metric: metric.to_string(),
+            window: VecDeque::with_capacity(config.window),
+            ewma: None,
+            seasons: vec![RunningStats::default(); config.season_length.max(1)],
+            observed: 0,
+            config,
+        }
+    }
+
+    /// Check a new sample against the baseline, then add it to the baseline
+    pub fn observe(&mut self, timestamp: chrono::DateTime<chrono::Utc>, value: f64) -> Vec<Anomaly> {
+        if !self.config.enabled || !value.is_finite() {
+            return Vec::new();
+        }
+
+        let mut baseline = RunningStats::default();
+        self.window.iter().for_each(|v| baseline.push(*v));
+        let ready = self.window.len() >= self.config.min_samples;
+
+        let alpha = self.config.ewma_alpha.clamp(0.01, 1.0);
+        let ewma = alpha * value + (1.0 - alpha) * self.ewma.unwrap_or(value);
+        let slot = (self.observed % self.seasons.len() as u64) as usize;
+
+        let mut anomalies = Vec::new();
+        let mut flag = |method, expected: f64, score: f64| {
+            anomalies.push(Anomaly {
+                metric: self.metric.clone(),
+                method,
+                timestamp,
+                value,
+                expected,
+                score,
+            });
+        };
+
+        for method in &self.config.methods {
+            match method {
+                DetectionMethod::ZScore if ready => {
+                    let std_dev = std_floor(baseline.std_dev(), baseline.mean);
+                    let score = (value - baseline.mean) / std_dev;
+                    if score.abs() > self.config.z_threshold {
+                        flag(DetectionMethod::ZScore, baseline.mean, score);
+                    }
+                }
+                DetectionMethod::Ewma if ready => {
+                    // Control limits for the EWMA statistic shrink by sqrt(alpha / (2 - alpha))
+                    let std_dev = std_floor(baseline.std_dev(), baseline.mean) * (alpha / (2.0 - alpha)).sqrt();
+                    let score = (ewma - baseline.mean) / std_dev;
+                    if score.abs() > self.config.ewma_limit {
+                        flag(DetectionMethod::Ewma, baseline.mean, score);
+                    }
+                }
+                DetectionMethod::Seasonal => {
+                    let season = self.seasons[slot];
+                    if season.count >= self.config.min_seasons as u64 {
+                        let score = (value - season.mean) / std_floor(season.std_dev(), season.mean);
+                        if score.abs() > self.config.z_threshold {
+                            flag(DetectionMethod::Seasonal, season.mean, score);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if self.window.len() == self.config.window.max(1) {
+            self.window.pop_front();
+        }
+        self.window.push_back(value);
+        self.ewma = Some(ewma);
+        self.seasons[slot].push(value);
+        self.observed += 1;
+
+        anomalies
+    }
+}
+
+/// Detectors for the network metric series
+#[derive(Debug, Clone)]
+pub struct NetworkAnomalyDetector {
+    tps: MetricDetector,
+    latency: MetricDetector,
+    error_rate: MetricDetector,
+}
+
+impl NetworkAnomalyDetector {
+    pub fn new(config: &AnomalyConfig) -> Self {
+        Self {
+            tps: MetricDetector::new("tps", config.tps.clone()),
+            latency: MetricDetector::new("latency", config.latency.clone()),
+            error_rate: MetricDetector::new("error_rate", config.error_rate.clone()),
+        }
+    }
+
+    /// Feed one network sample to every detector
+    pub fn observe(&mut self, metrics: &crate::NetworkMetrics) -> Vec<Anomaly> {
+        let mut anomalies = self.tps.observe(metrics.timestamp, metrics.total_tps);
+        anomalies.extend(self.latency.observe(metrics.timestamp, metrics.network_latency));
+        anomalies.extend(self.error_rate.observe(metrics.timestamp, metrics.error_rate));
+        anomalies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector(methods: Vec<DetectionMethod>) -> MetricDetector {
+        MetricDetector::new("test", MetricSensitivity {
+            methods,
+            min_samples: 10,
+            season_length: 4,
+            ..MetricSensitivity::default()
+        })
+    }
+
+    fn feed(detector: &mut MetricDetector, values: impl IntoIterator<Item = f64>) -> Vec<Anomaly> {
+        values.into_iter().flat_map(|v| detector.observe(chrono::Utc::now(), v)).collect()
+    }
+
+    #[test]
+    fn test_z_score_flags_spike() {
+        let mut d = detector(vec![DetectionMethod::ZScore]);
+        let noise = (0..40).map(|i| 100.0 + (i % 5) as f64);
+        assert!(feed(&mut d, noise).is_empty());
+
+        let flagged = feed(&mut d, [160.0]);
+        assert_eq!(flagged.len(), 1);
+        assert!(flagged[0].score > 3.0);
+    }
+
+    #[test]
+    fn test_ewma_flags_small_shift() {
+        let mut d = detector(vec![DetectionMethod::ZScore, DetectionMethod::Ewma]);
+        feed(&mut d, (0..40).map(|i| 100.0 + (i % 5) as f64));
+
+        // A shift of ~2 sigma never trips the z-score but accumulates in the EWMA
+        let flagged = feed(&mut d, std::iter::repeat(105.5).take(10));
+        assert!(flagged.iter().all(|a| a.method == DetectionMethod::Ewma));
+        assert!(!flagged.is_empty());
+    }
+
+    #[test]
+    fn test_seasonal_baseline() {
+        let mut d = detector(vec![DetectionMethod::Seasonal]);
+        let season = [10.0, 50.0, 90.0, 50.0];
+        let cycles = (0..5).flat_map(|c| season.iter().map(move |v| v + (c % 2) as f64));
+        assert!(feed(&mut d, cycles).is_empty());
+
+        // 90 is normal in slot 2 but not in slot 0
+        assert_eq!(feed(&mut d, [90.0]).len(), 1);
+    }
+}
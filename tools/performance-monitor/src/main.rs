@@ -7,6 +7,14 @@ use tracing::{info, warn, error, debug};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use solace_simulation::{DataMode, DataSource};
+
+mod anomaly;
+mod slo;
+
+use anomaly::{Anomaly, AnomalyConfig, NetworkAnomalyDetector};
+use slo::{LatencyHistogram, SloDefinition, SloStatus};
 
 #[derive(Parser)]
 #[command(name = "solace-monitor")]
@@ -27,6 +35,10 @@ struct Cli {
     /// Metrics export port
     #[arg(short = 'p', long, default_value = "9090")]
     port: u16,
+
+    /// Use simulated data instead of a live node
+    #[arg(long, global = true)]
+    simulated: bool,
 }
 
 #[derive(Subcommand)]
@@ -82,6 +94,13 @@ enum Commands {
         disk_io: bool,
     },
     
+    /// Transport bandwidth per peer
+    Bandwidth {
+        /// Number of busiest peers to list
+        #[arg(short, long, default_value = "10")]
+        top: usize,
+    },
+    
     /// Performance benchmarking
     Benchmark {
         /// Benchmark type
@@ -115,6 +134,9 @@ enum Commands {
         bind: String,
     },
     
+    /// Show SLO compliance and remaining error budgets
+    Slo,
+    
     /// Interactive TUI dashboard
     Dashboard,
 }
@@ -131,6 +153,8 @@ struct AgentMetrics {
     pub transaction_count: u64,
     pub transaction_success_rate: f64,
     pub average_response_time: f64,
+    #[serde(default)]
+    pub response_times: LatencyHistogram,
     pub reputation_score: f64,
     pub active_connections: u32,
 }
@@ -165,12 +189,15 @@ struct SystemMetrics {
 
 /// Alert configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 struct AlertConfig {
     pub cpu_threshold: f64,
     pub memory_threshold: f64,
     pub latency_threshold: f64,
     pub error_rate_threshold: f64,
     pub tps_minimum: f64,
+    pub slos: Vec<SloDefinition>,
+    pub anomaly: AnomalyConfig,
 }
 
 impl Default for AlertConfig {
@@ -181,6 +208,8 @@ impl Default for AlertConfig {
             latency_threshold: 1000.0,
             error_rate_threshold: 5.0,
             tps_minimum: 50.0,
+            slos: Vec::new(),
+            anomaly: AnomalyConfig::default(),
         }
     }
 }
@@ -188,28 +217,57 @@ impl Default for AlertConfig {
 /// Performance monitor implementation
 struct PerformanceMonitor {
     config: AlertConfig,
+    source: DataSource,
     metrics_storage: Arc<RwLock<Vec<NetworkMetrics>>>,
     agent_metrics: Arc<RwLock<HashMap<String, Vec<AgentMetrics>>>>,
     system_metrics: Arc<RwLock<Vec<SystemMetrics>>>,
+    tracked_agents: Vec<String>,
+    anomaly_detector: Arc<RwLock<NetworkAnomalyDetector>>,
+    anomalies: Arc<RwLock<Vec<Anomaly>>>,
 }
 
 impl PerformanceMonitor {
-    fn new(config: AlertConfig) -> Self {
+    fn new(config: AlertConfig, mode: DataMode) -> Self {
+        // Agents named by an SLO are sampled on every monitoring tick
+        let mut tracked_agents: Vec<String> = config.slos.iter().filter_map(|slo| slo.agent.clone()).collect();
+        tracked_agents.sort();
+        tracked_agents.dedup();
+        
+        let anomaly_detector = NetworkAnomalyDetector::new(&config.anomaly);
+        
         Self {
             config,
+            source: DataSource::new(mode),
             metrics_storage: Arc::new(RwLock::new(Vec::new())),
             agent_metrics: Arc::new(RwLock::new(HashMap::new())),
             system_metrics: Arc::new(RwLock::new(Vec::new())),
+            tracked_agents,
+            anomaly_detector: Arc::new(RwLock::new(anomaly_detector)),
+            anomalies: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
-    async fn start_monitoring(&self, interval: Duration) -> Result<()> {
+    /// Sample an agent's metrics on every monitoring tick
+    fn track_agent(&mut self, agent_id: String) {
+        if !self.tracked_agents.contains(&agent_id) {
+            self.tracked_agents.push(agent_id);
+        }
+    }
+
+    /// Collect metrics and check alerts every `interval` until `cancel` fires
+    async fn start_monitoring(&self, interval: Duration, cancel: &CancellationToken) -> Result<()> {
         info!("Starting performance monitoring with interval {:?}", interval);
         
         let mut ticker = tokio::time::interval(interval);
         
         loop {
-            ticker.tick().await;
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = cancel.cancelled() => {
+                    info!("Performance monitoring stopped");
+                    return Ok(());
+                }
+            }
             
             // Collect metrics
             if let Err(e) = self.collect_network_metrics().await {
@@ -220,21 +278,28 @@ impl PerformanceMonitor {
                 error!("Failed to collect system metrics: {}", e);
             }
             
+            for agent_id in &self.tracked_agents {
+                if let Err(e) = self.collect_agent_metrics(agent_id).await {
+                    error!("Failed to collect metrics for agent {}: {}", agent_id, e);
+                }
+            }
+            
             // Check alerts
             self.check_alerts().await;
         }
     }
 
     async fn collect_network_metrics(&self) -> Result<()> {
+        let sample = self.source.simulated("network metrics")?.network_sample();
         let metrics = NetworkMetrics {
             timestamp: chrono::Utc::now(),
-            total_tps: 150.0 + (rand::random::<f64>() * 100.0),
-            consensus_time: 500.0 + (rand::random::<f64>() * 200.0),
-            network_latency: 45.0 + (rand::random::<f64>() * 30.0),
-            active_validators: 25,
-            total_agents: 150,
-            network_utilization: 0.6 + (rand::random::<f64>() * 0.3),
-            error_rate: rand::random::<f64>() * 2.0,
+            total_tps: sample.transactions_per_second,
+            consensus_time: sample.consensus_time_ms,
+            network_latency: sample.latency_ms,
+            active_validators: sample.active_validators,
+            total_agents: sample.total_agents,
+            network_utilization: sample.utilization,
+            error_rate: sample.error_rate,
         };
         
         let mut storage = self.metrics_storage.write().await;
@@ -244,10 +309,26 @@ impl PerformanceMonitor {
         if storage.len() > 1000 {
             storage.drain(0..storage.len() - 1000);
         }
+        drop(storage);
         
         debug!("Collected network metrics: TPS={:.1}, Latency={:.1}ms", 
             metrics.total_tps, metrics.network_latency);
         
+        let detected = self.anomaly_detector.write().await.observe(&metrics);
+        if !detected.is_empty() {
+            for anomaly in &detected {
+                warn!("🔎 Anomalous {} detected ({:?}): {:.2} vs expected {:.2} ({:+.1}σ)",
+                    anomaly.metric, anomaly.method, anomaly.value, anomaly.expected, anomaly.score);
+            }
+            
+            let mut anomalies = self.anomalies.write().await;
+            anomalies.extend(detected);
+            if anomalies.len() > 1000 {
+                let excess = anomalies.len() - 1000;
+                anomalies.drain(0..excess);
+            }
+        }
+        
         Ok(())
     }
 
@@ -255,16 +336,22 @@ impl PerformanceMonitor {
         let mut sys = sysinfo::System::new_all();
         sys.refresh_all();
         
+        let io = if self.source.is_simulated() {
+            self.source.simulated("host I/O")?.host_io_sample()
+        } else {
+            host_io_sample(&sys)
+        };
+        
         let metrics = SystemMetrics {
             timestamp: chrono::Utc::now(),
             cpu_usage: sys.global_cpu_info().cpu_usage() as f64,
             memory_usage: (sys.used_memory() as f64 / sys.total_memory() as f64) * 100.0,
             memory_total: sys.total_memory(),
-            disk_usage: 45.0 + (rand::random::<f64>() * 20.0), // Simulated
-            disk_io_read: rand::random::<u64>() % 1000000,
-            disk_io_write: rand::random::<u64>() % 500000,
-            network_rx: rand::random::<u64>() % 10000000,
-            network_tx: rand::random::<u64>() % 5000000,
+            disk_usage: io.disk_usage,
+            disk_io_read: io.disk_io_read,
+            disk_io_write: io.disk_io_write,
+            network_rx: io.network_rx,
+            network_tx: io.network_tx,
             load_average: sys.load_average().into(),
         };
         
@@ -283,28 +370,51 @@ impl PerformanceMonitor {
     }
 
     async fn collect_agent_metrics(&self, agent_id: &str) -> Result<AgentMetrics> {
+        let sample = self.source.simulated("agent metrics")?.agent_sample();
         let metrics = AgentMetrics {
             agent_id: agent_id.to_string(),
             timestamp: chrono::Utc::now(),
-            cpu_usage: 15.0 + (rand::random::<f64>() * 30.0),
-            memory_usage: 20.0 + (rand::random::<f64>() * 25.0),
-            network_in: rand::random::<u64>() % 1000000,
-            network_out: rand::random::<u64>() % 500000,
-            transaction_count: rand::random::<u64>() % 100,
-            transaction_success_rate: 95.0 + (rand::random::<f64>() * 5.0),
-            average_response_time: 50.0 + (rand::random::<f64>() * 100.0),
-            reputation_score: 0.7 + (rand::random::<f64>() * 0.3),
-            active_connections: rand::random::<u32>() % 20,
+            cpu_usage: sample.cpu_usage,
+            memory_usage: sample.memory_usage,
+            network_in: sample.network_in,
+            network_out: sample.network_out,
+            transaction_count: sample.transaction_count,
+            transaction_success_rate: sample.transaction_success_rate,
+            average_response_time: sample.average_response_time,
+            response_times: LatencyHistogram::from_samples(&sample.response_times),
+            reputation_score: sample.reputation_score,
+            active_connections: sample.active_connections,
         };
         
+        // Keep history for the longest SLO window
+        let retention_hours = self.config.slos.iter().map(|slo| slo.window_hours).max().unwrap_or(24);
+        let cutoff = chrono::Utc::now() - chrono::Duration::hours(retention_hours as i64);
+        
         let mut storage = self.agent_metrics.write().await;
-        storage.entry(agent_id.to_string())
-            .or_insert_with(Vec::new)
-            .push(metrics.clone());
+        let history = storage.entry(agent_id.to_string()).or_insert_with(Vec::new);
+        history.retain(|m| m.timestamp > cutoff);
+        history.push(metrics.clone());
         
         Ok(metrics)
     }
 
+    /// Evaluate every configured SLO against each agent it applies to
+    async fn slo_status(&self) -> Vec<SloStatus> {
+        let agent_metrics = self.agent_metrics.read().await;
+        let now = chrono::Utc::now();
+        
+        let mut agents: Vec<_> = agent_metrics.keys().collect();
+        agents.sort();
+        
+        let mut statuses = Vec::new();
+        for slo in &self.config.slos {
+            for agent_id in agents.iter().filter(|agent_id| slo.applies_to(agent_id)) {
+                statuses.push(slo.evaluate(agent_id, &agent_metrics[*agent_id], now));
+            }
+        }
+        statuses
+    }
+
     async fn check_alerts(&self) {
         let network_metrics = self.metrics_storage.read().await;
         let system_metrics = self.system_metrics.read().await;
@@ -332,9 +442,17 @@ impl PerformanceMonitor {
                 warn!("🚨 High memory usage detected: {:.1}%", latest_system.memory_usage);
             }
         }
+        drop(network_metrics);
+        drop(system_metrics);
+        
+        for status in self.slo_status().await.iter().filter(|status| status.alerting) {
+            warn!("🚨 SLO '{}' for {} burning error budget at {:.1}x ({:.0}% budget left)",
+                status.name, status.agent, status.burn_rate, status.error_budget_remaining * 100.0);
+        }
     }
 
     async fn get_network_summary(&self, period_hours: u64) -> Result<NetworkSummary> {
+        let uptime_percentage = self.source.simulated("network uptime")?.uptime_percentage();
         let metrics = self.metrics_storage.read().await;
         let cutoff = chrono::Utc::now() - chrono::Duration::hours(period_hours as i64);
         
@@ -362,44 +480,122 @@ impl PerformanceMonitor {
             min_latency,
             avg_error_rate,
             total_transactions: (avg_tps * period_hours as f64 * 3600.0) as u64,
-            uptime_percentage: 99.5, // Simulated
+            uptime_percentage,
         })
     }
 
-    async fn run_benchmark(&self, duration: Duration) -> Result<BenchmarkResults> {
+    /// Run the benchmark, giving up if `cancel` fires first. A cancelled
+    /// run reports no results, since a partial sample would skew them.
+    async fn run_benchmark(&self, duration: Duration, cancel: &CancellationToken) -> Result<BenchmarkResults> {
         info!("Running performance benchmark for {:?}", duration);
         
         let start_time = Instant::now();
-        let mut results = BenchmarkResults::default();
+        let sample = self.source.simulated("benchmark target")?.benchmark();
         
         // Simulate various benchmark tests
-        tokio::time::sleep(Duration::from_secs(2)).await;
-        
-        results.transaction_throughput = 1250.0;
-        results.latency_p50 = 45.0;
-        results.latency_p95 = 150.0;
-        results.latency_p99 = 300.0;
-        results.cpu_efficiency = 85.0;
-        results.memory_efficiency = 90.0;
-        results.network_efficiency = 88.0;
-        results.consensus_performance = 92.0;
-        
-        results.duration = start_time.elapsed();
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(2)) => {}
+            _ = cancel.cancelled() => anyhow::bail!("Benchmark cancelled after {:?}", start_time.elapsed()),
+        }
         
-        Ok(results)
+        Ok(BenchmarkResults {
+            duration: start_time.elapsed(),
+            transaction_throughput: sample.transaction_throughput,
+            latency_p50: sample.latency_p50,
+            latency_p95: sample.latency_p95,
+            latency_p99: sample.latency_p99,
+            cpu_efficiency: sample.cpu_efficiency,
+            memory_efficiency: sample.memory_efficiency,
+            network_efficiency: sample.network_efficiency,
+            consensus_performance: sample.consensus_performance,
+        })
     }
 
-    fn export_metrics(&self, format: &str, range_hours: u64) -> Result<String> {
-        // This would export metrics in the specified format
+    async fn export_metrics(&self, format: &str, range_hours: u64) -> Result<String> {
+        let slos = self.slo_status().await;
+        let metrics = self.metrics_storage.read().await;
+        let anomalies = self.anomalies.read().await;
+        let cutoff = chrono::Utc::now() - chrono::Duration::hours(range_hours as i64);
+        let recent: Vec<_> = metrics.iter().filter(|m| m.timestamp > cutoff).collect();
+        
         match format {
-            "json" => Ok(serde_json::to_string_pretty(&"metrics data")?),
-            "csv" => Ok("timestamp,tps,latency,error_rate\n2023-01-01T00:00:00Z,150.0,45.0,1.2".to_string()),
-            "prometheus" => Ok("# HELP solace_tps Transactions per second\nsolace_tps 150.0".to_string()),
+            "json" => Ok(serde_json::to_string_pretty(&serde_json::json!({
+                "network": recent,
+                "slos": slos,
+                "anomalies": anomalies.iter().filter(|a| a.timestamp > cutoff).collect::<Vec<_>>(),
+            }))?),
+            "csv" => {
+                let mut csv = "timestamp,tps,latency,error_rate".to_string();
+                for m in &recent {
+                    csv.push_str(&format!("\n{},{:.1},{:.1},{:.2}",
+                        m.timestamp.to_rfc3339(), m.total_tps, m.network_latency, m.error_rate));
+                }
+                if !slos.is_empty() {
+                    csv.push_str("\n\nslo,agent,objective,compliance,error_budget_remaining,burn_rate,met");
+                    for s in &slos {
+                        csv.push_str(&format!("\n{},{},{:.4},{:.4},{:.4},{:.2},{}",
+                            s.name, s.agent, s.objective, s.compliance, s.error_budget_remaining, s.burn_rate, s.met));
+                    }
+                }
+                Ok(csv)
+            },
+            "prometheus" => {
+                let mut output = "# HELP solace_tps Transactions per second".to_string();
+                if let Some(latest) = recent.last() {
+                    output.push_str(&format!("\nsolace_tps {:.1}", latest.total_tps));
+                }
+                if !slos.is_empty() {
+                    output.push_str("\n# HELP solace_slo_compliance Fraction of good events in the SLO window");
+                    output.push_str("\n# HELP solace_slo_error_budget_remaining Fraction of error budget left");
+                    output.push_str("\n# HELP solace_slo_burn_rate Error budget burn rate over the alert window");
+                    for s in &slos {
+                        let labels = format!("slo=\"{}\",agent=\"{}\"", s.name, s.agent);
+                        output.push_str(&format!("\nsolace_slo_compliance{{{}}} {:.4}", labels, s.compliance));
+                        output.push_str(&format!("\nsolace_slo_error_budget_remaining{{{}}} {:.4}", labels, s.error_budget_remaining));
+                        output.push_str(&format!("\nsolace_slo_burn_rate{{{}}} {:.2}", labels, s.burn_rate));
+                    }
+                }
+                Ok(output)
+            },
             _ => Err(anyhow::anyhow!("Unsupported export format: {}", format)),
         }
     }
 }
 
+/// Read real disk and network I/O counters from the host
+fn host_io_sample(sys: &sysinfo::System) -> solace_simulation::HostIoSample {
+    use sysinfo::{DiskExt, NetworkExt, ProcessExt, SystemExt};
+    
+    let (disk_total, disk_available) = sys.disks().iter()
+        .fold((0u64, 0u64), |(total, available), disk| {
+            (total + disk.total_space(), available + disk.available_space())
+        });
+    let disk_usage = if disk_total > 0 {
+        (disk_total - disk_available) as f64 / disk_total as f64 * 100.0
+    } else {
+        0.0
+    };
+    
+    let (disk_io_read, disk_io_write) = sys.processes().values()
+        .map(|process| process.disk_usage())
+        .fold((0u64, 0u64), |(read, written), usage| {
+            (read + usage.read_bytes, written + usage.written_bytes)
+        });
+    
+    let (network_rx, network_tx) = sys.networks().iter()
+        .fold((0u64, 0u64), |(rx, tx), (_, data)| {
+            (rx + data.received(), tx + data.transmitted())
+        });
+    
+    solace_simulation::HostIoSample {
+        disk_usage,
+        disk_io_read,
+        disk_io_write,
+        network_rx,
+        network_tx,
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct NetworkSummary {
     pub period_hours: u64,
@@ -425,6 +621,22 @@ struct BenchmarkResults {
     pub consensus_performance: f64,
 }
 
+/// Byte count with a binary unit
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
 fn load_alert_config(path: Option<&str>) -> Result<AlertConfig> {
     if let Some(config_path) = path {
         let content = std::fs::read_to_string(config_path)
@@ -449,17 +661,28 @@ async fn main() -> Result<()> {
 
     // Load configuration
     let alert_config = load_alert_config(cli.config.as_deref()).unwrap_or_default();
-    let monitor = PerformanceMonitor::new(alert_config);
+    let mut monitor = PerformanceMonitor::new(alert_config, DataMode::from_flag(cli.simulated));
+    debug!("Data mode: {}", monitor.source.mode());
+
+    // Ctrl-C stops monitoring and benchmarks cleanly
+    let cancel = CancellationToken::new();
+    let interrupted = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            interrupted.cancel();
+        }
+    });
 
     match cli.command {
         Commands::Monitor { target, interval, alerts: _alerts } => {
             if let Some(target) = target {
                 info!("Monitoring target: {}", target);
+                monitor.track_agent(target);
             } else {
                 info!("Monitoring entire network");
             }
             
-            monitor.start_monitoring(Duration::from_secs(interval)).await?;
+            monitor.start_monitoring(Duration::from_secs(interval), &cancel).await?;
         },
         
         Commands::Agent { agent_id, window, detailed } => {
@@ -488,6 +711,7 @@ async fn main() -> Result<()> {
             println!("🌐 Network Performance Analysis ({})", analysis_type);
             println!("════════════════════════════════════════");
             
+            monitor.collect_network_metrics().await?;
             let summary = monitor.get_network_summary(period).await?;
             
             println!("Period: {} hours", summary.period_hours);
@@ -518,10 +742,28 @@ async fn main() -> Result<()> {
             }
         },
         
+        Commands::Bandwidth { top } => {
+            println!("📶 Transport Bandwidth");
+            println!("═════════════════════");
+            
+            let sample = monitor.source.simulated("bandwidth")?.bandwidth_sample(25);
+            
+            println!("Read: {} ({}/s)", format_bytes(sample.bytes_read as f64), format_bytes(sample.read_rate));
+            println!("Written: {} ({}/s)", format_bytes(sample.bytes_written as f64), format_bytes(sample.write_rate));
+            println!("Throttled transfers: {}", sample.throttled);
+            
+            println!("\n{:<12} {:>12} {:>12} {:>12} {:>12}", "Peer", "Read", "Written", "Read/s", "Written/s");
+            for peer in sample.peers.iter().take(top) {
+                println!("{:<12} {:>12} {:>12} {:>12} {:>12}", peer.node_id,
+                    format_bytes(peer.bytes_read as f64), format_bytes(peer.bytes_written as f64),
+                    format_bytes(peer.read_rate), format_bytes(peer.write_rate));
+            }
+        },
+        
         Commands::Benchmark { benchmark_type, duration } => {
             println!("🚀 Running {} benchmark for {} minutes...", benchmark_type, duration);
             
-            let results = monitor.run_benchmark(Duration::from_secs(duration * 60)).await?;
+            let results = monitor.run_benchmark(Duration::from_secs(duration * 60), &cancel).await?;
             
             println!("\n📊 Benchmark Results");
             println!("═══════════════════");
@@ -539,7 +781,11 @@ async fn main() -> Result<()> {
         Commands::Export { format, output, range } => {
             println!("📤 Exporting metrics data ({} format, {} hours)...", format, range);
             
-            let data = monitor.export_metrics(&format, range)?;
+            monitor.collect_network_metrics().await?;
+            for agent_id in monitor.tracked_agents.clone() {
+                monitor.collect_agent_metrics(&agent_id).await?;
+            }
+            let data = monitor.export_metrics(&format, range).await?;
             std::fs::write(&output, data)?;
             
             println!("✅ Metrics exported to: {}", output);
@@ -553,16 +799,44 @@ async fn main() -> Result<()> {
             let monitor_clone = Arc::new(monitor);
             let _monitor_handle = {
                 let monitor = monitor_clone.clone();
+                let cancel = cancel.clone();
                 tokio::spawn(async move {
-                    if let Err(e) = monitor.start_monitoring(Duration::from_secs(5)).await {
+                    if let Err(e) = monitor.start_monitoring(Duration::from_secs(5), &cancel).await {
                         error!("Monitoring task failed: {}", e);
                     }
                 })
             };
             
-            // Keep server running
-            loop {
-                tokio::time::sleep(Duration::from_secs(1)).await;
+            // Keep server running until interrupted
+            cancel.cancelled().await;
+        },
+        
+        Commands::Slo => {
+            println!("🎯 Service Level Objectives");
+            println!("═══════════════════════════");
+            
+            if monitor.config.slos.is_empty() {
+                println!("No SLOs configured. Add [[slos]] entries to the config file.");
+                return Ok(());
+            }
+            
+            for agent_id in monitor.tracked_agents.clone() {
+                monitor.collect_agent_metrics(&agent_id).await?;
+            }
+            
+            let statuses = monitor.slo_status().await;
+            if statuses.is_empty() {
+                println!("No agent metrics collected yet. Name agents in [[slos]] or run 'monitor --target <agent>'.");
+            }
+            
+            for status in statuses {
+                let icon = if status.alerting { "🔥" } else if status.met { "✅" } else { "❌" };
+                println!("{} {} [{}]: {:.3}% of {:.3}% target over {}h, {:.1}% budget left, burn {:.2}x",
+                    icon, status.name, status.agent, status.compliance * 100.0, status.objective * 100.0,
+                    status.window_hours, status.error_budget_remaining * 100.0, status.burn_rate);
+                if let Some(latency) = status.latency_quantile_ms {
+                    println!("   p{}: {:.1}ms", (status.objective * 1000.0).round() / 10.0, latency);
+                }
             }
         },
         
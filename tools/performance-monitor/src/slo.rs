@@ -0,0 +1,310 @@
+//! Service Level Objectives
+//!
+//! Operators declare SLOs in the monitor config, for example "99% of
+//! proposals answered in under 2s" or "success rate of at least 97%". Each
+//! SLO is evaluated over a rolling window of collected agent metrics to give
+//! compliance and remaining error budget, and over a shorter alert window to
+//! give the burn rate used for alerting.
+//!
+//! Response time SLOs count individual responses, not sample averages: each
+//! sample carries a `LatencyHistogram` of the responses it covers, so "99%
+//! under 2s" holds exactly when the window's p99 is under 2s. The status
+//! reports that quantile alongside compliance.
+//!
+//! ```toml
+//! [[slos]]
+//! name = "proposal-latency"
+//! objective = 0.99
+//! indicator = { type = "response_time", threshold_ms = 2000.0 }
+//!
+//! [[slos]]
+//! name = "success-rate"
+//! agent = "agent-1"
+//! objective = 0.97
+//! window_hours = 168
+//! indicator = { type = "success_rate" }
+//! ```
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::AgentMetrics;
+
+/// Relative width of a histogram bucket
+const BUCKET_GROWTH: f64 = 1.05;
+
+/// Response times bucketed on a log scale, each bucket 5% wider than the
+/// last, so quantiles over many responses cost a few dozen counters
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    buckets: BTreeMap<i32, u64>,          // Bucket index -> responses
+    count: u64,
+}
+
+impl LatencyHistogram {
+    pub fn from_samples(response_times_ms: &[f64]) -> Self {
+        let mut histogram = Self::default();
+        for &ms in response_times_ms {
+            histogram.record(ms);
+        }
+        histogram
+    }
+
+    pub fn record(&mut self, ms: f64) {
+        *self.buckets.entry(bucket(ms)).or_default() += 1;
+        self.count += 1;
+    }
+
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (&index, &count) in &other.buckets {
+            *self.buckets.entry(index).or_default() += count;
+        }
+        self.count += other.count;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Responses faster than `ms`; a response in the bucket holding `ms` counts as slower
+    pub fn count_below(&self, ms: f64) -> u64 {
+        self.buckets.range(..bucket(ms)).map(|(_, &count)| count).sum()
+    }
+
+    /// Latency at quantile `q`, as the upper bound of the bucket it falls in
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (&index, &count) in &self.buckets {
+            seen += count;
+            if seen >= rank {
+                return Some(BUCKET_GROWTH.powi(index));
+            }
+        }
+        None
+    }
+}
+
+/// Index of the bucket `(1.05^(i-1), 1.05^i]` holding `ms`
+fn bucket(ms: f64) -> i32 {
+    (ms.max(0.01).ln() / BUCKET_GROWTH.ln()).ceil() as i32
+}
+
+/// What counts as a good event for an SLO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SloIndicator {
+    /// Transactions answered faster than the threshold
+    ResponseTime { threshold_ms: f64 },
+    /// Transactions that completed successfully
+    SuccessRate,
+}
+
+/// A service level objective from the monitor config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub agent: Option<String>,            // None = every tracked agent
+    pub indicator: SloIndicator,
+    pub objective: f64,                   // Target fraction of good events, e.g. 0.99
+    #[serde(default = "default_window_hours")]
+    pub window_hours: u64,                // Rolling compliance window
+    #[serde(default = "default_alert_window_minutes")]
+    pub alert_window_minutes: u64,        // Window for burn rate alerts
+    #[serde(default = "default_burn_rate_threshold")]
+    pub burn_rate_threshold: f64,         // Alert when budget burns this many times too fast
+}
+
+fn default_window_hours() -> u64 {
+    24 * 30
+}
+
+fn default_alert_window_minutes() -> u64 {
+    60
+}
+
+fn default_burn_rate_threshold() -> f64 {
+    2.0
+}
+
+/// Evaluated state of one SLO for one agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloStatus {
+    pub name: String,
+    pub agent: String,
+    pub objective: f64,
+    pub window_hours: u64,
+    pub samples: usize,
+    pub compliance: f64,
+    pub error_budget_remaining: f64,      // Fraction of budget left, negative once exhausted
+    pub burn_rate: f64,                   // 1.0 = exactly on budget over the alert window
+    pub latency_quantile_ms: Option<f64>, // Response time SLOs: latency at the objective's quantile, e.g. p99
+    pub met: bool,
+    pub alerting: bool,
+}
+
+/// Good and total event counts over a set of samples
+fn good_and_total(indicator: &SloIndicator, samples: &[&AgentMetrics]) -> (f64, f64) {
+    samples.iter().fold((0.0, 0.0), |(good, total), sample| match indicator {
+        SloIndicator::ResponseTime { threshold_ms } => {
+            let responses = &sample.response_times;
+            (good + responses.count_below(*threshold_ms) as f64, total + responses.count() as f64)
+        }
+        SloIndicator::SuccessRate => {
+            // Samples are weighted by the transactions they cover
+            let weight = sample.transaction_count.max(1) as f64;
+            (good + weight * (sample.transaction_success_rate / 100.0).clamp(0.0, 1.0), total + weight)
+        }
+    })
+}
+
+impl SloDefinition {
+    /// Whether this SLO applies to an agent
+    pub fn applies_to(&self, agent_id: &str) -> bool {
+        self.agent.as_deref().is_none_or(|agent| agent == agent_id)
+    }
+
+    /// Evaluate the SLO against an agent's metric history
+    pub fn evaluate(&self, agent_id: &str, history: &[AgentMetrics], now: DateTime<Utc>) -> SloStatus {
+        let window_start = now - Duration::hours(self.window_hours as i64);
+        let alert_start = now - Duration::minutes(self.alert_window_minutes as i64);
+
+        let in_window: Vec<&AgentMetrics> = history.iter().filter(|m| m.timestamp > window_start).collect();
+        let in_alert_window: Vec<&AgentMetrics> = in_window.iter().copied().filter(|m| m.timestamp > alert_start).collect();
+
+        let error_budget = (1.0 - self.objective).max(f64::EPSILON);
+
+        let (good, total) = good_and_total(&self.indicator, &in_window);
+        let compliance = if total > 0.0 { good / total } else { 1.0 };
+        let error_budget_remaining = 1.0 - (1.0 - compliance) / error_budget;
+
+        let latency_quantile_ms = match self.indicator {
+            SloIndicator::ResponseTime { .. } => {
+                let mut responses = LatencyHistogram::default();
+                for sample in &in_window {
+                    responses.merge(&sample.response_times);
+                }
+                responses.quantile(self.objective)
+            }
+            SloIndicator::SuccessRate => None,
+        };
+
+        let (alert_good, alert_total) = good_and_total(&self.indicator, &in_alert_window);
+        let burn_rate = if alert_total > 0.0 {
+            (1.0 - alert_good / alert_total) / error_budget
+        } else {
+            0.0
+        };
+
+        SloStatus {
+            name: self.name.clone(),
+            agent: agent_id.to_string(),
+            objective: self.objective,
+            window_hours: self.window_hours,
+            samples: in_window.len(),
+            compliance,
+            error_budget_remaining,
+            burn_rate,
+            latency_quantile_ms,
+            met: compliance >= self.objective,
+            alerting: burn_rate > self.burn_rate_threshold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(minutes_ago: i64, response_ms: f64, success_rate: f64) -> AgentMetrics {
+        sample_with(minutes_ago, &[response_ms; 10], success_rate)
+    }
+
+    fn sample_with(minutes_ago: i64, response_times: &[f64], success_rate: f64) -> AgentMetrics {
+        AgentMetrics {
+            agent_id: "agent-1".to_string(),
+            timestamp: Utc::now() - Duration::minutes(minutes_ago),
+            cpu_usage: 0.0,
+            memory_usage: 0.0,
+            network_in: 0,
+            network_out: 0,
+            transaction_count: response_times.len() as u64,
+            transaction_success_rate: success_rate,
+            average_response_time: response_times.iter().sum::<f64>() / response_times.len() as f64,
+            response_times: LatencyHistogram::from_samples(response_times),
+            reputation_score: 0.5,
+            active_connections: 1,
+        }
+    }
+
+    fn slo(indicator: SloIndicator, objective: f64) -> SloDefinition {
+        SloDefinition {
+            name: "test".to_string(),
+            agent: None,
+            indicator,
+            objective,
+            window_hours: 24,
+            alert_window_minutes: 60,
+            burn_rate_threshold: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_response_time_budget() {
+        // 1 slow sample in 10 against a 95% objective: budget overspent twice over
+        let history: Vec<_> = (0..10).map(|i| sample(i * 10, if i == 0 { 3000.0 } else { 500.0 }, 100.0)).collect();
+        let status = slo(SloIndicator::ResponseTime { threshold_ms: 2000.0 }, 0.95)
+            .evaluate("agent-1", &history, Utc::now());
+
+        assert!((status.compliance - 0.9).abs() < 1e-9);
+        assert!((status.error_budget_remaining + 1.0).abs() < 1e-9);
+        assert!(!status.met);
+        assert!(status.alerting);
+    }
+
+    #[test]
+    fn test_response_time_counts_responses_not_averages() {
+        // Averages of 150ms hide one 3s response in every hundred: p99 misses 2s
+        let mut responses = vec![120.0; 99];
+        responses.push(3000.0);
+        let history: Vec<_> = (0..10).map(|i| sample_with(i * 10, &responses, 100.0)).collect();
+        assert!(history.iter().all(|m| m.average_response_time < 200.0));
+
+        let strict = slo(SloIndicator::ResponseTime { threshold_ms: 2000.0 }, 0.995)
+            .evaluate("agent-1", &history, Utc::now());
+        assert!((strict.compliance - 0.99).abs() < 1e-9);
+        assert!(!strict.met);
+        assert!(strict.latency_quantile_ms.unwrap() > 2000.0);
+
+        let p99 = slo(SloIndicator::ResponseTime { threshold_ms: 2000.0 }, 0.99)
+            .evaluate("agent-1", &history, Utc::now());
+        assert!(p99.met);
+        let latency = p99.latency_quantile_ms.unwrap();
+        assert!((120.0..=126.0).contains(&latency));
+    }
+
+    #[test]
+    fn test_success_rate_within_budget() {
+        let history: Vec<_> = (0..10).map(|i| sample(i * 10, 100.0, 98.0)).collect();
+        let status = slo(SloIndicator::SuccessRate, 0.97).evaluate("agent-1", &history, Utc::now());
+
+        assert!(status.met);
+        assert!(status.error_budget_remaining > 0.0);
+        assert!(!status.alerting);
+    }
+
+    #[test]
+    fn test_old_samples_leave_window() {
+        let history = vec![sample(60 * 48, 5000.0, 0.0)];
+        let status = slo(SloIndicator::SuccessRate, 0.99).evaluate("agent-1", &history, Utc::now());
+
+        assert_eq!(status.samples, 0);
+        assert_eq!(status.compliance, 1.0);
+    }
+}
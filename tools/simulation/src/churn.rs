@@ -0,0 +1,366 @@
+//! Consensus Churn Simulation
+//!
+//! Runs the Proof-of-Reputation validator rotation through many epochs while
+//! validators join and leave, stakes drift, and network delay changes from
+//! one epoch to the next, then reports how often blocks fork, how long they
+//! take to finalize, and how evenly rewards land on the validators that
+//! served. Running the same scenario under different `ConsensusParams` gives
+//! evidence for tuning the framework's `ConsensusConfig` defaults.
+//!
+//! The model follows the consensus engine: each epoch the heaviest active
+//! validators are selected by the same stake and reputation weighting,
+//! producers rotate round-robin by height, and a block finalizes once
+//! two thirds of the epoch's validators plus one have voted for it. A block
+//! forks when it reaches the next producer later than one block time, and
+//! the orphaned block earns nothing. Validators that leave mid-epoch stay in
+//! the epoch's set but stop producing and voting.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Consensus parameters under test, mirroring the framework's
+/// `ConsensusConfig` and its defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusParams {
+    pub block_time_ms: u64,
+    pub validators_per_epoch: usize,
+    pub min_validator_stake: u64,
+    pub reputation_weight: f64,
+    pub stake_weight: f64,
+    pub epoch_duration: u32,              // Blocks per epoch
+}
+
+impl Default for ConsensusParams {
+    fn default() -> Self {
+        Self {
+            block_time_ms: 2_000,
+            validators_per_epoch: 21,
+            min_validator_stake: 1000,
+            reputation_weight: 0.4,
+            stake_weight: 0.6,
+            epoch_duration: 1000,
+        }
+    }
+}
+
+impl ConsensusParams {
+    /// Selection weight of a validator, as the consensus engine computes it
+    pub fn weight(&self, stake: u64, reputation: f64) -> f64 {
+        if stake < self.min_validator_stake {
+            return 0.0;
+        }
+        let stake_component = (stake as f64 / 1_000_000.0).ln_1p() * self.stake_weight;
+        reputation * self.reputation_weight + stake_component
+    }
+
+    /// Approving votes needed to finalize a block with `validators` in the epoch
+    pub fn quorum(&self, validators: usize) -> usize {
+        validators * 2 / 3 + 1
+    }
+}
+
+/// How the validator pool and the network behave over the run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChurnScenario {
+    pub epochs: u32,
+    pub initial_validators: usize,
+    pub join_rate: f64,                   // New validators per epoch, as a fraction of the pool
+    pub leave_rate: f64,                  // Chance a validator leaves during an epoch
+    pub stake_volatility: f64,            // Largest fractional stake change per epoch
+    pub base_delay_ms: f64,               // Mean one-way message delay
+    pub delay_jitter: f64,                // Epoch delay varies within ±this fraction of the base
+    pub uptime: f64,                      // Chance an online validator answers in a given slot
+    pub block_reward: u64,
+}
+
+impl Default for ChurnScenario {
+    fn default() -> Self {
+        Self {
+            epochs: 50,
+            initial_validators: 40,
+            join_rate: 0.05,
+            leave_rate: 0.05,
+            stake_volatility: 0.1,
+            base_delay_ms: 150.0,
+            delay_jitter: 0.5,
+            uptime: 0.98,
+            block_reward: 10,
+        }
+    }
+}
+
+/// What happened in one epoch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochOutcome {
+    pub epoch: u32,
+    pub pool: usize,                      // Registered validators at the epoch boundary
+    pub selected: usize,
+    pub joined: usize,
+    pub left: usize,
+    pub delay_ms: f64,
+    pub missed: u64,                      // Slots whose producer was gone or down
+    pub forks: u64,
+    pub finalized: u64,
+    pub stalled: u64,                     // Blocks that never reached a quorum
+}
+
+/// Outcome of a full run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChurnReport {
+    pub params: ConsensusParams,
+    pub scenario: ChurnScenario,
+    pub seed: u64,
+    pub epochs: Vec<EpochOutcome>,
+    pub slots: u64,
+    pub produced: u64,
+    pub forks: u64,
+    pub finalized: u64,
+    pub stalled: u64,
+    pub fork_rate: f64,                   // Forks per produced block
+    pub finality_p50_ms: f64,
+    pub finality_p95_ms: f64,
+    pub finality_p99_ms: f64,
+    pub reward_gini: f64,                 // Over every validator that was ever selected
+    pub slot_fairness: f64,               // Jain's index of reward per assigned slot; 1.0 is even
+}
+
+#[derive(Debug, Clone)]
+struct SimValidator {
+    stake: u64,
+    reputation: f64,
+    leaves_at: Option<u64>,               // Block offset in the current epoch
+    slots: u64,
+    reward: u64,
+    served: bool,
+}
+
+/// Reproducible consensus run under validator churn
+#[derive(Debug)]
+pub struct ChurnSimulation {
+    params: ConsensusParams,
+    scenario: ChurnScenario,
+    seed: u64,
+    rng: StdRng,
+    validators: Vec<SimValidator>,        // Registered and departed, by index
+}
+
+impl ChurnSimulation {
+    pub fn new(params: ConsensusParams, scenario: ChurnScenario, seed: u64) -> Self {
+        Self {
+            params,
+            scenario,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            validators: Vec::new(),
+        }
+    }
+
+    fn join(&mut self) {
+        let stake = self.params.min_validator_stake + self.rng.gen_range(0..1_000_000);
+        let reputation = self.rng.gen_range(0.3..1.0);
+        self.validators.push(SimValidator { stake, reputation, leaves_at: None, slots: 0, reward: 0, served: false });
+    }
+
+    /// Heaviest active validators in the pool, as the engine selects them
+    fn select(&self, pool: &[usize]) -> Vec<usize> {
+        let mut weighted: Vec<(usize, f64)> = pool
+            .iter()
+            .map(|&i| (i, self.params.weight(self.validators[i].stake, self.validators[i].reputation)))
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect();
+        weighted.sort_by(|a, b| b.1.total_cmp(&a.1));
+        weighted.into_iter().take(self.params.validators_per_epoch).map(|(i, _)| i).collect()
+    }
+
+    /// One-way delay to a peer, exponentially distributed around the epoch's mean
+    fn delay(&mut self, mean_ms: f64) -> f64 {
+        -mean_ms * (1.0 - self.rng.gen::<f64>()).ln()
+    }
+
+    pub fn run(mut self) -> ChurnReport {
+        for _ in 0..self.scenario.initial_validators {
+            self.join();
+        }
+
+        let blocks = self.params.epoch_duration as u64;
+        let block_time = self.params.block_time_ms as f64;
+        let mut pool: Vec<usize> = (0..self.validators.len()).collect();
+        let mut epochs = Vec::new();
+        let mut latencies = Vec::new();
+
+        for epoch in 0..self.scenario.epochs {
+            // Joins made during the last epoch count from this boundary on
+            let joins = self.scenario.join_rate * pool.len() as f64;
+            let joined = joins as usize + usize::from(self.rng.gen_bool(joins.fract()));
+            for _ in 0..joined {
+                self.join();
+                pool.push(self.validators.len() - 1);
+            }
+            for &i in &pool {
+                let drift = self.rng.gen_range(-self.scenario.stake_volatility..=self.scenario.stake_volatility);
+                let validator = &mut self.validators[i];
+                validator.stake = (validator.stake as f64 * (1.0 + drift)).max(0.0) as u64;
+                validator.leaves_at = self.rng.gen_bool(self.scenario.leave_rate).then(|| self.rng.gen_range(0..blocks));
+            }
+
+            let selected = self.select(&pool);
+            let delay_ms = self.scenario.base_delay_ms
+                * (1.0 + self.rng.gen_range(-self.scenario.delay_jitter..=self.scenario.delay_jitter));
+            let quorum = self.params.quorum(selected.len());
+            let mut outcome = EpochOutcome {
+                epoch,
+                pool: pool.len(),
+                selected: selected.len(),
+                joined,
+                left: 0,
+                delay_ms,
+                missed: 0,
+                forks: 0,
+                finalized: 0,
+                stalled: 0,
+            };
+
+            for offset in 0..blocks {
+                if selected.is_empty() {
+                    outcome.missed += 1;
+                    continue;
+                }
+                let producer = selected[offset as usize % selected.len()];
+                self.validators[producer].slots += 1;
+                self.validators[producer].served = true;
+
+                let uptime = self.scenario.uptime;
+                let up = |v: &SimValidator, rng: &mut StdRng| v.leaves_at.is_none_or(|at| offset < at) && rng.gen_bool(uptime);
+                if !up(&self.validators[producer], &mut self.rng) {
+                    outcome.missed += 1;
+                    continue;
+                }
+
+                // The next producer builds on the parent if the block is late
+                if self.delay(delay_ms) > block_time {
+                    outcome.forks += 1;
+                    continue;
+                }
+
+                let mut votes = Vec::with_capacity(selected.len());
+                for &voter in &selected {
+                    if up(&self.validators[voter], &mut self.rng) {
+                        let round_trip = self.delay(delay_ms) + self.delay(delay_ms);
+                        votes.push(round_trip);
+                    }
+                }
+                if votes.len() < quorum {
+                    outcome.stalled += 1;
+                    continue;
+                }
+                votes.sort_by(f64::total_cmp);
+                latencies.push(votes[quorum - 1]);
+                outcome.finalized += 1;
+                self.validators[producer].reward += self.scenario.block_reward;
+            }
+
+            let departed: Vec<usize> = pool.iter().copied().filter(|&i| self.validators[i].leaves_at.is_some()).collect();
+            outcome.left = departed.len();
+            pool.retain(|i| !departed.contains(i));
+            epochs.push(outcome);
+        }
+
+        self.report(epochs, latencies)
+    }
+
+    fn report(self, epochs: Vec<EpochOutcome>, mut latencies: Vec<f64>) -> ChurnReport {
+        latencies.sort_by(f64::total_cmp);
+        let sum = |field: fn(&EpochOutcome) -> u64| epochs.iter().map(field).sum::<u64>();
+        let slots = self.params.epoch_duration as u64 * epochs.len() as u64;
+        let (missed, forks, finalized, stalled) = (sum(|e| e.missed), sum(|e| e.forks), sum(|e| e.finalized), sum(|e| e.stalled));
+        let produced = slots - missed;
+
+        let served: Vec<&SimValidator> = self.validators.iter().filter(|v| v.served).collect();
+        let rewards: Vec<f64> = served.iter().map(|v| v.reward as f64).collect();
+        let per_slot: Vec<f64> = served.iter().map(|v| v.reward as f64 / v.slots as f64).collect();
+
+        ChurnReport {
+            params: self.params,
+            scenario: self.scenario,
+            seed: self.seed,
+            epochs,
+            slots,
+            produced,
+            forks,
+            finalized,
+            stalled,
+            fork_rate: if produced > 0 { forks as f64 / produced as f64 } else { 0.0 },
+            finality_p50_ms: percentile(&latencies, 0.50),
+            finality_p95_ms: percentile(&latencies, 0.95),
+            finality_p99_ms: percentile(&latencies, 0.99),
+            reward_gini: gini(&rewards),
+            slot_fairness: jain_index(&per_slot),
+        }
+    }
+}
+
+/// Value at quantile `q` of sorted samples, 0.0 if there are none
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * q).round() as usize;
+    sorted[rank]
+}
+
+/// Gini coefficient: 0.0 when everyone has the same, toward 1.0 when one has all
+fn gini(values: &[f64]) -> f64 {
+    let total: f64 = values.iter().sum();
+    if values.len() < 2 || total <= 0.0 {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+    let ranked: f64 = sorted.iter().enumerate().map(|(i, v)| (i as f64 + 1.0) * v).sum();
+    (2.0 * ranked) / (n * total) - (n + 1.0) / n
+}
+
+/// Jain's fairness index: 1.0 when all values are equal, 1/n at worst
+fn jain_index(values: &[f64]) -> f64 {
+    let total: f64 = values.iter().sum();
+    let squares: f64 = values.iter().map(|v| v * v).sum();
+    if squares <= 0.0 {
+        return 1.0;
+    }
+    total * total / (values.len() as f64 * squares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn short(scenario: ChurnScenario) -> ChurnScenario {
+        ChurnScenario { epochs: 10, ..scenario }
+    }
+
+    #[test]
+    fn test_churn_and_delay_degrade_consensus() {
+        let params = ConsensusParams { epoch_duration: 200, ..ConsensusParams::default() };
+        let calm = ChurnScenario { leave_rate: 0.0, join_rate: 0.0, base_delay_ms: 50.0, delay_jitter: 0.0, uptime: 1.0, ..ChurnScenario::default() };
+        let rough = ChurnScenario { leave_rate: 0.3, join_rate: 0.3, base_delay_ms: 600.0, uptime: 0.9, ..ChurnScenario::default() };
+
+        let quiet = ChurnSimulation::new(params.clone(), short(calm.clone()), 1).run();
+        assert_eq!(quiet.slots, 2000);
+        assert_eq!(quiet.stalled, 0);
+        assert!(quiet.slot_fairness > 0.99);
+        assert!(quiet.finality_p50_ms <= quiet.finality_p99_ms);
+
+        let churned = ChurnSimulation::new(params.clone(), short(rough), 1).run();
+        assert!(churned.fork_rate > quiet.fork_rate);
+        assert!(churned.finality_p95_ms > quiet.finality_p95_ms);
+        assert!(churned.epochs.iter().map(|e| e.left).sum::<usize>() > 0);
+        assert!(churned.slot_fairness < quiet.slot_fairness);
+
+        // Same seed, same run
+        let again = ChurnSimulation::new(params, short(calm), 1).run();
+        assert_eq!(again.finality_p95_ms, quiet.finality_p95_ms);
+        assert_eq!(again.reward_gini, quiet.reward_gini);
+    }
+}
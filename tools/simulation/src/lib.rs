@@ -0,0 +1,637 @@
+//! Solace Simulation Data Provider
+//!
+//! Shared source of generated data for Solace Protocol tooling. Tools run in
+//! one of two explicit modes: in simulated mode every metric comes from the
+//! [`SimulationProvider`], and in real mode any request for data that has no
+//! live source fails with [`DataSourceError::NotConnected`] instead of
+//! returning fabricated numbers.
+
+pub mod churn;
+pub mod persona;
+
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use solace_ai::strategy::{CounterOfferResponse, NegotiationState};
+use solace_ai::{DecisionContext, MarketConditions};
+use thiserror::Error;
+
+pub use churn::{ChurnReport, ChurnScenario, ChurnSimulation, ConsensusParams, EpochOutcome};
+pub use persona::{Persona, PersonaMix, PersonaProfile, SimulatedAgent};
+
+/// Data source errors
+#[derive(Debug, Error)]
+pub enum DataSourceError {
+    #[error("not connected: no live source for {0} (rerun with --simulated to use generated data)")]
+    NotConnected(String),
+}
+
+/// Where tool data comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DataMode {
+    Real,
+    Simulated,
+}
+
+impl DataMode {
+    /// Select the mode from a `--simulated` flag
+    pub fn from_flag(simulated: bool) -> Self {
+        if simulated {
+            DataMode::Simulated
+        } else {
+            DataMode::Real
+        }
+    }
+}
+
+impl fmt::Display for DataMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataMode::Real => write!(f, "real"),
+            DataMode::Simulated => write!(f, "simulated"),
+        }
+    }
+}
+
+/// Mode-aware gateway to simulated data
+///
+/// Every code path that would otherwise invent data must go through
+/// [`DataSource::simulated`], which only hands out the provider in
+/// simulated mode.
+#[derive(Debug)]
+pub struct DataSource {
+    mode: DataMode,
+    provider: Option<Mutex<SimulationProvider>>,
+}
+
+impl DataSource {
+    /// Create a data source for the given mode
+    pub fn new(mode: DataMode) -> Self {
+        let provider = match mode {
+            DataMode::Real => None,
+            DataMode::Simulated => Some(Mutex::new(SimulationProvider::new())),
+        };
+
+        Self { mode, provider }
+    }
+
+    /// Create a simulated data source with a fixed seed
+    pub fn simulated_with_seed(seed: u64) -> Self {
+        Self {
+            mode: DataMode::Simulated,
+            provider: Some(Mutex::new(SimulationProvider::with_seed(seed))),
+        }
+    }
+
+    /// Current data mode
+    pub fn mode(&self) -> DataMode {
+        self.mode
+    }
+
+    /// Check whether data is simulated
+    pub fn is_simulated(&self) -> bool {
+        self.mode == DataMode::Simulated
+    }
+
+    /// Access the simulation provider, or fail with `NotConnected` in real mode
+    pub fn simulated(&self, what: &str) -> Result<MutexGuard<'_, SimulationProvider>, DataSourceError> {
+        match &self.provider {
+            Some(provider) => Ok(provider.lock().unwrap_or_else(|poisoned| poisoned.into_inner())),
+            None => Err(DataSourceError::NotConnected(what.to_string())),
+        }
+    }
+}
+
+/// Simulated network-wide metrics sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkSample {
+    pub transactions_per_second: f64,
+    pub consensus_time_ms: f64,
+    pub consensus_rate: f64,
+    pub latency_ms: f64,
+    pub utilization: f64,
+    pub error_rate: f64,
+    pub total_nodes: usize,
+    pub active_validators: u32,
+    pub total_agents: u32,
+    pub active_agents: usize,
+}
+
+/// Simulated agent metrics sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSample {
+    pub cpu_usage: f64,
+    pub memory_usage: f64,
+    pub network_in: u64,
+    pub network_out: u64,
+    pub transaction_count: u64,
+    pub transaction_success_rate: f64,
+    pub average_response_time: f64,
+    pub response_times: Vec<f64>,         // Each transaction's response time, ms
+    pub reputation_score: f64,
+    pub active_connections: u32,
+}
+
+/// Simulated host disk and network I/O sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostIoSample {
+    pub disk_usage: f64,
+    pub disk_io_read: u64,
+    pub disk_io_write: u64,
+    pub network_rx: u64,
+    pub network_tx: u64,
+}
+
+/// Simulated bytes one peer moved, mirroring ACP's `PeerBandwidth`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerBandwidthSample {
+    pub node_id: String,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    pub read_rate: f64,
+    pub write_rate: f64,
+}
+
+/// Simulated transport traffic, mirroring ACP's `BandwidthStats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BandwidthSample {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    pub read_rate: f64,
+    pub write_rate: f64,
+    pub throttled: u64,
+    pub peers: Vec<PeerBandwidthSample>,  // Busiest first
+}
+
+/// Simulated benchmark results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkSample {
+    pub transaction_throughput: f64,
+    pub latency_p50: f64,
+    pub latency_p95: f64,
+    pub latency_p99: f64,
+    pub cpu_efficiency: f64,
+    pub memory_efficiency: f64,
+    pub network_efficiency: f64,
+    pub consensus_performance: f64,
+}
+
+/// Role of a simulated network node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeRole {
+    Agent,
+    Validator,
+    Relay,
+    Client,
+}
+
+/// Simulated network node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedNode {
+    pub id: String,
+    pub address: String,
+    pub role: NodeRole,
+    pub connections: Vec<String>,
+    pub uptime: Duration,
+    pub latency_ms: f64,
+    pub throughput_tps: f64,
+    pub error_rate: f64,
+    pub reputation_score: f64,
+}
+
+/// Simulated transaction totals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionSample {
+    pub total_transactions: u64,
+    pub successful_transactions: u64,
+    pub failed_transactions: u64,
+    pub average_value: f64,
+    pub peak_tps: f64,
+    pub volume_distribution: Vec<(String, u64)>,
+}
+
+/// Simulated agent population
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentPopulation {
+    pub total_agents: usize,
+    pub active_agents: usize,
+    pub by_capability: Vec<(String, usize)>,
+    pub reputation_scores: Vec<f64>,
+    pub transaction_counts: Vec<u64>,
+    pub average_connections: f64,
+    pub clustering_coefficient: f64,
+    pub network_diameter: usize,
+    pub isolated_nodes: usize,
+}
+
+/// Simulated validator state for the current epoch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorSample {
+    pub identity: String,
+    pub vote_account: String,
+    pub stake: u64,
+    pub delinquent: bool,
+    pub last_vote: u64,
+    pub leader_slots: u64,
+    pub blocks_produced: u64,
+    pub epoch_credits: u64,
+}
+
+/// Simulated slashing event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashingSample {
+    pub validator: String,
+    pub epoch: u64,
+    pub slot: u64,
+    pub reason: String,
+    pub amount: u64,
+}
+
+/// Simulated consensus state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusSample {
+    pub epoch: u64,
+    pub absolute_slot: u64,
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    pub recent_epoch_durations_secs: Vec<f64>,
+    pub validators: Vec<ValidatorSample>,
+    pub slashing_events: Vec<SlashingSample>,
+    pub finality_lag_ms: Vec<f64>,
+}
+
+/// Simulated trade between two persona-driven agents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSample {
+    pub seller: String,
+    pub buyer: String,
+    pub seller_persona: Persona,
+    pub buyer_persona: Persona,
+    pub base_price: f64,
+    pub agreed_price: Option<f64>,
+    pub rounds: u32,
+    pub paid: bool,
+    pub quality: f64,                     // True quality of the delivered service
+    pub rating: f64,                      // Buyer's rating of the seller
+}
+
+/// Generator for simulated tool data
+#[derive(Debug)]
+pub struct SimulationProvider {
+    rng: StdRng,
+}
+
+impl SimulationProvider {
+    /// Create a provider seeded from the OS
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Create a reproducible provider
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Generate a network metrics sample
+    pub fn network_sample(&mut self) -> NetworkSample {
+        NetworkSample {
+            transactions_per_second: self.rng.gen_range(150.0..250.0),
+            consensus_time_ms: self.rng.gen_range(500.0..700.0),
+            consensus_rate: self.rng.gen_range(0.98..1.0),
+            latency_ms: self.rng.gen_range(45.0..75.0),
+            utilization: self.rng.gen_range(0.6..0.9),
+            error_rate: self.rng.gen_range(0.0..2.0),
+            total_nodes: 50,
+            active_validators: 25,
+            total_agents: 150,
+            active_agents: 35,
+        }
+    }
+
+    /// Generate an agent metrics sample
+    pub fn agent_sample(&mut self) -> AgentSample {
+        let transaction_count = self.rng.gen_range(0..100);
+        // Mostly fast responses with an occasional slow tail
+        let response_times: Vec<f64> = (0..transaction_count)
+            .map(|_| if self.rng.gen_bool(0.02) { self.rng.gen_range(500.0..3000.0) } else { self.rng.gen_range(50.0..150.0) })
+            .collect();
+        let average_response_time = if response_times.is_empty() {
+            self.rng.gen_range(50.0..150.0)
+        } else {
+            response_times.iter().sum::<f64>() / response_times.len() as f64
+        };
+        AgentSample {
+            cpu_usage: self.rng.gen_range(15.0..45.0),
+            memory_usage: self.rng.gen_range(20.0..45.0),
+            network_in: self.rng.gen_range(0..1_000_000),
+            network_out: self.rng.gen_range(0..500_000),
+            transaction_count,
+            transaction_success_rate: self.rng.gen_range(95.0..100.0),
+            average_response_time,
+            response_times,
+            reputation_score: self.rng.gen_range(0.7..1.0),
+            active_connections: self.rng.gen_range(0..20),
+        }
+    }
+
+    /// Generate a host I/O sample
+    pub fn host_io_sample(&mut self) -> HostIoSample {
+        HostIoSample {
+            disk_usage: self.rng.gen_range(45.0..65.0),
+            disk_io_read: self.rng.gen_range(0..1_000_000),
+            disk_io_write: self.rng.gen_range(0..500_000),
+            network_rx: self.rng.gen_range(0..10_000_000),
+            network_tx: self.rng.gen_range(0..5_000_000),
+        }
+    }
+
+    /// Generate a node's traffic with `peers` connected peers
+    pub fn bandwidth_sample(&mut self, peers: usize) -> BandwidthSample {
+        let mut peers: Vec<PeerBandwidthSample> = (0..peers)
+            .map(|i| PeerBandwidthSample {
+                node_id: format!("node-{:04}", i),
+                bytes_read: self.rng.gen_range(0..50_000_000),
+                bytes_written: self.rng.gen_range(0..20_000_000),
+                read_rate: self.rng.gen_range(0.0..200_000.0),
+                write_rate: self.rng.gen_range(0.0..80_000.0),
+            })
+            .collect();
+        peers.sort_by_key(|peer| std::cmp::Reverse(peer.bytes_read + peer.bytes_written));
+        BandwidthSample {
+            bytes_read: peers.iter().map(|peer| peer.bytes_read).sum(),
+            bytes_written: peers.iter().map(|peer| peer.bytes_written).sum(),
+            read_rate: peers.iter().map(|peer| peer.read_rate).sum(),
+            write_rate: peers.iter().map(|peer| peer.write_rate).sum(),
+            throttled: self.rng.gen_range(0..50),
+            peers,
+        }
+    }
+
+    /// Generate benchmark results
+    pub fn benchmark(&mut self) -> BenchmarkSample {
+        BenchmarkSample {
+            transaction_throughput: 1250.0,
+            latency_p50: 45.0,
+            latency_p95: 150.0,
+            latency_p99: 300.0,
+            cpu_efficiency: 85.0,
+            memory_efficiency: 90.0,
+            network_efficiency: 88.0,
+            consensus_performance: 92.0,
+        }
+    }
+
+    /// Simulated network uptime percentage
+    pub fn uptime_percentage(&mut self) -> f64 {
+        99.5
+    }
+
+    /// Generate a network topology of `count` nodes
+    pub fn topology(&mut self, count: usize) -> Vec<SimulatedNode> {
+        (0..count)
+            .map(|i| SimulatedNode {
+                id: format!("node-{:04}", i),
+                address: format!("192.168.1.{}", i + 1),
+                role: match i % 4 {
+                    0 => NodeRole::Agent,
+                    1 => NodeRole::Validator,
+                    2 => NodeRole::Relay,
+                    _ => NodeRole::Client,
+                },
+                connections: (0..5).map(|j| format!("node-{:04}", (i + j + 1) % count)).collect(),
+                uptime: Duration::from_secs(3600 * 24 * (i as u64 % 30)),
+                latency_ms: 20.0 + (i as f64 * 2.5) % 100.0,
+                throughput_tps: 100.0 + (i as f64 * 10.0) % 500.0,
+                error_rate: (i as f64 * 0.01) % 0.05,
+                reputation_score: 0.5 + (i as f64 * 0.01) % 0.5,
+            })
+            .collect()
+    }
+
+    /// Generate transaction totals
+    pub fn transactions(&mut self) -> TransactionSample {
+        TransactionSample {
+            total_transactions: 15_000,
+            successful_transactions: 14_750,
+            failed_transactions: 250,
+            average_value: 2.5,
+            peak_tps: 450.0,
+            volume_distribution: vec![
+                ("< 1 SOL".to_string(), 8_000),
+                ("1-10 SOL".to_string(), 5_000),
+                ("10-100 SOL".to_string(), 1_800),
+                ("> 100 SOL".to_string(), 200),
+            ],
+        }
+    }
+
+    /// Generate the agent population
+    pub fn agent_population(&mut self) -> AgentPopulation {
+        AgentPopulation {
+            total_agents: 150,
+            active_agents: 120,
+            by_capability: vec![
+                ("data_analysis".to_string(), 45),
+                ("computational_task".to_string(), 30),
+                ("market_research".to_string(), 25),
+                ("content_creation".to_string(), 20),
+                ("trading_service".to_string(), 15),
+                ("machine_learning".to_string(), 15),
+            ],
+            reputation_scores: (0..150).map(|i| 0.3 + (i as f64 * 0.005) % 0.7).collect(),
+            // Heavy-tailed: a few agents carry most of the volume
+            transaction_counts: (0..150).map(|_| (self.rng.gen::<f64>().powi(4) * 2000.0) as u64).collect(),
+            average_connections: 8.5,
+            clustering_coefficient: 0.45,
+            network_diameter: 6,
+            isolated_nodes: 3,
+        }
+    }
+
+    /// Generate `count` agents with personas drawn from `mix`, in random order
+    pub fn persona_agents(&mut self, count: usize, mix: &PersonaMix) -> Vec<SimulatedAgent> {
+        let mut personas = mix.assign(count);
+        personas.shuffle(&mut self.rng);
+        personas
+            .into_iter()
+            .enumerate()
+            .map(|(i, persona)| SimulatedAgent::new(format!("agent-{:04}", i), persona))
+            .collect()
+    }
+
+    /// Negotiate, deliver, pay for, and rate one service between two agents
+    pub fn trade(&mut self, seller: &mut SimulatedAgent, buyer: &mut SimulatedAgent, base_price: f64) -> TradeSample {
+        let context = DecisionContext {
+            agent_reputation: 0.7,
+            counterparty_reputation: 0.7,
+            transaction_value: base_price,
+            market_conditions: MarketConditions {
+                demand_level: self.rng.gen_range(0.3..0.8),
+                competition_level: self.rng.gen_range(0.3..0.8),
+                average_pricing: base_price,
+                risk_indicators: vec![],
+            },
+            historical_performance: vec![],
+            counterparty_profile: None,
+            time_pressure: None,
+        };
+        let mut state = NegotiationState::new(context, base_price, 5);
+
+        // The seller's persona shapes its opening ask on top of its strategy
+        let drift = seller.ask(base_price, &mut self.rng) / (base_price * seller.profile.opening_premium);
+        let mut ask = seller.strategy.propose_price(&state) * drift;
+        let mut offer = buyer.bid(base_price, &mut self.rng).min(ask);
+        let mut agreed_price = None;
+
+        while !seller.strategy.should_walk_away(&state) {
+            state.our_asks.push(ask);
+            state.their_offers.push(offer);
+            state.round += 1;
+            match seller.strategy.evaluate_counter_offer(&state, offer) {
+                CounterOfferResponse::Accept => {
+                    agreed_price = Some(offer);
+                    break;
+                }
+                CounterOfferResponse::Counter(counter) => {
+                    ask = counter;
+                    // The buyer concedes toward the counter at its persona's pace
+                    offer = (offer + (counter - offer) * (buyer.profile.concession * 5.0).min(1.0)).min(counter);
+                }
+                CounterOfferResponse::Reject => break,
+            }
+        }
+
+        let quality = match seller.persona() {
+            Persona::Adversarial => self.rng.gen_range(0.1..0.6),
+            _ => self.rng.gen_range(0.6..1.0),
+        };
+        let (paid, rating) = match agreed_price {
+            Some(_) => (buyer.pays(&mut self.rng), buyer.rate(quality, &mut self.rng)),
+            None => (false, 0.0),
+        };
+
+        TradeSample {
+            seller: seller.id.clone(),
+            buyer: buyer.id.clone(),
+            seller_persona: seller.persona(),
+            buyer_persona: buyer.persona(),
+            base_price,
+            agreed_price,
+            rounds: state.round,
+            paid,
+            quality,
+            rating,
+        }
+    }
+
+    /// Generate consensus state for `count` validators
+    pub fn consensus(&mut self, count: usize) -> ConsensusSample {
+        let slots_in_epoch = 432_000;
+        let slot_index = 250_000;
+        let absolute_slot = 600 * slots_in_epoch + slot_index;
+
+        let validators = (0..count)
+            .map(|i| {
+                // Roughly one validator in twelve is struggling
+                let struggling = i % 12 == 5;
+                let leader_slots = 400 + self.rng.gen_range(0..200);
+                let skip = if struggling { self.rng.gen_range(0.2..0.6) } else { self.rng.gen_range(0.0..0.05) };
+                ValidatorSample {
+                    identity: format!("validator-{:04}", i),
+                    vote_account: format!("vote-{:04}", i),
+                    stake: 1_000_000_000_000 + self.rng.gen_range(0..50_000_000_000_000),
+                    delinquent: struggling && i % 24 == 5,
+                    last_vote: absolute_slot - if struggling { self.rng.gen_range(50..500) } else { self.rng.gen_range(0..3) },
+                    leader_slots,
+                    blocks_produced: (leader_slots as f64 * (1.0 - skip)) as u64,
+                    epoch_credits: (slot_index as f64 * if struggling { 0.7 } else { 0.97 + self.rng.gen_range(0.0..0.03) }) as u64,
+                }
+            })
+            .collect();
+
+        let slashing_events = (0..count / 25)
+            .map(|i| SlashingSample {
+                validator: format!("validator-{:04}", i * 12 + 5),
+                epoch: 600 - (i as u64 % 3),
+                slot: absolute_slot - self.rng.gen_range(0..1_000_000),
+                reason: "double vote".to_string(),
+                amount: 10_000_000_000,
+            })
+            .collect();
+
+        ConsensusSample {
+            epoch: 600,
+            absolute_slot,
+            slot_index,
+            slots_in_epoch,
+            recent_epoch_durations_secs: (0..5).map(|_| 172_800.0 * self.rng.gen_range(0.95..1.15)).collect(),
+            validators,
+            slashing_events,
+            finality_lag_ms: (0..60).map(|_| 12_800.0 + self.rng.gen::<f64>().powi(3) * 8_000.0).collect(),
+        }
+    }
+
+    /// Generate component health statuses
+    pub fn health(&mut self) -> Vec<(String, String)> {
+        vec![
+            ("consensus".to_string(), "✅ Healthy".to_string()),
+            ("connectivity".to_string(), "✅ Good".to_string()),
+            ("throughput".to_string(), "⚠️ Moderate".to_string()),
+            ("latency".to_string(), "✅ Low".to_string()),
+            ("error_rate".to_string(), "✅ Acceptable".to_string()),
+        ]
+    }
+}
+
+impl Default for SimulationProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_real_mode_is_not_connected() {
+        let source = DataSource::new(DataMode::Real);
+        let err = source.simulated("network metrics").unwrap_err();
+        assert!(err.to_string().starts_with("not connected"));
+        assert!(err.to_string().contains("network metrics"));
+    }
+
+    #[test]
+    fn test_seeded_provider_is_reproducible() {
+        let a = DataSource::simulated_with_seed(7).simulated("test").unwrap().network_sample();
+        let b = DataSource::simulated_with_seed(7).simulated("test").unwrap().network_sample();
+        assert_eq!(a.transactions_per_second, b.transactions_per_second);
+        assert_eq!(a.latency_ms, b.latency_ms);
+    }
+
+    #[test]
+    fn test_persona_agents_trade() {
+        let mut provider = SimulationProvider::with_seed(3);
+        let mix: PersonaMix = "cooperative=3,adversarial=1".parse().unwrap();
+        let mut agents = provider.persona_agents(8, &mix);
+        assert_eq!(agents.iter().filter(|a| a.persona() == Persona::Adversarial).count(), 2);
+
+        let mut seller = SimulatedAgent::new("seller", Persona::Cooperative);
+        let mut buyer = agents.remove(0);
+        let trades: Vec<TradeSample> = (0..50).map(|_| provider.trade(&mut seller, &mut buyer, 10.0)).collect();
+        assert!(trades.iter().any(|t| t.agreed_price.is_some()));
+        for trade in trades.iter().filter(|t| t.agreed_price.is_some()) {
+            assert!(trade.agreed_price.unwrap() >= 10.0 * seller.profile.floor);
+            assert!(trade.rounds >= 1);
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Agent Personas
+//!
+//! Behavioral archetypes for simulated economies. A persona fixes an agent's
+//! negotiation parameters (AI learning rate and risk tolerance, opening
+//! premium, concession, floor), how honestly it rates counterparties after a
+//! transaction, and how reliably it pays. A [`PersonaMix`] such as
+//! `aggressive=2,cooperative=5,adversarial=1` assigns personas across a
+//! simulated population in proportion to the weights.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use solace_ai::strategy::{AggressiveStrategy, AiStrategy, ConservativeStrategy, NegotiationStrategy};
+use solace_ai::NegotiationAI;
+
+/// Behavioral archetype of a simulated agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Persona {
+    Aggressive,
+    Cooperative,
+    Adversarial,
+    RandomWalker,
+}
+
+impl Persona {
+    /// Every persona, in display order
+    pub const ALL: [Persona; 4] = [
+        Persona::Aggressive,
+        Persona::Cooperative,
+        Persona::Adversarial,
+        Persona::RandomWalker,
+    ];
+
+    /// Name used on the command line and in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            Persona::Aggressive => "aggressive",
+            Persona::Cooperative => "cooperative",
+            Persona::Adversarial => "adversarial",
+            Persona::RandomWalker => "random_walker",
+        }
+    }
+
+    /// Behavior parameters for this persona
+    pub fn profile(&self) -> PersonaProfile {
+        match self {
+            Persona::Aggressive => PersonaProfile {
+                persona: *self,
+                learning_rate: 0.05,
+                risk_tolerance: 0.8,
+                opening_premium: 1.3,
+                concession: 0.03,
+                floor: 0.95,
+                rating_honesty: 0.9,
+                payment_reliability: 0.97,
+            },
+            Persona::Cooperative => PersonaProfile {
+                persona: *self,
+                learning_rate: 0.1,
+                risk_tolerance: 0.4,
+                opening_premium: 1.05,
+                concession: 0.08,
+                floor: 0.8,
+                rating_honesty: 0.98,
+                payment_reliability: 0.995,
+            },
+            // Prices like an aggressive agent, but rates dishonestly and often defaults
+            Persona::Adversarial => PersonaProfile {
+                persona: *self,
+                learning_rate: 0.05,
+                risk_tolerance: 0.9,
+                opening_premium: 1.5,
+                concession: 0.02,
+                floor: 1.0,
+                rating_honesty: 0.2,
+                payment_reliability: 0.6,
+            },
+            Persona::RandomWalker => PersonaProfile {
+                persona: *self,
+                learning_rate: 0.2,
+                risk_tolerance: 0.5,
+                opening_premium: 1.1,
+                concession: 0.05,
+                floor: 0.7,
+                rating_honesty: 0.7,
+                payment_reliability: 0.9,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Persona {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Persona {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "aggressive" => Ok(Persona::Aggressive),
+            "cooperative" => Ok(Persona::Cooperative),
+            "adversarial" => Ok(Persona::Adversarial),
+            "random_walker" | "random" => Ok(Persona::RandomWalker),
+            other => Err(format!(
+                "unknown persona '{}' (use aggressive, cooperative, adversarial, or random_walker)",
+                other
+            )),
+        }
+    }
+}
+
+/// Parameters a persona sets on a simulated agent
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PersonaProfile {
+    pub persona: Persona,
+    pub learning_rate: f64,
+    pub risk_tolerance: f64,
+    pub opening_premium: f64,             // Multiplier on the base price
+    pub concession: f64,                  // Fraction of the ask given up per round
+    pub floor: f64,                       // Lowest acceptable price / base price
+    pub rating_honesty: f64,              // Probability a rating reflects true quality
+    pub payment_reliability: f64,         // Probability of paying in full
+}
+
+impl PersonaProfile {
+    /// Negotiation AI configured for this persona
+    pub fn negotiation_ai(&self) -> NegotiationAI {
+        NegotiationAI::new(self.learning_rate, self.risk_tolerance)
+    }
+
+    /// Negotiation strategy configured for this persona
+    pub fn strategy(&self) -> Box<dyn NegotiationStrategy> {
+        match self.persona {
+            Persona::Aggressive | Persona::Adversarial => Box::new(AggressiveStrategy {
+                opening_premium: self.opening_premium,
+                concession: self.concession,
+                floor: self.floor,
+            }),
+            Persona::Cooperative => Box::new(ConservativeStrategy {
+                opening_premium: self.opening_premium,
+                concession: self.concession,
+                floor: self.floor,
+                ..ConservativeStrategy::default()
+            }),
+            Persona::RandomWalker => Box::new(AiStrategy {
+                ai: self.negotiation_ai(),
+                floor: self.floor,
+            }),
+        }
+    }
+}
+
+/// Weighted mix of personas across a population
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersonaMix {
+    weights: Vec<(Persona, u32)>,
+}
+
+impl PersonaMix {
+    /// Mix of a single persona
+    pub fn only(persona: Persona) -> Self {
+        Self {
+            weights: vec![(persona, 1)],
+        }
+    }
+
+    /// Personas and their weights
+    pub fn weights(&self) -> &[(Persona, u32)] {
+        &self.weights
+    }
+
+    /// Personas for `count` agents, proportional to the weights
+    ///
+    /// Uses largest remainders so counts are exact and the assignment is the
+    /// same on every run; shuffle the result for random placement.
+    pub fn assign(&self, count: usize) -> Vec<Persona> {
+        let total: u64 = self.weights.iter().map(|(_, weight)| *weight as u64).sum();
+        let mut shares: Vec<(Persona, usize, u64)> = self
+            .weights
+            .iter()
+            .map(|&(persona, weight)| {
+                let exact = count as u64 * weight as u64;
+                (persona, (exact / total) as usize, exact % total)
+            })
+            .collect();
+
+        let assigned: usize = shares.iter().map(|(_, share, _)| share).sum();
+        let mut by_remainder: Vec<usize> = (0..shares.len()).collect();
+        by_remainder.sort_by(|&a, &b| shares[b].2.cmp(&shares[a].2));
+        for &i in by_remainder.iter().take(count - assigned) {
+            shares[i].1 += 1;
+        }
+
+        shares
+            .into_iter()
+            .flat_map(|(persona, share, _)| std::iter::repeat_n(persona, share))
+            .collect()
+    }
+}
+
+impl Default for PersonaMix {
+    fn default() -> Self {
+        Self::only(Persona::Cooperative)
+    }
+}
+
+impl FromStr for PersonaMix {
+    type Err = String;
+
+    /// Parse `persona[=weight],...`; a missing weight counts as 1
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut weights: Vec<(Persona, u32)> = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (name, weight) = match entry.split_once('=') {
+                Some((name, weight)) => {
+                    let weight = weight
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid weight in '{}'", entry))?;
+                    (name, weight)
+                }
+                None => (entry, 1),
+            };
+            let persona: Persona = name.parse()?;
+            match weights.iter_mut().find(|(existing, _)| *existing == persona) {
+                Some((_, total)) => *total += weight,
+                None => weights.push((persona, weight)),
+            }
+        }
+
+        weights.retain(|(_, weight)| *weight > 0);
+        if weights.is_empty() {
+            return Err("persona mix needs at least one persona with a positive weight".to_string());
+        }
+        Ok(Self { weights })
+    }
+}
+
+impl fmt::Display for PersonaMix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self
+            .weights
+            .iter()
+            .map(|(persona, weight)| format!("{}={}", persona, weight))
+            .collect();
+        write!(f, "{}", entries.join(","))
+    }
+}
+
+/// Simulated agent driven by a persona
+#[derive(Debug)]
+pub struct SimulatedAgent {
+    pub id: String,
+    pub profile: PersonaProfile,
+    pub strategy: Box<dyn NegotiationStrategy>,
+    drift: f64,                           // Random walker's current price multiplier
+}
+
+impl SimulatedAgent {
+    pub fn new(id: impl Into<String>, persona: Persona) -> Self {
+        let profile = persona.profile();
+        Self {
+            id: id.into(),
+            strategy: profile.strategy(),
+            profile,
+            drift: 1.0,
+        }
+    }
+
+    pub fn persona(&self) -> Persona {
+        self.profile.persona
+    }
+
+    /// Price this agent asks for a service, before negotiation
+    ///
+    /// Random walkers drift their pricing a little with every ask.
+    pub fn ask(&mut self, base_price: f64, rng: &mut impl Rng) -> f64 {
+        if self.persona() == Persona::RandomWalker {
+            self.drift = (self.drift + rng.gen_range(-0.05..0.05)).clamp(0.5, 2.0);
+        }
+        base_price * self.profile.opening_premium * self.drift
+    }
+
+    /// Opening offer this agent makes as a buyer
+    pub fn bid(&mut self, base_price: f64, rng: &mut impl Rng) -> f64 {
+        let ask = self.ask(base_price, rng);
+        (base_price * 2.0 - ask).max(base_price * 0.1)
+    }
+
+    /// Rating (0.0 to 1.0) this agent gives for a service of `quality`
+    ///
+    /// Honest ratings track quality with a little noise. Dishonest adversarial
+    /// ratings invert it, badmouthing good providers and boosting bad ones;
+    /// other personas rate carelessly at random.
+    pub fn rate(&self, quality: f64, rng: &mut impl Rng) -> f64 {
+        let rating = if rng.gen_bool(self.profile.rating_honesty.clamp(0.0, 1.0)) {
+            quality + rng.gen_range(-0.05..0.05)
+        } else if self.persona() == Persona::Adversarial {
+            1.0 - quality
+        } else {
+            rng.gen()
+        };
+        rating.clamp(0.0, 1.0)
+    }
+
+    /// Whether this agent pays for a completed service
+    pub fn pays(&self, rng: &mut impl Rng) -> bool {
+        rng.gen_bool(self.profile.payment_reliability.clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_mix_parsing_and_assignment() {
+        let mix: PersonaMix = "aggressive=2, cooperative=5, adversarial".parse().unwrap();
+        assert_eq!(mix.to_string(), "aggressive=2,cooperative=5,adversarial=1");
+
+        let personas = mix.assign(16);
+        let count = |persona| personas.iter().filter(|p| **p == persona).count();
+        assert_eq!(personas.len(), 16);
+        assert_eq!((count(Persona::Aggressive), count(Persona::Cooperative), count(Persona::Adversarial)), (4, 10, 2));
+
+        assert!("aggressive=x".parse::<PersonaMix>().is_err());
+        assert!("haggler".parse::<PersonaMix>().is_err());
+        assert!("cooperative=0".parse::<PersonaMix>().is_err());
+    }
+
+    #[test]
+    fn test_personas_differ_in_honesty_and_reliability() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let honest = SimulatedAgent::new("honest", Persona::Cooperative);
+        let liar = SimulatedAgent::new("liar", Persona::Adversarial);
+
+        let mean_rating = |agent: &SimulatedAgent, rng: &mut StdRng| {
+            (0..500).map(|_| agent.rate(0.9, rng)).sum::<f64>() / 500.0
+        };
+        assert!(mean_rating(&honest, &mut rng) > 0.85);
+        assert!(mean_rating(&liar, &mut rng) < 0.4);
+
+        let paid = |agent: &SimulatedAgent, rng: &mut StdRng| (0..1000).filter(|_| agent.pays(rng)).count();
+        assert!(paid(&honest, &mut rng) > paid(&liar, &mut rng) + 200);
+    }
+}
@@ -0,0 +1,538 @@
+//! Solace Protocol Soak Test
+//!
+//! Runs a small in-process network of gossip nodes under continuous light
+//! load for hours or days, sampling process memory, live Tokio tasks, message
+//! cache sizes, and error rates. At the end it writes a stability report and
+//! exits non-zero if memory, tasks, or caches kept growing or the error rate
+//! crept up, so it can gate nightly or weekly CI jobs on a dedicated runner.
+//!
+//! Each node is driven by a simulated agent persona; every message is a trade
+//! negotiated between two personas, so `--personas` shapes the traffic mix
+//! (prices, defaults, dishonest ratings) the network has to carry.
+//!
+//! ```bash
+//! solace-soak-test --agents 10 --duration 72h --report soak-report.json
+//! solace-soak-test --agents 20 --personas cooperative=3,aggressive=1,adversarial=1
+//! ```
+
+use acp::gossip::{GossipConfig, GossipMessage, GossipMessageType, GossipProtocol};
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use solace_simulation::{PersonaMix, SimulatedAgent, SimulationProvider};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessExt, SystemExt};
+use tracing::{error, info, warn};
+
+#[derive(Parser)]
+#[command(name = "solace-soak-test")]
+#[command(about = "Long-running stability test for Solace Protocol nodes")]
+#[command(version = "1.0.0")]
+struct Cli {
+    /// Number of nodes in the network
+    #[arg(short, long, default_value = "10")]
+    agents: usize,
+
+    /// Total run time (e.g. 30m, 12h, 3d)
+    #[arg(short, long, default_value = "72h", value_parser = parse_duration)]
+    duration: Duration,
+
+    /// Messages originated per second across the network
+    #[arg(short, long, default_value = "5")]
+    rate: f64,
+
+    /// Seconds between resource samples
+    #[arg(long, default_value = "60")]
+    sample_interval: u64,
+
+    /// Initial period excluded from trend analysis (e.g. 10m)
+    #[arg(long, default_value = "10m", value_parser = parse_duration)]
+    warmup: Duration,
+
+    /// Where to write the JSON stability report
+    #[arg(long, default_value = "soak-report.json")]
+    report: PathBuf,
+
+    /// Persona mix across nodes (e.g. cooperative=3,aggressive=1,adversarial=1,random_walker=1)
+    #[arg(long, default_value = "cooperative")]
+    personas: PersonaMix,
+
+    /// Base RNG seed (defaults to SOLACE_SEED, then random)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Maximum tolerated resident memory growth in MB per hour
+    #[arg(long, default_value = "5.0")]
+    max_memory_growth: f64,
+
+    /// Maximum tolerated growth in live tasks between the first and last quarter
+    #[arg(long, default_value = "10")]
+    max_task_growth: f64,
+
+    /// Maximum tolerated increase in error rate between the first and last quarter
+    #[arg(long, default_value = "0.01")]
+    max_error_rate_increase: f64,
+
+    /// Verbose output
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// Parse a duration such as `90s`, `30m`, `12h`, or `3d`
+fn parse_duration(value: &str) -> std::result::Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| format!("invalid duration: {}", value))?;
+
+    let seconds = match unit {
+        "" | "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 3600,
+        "d" => amount * 86400,
+        _ => return Err(format!("unknown duration unit '{}' (use s, m, h, or d)", unit)),
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+/// One resource sample taken during the run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sample {
+    elapsed_secs: f64,
+    rss_bytes: u64,
+    alive_tasks: usize,
+    cache_entries: usize,
+    messages: u64,
+    errors: u64,
+}
+
+/// Outcome of a single stability check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CheckStatus {
+    Pass,
+    Fail,
+    Inconclusive,
+}
+
+/// Result of a single stability check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CheckResult {
+    name: String,
+    status: CheckStatus,
+    detail: String,
+}
+
+/// Final stability report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StabilityReport {
+    started_at: chrono::DateTime<chrono::Utc>,
+    finished_at: chrono::DateTime<chrono::Utc>,
+    planned_secs: u64,
+    elapsed_secs: f64,
+    interrupted: bool,
+    agents: usize,
+    seed: u64,
+    personas: BTreeMap<String, usize>,
+    total_messages: u64,
+    total_errors: u64,
+    total_trades: u64,
+    total_defaults: u64,
+    checks: Vec<CheckResult>,
+    samples: Vec<Sample>,
+}
+
+impl StabilityReport {
+    /// Whether any check failed
+    fn failed(&self) -> bool {
+        self.checks.iter().any(|check| check.status == CheckStatus::Fail)
+    }
+}
+
+/// Counters shared between the load generator and the sampler
+#[derive(Debug, Default)]
+struct LoadCounters {
+    messages: AtomicU64,
+    errors: AtomicU64,
+    trades: AtomicU64,
+    defaults: AtomicU64,
+}
+
+/// Soak test harness
+struct SoakTest {
+    nodes: Vec<(String, Arc<GossipProtocol>)>,
+    agents: Mutex<Vec<SimulatedAgent>>,   // Persona driving each node, by index
+    economy: Mutex<SimulationProvider>,
+    counters: Arc<LoadCounters>,
+    fanout: usize,
+    message_ttl: u32,
+}
+
+impl SoakTest {
+    /// Start a fully meshed network of gossip nodes with personas from `mix`
+    async fn start(agents: usize, seed: u64, mix: &PersonaMix) -> Result<Self> {
+        if agents < 2 {
+            return Err(anyhow!("soak test needs at least 2 agents"));
+        }
+
+        let config = GossipConfig {
+            rng_seed: Some(seed),
+            ..GossipConfig::default()
+        };
+        let ids: Vec<String> = (0..agents).map(|i| format!("soak-node-{}", i)).collect();
+
+        let mut nodes = Vec::with_capacity(agents);
+        for id in &ids {
+            let mut gossip = GossipProtocol::new(id.clone(), config.clone());
+            gossip.start().await.with_context(|| format!("starting {}", id))?;
+            for peer in ids.iter().filter(|peer| *peer != id) {
+                gossip.add_peer(peer.clone()).await;
+            }
+            nodes.push((id.clone(), Arc::new(gossip)));
+        }
+
+        let mut economy = SimulationProvider::with_seed(seed);
+        let mut personas = economy.persona_agents(agents, mix);
+        for (agent, id) in personas.iter_mut().zip(&ids) {
+            agent.id = id.clone();
+        }
+
+        info!("Started {} soak nodes ({})", agents, mix);
+        Ok(Self {
+            nodes,
+            agents: Mutex::new(personas),
+            economy: Mutex::new(economy),
+            counters: Arc::new(LoadCounters::default()),
+            fanout: config.fanout,
+            message_ttl: config.message_ttl,
+        })
+    }
+
+    /// Trade between two persona-driven nodes and gossip the outcome to a few peers
+    async fn step(&self, rng: &mut StdRng, sequence: u64) {
+        let seller = rng.gen_range(0..self.nodes.len());
+        let buyer = (seller + rng.gen_range(1..self.nodes.len())) % self.nodes.len();
+        let trade = self.trade(seller, buyer, rng.gen_range(1..10_000u64) as f64);
+        if trade.agreed_price.is_some() {
+            self.counters.trades.fetch_add(1, Ordering::Relaxed);
+            if !trade.paid {
+                self.counters.defaults.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let (origin_id, origin) = &self.nodes[seller];
+        let message = GossipMessage::new(
+            GossipMessageType::TransactionBroadcast,
+            origin_id.clone(),
+            serde_json::json!({
+                "sequence": sequence,
+                "amount": trade.agreed_price.unwrap_or(trade.base_price) as u64,
+                "seller_persona": trade.seller_persona,
+                "buyer_persona": trade.buyer_persona,
+                "agreed": trade.agreed_price.is_some(),
+                "paid": trade.paid,
+                "rating": trade.rating,
+            }),
+            self.message_ttl,
+        );
+
+        self.counters.messages.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = origin.gossip_message(message.clone()).await {
+            self.record_error(origin_id, e);
+        }
+
+        let receivers: Vec<_> = self.nodes.iter().filter(|(id, _)| id != origin_id).collect();
+        for (id, node) in receivers.choose_multiple(rng, self.fanout) {
+            if let Err(e) = node.handle_incoming_message(message.clone()).await {
+                self.record_error(id, e);
+            }
+        }
+    }
+
+    /// Negotiate one trade between the agents at two node indices
+    fn trade(&self, seller: usize, buyer: usize, base_price: f64) -> solace_simulation::TradeSample {
+        let mut agents = self.agents.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut economy = self.economy.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (low, high) = agents.split_at_mut(seller.max(buyer));
+        let (a, b) = (&mut low[seller.min(buyer)], &mut high[0]);
+        let (seller, buyer) = if seller < buyer { (a, b) } else { (b, a) };
+        economy.trade(seller, buyer, base_price)
+    }
+
+    /// Persona counts across the network
+    fn persona_counts(&self) -> BTreeMap<String, usize> {
+        let agents = self.agents.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut counts = BTreeMap::new();
+        for agent in agents.iter() {
+            *counts.entry(agent.persona().to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Count and log a failed operation
+    fn record_error(&self, node_id: &str, error: anyhow::Error) {
+        self.counters.errors.fetch_add(1, Ordering::Relaxed);
+        warn!("Node {} error: {}", node_id, error);
+    }
+
+    /// Total entries across all node message caches
+    async fn cache_entries(&self) -> usize {
+        let mut total = 0;
+        for (_, node) in &self.nodes {
+            total += node.get_cache_size().await;
+        }
+        total
+    }
+}
+
+/// Resident memory of this process in bytes
+fn resident_memory(sys: &mut sysinfo::System, pid: sysinfo::Pid) -> u64 {
+    sys.refresh_process(pid);
+    sys.process(pid).map(|process| process.memory()).unwrap_or(0)
+}
+
+/// Least-squares slope of `y` over `x`
+fn slope(points: &[(f64, f64)]) -> f64 {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+/// Mean of a metric over the first and last quarter of the samples
+fn quarter_means(samples: &[Sample], metric: impl Fn(&Sample) -> f64) -> (f64, f64) {
+    let quarter = (samples.len() / 4).max(1);
+    let mean = |window: &[Sample]| window.iter().map(&metric).sum::<f64>() / window.len() as f64;
+    (mean(&samples[..quarter]), mean(&samples[samples.len() - quarter..]))
+}
+
+/// Error rate over a window of consecutive samples
+fn error_rate(window: &[Sample]) -> f64 {
+    let (first, last) = (&window[0], &window[window.len() - 1]);
+    let messages = last.messages.saturating_sub(first.messages);
+    if messages == 0 {
+        0.0
+    } else {
+        last.errors.saturating_sub(first.errors) as f64 / messages as f64
+    }
+}
+
+/// Evaluate the stability checks over the post-warmup samples
+fn analyze(cli: &Cli, samples: &[Sample]) -> Vec<CheckResult> {
+    let steady: Vec<Sample> = samples
+        .iter()
+        .filter(|sample| sample.elapsed_secs >= cli.warmup.as_secs_f64())
+        .cloned()
+        .collect();
+
+    let names = ["memory_growth", "task_leaks", "cache_growth", "error_rate_creep"];
+    if steady.len() < 8 {
+        let detail = format!("only {} samples after warmup, need at least 8", steady.len());
+        return names
+            .iter()
+            .map(|name| CheckResult {
+                name: name.to_string(),
+                status: CheckStatus::Inconclusive,
+                detail: detail.clone(),
+            })
+            .collect();
+    }
+
+    let verdict = |passed: bool| if passed { CheckStatus::Pass } else { CheckStatus::Fail };
+    let mut checks = Vec::new();
+
+    let memory: Vec<(f64, f64)> = steady
+        .iter()
+        .map(|s| (s.elapsed_secs / 3600.0, s.rss_bytes as f64 / (1024.0 * 1024.0)))
+        .collect();
+    let memory_slope = slope(&memory);
+    checks.push(CheckResult {
+        name: names[0].to_string(),
+        status: verdict(memory_slope <= cli.max_memory_growth),
+        detail: format!("{:.2} MB/h (limit {:.2} MB/h)", memory_slope, cli.max_memory_growth),
+    });
+
+    let (first_tasks, last_tasks) = quarter_means(&steady, |s| s.alive_tasks as f64);
+    checks.push(CheckResult {
+        name: names[1].to_string(),
+        status: verdict(last_tasks - first_tasks <= cli.max_task_growth),
+        detail: format!(
+            "{:.1} -> {:.1} live tasks (limit +{:.0})",
+            first_tasks, last_tasks, cli.max_task_growth
+        ),
+    });
+
+    // Caches may fill up after warmup but must level off, not keep climbing
+    let (first_cache, last_cache) = quarter_means(&steady, |s| s.cache_entries as f64);
+    let cache_limit = first_cache * 1.5 + cli.agents as f64;
+    checks.push(CheckResult {
+        name: names[2].to_string(),
+        status: verdict(last_cache <= cache_limit),
+        detail: format!(
+            "{:.0} -> {:.0} cached messages (limit {:.0})",
+            first_cache, last_cache, cache_limit
+        ),
+    });
+
+    let quarter = (steady.len() / 4).max(2);
+    let first_rate = error_rate(&steady[..quarter]);
+    let last_rate = error_rate(&steady[steady.len() - quarter..]);
+    checks.push(CheckResult {
+        name: names[3].to_string(),
+        status: verdict(last_rate - first_rate <= cli.max_error_rate_increase),
+        detail: format!(
+            "{:.4} -> {:.4} errors per message (limit +{:.4})",
+            first_rate, last_rate, cli.max_error_rate_increase
+        ),
+    });
+
+    checks
+}
+
+/// Print a human-readable summary of the report
+fn print_report(report: &StabilityReport) {
+    println!("🧪 Soak Test Report");
+    println!("===================");
+    println!("Agents:     {}", report.agents);
+    println!("Seed:       {}", report.seed);
+    let personas: Vec<String> = report.personas.iter().map(|(name, count)| format!("{}={}", name, count)).collect();
+    println!("Personas:   {}", personas.join(", "));
+    println!(
+        "Duration:   {:.1}h of {:.1}h planned{}",
+        report.elapsed_secs / 3600.0,
+        report.planned_secs as f64 / 3600.0,
+        if report.interrupted { " (interrupted)" } else { "" }
+    );
+    println!("Messages:   {}", report.total_messages);
+    println!("Errors:     {}", report.total_errors);
+    println!("Trades:     {} ({} defaulted)", report.total_trades, report.total_defaults);
+    println!();
+
+    for check in &report.checks {
+        let icon = match check.status {
+            CheckStatus::Pass => "✅",
+            CheckStatus::Fail => "❌",
+            CheckStatus::Inconclusive => "⚠️",
+        };
+        println!("{} {:<18} {}", icon, check.name, check.detail);
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let level = if cli.verbose { "debug" } else { "info" };
+    tracing_subscriber::fmt()
+        .with_env_filter(format!("solace_soak_test={},acp=warn", level))
+        .init();
+
+    if cli.rate <= 0.0 {
+        return Err(anyhow!("--rate must be positive"));
+    }
+
+    let seed = cli
+        .seed
+        .or_else(acp::rng::seed_from_env)
+        .unwrap_or_else(rand::random);
+    info!("Soak test seed: {} (pass --seed {} to reproduce)", seed, seed);
+
+    let soak = SoakTest::start(cli.agents, seed, &cli.personas).await?;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let pid = sysinfo::get_current_pid().map_err(|e| anyhow!("cannot determine own pid: {}", e))?;
+    let mut sys = sysinfo::System::new();
+    let runtime = tokio::runtime::Handle::current().metrics();
+
+    let started_at = chrono::Utc::now();
+    let start = Instant::now();
+    let deadline = tokio::time::sleep(cli.duration);
+    tokio::pin!(deadline);
+
+    let mut load = tokio::time::interval(Duration::from_secs_f64(1.0 / cli.rate));
+    load.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut sampler = tokio::time::interval(Duration::from_secs(cli.sample_interval.max(1)));
+
+    let mut samples = Vec::new();
+    let mut sequence = 0u64;
+    let mut interrupted = false;
+
+    info!("Running {} agents for {:?} at {} msg/s", cli.agents, cli.duration, cli.rate);
+
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            _ = tokio::signal::ctrl_c() => {
+                warn!("Interrupted, writing report for the partial run");
+                interrupted = true;
+                break;
+            }
+            _ = load.tick() => {
+                soak.step(&mut rng, sequence).await;
+                sequence += 1;
+            }
+            _ = sampler.tick() => {
+                let sample = Sample {
+                    elapsed_secs: start.elapsed().as_secs_f64(),
+                    rss_bytes: resident_memory(&mut sys, pid),
+                    alive_tasks: runtime.num_alive_tasks(),
+                    cache_entries: soak.cache_entries().await,
+                    messages: soak.counters.messages.load(Ordering::Relaxed),
+                    errors: soak.counters.errors.load(Ordering::Relaxed),
+                };
+                info!(
+                    "t={:.0}s rss={}MB tasks={} cache={} messages={} errors={}",
+                    sample.elapsed_secs,
+                    sample.rss_bytes / (1024 * 1024),
+                    sample.alive_tasks,
+                    sample.cache_entries,
+                    sample.messages,
+                    sample.errors
+                );
+                samples.push(sample);
+            }
+        }
+    }
+
+    let report = StabilityReport {
+        started_at,
+        finished_at: chrono::Utc::now(),
+        planned_secs: cli.duration.as_secs(),
+        elapsed_secs: start.elapsed().as_secs_f64(),
+        interrupted,
+        agents: cli.agents,
+        seed,
+        personas: soak.persona_counts(),
+        total_messages: soak.counters.messages.load(Ordering::Relaxed),
+        total_errors: soak.counters.errors.load(Ordering::Relaxed),
+        total_trades: soak.counters.trades.load(Ordering::Relaxed),
+        total_defaults: soak.counters.defaults.load(Ordering::Relaxed),
+        checks: analyze(&cli, &samples),
+        samples,
+    };
+
+    print_report(&report);
+    std::fs::write(&cli.report, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("writing report to {}", cli.report.display()))?;
+    println!("\n📄 Report written to {}", cli.report.display());
+
+    if report.failed() {
+        error!("Soak test detected instability");
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,204 @@
+//! Snapshot Diffing
+//!
+//! Compares two storage snapshots section by section. Records are matched
+//! by key; one present on both sides with different contents is reported
+//! with the JSON paths of the fields that differ, so a diverged transaction
+//! reads as `status` or `payment.amount` rather than as two blobs.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+use solace_protocol::storage::StorageSnapshot;
+
+/// Reputation scores closer than this are considered equal
+pub const REPUTATION_TOLERANCE: f64 = 1e-9;
+
+/// A record present on both sides with different contents
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub key: String,
+    pub fields: Vec<String>,              // Differing JSON paths; empty for plain values
+    pub left: Value,
+    pub right: Value,
+}
+
+/// Differences within one kind of record
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SectionDiff {
+    pub added: Vec<String>,               // Only on the right
+    pub removed: Vec<String>,             // Only on the left
+    pub changed: Vec<Change>,
+}
+
+impl SectionDiff {
+    fn between<T>(left: &BTreeMap<String, T>, right: &BTreeMap<String, T>, same: impl Fn(&T, &T) -> bool) -> Self
+    where
+        T: Clone + Into<Value>,
+    {
+        let mut diff = SectionDiff::default();
+        for (key, left_value) in left {
+            match right.get(key) {
+                None => diff.removed.push(key.clone()),
+                Some(right_value) if !same(left_value, right_value) => {
+                    let (left_value, right_value) = (left_value.clone().into(), right_value.clone().into());
+                    let mut fields = Vec::new();
+                    differing_paths(&left_value, &right_value, "", &mut fields);
+                    diff.changed.push(Change {
+                        key: key.clone(),
+                        fields,
+                        left: left_value,
+                        right: right_value,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        diff.added = right.keys().filter(|key| !left.contains_key(*key)).cloned().collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Everything that differs between two snapshots
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StateDiff {
+    pub agents: SectionDiff,
+    pub transactions: SectionDiff,
+    pub reputations: SectionDiff,
+    pub consensus: SectionDiff,
+}
+
+impl StateDiff {
+    /// Differences going from `left` to `right`
+    pub fn between(left: &StorageSnapshot, right: &StorageSnapshot) -> Self {
+        Self {
+            agents: SectionDiff::between(&left.agents, &right.agents, |a, b| a == b),
+            transactions: SectionDiff::between(&left.transactions, &right.transactions, |a, b| a == b),
+            reputations: SectionDiff::between(&left.reputations, &right.reputations, |a, b| {
+                (a - b).abs() <= REPUTATION_TOLERANCE
+            }),
+            consensus: SectionDiff::between(&left.consensus, &right.consensus, |a, b| a == b),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections().iter().all(|(_, section)| section.is_empty())
+    }
+
+    pub fn sections(&self) -> [(&'static str, &SectionDiff); 4] {
+        [
+            ("agents", &self.agents),
+            ("transactions", &self.transactions),
+            ("reputations", &self.reputations),
+            ("consensus", &self.consensus),
+        ]
+    }
+
+    /// Human-readable report: `+` added, `-` removed, `~` changed
+    pub fn render(&self) -> String {
+        if self.is_empty() {
+            return "States match\n".to_string();
+        }
+
+        let mut out = String::new();
+        for (name, section) in self.sections() {
+            if section.is_empty() {
+                continue;
+            }
+            out.push_str(&format!(
+                "{}: {} added, {} removed, {} changed\n",
+                name,
+                section.added.len(),
+                section.removed.len(),
+                section.changed.len()
+            ));
+            for key in &section.added {
+                out.push_str(&format!("  + {}\n", key));
+            }
+            for key in &section.removed {
+                out.push_str(&format!("  - {}\n", key));
+            }
+            for change in &section.changed {
+                if change.fields.is_empty() {
+                    out.push_str(&format!("  ~ {}: {} -> {}\n", change.key, change.left, change.right));
+                } else {
+                    out.push_str(&format!("  ~ {}: {}\n", change.key, change.fields.join(", ")));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Collect the JSON paths below `path` at which two values differ
+fn differing_paths(left: &Value, right: &Value, path: &str, out: &mut Vec<String>) {
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+            for key in keys {
+                let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                match (left.get(key), right.get(key)) {
+                    (Some(left), Some(right)) => differing_paths(left, right, &child, out),
+                    _ => out.push(child),
+                }
+            }
+        }
+        (Value::Array(left), Value::Array(right)) if left.len() == right.len() => {
+            for (i, (left, right)) in left.iter().zip(right).enumerate() {
+                differing_paths(left, right, &format!("{}[{}]", path, i), out);
+            }
+        }
+        _ if left != right && !path.is_empty() => out.push(path.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(transactions: Vec<(&str, Value)>, reputations: Vec<(&str, f64)>) -> StorageSnapshot {
+        StorageSnapshot {
+            taken_at: chrono::Utc::now(),
+            agents: BTreeMap::new(),
+            transactions: transactions.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+            reputations: reputations.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+            consensus: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_added_removed_and_changed_fields() {
+        let left = snapshot(
+            vec![
+                ("tx-1", json!({ "status": "Pending", "price": { "lamports": 10 }, "tags": ["a"] })),
+                ("tx-2", json!({ "status": "Completed" })),
+            ],
+            vec![("agent-1", 0.5), ("agent-2", 0.8)],
+        );
+        let right = snapshot(
+            vec![
+                ("tx-1", json!({ "status": "Completed", "price": { "lamports": 12 }, "tags": ["a"] })),
+                ("tx-3", json!({ "status": "Pending" })),
+            ],
+            vec![("agent-1", 0.5 + REPUTATION_TOLERANCE / 2.0), ("agent-2", 0.7)],
+        );
+
+        let diff = StateDiff::between(&left, &right);
+        assert_eq!(diff.transactions.added, vec!["tx-3"]);
+        assert_eq!(diff.transactions.removed, vec!["tx-2"]);
+        assert_eq!(diff.transactions.changed[0].fields, vec!["price.lamports", "status"]);
+        assert_eq!(diff.reputations.changed.len(), 1);
+        assert!(diff.agents.is_empty() && diff.consensus.is_empty());
+
+        let report = diff.render();
+        assert!(report.contains("transactions: 1 added, 1 removed, 1 changed"));
+        assert!(report.contains("  ~ agent-2: 0.8 -> 0.7"));
+        assert!(StateDiff::between(&left, &left).is_empty());
+    }
+}